@@ -0,0 +1,100 @@
+//! Loading melonbooks products with their artists, category, tags and flags from a generated database.
+//!
+//! Relations used to be loaded with four queries per product. To compare against that path, check out the
+//! commit that introduced `LOAD_BATCH_SIZE` with its changes outside of the bench reverted, save its results as
+//! a criterion baseline into the same target directory and bench the current tree against it:
+//!
+//! ```sh
+//! commit=$(git log --format=%h -S LOAD_BATCH_SIZE --reverse -- src/lib/outbound/sqlite/melonbooks/mod.rs | head -n 1)
+//! git worktree add ../moe-scraper-per-product "$commit"
+//! cd ../moe-scraper-per-product
+//! git show "$commit" -- ':!benches' ':!Cargo.toml' | git apply -R
+//! CARGO_TARGET_DIR=../moe-scraper/target cargo bench --bench melonbooks_products -- --save-baseline per-product
+//! cd ../moe-scraper
+//! cargo bench --bench melonbooks_products -- --baseline per-product
+//! ```
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use moe_scraper::domain::availability::Availability;
+use moe_scraper::domain::melonbooks::models::product::CreateProductArgs;
+use moe_scraper::domain::melonbooks::models::query::ProductQuery;
+use moe_scraper::domain::melonbooks::ports::MelonbooksRepository;
+use moe_scraper::domain::pagination::{PageRequest, SortDirection};
+use moe_scraper::domain::melonbooks::models::query::ProductSort;
+use moe_scraper::domain::user::models::user::DEFAULT_USERNAME;
+use moe_scraper::domain::user::ports::UserRepository;
+use moe_scraper::outbound::sqlite::Sqlite;
+use tokio::runtime::Runtime;
+
+const PRODUCTS: usize = 20_000;
+const ARTISTS: usize = 2_000;
+const CATEGORIES: usize = 20;
+const TAGS: usize = 200;
+const FLAGS: usize = 10;
+
+fn product_args(i: usize) -> CreateProductArgs {
+    let artists = (0..1 + i % 3)
+        .map(|a| format!("artist_{}", (i + a * 7) % ARTISTS))
+        .collect();
+    let tags = (0..3)
+        .map(|t| format!("tag_{}", (i + t * 13) % TAGS))
+        .collect();
+    let flags = (0..i % 3)
+        .map(|f| format!("flag_{}", (i + f) % FLAGS))
+        .collect();
+    CreateProductArgs::new(
+        format!("https://www.melonbooks.co.jp/detail/detail.php?product_id={}", i),
+        format!("product title {}", i),
+        Some(format!("circle {}", i % 500)),
+        artists,
+        format!("https://melonbooks.akamaized.net/user_data/packages/resize_image.php?image={}.jpg", i),
+        format!("category_{}", i % CATEGORIES),
+        tags,
+        flags,
+        Some(format!("¥{}", 500 + i % 3000)),
+        Availability::Available,
+    )
+}
+
+fn generate_database(runtime: &Runtime) -> Sqlite {
+    let db = Sqlite::new(":memory:").unwrap();
+    db.setup().unwrap();
+    runtime.block_on(async {
+        for i in 0..PRODUCTS {
+            db.create_melonbooks_product(&product_args(i)).await.unwrap();
+        }
+    });
+    db
+}
+
+fn bench_products(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let db = generate_database(&runtime);
+    let user = runtime.block_on(db.get_user_by_name(DEFAULT_USERNAME)).unwrap().unwrap();
+    let artist_id = runtime.block_on(db.get_melonbooks_artists(user.id())).unwrap()
+        .into_iter()
+        .find(|a| a.name() == "artist_0")
+        .unwrap()
+        .id();
+
+    let mut group = c.benchmark_group("melonbooks_products");
+    group.sample_size(10);
+    group.bench_function("get_all", |b| b.iter(|| {
+        runtime.block_on(db.get_melonbooks_products()).unwrap()
+    }));
+    group.bench_function("get_by_artist", |b| b.iter(|| {
+        runtime.block_on(db.get_melonbooks_products_by_artist(artist_id)).unwrap()
+    }));
+    let query = ProductQuery::new(PageRequest::new(10, 50), ProductSort::DateAdded, SortDirection::Desc);
+    group.bench_function("get_page", |b| b.iter(|| {
+        runtime.block_on(db.get_melonbooks_products_page(&query)).unwrap()
+    }));
+    let query = ProductQuery::new(PageRequest::new(1, 500), ProductSort::Title, SortDirection::Asc);
+    group.bench_function("get_large_page", |b| b.iter(|| {
+        runtime.block_on(db.get_melonbooks_products_page(&query)).unwrap()
+    }));
+    group.finish();
+}
+
+criterion_group!(benches, bench_products);
+criterion_main!(benches);
@@ -0,0 +1,4 @@
+DROP TRIGGER tr__amiami_remove_product_search;
+DROP TABLE amiami_product_search;
+DROP TRIGGER tr__melonbooks_remove_product_search;
+DROP TABLE melonbooks_product_search;
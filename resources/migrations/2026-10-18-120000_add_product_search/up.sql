@@ -0,0 +1,41 @@
+CREATE VIRTUAL TABLE melonbooks_product_search USING fts5 (
+    title,
+    circle,
+    artists,
+    tags,
+    tokenize = 'trigram'
+);
+
+INSERT INTO melonbooks_product_search (rowid, title, circle, artists, tags)
+SELECT p.id,
+       p.title,
+       coalesce(p.circle, ''),
+       coalesce((SELECT group_concat(a.name, ' | ')
+                 FROM melonbooks_product_artist pa
+                          JOIN melonbooks_artist a ON a.id = pa.artist_id
+                 WHERE pa.product_id = p.id), ''),
+       coalesce((SELECT group_concat(t.tag, ' | ')
+                 FROM melonbooks_product_tag pt
+                          JOIN melonbooks_tag t ON t.id = pt.tag_id
+                 WHERE pt.product_id = p.id), '')
+FROM melonbooks_product p;
+
+CREATE TRIGGER tr__melonbooks_remove_product_search AFTER DELETE ON melonbooks_product
+BEGIN
+    DELETE FROM melonbooks_product_search WHERE rowid = OLD.id;
+end;
+
+CREATE VIRTUAL TABLE amiami_product_search USING fts5 (
+    title,
+    maker,
+    tokenize = 'trigram'
+);
+
+INSERT INTO amiami_product_search (rowid, title, maker)
+SELECT id, title, maker
+FROM amiami_product;
+
+CREATE TRIGGER tr__amiami_remove_product_search AFTER DELETE ON amiami_product
+BEGIN
+    DELETE FROM amiami_product_search WHERE rowid = OLD.id;
+end;
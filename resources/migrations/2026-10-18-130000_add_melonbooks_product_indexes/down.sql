@@ -0,0 +1,4 @@
+DROP INDEX ix__melonbooks_product_flag_flag_id;
+DROP INDEX ix__melonbooks_product_tag_tag_id;
+DROP INDEX ix__melonbooks_product_artist_artist_id;
+DROP INDEX ix__melonbooks_product_date_added;
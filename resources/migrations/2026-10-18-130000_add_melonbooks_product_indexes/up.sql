@@ -0,0 +1,4 @@
+CREATE INDEX ix__melonbooks_product_date_added ON melonbooks_product (date_added);
+CREATE INDEX ix__melonbooks_product_artist_artist_id ON melonbooks_product_artist (artist_id);
+CREATE INDEX ix__melonbooks_product_tag_tag_id ON melonbooks_product_tag (tag_id);
+CREATE INDEX ix__melonbooks_product_flag_flag_id ON melonbooks_product_flag (flag_id);
@@ -0,0 +1,19 @@
+CREATE TABLE melonbooks_global_title_skip_sequence (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    sequence TEXT NOT NULL,
+    CONSTRAINT uk__melonbooks_title_skip_sequence__sequence UNIQUE (sequence)
+);
+
+INSERT OR IGNORE INTO melonbooks_global_title_skip_sequence (date_added, sequence)
+SELECT date_added, sequence
+FROM melonbooks_title_skip_sequence;
+
+DROP TABLE melonbooks_title_skip_sequence;
+ALTER TABLE melonbooks_global_title_skip_sequence RENAME TO melonbooks_title_skip_sequence;
+
+DROP INDEX ix__amiami_category_follower_user_id;
+DROP TABLE amiami_category_follower;
+DROP INDEX ix__melonbooks_artist_follower_user_id;
+DROP TABLE melonbooks_artist_follower;
+DROP TABLE app_user;
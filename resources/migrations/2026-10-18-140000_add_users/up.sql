@@ -0,0 +1,57 @@
+CREATE TABLE app_user (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    username TEXT NOT NULL,
+    CONSTRAINT uk__app_user__username UNIQUE (username)
+);
+
+-- everything followed so far belongs to the user used when authentication is disabled
+INSERT INTO app_user (username) VALUES ('default');
+
+CREATE TABLE melonbooks_artist_follower (
+    artist_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    date_followed TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    PRIMARY KEY (artist_id, user_id),
+    CONSTRAINT fk__melonbooks_artist_follower__artist FOREIGN KEY (artist_id) REFERENCES melonbooks_artist (id) ON DELETE CASCADE,
+    CONSTRAINT fk__melonbooks_artist_follower__user FOREIGN KEY (user_id) REFERENCES app_user (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__melonbooks_artist_follower_user_id ON melonbooks_artist_follower (user_id);
+
+INSERT INTO melonbooks_artist_follower (artist_id, user_id, date_followed)
+SELECT id, (SELECT id FROM app_user WHERE username = 'default'), COALESCE(date_followed, CURRENT_TIMESTAMP)
+FROM melonbooks_artist
+WHERE following;
+
+CREATE TABLE amiami_category_follower (
+    category_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    date_followed TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    PRIMARY KEY (category_id, user_id),
+    CONSTRAINT fk__amiami_category_follower__category FOREIGN KEY (category_id) REFERENCES amiami_category (id) ON DELETE CASCADE,
+    CONSTRAINT fk__amiami_category_follower__user FOREIGN KEY (user_id) REFERENCES app_user (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__amiami_category_follower_user_id ON amiami_category_follower (user_id);
+
+INSERT INTO amiami_category_follower (category_id, user_id)
+SELECT id, (SELECT id FROM app_user WHERE username = 'default')
+FROM amiami_category
+WHERE following;
+
+CREATE TABLE melonbooks_user_title_skip_sequence (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    user_id INTEGER NOT NULL,
+    sequence TEXT NOT NULL,
+    CONSTRAINT fk__melonbooks_title_skip_sequence__user FOREIGN KEY (user_id) REFERENCES app_user (id) ON DELETE CASCADE,
+    CONSTRAINT uk__melonbooks_title_skip_sequence__user_sequence UNIQUE (user_id, sequence)
+);
+
+INSERT INTO melonbooks_user_title_skip_sequence (id, date_added, user_id, sequence)
+SELECT id, date_added, (SELECT id FROM app_user WHERE username = 'default'), sequence
+FROM melonbooks_title_skip_sequence;
+
+DROP TABLE melonbooks_title_skip_sequence;
+ALTER TABLE melonbooks_user_title_skip_sequence RENAME TO melonbooks_title_skip_sequence;
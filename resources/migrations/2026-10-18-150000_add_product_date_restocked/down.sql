@@ -0,0 +1,2 @@
+ALTER TABLE amiami_product DROP COLUMN date_restocked;
+ALTER TABLE melonbooks_product DROP COLUMN date_restocked;
@@ -0,0 +1,2 @@
+ALTER TABLE melonbooks_product ADD COLUMN date_restocked TIMESTAMP NULL;
+ALTER TABLE amiami_product ADD COLUMN date_restocked TIMESTAMP NULL;
@@ -0,0 +1,4 @@
+DROP TABLE amiami_notification;
+DROP TABLE amiami_price_event;
+DROP TABLE melonbooks_notification;
+DROP TABLE melonbooks_price_event;
@@ -0,0 +1,44 @@
+CREATE TABLE melonbooks_price_event (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    product_id INTEGER NOT NULL,
+    price TEXT NULL,
+    CONSTRAINT fk__melonbooks_price_event__product FOREIGN KEY (product_id) REFERENCES melonbooks_product (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__melonbooks_price_event_product_id ON melonbooks_price_event (product_id);
+
+CREATE TABLE melonbooks_notification (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    product_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    CONSTRAINT fk__melonbooks_notification__product FOREIGN KEY (product_id) REFERENCES melonbooks_product (id) ON DELETE CASCADE,
+    CONSTRAINT fk__melonbooks_notification__user FOREIGN KEY (user_id) REFERENCES app_user (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__melonbooks_notification_product_id ON melonbooks_notification (product_id);
+
+CREATE TABLE amiami_price_event (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    product_id INTEGER NOT NULL,
+    full_price INTEGER NOT NULL,
+    min_price INTEGER NOT NULL,
+    CONSTRAINT fk__amiami_price_event__product FOREIGN KEY (product_id) REFERENCES amiami_product (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__amiami_price_event_product_id ON amiami_price_event (product_id);
+
+CREATE TABLE amiami_notification (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    product_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    CONSTRAINT fk__amiami_notification__product FOREIGN KEY (product_id) REFERENCES amiami_product (id) ON DELETE CASCADE,
+    CONSTRAINT fk__amiami_notification__user FOREIGN KEY (user_id) REFERENCES app_user (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__amiami_notification_product_id ON amiami_notification (product_id);
@@ -0,0 +1,2 @@
+DROP TABLE amiami_availability_event;
+DROP TABLE melonbooks_availability_event;
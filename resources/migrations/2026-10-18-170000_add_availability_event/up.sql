@@ -0,0 +1,23 @@
+CREATE TABLE melonbooks_availability_event (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    product_id INTEGER NOT NULL,
+    availability TEXT NOT NULL,
+    -- none for the first event of a product
+    previous_availability TEXT NULL,
+    CONSTRAINT fk__melonbooks_availability_event__product FOREIGN KEY (product_id) REFERENCES melonbooks_product (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__melonbooks_availability_event_product_id ON melonbooks_availability_event (product_id);
+
+CREATE TABLE amiami_availability_event (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    product_id INTEGER NOT NULL,
+    availability TEXT NOT NULL,
+    -- none for the first event of a product
+    previous_availability TEXT NULL,
+    CONSTRAINT fk__amiami_availability_event__product FOREIGN KEY (product_id) REFERENCES amiami_product (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__amiami_availability_event_product_id ON amiami_availability_event (product_id);
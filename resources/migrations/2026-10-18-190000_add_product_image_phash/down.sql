@@ -0,0 +1,2 @@
+ALTER TABLE amiami_product DROP COLUMN image_phash;
+ALTER TABLE melonbooks_product DROP COLUMN image_phash;
@@ -0,0 +1,2 @@
+ALTER TABLE melonbooks_product ADD COLUMN image_phash BIGINT NULL;
+ALTER TABLE amiami_product ADD COLUMN image_phash BIGINT NULL;
@@ -0,0 +1,9 @@
+DROP TABLE toranoana_skip_product_creator;
+DROP TABLE toranoana_skip_product;
+DROP TABLE toranoana_notification;
+DROP TABLE toranoana_price_event;
+DROP TABLE toranoana_availability_event;
+DROP TABLE toranoana_product_creator;
+DROP TABLE toranoana_creator_follower;
+DROP TABLE toranoana_creator;
+DROP TABLE toranoana_product;
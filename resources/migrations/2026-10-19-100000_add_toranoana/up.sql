@@ -0,0 +1,93 @@
+CREATE TABLE toranoana_product (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    url TEXT NOT NULL,
+    title TEXT NOT NULL,
+    image_url TEXT NOT NULL,
+    category TEXT NOT NULL,
+    price INTEGER NULL,
+    availability TEXT NOT NULL,
+    date_restocked TIMESTAMP NULL,
+    image_hash TEXT NULL,
+    image_phash BIGINT NULL,
+    CONSTRAINT uk__toranoana_product__url UNIQUE (url)
+);
+
+-- artists and circles are searched separately, so the same name can be followed as both
+CREATE TABLE toranoana_creator (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    name TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    following BOOLEAN NOT NULL,
+    date_followed TIMESTAMP,
+    CONSTRAINT uk__toranoana_creator__name_kind UNIQUE (name, kind)
+);
+
+CREATE TABLE toranoana_creator_follower (
+    creator_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    date_followed TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    PRIMARY KEY (creator_id, user_id),
+    CONSTRAINT fk__toranoana_creator_follower__creator FOREIGN KEY (creator_id) REFERENCES toranoana_creator (id) ON DELETE CASCADE,
+    CONSTRAINT fk__toranoana_creator_follower__user FOREIGN KEY (user_id) REFERENCES app_user (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__toranoana_creator_follower_user_id ON toranoana_creator_follower (user_id);
+
+CREATE TABLE toranoana_product_creator (
+    product_id INTEGER NOT NULL,
+    creator_id INTEGER NOT NULL,
+    PRIMARY KEY (product_id, creator_id),
+    CONSTRAINT fk__toranoana_product_creator__product FOREIGN KEY (product_id) REFERENCES toranoana_product (id) ON DELETE CASCADE,
+    CONSTRAINT fk__toranoana_product_creator__creator FOREIGN KEY (creator_id) REFERENCES toranoana_creator (id)
+);
+
+CREATE INDEX ix__toranoana_product_creator_creator_id ON toranoana_product_creator (creator_id);
+
+CREATE TABLE toranoana_availability_event (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    product_id INTEGER NOT NULL,
+    availability TEXT NOT NULL,
+    previous_availability TEXT NULL,
+    CONSTRAINT fk__toranoana_availability_event__product FOREIGN KEY (product_id) REFERENCES toranoana_product (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__toranoana_availability_event_product_id ON toranoana_availability_event (product_id);
+
+CREATE TABLE toranoana_price_event (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    product_id INTEGER NOT NULL,
+    price INTEGER NULL,
+    CONSTRAINT fk__toranoana_price_event__product FOREIGN KEY (product_id) REFERENCES toranoana_product (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__toranoana_price_event_product_id ON toranoana_price_event (product_id);
+
+CREATE TABLE toranoana_notification (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    product_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    CONSTRAINT fk__toranoana_notification__product FOREIGN KEY (product_id) REFERENCES toranoana_product (id) ON DELETE CASCADE,
+    CONSTRAINT fk__toranoana_notification__user FOREIGN KEY (user_id) REFERENCES app_user (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__toranoana_notification_product_id ON toranoana_notification (product_id);
+
+CREATE TABLE toranoana_skip_product (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    url TEXT NOT NULL,
+    CONSTRAINT uk__toranoana_skip_product__url UNIQUE (url)
+);
+
+CREATE TABLE toranoana_skip_product_creator (
+    skip_product_id INTEGER NOT NULL,
+    creator_name TEXT NOT NULL,
+    PRIMARY KEY (skip_product_id, creator_name),
+    CONSTRAINT fk__toranoana_skip_product_creator__skip_product FOREIGN KEY (skip_product_id) REFERENCES toranoana_skip_product (id) ON DELETE CASCADE
+);
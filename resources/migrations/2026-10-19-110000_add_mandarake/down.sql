@@ -0,0 +1,7 @@
+DROP TABLE mandarake_notification;
+DROP TABLE mandarake_price_event;
+DROP TABLE mandarake_availability_event;
+DROP TABLE mandarake_product_search;
+DROP TABLE mandarake_search_follower;
+DROP TABLE mandarake_search;
+DROP TABLE mandarake_product;
@@ -0,0 +1,7 @@
+DROP TABLE surugaya_notification;
+DROP TABLE surugaya_price_event;
+DROP TABLE surugaya_availability_event;
+DROP TABLE surugaya_product_search;
+DROP TABLE surugaya_search_follower;
+DROP TABLE surugaya_search;
+DROP TABLE surugaya_product;
@@ -0,0 +1,75 @@
+CREATE TABLE surugaya_product (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    url TEXT NOT NULL,
+    title TEXT NOT NULL,
+    image_url TEXT NOT NULL,
+    condition TEXT NOT NULL,
+    price INTEGER NULL,
+    availability TEXT NOT NULL,
+    date_restocked TIMESTAMP NULL,
+    image_hash TEXT NULL,
+    image_phash BIGINT NULL,
+    CONSTRAINT uk__surugaya_product__url UNIQUE (url)
+);
+
+CREATE TABLE surugaya_search (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    keyword TEXT NOT NULL,
+    CONSTRAINT uk__surugaya_search__keyword UNIQUE (keyword)
+);
+
+CREATE TABLE surugaya_search_follower (
+    search_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    date_followed TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    PRIMARY KEY (search_id, user_id),
+    CONSTRAINT fk__surugaya_search_follower__search FOREIGN KEY (search_id) REFERENCES surugaya_search (id) ON DELETE CASCADE,
+    CONSTRAINT fk__surugaya_search_follower__user FOREIGN KEY (user_id) REFERENCES app_user (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__surugaya_search_follower_user_id ON surugaya_search_follower (user_id);
+
+CREATE TABLE surugaya_product_search (
+    product_id INTEGER NOT NULL,
+    search_id INTEGER NOT NULL,
+    PRIMARY KEY (product_id, search_id),
+    CONSTRAINT fk__surugaya_product_search__product FOREIGN KEY (product_id) REFERENCES surugaya_product (id) ON DELETE CASCADE,
+    CONSTRAINT fk__surugaya_product_search__search FOREIGN KEY (search_id) REFERENCES surugaya_search (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__surugaya_product_search_search_id ON surugaya_product_search (search_id);
+
+CREATE TABLE surugaya_availability_event (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    product_id INTEGER NOT NULL,
+    availability TEXT NOT NULL,
+    previous_availability TEXT NULL,
+    CONSTRAINT fk__surugaya_availability_event__product FOREIGN KEY (product_id) REFERENCES surugaya_product (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__surugaya_availability_event_product_id ON surugaya_availability_event (product_id);
+
+CREATE TABLE surugaya_price_event (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    product_id INTEGER NOT NULL,
+    price INTEGER NULL,
+    CONSTRAINT fk__surugaya_price_event__product FOREIGN KEY (product_id) REFERENCES surugaya_product (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__surugaya_price_event_product_id ON surugaya_price_event (product_id);
+
+CREATE TABLE surugaya_notification (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    product_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    CONSTRAINT fk__surugaya_notification__product FOREIGN KEY (product_id) REFERENCES surugaya_product (id) ON DELETE CASCADE,
+    CONSTRAINT fk__surugaya_notification__user FOREIGN KEY (user_id) REFERENCES app_user (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__surugaya_notification_product_id ON surugaya_notification (product_id);
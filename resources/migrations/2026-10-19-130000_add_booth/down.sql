@@ -0,0 +1,8 @@
+DROP TABLE booth_notification;
+DROP TABLE booth_price_event;
+DROP TABLE booth_availability_event;
+DROP TABLE booth_product_source;
+DROP TABLE booth_source_follower;
+DROP TABLE booth_source;
+DROP TABLE booth_variation;
+DROP TABLE booth_product;
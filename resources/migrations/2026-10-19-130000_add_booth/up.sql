@@ -0,0 +1,91 @@
+-- price and availability are those of the cheapest variation on sale, kept for history and sorting
+CREATE TABLE booth_product (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    url TEXT NOT NULL,
+    title TEXT NOT NULL,
+    shop_name TEXT NOT NULL,
+    image_url TEXT NOT NULL,
+    price INTEGER NULL,
+    availability TEXT NOT NULL,
+    date_restocked TIMESTAMP NULL,
+    image_hash TEXT NULL,
+    image_phash BIGINT NULL,
+    CONSTRAINT uk__booth_product__url UNIQUE (url)
+);
+
+CREATE TABLE booth_variation (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    product_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    price INTEGER NOT NULL,
+    availability TEXT NOT NULL,
+    CONSTRAINT uk__booth_variation__product_id_name UNIQUE (product_id, name),
+    CONSTRAINT fk__booth_variation__product FOREIGN KEY (product_id) REFERENCES booth_product (id) ON DELETE CASCADE
+);
+
+-- shops are followed by their subdomain, a shop can be linked to the melonbooks artist running it
+CREATE TABLE booth_source (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    name TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    melonbooks_artist_id INTEGER NULL,
+    CONSTRAINT uk__booth_source__name_kind UNIQUE (name, kind),
+    CONSTRAINT fk__booth_source__melonbooks_artist FOREIGN KEY (melonbooks_artist_id) REFERENCES melonbooks_artist (id) ON DELETE SET NULL
+);
+
+CREATE TABLE booth_source_follower (
+    source_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    date_followed TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    PRIMARY KEY (source_id, user_id),
+    CONSTRAINT fk__booth_source_follower__source FOREIGN KEY (source_id) REFERENCES booth_source (id) ON DELETE CASCADE,
+    CONSTRAINT fk__booth_source_follower__user FOREIGN KEY (user_id) REFERENCES app_user (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__booth_source_follower_user_id ON booth_source_follower (user_id);
+
+CREATE TABLE booth_product_source (
+    product_id INTEGER NOT NULL,
+    source_id INTEGER NOT NULL,
+    PRIMARY KEY (product_id, source_id),
+    CONSTRAINT fk__booth_product_source__product FOREIGN KEY (product_id) REFERENCES booth_product (id) ON DELETE CASCADE,
+    CONSTRAINT fk__booth_product_source__source FOREIGN KEY (source_id) REFERENCES booth_source (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__booth_product_source_source_id ON booth_product_source (source_id);
+
+CREATE TABLE booth_availability_event (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    product_id INTEGER NOT NULL,
+    availability TEXT NOT NULL,
+    previous_availability TEXT NULL,
+    CONSTRAINT fk__booth_availability_event__product FOREIGN KEY (product_id) REFERENCES booth_product (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__booth_availability_event_product_id ON booth_availability_event (product_id);
+
+CREATE TABLE booth_price_event (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    product_id INTEGER NOT NULL,
+    price INTEGER NULL,
+    CONSTRAINT fk__booth_price_event__product FOREIGN KEY (product_id) REFERENCES booth_product (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__booth_price_event_product_id ON booth_price_event (product_id);
+
+CREATE TABLE booth_notification (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    product_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    CONSTRAINT fk__booth_notification__product FOREIGN KEY (product_id) REFERENCES booth_product (id) ON DELETE CASCADE,
+    CONSTRAINT fk__booth_notification__user FOREIGN KEY (user_id) REFERENCES app_user (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__booth_notification_product_id ON booth_notification (product_id);
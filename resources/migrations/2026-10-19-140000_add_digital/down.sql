@@ -0,0 +1,6 @@
+DROP TABLE digital_notification;
+DROP TABLE digital_sale_event;
+DROP TABLE digital_price_event;
+DROP TABLE digital_product;
+DROP TABLE digital_circle_follower;
+DROP TABLE digital_circle;
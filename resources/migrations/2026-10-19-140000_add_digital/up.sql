@@ -0,0 +1,76 @@
+-- circles are followed by their id in the store, the name is updated on every scrape
+CREATE TABLE digital_circle (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    store TEXT NOT NULL,
+    code TEXT NOT NULL,
+    name TEXT NOT NULL,
+    CONSTRAINT uk__digital_circle__store_code UNIQUE (store, code)
+);
+
+CREATE TABLE digital_circle_follower (
+    circle_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    date_followed TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    PRIMARY KEY (circle_id, user_id),
+    CONSTRAINT fk__digital_circle_follower__circle FOREIGN KEY (circle_id) REFERENCES digital_circle (id) ON DELETE CASCADE,
+    CONSTRAINT fk__digital_circle_follower__user FOREIGN KEY (user_id) REFERENCES app_user (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__digital_circle_follower_user_id ON digital_circle_follower (user_id);
+
+-- sale_end is in the store's local time
+CREATE TABLE digital_product (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    circle_id INTEGER NOT NULL,
+    store TEXT NOT NULL,
+    url TEXT NOT NULL,
+    title TEXT NOT NULL,
+    circle_name TEXT NOT NULL,
+    image_url TEXT NOT NULL,
+    list_price INTEGER NOT NULL,
+    sale_price INTEGER NULL,
+    sale_end TIMESTAMP NULL,
+    date_discounted TIMESTAMP NULL,
+    image_hash TEXT NULL,
+    image_phash BIGINT NULL,
+    CONSTRAINT uk__digital_product__url UNIQUE (url),
+    CONSTRAINT fk__digital_product__circle FOREIGN KEY (circle_id) REFERENCES digital_circle (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__digital_product_circle_id ON digital_product (circle_id);
+
+CREATE TABLE digital_price_event (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    product_id INTEGER NOT NULL,
+    list_price INTEGER NOT NULL,
+    CONSTRAINT fk__digital_price_event__product FOREIGN KEY (product_id) REFERENCES digital_product (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__digital_price_event_product_id ON digital_price_event (product_id);
+
+-- a sale starting, changing or ending, the sale columns are null when it ended
+CREATE TABLE digital_sale_event (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    product_id INTEGER NOT NULL,
+    sale_price INTEGER NULL,
+    sale_end TIMESTAMP NULL,
+    CONSTRAINT fk__digital_sale_event__product FOREIGN KEY (product_id) REFERENCES digital_product (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__digital_sale_event_product_id ON digital_sale_event (product_id);
+
+CREATE TABLE digital_notification (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    product_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    CONSTRAINT fk__digital_notification__product FOREIGN KEY (product_id) REFERENCES digital_product (id) ON DELETE CASCADE,
+    CONSTRAINT fk__digital_notification__user FOREIGN KEY (user_id) REFERENCES app_user (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__digital_notification_product_id ON digital_notification (product_id);
@@ -0,0 +1,7 @@
+DROP TABLE figure_notification;
+DROP TABLE figure_preorder_event;
+DROP TABLE figure_price_event;
+DROP TABLE figure_product_source;
+DROP TABLE figure_product;
+DROP TABLE figure_source_follower;
+DROP TABLE figure_source;
@@ -0,0 +1,88 @@
+-- makers and series are followed by their id in the store's search, the name is updated on every scrape
+CREATE TABLE figure_source (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    store TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    code TEXT NOT NULL,
+    name TEXT NOT NULL,
+    CONSTRAINT uk__figure_source__store_kind_code UNIQUE (store, kind, code)
+);
+
+CREATE TABLE figure_source_follower (
+    source_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    date_followed TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    PRIMARY KEY (source_id, user_id),
+    CONSTRAINT fk__figure_source_follower__source FOREIGN KEY (source_id) REFERENCES figure_source (id) ON DELETE CASCADE,
+    CONSTRAINT fk__figure_source_follower__user FOREIGN KEY (user_id) REFERENCES app_user (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__figure_source_follower_user_id ON figure_source_follower (user_id);
+
+-- the preorder window is in the store's local time, either end is null when the store does not announce it
+-- date_preorder_opened and date_preorder_closing are when the current window was notified
+CREATE TABLE figure_product (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    store TEXT NOT NULL,
+    url TEXT NOT NULL,
+    title TEXT NOT NULL,
+    maker_name TEXT NOT NULL,
+    image_url TEXT NOT NULL,
+    price INTEGER NULL,
+    release_date TEXT NULL,
+    has_preorder BOOLEAN NOT NULL,
+    preorder_start TIMESTAMP NULL,
+    preorder_end TIMESTAMP NULL,
+    date_preorder_opened TIMESTAMP NULL,
+    date_preorder_closing TIMESTAMP NULL,
+    image_hash TEXT NULL,
+    image_phash BIGINT NULL,
+    CONSTRAINT uk__figure_product__url UNIQUE (url)
+);
+
+CREATE TABLE figure_product_source (
+    product_id INTEGER NOT NULL,
+    source_id INTEGER NOT NULL,
+    PRIMARY KEY (product_id, source_id),
+    CONSTRAINT fk__figure_product_source__product FOREIGN KEY (product_id) REFERENCES figure_product (id) ON DELETE CASCADE,
+    CONSTRAINT fk__figure_product_source__source FOREIGN KEY (source_id) REFERENCES figure_source (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__figure_product_source_source_id ON figure_product_source (source_id);
+
+CREATE TABLE figure_price_event (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    product_id INTEGER NOT NULL,
+    price INTEGER NULL,
+    CONSTRAINT fk__figure_price_event__product FOREIGN KEY (product_id) REFERENCES figure_product (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__figure_price_event_product_id ON figure_price_event (product_id);
+
+-- a preorder window opening, moving or closing
+CREATE TABLE figure_preorder_event (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    product_id INTEGER NOT NULL,
+    has_preorder BOOLEAN NOT NULL,
+    preorder_start TIMESTAMP NULL,
+    preorder_end TIMESTAMP NULL,
+    CONSTRAINT fk__figure_preorder_event__product FOREIGN KEY (product_id) REFERENCES figure_product (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__figure_preorder_event_product_id ON figure_preorder_event (product_id);
+
+CREATE TABLE figure_notification (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date_added TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    product_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    CONSTRAINT fk__figure_notification__product FOREIGN KEY (product_id) REFERENCES figure_product (id) ON DELETE CASCADE,
+    CONSTRAINT fk__figure_notification__user FOREIGN KEY (user_id) REFERENCES app_user (id) ON DELETE CASCADE
+);
+
+CREATE INDEX ix__figure_notification_product_id ON figure_notification (product_id);
@@ -0,0 +1,2 @@
+ALTER TABLE amiami_category DROP COLUMN schedule;
+ALTER TABLE melonbooks_artist DROP COLUMN schedule;
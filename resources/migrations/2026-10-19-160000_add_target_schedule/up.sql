@@ -0,0 +1,2 @@
+ALTER TABLE melonbooks_artist ADD COLUMN schedule TEXT NULL;
+ALTER TABLE amiami_category ADD COLUMN schedule TEXT NULL;
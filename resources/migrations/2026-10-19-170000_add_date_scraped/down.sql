@@ -0,0 +1,4 @@
+ALTER TABLE amiami_category DROP COLUMN date_first_scraped;
+ALTER TABLE melonbooks_artist DROP COLUMN date_first_scraped;
+ALTER TABLE amiami_category DROP COLUMN date_scraped;
+ALTER TABLE melonbooks_artist DROP COLUMN date_scraped;
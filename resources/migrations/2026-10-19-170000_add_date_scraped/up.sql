@@ -0,0 +1,30 @@
+ALTER TABLE melonbooks_artist ADD COLUMN date_scraped TIMESTAMP NULL;
+ALTER TABLE amiami_category ADD COLUMN date_scraped TIMESTAMP NULL;
+
+-- end of the first scrape, products added until then are the backfill and not the activity of the artist
+ALTER TABLE melonbooks_artist ADD COLUMN date_first_scraped TIMESTAMP NULL;
+ALTER TABLE amiami_category ADD COLUMN date_first_scraped TIMESTAMP NULL;
+
+-- the first scrape of an artist or category is taken to be over an hour after its first product
+UPDATE melonbooks_artist SET date_first_scraped = (
+    SELECT MAX(p.date_added)
+    FROM melonbooks_product p
+    INNER JOIN melonbooks_product_artist pa ON pa.product_id = p.id
+    WHERE pa.artist_id = melonbooks_artist.id
+      AND p.date_added <= (
+          SELECT datetime(MIN(p2.date_added), '+1 hour')
+          FROM melonbooks_product p2
+          INNER JOIN melonbooks_product_artist pa2 ON pa2.product_id = p2.id
+          WHERE pa2.artist_id = melonbooks_artist.id
+      )
+);
+UPDATE amiami_category SET date_first_scraped = (
+    SELECT MAX(p.date_added)
+    FROM amiami_product p
+    WHERE p.category_id = amiami_category.id
+      AND p.date_added <= (
+          SELECT datetime(MIN(p2.date_added), '+1 hour')
+          FROM amiami_product p2
+          WHERE p2.category_id = amiami_category.id
+      )
+);
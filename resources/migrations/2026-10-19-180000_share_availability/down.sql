@@ -0,0 +1,3 @@
+UPDATE mandarake_availability_event SET previous_availability = 'SoldOut' WHERE previous_availability = 'NotAvailable';
+UPDATE mandarake_availability_event SET availability = 'SoldOut' WHERE availability = 'NotAvailable';
+UPDATE mandarake_product SET availability = 'SoldOut' WHERE availability = 'NotAvailable';
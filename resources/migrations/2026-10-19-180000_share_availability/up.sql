@@ -0,0 +1,4 @@
+-- mandarake listings share the availability of the other sites, sold listings are no longer available
+UPDATE mandarake_product SET availability = 'NotAvailable' WHERE availability = 'SoldOut';
+UPDATE mandarake_availability_event SET availability = 'NotAvailable' WHERE availability = 'SoldOut';
+UPDATE mandarake_availability_event SET previous_availability = 'NotAvailable' WHERE previous_availability = 'SoldOut';
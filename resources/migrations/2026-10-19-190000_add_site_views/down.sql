@@ -0,0 +1,7 @@
+DROP TRIGGER tr__site_notification_insert;
+DROP TRIGGER tr__site_product_set_image;
+DROP VIEW site_notification;
+DROP VIEW site_product_target;
+DROP VIEW site_target_follower;
+DROP VIEW site_target;
+DROP VIEW site_product;
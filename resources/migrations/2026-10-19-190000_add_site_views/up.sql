@@ -0,0 +1,137 @@
+-- the tables every site has, queried across sites by the product index, the duplicate detection and the notifications
+-- a new site adds its tables to the views and triggers in its own migration by dropping and creating them again
+
+-- prices are text as Melonbooks keeps them as shown, figures are available while their preorder window is open in Japan
+CREATE VIEW site_product AS
+SELECT 'melonbooks' AS site, id AS product_id, date_added, url, title, image_url, image_hash, image_phash,
+       price, availability, date_restocked
+FROM melonbooks_product
+UNION ALL
+SELECT 'toranoana', id, date_added, url, title, image_url, image_hash, image_phash,
+       CAST(price AS TEXT), availability, date_restocked
+FROM toranoana_product
+UNION ALL
+SELECT 'mandarake', id, date_added, url, title, image_url, image_hash, image_phash,
+       CAST(price AS TEXT), availability, date_restocked
+FROM mandarake_product
+UNION ALL
+SELECT 'surugaya', id, date_added, url, title, image_url, image_hash, image_phash,
+       CAST(price AS TEXT), availability, date_restocked
+FROM surugaya_product
+UNION ALL
+SELECT 'booth', id, date_added, url, title, image_url, image_hash, image_phash,
+       CAST(price AS TEXT), availability, date_restocked
+FROM booth_product
+UNION ALL
+SELECT 'digital', id, date_added, url, title, image_url, image_hash, image_phash,
+       CAST(COALESCE(sale_price, list_price) AS TEXT), 'Available', NULL
+FROM digital_product
+UNION ALL
+SELECT 'figure', id, date_added, url, title, image_url, image_hash, image_phash,
+       CAST(price AS TEXT),
+       CASE
+           WHEN has_preorder
+               AND (preorder_start IS NULL OR preorder_start <= datetime('now', '+9 hours'))
+               AND (preorder_end IS NULL OR datetime('now', '+9 hours') < preorder_end)
+           THEN 'Preorder'
+           ELSE 'NotAvailable'
+       END,
+       NULL
+FROM figure_product
+UNION ALL
+SELECT 'amiami', id, date_added, url, title, image_url, image_hash, image_phash,
+       CAST(min_price AS TEXT), availability, date_restocked
+FROM amiami_product;
+
+-- the artists, categories, searches, shops and circles products are found for
+CREATE VIEW site_target AS
+SELECT 'melonbooks' AS site, id AS target_id, name FROM melonbooks_artist
+UNION ALL
+SELECT 'toranoana', id, name FROM toranoana_creator
+UNION ALL
+SELECT 'mandarake', id, keyword FROM mandarake_search
+UNION ALL
+SELECT 'surugaya', id, keyword FROM surugaya_search
+UNION ALL
+SELECT 'booth', id, name FROM booth_source
+UNION ALL
+SELECT 'digital', id, name FROM digital_circle
+UNION ALL
+SELECT 'figure', id, name FROM figure_source
+UNION ALL
+SELECT 'amiami', id, category FROM amiami_category;
+
+CREATE VIEW site_target_follower AS
+SELECT 'melonbooks' AS site, artist_id AS target_id, user_id FROM melonbooks_artist_follower
+UNION ALL
+SELECT 'toranoana', creator_id, user_id FROM toranoana_creator_follower
+UNION ALL
+SELECT 'mandarake', search_id, user_id FROM mandarake_search_follower
+UNION ALL
+SELECT 'surugaya', search_id, user_id FROM surugaya_search_follower
+UNION ALL
+SELECT 'booth', source_id, user_id FROM booth_source_follower
+UNION ALL
+SELECT 'digital', circle_id, user_id FROM digital_circle_follower
+UNION ALL
+SELECT 'figure', source_id, user_id FROM figure_source_follower
+UNION ALL
+SELECT 'amiami', category_id, user_id FROM amiami_category_follower;
+
+CREATE VIEW site_product_target AS
+SELECT 'melonbooks' AS site, product_id, artist_id AS target_id FROM melonbooks_product_artist
+UNION ALL
+SELECT 'toranoana', product_id, creator_id FROM toranoana_product_creator
+UNION ALL
+SELECT 'mandarake', product_id, search_id FROM mandarake_product_search
+UNION ALL
+SELECT 'surugaya', product_id, search_id FROM surugaya_product_search
+UNION ALL
+SELECT 'booth', product_id, source_id FROM booth_product_source
+UNION ALL
+SELECT 'digital', id, circle_id FROM digital_product
+UNION ALL
+SELECT 'figure', product_id, source_id FROM figure_product_source
+UNION ALL
+SELECT 'amiami', id, category_id FROM amiami_product;
+
+CREATE VIEW site_notification AS
+SELECT 'melonbooks' AS site, id, date_added, product_id, user_id, kind FROM melonbooks_notification
+UNION ALL
+SELECT 'toranoana', id, date_added, product_id, user_id, kind FROM toranoana_notification
+UNION ALL
+SELECT 'mandarake', id, date_added, product_id, user_id, kind FROM mandarake_notification
+UNION ALL
+SELECT 'surugaya', id, date_added, product_id, user_id, kind FROM surugaya_notification
+UNION ALL
+SELECT 'booth', id, date_added, product_id, user_id, kind FROM booth_notification
+UNION ALL
+SELECT 'digital', id, date_added, product_id, user_id, kind FROM digital_notification
+UNION ALL
+SELECT 'figure', id, date_added, product_id, user_id, kind FROM figure_notification
+UNION ALL
+SELECT 'amiami', id, date_added, product_id, user_id, kind FROM amiami_notification;
+
+CREATE TRIGGER tr__site_product_set_image INSTEAD OF UPDATE OF image_hash, image_phash ON site_product
+BEGIN
+    UPDATE melonbooks_product SET image_hash = NEW.image_hash, image_phash = NEW.image_phash WHERE OLD.site = 'melonbooks' AND id = OLD.product_id;
+    UPDATE toranoana_product SET image_hash = NEW.image_hash, image_phash = NEW.image_phash WHERE OLD.site = 'toranoana' AND id = OLD.product_id;
+    UPDATE mandarake_product SET image_hash = NEW.image_hash, image_phash = NEW.image_phash WHERE OLD.site = 'mandarake' AND id = OLD.product_id;
+    UPDATE surugaya_product SET image_hash = NEW.image_hash, image_phash = NEW.image_phash WHERE OLD.site = 'surugaya' AND id = OLD.product_id;
+    UPDATE booth_product SET image_hash = NEW.image_hash, image_phash = NEW.image_phash WHERE OLD.site = 'booth' AND id = OLD.product_id;
+    UPDATE digital_product SET image_hash = NEW.image_hash, image_phash = NEW.image_phash WHERE OLD.site = 'digital' AND id = OLD.product_id;
+    UPDATE figure_product SET image_hash = NEW.image_hash, image_phash = NEW.image_phash WHERE OLD.site = 'figure' AND id = OLD.product_id;
+    UPDATE amiami_product SET image_hash = NEW.image_hash, image_phash = NEW.image_phash WHERE OLD.site = 'amiami' AND id = OLD.product_id;
+end;
+
+CREATE TRIGGER tr__site_notification_insert INSTEAD OF INSERT ON site_notification
+BEGIN
+    INSERT INTO melonbooks_notification (product_id, user_id, kind) SELECT NEW.product_id, NEW.user_id, NEW.kind WHERE NEW.site = 'melonbooks';
+    INSERT INTO toranoana_notification (product_id, user_id, kind) SELECT NEW.product_id, NEW.user_id, NEW.kind WHERE NEW.site = 'toranoana';
+    INSERT INTO mandarake_notification (product_id, user_id, kind) SELECT NEW.product_id, NEW.user_id, NEW.kind WHERE NEW.site = 'mandarake';
+    INSERT INTO surugaya_notification (product_id, user_id, kind) SELECT NEW.product_id, NEW.user_id, NEW.kind WHERE NEW.site = 'surugaya';
+    INSERT INTO booth_notification (product_id, user_id, kind) SELECT NEW.product_id, NEW.user_id, NEW.kind WHERE NEW.site = 'booth';
+    INSERT INTO digital_notification (product_id, user_id, kind) SELECT NEW.product_id, NEW.user_id, NEW.kind WHERE NEW.site = 'digital';
+    INSERT INTO figure_notification (product_id, user_id, kind) SELECT NEW.product_id, NEW.user_id, NEW.kind WHERE NEW.site = 'figure';
+    INSERT INTO amiami_notification (product_id, user_id, kind) SELECT NEW.product_id, NEW.user_id, NEW.kind WHERE NEW.site = 'amiami';
+end;
@@ -0,0 +1,15 @@
+DROP TRIGGER tr__site_product_set_image;
+
+CREATE TRIGGER tr__site_product_set_image INSTEAD OF UPDATE OF image_hash, image_phash ON site_product
+BEGIN
+    UPDATE melonbooks_product SET image_hash = NEW.image_hash, image_phash = NEW.image_phash WHERE OLD.site = 'melonbooks' AND id = OLD.product_id;
+    UPDATE toranoana_product SET image_hash = NEW.image_hash, image_phash = NEW.image_phash WHERE OLD.site = 'toranoana' AND id = OLD.product_id;
+    UPDATE mandarake_product SET image_hash = NEW.image_hash, image_phash = NEW.image_phash WHERE OLD.site = 'mandarake' AND id = OLD.product_id;
+    UPDATE surugaya_product SET image_hash = NEW.image_hash, image_phash = NEW.image_phash WHERE OLD.site = 'surugaya' AND id = OLD.product_id;
+    UPDATE booth_product SET image_hash = NEW.image_hash, image_phash = NEW.image_phash WHERE OLD.site = 'booth' AND id = OLD.product_id;
+    UPDATE digital_product SET image_hash = NEW.image_hash, image_phash = NEW.image_phash WHERE OLD.site = 'digital' AND id = OLD.product_id;
+    UPDATE figure_product SET image_hash = NEW.image_hash, image_phash = NEW.image_phash WHERE OLD.site = 'figure' AND id = OLD.product_id;
+    UPDATE amiami_product SET image_hash = NEW.image_hash, image_phash = NEW.image_phash WHERE OLD.site = 'amiami' AND id = OLD.product_id;
+end;
+
+DROP TABLE image_phash_band;
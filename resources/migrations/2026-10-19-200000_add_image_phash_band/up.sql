@@ -0,0 +1,63 @@
+-- the perceptual hash split into 8 bands of 7 bits and one of 8 bits, two hashes within 8 bits have at least one equal band
+-- so only products sharing a band with the image are compared for duplicates
+CREATE TABLE image_phash_band (
+    site TEXT NOT NULL,
+    product_id INTEGER NOT NULL,
+    band INTEGER NOT NULL,
+    value INTEGER NOT NULL,
+    PRIMARY KEY (band, value, site, product_id)
+);
+
+CREATE INDEX ix__image_phash_band_site_product_id ON image_phash_band (site, product_id);
+
+INSERT INTO image_phash_band (site, product_id, band, value)
+SELECT site, product_id, 0, image_phash & 127 FROM site_product WHERE image_phash IS NOT NULL
+UNION ALL
+SELECT site, product_id, 1, (image_phash >> 7) & 127 FROM site_product WHERE image_phash IS NOT NULL
+UNION ALL
+SELECT site, product_id, 2, (image_phash >> 14) & 127 FROM site_product WHERE image_phash IS NOT NULL
+UNION ALL
+SELECT site, product_id, 3, (image_phash >> 21) & 127 FROM site_product WHERE image_phash IS NOT NULL
+UNION ALL
+SELECT site, product_id, 4, (image_phash >> 28) & 127 FROM site_product WHERE image_phash IS NOT NULL
+UNION ALL
+SELECT site, product_id, 5, (image_phash >> 35) & 127 FROM site_product WHERE image_phash IS NOT NULL
+UNION ALL
+SELECT site, product_id, 6, (image_phash >> 42) & 127 FROM site_product WHERE image_phash IS NOT NULL
+UNION ALL
+SELECT site, product_id, 7, (image_phash >> 49) & 127 FROM site_product WHERE image_phash IS NOT NULL
+UNION ALL
+SELECT site, product_id, 8, (image_phash >> 56) & 255 FROM site_product WHERE image_phash IS NOT NULL;
+
+DROP TRIGGER tr__site_product_set_image;
+
+CREATE TRIGGER tr__site_product_set_image INSTEAD OF UPDATE OF image_hash, image_phash ON site_product
+BEGIN
+    UPDATE melonbooks_product SET image_hash = NEW.image_hash, image_phash = NEW.image_phash WHERE OLD.site = 'melonbooks' AND id = OLD.product_id;
+    UPDATE toranoana_product SET image_hash = NEW.image_hash, image_phash = NEW.image_phash WHERE OLD.site = 'toranoana' AND id = OLD.product_id;
+    UPDATE mandarake_product SET image_hash = NEW.image_hash, image_phash = NEW.image_phash WHERE OLD.site = 'mandarake' AND id = OLD.product_id;
+    UPDATE surugaya_product SET image_hash = NEW.image_hash, image_phash = NEW.image_phash WHERE OLD.site = 'surugaya' AND id = OLD.product_id;
+    UPDATE booth_product SET image_hash = NEW.image_hash, image_phash = NEW.image_phash WHERE OLD.site = 'booth' AND id = OLD.product_id;
+    UPDATE digital_product SET image_hash = NEW.image_hash, image_phash = NEW.image_phash WHERE OLD.site = 'digital' AND id = OLD.product_id;
+    UPDATE figure_product SET image_hash = NEW.image_hash, image_phash = NEW.image_phash WHERE OLD.site = 'figure' AND id = OLD.product_id;
+    UPDATE amiami_product SET image_hash = NEW.image_hash, image_phash = NEW.image_phash WHERE OLD.site = 'amiami' AND id = OLD.product_id;
+    DELETE FROM image_phash_band WHERE site = OLD.site AND product_id = OLD.product_id;
+    INSERT INTO image_phash_band (site, product_id, band, value)
+    SELECT OLD.site, OLD.product_id, 0, NEW.image_phash & 127 WHERE NEW.image_phash IS NOT NULL
+    UNION ALL
+    SELECT OLD.site, OLD.product_id, 1, (NEW.image_phash >> 7) & 127 WHERE NEW.image_phash IS NOT NULL
+    UNION ALL
+    SELECT OLD.site, OLD.product_id, 2, (NEW.image_phash >> 14) & 127 WHERE NEW.image_phash IS NOT NULL
+    UNION ALL
+    SELECT OLD.site, OLD.product_id, 3, (NEW.image_phash >> 21) & 127 WHERE NEW.image_phash IS NOT NULL
+    UNION ALL
+    SELECT OLD.site, OLD.product_id, 4, (NEW.image_phash >> 28) & 127 WHERE NEW.image_phash IS NOT NULL
+    UNION ALL
+    SELECT OLD.site, OLD.product_id, 5, (NEW.image_phash >> 35) & 127 WHERE NEW.image_phash IS NOT NULL
+    UNION ALL
+    SELECT OLD.site, OLD.product_id, 6, (NEW.image_phash >> 42) & 127 WHERE NEW.image_phash IS NOT NULL
+    UNION ALL
+    SELECT OLD.site, OLD.product_id, 7, (NEW.image_phash >> 49) & 127 WHERE NEW.image_phash IS NOT NULL
+    UNION ALL
+    SELECT OLD.site, OLD.product_id, 8, (NEW.image_phash >> 56) & 255 WHERE NEW.image_phash IS NOT NULL;
+end;
@@ -0,0 +1 @@
+DROP VIEW site_product_event;
@@ -0,0 +1,33 @@
+-- the availability, price, sale and preorder changes of the products of all sites, feeds are updated by their latest one
+CREATE VIEW site_product_event AS
+SELECT 'melonbooks' AS site, product_id, date_added FROM melonbooks_availability_event
+UNION ALL
+SELECT 'melonbooks', product_id, date_added FROM melonbooks_price_event
+UNION ALL
+SELECT 'toranoana', product_id, date_added FROM toranoana_availability_event
+UNION ALL
+SELECT 'toranoana', product_id, date_added FROM toranoana_price_event
+UNION ALL
+SELECT 'mandarake', product_id, date_added FROM mandarake_availability_event
+UNION ALL
+SELECT 'mandarake', product_id, date_added FROM mandarake_price_event
+UNION ALL
+SELECT 'surugaya', product_id, date_added FROM surugaya_availability_event
+UNION ALL
+SELECT 'surugaya', product_id, date_added FROM surugaya_price_event
+UNION ALL
+SELECT 'booth', product_id, date_added FROM booth_availability_event
+UNION ALL
+SELECT 'booth', product_id, date_added FROM booth_price_event
+UNION ALL
+SELECT 'digital', product_id, date_added FROM digital_price_event
+UNION ALL
+SELECT 'digital', product_id, date_added FROM digital_sale_event
+UNION ALL
+SELECT 'figure', product_id, date_added FROM figure_price_event
+UNION ALL
+SELECT 'figure', product_id, date_added FROM figure_preorder_event
+UNION ALL
+SELECT 'amiami', product_id, date_added FROM amiami_availability_event
+UNION ALL
+SELECT 'amiami', product_id, date_added FROM amiami_price_event;
@@ -0,0 +1,2 @@
+ALTER TABLE amiami_category_follower DROP COLUMN schedule;
+ALTER TABLE melonbooks_artist_follower DROP COLUMN schedule;
@@ -0,0 +1,7 @@
+ALTER TABLE melonbooks_artist_follower ADD COLUMN schedule TEXT NULL;
+UPDATE melonbooks_artist_follower SET schedule = (SELECT a.schedule FROM melonbooks_artist a WHERE a.id = melonbooks_artist_follower.artist_id);
+UPDATE melonbooks_artist SET schedule = NULL WHERE NOT following;
+
+ALTER TABLE amiami_category_follower ADD COLUMN schedule TEXT NULL;
+UPDATE amiami_category_follower SET schedule = (SELECT c.schedule FROM amiami_category c WHERE c.id = amiami_category_follower.category_id);
+UPDATE amiami_category SET schedule = NULL WHERE NOT following;
@@ -1,25 +1,27 @@
-use chrono::Local;
-use log::{error, info};
+use chrono::Duration;
+use log::{info, warn};
 use moe_scraper::config::ServerConfiguration;
-use moe_scraper::domain::amiami::ports::{AmiamiRepository, AmiamiService};
-use moe_scraper::domain::amiami::service::AmiamiServiceImpl;
-use moe_scraper::domain::melonbooks::ports::{MelonbooksRepository, MelonbooksService};
-use moe_scraper::domain::melonbooks::service::MelonbooksServiceImpl;
+use moe_scraper::domain::duplicate::service::DuplicateServiceImpl;
+use moe_scraper::domain::image::service::ImageServiceImpl;
+use moe_scraper::domain::product_index::service::ProductIndexServiceImpl;
+use moe_scraper::domain::site::SITES;
+use moe_scraper::domain::user::ports::UserService;
+use moe_scraper::domain::user::service::UserServiceImpl;
+use moe_scraper::inbound::http::auth::{HttpAuthConfig, HttpUser};
 use moe_scraper::inbound::http::{HttpServer, HttpServerConfig};
-use moe_scraper::outbound::amiami_discord_notifier::AmiamiDiscordNotifier;
-use moe_scraper::outbound::amiami_scraper::AmiamiScraperImpl;
-use moe_scraper::outbound::melonbooks_discord_notifier::MelonbooksDiscordNotifier;
-use moe_scraper::outbound::melonbooks_scraper::MelonbooksScraperImpl;
+use moe_scraper::inbound::scheduler::Scheduler;
+use moe_scraper::outbound::image_cache::FsImageCache;
 use moe_scraper::outbound::sqlite::Sqlite;
+use moe_scraper::sites::http_sites;
 use std::env;
 use std::sync::Arc;
-use tokio_cron_scheduler::{Job, JobScheduler};
 
 const OPENSSL_CONFIG_ENV_VAR: &str = "OPENSSL_CONF";
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let config = ServerConfiguration::load_config()?;
+    let site_ids = SITES.map(|s| s.id());
+    let config = ServerConfiguration::load_config(&site_ids)?;
     tracing_subscriber::fmt::fmt()
         .with_max_level(config.log_level)
         .init();
@@ -29,72 +31,39 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("using config:\n{:#?}", config);
     let db = Sqlite::new(config.db_path.as_path().to_str().unwrap())?;
     db.setup()?;
-    let scheduler = JobScheduler::new().await?;
-    let melonbooks_service = init_melonbooks(&config, db.clone(), &scheduler).await?;
-    let amiami_service = init_amiami(&config, db.clone(), &scheduler).await?;
+    let user_service = Arc::new(UserServiceImpl::new(db.clone()));
+    let usernames = config.users.iter().map(|u| u.username.clone()).collect::<Vec<_>>();
+    user_service.setup_users(&config.default_user, &usernames).await?;
+    let image_cache = FsImageCache::new(config.image_dir.clone())?;
+    let image_service = Arc::new(ImageServiceImpl::new(image_cache.clone()));
+    let duplicate_service = Arc::new(DuplicateServiceImpl::new(db.clone()));
+    let product_index_service = Arc::new(ProductIndexServiceImpl::new(db.clone()));
+    let scheduler = Scheduler::new().await?;
+    let sites = http_sites(&config, db, image_cache)?;
+    for site in &sites {
+        let settings = config.site_settings(site.site().id());
+        if settings.adaptive.is_some() && site.service().adaptive_interval().is_none() {
+            warn!("ignoring adaptive interval of {}, only artists and categories are scraped by their activity", site.site().id());
+        }
+        scheduler.schedule_site(settings.schedule.as_deref(), site.service()).await?;
+    }
     scheduler.start().await?;
-    let http_config = HttpServerConfig { port: config.http_settings.port, assets_dir: config.http_settings.assets_dir };
-    let http_server = HttpServer::new(http_config, melonbooks_service, amiami_service).await?;
+    let http_config = HttpServerConfig {
+        port: config.http_settings.port,
+        assets_dir: config.http_settings.assets_dir,
+        auth: config.http_settings.auth.map(|a| HttpAuthConfig {
+            users: config.users.iter().map(|u| HttpUser {
+                username: u.username.clone(),
+                password_hash: u.password_hash.clone(),
+                api_tokens: u.api_tokens.clone(),
+            }).collect(),
+            session_ttl: Duration::hours(a.session_ttl_hours.into()),
+            secure_cookie: a.secure_cookie,
+        }),
+        default_user: config.default_user,
+    };
+    let http_server = HttpServer::new(http_config, sites, user_service, image_service, duplicate_service, product_index_service).await?;
     http_server.run().await?;
     Ok(())
 }
 
-async fn init_melonbooks(config: &ServerConfiguration, repo: impl MelonbooksRepository, scheduler: &JobScheduler) -> Result<Arc<impl MelonbooksService>, anyhow::Error> {
-    let melonbooks_settings = &config.melonbooks;
-    let discord_settings = &melonbooks_settings.discord_settings;
-    let schedule = &melonbooks_settings.schedule;
-    let notifier = MelonbooksDiscordNotifier::new(discord_settings.to_owned());
-    let scraper = MelonbooksScraperImpl::new()?;
-    let service = Arc::new(MelonbooksServiceImpl::new(repo, notifier, scraper));
-    if let Some(schedule) = schedule {
-        schedule_melonbooks(&scheduler, &schedule, service.clone()).await?;
-    }
-    Ok(service)
-}
-
-async fn schedule_melonbooks<S: MelonbooksService>(scheduler: &JobScheduler, schedule: &str, service: Arc<S>) -> Result<(), anyhow::Error> {
-    scheduler.add(
-        Job::new_async_tz(schedule, Local, move |_uuid, _l| {
-            Box::pin({
-                let service = service.clone();
-                async move {
-                    match service.scrape_available_products().await {
-                        Ok(_) => info!("Successfully scraped melonbooks"),
-                        Err(e) => error!("{:?}", e),
-                    };
-                }
-            })
-        })?
-    ).await?;
-    Ok(())
-}
-
-async fn init_amiami(config: &ServerConfiguration, repo: impl AmiamiRepository, scheduler: &JobScheduler) -> Result<Arc<impl AmiamiService>, anyhow::Error> {
-    let amiami_settings = &config.amiami;
-    let discord_settings = &amiami_settings.discord_settings;
-    let schedule = &amiami_settings.schedule;
-    let notifier = AmiamiDiscordNotifier::new(discord_settings.to_owned());
-    let scraper = AmiamiScraperImpl::new()?;
-    let service = Arc::new(AmiamiServiceImpl::new(repo, notifier, scraper));
-    if let Some(schedule) = schedule {
-        schedule_amiami(&scheduler, &schedule, service.clone()).await?;
-    }
-    Ok(service)
-}
-
-async fn schedule_amiami<S: AmiamiService>(scheduler: &JobScheduler, schedule: &str, service: Arc<S>) -> Result<(), anyhow::Error> {
-    scheduler.add(
-        Job::new_async_tz(schedule, Local, move |_uuid, _l| {
-            Box::pin({
-                let service = service.clone();
-                async move {
-                    match service.scrape_available_products().await {
-                        Ok(_) => info!("Successfully scraped amiami"),
-                        Err(e) => error!("{:?}", e),
-                    };
-                }
-            })
-        })?
-    ).await?;
-    Ok(())
-}
\ No newline at end of file
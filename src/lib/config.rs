@@ -1,27 +1,41 @@
+use crate::domain::user::models::user::DEFAULT_USERNAME;
 use debug_ignore::DebugIgnore;
 use figment::providers::{Env, Format, Yaml};
 use figment::Figment;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
+use std::collections::HashMap;
 use std::path::PathBuf;
-use strum_macros::{Display, EnumString};
 use thiserror::Error;
 use tracing::level_filters::LevelFilter;
 
 #[derive(Debug)]
 pub struct ServerConfiguration {
     pub db_path: PathBuf,
+    pub image_dir: PathBuf,
     pub log_level: LevelFilter,
-    pub melonbooks: SiteSettings,
-    pub amiami: SiteSettings,
+    /// Settings of the sites by their id.
+    pub sites: HashMap<String, SiteSettings>,
     pub openssl_config: Option<PathBuf>,
     pub http_settings: HttpSettings,
+    pub users: Vec<UserSettings>,
+    pub default_user: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct SiteSettings {
     pub schedule: Option<String>,
     pub discord_settings: Option<DiscordSettings>,
+    /// Whether new products already notified under another url or site are left out of the notifications.
+    pub suppress_duplicates: bool,
+    /// Scrapes the artists and categories without their own schedule by their activity instead of with the site.
+    pub adaptive: Option<AdaptiveSettings>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AdaptiveSettings {
+    pub min_interval_minutes: u32,
+    pub max_interval_minutes: u32,
 }
 
 #[derive(Debug, Clone)]
@@ -36,31 +50,69 @@ pub struct DiscordSettings {
 pub struct HttpSettings {
     pub port: u16,
     pub assets_dir: Option<PathBuf>,
+    pub auth: Option<AuthSettings>,
+    /// Url the server is reachable at from outside, e.g. for Discord to load the cached images.
+    pub public_url: Option<String>,
 }
 
 impl Default for HttpSettings {
     fn default() -> Self {
         Self { 
             port: 80,
-            assets_dir: None
+            assets_dir: None,
+            auth: None,
+            public_url: None,
         }
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct AuthSettings {
+    pub session_ttl_hours: u32,
+    pub secure_cookie: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct UserSettings {
+    pub username: String,
+    pub password_hash: Option<DebugIgnore<String>>,
+    pub api_tokens: DebugIgnore<Vec<String>>,
+    /// The user's own webhooks by site id.
+    pub discord_settings: HashMap<String, DiscordSettings>,
+}
+
 #[derive(Debug, Error)]
-#[error("invalid config: {0}")]
-pub struct ConfigurationError(figment::Error);
+pub enum ConfigurationError {
+    #[error("invalid config: {0}")]
+    Invalid(#[from] Box<figment::Error>),
+    #[error("invalid config: unknown site {0}")]
+    UnknownSite(String),
+}
 
 impl ServerConfiguration {
-    pub fn load_config() -> Result<Self, ConfigurationError> {
-        let config = Figment::from(Yaml::file("/config/moe-scraper.yaml"))
+    /// Loads the config, `site_ids` are the sites settings may be given for.
+    pub fn load_config(site_ids: &[&str]) -> Result<Self, ConfigurationError> {
+        let figment = Figment::from(Yaml::file("/config/moe-scraper.yaml"))
             .merge(Yaml::file("./config/moe-scraper.yaml"))
             .merge(Yaml::file("./moe-scraper.yaml"))
-            .merge(Env::prefixed("MOE_").split('_'))
-            .extract::<ServerConfigurationOptions>()
-            .or_else(|e| Err(ConfigurationError(e)))?
-            .into_actual();
-        Ok(config)
+            .merge(Env::prefixed("MOE_").split('_'));
+        Self::from_figment(figment, site_ids)
+    }
+
+    fn from_figment(figment: Figment, site_ids: &[&str]) -> Result<Self, ConfigurationError> {
+        let options = figment.extract::<ServerConfigurationOptions>().map_err(Box::new)?;
+        let unknown_site = options.sites.keys()
+            .chain(options.users.iter().flatten().flat_map(|u| u.sites.keys()))
+            .find(|id| !site_ids.contains(&id.as_str()));
+        if let Some(site_id) = unknown_site {
+            return Err(ConfigurationError::UnknownSite(site_id.clone()));
+        }
+        Ok(options.into_actual())
+    }
+
+    /// Settings of the site, a site without settings is neither scheduled nor notifies.
+    pub fn site_settings(&self, site_id: &str) -> SiteSettings {
+        self.sites.get(site_id).cloned().unwrap_or_default()
     }
 }
 
@@ -68,19 +120,31 @@ impl ServerConfiguration {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ServerConfigurationOptions {
     pub dbpath: Option<PathBuf>,
+    pub imagedir: Option<PathBuf>,
     #[serde_as(as = "DisplayFromStr")]
     #[serde(default = "default_log_level")]
     pub loglevel: LevelFilter,
-    pub melonbooks: SiteSettingsOptions,
-    pub amiami: SiteSettingsOptions,
     pub opensslconfig: Option<PathBuf>,
     pub http: Option<HttpSettingsOptions>,
+    pub users: Option<Vec<UserSettingsOptions>>,
+    pub defaultuser: Option<String>,
+    /// Every other key is the id of a site, e.g. `melonbooks`.
+    #[serde(flatten)]
+    pub sites: HashMap<String, SiteSettingsOptions>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SiteSettingsOptions {
     schedule: Option<String>,
     discord: Option<DiscordSettingsOptions>,
+    suppressduplicates: Option<bool>,
+    adaptive: Option<AdaptiveSettingsOptions>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AdaptiveSettingsOptions {
+    pub mininterval: Option<u32>,
+    pub maxinterval: Option<u32>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -95,17 +159,63 @@ pub struct DiscordSettingsOptions {
 pub struct HttpSettingsOptions {
     pub port: Option<u16>,
     pub assetsdir: Option<PathBuf>,
+    pub auth: Option<AuthSettingsOptions>,
+    pub publicurl: Option<String>,
+}
+
+/// `username`, `passwordhash` and `apitokens` are the single user from before `users` existed.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuthSettingsOptions {
+    pub username: Option<String>,
+    pub passwordhash: Option<String>,
+    pub apitokens: Option<Vec<String>>,
+    pub sessionttl: Option<u32>,
+    pub securecookie: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserSettingsOptions {
+    pub username: String,
+    pub passwordhash: Option<String>,
+    pub apitokens: Option<Vec<String>>,
+    #[serde(flatten)]
+    pub sites: HashMap<String, UserSiteSettingsOptions>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserSiteSettingsOptions {
+    discord: Option<DiscordSettingsOptions>,
 }
 
 impl ServerConfigurationOptions {
-    fn into_actual(self) -> ServerConfiguration {
+    fn into_actual(mut self) -> ServerConfiguration {
+        let mut users = self.users.take().unwrap_or_default()
+            .into_iter()
+            .map(|u| u.into_actual())
+            .collect::<Vec<_>>();
+        let legacy_user = self.http.as_mut()
+            .and_then(|h| h.auth.as_mut())
+            .and_then(|a| a.take_legacy_user());
+        let default_user = self.defaultuser
+            .or_else(|| legacy_user.as_ref().map(|u| u.username.clone()))
+            .unwrap_or_else(|| DEFAULT_USERNAME.to_owned());
+        if let Some(legacy_user) = legacy_user.filter(|l| users.iter().all(|u| u.username != l.username)) {
+            users.push(legacy_user);
+        }
+        let db_path = self.dbpath.unwrap_or_else(|| PathBuf::from("/data/moe-scraper.sqlite"));
+        let image_dir = self.imagedir.unwrap_or_else(|| db_path.with_file_name("images"));
         ServerConfiguration {
-            db_path: self.dbpath.unwrap_or_else(|| PathBuf::from("/data/moe-scraper.sqlite")),
+            db_path,
+            image_dir,
             log_level: self.loglevel,
-            melonbooks: self.melonbooks.into_actual(&Site::Melonbooks),
-            amiami: self.amiami.into_actual(&Site::Amiami),
+            sites: self.sites.into_iter().map(|(id, s)| {
+                let settings = s.into_actual(&id);
+                (id, settings)
+            }).collect(),
             openssl_config: self.opensslconfig,
             http_settings: self.http.map(|h| h.into_actual()).unwrap_or_else(|| HttpSettings::default()),
+            users,
+            default_user,
         }
     }
 }
@@ -115,37 +225,133 @@ fn default_log_level() -> LevelFilter {
 }
 
 impl SiteSettingsOptions {
-    pub fn into_actual(self, site: &Site) -> SiteSettings {
+    pub fn into_actual(self, site_id: &str) -> SiteSettings {
         SiteSettings {
             schedule: self.schedule,
-            discord_settings: self.discord.map(|ds| ds.into_actual(site))
+            discord_settings: self.discord.map(|ds| ds.into_actual(site_id)),
+            suppress_duplicates: self.suppressduplicates.unwrap_or(false),
+            adaptive: self.adaptive.map(|a| a.into_actual()),
+        }
+    }
+}
+
+impl AdaptiveSettingsOptions {
+    fn into_actual(self) -> AdaptiveSettings {
+        AdaptiveSettings {
+            min_interval_minutes: self.mininterval.unwrap_or(60),
+            max_interval_minutes: self.maxinterval.unwrap_or(1440),
         }
     }
 }
 
 impl DiscordSettingsOptions {
-    fn into_actual(self, site: &Site) -> DiscordSettings {
+    fn into_actual(self, site_id: &str) -> DiscordSettings {
         DiscordSettings {
             api_key: self.apikey.into(),
             image_url: self.imageurl,
-            username: self.username.unwrap_or_else(|| site.to_string() + "-Scraper"),
+            username: self.username.unwrap_or_else(|| default_discord_username(site_id)),
             chunk_size: self.chunksize.unwrap_or(10)
         }
     }
 }
 
+/// `melonbooks` posts as `Melonbooks-Scraper`.
+fn default_discord_username(site_id: &str) -> String {
+    let mut chars = site_id.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect::<String>() + "-Scraper",
+        None => "Scraper".to_owned(),
+    }
+}
+
 impl HttpSettingsOptions {
     fn into_actual(self) -> HttpSettings {
         HttpSettings {
             port: self.port.unwrap_or(80),
-            assets_dir: self.assetsdir
+            assets_dir: self.assetsdir,
+            auth: self.auth.map(|a| a.into_actual()),
+            public_url: self.publicurl.map(|u| u.trim_end_matches('/').to_owned()),
+        }
+    }
+}
+
+impl AuthSettingsOptions {
+    fn take_legacy_user(&mut self) -> Option<UserSettings> {
+        let username = self.username.take()?;
+        Some(UserSettings {
+            username,
+            password_hash: self.passwordhash.take().map(|h| h.into()),
+            api_tokens: self.apitokens.take().unwrap_or_default().into(),
+            discord_settings: HashMap::new(),
+        })
+    }
+
+    fn into_actual(self) -> AuthSettings {
+        AuthSettings {
+            session_ttl_hours: self.sessionttl.unwrap_or(168),
+            secure_cookie: self.securecookie.unwrap_or(false),
+        }
+    }
+}
+
+impl UserSettingsOptions {
+    fn into_actual(self) -> UserSettings {
+        UserSettings {
+            discord_settings: self.sites.into_iter()
+                .filter_map(|(id, s)| {
+                    let discord = s.discord?.into_actual(&id);
+                    Some((id, discord))
+                })
+                .collect(),
+            username: self.username,
+            password_hash: self.passwordhash.map(|h| h.into()),
+            api_tokens: self.apitokens.unwrap_or_default().into(),
         }
     }
 }
+#[cfg(test)]
+mod test {
+    use super::*;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Display, EnumString)]
-#[serde(rename_all = "snake_case")]
-pub enum Site {
-    Melonbooks,
-    Amiami,
-} 
\ No newline at end of file
+    const SITE_IDS: [&str; 2] = ["melonbooks", "amiami"];
+
+    #[test]
+    fn test_load_site_settings() {
+        let figment = Figment::from(Yaml::string("
+melonbooks:
+  schedule: 0 0 * * * *
+users:
+  - username: user
+    amiami:
+      discord:
+        apikey: key
+"));
+        let config = ServerConfiguration::from_figment(figment, &SITE_IDS).unwrap();
+        assert_eq!(config.site_settings("melonbooks").schedule.as_deref(), Some("0 0 * * * *"));
+        assert!(config.site_settings("amiami").schedule.is_none());
+        assert_eq!(config.users[0].discord_settings["amiami"].username, "Amiami-Scraper");
+    }
+
+    #[test]
+    fn test_reject_unknown_site() {
+        let figment = Figment::from(Yaml::string("
+melonboks:
+  schedule: 0 0 * * * *
+"));
+        let result = ServerConfiguration::from_figment(figment, &SITE_IDS);
+        assert!(matches!(result, Err(ConfigurationError::UnknownSite(id)) if id == "melonboks"));
+    }
+
+    #[test]
+    fn test_reject_unknown_user_site() {
+        let figment = Figment::from(Yaml::string("
+users:
+  - username: user
+    amiamii:
+      discord:
+        apikey: key
+"));
+        let result = ServerConfiguration::from_figment(figment, &SITE_IDS);
+        assert!(matches!(result, Err(ConfigurationError::UnknownSite(id)) if id == "amiamii"));
+    }
+}
@@ -1,3 +1,7 @@
+use crate::domain::site::Site;
+
 pub mod ports;
 pub mod models;
-pub mod service;
\ No newline at end of file
+pub mod service;
+
+pub const SITE: Site = Site::new("amiami", "AmiAmi", "https://www.amiami.com/eng/");
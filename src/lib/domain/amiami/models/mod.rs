@@ -1,2 +1,3 @@
-pub mod availability;
 pub mod product;
+pub mod query;
+pub mod release;
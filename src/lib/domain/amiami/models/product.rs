@@ -1,9 +1,16 @@
-use crate::domain::amiami::models::availability::Availability;
+use crate::domain::availability::Availability;
+use crate::domain::product_history;
+use crate::domain::product_history::AddNotificationsError;
+use crate::domain::schedule::{Schedule, SetDateScrapedError};
+use crate::domain::amiami::SITE;
+use crate::domain::image::models::image::CachedImage;
+use crate::domain::site::{Site, SiteProduct};
+use crate::domain::user::models::user::User;
 use crate::outbound::amiami_scraper::parser::ParseError;
 use chrono::{DateTime, NaiveDate, Utc};
 use thiserror::Error;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Product {
     id: i32,
     date_added: DateTime<Utc>,
@@ -16,11 +23,29 @@ pub struct Product {
     min_price: i32,
     release_date: NaiveDate,
     availability: Availability,
+    date_restocked: Option<DateTime<Utc>>,
+    image_hash: Option<String>,
+    image_phash: Option<u64>,
 }
 
 impl Product {
     pub fn new(id: i32, date_added: DateTime<Utc>, url: String, title: String, image_url: String, category: String, maker: String, full_price: i32, min_price: i32, release_date: NaiveDate, availability: Availability) -> Self {
-        Self { id, date_added, url, title, image_url, category, maker, full_price, min_price, release_date, availability }
+        Self { id, date_added, url, title, image_url, category, maker, full_price, min_price, release_date, availability, date_restocked: None, image_hash: None, image_phash: None }
+    }
+
+    pub fn with_date_restocked(mut self, date_restocked: Option<DateTime<Utc>>) -> Self {
+        self.date_restocked = date_restocked;
+        self
+    }
+
+    pub fn with_image_hash(mut self, image_hash: Option<String>) -> Self {
+        self.image_hash = image_hash;
+        self
+    }
+
+    pub fn with_image_phash(mut self, image_phash: Option<u64>) -> Self {
+        self.image_phash = image_phash;
+        self
     }
 
     pub fn id(&self) -> i32 { self.id }
@@ -34,14 +59,62 @@ impl Product {
     pub fn min_price(&self) -> i32 { self.min_price }
     pub fn release_date(&self) -> NaiveDate { self.release_date }
     pub fn availability(&self) -> Availability { self.availability.clone() }
+    pub fn date_restocked(&self) -> Option<DateTime<Utc>> { self.date_restocked }
+    pub fn image_hash(&self) -> Option<&str> { self.image_hash.as_deref() }
+    pub fn image_phash(&self) -> Option<u64> { self.image_phash }
+
+    /// When the product was added or, if it was restocked since, restocked.
+    pub fn date_changed(&self) -> DateTime<Utc> {
+        self.date_restocked.unwrap_or(self.date_added)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Price {
+    full_price: i32,
+    min_price: i32,
 }
 
+impl Price {
+    pub fn new(full_price: i32, min_price: i32) -> Self {
+        Self { full_price, min_price }
+    }
+
+    pub fn full_price(&self) -> i32 { self.full_price }
+    pub fn min_price(&self) -> i32 { self.min_price }
+}
+
+pub type ProductHistoryEntry = product_history::ProductHistoryEntry<Availability, Price>;
+
 impl AsRef<Product> for Product {
     fn as_ref(&self) -> &Self {
         self
     }
 }
 
+impl SiteProduct for Product {
+    const SITE: Site = SITE;
+
+    fn id(&self) -> i32 { self.id }
+    fn title(&self) -> &str { &self.title }
+    fn url(&self) -> &str { &self.url }
+    fn image_url(&self) -> &str { &self.image_url }
+    fn image_hash(&self) -> Option<&str> { self.image_hash.as_deref() }
+    fn image_phash(&self) -> Option<u64> { self.image_phash }
+
+    fn with_image(self, image: &CachedImage) -> Self {
+        self.with_image_hash(Some(image.hash().to_owned())).with_image_phash(image.perceptual_hash())
+    }
+
+    fn summary(&self) -> String {
+        format!("{} — {}\n¥{}", self.release_date.format("%Y %B"), self.maker, self.min_price)
+    }
+
+    fn notification_target(category: &str) -> String {
+        format!("Category {}", category)
+    }
+}
+
 #[derive(Debug)]
 pub struct ProductData {
     url: String,
@@ -71,7 +144,7 @@ impl ProductData {
     pub fn availability(&self) -> Availability { self.availability.to_owned() }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CreateProductArgs {
     url: String,
     title: String,
@@ -110,7 +183,7 @@ impl AsRef<CreateProductArgs> for CreateProductArgs {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct UpdateProductArgs {
     url: String,
     full_price: i32,
@@ -157,6 +230,35 @@ pub enum UpdateProductError {
     Unknown(#[from] anyhow::Error),
 }
 
+/// Category followed by at least one user, scraped once for all of its followers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FollowedCategory {
+    id: i32,
+    category: String,
+    schedule: Option<Schedule>,
+    followers: Vec<User>,
+    date_scraped: Option<DateTime<Utc>>,
+}
+
+impl FollowedCategory {
+    pub fn new(id: i32, category: String, schedule: Option<Schedule>, followers: Vec<User>) -> Self {
+        Self { id, category, schedule, followers, date_scraped: None }
+    }
+
+    pub fn with_date_scraped(mut self, date_scraped: Option<DateTime<Utc>>) -> Self {
+        self.date_scraped = date_scraped;
+        self
+    }
+
+    pub fn id(&self) -> i32 { self.id }
+    pub fn category(&self) -> &str { &self.category }
+    /// Overrides the schedule of the site for this category.
+    pub fn schedule(&self) -> Option<&Schedule> { self.schedule.as_ref() }
+    pub fn followers(&self) -> &[User] { &self.followers }
+    /// Start of the last scrape of the category.
+    pub fn date_scraped(&self) -> Option<DateTime<Utc>> { self.date_scraped }
+}
+
 #[derive(Debug, Error)]
 pub enum GetProductsError {
     #[error(transparent)]
@@ -169,6 +271,48 @@ pub enum GetCategoriesError {
     Unknown(#[from] anyhow::Error),
 }
 
+#[derive(Debug, Error)]
+pub enum FollowCategoryError {
+    #[error("category '{category}' already followed")]
+    AlreadyFollowed { category: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum UnfollowCategoryError {
+    #[error("unknown category '{category}'")]
+    UnknownCategory { category: String },
+    #[error("category '{category}' not followed")]
+    CategoryNotFollowed { category: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum SetCategoryScheduleError {
+    #[error("unknown category '{category}'")]
+    UnknownCategory { category: String },
+    #[error("category '{category}' not followed")]
+    CategoryNotFollowed { category: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum GetMakersError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum GetAvailabilityStatsError {
+    #[error(transparent)]
+    GetProductsError(#[from] GetProductsError),
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
 #[derive(Debug, Error)]
 pub enum ScrapeProductsError {
     #[error(transparent)]
@@ -182,5 +326,9 @@ pub enum ScrapeProductsError {
     #[error(transparent)]
     GetCategoriesError(#[from] GetCategoriesError),
     #[error(transparent)]
+    AddNotificationsError(#[from] AddNotificationsError),
+    #[error(transparent)]
+    SetDateScrapedError(#[from] SetDateScrapedError),
+    #[error(transparent)]
     Unknown(#[from] anyhow::Error),
 }
\ No newline at end of file
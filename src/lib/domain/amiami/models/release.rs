@@ -0,0 +1,20 @@
+use chrono::NaiveDate;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReleaseFilter {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    category: Option<String>,
+    maker: Option<String>,
+}
+
+impl ReleaseFilter {
+    pub fn new(from: Option<NaiveDate>, to: Option<NaiveDate>, category: Option<String>, maker: Option<String>) -> Self {
+        Self { from, to, category, maker }
+    }
+
+    pub fn from(&self) -> Option<NaiveDate> { self.from }
+    pub fn to(&self) -> Option<NaiveDate> { self.to }
+    pub fn category(&self) -> Option<&str> { self.category.as_deref() }
+    pub fn maker(&self) -> Option<&str> { self.maker.as_deref() }
+}
@@ -1,24 +1,70 @@
-use crate::domain::amiami::models::product::{CreateProductArgs, CreateProductError, GetCategoriesError, GetProductsError, Product, ProductData, ScrapeProductsError, UpdateProductArgs, UpdateProductError};
+use crate::domain::amiami::models::product::{CreateProductArgs, CreateProductError, FollowCategoryError, FollowedCategory, GetAvailabilityStatsError, GetCategoriesError, GetMakersError, GetProductsError, Product, ProductData, ProductHistoryEntry, ScrapeProductsError, SetCategoryScheduleError, UnfollowCategoryError, UpdateProductArgs, UpdateProductError};
+use crate::domain::amiami::models::query::ProductQuery;
+use crate::domain::amiami::models::release::ReleaseFilter;
+use crate::domain::availability_stats::{AvailabilityEvent, AvailabilityStats};
+use crate::domain::pagination::{Page, PageRequest};
+use crate::domain::product_history::GetProductError;
+use crate::domain::schedule::{GetTargetSchedulesError, Schedule, SetDateScrapedError};
+use crate::domain::scrape_event::ScrapeEvent;
+use crate::domain::site::SiteService;
+use crate::domain::search::{SearchProductsError, SearchResult};
+use crate::domain::user::models::user::User;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use tokio::sync::broadcast;
 
 #[async_trait]
-pub trait AmiamiService: Send + Sync + 'static {
+pub trait AmiamiService: SiteService {
     async fn get_products(&self) -> Result<Vec<Product>, GetProductsError>;
-    async fn scrape_available_products(&self) -> Result<(), ScrapeProductsError>;
+    async fn get_products_page(&self, query: &ProductQuery) -> Result<Page<Product>, GetProductsError>;
+    async fn get_product(&self, product_id: i32) -> Result<Product, GetProductError>;
+    async fn get_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError>;
+    async fn get_availability_stats(&self) -> Result<AvailabilityStats, GetAvailabilityStatsError>;
+    async fn search_products(&self, query: &str) -> Result<Vec<SearchResult<Product>>, SearchProductsError>;
+    async fn search_products_page(&self, query: &str, page: PageRequest) -> Result<Page<SearchResult<Product>>, SearchProductsError>;
+    async fn get_releases(&self, filter: &ReleaseFilter) -> Result<Vec<Product>, GetProductsError>;
+    async fn get_releases_page(&self, filter: &ReleaseFilter, page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    async fn get_categories(&self) -> Result<Vec<String>, GetCategoriesError>;
+    async fn get_categories_page(&self, page: PageRequest) -> Result<Page<String>, GetCategoriesError>;
+    async fn get_followed_categories(&self, user: &User) -> Result<Vec<String>, GetCategoriesError>;
+    async fn get_followed_categories_page(&self, user: &User, page: PageRequest) -> Result<Page<String>, GetCategoriesError>;
+    async fn follow_category(&self, user: &User, category: &str) -> Result<(), FollowCategoryError>;
+    async fn unfollow_category(&self, user: &User, category: &str) -> Result<(), UnfollowCategoryError>;
+    /// Chooses `schedule` instead of the schedule of the site for the category, `None` goes back to the site's.
+    /// The category is scraped on the choice of most of its followers.
+    async fn set_category_schedule(&self, user: &User, category: &str, schedule: Option<&Schedule>) -> Result<(), SetCategoryScheduleError>;
+    async fn get_makers(&self) -> Result<Vec<String>, GetMakersError>;
+    async fn get_makers_page(&self, page: PageRequest) -> Result<Page<String>, GetMakersError>;
+    fn subscribe_scrape_events(&self) -> broadcast::Receiver<ScrapeEvent<Product>>;
 }
 
 #[async_trait]
 pub trait AmiamiRepository: Clone + Send + Sync + 'static {
     async fn create_amiami_product(&self, req: &CreateProductArgs) -> Result<Product, CreateProductError>;
     async fn update_amiami_product(&self, req: &UpdateProductArgs, ) -> Result<Product, UpdateProductError>;
+    async fn get_amiami_product(&self, product_id: i32) -> Result<Product, GetProductError>;
+    async fn get_amiami_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError>;
+    async fn get_amiami_availability_events(&self) -> Result<Vec<AvailabilityEvent>, GetAvailabilityStatsError>;
     async fn get_amiami_products(&self) -> Result<Vec<Product>, GetProductsError>;
-    async fn get_following_amiami_categories(&self) -> Result<Vec<String>, GetCategoriesError>;
-}
-
-#[async_trait]
-pub trait AmiamiNotifier: Clone + Send + Sync + 'static {
-    async fn new_products<P: AsRef<Product> + Sync>(&self, category: &str, products: &[P]);
-    async fn restocked_products<P: AsRef<Product> + Sync>(&self, category: &str, products: &[P]);
+    async fn get_amiami_products_page(&self, query: &ProductQuery) -> Result<Page<Product>, GetProductsError>;
+    async fn search_amiami_products(&self, query: &str) -> Result<Vec<SearchResult<Product>>, SearchProductsError>;
+    async fn search_amiami_products_page(&self, query: &str, page: PageRequest) -> Result<Page<SearchResult<Product>>, SearchProductsError>;
+    async fn get_amiami_products_by_release(&self, filter: &ReleaseFilter) -> Result<Vec<Product>, GetProductsError>;
+    async fn get_amiami_products_page_by_release(&self, filter: &ReleaseFilter, page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    async fn get_amiami_categories(&self) -> Result<Vec<String>, GetCategoriesError>;
+    async fn get_amiami_categories_page(&self, page: PageRequest) -> Result<Page<String>, GetCategoriesError>;
+    async fn get_following_amiami_categories(&self, user_id: i32) -> Result<Vec<String>, GetCategoriesError>;
+    async fn get_following_amiami_categories_page(&self, user_id: i32, page: PageRequest) -> Result<Page<String>, GetCategoriesError>;
+    async fn get_followed_amiami_categories(&self) -> Result<Vec<FollowedCategory>, GetCategoriesError>;
+    async fn follow_amiami_category(&self, user_id: i32, category: &str) -> Result<(), FollowCategoryError>;
+    async fn unfollow_amiami_category(&self, user_id: i32, category: &str) -> Result<(), UnfollowCategoryError>;
+    async fn set_amiami_category_schedule(&self, user_id: i32, category: &str, schedule: Option<&Schedule>) -> Result<(), SetCategoryScheduleError>;
+    async fn set_amiami_category_scraped(&self, category_id: i32, date_scraped: DateTime<Utc>) -> Result<(), SetDateScrapedError>;
+    /// Products added after the first scrape of their category and restocks since `since` by the id of the category.
+    async fn get_amiami_category_activity(&self, since: DateTime<Utc>) -> Result<HashMap<i32, usize>, GetTargetSchedulesError>;
+    async fn get_amiami_makers(&self) -> Result<Vec<String>, GetMakersError>;
+    async fn get_amiami_makers_page(&self, page: PageRequest) -> Result<Page<String>, GetMakersError>;
 }
 
 #[async_trait]
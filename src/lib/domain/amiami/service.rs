@@ -1,50 +1,291 @@
-use crate::domain::amiami::models::product::{CreateProductArgs, GetProductsError, Product, ScrapeProductsError, UpdateProductArgs};
-use crate::domain::amiami::ports::{AmiamiNotifier, AmiamiRepository, AmiamiScraper, AmiamiService};
+use crate::domain::amiami::models::product::{CreateProductArgs, FollowCategoryError, FollowedCategory, GetAvailabilityStatsError, GetCategoriesError, GetMakersError, GetProductsError, Product, ProductHistoryEntry, ScrapeProductsError, SetCategoryScheduleError, UnfollowCategoryError, UpdateProductArgs};
+use crate::domain::amiami::models::query::ProductQuery;
+use crate::domain::amiami::models::release::ReleaseFilter;
+use crate::domain::availability_stats::{AvailabilityStats, StatsProduct};
+use crate::domain::amiami::SITE;
+use crate::domain::image::ports::ImageCache;
+use crate::domain::pagination::{Page, PageRequest};
+use crate::domain::product_history::GetProductError;
+use crate::domain::product_index::models::target::TargetId;
+use crate::domain::schedule::{AdaptiveInterval, AdaptiveTarget, GetTargetSchedulesError, Schedule, ScheduleChanges, TargetSchedule};
+use crate::domain::scrape_event::{ScrapeEvent, ScrapeEvents};
+use crate::domain::amiami::ports::{AmiamiRepository, AmiamiScraper, AmiamiService};
+use crate::domain::site::{ScrapeGuard, ScrapeLock, ScrapeSiteError, Site, SiteCore, SiteNotifier, SiteRepository, SiteService};
+use crate::domain::search::{SearchProductsError, SearchResult};
+use crate::domain::user::models::user::User;
 use log::info;
 use std::collections::BTreeSet;
 use async_trait::async_trait;
+use chrono::Utc;
+use tokio::sync::broadcast;
 
 #[derive(Debug, Clone)]
-pub struct AmiamiServiceImpl<R, N, S>
+pub struct AmiamiServiceImpl<R, N, S, I>
 where
-    R: AmiamiRepository,
-    N: AmiamiNotifier,
-    S: AmiamiScraper
+    R: AmiamiRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: AmiamiScraper,
+    I: ImageCache
 {
     repo: R,
-    notifier: N,
     scraper: S,
+    core: SiteCore<N, I>,
+    scrape_events: ScrapeEvents<Product>,
+    schedule_changes: ScheduleChanges,
+    /// Scrapes of the site and of single categories on their own schedule run one after another.
+    adaptive_interval: Option<AdaptiveInterval>,
 }
 
-impl<R, N, S> AmiamiServiceImpl<R, N, S>
+impl<R, N, S, I> AmiamiServiceImpl<R, N, S, I>
 where
-    R: AmiamiRepository,
-    N: AmiamiNotifier,
-    S: AmiamiScraper
+    R: AmiamiRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: AmiamiScraper,
+    I: ImageCache
 {
-    pub fn new(repo: R, notifier: N, scraper: S) -> Self {
-        Self { repo, notifier, scraper }
+    pub fn new(repo: R, scraper: S, core: SiteCore<N, I>) -> Self {
+        Self { repo, scraper, core, scrape_events: ScrapeEvents::new(), schedule_changes: ScheduleChanges::new(), adaptive_interval: None }
+    }
+
+    /// Scrapes the categories without their own schedule more often the more new and restocked products they had.
+    pub fn with_adaptive_interval(mut self, adaptive_interval: Option<AdaptiveInterval>) -> Self {
+        self.adaptive_interval = adaptive_interval;
+        self
     }
 }
 
 #[async_trait]
-impl<R, N, S> AmiamiService for AmiamiServiceImpl<R, N, S>
+impl<R, N, S, I> SiteService for AmiamiServiceImpl<R, N, S, I>
 where
-    R: AmiamiRepository,
-    N: AmiamiNotifier,
-    S: AmiamiScraper
+    R: AmiamiRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: AmiamiScraper,
+    I: ImageCache
+{
+    fn site(&self) -> Site {
+        SITE
+    }
+
+    fn scrape_lock(&self) -> &ScrapeLock {
+        self.core.scrape_lock()
+    }
+
+    async fn scrape(&self, _guard: &ScrapeGuard) -> Result<(), ScrapeSiteError> {
+        info!("scrape available products of categories without schedule");
+        let adaptive = self.adaptive_interval.is_some();
+        self.scrape_categories(|c| c.schedule().is_none() && !adaptive).await
+            .map_err(|e| anyhow::Error::new(e).into())
+    }
+
+    async fn scrape_all(&self, _guard: &ScrapeGuard) -> Result<(), ScrapeSiteError> {
+        info!("scrape available products");
+        self.scrape_categories(|_| true).await
+            .map_err(|e| anyhow::Error::new(e).into())
+    }
+
+    async fn get_target_schedules(&self) -> Result<Vec<TargetSchedule>, GetTargetSchedulesError> {
+        let categories = self.repo.get_followed_amiami_categories().await
+            .map_err(anyhow::Error::new)?;
+        Ok(
+            categories.into_iter()
+                .filter_map(|c| {
+                    let schedule = c.schedule()?.clone();
+                    Some(TargetSchedule::new(TargetId::new(SITE, c.id()), c.category().to_owned(), schedule))
+                })
+                .collect()
+        )
+    }
+
+    async fn scrape_target(&self, _guard: &ScrapeGuard, category_id: i32) -> Result<(), ScrapeSiteError> {
+        info!("scrape available products of category with id '{}'", category_id);
+        self.scrape_categories(|c| c.id() == category_id).await
+            .map_err(|e| anyhow::Error::new(e).into())
+    }
+
+    fn subscribe_schedule_changes(&self) -> Option<broadcast::Receiver<()>> {
+        Some(self.schedule_changes.subscribe())
+    }
+
+    fn adaptive_interval(&self) -> Option<AdaptiveInterval> {
+        self.adaptive_interval
+    }
+
+    async fn get_adaptive_targets(&self) -> Result<Vec<AdaptiveTarget>, GetTargetSchedulesError> {
+        let Some(adaptive_interval) = self.adaptive_interval else {
+            return Ok(Vec::new());
+        };
+        let categories = self.repo.get_followed_amiami_categories().await
+            .map_err(anyhow::Error::new)?;
+        let activity = self.repo.get_amiami_category_activity(AdaptiveInterval::activity_since(Utc::now())).await?;
+        Ok(
+            categories.into_iter()
+                .filter(|c| c.schedule().is_none())
+                .map(|c| {
+                    let activity = activity.get(&c.id()).copied().unwrap_or_default();
+                    AdaptiveTarget::new(TargetId::new(SITE, c.id()), c.category().to_owned(), activity, c.date_scraped(), &adaptive_interval)
+                })
+                .collect()
+        )
+    }
+
+    async fn scrape_due_targets(&self, _guard: &ScrapeGuard) -> Result<(), ScrapeSiteError> {
+        let now = Utc::now();
+        let due = self.get_adaptive_targets().await
+            .map_err(anyhow::Error::new)?
+            .into_iter()
+            .filter(|t| t.is_due(now))
+            .map(|t| t.target_id().id())
+            .collect::<BTreeSet<_>>();
+        if due.is_empty() {
+            return Ok(());
+        }
+        info!("scrape available products of '{}' categories due by their activity", due.len());
+        self.scrape_categories(|c| due.contains(&c.id())).await
+            .map_err(|e| anyhow::Error::new(e).into())
+    }
+}
+
+#[async_trait]
+impl<R, N, S, I> AmiamiService for AmiamiServiceImpl<R, N, S, I>
+where
+    R: AmiamiRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: AmiamiScraper,
+    I: ImageCache
 {
     async fn get_products(&self) -> Result<Vec<Product>, GetProductsError> {
         info!("get products");
         self.repo.get_amiami_products().await
     }
 
-    async fn scrape_available_products(&self) -> Result<(), ScrapeProductsError> {
-        info!("scrape available products");
+    async fn get_products_page(&self, query: &ProductQuery) -> Result<Page<Product>, GetProductsError> {
+        info!("get products page for {:?}", query);
+        self.repo.get_amiami_products_page(query).await
+    }
 
+    async fn get_product(&self, product_id: i32) -> Result<Product, GetProductError> {
+        info!("get product with id '{}'", product_id);
+        self.repo.get_amiami_product(product_id).await
+    }
+
+    async fn get_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError> {
+        info!("get history of product with id '{}'", product_id);
+        self.repo.get_amiami_product_history(product_id).await
+    }
+
+    async fn get_availability_stats(&self) -> Result<AvailabilityStats, GetAvailabilityStatsError> {
+        info!("get availability stats");
+        let products = self.repo.get_amiami_products().await?
+            .into_iter()
+            .map(|p| StatsProduct::new(p.id(), p.title().to_owned(), p.date_added(), vec![p.maker().to_owned()]))
+            .collect::<Vec<_>>();
+        let events = self.repo.get_amiami_availability_events().await?;
+        Ok(AvailabilityStats::new(&products, &events, Utc::now()))
+    }
+
+    async fn search_products(&self, query: &str) -> Result<Vec<SearchResult<Product>>, SearchProductsError> {
+        info!("search products for '{}'", query);
+        self.repo.search_amiami_products(query).await
+    }
+
+    async fn search_products_page(&self, query: &str, page: PageRequest) -> Result<Page<SearchResult<Product>>, SearchProductsError> {
+        info!("search page {} of products for '{}'", page.page(), query);
+        self.repo.search_amiami_products_page(query, page).await
+    }
+
+    async fn get_releases(&self, filter: &ReleaseFilter) -> Result<Vec<Product>, GetProductsError> {
+        info!("get releases for {:?}", filter);
+        self.repo.get_amiami_products_by_release(filter).await
+    }
+
+    async fn get_releases_page(&self, filter: &ReleaseFilter, page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        info!("get page {} of releases for {:?}", page.page(), filter);
+        self.repo.get_amiami_products_page_by_release(filter, page).await
+    }
+
+    async fn get_categories(&self) -> Result<Vec<String>, GetCategoriesError> {
+        info!("get categories");
+        self.repo.get_amiami_categories().await
+    }
+
+    async fn get_categories_page(&self, page: PageRequest) -> Result<Page<String>, GetCategoriesError> {
+        info!("get page {} of categories", page.page());
+        self.repo.get_amiami_categories_page(page).await
+    }
+
+    async fn get_followed_categories(&self, user: &User) -> Result<Vec<String>, GetCategoriesError> {
+        info!("get followed categories for '{}'", user.username());
+        self.repo.get_following_amiami_categories(user.id()).await
+    }
+
+    async fn get_followed_categories_page(&self, user: &User, page: PageRequest) -> Result<Page<String>, GetCategoriesError> {
+        info!("get page {} of followed categories for '{}'", page.page(), user.username());
+        self.repo.get_following_amiami_categories_page(user.id(), page).await
+    }
+
+    async fn follow_category(&self, user: &User, category: &str) -> Result<(), FollowCategoryError> {
+        info!("follow category '{}' for '{}'", category, user.username());
+        self.repo.follow_amiami_category(user.id(), category).await?;
+        self.schedule_changes.publish();
+        Ok(())
+    }
+
+    async fn unfollow_category(&self, user: &User, category: &str) -> Result<(), UnfollowCategoryError> {
+        info!("unfollow category '{}' for '{}'", category, user.username());
+        self.repo.unfollow_amiami_category(user.id(), category).await?;
+        self.schedule_changes.publish();
+        Ok(())
+    }
+
+    async fn set_category_schedule(&self, user: &User, category: &str, schedule: Option<&Schedule>) -> Result<(), SetCategoryScheduleError> {
+        info!("set schedule of category '{}' to {:?} for '{}'", category, schedule.map(|s| s.expression()), user.username());
+        self.repo.set_amiami_category_schedule(user.id(), category, schedule).await?;
+        self.schedule_changes.publish();
+        Ok(())
+    }
+
+    async fn get_makers(&self) -> Result<Vec<String>, GetMakersError> {
+        info!("get makers");
+        self.repo.get_amiami_makers().await
+    }
+
+    async fn get_makers_page(&self, page: PageRequest) -> Result<Page<String>, GetMakersError> {
+        info!("get page {} of makers", page.page());
+        self.repo.get_amiami_makers_page(page).await
+    }
+
+    fn subscribe_scrape_events(&self) -> broadcast::Receiver<ScrapeEvent<Product>> {
+        self.scrape_events.subscribe()
+    }
+}
+
+impl<R, N, S, I> AmiamiServiceImpl<R, N, S, I>
+where
+    R: AmiamiRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: AmiamiScraper,
+    I: ImageCache
+{
+    /// Scrapes the followed categories matching `filter`, the caller holds the scrape lock.
+    async fn scrape_categories(&self, filter: impl Fn(&FollowedCategory) -> bool + Send + Sync) -> Result<(), ScrapeProductsError> {
+        let result = self.scrape_followed_categories(filter).await;
+        self.scrape_events.publish(ScrapeEvent::Finished);
+        result
+    }
+
+    async fn scrape_followed_categories(&self, filter: impl Fn(&FollowedCategory) -> bool + Send + Sync) -> Result<(), ScrapeProductsError> {
         let products = self.repo.get_amiami_products().await?;
-        let categories = self.repo.get_following_amiami_categories().await?;
-        for category in categories.iter() {
+        let followed_categories = self.repo.get_followed_amiami_categories().await?
+            .into_iter()
+            .filter(|c| filter(c))
+            .collect::<Vec<_>>();
+        let targets = followed_categories.len();
+        self.scrape_events.publish(ScrapeEvent::Started { targets });
+        for (index, followed_category) in followed_categories.iter().enumerate() {
+            let category = followed_category.category();
+            let progress = |pages_fetched: usize| ScrapeEvent::Progress { target: category.to_owned(), index, targets, pages_fetched };
+            self.scrape_events.publish(progress(0));
+            let followers = followed_category.followers().iter().map(|u| u.id()).collect::<Vec<_>>();
             let (available_products, unavailable_products) = products.iter()
                 .filter(|p| p.category() == category)
                 .partition::<Vec<_>, _>(|p| p.availability().is_available());
@@ -52,6 +293,7 @@ where
             let unavailable_urls = unavailable_products.iter().map(|p| p.url()).collect::<BTreeSet<_>>();
 
             let current_product_data_list = self.scraper.get_products(category).await?;
+            self.scrape_events.publish(progress(1));
 
             let (new_product_data_list, restocked_product_data_list) = current_product_data_list.into_iter()
                 .filter(|p| !available_urls.contains(p.url()))
@@ -66,19 +308,24 @@ where
                     restocked_product_data.release_date(),
                     restocked_product_data.availability()
                 )).await?;
+                let product = self.core.cache_product_image(&self.repo, product).await;
+                self.scrape_events.publish(ScrapeEvent::RestockedProduct { product: product.clone(), followers: followers.clone() });
                 restocked_products.push(product);
             }
             info!("found '{}' restocked products for category '{}'", restocked_products.len(), category);
-            self.notifier.restocked_products(category, &restocked_products).await;
 
             let mut new_products = Vec::<Product>::new();
             for product_data in new_product_data_list.into_iter() {
                 let args = CreateProductArgs::new_from_data(product_data);
                 let product = self.repo.create_amiami_product(&args).await?;
+                let product = self.core.cache_product_image(&self.repo, product).await;
+                self.scrape_events.publish(ScrapeEvent::NewProduct { product: product.clone(), followers: followers.clone() });
                 new_products.push(product);
             }
             info!("found '{}' new products for category '{}'", new_products.len(), category);
-            self.notifier.new_products(category, &new_products).await;
+
+            self.core.notify(&self.repo, category, followed_category.followers(), &new_products, &restocked_products).await?;
+            self.repo.set_amiami_category_scraped(followed_category.id(), Utc::now()).await?;
         }
 
         Ok(())
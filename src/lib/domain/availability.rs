@@ -1,6 +1,8 @@
+use serde::{Deserialize, Serialize};
 use strum_macros::{Display, EnumIter, EnumString};
 
-#[derive(Debug, Clone, PartialEq, Eq, Display, EnumString, EnumIter)]
+/// Whether a product of any site can be bought, second-hand listings are `NotAvailable` once sold.
+#[derive(Debug, Clone, PartialEq, Eq, Display, EnumString, EnumIter, Serialize, Deserialize)]
 pub enum Availability {
     Available,
     Preorder,
@@ -10,7 +12,7 @@ pub enum Availability {
 
 impl Availability {
     pub fn is_available(&self) -> bool {
-        match self { 
+        match self {
             Availability::Available | Availability::Preorder => true,
             Availability::NotAvailable | Availability::Deleted => false,
         }
@@ -0,0 +1,245 @@
+use chrono::{DateTime, Days, NaiveDate, TimeDelta, Utc};
+use itertools::Itertools;
+use std::collections::{BTreeMap, HashMap};
+
+/// Number of days shown in the daily changes.
+pub const STATS_DAYS: u64 = 30;
+
+/// An availability change of a product, reduced to whether the product could be bought before and after.
+/// `was_available` is `None` for the availability the product was added with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AvailabilityEvent {
+    product_id: i32,
+    date: DateTime<Utc>,
+    was_available: Option<bool>,
+    available: bool,
+}
+
+impl AvailabilityEvent {
+    pub fn new(product_id: i32, date: DateTime<Utc>, was_available: Option<bool>, available: bool) -> Self {
+        Self { product_id, date, was_available, available }
+    }
+
+    pub fn product_id(&self) -> i32 { self.product_id }
+    pub fn date(&self) -> DateTime<Utc> { self.date }
+
+    pub fn is_sellout(&self) -> bool {
+        self.was_available == Some(true) && !self.available
+    }
+
+    pub fn is_restock(&self) -> bool {
+        self.was_available == Some(false) && self.available
+    }
+
+    /// The event of the product being scraped for the first time.
+    pub fn is_added(&self) -> bool {
+        self.was_available.is_none()
+    }
+}
+
+/// The product the stats are computed for, `groups` are its artists or its maker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatsProduct {
+    id: i32,
+    title: String,
+    date_added: DateTime<Utc>,
+    groups: Vec<String>,
+}
+
+impl StatsProduct {
+    pub fn new(id: i32, title: String, date_added: DateTime<Utc>, groups: Vec<String>) -> Self {
+        Self { id, title, date_added, groups }
+    }
+
+    pub fn has_groups(&self) -> bool { !self.groups.is_empty() }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProductStats {
+    product_id: i32,
+    title: String,
+    time_to_sellout: TimeDelta,
+    restocks: usize,
+}
+
+impl ProductStats {
+    pub fn product_id(&self) -> i32 { self.product_id }
+    pub fn title(&self) -> &str { &self.title }
+    /// Time from being added or restocked until the first sellout.
+    pub fn time_to_sellout(&self) -> TimeDelta { self.time_to_sellout }
+    pub fn restocks(&self) -> usize { self.restocks }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupStats {
+    name: String,
+    products: usize,
+    sellouts: usize,
+    restocks: usize,
+    restocks_per_month: f64,
+    average_time_to_sellout: Option<TimeDelta>,
+}
+
+impl GroupStats {
+    pub fn name(&self) -> &str { &self.name }
+    pub fn products(&self) -> usize { self.products }
+    pub fn sellouts(&self) -> usize { self.sellouts }
+    pub fn restocks(&self) -> usize { self.restocks }
+    /// Restocks per 30 days since the oldest product of the group was added.
+    pub fn restocks_per_month(&self) -> f64 { self.restocks_per_month }
+    pub fn average_time_to_sellout(&self) -> Option<TimeDelta> { self.average_time_to_sellout }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DayStats {
+    date: NaiveDate,
+    sellouts: usize,
+    restocks: usize,
+}
+
+impl DayStats {
+    pub fn date(&self) -> NaiveDate { self.date }
+    pub fn sellouts(&self) -> usize { self.sellouts }
+    pub fn restocks(&self) -> usize { self.restocks }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AvailabilityStats {
+    products: Vec<ProductStats>,
+    groups: Vec<GroupStats>,
+    days: Vec<DayStats>,
+}
+
+impl AvailabilityStats {
+    /// Only events of the given products are counted. Products are sorted by the fastest sellout,
+    /// groups by the most restocks, the days end with `now`.
+    pub fn new(products: &[StatsProduct], events: &[AvailabilityEvent], now: DateTime<Utc>) -> Self {
+        let events_by_product = events.iter()
+            .sorted_by_key(|e| e.date)
+            .into_group_map_by(|e| e.product_id);
+
+        let mut product_stats = Vec::new();
+        let mut time_to_sellout = HashMap::new();
+        let mut restocks = HashMap::new();
+        let mut sellouts = HashMap::new();
+        for product in products {
+            let product_events = events_by_product.get(&product.id).map(|e| e.as_slice()).unwrap_or_default();
+            let mut available_since = product.date_added;
+            let mut first_sellout = None;
+            for event in product_events {
+                if event.is_restock() {
+                    available_since = event.date;
+                }
+                if event.is_sellout() && first_sellout.is_none() {
+                    first_sellout = Some(event.date - available_since);
+                }
+            }
+            let product_restocks = product_events.iter().filter(|e| e.is_restock()).count();
+            let product_sellouts = product_events.iter().filter(|e| e.is_sellout()).count();
+            restocks.insert(product.id, product_restocks);
+            sellouts.insert(product.id, product_sellouts);
+            if let Some(first_sellout) = first_sellout {
+                time_to_sellout.insert(product.id, first_sellout);
+                product_stats.push(ProductStats {
+                    product_id: product.id,
+                    title: product.title.clone(),
+                    time_to_sellout: first_sellout,
+                    restocks: product_restocks,
+                });
+            }
+        }
+        product_stats.sort_by_key(|p| (p.time_to_sellout, p.product_id));
+
+        let mut groups = products.iter()
+            .flat_map(|p| p.groups.iter().map(move |g| (g, p)))
+            .into_group_map()
+            .into_iter()
+            .map(|(name, group_products)| {
+                let oldest = group_products.iter().map(|p| p.date_added).min().unwrap_or(now);
+                let months = ((now - oldest).num_days() as f64 / 30.0).max(1.0);
+                let group_restocks = group_products.iter().map(|p| restocks[&p.id]).sum::<usize>();
+                let times = group_products.iter().filter_map(|p| time_to_sellout.get(&p.id)).collect::<Vec<_>>();
+                let average_time_to_sellout = match times.len() {
+                    0 => None,
+                    n => Some(times.iter().copied().sum::<TimeDelta>() / n as i32),
+                };
+                GroupStats {
+                    name: name.clone(),
+                    products: group_products.len(),
+                    sellouts: group_products.iter().map(|p| sellouts[&p.id]).sum(),
+                    restocks: group_restocks,
+                    restocks_per_month: group_restocks as f64 / months,
+                    average_time_to_sellout,
+                }
+            })
+            .collect::<Vec<_>>();
+        groups.sort_by(|a, b| b.restocks.cmp(&a.restocks).then_with(|| a.name.cmp(&b.name)));
+
+        let today = now.date_naive();
+        let first_day = today - Days::new(STATS_DAYS - 1);
+        let mut days = first_day.iter_days()
+            .take_while(|d| *d <= today)
+            .map(|date| (date, DayStats { date, sellouts: 0, restocks: 0 }))
+            .collect::<BTreeMap<_, _>>();
+        for event in events.iter().filter(|e| restocks.contains_key(&e.product_id)) {
+            if let Some(day) = days.get_mut(&event.date.date_naive()) {
+                day.sellouts += event.is_sellout() as usize;
+                day.restocks += event.is_restock() as usize;
+            }
+        }
+
+        Self { products: product_stats, groups, days: days.into_values().collect() }
+    }
+
+    pub fn products(&self) -> &[ProductStats] { &self.products }
+    pub fn groups(&self) -> &[GroupStats] { &self.groups }
+    pub fn days(&self) -> &[DayStats] { &self.days }
+
+    /// Largest number of changes of a day, to scale the chart.
+    pub fn max_day_changes(&self) -> usize {
+        self.days.iter().map(|d| d.sellouts.max(d.restocks)).max().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn date(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().to_utc()
+    }
+
+    #[test]
+    fn test_availability_stats() {
+        let products = vec![
+            StatsProduct::new(1, "first".to_owned(), date("2026-10-01T00:00:00Z"), vec!["artist1".to_owned(), "artist2".to_owned()]),
+            StatsProduct::new(2, "second".to_owned(), date("2026-10-10T00:00:00Z"), vec!["artist2".to_owned()]),
+        ];
+        let events = vec![
+            AvailabilityEvent::new(1, date("2026-10-01T00:00:00Z"), None, true),
+            AvailabilityEvent::new(1, date("2026-10-03T00:00:00Z"), Some(true), false),
+            AvailabilityEvent::new(1, date("2026-10-15T12:00:00Z"), Some(false), true),
+            AvailabilityEvent::new(1, date("2026-10-16T12:00:00Z"), Some(true), false),
+            AvailabilityEvent::new(2, date("2026-10-10T00:00:00Z"), None, true),
+            AvailabilityEvent::new(2, date("2026-10-11T00:00:00Z"), Some(true), false),
+            AvailabilityEvent::new(3, date("2026-10-15T00:00:00Z"), Some(false), true),
+        ];
+        let stats = AvailabilityStats::new(&products, &events, date("2026-10-18T10:00:00Z"));
+
+        let products = stats.products().iter().map(|p| (p.product_id(), p.time_to_sellout(), p.restocks())).collect::<Vec<_>>();
+        assert_eq!(products, vec![(2, TimeDelta::days(1), 0), (1, TimeDelta::days(2), 1)]);
+
+        let groups = stats.groups().iter().map(|g| (g.name(), g.products(), g.sellouts(), g.restocks(), g.average_time_to_sellout())).collect::<Vec<_>>();
+        assert_eq!(groups, vec![
+            ("artist1", 1, 2, 1, Some(TimeDelta::days(2))),
+            ("artist2", 2, 3, 1, Some(TimeDelta::hours(36))),
+        ]);
+        assert_eq!(stats.groups()[0].restocks_per_month(), 1.0);
+
+        assert_eq!(stats.days().len(), STATS_DAYS as usize);
+        assert_eq!(stats.days().last().unwrap().date(), NaiveDate::from_ymd_opt(2026, 10, 18).unwrap());
+        let day = stats.days().iter().find(|d| d.date() == NaiveDate::from_ymd_opt(2026, 10, 15).unwrap()).unwrap();
+        assert_eq!((day.sellouts(), day.restocks()), (0, 1));
+        assert_eq!(stats.max_day_changes(), 1);
+    }
+}
@@ -0,0 +1,271 @@
+use crate::domain::availability::Availability;
+use crate::domain::booth::models::source::GetSourcesError;
+use crate::domain::booth::SITE;
+use crate::domain::product_history;
+use crate::domain::product_history::AddNotificationsError;
+use crate::domain::image::models::image::CachedImage;
+use crate::domain::site::{Site, SiteProduct};
+use crate::outbound::booth_scraper::ParseError;
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+/// A variation of an item, e.g. a download and a printed version, with its own price and stock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variation {
+    id: i32,
+    name: String,
+    price: i32,
+    availability: Availability,
+}
+
+impl Variation {
+    pub fn new(id: i32, name: String, price: i32, availability: Availability) -> Self {
+        Self { id, name, price, availability }
+    }
+
+    pub fn id(&self) -> i32 { self.id }
+    pub fn name(&self) -> &str { &self.name }
+    /// Price in yen.
+    pub fn price(&self) -> i32 { self.price }
+    pub fn availability(&self) -> Availability { self.availability.clone() }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Product {
+    id: i32,
+    date_added: DateTime<Utc>,
+    url: String,
+    title: String,
+    shop_name: String,
+    image_url: String,
+    variations: Vec<Variation>,
+    price: Option<i32>,
+    availability: Availability,
+    date_restocked: Option<DateTime<Utc>>,
+    image_hash: Option<String>,
+    image_phash: Option<u64>,
+}
+
+impl Product {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(id: i32, date_added: DateTime<Utc>, url: String, title: String, shop_name: String, image_url: String, variations: Vec<Variation>, price: Option<i32>, availability: Availability) -> Self {
+        Self { id, date_added, url, title, shop_name, image_url, variations, price, availability, date_restocked: None, image_hash: None, image_phash: None }
+    }
+
+    pub fn with_date_restocked(mut self, date_restocked: Option<DateTime<Utc>>) -> Self {
+        self.date_restocked = date_restocked;
+        self
+    }
+
+    pub fn with_image_hash(mut self, image_hash: Option<String>) -> Self {
+        self.image_hash = image_hash;
+        self
+    }
+
+    pub fn with_image_phash(mut self, image_phash: Option<u64>) -> Self {
+        self.image_phash = image_phash;
+        self
+    }
+
+    pub fn id(&self) -> i32 { self.id }
+    pub fn date_added(&self) -> DateTime<Utc> { self.date_added }
+    pub fn url(&self) -> &str { &self.url }
+    pub fn title(&self) -> &str { &self.title }
+    pub fn shop_name(&self) -> &str { &self.shop_name }
+    pub fn image_url(&self) -> &str { &self.image_url }
+    pub fn variations(&self) -> &[Variation] { &self.variations }
+    /// Lowest price of the variations on sale or, when all are sold out, of all variations.
+    pub fn price(&self) -> Option<i32> { self.price }
+    /// Available while any variation is on sale.
+    pub fn availability(&self) -> Availability { self.availability.clone() }
+    pub fn date_restocked(&self) -> Option<DateTime<Utc>> { self.date_restocked }
+    pub fn image_hash(&self) -> Option<&str> { self.image_hash.as_deref() }
+    pub fn image_phash(&self) -> Option<u64> { self.image_phash }
+
+    /// Whether the item on BOOTH differs from the stored variations.
+    pub fn has_changes(&self, item: &ItemData) -> bool {
+        self.variations.len() != item.variations().len()
+            || item.variations().iter().any(|v| !self.variations.iter().any(|pv| pv.name() == v.name() && pv.price() == v.price() && &pv.availability() == v.availability()))
+    }
+
+    /// Variations of the item that are on sale again or were added on sale.
+    pub fn restocked_variations<'a>(&self, item: &'a ItemData) -> Vec<&'a VariationData> {
+        item.variations().iter()
+            .filter(|v| v.availability().is_available())
+            .filter(|v| self.variations.iter().find(|pv| pv.name() == v.name()).is_none_or(|pv| !pv.availability().is_available()))
+            .collect()
+    }
+}
+
+pub type ProductHistoryEntry = product_history::ProductHistoryEntry<Availability, Option<i32>>;
+
+impl AsRef<Product> for Product {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+impl SiteProduct for Product {
+    const SITE: Site = SITE;
+
+    fn id(&self) -> i32 { self.id }
+    fn title(&self) -> &str { &self.title }
+    fn url(&self) -> &str { &self.url }
+    fn image_url(&self) -> &str { &self.image_url }
+    fn image_hash(&self) -> Option<&str> { self.image_hash.as_deref() }
+    fn image_phash(&self) -> Option<u64> { self.image_phash }
+
+    fn with_image(self, image: &CachedImage) -> Self {
+        self.with_image_hash(Some(image.hash().to_owned())).with_image_phash(image.perceptual_hash())
+    }
+
+    fn summary(&self) -> String {
+        let variations = self.variations.iter()
+            .map(|v| match v.availability().is_available() {
+                true => format!("{} ¥{}", v.name(), v.price()),
+                false => format!("{} ¥{} (sold out)", v.name(), v.price()),
+            })
+            .collect::<Vec<_>>();
+        format!("{}\n{}", self.shop_name, variations.join("\n"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariationData {
+    name: String,
+    price: i32,
+    availability: Availability,
+}
+
+impl VariationData {
+    pub fn new(name: String, price: i32, availability: Availability) -> Self {
+        Self { name, price, availability }
+    }
+
+    pub fn name(&self) -> &str { &self.name }
+    pub fn price(&self) -> i32 { self.price }
+    pub fn availability(&self) -> &Availability { &self.availability }
+}
+
+/// An item as found on its page.
+#[derive(Debug, Clone)]
+pub struct ItemData {
+    url: String,
+    title: String,
+    shop_name: String,
+    image_url: String,
+    variations: Vec<VariationData>,
+}
+
+impl ItemData {
+    pub fn new(url: String, title: String, shop_name: String, image_url: String, variations: Vec<VariationData>) -> Self {
+        Self { url, title, shop_name, image_url, variations }
+    }
+
+    pub fn url(&self) -> &str { &self.url }
+    pub fn title(&self) -> &str { &self.title }
+    pub fn shop_name(&self) -> &str { &self.shop_name }
+    pub fn image_url(&self) -> &str { &self.image_url }
+    pub fn variations(&self) -> &[VariationData] { &self.variations }
+
+    pub fn availability(&self) -> Availability {
+        match self.variations.iter().any(|v| v.availability().is_available()) {
+            true => Availability::Available,
+            false => Availability::NotAvailable,
+        }
+    }
+
+    pub fn price(&self) -> Option<i32> {
+        let available = self.variations.iter().filter(|v| v.availability().is_available()).map(|v| v.price()).min();
+        available.or_else(|| self.variations.iter().map(|v| v.price()).min())
+    }
+
+    /// The item with every variation sold out, for items no longer listed by their shop.
+    pub fn sold_out(product: &Product) -> Self {
+        let variations = product.variations().iter()
+            .map(|v| VariationData::new(v.name().to_owned(), v.price(), Availability::NotAvailable))
+            .collect();
+        Self::new(product.url().to_owned(), product.title().to_owned(), product.shop_name().to_owned(), product.image_url().to_owned(), variations)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateProductArgs {
+    source_id: i32,
+    item: ItemData,
+}
+
+impl CreateProductArgs {
+    pub fn new(source_id: i32, item: ItemData) -> Self {
+        Self { source_id, item }
+    }
+
+    /// The shop or tag the item was found for.
+    pub fn source_id(&self) -> i32 { self.source_id }
+    pub fn item(&self) -> &ItemData { &self.item }
+}
+
+/// Replaces the variations of the product, variations missing from the item are kept as sold out.
+#[derive(Debug, Clone)]
+pub struct UpdateProductArgs {
+    item: ItemData,
+}
+
+impl UpdateProductArgs {
+    pub fn new(item: ItemData) -> Self {
+        Self { item }
+    }
+
+    pub fn url(&self) -> &str { self.item.url() }
+    pub fn item(&self) -> &ItemData { &self.item }
+}
+
+#[derive(Debug, Error)]
+pub enum CreateProductError {
+    #[error("Product '{title}' ({url}) already exists")]
+    DuplicateProduct { url: String, title: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum UpdateProductError {
+    #[error("Product {url} does not exist")]
+    ProductMissing { url: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum AddProductSourceError {
+    #[error("Product {url} does not exist")]
+    ProductMissing { url: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum GetProductsError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum ScrapeProductsError {
+    #[error(transparent)]
+    ParseError(#[from] ParseError),
+    #[error(transparent)]
+    GetSourcesError(#[from] GetSourcesError),
+    #[error(transparent)]
+    GetProductError(#[from] GetProductsError),
+    #[error(transparent)]
+    CreateProductError(#[from] CreateProductError),
+    #[error(transparent)]
+    UpdateProductError(#[from] UpdateProductError),
+    #[error(transparent)]
+    AddProductSourceError(#[from] AddProductSourceError),
+    #[error(transparent)]
+    AddNotificationsError(#[from] AddNotificationsError),
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
@@ -0,0 +1,166 @@
+use crate::domain::user::models::user::User;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter, EnumString};
+use thiserror::Error;
+
+/// BOOTH lists the items of a shop on its own subdomain and the items of a tag across all shops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumString, EnumIter, Serialize, Deserialize)]
+pub enum SourceKind {
+    Shop,
+    Tag,
+}
+
+impl TryFrom<String> for SourceKind {
+    type Error = strum::ParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::try_from(value.as_str())
+    }
+}
+
+impl From<SourceKind> for String {
+    fn from(value: SourceKind) -> Self {
+        value.to_string()
+    }
+}
+
+/// A shop or tag on BOOTH, `following` is whether the user follows it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Source {
+    id: i32,
+    date_added: DateTime<Utc>,
+    name: String,
+    kind: SourceKind,
+    melonbooks_artist_id: Option<i32>,
+    following: bool,
+    date_followed: Option<DateTime<Utc>>,
+}
+
+impl Source {
+    pub fn new(id: i32, date_added: DateTime<Utc>, name: String, kind: SourceKind, melonbooks_artist_id: Option<i32>, following: bool, date_followed: Option<DateTime<Utc>>) -> Self {
+        Source { id, date_added, name, kind, melonbooks_artist_id, following, date_followed }
+    }
+
+    pub fn id(&self) -> i32 { self.id }
+    pub fn date_added(&self) -> DateTime<Utc> { self.date_added }
+    /// The subdomain of a shop or the name of a tag.
+    pub fn name(&self) -> &str { &self.name }
+    pub fn kind(&self) -> SourceKind { self.kind }
+    /// The melonbooks artist running the shop, always `None` for tags.
+    pub fn melonbooks_artist_id(&self) -> Option<i32> { self.melonbooks_artist_id }
+    pub fn following(&self) -> bool { self.following }
+    pub fn date_followed(&self) -> Option<DateTime<Utc>> { self.date_followed }
+
+    pub fn url(&self) -> String {
+        SourceArgs::new(self.name.clone(), self.kind).url()
+    }
+}
+
+/// Source followed by at least one user, scraped once for all of its followers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FollowedSource {
+    source: Source,
+    followers: Vec<User>,
+}
+
+impl FollowedSource {
+    pub fn new(source: Source, followers: Vec<User>) -> Self {
+        FollowedSource { source, followers }
+    }
+
+    pub fn source(&self) -> &Source { &self.source }
+    pub fn followers(&self) -> &[User] { &self.followers }
+}
+
+#[derive(Debug, Clone)]
+pub struct SourceArgs {
+    name: String,
+    kind: SourceKind,
+}
+
+impl SourceArgs {
+    /// Shops can also be given by their url, e.g. `https://example.booth.pm/items`.
+    pub fn new(name: String, kind: SourceKind) -> Self {
+        let name = match kind {
+            SourceKind::Shop => {
+                let name = name.trim();
+                let name = name.strip_prefix("https://").or_else(|| name.strip_prefix("http://")).unwrap_or(name);
+                let name = name.split('/').next().unwrap_or(name);
+                name.strip_suffix(".booth.pm").unwrap_or(name).to_owned()
+            }
+            SourceKind::Tag => name.trim().to_owned(),
+        };
+        SourceArgs { name, kind }
+    }
+
+    pub fn name(&self) -> &str { &self.name }
+    pub fn kind(&self) -> SourceKind { self.kind }
+
+    pub fn url(&self) -> String {
+        match self.kind {
+            SourceKind::Shop => format!("https://{}.booth.pm/items", self.name),
+            SourceKind::Tag => {
+                let query = serde_urlencoded::to_string([("tags[]", self.name.as_str())]).unwrap_or_default();
+                format!("https://booth.pm/ja/items?{}", query)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum FollowSourceError {
+    #[error("{kind} '{name}' is already followed")]
+    AlreadyFollowedError { name: String, kind: SourceKind },
+    #[error("shop or tag name must not be empty")]
+    EmptyName,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum UnfollowSourceError {
+    #[error("unknown shop or tag with id '{id}'")]
+    UnknownSource { id: i32 },
+    #[error("{kind} '{name}' not followed")]
+    SourceNotFollowed { name: String, kind: SourceKind },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum GetSourcesError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum LinkMelonbooksArtistError {
+    #[error("unknown shop or tag with id '{id}'")]
+    UnknownSource { id: i32 },
+    #[error("only shops can be linked to a melonbooks artist, '{name}' is a tag")]
+    NotAShop { name: String },
+    #[error("unknown melonbooks artist with id '{id}'")]
+    UnknownArtist { id: i32 },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_source_args_shop_url() {
+        let args = SourceArgs::new(" https://mafuyu.booth.pm/items?page=2 ".to_owned(), SourceKind::Shop);
+        assert_eq!(args.name(), "mafuyu");
+        assert_eq!(args.url(), "https://mafuyu.booth.pm/items");
+        assert_eq!(SourceArgs::new("mafuyu".to_owned(), SourceKind::Shop).name(), "mafuyu");
+    }
+
+    #[test]
+    fn test_source_args_tag_url() {
+        let args = SourceArgs::new("ブルーアーカイブ".to_owned(), SourceKind::Tag);
+        assert_eq!(args.url(), "https://booth.pm/ja/items?tags%5B%5D=%E3%83%96%E3%83%AB%E3%83%BC%E3%82%A2%E3%83%BC%E3%82%AB%E3%82%A4%E3%83%96");
+    }
+}
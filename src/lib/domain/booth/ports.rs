@@ -0,0 +1,54 @@
+use crate::domain::booth::models::product::{AddProductSourceError, CreateProductArgs, CreateProductError, GetProductsError, ItemData, Product, ProductHistoryEntry, ScrapeProductsError, UpdateProductArgs, UpdateProductError};
+use crate::domain::booth::models::source::{FollowSourceError, FollowedSource, GetSourcesError, LinkMelonbooksArtistError, Source, SourceArgs, UnfollowSourceError};
+use crate::domain::pagination::{Page, PageRequest};
+use crate::domain::product_history::GetProductError;
+use crate::domain::site::SiteService;
+use crate::domain::user::models::user::User;
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait BoothService: SiteService {
+    async fn follow_source(&self, user: &User, req: &SourceArgs) -> Result<(), FollowSourceError>;
+    async fn unfollow_source(&self, user: &User, source_id: i32) -> Result<(), UnfollowSourceError>;
+    async fn get_sources(&self, user: &User) -> Result<Vec<Source>, GetSourcesError>;
+    async fn get_followed_sources(&self, user: &User) -> Result<Vec<Source>, GetSourcesError>;
+    async fn get_sources_page(&self, user: &User, following: Option<bool>, page: PageRequest) -> Result<Page<Source>, GetSourcesError>;
+    /// Links the shop to the melonbooks artist running it, `None` removes the link.
+    async fn link_melonbooks_artist(&self, source_id: i32, artist_id: Option<i32>) -> Result<(), LinkMelonbooksArtistError>;
+
+    async fn get_products_page(&self, page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    async fn get_products_by_source(&self, source_id: i32) -> Result<Vec<Product>, GetProductsError>;
+    async fn get_products_page_by_source(&self, source_id: i32, page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    async fn get_products_page_by_sources(&self, source_ids: &[i32], page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    async fn get_product(&self, product_id: i32) -> Result<Product, GetProductError>;
+    async fn get_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError>;
+}
+
+#[async_trait]
+pub trait BoothRepository: Clone + Send + Sync + 'static {
+    async fn follow_booth_source(&self, user_id: i32, req: &SourceArgs) -> Result<(), FollowSourceError>;
+    async fn unfollow_booth_source(&self, user_id: i32, source_id: i32) -> Result<(), UnfollowSourceError>;
+    async fn get_booth_sources(&self, user_id: i32) -> Result<Vec<Source>, GetSourcesError>;
+    async fn get_booth_sources_page(&self, user_id: i32, following: Option<bool>, page: PageRequest) -> Result<Page<Source>, GetSourcesError>;
+    async fn get_followed_booth_sources(&self) -> Result<Vec<FollowedSource>, GetSourcesError>;
+    async fn link_booth_source_to_melonbooks_artist(&self, source_id: i32, artist_id: Option<i32>) -> Result<(), LinkMelonbooksArtistError>;
+
+    async fn create_booth_product(&self, req: &CreateProductArgs) -> Result<Product, CreateProductError>;
+    async fn update_booth_product(&self, req: &UpdateProductArgs) -> Result<Product, UpdateProductError>;
+    /// Links an item that was first found for another shop or tag to the source.
+    async fn add_booth_product_source(&self, url: &str, source_id: i32) -> Result<(), AddProductSourceError>;
+    async fn get_booth_product(&self, product_id: i32) -> Result<Product, GetProductError>;
+    async fn get_booth_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError>;
+    async fn get_booth_products(&self) -> Result<Vec<Product>, GetProductsError>;
+    async fn get_booth_products_by_source(&self, source_id: i32) -> Result<Vec<Product>, GetProductsError>;
+    async fn get_booth_products_page(&self, page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    async fn get_booth_products_page_by_source(&self, source_id: i32, page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    /// Products found for any of the sources, the newest first.
+    async fn get_booth_products_page_by_sources(&self, source_ids: &[i32], page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+}
+
+#[async_trait]
+pub trait BoothScraper: Clone + Send + Sync + 'static {
+    async fn get_item_urls(&self, source: &SourceArgs) -> Result<Vec<String>, ScrapeProductsError>;
+    async fn get_item(&self, url: &str) -> Result<ItemData, ScrapeProductsError>;
+}
@@ -0,0 +1,234 @@
+use crate::domain::booth::models::product::{CreateProductArgs, GetProductsError, ItemData, Product, ProductHistoryEntry, ScrapeProductsError, UpdateProductArgs};
+use crate::domain::booth::models::source::{FollowSourceError, GetSourcesError, LinkMelonbooksArtistError, Source, SourceArgs, SourceKind, UnfollowSourceError};
+use crate::domain::booth::ports::{BoothRepository, BoothScraper, BoothService};
+use crate::domain::booth::SITE;
+use crate::domain::image::ports::ImageCache;
+use crate::domain::pagination::{Page, PageRequest};
+use crate::domain::product_history::GetProductError;
+use crate::domain::site::{ScrapeGuard, ScrapeLock, ScrapeSiteError, Site, SiteCore, SiteNotifier, SiteRepository, SiteService};
+use crate::domain::user::models::user::User;
+use async_trait::async_trait;
+use log::info;
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(Debug, Clone)]
+pub struct BoothServiceImpl<R, N, S, I>
+where
+    R: BoothRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: BoothScraper,
+    I: ImageCache
+{
+    repo: R,
+    scraper: S,
+    core: SiteCore<N, I>,
+}
+
+impl<R, N, S, I> BoothServiceImpl<R, N, S, I>
+where
+    R: BoothRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: BoothScraper,
+    I: ImageCache
+{
+    pub fn new(repo: R, scraper: S, core: SiteCore<N, I>) -> Self {
+        Self { repo, scraper, core }
+    }
+}
+
+#[async_trait]
+impl<R, N, S, I> SiteService for BoothServiceImpl<R, N, S, I>
+where
+    R: BoothRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: BoothScraper,
+    I: ImageCache
+{
+    fn site(&self) -> Site {
+        SITE
+    }
+
+    fn scrape_lock(&self) -> &ScrapeLock {
+        self.core.scrape_lock()
+    }
+
+    async fn scrape(&self, _guard: &ScrapeGuard) -> Result<(), ScrapeSiteError> {
+        info!("scrape available products");
+        self.scrape_followed_sources().await
+            .map_err(|e| anyhow::Error::new(e).into())
+    }
+}
+
+#[async_trait]
+impl<R, N, S, I> BoothService for BoothServiceImpl<R, N, S, I>
+where
+    R: BoothRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: BoothScraper,
+    I: ImageCache
+{
+    async fn follow_source(&self, user: &User, source_args: &SourceArgs) -> Result<(), FollowSourceError> {
+        if source_args.name().is_empty() {
+            return Err(FollowSourceError::EmptyName);
+        }
+        info!("follow {} '{}' for '{}'", source_args.kind(), source_args.name(), user.username());
+        self.repo.follow_booth_source(user.id(), source_args).await
+    }
+
+    async fn unfollow_source(&self, user: &User, source_id: i32) -> Result<(), UnfollowSourceError> {
+        info!("unfollow source with id '{}' for '{}'", source_id, user.username());
+        self.repo.unfollow_booth_source(user.id(), source_id).await
+    }
+
+    async fn get_sources(&self, user: &User) -> Result<Vec<Source>, GetSourcesError> {
+        info!("get sources for '{}'", user.username());
+        self.repo.get_booth_sources(user.id()).await
+    }
+
+    async fn get_followed_sources(&self, user: &User) -> Result<Vec<Source>, GetSourcesError> {
+        info!("get followed sources for '{}'", user.username());
+        let sources = self.repo.get_booth_sources(user.id()).await?;
+        Ok(
+            sources.into_iter()
+                .filter(|s| s.following())
+                .collect()
+        )
+    }
+
+    async fn get_sources_page(&self, user: &User, following: Option<bool>, page: PageRequest) -> Result<Page<Source>, GetSourcesError> {
+        info!("get page {} of sources for '{}'", page.page(), user.username());
+        self.repo.get_booth_sources_page(user.id(), following, page).await
+    }
+
+    async fn link_melonbooks_artist(&self, source_id: i32, artist_id: Option<i32>) -> Result<(), LinkMelonbooksArtistError> {
+        info!("link source with id '{}' to melonbooks artist {:?}", source_id, artist_id);
+        self.repo.link_booth_source_to_melonbooks_artist(source_id, artist_id).await
+    }
+
+    async fn get_products_page(&self, page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        info!("get page {} of products", page.page());
+        self.repo.get_booth_products_page(page).await
+    }
+
+    async fn get_products_by_source(&self, source_id: i32) -> Result<Vec<Product>, GetProductsError> {
+        info!("get products by source with id '{}'", source_id);
+        self.repo.get_booth_products_by_source(source_id).await
+    }
+
+    async fn get_products_page_by_source(&self, source_id: i32, page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        info!("get page {} of products by source with id '{}'", page.page(), source_id);
+        self.repo.get_booth_products_page_by_source(source_id, page).await
+    }
+
+    async fn get_products_page_by_sources(&self, source_ids: &[i32], page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        info!("get page {} of products by sources with ids {:?}", page.page(), source_ids);
+        self.repo.get_booth_products_page_by_sources(source_ids, page).await
+    }
+
+    async fn get_product(&self, product_id: i32) -> Result<Product, GetProductError> {
+        info!("get product with id '{}'", product_id);
+        self.repo.get_booth_product(product_id).await
+    }
+
+    async fn get_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError> {
+        info!("get history of product with id '{}'", product_id);
+        self.repo.get_booth_product_history(product_id).await
+    }
+}
+
+impl<R, N, S, I> BoothServiceImpl<R, N, S, I>
+where
+    R: BoothRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: BoothScraper,
+    I: ImageCache
+{
+    /// The stock of the variations is only on the item page, so every listed item is loaded on each scrape.
+    /// Items added or restocked in any variation are notified, sold out items are stored to notice their restock.
+    async fn scrape_followed_sources(&self) -> Result<(), ScrapeProductsError> {
+        let followed_sources = self.repo.get_followed_booth_sources().await?;
+        // items are shared between shops and tags, an item found for an earlier source is not new for a later one
+        let mut known_products = self.repo.get_booth_products().await?
+            .into_iter()
+            .map(|p| (p.url().to_owned(), p))
+            .collect::<HashMap<_, _>>();
+        let mut scraped_urls = BTreeSet::<String>::new();
+        for followed_source in followed_sources.iter() {
+            let source = followed_source.source();
+            let target = format!("{} {}", source.kind(), source.name());
+            info!("scrape available products for {}", target);
+            let source_urls = self.repo.get_booth_products_by_source(source.id()).await?
+                .into_iter()
+                .map(|p| p.url().to_owned())
+                .collect::<BTreeSet<_>>();
+            let source_args = SourceArgs::new(source.name().to_owned(), source.kind());
+            let urls = self.scraper.get_item_urls(&source_args).await?;
+
+            let mut new_products = Vec::<Product>::new();
+            let mut restocked_products = Vec::<Product>::new();
+            for url in urls.iter() {
+                if scraped_urls.contains(url) {
+                    if !source_urls.contains(url) {
+                        self.repo.add_booth_product_source(url, source.id()).await?;
+                    }
+                    continue;
+                }
+                let item = self.scraper.get_item(url).await?;
+                scraped_urls.insert(url.to_owned());
+                match known_products.get(url) {
+                    None => {
+                        let product = self.repo.create_booth_product(&CreateProductArgs::new(source.id(), item)).await?;
+                        let product = match product.availability().is_available() {
+                            true => {
+                                let product = self.core.cache_product_image(&self.repo, product).await;
+                                new_products.push(product.clone());
+                                product
+                            }
+                            false => product,
+                        };
+                        known_products.insert(product.url().to_owned(), product);
+                    }
+                    Some(product) => {
+                        if !source_urls.contains(url) {
+                            self.repo.add_booth_product_source(url, source.id()).await?;
+                        }
+                        if !product.has_changes(&item) {
+                            continue;
+                        }
+                        let restocked = !product.restocked_variations(&item).is_empty();
+                        let product = self.repo.update_booth_product(&UpdateProductArgs::new(item)).await?;
+                        let product = match restocked {
+                            true => {
+                                let product = self.core.cache_product_image(&self.repo, product).await;
+                                restocked_products.push(product.clone());
+                                product
+                            }
+                            false => product,
+                        };
+                        known_products.insert(product.url().to_owned(), product);
+                    }
+                }
+            }
+            info!("found '{}' new and '{}' restocked products for {}", new_products.len(), restocked_products.len(), target);
+
+            self.core.notify(&self.repo, &target, followed_source.followers(), &new_products, &restocked_products).await?;
+
+            // only the items of a shop are listed completely, older items of a tag drop out of the scraped pages
+            if source.kind() != SourceKind::Shop {
+                continue;
+            }
+            let gone_products = source_urls.iter()
+                .filter(|u| !urls.contains(u))
+                .filter_map(|u| known_products.get(u.as_str()))
+                .filter(|p| p.availability().is_available())
+                .map(|p| UpdateProductArgs::new(ItemData::sold_out(p)))
+                .collect::<Vec<_>>();
+            info!("update '{}' products as now unavailable for {}", gone_products.len(), target);
+            for gone_product in gone_products.iter() {
+                let product = self.repo.update_booth_product(gone_product).await?;
+                known_products.insert(product.url().to_owned(), product);
+            }
+        }
+        Ok(())
+    }
+}
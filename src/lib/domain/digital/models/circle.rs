@@ -0,0 +1,170 @@
+use crate::domain::user::models::user::User;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter, EnumString};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumString, EnumIter, Serialize, Deserialize)]
+pub enum Store {
+    #[strum(serialize = "DLsite")]
+    #[serde(rename = "DLsite")]
+    Dlsite,
+    #[strum(serialize = "FANZA")]
+    #[serde(rename = "FANZA")]
+    Fanza,
+}
+
+impl TryFrom<String> for Store {
+    type Error = strum::ParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::try_from(value.as_str())
+    }
+}
+
+impl From<Store> for String {
+    fn from(value: Store) -> Self {
+        value.to_string()
+    }
+}
+
+/// A circle selling its works on DLsite or FANZA, `following` is whether the user follows it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Circle {
+    id: i32,
+    date_added: DateTime<Utc>,
+    store: Store,
+    code: String,
+    name: String,
+    following: bool,
+    date_followed: Option<DateTime<Utc>>,
+}
+
+impl Circle {
+    pub fn new(id: i32, date_added: DateTime<Utc>, store: Store, code: String, name: String, following: bool, date_followed: Option<DateTime<Utc>>) -> Self {
+        Circle { id, date_added, store, code, name, following, date_followed }
+    }
+
+    pub fn id(&self) -> i32 { self.id }
+    pub fn date_added(&self) -> DateTime<Utc> { self.date_added }
+    pub fn store(&self) -> Store { self.store }
+    /// The id of the circle in the store, e.g. `RG12345` on DLsite.
+    pub fn code(&self) -> &str { &self.code }
+    /// The name shown by the store, the code until the circle was first scraped.
+    pub fn name(&self) -> &str { &self.name }
+    pub fn following(&self) -> bool { self.following }
+    pub fn date_followed(&self) -> Option<DateTime<Utc>> { self.date_followed }
+
+    pub fn url(&self) -> String {
+        CircleArgs::new(self.store, self.code.clone()).url()
+    }
+}
+
+/// Circle followed by at least one user, scraped once for all of its followers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FollowedCircle {
+    circle: Circle,
+    followers: Vec<User>,
+}
+
+impl FollowedCircle {
+    pub fn new(circle: Circle, followers: Vec<User>) -> Self {
+        FollowedCircle { circle, followers }
+    }
+
+    pub fn circle(&self) -> &Circle { &self.circle }
+    pub fn followers(&self) -> &[User] { &self.followers }
+}
+
+#[derive(Debug, Clone)]
+pub struct CircleArgs {
+    store: Store,
+    code: String,
+}
+
+impl CircleArgs {
+    /// Circles can also be given by the url of their page,
+    /// e.g. `https://www.dlsite.com/maniax/circle/profile/=/maker_id/RG12345.html`
+    /// or `https://www.dmm.co.jp/dc/doujin/-/list/=/article=maker/id=12345/`.
+    pub fn new(store: Store, code: String) -> Self {
+        let code = code.trim();
+        let marker = match store {
+            Store::Dlsite => "maker_id/",
+            Store::Fanza => "article=maker/id=",
+        };
+        let code = match code.find(marker) {
+            Some(index) => code[index + marker.len()..].chars().take_while(|c| c.is_ascii_alphanumeric()).collect::<String>(),
+            None => code.to_owned(),
+        };
+        let code = match store {
+            Store::Dlsite => code.to_uppercase(),
+            Store::Fanza => code,
+        };
+        CircleArgs { store, code }
+    }
+
+    pub fn store(&self) -> Store { self.store }
+    pub fn code(&self) -> &str { &self.code }
+
+    pub fn url(&self) -> String {
+        match self.store {
+            Store::Dlsite => format!("https://www.dlsite.com/maniax/circle/profile/=/maker_id/{}.html", self.code),
+            Store::Fanza => format!("https://www.dmm.co.jp/dc/doujin/-/list/=/article=maker/id={}/sort=date/", self.code),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum FollowCircleError {
+    #[error("{store} circle '{code}' is already followed")]
+    AlreadyFollowedError { store: Store, code: String },
+    #[error("circle id must not be empty")]
+    EmptyCode,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum UnfollowCircleError {
+    #[error("unknown circle with id '{id}'")]
+    UnknownCircle { id: i32 },
+    #[error("{store} circle '{code}' not followed")]
+    CircleNotFollowed { store: Store, code: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum GetCirclesError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum SetCircleNameError {
+    #[error("unknown circle with id '{id}'")]
+    UnknownCircle { id: i32 },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dlsite_circle_args() {
+        let args = CircleArgs::new(Store::Dlsite, " https://www.dlsite.com/maniax/circle/profile/=/maker_id/RG12345.html ".to_owned());
+        assert_eq!(args.code(), "RG12345");
+        assert_eq!(args.url(), "https://www.dlsite.com/maniax/circle/profile/=/maker_id/RG12345.html");
+        assert_eq!(CircleArgs::new(Store::Dlsite, "rg12345".to_owned()).code(), "RG12345");
+    }
+
+    #[test]
+    fn test_fanza_circle_args() {
+        let args = CircleArgs::new(Store::Fanza, "https://www.dmm.co.jp/dc/doujin/-/list/=/article=maker/id=67890/".to_owned());
+        assert_eq!(args.code(), "67890");
+        assert_eq!(args.url(), "https://www.dmm.co.jp/dc/doujin/-/list/=/article=maker/id=67890/sort=date/");
+        assert_eq!(CircleArgs::new(Store::Fanza, "67890".to_owned()).code(), "67890");
+    }
+}
@@ -0,0 +1,282 @@
+use crate::domain::digital::models::circle::{GetCirclesError, SetCircleNameError, Store};
+use crate::domain::digital::SITE;
+use crate::domain::product_history;
+use crate::domain::product_history::AddNotificationsError;
+use crate::domain::image::models::image::CachedImage;
+use crate::domain::site::{Site, SiteProduct};
+use crate::outbound::digital_scraper::ParseError;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use thiserror::Error;
+
+/// A discount of a work, `end` is in the store's local time and `None` when the store does not announce it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sale {
+    price: i32,
+    end: Option<NaiveDateTime>,
+}
+
+impl Sale {
+    pub fn new(price: i32, end: Option<NaiveDateTime>) -> Self {
+        Self { price, end }
+    }
+
+    /// Price in yen.
+    pub fn price(&self) -> i32 { self.price }
+    pub fn end(&self) -> Option<NaiveDateTime> { self.end }
+}
+
+/// A digital work, which never runs out of stock but is discounted from time to time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Product {
+    id: i32,
+    date_added: DateTime<Utc>,
+    circle_id: i32,
+    store: Store,
+    url: String,
+    title: String,
+    circle_name: String,
+    image_url: String,
+    list_price: i32,
+    sale: Option<Sale>,
+    date_discounted: Option<DateTime<Utc>>,
+    image_hash: Option<String>,
+    image_phash: Option<u64>,
+}
+
+impl Product {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(id: i32, date_added: DateTime<Utc>, circle_id: i32, store: Store, url: String, title: String, circle_name: String, image_url: String, list_price: i32, sale: Option<Sale>) -> Self {
+        Self { id, date_added, circle_id, store, url, title, circle_name, image_url, list_price, sale, date_discounted: None, image_hash: None, image_phash: None }
+    }
+
+    pub fn with_date_discounted(mut self, date_discounted: Option<DateTime<Utc>>) -> Self {
+        self.date_discounted = date_discounted;
+        self
+    }
+
+    pub fn with_image_hash(mut self, image_hash: Option<String>) -> Self {
+        self.image_hash = image_hash;
+        self
+    }
+
+    pub fn with_image_phash(mut self, image_phash: Option<u64>) -> Self {
+        self.image_phash = image_phash;
+        self
+    }
+
+    pub fn id(&self) -> i32 { self.id }
+    pub fn date_added(&self) -> DateTime<Utc> { self.date_added }
+    pub fn circle_id(&self) -> i32 { self.circle_id }
+    pub fn store(&self) -> Store { self.store }
+    pub fn url(&self) -> &str { &self.url }
+    pub fn title(&self) -> &str { &self.title }
+    pub fn circle_name(&self) -> &str { &self.circle_name }
+    pub fn image_url(&self) -> &str { &self.image_url }
+    /// Price in yen without a discount.
+    pub fn list_price(&self) -> i32 { self.list_price }
+    pub fn sale(&self) -> Option<&Sale> { self.sale.as_ref() }
+    /// When the current or last sale was first seen.
+    pub fn date_discounted(&self) -> Option<DateTime<Utc>> { self.date_discounted }
+    pub fn image_hash(&self) -> Option<&str> { self.image_hash.as_deref() }
+    pub fn image_phash(&self) -> Option<u64> { self.image_phash }
+
+    /// The price the work is sold at now.
+    pub fn price(&self) -> i32 {
+        self.sale.as_ref().map(|s| s.price()).unwrap_or(self.list_price)
+    }
+
+    /// Discount in percent of the list price, `None` while not on sale.
+    pub fn discount_rate(&self) -> Option<i32> {
+        let sale = self.sale.as_ref()?;
+        if self.list_price <= 0 {
+            return None;
+        }
+        Some(100 - sale.price() * 100 / self.list_price)
+    }
+
+    pub fn has_changes(&self, work: &WorkData) -> bool {
+        self.title != work.title() || self.list_price != work.list_price() || self.sale.as_ref() != work.sale()
+    }
+
+    /// Whether the work went on sale since the product was stored.
+    pub fn is_discount_started(&self, work: &WorkData) -> bool {
+        self.sale.is_none() && work.sale().is_some()
+    }
+}
+
+/// The availability of a work is its sale, `None` when sold at the list price.
+pub type ProductHistoryEntry = product_history::ProductHistoryEntry<Option<Sale>, i32>;
+
+impl AsRef<Product> for Product {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+impl SiteProduct for Product {
+    const SITE: Site = SITE;
+
+    fn id(&self) -> i32 { self.id }
+    fn title(&self) -> &str { &self.title }
+    fn url(&self) -> &str { &self.url }
+    fn image_url(&self) -> &str { &self.image_url }
+    fn image_hash(&self) -> Option<&str> { self.image_hash.as_deref() }
+    fn image_phash(&self) -> Option<u64> { self.image_phash }
+
+    fn with_image(self, image: &CachedImage) -> Self {
+        self.with_image_hash(Some(image.hash().to_owned())).with_image_phash(image.perceptual_hash())
+    }
+
+    fn summary(&self) -> String {
+        let price = match (&self.sale, self.discount_rate()) {
+            (Some(sale), Some(rate)) => {
+                let end = sale.end().map(|e| format!(" until {}", e.format("%Y-%m-%d %H:%M"))).unwrap_or_default();
+                format!("¥{} instead of ¥{} (-{}%){}", sale.price(), self.list_price, rate, end)
+            }
+            _ => format!("¥{}", self.list_price),
+        };
+        format!("{} — {}\n{}", self.circle_name, self.store, price)
+    }
+
+    fn notification_target(target: &str) -> String {
+        format!("Circle {}", target)
+    }
+}
+
+/// A work as found in the store.
+#[derive(Debug, Clone)]
+pub struct WorkData {
+    url: String,
+    title: String,
+    circle_name: String,
+    image_url: String,
+    list_price: i32,
+    sale: Option<Sale>,
+}
+
+impl WorkData {
+    pub fn new(url: String, title: String, circle_name: String, image_url: String, list_price: i32, sale: Option<Sale>) -> Self {
+        Self { url, title, circle_name, image_url, list_price, sale }
+    }
+
+    pub fn url(&self) -> &str { &self.url }
+    pub fn title(&self) -> &str { &self.title }
+    pub fn circle_name(&self) -> &str { &self.circle_name }
+    pub fn image_url(&self) -> &str { &self.image_url }
+    pub fn list_price(&self) -> i32 { self.list_price }
+    pub fn sale(&self) -> Option<&Sale> { self.sale.as_ref() }
+}
+
+/// The works of a circle as listed by the store, newest first.
+#[derive(Debug, Clone)]
+pub struct CircleData {
+    name: String,
+    works: Vec<WorkData>,
+}
+
+impl CircleData {
+    pub fn new(name: String, works: Vec<WorkData>) -> Self {
+        Self { name, works }
+    }
+
+    pub fn name(&self) -> &str { &self.name }
+    pub fn works(&self) -> &[WorkData] { &self.works }
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateProductArgs {
+    circle_id: i32,
+    store: Store,
+    work: WorkData,
+}
+
+impl CreateProductArgs {
+    pub fn new(circle_id: i32, store: Store, work: WorkData) -> Self {
+        Self { circle_id, store, work }
+    }
+
+    pub fn circle_id(&self) -> i32 { self.circle_id }
+    pub fn store(&self) -> Store { self.store }
+    pub fn work(&self) -> &WorkData { &self.work }
+}
+
+#[derive(Debug, Clone)]
+pub struct UpdateProductArgs {
+    work: WorkData,
+}
+
+impl UpdateProductArgs {
+    pub fn new(work: WorkData) -> Self {
+        Self { work }
+    }
+
+    pub fn url(&self) -> &str { self.work.url() }
+    pub fn work(&self) -> &WorkData { &self.work }
+}
+
+#[derive(Debug, Error)]
+pub enum CreateProductError {
+    #[error("Product '{title}' ({url}) already exists")]
+    DuplicateProduct { url: String, title: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum UpdateProductError {
+    #[error("Product {url} does not exist")]
+    ProductMissing { url: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum GetProductsError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum ScrapeProductsError {
+    #[error(transparent)]
+    ParseError(#[from] ParseError),
+    #[error(transparent)]
+    GetCirclesError(#[from] GetCirclesError),
+    #[error(transparent)]
+    SetCircleNameError(#[from] SetCircleNameError),
+    #[error(transparent)]
+    GetProductError(#[from] GetProductsError),
+    #[error(transparent)]
+    CreateProductError(#[from] CreateProductError),
+    #[error(transparent)]
+    UpdateProductError(#[from] UpdateProductError),
+    #[error(transparent)]
+    AddNotificationsError(#[from] AddNotificationsError),
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_discount() {
+        let end = NaiveDateTime::parse_from_str("2026-10-31 23:59:59", "%Y-%m-%d %H:%M:%S").unwrap();
+        let product = Product::new(1, Utc::now(), 1, Store::Dlsite, "https://www.dlsite.com/maniax/work/=/product_id/RJ01234567.html".to_owned(),
+                                   "title".to_owned(), "circle".to_owned(), "https://img.dlsite.jp/main.jpg".to_owned(), 1100, None);
+        assert_eq!(product.price(), 1100);
+        assert_eq!(product.discount_rate(), None);
+        let work = |sale| WorkData::new(product.url().to_owned(), "title".to_owned(), "circle".to_owned(), product.image_url().to_owned(), 1100, sale);
+        assert!(!product.has_changes(&work(None)));
+        assert!(product.has_changes(&work(Some(Sale::new(770, Some(end))))));
+        assert!(product.is_discount_started(&work(Some(Sale::new(770, Some(end))))));
+
+        let product = Product::new(1, Utc::now(), 1, Store::Dlsite, product.url().to_owned(), "title".to_owned(), "circle".to_owned(),
+                                   product.image_url().to_owned(), 1100, Some(Sale::new(770, Some(end))));
+        assert_eq!(product.price(), 770);
+        assert_eq!(product.discount_rate(), Some(30));
+        assert!(!product.is_discount_started(&work(Some(Sale::new(550, None)))));
+        assert_eq!(product.summary(), "circle — DLsite\n¥770 instead of ¥1100 (-30%) until 2026-10-31 23:59");
+    }
+}
@@ -0,0 +1,55 @@
+use crate::domain::digital::models::circle::{Circle, CircleArgs, FollowCircleError, FollowedCircle, GetCirclesError, SetCircleNameError, UnfollowCircleError};
+use crate::domain::digital::models::product::{CircleData, CreateProductArgs, CreateProductError, GetProductsError, Product, ProductHistoryEntry, ScrapeProductsError, UpdateProductArgs, UpdateProductError};
+use crate::domain::pagination::{Page, PageRequest};
+use crate::domain::product_history::GetProductError;
+use crate::domain::site::{SiteNotifier, SiteService};
+use crate::domain::user::models::user::User;
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait DigitalService: SiteService {
+    async fn follow_circle(&self, user: &User, req: &CircleArgs) -> Result<(), FollowCircleError>;
+    async fn unfollow_circle(&self, user: &User, circle_id: i32) -> Result<(), UnfollowCircleError>;
+    async fn get_circles(&self, user: &User) -> Result<Vec<Circle>, GetCirclesError>;
+    async fn get_followed_circles(&self, user: &User) -> Result<Vec<Circle>, GetCirclesError>;
+    async fn get_circles_page(&self, user: &User, following: Option<bool>, page: PageRequest) -> Result<Page<Circle>, GetCirclesError>;
+
+    async fn get_products_page(&self, page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    async fn get_products_by_circle(&self, circle_id: i32) -> Result<Vec<Product>, GetProductsError>;
+    async fn get_products_page_by_circle(&self, circle_id: i32, page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    async fn get_products_page_by_circles(&self, circle_ids: &[i32], page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    async fn get_product(&self, product_id: i32) -> Result<Product, GetProductError>;
+    async fn get_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError>;
+}
+
+#[async_trait]
+pub trait DigitalRepository: Clone + Send + Sync + 'static {
+    async fn follow_digital_circle(&self, user_id: i32, req: &CircleArgs) -> Result<(), FollowCircleError>;
+    async fn unfollow_digital_circle(&self, user_id: i32, circle_id: i32) -> Result<(), UnfollowCircleError>;
+    async fn get_digital_circles(&self, user_id: i32) -> Result<Vec<Circle>, GetCirclesError>;
+    async fn get_digital_circles_page(&self, user_id: i32, following: Option<bool>, page: PageRequest) -> Result<Page<Circle>, GetCirclesError>;
+    async fn get_followed_digital_circles(&self) -> Result<Vec<FollowedCircle>, GetCirclesError>;
+    async fn set_digital_circle_name(&self, circle_id: i32, name: &str) -> Result<(), SetCircleNameError>;
+
+    async fn create_digital_product(&self, req: &CreateProductArgs) -> Result<Product, CreateProductError>;
+    async fn update_digital_product(&self, req: &UpdateProductArgs) -> Result<Product, UpdateProductError>;
+    async fn get_digital_product(&self, product_id: i32) -> Result<Product, GetProductError>;
+    async fn get_digital_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError>;
+    async fn get_digital_products(&self) -> Result<Vec<Product>, GetProductsError>;
+    async fn get_digital_products_by_circle(&self, circle_id: i32) -> Result<Vec<Product>, GetProductsError>;
+    async fn get_digital_products_page(&self, page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    async fn get_digital_products_page_by_circle(&self, circle_id: i32, page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    /// Works of any of the circles, the newest first.
+    async fn get_digital_products_page_by_circles(&self, circle_ids: &[i32], page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+}
+
+#[async_trait]
+pub trait DigitalScraper: Clone + Send + Sync + 'static {
+    /// The name of the circle and its works with their current prices.
+    async fn get_circle(&self, circle: &CircleArgs) -> Result<CircleData, ScrapeProductsError>;
+}
+
+#[async_trait]
+pub trait DigitalNotifier: SiteNotifier<Product> {
+    async fn discounted_products<Q: AsRef<Product> + Sync>(&self, circle: &str, products: &[Q]);
+}
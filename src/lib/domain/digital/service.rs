@@ -0,0 +1,378 @@
+use crate::domain::digital::models::circle::{Circle, CircleArgs, FollowCircleError, GetCirclesError, UnfollowCircleError};
+use crate::domain::digital::models::product::{CreateProductArgs, GetProductsError, Product, ProductHistoryEntry, ScrapeProductsError, UpdateProductArgs};
+use crate::domain::digital::ports::{DigitalNotifier, DigitalRepository, DigitalScraper, DigitalService};
+use crate::domain::digital::SITE;
+use crate::domain::image::ports::ImageCache;
+use crate::domain::pagination::{Page, PageRequest};
+use crate::domain::product_history::{GetProductError, NotificationKind};
+use crate::domain::site::{ScrapeGuard, ScrapeLock, ScrapeSiteError, Site, SiteCore, SiteRepository, SiteService};
+use crate::domain::user::models::user::User;
+use async_trait::async_trait;
+use log::info;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct DigitalServiceImpl<R, N, S, I>
+where
+    R: DigitalRepository + SiteRepository,
+    N: DigitalNotifier,
+    S: DigitalScraper,
+    I: ImageCache
+{
+    repo: R,
+    scraper: S,
+    core: SiteCore<N, I>,
+}
+
+impl<R, N, S, I> DigitalServiceImpl<R, N, S, I>
+where
+    R: DigitalRepository + SiteRepository,
+    N: DigitalNotifier,
+    S: DigitalScraper,
+    I: ImageCache
+{
+    pub fn new(repo: R, scraper: S, core: SiteCore<N, I>) -> Self {
+        Self { repo, scraper, core }
+    }
+}
+
+#[async_trait]
+impl<R, N, S, I> SiteService for DigitalServiceImpl<R, N, S, I>
+where
+    R: DigitalRepository + SiteRepository,
+    N: DigitalNotifier,
+    S: DigitalScraper,
+    I: ImageCache
+{
+    fn site(&self) -> Site {
+        SITE
+    }
+
+    fn scrape_lock(&self) -> &ScrapeLock {
+        self.core.scrape_lock()
+    }
+
+    async fn scrape(&self, _guard: &ScrapeGuard) -> Result<(), ScrapeSiteError> {
+        info!("scrape available products");
+        self.scrape_followed_circles().await
+            .map_err(|e| anyhow::Error::new(e).into())
+    }
+}
+
+#[async_trait]
+impl<R, N, S, I> DigitalService for DigitalServiceImpl<R, N, S, I>
+where
+    R: DigitalRepository + SiteRepository,
+    N: DigitalNotifier,
+    S: DigitalScraper,
+    I: ImageCache
+{
+    async fn follow_circle(&self, user: &User, circle_args: &CircleArgs) -> Result<(), FollowCircleError> {
+        if circle_args.code().is_empty() {
+            return Err(FollowCircleError::EmptyCode);
+        }
+        info!("follow {} circle '{}' for '{}'", circle_args.store(), circle_args.code(), user.username());
+        self.repo.follow_digital_circle(user.id(), circle_args).await
+    }
+
+    async fn unfollow_circle(&self, user: &User, circle_id: i32) -> Result<(), UnfollowCircleError> {
+        info!("unfollow circle with id '{}' for '{}'", circle_id, user.username());
+        self.repo.unfollow_digital_circle(user.id(), circle_id).await
+    }
+
+    async fn get_circles(&self, user: &User) -> Result<Vec<Circle>, GetCirclesError> {
+        info!("get circles for '{}'", user.username());
+        self.repo.get_digital_circles(user.id()).await
+    }
+
+    async fn get_followed_circles(&self, user: &User) -> Result<Vec<Circle>, GetCirclesError> {
+        info!("get followed circles for '{}'", user.username());
+        let circles = self.repo.get_digital_circles(user.id()).await?;
+        Ok(
+            circles.into_iter()
+                .filter(|c| c.following())
+                .collect()
+        )
+    }
+
+    async fn get_circles_page(&self, user: &User, following: Option<bool>, page: PageRequest) -> Result<Page<Circle>, GetCirclesError> {
+        info!("get page {} of circles for '{}'", page.page(), user.username());
+        self.repo.get_digital_circles_page(user.id(), following, page).await
+    }
+
+    async fn get_products_page(&self, page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        info!("get page {} of products", page.page());
+        self.repo.get_digital_products_page(page).await
+    }
+
+    async fn get_products_by_circle(&self, circle_id: i32) -> Result<Vec<Product>, GetProductsError> {
+        info!("get products by circle with id '{}'", circle_id);
+        self.repo.get_digital_products_by_circle(circle_id).await
+    }
+
+    async fn get_products_page_by_circle(&self, circle_id: i32, page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        info!("get page {} of products by circle with id '{}'", page.page(), circle_id);
+        self.repo.get_digital_products_page_by_circle(circle_id, page).await
+    }
+
+    async fn get_products_page_by_circles(&self, circle_ids: &[i32], page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        info!("get page {} of products by circles with ids {:?}", page.page(), circle_ids);
+        self.repo.get_digital_products_page_by_circles(circle_ids, page).await
+    }
+
+    async fn get_product(&self, product_id: i32) -> Result<Product, GetProductError> {
+        info!("get product with id '{}'", product_id);
+        self.repo.get_digital_product(product_id).await
+    }
+
+    async fn get_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError> {
+        info!("get history of product with id '{}'", product_id);
+        self.repo.get_digital_product_history(product_id).await
+    }
+}
+
+impl<R, N, S, I> DigitalServiceImpl<R, N, S, I>
+where
+    R: DigitalRepository + SiteRepository,
+    N: DigitalNotifier,
+    S: DigitalScraper,
+    I: ImageCache
+{
+    /// Digital works are never sold out, so new works and the start of their sales are notified.
+    async fn scrape_followed_circles(&self) -> Result<(), ScrapeProductsError> {
+        let followed_circles = self.repo.get_followed_digital_circles().await?;
+        // collaborations are listed by every circle taking part
+        let mut known_products = self.repo.get_digital_products().await?
+            .into_iter()
+            .map(|p| (p.url().to_owned(), p))
+            .collect::<HashMap<_, _>>();
+        for followed_circle in followed_circles.iter() {
+            let circle = followed_circle.circle();
+            info!("scrape available products for {} circle '{}'", circle.store(), circle.code());
+            let circle_data = self.scraper.get_circle(&CircleArgs::new(circle.store(), circle.code().to_owned())).await?;
+            if !circle_data.name().is_empty() && circle_data.name() != circle.name() {
+                self.repo.set_digital_circle_name(circle.id(), circle_data.name()).await?;
+            }
+            let target = format!("{} ({})", circle_data.name(), circle.store());
+
+            let mut new_products = Vec::<Product>::new();
+            let mut discounted_products = Vec::<Product>::new();
+            for work in circle_data.works() {
+                match known_products.get(work.url()) {
+                    None => {
+                        let product = self.repo.create_digital_product(&CreateProductArgs::new(circle.id(), circle.store(), work.clone())).await?;
+                        let product = self.core.cache_product_image(&self.repo, product).await;
+                        new_products.push(product.clone());
+                        known_products.insert(product.url().to_owned(), product);
+                    }
+                    Some(product) => {
+                        if !product.has_changes(work) {
+                            continue;
+                        }
+                        let discount_started = product.is_discount_started(work);
+                        let product = self.repo.update_digital_product(&UpdateProductArgs::new(work.clone())).await?;
+                        let product = match discount_started {
+                            true => {
+                                let product = self.core.cache_product_image(&self.repo, product).await;
+                                discounted_products.push(product.clone());
+                                product
+                            }
+                            false => product,
+                        };
+                        known_products.insert(product.url().to_owned(), product);
+                    }
+                }
+            }
+            info!("found '{}' new and '{}' discounted products for {}", new_products.len(), discounted_products.len(), target);
+
+            for (notifier, products) in self.core.recipients(followed_circle.followers(), &discounted_products) {
+                notifier.discounted_products(&target, &products).await;
+            }
+            self.core.add_notifications(&self.repo, followed_circle.followers(), NotificationKind::DiscountedProduct, &discounted_products).await?;
+            self.core.notify(&self.repo, &target, followed_circle.followers(), &new_products, &[]).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::domain::digital::models::circle::{FollowedCircle, SetCircleNameError, Store};
+    use crate::domain::digital::models::product::{CircleData, CreateProductError, Sale, UpdateProductError, WorkData};
+    use crate::domain::test_util::{user, TestImageCache, TestNotifier, TestRepo};
+    use chrono::Utc;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Default)]
+    struct Works {
+        circles: Vec<FollowedCircle>,
+        products: Vec<Product>,
+    }
+
+    #[async_trait]
+    impl DigitalRepository for TestRepo<Works> {
+        async fn follow_digital_circle(&self, _user_id: i32, _req: &CircleArgs) -> Result<(), FollowCircleError> {
+            unimplemented!()
+        }
+
+        async fn unfollow_digital_circle(&self, _user_id: i32, _circle_id: i32) -> Result<(), UnfollowCircleError> {
+            unimplemented!()
+        }
+
+        async fn get_digital_circles(&self, _user_id: i32) -> Result<Vec<Circle>, GetCirclesError> {
+            unimplemented!()
+        }
+
+        async fn get_digital_circles_page(&self, _user_id: i32, _following: Option<bool>, _page: PageRequest) -> Result<Page<Circle>, GetCirclesError> {
+            unimplemented!()
+        }
+
+        async fn get_followed_digital_circles(&self) -> Result<Vec<FollowedCircle>, GetCirclesError> {
+            Ok(self.with_site(|s| s.circles.clone()))
+        }
+
+        async fn set_digital_circle_name(&self, circle_id: i32, name: &str) -> Result<(), SetCircleNameError> {
+            self.with_site(|s| {
+                let followed = s.circles.iter_mut()
+                    .find(|f| f.circle().id() == circle_id)
+                    .ok_or(SetCircleNameError::UnknownCircle { id: circle_id })?;
+                let c = followed.circle();
+                let circle = Circle::new(c.id(), c.date_added(), c.store(), c.code().to_owned(), name.to_owned(), c.following(), c.date_followed());
+                *followed = FollowedCircle::new(circle, followed.followers().to_vec());
+                Ok(())
+            })
+        }
+
+        async fn create_digital_product(&self, req: &CreateProductArgs) -> Result<Product, CreateProductError> {
+            self.with_site(|s| {
+                let work = req.work();
+                if s.products.iter().any(|p| p.url() == work.url()) {
+                    return Err(CreateProductError::DuplicateProduct { url: work.url().to_owned(), title: work.title().to_owned() });
+                }
+                let product = Product::new(
+                    s.products.len() as i32 + 1, Utc::now(), req.circle_id(), req.store(), work.url().to_owned(), work.title().to_owned(),
+                    work.circle_name().to_owned(), work.image_url().to_owned(), work.list_price(), work.sale().cloned()
+                ).with_date_discounted(work.sale().map(|_| Utc::now()));
+                s.products.push(product.clone());
+                Ok(product)
+            })
+        }
+
+        async fn update_digital_product(&self, req: &UpdateProductArgs) -> Result<Product, UpdateProductError> {
+            self.with_site(|s| {
+                let product = s.products.iter_mut()
+                    .find(|p| p.url() == req.url())
+                    .ok_or(UpdateProductError::ProductMissing { url: req.url().to_owned() })?;
+                let work = req.work();
+                let date_discounted = match product.is_discount_started(work) {
+                    true => Some(Utc::now()),
+                    false => product.date_discounted(),
+                };
+                *product = Product::new(
+                    product.id(), product.date_added(), product.circle_id(), product.store(), product.url().to_owned(), work.title().to_owned(),
+                    work.circle_name().to_owned(), work.image_url().to_owned(), work.list_price(), work.sale().cloned()
+                ).with_date_discounted(date_discounted);
+                Ok(product.clone())
+            })
+        }
+
+        async fn get_digital_product(&self, _product_id: i32) -> Result<Product, GetProductError> {
+            unimplemented!()
+        }
+
+        async fn get_digital_product_history(&self, _product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError> {
+            unimplemented!()
+        }
+
+        async fn get_digital_products(&self) -> Result<Vec<Product>, GetProductsError> {
+            Ok(self.with_site(|s| s.products.clone()))
+        }
+
+        async fn get_digital_products_by_circle(&self, circle_id: i32) -> Result<Vec<Product>, GetProductsError> {
+            Ok(self.with_site(|s| s.products.iter().filter(|p| p.circle_id() == circle_id).cloned().collect()))
+        }
+
+        async fn get_digital_products_page(&self, _page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+            unimplemented!()
+        }
+
+        async fn get_digital_products_page_by_circle(&self, _circle_id: i32, _page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+            unimplemented!()
+        }
+
+        async fn get_digital_products_page_by_circles(&self, _circle_ids: &[i32], _page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+            unimplemented!()
+        }
+    }
+
+    /// Finds the works set for the circle code.
+    #[derive(Debug, Clone, Default)]
+    struct TestScraper {
+        circles: Arc<Mutex<HashMap<String, CircleData>>>,
+    }
+
+    impl TestScraper {
+        fn set_works(&self, code: &str, works: Vec<WorkData>) {
+            self.circles.lock().unwrap().insert(code.to_owned(), CircleData::new("mafuyu".to_owned(), works));
+        }
+    }
+
+    #[async_trait]
+    impl DigitalScraper for TestScraper {
+        async fn get_circle(&self, circle: &CircleArgs) -> Result<CircleData, ScrapeProductsError> {
+            Ok(self.circles.lock().unwrap().get(circle.code()).cloned().unwrap_or_else(|| CircleData::new(String::new(), Vec::new())))
+        }
+    }
+
+    #[async_trait]
+    impl DigitalNotifier for TestNotifier {
+        async fn discounted_products<Q: AsRef<Product> + Sync>(&self, circle: &str, products: &[Q]) {
+            self.record::<Product, Q>(NotificationKind::DiscountedProduct, circle, products);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scrape_notifies_discount_once() {
+        let circle = Circle::new(1, Utc::now(), Store::Dlsite, "RG12345".to_owned(), "RG12345".to_owned(), true, Some(Utc::now()));
+        let repo = TestRepo::new(Works { circles: vec![FollowedCircle::new(circle, vec![user(1, "alice")])], ..Default::default() });
+        let scraper = TestScraper::default();
+        scraper.set_works("RG12345", vec![work("RJ01234567", None)]);
+        let (notifier, alice_notifier) = (TestNotifier::default(), TestNotifier::default());
+        let core = SiteCore::new(notifier.clone(), TestImageCache).with_user_notifiers(HashMap::from([("alice".to_owned(), alice_notifier.clone())]));
+        let service = DigitalServiceImpl::new(repo.clone(), scraper.clone(), core);
+        let target = "mafuyu (DLsite)".to_owned();
+
+        service.scrape(&service.scrape_lock().lock().await).await.unwrap();
+        assert_eq!(notifier.take(), vec![(NotificationKind::NewProduct, target.clone(), vec![url("RJ01234567")])]);
+        assert_eq!(repo.with_site(|s| s.circles[0].circle().name().to_owned()), "mafuyu");
+
+        scraper.set_works("RG12345", vec![work("RJ01234567", Some(Sale::new(770, None)))]);
+        service.scrape(&service.scrape_lock().lock().await).await.unwrap();
+        let discounted = vec![(NotificationKind::DiscountedProduct, target.clone(), vec![url("RJ01234567")])];
+        assert_eq!(notifier.take(), discounted);
+
+        service.scrape(&service.scrape_lock().lock().await).await.unwrap();
+        scraper.set_works("RG12345", vec![work("RJ01234567", Some(Sale::new(550, None)))]);
+        service.scrape(&service.scrape_lock().lock().await).await.unwrap();
+        assert!(notifier.take().is_empty());
+        assert_eq!(repo.with_site(|s| s.products[0].price()), 550);
+        assert_eq!(alice_notifier.take().into_iter().filter(|n| n.0 == NotificationKind::DiscountedProduct).collect::<Vec<_>>(), discounted);
+        assert_eq!(repo.notifications(), vec![(1, NotificationKind::NewProduct, 1), (1, NotificationKind::DiscountedProduct, 1)]);
+    }
+
+    fn work(work_id: &str, sale: Option<Sale>) -> WorkData {
+        WorkData::new(
+            url(work_id),
+            "mafuyu_title".to_owned(),
+            "mafuyu".to_owned(),
+            format!("https://img.dlsite.jp/modpub/images2/work/doujin/RJ01235000/{}_img_main.jpg", work_id),
+            1100,
+            sale
+        )
+    }
+
+    fn url(work_id: &str) -> String {
+        format!("https://www.dlsite.com/maniax/work/=/product_id/{}.html", work_id)
+    }
+}
@@ -0,0 +1,58 @@
+use crate::domain::site::Site;
+use thiserror::Error;
+
+/// Most bits the perceptual hashes of two images of the same product differ by, e.g. after being scaled or re-encoded.
+pub const MAX_DUPLICATE_DISTANCE: u32 = 8;
+
+pub fn is_duplicate_image(image_phash: u64, other_image_phash: u64) -> bool {
+    (image_phash ^ other_image_phash).count_ones() <= MAX_DUPLICATE_DISTANCE
+}
+
+/// A product of any site, found by its image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Listing {
+    site: Site,
+    product_id: i32,
+    title: String,
+    url: String,
+    image_phash: u64,
+    notified_user_ids: Vec<i32>,
+}
+
+impl Listing {
+    pub fn new(site: Site, product_id: i32, title: String, url: String, image_phash: u64, notified_user_ids: Vec<i32>) -> Self {
+        Self { site, product_id, title, url, image_phash, notified_user_ids }
+    }
+
+    pub fn site(&self) -> Site { self.site }
+    pub fn product_id(&self) -> i32 { self.product_id }
+    pub fn title(&self) -> &str { &self.title }
+    pub fn url(&self) -> &str { &self.url }
+    pub fn image_phash(&self) -> u64 { self.image_phash }
+    /// Users that got a new or restocked notification for the product.
+    pub fn notified_user_ids(&self) -> &[i32] { &self.notified_user_ids }
+
+    pub fn is_product(&self, site: Site, product_id: i32) -> bool {
+        self.site == site && self.product_id == product_id
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum GetListingsError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_is_duplicate_image() {
+        assert!(is_duplicate_image(0x63638c934c5cf2e3, 0x63638c934c5cf2e3));
+        assert!(is_duplicate_image(0x63638c934c5cf2e3, 0x73638c934c1cf2e3));
+        assert!(is_duplicate_image(0, 0xFF));
+        assert!(!is_duplicate_image(0, 0x1FF));
+        assert!(!is_duplicate_image(0x63638c934c5cf2e3, 0xbf00bfa0bf00bf41));
+    }
+}
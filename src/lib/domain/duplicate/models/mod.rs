@@ -0,0 +1 @@
+pub mod listing;
\ No newline at end of file
@@ -0,0 +1,15 @@
+use crate::domain::duplicate::models::listing::{GetListingsError, Listing};
+use crate::domain::site::Site;
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait DuplicateService: Send + Sync + 'static {
+    /// The other listings of the product, empty if the product has no perceptual hash.
+    async fn get_duplicate_listings(&self, site: Site, product_id: i32, image_phash: Option<u64>) -> Result<Vec<Listing>, GetListingsError>;
+}
+
+#[async_trait]
+pub trait DuplicateRepository: Clone + Send + Sync + 'static {
+    /// Products of all sites with an image similar to the one of `image_phash`.
+    async fn get_listings_by_image(&self, image_phash: u64) -> Result<Vec<Listing>, GetListingsError>;
+}
@@ -0,0 +1,41 @@
+use crate::domain::duplicate::models::listing::{GetListingsError, Listing};
+use crate::domain::site::Site;
+use crate::domain::duplicate::ports::{DuplicateRepository, DuplicateService};
+use async_trait::async_trait;
+use log::info;
+
+#[derive(Debug, Clone)]
+pub struct DuplicateServiceImpl<R>
+where
+    R: DuplicateRepository
+{
+    repo: R,
+}
+
+impl<R> DuplicateServiceImpl<R>
+where
+    R: DuplicateRepository
+{
+    pub fn new(repo: R) -> Self {
+        Self { repo }
+    }
+}
+
+#[async_trait]
+impl<R> DuplicateService for DuplicateServiceImpl<R>
+where
+    R: DuplicateRepository
+{
+    async fn get_duplicate_listings(&self, site: Site, product_id: i32, image_phash: Option<u64>) -> Result<Vec<Listing>, GetListingsError> {
+        let Some(image_phash) = image_phash else {
+            return Ok(Vec::new());
+        };
+        info!("get duplicate listings of {} product with id '{}'", site, product_id);
+        let listings = self.repo.get_listings_by_image(image_phash).await?;
+        Ok(
+            listings.into_iter()
+                .filter(|l| !l.is_product(site, product_id))
+                .collect()
+        )
+    }
+}
@@ -0,0 +1,326 @@
+use crate::domain::availability::Availability;
+use crate::domain::figure::models::source::{GetSourcesError, SetSourceNameError, Store};
+use crate::domain::figure::SITE;
+use crate::domain::product_history;
+use crate::domain::product_history::AddNotificationsError;
+use crate::domain::image::models::image::CachedImage;
+use crate::domain::site::{Site, SiteProduct};
+use crate::outbound::figure_scraper::ParseError;
+use chrono::{DateTime, Duration, FixedOffset, NaiveDateTime, Utc};
+use thiserror::Error;
+
+/// Both stores announce preorder windows in Japan Standard Time.
+pub fn store_now() -> NaiveDateTime {
+    Utc::now().with_timezone(&FixedOffset::east_opt(9 * 3600).unwrap()).naive_local()
+}
+
+/// The announced preorder window of a product, either end is `None` when the store does not announce it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreorderWindow {
+    start: Option<NaiveDateTime>,
+    end: Option<NaiveDateTime>,
+}
+
+impl PreorderWindow {
+    pub fn new(start: Option<NaiveDateTime>, end: Option<NaiveDateTime>) -> Self {
+        Self { start, end }
+    }
+
+    pub fn start(&self) -> Option<NaiveDateTime> { self.start }
+    pub fn end(&self) -> Option<NaiveDateTime> { self.end }
+
+    pub fn is_open(&self, now: NaiveDateTime) -> bool {
+        self.start.is_none_or(|s| s <= now) && self.end.is_none_or(|e| now < e)
+    }
+
+    /// Figures are listed before they can be preordered and stay listed after, only an open window counts.
+    pub fn availability(window: Option<&PreorderWindow>, now: NaiveDateTime) -> Availability {
+        match window.is_some_and(|w| w.is_open(now)) {
+            true => Availability::Preorder,
+            false => Availability::NotAvailable,
+        }
+    }
+
+    /// Whether the window is open and closes within `notice`.
+    pub fn is_closing(&self, now: NaiveDateTime, notice: Duration) -> bool {
+        self.is_open(now) && self.end.is_some_and(|e| e - notice <= now)
+    }
+}
+
+/// A figure of HobbySearch or Good Smile, `preorder` is `None` while it cannot be preordered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Product {
+    id: i32,
+    date_added: DateTime<Utc>,
+    store: Store,
+    url: String,
+    title: String,
+    maker_name: String,
+    image_url: String,
+    price: Option<i32>,
+    release_date: Option<String>,
+    preorder: Option<PreorderWindow>,
+    date_preorder_opened: Option<DateTime<Utc>>,
+    date_preorder_closing: Option<DateTime<Utc>>,
+    image_hash: Option<String>,
+    image_phash: Option<u64>,
+}
+
+impl Product {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(id: i32, date_added: DateTime<Utc>, store: Store, url: String, title: String, maker_name: String, image_url: String, price: Option<i32>, release_date: Option<String>, preorder: Option<PreorderWindow>) -> Self {
+        Self { id, date_added, store, url, title, maker_name, image_url, price, release_date, preorder, date_preorder_opened: None, date_preorder_closing: None, image_hash: None, image_phash: None }
+    }
+
+    pub fn with_preorder_notices(mut self, date_preorder_opened: Option<DateTime<Utc>>, date_preorder_closing: Option<DateTime<Utc>>) -> Self {
+        self.date_preorder_opened = date_preorder_opened;
+        self.date_preorder_closing = date_preorder_closing;
+        self
+    }
+
+    pub fn with_image_hash(mut self, image_hash: Option<String>) -> Self {
+        self.image_hash = image_hash;
+        self
+    }
+
+    pub fn with_image_phash(mut self, image_phash: Option<u64>) -> Self {
+        self.image_phash = image_phash;
+        self
+    }
+
+    pub fn id(&self) -> i32 { self.id }
+    pub fn date_added(&self) -> DateTime<Utc> { self.date_added }
+    pub fn store(&self) -> Store { self.store }
+    pub fn url(&self) -> &str { &self.url }
+    pub fn title(&self) -> &str { &self.title }
+    pub fn maker_name(&self) -> &str { &self.maker_name }
+    pub fn image_url(&self) -> &str { &self.image_url }
+    /// Price in yen, `None` until it is announced.
+    pub fn price(&self) -> Option<i32> { self.price }
+    /// The release as the store announces it, e.g. `2027年03月`.
+    pub fn release_date(&self) -> Option<&str> { self.release_date.as_deref() }
+    pub fn preorder(&self) -> Option<&PreorderWindow> { self.preorder.as_ref() }
+    /// When the opening of the current preorder window was notified.
+    pub fn date_preorder_opened(&self) -> Option<DateTime<Utc>> { self.date_preorder_opened }
+    /// When the closing of the current preorder window was notified.
+    pub fn date_preorder_closing(&self) -> Option<DateTime<Utc>> { self.date_preorder_closing }
+    pub fn image_hash(&self) -> Option<&str> { self.image_hash.as_deref() }
+    pub fn image_phash(&self) -> Option<u64> { self.image_phash }
+
+    pub fn is_preorder_open(&self, now: NaiveDateTime) -> bool {
+        self.preorder.as_ref().is_some_and(|p| p.is_open(now))
+    }
+
+    pub fn has_changes(&self, item: &ItemData) -> bool {
+        self.title != item.title() || self.price != item.price() || self.release_date.as_deref() != item.release_date()
+            || self.preorder.as_ref() != item.preorder()
+    }
+}
+
+/// The availability of a figure is its preorder window, `None` while it cannot be preordered.
+pub type ProductHistoryEntry = product_history::ProductHistoryEntry<Option<PreorderWindow>, Option<i32>>;
+
+impl AsRef<Product> for Product {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+impl SiteProduct for Product {
+    const SITE: Site = SITE;
+
+    fn id(&self) -> i32 { self.id }
+    fn title(&self) -> &str { &self.title }
+    fn url(&self) -> &str { &self.url }
+    fn image_url(&self) -> &str { &self.image_url }
+    fn image_hash(&self) -> Option<&str> { self.image_hash.as_deref() }
+    fn image_phash(&self) -> Option<u64> { self.image_phash }
+
+    fn with_image(self, image: &CachedImage) -> Self {
+        self.with_image_hash(Some(image.hash().to_owned())).with_image_phash(image.perceptual_hash())
+    }
+
+    fn summary(&self) -> String {
+        let price = self.price.map(|p| format!("¥{}", p)).unwrap_or_else(|| "price TBA".to_owned());
+        let release = self.release_date.as_deref().map(|r| format!(", release {}", r)).unwrap_or_default();
+        let preorder = match self.preorder.as_ref().and_then(|p| p.end()) {
+            Some(end) => format!("\npreorders until {} JST", end.format("%Y-%m-%d %H:%M")),
+            None => String::new(),
+        };
+        format!("{} — {}\n{}{}{}", self.maker_name, self.store, price, release, preorder)
+    }
+}
+
+/// A figure as listed by the store.
+#[derive(Debug, Clone)]
+pub struct ItemData {
+    url: String,
+    title: String,
+    maker_name: String,
+    image_url: String,
+    price: Option<i32>,
+    release_date: Option<String>,
+    preorder: Option<PreorderWindow>,
+}
+
+impl ItemData {
+    pub fn new(url: String, title: String, maker_name: String, image_url: String, price: Option<i32>, release_date: Option<String>, preorder: Option<PreorderWindow>) -> Self {
+        Self { url, title, maker_name, image_url, price, release_date, preorder }
+    }
+
+    pub fn url(&self) -> &str { &self.url }
+    pub fn title(&self) -> &str { &self.title }
+    pub fn maker_name(&self) -> &str { &self.maker_name }
+    pub fn image_url(&self) -> &str { &self.image_url }
+    pub fn price(&self) -> Option<i32> { self.price }
+    pub fn release_date(&self) -> Option<&str> { self.release_date.as_deref() }
+    pub fn preorder(&self) -> Option<&PreorderWindow> { self.preorder.as_ref() }
+}
+
+/// The products of a maker or series as listed by the store, newest first.
+#[derive(Debug, Clone)]
+pub struct SourceData {
+    name: String,
+    items: Vec<ItemData>,
+}
+
+impl SourceData {
+    pub fn new(name: String, items: Vec<ItemData>) -> Self {
+        Self { name, items }
+    }
+
+    pub fn name(&self) -> &str { &self.name }
+    pub fn items(&self) -> &[ItemData] { &self.items }
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateProductArgs {
+    source_id: i32,
+    store: Store,
+    item: ItemData,
+}
+
+impl CreateProductArgs {
+    pub fn new(source_id: i32, store: Store, item: ItemData) -> Self {
+        Self { source_id, store, item }
+    }
+
+    pub fn source_id(&self) -> i32 { self.source_id }
+    pub fn store(&self) -> Store { self.store }
+    pub fn item(&self) -> &ItemData { &self.item }
+}
+
+#[derive(Debug, Clone)]
+pub struct UpdateProductArgs {
+    item: ItemData,
+}
+
+impl UpdateProductArgs {
+    pub fn new(item: ItemData) -> Self {
+        Self { item }
+    }
+
+    pub fn url(&self) -> &str { self.item.url() }
+    pub fn item(&self) -> &ItemData { &self.item }
+}
+
+#[derive(Debug, Error)]
+pub enum CreateProductError {
+    #[error("Product '{title}' ({url}) already exists")]
+    DuplicateProduct { url: String, title: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum UpdateProductError {
+    #[error("Product {url} does not exist")]
+    ProductMissing { url: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum AddProductSourceError {
+    #[error("Product {url} does not exist")]
+    ProductMissing { url: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum SetPreorderNoticeError {
+    #[error("Product with id {id} does not exist")]
+    ProductMissing { id: i32 },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum GetProductsError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum ScrapeProductsError {
+    #[error(transparent)]
+    ParseError(#[from] ParseError),
+    #[error(transparent)]
+    GetSourcesError(#[from] GetSourcesError),
+    #[error(transparent)]
+    SetSourceNameError(#[from] SetSourceNameError),
+    #[error(transparent)]
+    GetProductError(#[from] GetProductsError),
+    #[error(transparent)]
+    CreateProductError(#[from] CreateProductError),
+    #[error(transparent)]
+    UpdateProductError(#[from] UpdateProductError),
+    #[error(transparent)]
+    AddProductSourceError(#[from] AddProductSourceError),
+    #[error(transparent)]
+    SetPreorderNoticeError(#[from] SetPreorderNoticeError),
+    #[error(transparent)]
+    AddNotificationsError(#[from] AddNotificationsError),
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_preorder_window() {
+        let date = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+        let window = PreorderWindow::new(Some(date("2026-10-01 12:00")), Some(date("2026-11-13 21:00")));
+        assert!(!window.is_open(date("2026-10-01 11:59")));
+        assert!(window.is_open(date("2026-10-01 12:00")));
+        assert!(!window.is_closing(date("2026-11-11 20:59"), Duration::hours(48)));
+        assert!(window.is_closing(date("2026-11-11 21:00"), Duration::hours(48)));
+        assert!(!window.is_open(date("2026-11-13 21:00")));
+        assert!(!window.is_closing(date("2026-11-13 21:00"), Duration::hours(48)));
+
+        let unannounced = PreorderWindow::new(None, None);
+        assert!(unannounced.is_open(date("2026-10-01 12:00")));
+        assert!(!unannounced.is_closing(date("2026-10-01 12:00"), Duration::hours(48)));
+    }
+
+    #[test]
+    fn test_preorder_changes() {
+        let date = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+        let window = PreorderWindow::new(Some(date("2026-10-01 12:00")), Some(date("2026-11-13 21:00")));
+        let product = Product::new(1, Utc::now(), Store::Gsc, "https://www.goodsmile.com/ja/product/12345".to_owned(), "title".to_owned(),
+                                   "maker".to_owned(), "https://images.goodsmile.info/12345.jpg".to_owned(), Some(5800), Some("2027年03月".to_owned()), None);
+        let item = |preorder| ItemData::new(product.url().to_owned(), "title".to_owned(), "maker".to_owned(), product.image_url().to_owned(), Some(5800), Some("2027年03月".to_owned()), preorder);
+        assert!(!product.has_changes(&item(None)));
+        assert!(product.has_changes(&item(Some(window.clone()))));
+        assert_eq!(product.summary(), "maker — GSC\n¥5800, release 2027年03月");
+
+        let product = Product::new(1, Utc::now(), Store::Gsc, product.url().to_owned(), "title".to_owned(), "maker".to_owned(),
+                                   product.image_url().to_owned(), Some(5800), Some("2027年03月".to_owned()), Some(window.clone()));
+        assert!(product.is_preorder_open(date("2026-11-01 00:00")));
+        assert!(product.has_changes(&item(Some(PreorderWindow::new(window.start(), Some(date("2026-11-20 21:00")))))));
+        assert_eq!(product.summary(), "maker — GSC\n¥5800, release 2027年03月\npreorders until 2026-11-13 21:00 JST");
+    }
+}
@@ -0,0 +1,196 @@
+use crate::domain::user::models::user::User;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter, EnumString};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumString, EnumIter, Serialize, Deserialize)]
+pub enum Store {
+    HobbySearch,
+    #[strum(serialize = "GSC")]
+    #[serde(rename = "GSC")]
+    Gsc,
+}
+
+impl TryFrom<String> for Store {
+    type Error = strum::ParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::try_from(value.as_str())
+    }
+}
+
+impl From<Store> for String {
+    fn from(value: Store) -> Self {
+        value.to_string()
+    }
+}
+
+/// Both stores list the figures of a maker, which Good Smile calls a brand, and of a series like Nendoroid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumString, EnumIter, Serialize, Deserialize)]
+pub enum SourceKind {
+    Maker,
+    Series,
+}
+
+impl TryFrom<String> for SourceKind {
+    type Error = strum::ParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::try_from(value.as_str())
+    }
+}
+
+impl From<SourceKind> for String {
+    fn from(value: SourceKind) -> Self {
+        value.to_string()
+    }
+}
+
+/// A maker or series of a store, `following` is whether the user follows it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Source {
+    id: i32,
+    date_added: DateTime<Utc>,
+    store: Store,
+    kind: SourceKind,
+    code: String,
+    name: String,
+    following: bool,
+    date_followed: Option<DateTime<Utc>>,
+}
+
+impl Source {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(id: i32, date_added: DateTime<Utc>, store: Store, kind: SourceKind, code: String, name: String, following: bool, date_followed: Option<DateTime<Utc>>) -> Self {
+        Source { id, date_added, store, kind, code, name, following, date_followed }
+    }
+
+    pub fn id(&self) -> i32 { self.id }
+    pub fn date_added(&self) -> DateTime<Utc> { self.date_added }
+    pub fn store(&self) -> Store { self.store }
+    pub fn kind(&self) -> SourceKind { self.kind }
+    /// The id of the maker or series in the store's search.
+    pub fn code(&self) -> &str { &self.code }
+    /// The name shown by the store, the code until the source was first scraped.
+    pub fn name(&self) -> &str { &self.name }
+    pub fn following(&self) -> bool { self.following }
+    pub fn date_followed(&self) -> Option<DateTime<Utc>> { self.date_followed }
+
+    pub fn url(&self) -> String {
+        SourceArgs::new(self.store, self.kind, self.code.clone()).url()
+    }
+}
+
+/// Source followed by at least one user, scraped once for all of its followers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FollowedSource {
+    source: Source,
+    followers: Vec<User>,
+}
+
+impl FollowedSource {
+    pub fn new(source: Source, followers: Vec<User>) -> Self {
+        FollowedSource { source, followers }
+    }
+
+    pub fn source(&self) -> &Source { &self.source }
+    pub fn followers(&self) -> &[User] { &self.followers }
+}
+
+#[derive(Debug, Clone)]
+pub struct SourceArgs {
+    store: Store,
+    kind: SourceKind,
+    code: String,
+}
+
+impl SourceArgs {
+    /// Sources can also be given by the url of their search, e.g. `https://www.1999.co.jp/search?maker=123`.
+    pub fn new(store: Store, kind: SourceKind, code: String) -> Self {
+        let code = code.trim();
+        let code = match code.split_once('?') {
+            Some((_, query)) => serde_urlencoded::from_str::<Vec<(String, String)>>(query)
+                .unwrap_or_default()
+                .into_iter()
+                .find(|(key, _)| key == Self::query_key(store, kind))
+                .map(|(_, value)| value.trim().to_owned())
+                .unwrap_or_default(),
+            None => code.to_owned(),
+        };
+        SourceArgs { store, kind, code }
+    }
+
+    pub fn store(&self) -> Store { self.store }
+    pub fn kind(&self) -> SourceKind { self.kind }
+    pub fn code(&self) -> &str { &self.code }
+
+    fn query_key(store: Store, kind: SourceKind) -> &'static str {
+        match (store, kind) {
+            (Store::HobbySearch, SourceKind::Maker) => "maker",
+            (Store::Gsc, SourceKind::Maker) => "brand",
+            (_, SourceKind::Series) => "series",
+        }
+    }
+
+    /// The search of the store, newest products first.
+    pub fn url(&self) -> String {
+        let base_url = match self.store {
+            Store::HobbySearch => "https://www.1999.co.jp/search",
+            Store::Gsc => "https://www.goodsmile.com/ja/search",
+        };
+        let query = serde_urlencoded::to_string([(Self::query_key(self.store, self.kind), self.code.as_str()), ("sort", "new")]).unwrap_or_default();
+        format!("{}?{}", base_url, query)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum FollowSourceError {
+    #[error("{store} {kind} '{code}' is already followed")]
+    AlreadyFollowedError { store: Store, kind: SourceKind, code: String },
+    #[error("maker or series id must not be empty")]
+    EmptyCode,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum UnfollowSourceError {
+    #[error("unknown maker or series with id '{id}'")]
+    UnknownSource { id: i32 },
+    #[error("{store} {kind} '{code}' not followed")]
+    SourceNotFollowed { store: Store, kind: SourceKind, code: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum GetSourcesError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum SetSourceNameError {
+    #[error("unknown maker or series with id '{id}'")]
+    UnknownSource { id: i32 },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_source_args() {
+        let args = SourceArgs::new(Store::HobbySearch, SourceKind::Maker, " https://www.1999.co.jp/search?maker=123&sort=new ".to_owned());
+        assert_eq!(args.code(), "123");
+        assert_eq!(args.url(), "https://www.1999.co.jp/search?maker=123&sort=new");
+        let args = SourceArgs::new(Store::Gsc, SourceKind::Maker, "https://www.goodsmile.com/ja/search?brand=goodsmile".to_owned());
+        assert_eq!(args.code(), "goodsmile");
+        assert_eq!(args.url(), "https://www.goodsmile.com/ja/search?brand=goodsmile&sort=new");
+        assert_eq!(SourceArgs::new(Store::Gsc, SourceKind::Series, "nendoroid".to_owned()).url(), "https://www.goodsmile.com/ja/search?series=nendoroid&sort=new");
+        assert_eq!(SourceArgs::new(Store::Gsc, SourceKind::Series, "https://www.goodsmile.com/ja/search?brand=goodsmile".to_owned()).code(), "");
+    }
+}
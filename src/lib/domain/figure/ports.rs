@@ -0,0 +1,63 @@
+use crate::domain::figure::models::product::{AddProductSourceError, CreateProductArgs, CreateProductError, GetProductsError, Product, ProductHistoryEntry, ScrapeProductsError, SetPreorderNoticeError, SourceData, UpdateProductArgs, UpdateProductError};
+use crate::domain::figure::models::source::{FollowSourceError, FollowedSource, GetSourcesError, SetSourceNameError, Source, SourceArgs, UnfollowSourceError};
+use crate::domain::pagination::{Page, PageRequest};
+use crate::domain::product_history::GetProductError;
+use crate::domain::site::{SiteNotifier, SiteService};
+use crate::domain::user::models::user::User;
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait FigureService: SiteService {
+    async fn follow_source(&self, user: &User, req: &SourceArgs) -> Result<(), FollowSourceError>;
+    async fn unfollow_source(&self, user: &User, source_id: i32) -> Result<(), UnfollowSourceError>;
+    async fn get_sources(&self, user: &User) -> Result<Vec<Source>, GetSourcesError>;
+    async fn get_followed_sources(&self, user: &User) -> Result<Vec<Source>, GetSourcesError>;
+    async fn get_sources_page(&self, user: &User, following: Option<bool>, page: PageRequest) -> Result<Page<Source>, GetSourcesError>;
+
+    async fn get_products_page(&self, page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    async fn get_products_by_source(&self, source_id: i32) -> Result<Vec<Product>, GetProductsError>;
+    async fn get_products_page_by_source(&self, source_id: i32, page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    async fn get_products_page_by_sources(&self, source_ids: &[i32], page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    async fn get_product(&self, product_id: i32) -> Result<Product, GetProductError>;
+    async fn get_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError>;
+}
+
+#[async_trait]
+pub trait FigureRepository: Clone + Send + Sync + 'static {
+    async fn follow_figure_source(&self, user_id: i32, req: &SourceArgs) -> Result<(), FollowSourceError>;
+    async fn unfollow_figure_source(&self, user_id: i32, source_id: i32) -> Result<(), UnfollowSourceError>;
+    async fn get_figure_sources(&self, user_id: i32) -> Result<Vec<Source>, GetSourcesError>;
+    async fn get_figure_sources_page(&self, user_id: i32, following: Option<bool>, page: PageRequest) -> Result<Page<Source>, GetSourcesError>;
+    async fn get_followed_figure_sources(&self) -> Result<Vec<FollowedSource>, GetSourcesError>;
+    async fn set_figure_source_name(&self, source_id: i32, name: &str) -> Result<(), SetSourceNameError>;
+
+    async fn create_figure_product(&self, req: &CreateProductArgs) -> Result<Product, CreateProductError>;
+    /// Also forgets the preorder notices when the preorder window is new or moved.
+    async fn update_figure_product(&self, req: &UpdateProductArgs) -> Result<Product, UpdateProductError>;
+    /// Links a figure that was first found for another maker or series to the source.
+    async fn add_figure_product_source(&self, url: &str, source_id: i32) -> Result<(), AddProductSourceError>;
+    /// Records that the opening of the current preorder window was notified.
+    async fn set_figure_preorder_opened(&self, product_id: i32) -> Result<Product, SetPreorderNoticeError>;
+    /// Records that the closing of the current preorder window was notified.
+    async fn set_figure_preorder_closing(&self, product_id: i32) -> Result<Product, SetPreorderNoticeError>;
+    async fn get_figure_product(&self, product_id: i32) -> Result<Product, GetProductError>;
+    async fn get_figure_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError>;
+    async fn get_figure_products(&self) -> Result<Vec<Product>, GetProductsError>;
+    async fn get_figure_products_by_source(&self, source_id: i32) -> Result<Vec<Product>, GetProductsError>;
+    async fn get_figure_products_page(&self, page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    async fn get_figure_products_page_by_source(&self, source_id: i32, page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    /// Products found for any of the sources, the newest first.
+    async fn get_figure_products_page_by_sources(&self, source_ids: &[i32], page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+}
+
+#[async_trait]
+pub trait FigureScraper: Clone + Send + Sync + 'static {
+    /// The name of the maker or series and its figures with their preorder windows.
+    async fn get_source(&self, source: &SourceArgs) -> Result<SourceData, ScrapeProductsError>;
+}
+
+#[async_trait]
+pub trait FigureNotifier: SiteNotifier<Product> {
+    async fn preorders_opened<Q: AsRef<Product> + Sync>(&self, source: &str, products: &[Q]);
+    async fn preorders_closing<Q: AsRef<Product> + Sync>(&self, source: &str, products: &[Q]);
+}
@@ -0,0 +1,458 @@
+use crate::domain::figure::models::product::{store_now, CreateProductArgs, GetProductsError, Product, ProductHistoryEntry, ScrapeProductsError, UpdateProductArgs};
+use crate::domain::figure::models::source::{FollowSourceError, GetSourcesError, Source, SourceArgs, UnfollowSourceError};
+use crate::domain::figure::ports::{FigureNotifier, FigureRepository, FigureScraper, FigureService};
+use crate::domain::figure::SITE;
+use crate::domain::image::ports::ImageCache;
+use crate::domain::pagination::{Page, PageRequest};
+use crate::domain::product_history::{GetProductError, NotificationKind};
+use crate::domain::site::{ScrapeGuard, ScrapeLock, ScrapeSiteError, Site, SiteCore, SiteRepository, SiteService};
+use crate::domain::user::models::user::User;
+use async_trait::async_trait;
+use chrono::Duration;
+use log::info;
+use std::collections::{BTreeSet, HashMap};
+
+/// How long before the end of a preorder window its closing is notified.
+const PREORDER_CLOSING_NOTICE: Duration = Duration::hours(48);
+
+#[derive(Debug, Clone)]
+pub struct FigureServiceImpl<R, N, S, I>
+where
+    R: FigureRepository + SiteRepository,
+    N: FigureNotifier,
+    S: FigureScraper,
+    I: ImageCache
+{
+    repo: R,
+    scraper: S,
+    core: SiteCore<N, I>,
+}
+
+impl<R, N, S, I> FigureServiceImpl<R, N, S, I>
+where
+    R: FigureRepository + SiteRepository,
+    N: FigureNotifier,
+    S: FigureScraper,
+    I: ImageCache
+{
+    pub fn new(repo: R, scraper: S, core: SiteCore<N, I>) -> Self {
+        Self { repo, scraper, core }
+    }
+}
+
+#[async_trait]
+impl<R, N, S, I> SiteService for FigureServiceImpl<R, N, S, I>
+where
+    R: FigureRepository + SiteRepository,
+    N: FigureNotifier,
+    S: FigureScraper,
+    I: ImageCache
+{
+    fn site(&self) -> Site {
+        SITE
+    }
+
+    fn scrape_lock(&self) -> &ScrapeLock {
+        self.core.scrape_lock()
+    }
+
+    async fn scrape(&self, _guard: &ScrapeGuard) -> Result<(), ScrapeSiteError> {
+        info!("scrape available products");
+        self.scrape_followed_sources().await
+            .map_err(|e| anyhow::Error::new(e).into())
+    }
+}
+
+#[async_trait]
+impl<R, N, S, I> FigureService for FigureServiceImpl<R, N, S, I>
+where
+    R: FigureRepository + SiteRepository,
+    N: FigureNotifier,
+    S: FigureScraper,
+    I: ImageCache
+{
+    async fn follow_source(&self, user: &User, source_args: &SourceArgs) -> Result<(), FollowSourceError> {
+        if source_args.code().is_empty() {
+            return Err(FollowSourceError::EmptyCode);
+        }
+        info!("follow {} {} '{}' for '{}'", source_args.store(), source_args.kind(), source_args.code(), user.username());
+        self.repo.follow_figure_source(user.id(), source_args).await
+    }
+
+    async fn unfollow_source(&self, user: &User, source_id: i32) -> Result<(), UnfollowSourceError> {
+        info!("unfollow source with id '{}' for '{}'", source_id, user.username());
+        self.repo.unfollow_figure_source(user.id(), source_id).await
+    }
+
+    async fn get_sources(&self, user: &User) -> Result<Vec<Source>, GetSourcesError> {
+        info!("get sources for '{}'", user.username());
+        self.repo.get_figure_sources(user.id()).await
+    }
+
+    async fn get_followed_sources(&self, user: &User) -> Result<Vec<Source>, GetSourcesError> {
+        info!("get followed sources for '{}'", user.username());
+        let sources = self.repo.get_figure_sources(user.id()).await?;
+        Ok(
+            sources.into_iter()
+                .filter(|s| s.following())
+                .collect()
+        )
+    }
+
+    async fn get_sources_page(&self, user: &User, following: Option<bool>, page: PageRequest) -> Result<Page<Source>, GetSourcesError> {
+        info!("get page {} of sources for '{}'", page.page(), user.username());
+        self.repo.get_figure_sources_page(user.id(), following, page).await
+    }
+
+    async fn get_products_page(&self, page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        info!("get page {} of products", page.page());
+        self.repo.get_figure_products_page(page).await
+    }
+
+    async fn get_products_by_source(&self, source_id: i32) -> Result<Vec<Product>, GetProductsError> {
+        info!("get products by source with id '{}'", source_id);
+        self.repo.get_figure_products_by_source(source_id).await
+    }
+
+    async fn get_products_page_by_source(&self, source_id: i32, page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        info!("get page {} of products by source with id '{}'", page.page(), source_id);
+        self.repo.get_figure_products_page_by_source(source_id, page).await
+    }
+
+    async fn get_products_page_by_sources(&self, source_ids: &[i32], page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        info!("get page {} of products by sources with ids {:?}", page.page(), source_ids);
+        self.repo.get_figure_products_page_by_sources(source_ids, page).await
+    }
+
+    async fn get_product(&self, product_id: i32) -> Result<Product, GetProductError> {
+        info!("get product with id '{}'", product_id);
+        self.repo.get_figure_product(product_id).await
+    }
+
+    async fn get_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError> {
+        info!("get history of product with id '{}'", product_id);
+        self.repo.get_figure_product_history(product_id).await
+    }
+}
+
+impl<R, N, S, I> FigureServiceImpl<R, N, S, I>
+where
+    R: FigureRepository + SiteRepository,
+    N: FigureNotifier,
+    S: FigureScraper,
+    I: ImageCache
+{
+    /// New figures are notified, and every preorder window once when it opens and once when it is about to close.
+    /// A window only closes by the passing of time, so the stored figures of a source are checked on every scrape.
+    async fn scrape_followed_sources(&self) -> Result<(), ScrapeProductsError> {
+        let followed_sources = self.repo.get_followed_figure_sources().await?;
+        // figures are listed by their maker and their series, a figure found for an earlier source is not new for a later one
+        let mut known_products = self.repo.get_figure_products().await?
+            .into_iter()
+            .map(|p| (p.url().to_owned(), p))
+            .collect::<HashMap<_, _>>();
+        for followed_source in followed_sources.iter() {
+            let source = followed_source.source();
+            info!("scrape available products for {} {} '{}'", source.store(), source.kind(), source.code());
+            let source_urls = self.repo.get_figure_products_by_source(source.id()).await?
+                .into_iter()
+                .map(|p| p.url().to_owned())
+                .collect::<BTreeSet<_>>();
+            let source_data = self.scraper.get_source(&SourceArgs::new(source.store(), source.kind(), source.code().to_owned())).await?;
+            if !source_data.name().is_empty() && source_data.name() != source.name() {
+                self.repo.set_figure_source_name(source.id(), source_data.name()).await?;
+            }
+            let target = format!("{} ({} {})", source_data.name(), source.store(), source.kind());
+
+            let mut new_products = Vec::<Product>::new();
+            for item in source_data.items() {
+                match known_products.get(item.url()) {
+                    None => {
+                        let product = self.repo.create_figure_product(&CreateProductArgs::new(source.id(), source.store(), item.clone())).await?;
+                        new_products.push(product.clone());
+                        known_products.insert(product.url().to_owned(), product);
+                    }
+                    Some(product) => {
+                        if !source_urls.contains(item.url()) {
+                            self.repo.add_figure_product_source(item.url(), source.id()).await?;
+                        }
+                        if !product.has_changes(item) {
+                            continue;
+                        }
+                        let product = self.repo.update_figure_product(&UpdateProductArgs::new(item.clone())).await?;
+                        known_products.insert(product.url().to_owned(), product);
+                    }
+                }
+            }
+
+            let now = store_now();
+            let mut opened_products = Vec::<Product>::new();
+            let mut closing_products = Vec::<Product>::new();
+            for product in self.repo.get_figure_products_by_source(source.id()).await? {
+                let closing = product.preorder().is_some_and(|p| p.is_closing(now, PREORDER_CLOSING_NOTICE));
+                if closing && product.date_preorder_closing().is_none() {
+                    // a window seen for the first time shortly before its end is only notified as closing
+                    if product.date_preorder_opened().is_none() {
+                        self.repo.set_figure_preorder_opened(product.id()).await?;
+                    }
+                    let product = self.repo.set_figure_preorder_closing(product.id()).await?;
+                    closing_products.push(self.core.cache_product_image(&self.repo, product).await);
+                } else if product.is_preorder_open(now) && product.date_preorder_opened().is_none() {
+                    let product = self.repo.set_figure_preorder_opened(product.id()).await?;
+                    opened_products.push(self.core.cache_product_image(&self.repo, product).await);
+                }
+            }
+            // figures that can be preordered right away are only notified as opened
+            let notified_ids = opened_products.iter().chain(closing_products.iter())
+                .map(|p| p.id())
+                .collect::<BTreeSet<_>>();
+            let mut unnotified_new_products = Vec::<Product>::new();
+            for product in new_products.into_iter().filter(|p| !notified_ids.contains(&p.id())) {
+                unnotified_new_products.push(self.core.cache_product_image(&self.repo, product).await);
+            }
+            info!("found '{}' new products, '{}' opened and '{}' closing preorders for {}",
+                unnotified_new_products.len(), opened_products.len(), closing_products.len(), target);
+
+            let followers = followed_source.followers();
+            for (notifier, products) in self.core.recipients(followers, &closing_products) {
+                notifier.preorders_closing(&target, &products).await;
+            }
+            self.core.add_notifications(&self.repo, followers, NotificationKind::PreorderClosing, &closing_products).await?;
+            for (notifier, products) in self.core.recipients(followers, &opened_products) {
+                notifier.preorders_opened(&target, &products).await;
+            }
+            self.core.add_notifications(&self.repo, followers, NotificationKind::PreorderOpened, &opened_products).await?;
+            self.core.notify(&self.repo, &target, followers, &unnotified_new_products, &[]).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::domain::figure::models::product::{AddProductSourceError, CreateProductError, ItemData, PreorderWindow, SetPreorderNoticeError, SourceData, UpdateProductError};
+    use crate::domain::figure::models::source::{FollowedSource, SetSourceNameError, SourceKind, Store};
+    use crate::domain::test_util::{user, TestImageCache, TestNotifier, TestRepo};
+    use chrono::Utc;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Default)]
+    struct Figures {
+        sources: Vec<FollowedSource>,
+        products: Vec<(Product, BTreeSet<i32>)>,
+    }
+
+    impl Figures {
+        fn set_notices(&mut self, product_id: i32, f: impl FnOnce(&Product) -> Product) -> Result<Product, SetPreorderNoticeError> {
+            let (product, _) = self.products.iter_mut()
+                .find(|(p, _)| p.id() == product_id)
+                .ok_or(SetPreorderNoticeError::ProductMissing { id: product_id })?;
+            *product = f(product);
+            Ok(product.clone())
+        }
+    }
+
+    #[async_trait]
+    impl FigureRepository for TestRepo<Figures> {
+        async fn follow_figure_source(&self, _user_id: i32, _req: &SourceArgs) -> Result<(), FollowSourceError> {
+            unimplemented!()
+        }
+
+        async fn unfollow_figure_source(&self, _user_id: i32, _source_id: i32) -> Result<(), UnfollowSourceError> {
+            unimplemented!()
+        }
+
+        async fn get_figure_sources(&self, _user_id: i32) -> Result<Vec<Source>, GetSourcesError> {
+            unimplemented!()
+        }
+
+        async fn get_figure_sources_page(&self, _user_id: i32, _following: Option<bool>, _page: PageRequest) -> Result<Page<Source>, GetSourcesError> {
+            unimplemented!()
+        }
+
+        async fn get_followed_figure_sources(&self) -> Result<Vec<FollowedSource>, GetSourcesError> {
+            Ok(self.with_site(|s| s.sources.clone()))
+        }
+
+        async fn set_figure_source_name(&self, source_id: i32, name: &str) -> Result<(), SetSourceNameError> {
+            self.with_site(|s| {
+                let followed = s.sources.iter_mut()
+                    .find(|f| f.source().id() == source_id)
+                    .ok_or(SetSourceNameError::UnknownSource { id: source_id })?;
+                let c = followed.source();
+                let source = Source::new(c.id(), c.date_added(), c.store(), c.kind(), c.code().to_owned(), name.to_owned(), c.following(), c.date_followed());
+                *followed = FollowedSource::new(source, followed.followers().to_vec());
+                Ok(())
+            })
+        }
+
+        async fn create_figure_product(&self, req: &CreateProductArgs) -> Result<Product, CreateProductError> {
+            self.with_site(|s| {
+                let item = req.item();
+                if s.products.iter().any(|(p, _)| p.url() == item.url()) {
+                    return Err(CreateProductError::DuplicateProduct { url: item.url().to_owned(), title: item.title().to_owned() });
+                }
+                let product = Product::new(
+                    s.products.len() as i32 + 1, Utc::now(), req.store(), item.url().to_owned(), item.title().to_owned(), item.maker_name().to_owned(),
+                    item.image_url().to_owned(), item.price(), item.release_date().map(str::to_owned), item.preorder().cloned()
+                );
+                s.products.push((product.clone(), BTreeSet::from([req.source_id()])));
+                Ok(product)
+            })
+        }
+
+        async fn update_figure_product(&self, req: &UpdateProductArgs) -> Result<Product, UpdateProductError> {
+            self.with_site(|s| {
+                let (product, _) = s.products.iter_mut()
+                    .find(|(p, _)| p.url() == req.url())
+                    .ok_or(UpdateProductError::ProductMissing { url: req.url().to_owned() })?;
+                let item = req.item();
+                let (opened, closing) = match product.preorder() == item.preorder() {
+                    true => (product.date_preorder_opened(), product.date_preorder_closing()),
+                    false => (None, None),
+                };
+                *product = Product::new(
+                    product.id(), product.date_added(), product.store(), product.url().to_owned(), item.title().to_owned(), item.maker_name().to_owned(),
+                    item.image_url().to_owned(), item.price(), item.release_date().map(str::to_owned), item.preorder().cloned()
+                ).with_preorder_notices(opened, closing);
+                Ok(product.clone())
+            })
+        }
+
+        async fn add_figure_product_source(&self, url: &str, source_id: i32) -> Result<(), AddProductSourceError> {
+            self.with_site(|s| {
+                let (_, sources) = s.products.iter_mut()
+                    .find(|(p, _)| p.url() == url)
+                    .ok_or(AddProductSourceError::ProductMissing { url: url.to_owned() })?;
+                sources.insert(source_id);
+                Ok(())
+            })
+        }
+
+        async fn set_figure_preorder_opened(&self, product_id: i32) -> Result<Product, SetPreorderNoticeError> {
+            self.with_site(|s| s.set_notices(product_id, |p| p.clone().with_preorder_notices(Some(Utc::now()), p.date_preorder_closing())))
+        }
+
+        async fn set_figure_preorder_closing(&self, product_id: i32) -> Result<Product, SetPreorderNoticeError> {
+            self.with_site(|s| s.set_notices(product_id, |p| p.clone().with_preorder_notices(p.date_preorder_opened(), Some(Utc::now()))))
+        }
+
+        async fn get_figure_product(&self, _product_id: i32) -> Result<Product, GetProductError> {
+            unimplemented!()
+        }
+
+        async fn get_figure_product_history(&self, _product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError> {
+            unimplemented!()
+        }
+
+        async fn get_figure_products(&self) -> Result<Vec<Product>, GetProductsError> {
+            Ok(self.with_site(|s| s.products.iter().map(|(p, _)| p.clone()).collect()))
+        }
+
+        async fn get_figure_products_by_source(&self, source_id: i32) -> Result<Vec<Product>, GetProductsError> {
+            Ok(self.with_site(|s| s.products.iter().filter(|(_, sources)| sources.contains(&source_id)).map(|(p, _)| p.clone()).collect()))
+        }
+
+        async fn get_figure_products_page(&self, _page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+            unimplemented!()
+        }
+
+        async fn get_figure_products_page_by_source(&self, _source_id: i32, _page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+            unimplemented!()
+        }
+
+        async fn get_figure_products_page_by_sources(&self, _source_ids: &[i32], _page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+            unimplemented!()
+        }
+    }
+
+    /// Finds the items set for the source code.
+    #[derive(Debug, Clone, Default)]
+    struct TestScraper {
+        sources: Arc<Mutex<HashMap<String, SourceData>>>,
+    }
+
+    impl TestScraper {
+        fn set_items(&self, code: &str, items: Vec<ItemData>) {
+            self.sources.lock().unwrap().insert(code.to_owned(), SourceData::new("Good Smile Company".to_owned(), items));
+        }
+    }
+
+    #[async_trait]
+    impl FigureScraper for TestScraper {
+        async fn get_source(&self, source: &SourceArgs) -> Result<SourceData, ScrapeProductsError> {
+            Ok(self.sources.lock().unwrap().get(source.code()).cloned().unwrap_or_else(|| SourceData::new(String::new(), Vec::new())))
+        }
+    }
+
+    #[async_trait]
+    impl FigureNotifier for TestNotifier {
+        async fn preorders_opened<Q: AsRef<Product> + Sync>(&self, source: &str, products: &[Q]) {
+            self.record::<Product, Q>(NotificationKind::PreorderOpened, source, products);
+        }
+
+        async fn preorders_closing<Q: AsRef<Product> + Sync>(&self, source: &str, products: &[Q]) {
+            self.record::<Product, Q>(NotificationKind::PreorderClosing, source, products);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scrape_notifies_preorder_opened_and_closing_once() {
+        let source = Source::new(1, Utc::now(), Store::Gsc, SourceKind::Maker, "1".to_owned(), "1".to_owned(), true, Some(Utc::now()));
+        let repo = TestRepo::new(Figures { sources: vec![FollowedSource::new(source, vec![user(1, "alice")])], ..Default::default() });
+        let scraper = TestScraper::default();
+        let (now, day) = (store_now(), Duration::days(1));
+        let open_until = |days: i64| Some(PreorderWindow::new(Some(now - day), Some(now + day * days as i32)));
+        scraper.set_items("1", vec![item("1001", None), item("1002", open_until(10)), item("1003", open_until(1))]);
+        let (notifier, alice_notifier) = (TestNotifier::default(), TestNotifier::default());
+        let core = SiteCore::new(notifier.clone(), TestImageCache).with_user_notifiers(HashMap::from([("alice".to_owned(), alice_notifier.clone())]));
+        let service = FigureServiceImpl::new(repo.clone(), scraper.clone(), core);
+        let target = format!("Good Smile Company ({} {})", Store::Gsc, SourceKind::Maker);
+
+        service.scrape(&service.scrape_lock().lock().await).await.unwrap();
+        let notified = vec![
+            (NotificationKind::PreorderClosing, target.clone(), vec![url("1003")]),
+            (NotificationKind::PreorderOpened, target.clone(), vec![url("1002")]),
+            (NotificationKind::NewProduct, target.clone(), vec![url("1001")]),
+        ];
+        assert_eq!(notifier.take(), notified);
+        assert_eq!(alice_notifier.take(), notified);
+        assert!(repo.with_site(|s| s.products[2].0.date_preorder_opened().is_some()));
+        assert_eq!(repo.images().into_iter().map(|(id, _)| id).collect::<Vec<_>>(), vec![2, 3, 1]);
+
+        service.scrape(&service.scrape_lock().lock().await).await.unwrap();
+        assert!(notifier.take().is_empty());
+
+        // a moved window is notified again
+        scraper.set_items("1", vec![item("1001", open_until(10)), item("1002", open_until(1)), item("1003", open_until(1))]);
+        service.scrape(&service.scrape_lock().lock().await).await.unwrap();
+        assert_eq!(notifier.take(), vec![
+            (NotificationKind::PreorderClosing, target.clone(), vec![url("1002")]),
+            (NotificationKind::PreorderOpened, target.clone(), vec![url("1001")]),
+        ]);
+        assert_eq!(repo.notifications(), vec![
+            (1, NotificationKind::PreorderClosing, 3),
+            (1, NotificationKind::PreorderOpened, 2),
+            (1, NotificationKind::NewProduct, 1),
+            (1, NotificationKind::PreorderClosing, 2),
+            (1, NotificationKind::PreorderOpened, 1),
+        ]);
+    }
+
+    fn item(product_id: &str, preorder: Option<PreorderWindow>) -> ItemData {
+        ItemData::new(
+            url(product_id),
+            "figure_title".to_owned(),
+            "Good Smile Company".to_owned(),
+            format!("https://images.goodsmile.info/cgm/images/product/{}.jpg", product_id),
+            Some(16800),
+            Some("2027年03月".to_owned()),
+            preorder
+        )
+    }
+
+    fn url(product_id: &str) -> String {
+        format!("https://www.goodsmile.com/ja/product/{}", product_id)
+    }
+}
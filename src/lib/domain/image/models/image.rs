@@ -0,0 +1,64 @@
+use strum_macros::{Display, EnumString};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum ImageVariant {
+    Original,
+    /// Scaled down when cached, for the product cards and notifications.
+    Thumbnail,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    content_type: String,
+    data: Vec<u8>,
+}
+
+impl Image {
+    pub fn new(content_type: String, data: Vec<u8>) -> Self {
+        Self { content_type, data }
+    }
+
+    pub fn content_type(&self) -> &str { &self.content_type }
+    pub fn data(&self) -> &[u8] { &self.data }
+    pub fn into_data(self) -> Vec<u8> { self.data }
+}
+
+/// A stored image, `perceptual_hash` is only known for decodable images and is close for visually similar images.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedImage {
+    hash: String,
+    perceptual_hash: Option<u64>,
+}
+
+impl CachedImage {
+    pub fn new(hash: String, perceptual_hash: Option<u64>) -> Self {
+        Self { hash, perceptual_hash }
+    }
+
+    pub fn hash(&self) -> &str { &self.hash }
+    pub fn perceptual_hash(&self) -> Option<u64> { self.perceptual_hash }
+}
+
+#[derive(Debug, Error)]
+pub enum CacheImageError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum GetImageError {
+    #[error("Image {hash} does not exist")]
+    ImageMissing { hash: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum SetProductImageError {
+    #[error("Product {id} does not exist")]
+    ProductMissing { id: i32 },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
@@ -0,0 +1 @@
+pub mod image;
\ No newline at end of file
@@ -0,0 +1,15 @@
+use crate::domain::image::models::image::{CacheImageError, CachedImage, GetImageError, Image, ImageVariant};
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait ImageService: Send + Sync + 'static {
+    async fn get_image(&self, hash: &str, variant: ImageVariant) -> Result<Image, GetImageError>;
+}
+
+/// Content-addressed store of product images, keyed by the hash of the original image.
+#[async_trait]
+pub trait ImageCache: Clone + Send + Sync + 'static {
+    /// Downloads the image and stores it along with its thumbnail.
+    async fn cache_image(&self, url: &str) -> Result<CachedImage, CacheImageError>;
+    async fn get_image(&self, hash: &str, variant: ImageVariant) -> Result<Image, GetImageError>;
+}
@@ -0,0 +1,30 @@
+use crate::domain::image::models::image::{GetImageError, Image, ImageVariant};
+use crate::domain::image::ports::{ImageCache, ImageService};
+use async_trait::async_trait;
+
+#[derive(Debug, Clone)]
+pub struct ImageServiceImpl<C>
+where
+    C: ImageCache
+{
+    cache: C,
+}
+
+impl<C> ImageServiceImpl<C>
+where
+    C: ImageCache
+{
+    pub fn new(cache: C) -> Self {
+        Self { cache }
+    }
+}
+
+#[async_trait]
+impl<C> ImageService for ImageServiceImpl<C>
+where
+    C: ImageCache
+{
+    async fn get_image(&self, hash: &str, variant: ImageVariant) -> Result<Image, GetImageError> {
+        self.cache.get_image(hash, variant).await
+    }
+}
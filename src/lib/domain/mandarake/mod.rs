@@ -0,0 +1,7 @@
+use crate::domain::site::Site;
+
+pub mod ports;
+pub mod models;
+pub mod service;
+
+pub const SITE: Site = Site::new("mandarake", "Mandarake", "https://order.mandarake.co.jp/");
@@ -0,0 +1,212 @@
+use crate::domain::availability::Availability;
+use crate::domain::mandarake::models::search::GetSearchesError;
+use crate::domain::mandarake::SITE;
+use crate::domain::product_history;
+use crate::domain::product_history::AddNotificationsError;
+use crate::domain::image::models::image::CachedImage;
+use crate::domain::site::{Site, SiteProduct};
+use crate::outbound::mandarake_scraper::ParseError;
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+/// A second-hand listing, the same item listed twice is two products.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Product {
+    id: i32,
+    date_added: DateTime<Utc>,
+    url: String,
+    title: String,
+    image_url: String,
+    store: String,
+    condition: String,
+    price: i32,
+    availability: Availability,
+    date_restocked: Option<DateTime<Utc>>,
+    image_hash: Option<String>,
+    image_phash: Option<u64>,
+}
+
+impl Product {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(id: i32, date_added: DateTime<Utc>, url: String, title: String, image_url: String, store: String, condition: String, price: i32, availability: Availability) -> Self {
+        Self { id, date_added, url, title, image_url, store, condition, price, availability, date_restocked: None, image_hash: None, image_phash: None }
+    }
+
+    pub fn with_date_restocked(mut self, date_restocked: Option<DateTime<Utc>>) -> Self {
+        self.date_restocked = date_restocked;
+        self
+    }
+
+    pub fn with_image_hash(mut self, image_hash: Option<String>) -> Self {
+        self.image_hash = image_hash;
+        self
+    }
+
+    pub fn with_image_phash(mut self, image_phash: Option<u64>) -> Self {
+        self.image_phash = image_phash;
+        self
+    }
+
+    pub fn id(&self) -> i32 { self.id }
+    pub fn date_added(&self) -> DateTime<Utc> { self.date_added }
+    pub fn url(&self) -> &str { &self.url }
+    pub fn title(&self) -> &str { &self.title }
+    pub fn image_url(&self) -> &str { &self.image_url }
+    /// The branch the item is in, e.g. 中野店.
+    pub fn store(&self) -> &str { &self.store }
+    /// Mandarake's condition grade of the item, e.g. A or B.
+    pub fn condition(&self) -> &str { &self.condition }
+    /// Price in yen including tax.
+    pub fn price(&self) -> i32 { self.price }
+    pub fn availability(&self) -> Availability { self.availability.clone() }
+    pub fn date_restocked(&self) -> Option<DateTime<Utc>> { self.date_restocked }
+    pub fn image_hash(&self) -> Option<&str> { self.image_hash.as_deref() }
+    pub fn image_phash(&self) -> Option<u64> { self.image_phash }
+}
+
+pub type ProductHistoryEntry = product_history::ProductHistoryEntry<Availability, i32>;
+
+impl AsRef<Product> for Product {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+impl SiteProduct for Product {
+    const SITE: Site = SITE;
+
+    fn id(&self) -> i32 { self.id }
+    fn title(&self) -> &str { &self.title }
+    fn url(&self) -> &str { &self.url }
+    fn image_url(&self) -> &str { &self.image_url }
+    fn image_hash(&self) -> Option<&str> { self.image_hash.as_deref() }
+    fn image_phash(&self) -> Option<u64> { self.image_phash }
+
+    fn with_image(self, image: &CachedImage) -> Self {
+        self.with_image_hash(Some(image.hash().to_owned())).with_image_phash(image.perceptual_hash())
+    }
+
+    fn summary(&self) -> String {
+        format!("{} — condition {}\n¥{}", self.store, self.condition, self.price)
+    }
+
+    fn notification_target(target: &str) -> String {
+        format!("search '{}'", target)
+    }
+}
+
+/// A listing as found on the search result page.
+#[derive(Debug, Clone)]
+pub struct ListingData {
+    url: String,
+    title: String,
+    image_url: String,
+    store: String,
+    condition: String,
+    price: i32,
+    availability: Availability,
+}
+
+impl ListingData {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(url: String, title: String, image_url: String, store: String, condition: String, price: i32, availability: Availability) -> Self {
+        Self { url, title, image_url, store, condition, price, availability }
+    }
+
+    pub fn url(&self) -> &str { &self.url }
+    pub fn title(&self) -> &str { &self.title }
+    pub fn image_url(&self) -> &str { &self.image_url }
+    pub fn store(&self) -> &str { &self.store }
+    pub fn condition(&self) -> &str { &self.condition }
+    pub fn price(&self) -> i32 { self.price }
+    pub fn availability(&self) -> &Availability { &self.availability }
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateProductArgs {
+    search_id: i32,
+    listing: ListingData,
+}
+
+impl CreateProductArgs {
+    pub fn new(search_id: i32, listing: ListingData) -> Self {
+        Self { search_id, listing }
+    }
+
+    /// The search the listing was found for.
+    pub fn search_id(&self) -> i32 { self.search_id }
+    pub fn url(&self) -> &str { self.listing.url() }
+    pub fn title(&self) -> &str { self.listing.title() }
+    pub fn image_url(&self) -> &str { self.listing.image_url() }
+    pub fn store(&self) -> &str { self.listing.store() }
+    pub fn condition(&self) -> &str { self.listing.condition() }
+    pub fn price(&self) -> i32 { self.listing.price() }
+    pub fn availability(&self) -> Availability { self.listing.availability().clone() }
+}
+
+#[derive(Debug, Clone)]
+pub struct UpdateProductArgs {
+    url: String,
+    price: i32,
+    availability: Availability,
+}
+
+impl UpdateProductArgs {
+    pub fn new(url: String, price: i32, availability: Availability) -> Self {
+        Self { url, price, availability }
+    }
+
+    pub fn url(&self) -> &str { &self.url }
+    pub fn price(&self) -> i32 { self.price }
+    pub fn availability(&self) -> Availability { self.availability.clone() }
+}
+
+#[derive(Debug, Error)]
+pub enum CreateProductError {
+    #[error("Product '{title}' ({url}) already exists")]
+    DuplicateProduct { url: String, title: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum UpdateProductError {
+    #[error("Product {url} does not exist")]
+    ProductMissing { url: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum AddProductSearchError {
+    #[error("Product {url} does not exist")]
+    ProductMissing { url: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum GetProductsError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum ScrapeProductsError {
+    #[error(transparent)]
+    ParseError(#[from] ParseError),
+    #[error(transparent)]
+    GetSearchesError(#[from] GetSearchesError),
+    #[error(transparent)]
+    GetProductError(#[from] GetProductsError),
+    #[error(transparent)]
+    CreateProductError(#[from] CreateProductError),
+    #[error(transparent)]
+    UpdateProductError(#[from] UpdateProductError),
+    #[error(transparent)]
+    AddProductSearchError(#[from] AddProductSearchError),
+    #[error(transparent)]
+    AddNotificationsError(#[from] AddNotificationsError),
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
@@ -0,0 +1,112 @@
+use crate::domain::mandarake::models::product::Product;
+use crate::domain::site::Follower;
+use crate::domain::user::models::user::User;
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+/// A keyword or artist name searched on Mandarake, as saved by one user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Search {
+    id: i32,
+    date_added: DateTime<Utc>,
+    keyword: String,
+    following: bool,
+    max_price: Option<i32>,
+    date_followed: Option<DateTime<Utc>>,
+}
+
+impl Search {
+    pub fn new(id: i32, date_added: DateTime<Utc>, keyword: String, following: bool, max_price: Option<i32>, date_followed: Option<DateTime<Utc>>) -> Self {
+        Search { id, date_added, keyword, following, max_price, date_followed }
+    }
+
+    pub fn id(&self) -> i32 { self.id }
+    pub fn date_added(&self) -> DateTime<Utc> { self.date_added }
+    pub fn keyword(&self) -> &str { &self.keyword }
+    pub fn following(&self) -> bool { self.following }
+    /// Highest price in yen the user wants to be notified about, `None` for any price.
+    pub fn max_price(&self) -> Option<i32> { self.max_price }
+    pub fn date_followed(&self) -> Option<DateTime<Utc>> { self.date_followed }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchFollower {
+    user: User,
+    max_price: Option<i32>,
+}
+
+impl SearchFollower {
+    pub fn new(user: User, max_price: Option<i32>) -> Self {
+        SearchFollower { user, max_price }
+    }
+
+    pub fn user(&self) -> &User { &self.user }
+    pub fn max_price(&self) -> Option<i32> { self.max_price }
+
+    pub fn accepts_price(&self, price: i32) -> bool {
+        self.max_price.is_none_or(|max_price| price <= max_price)
+    }
+}
+
+impl Follower<Product> for SearchFollower {
+    fn user(&self) -> &User {
+        &self.user
+    }
+
+    fn wants(&self, product: &Product) -> bool {
+        self.accepts_price(product.price())
+    }
+}
+
+/// Search saved by at least one user, scraped once for all of its followers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FollowedSearch {
+    search: Search,
+    followers: Vec<SearchFollower>,
+}
+
+impl FollowedSearch {
+    pub fn new(search: Search, followers: Vec<SearchFollower>) -> Self {
+        FollowedSearch { search, followers }
+    }
+
+    pub fn search(&self) -> &Search { &self.search }
+    pub fn followers(&self) -> &[SearchFollower] { &self.followers }
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchArgs {
+    keyword: String,
+    max_price: Option<i32>,
+}
+
+impl SearchArgs {
+    pub fn new(keyword: String, max_price: Option<i32>) -> Self {
+        SearchArgs { keyword, max_price }
+    }
+
+    pub fn keyword(&self) -> &str { &self.keyword }
+    pub fn max_price(&self) -> Option<i32> { self.max_price }
+}
+
+#[derive(Debug, Error)]
+pub enum SaveSearchError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum DeleteSearchError {
+    #[error("unknown search with id '{id}'")]
+    UnknownSearch { id: i32 },
+    #[error("search '{keyword}' not saved")]
+    SearchNotFollowed { keyword: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum GetSearchesError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
@@ -0,0 +1,51 @@
+use crate::domain::mandarake::models::product::{AddProductSearchError, CreateProductArgs, CreateProductError, GetProductsError, ListingData, Product, ProductHistoryEntry, ScrapeProductsError, UpdateProductArgs, UpdateProductError};
+use crate::domain::mandarake::models::search::{DeleteSearchError, FollowedSearch, GetSearchesError, SaveSearchError, Search, SearchArgs};
+use crate::domain::pagination::{Page, PageRequest};
+use crate::domain::product_history::GetProductError;
+use crate::domain::site::SiteService;
+use crate::domain::user::models::user::User;
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait MandarakeService: SiteService {
+    /// Saves the search for the user, or updates the maximum price if it is saved already.
+    async fn save_search(&self, user: &User, req: &SearchArgs) -> Result<(), SaveSearchError>;
+    async fn delete_search(&self, user: &User, search_id: i32) -> Result<(), DeleteSearchError>;
+    async fn get_searches(&self, user: &User) -> Result<Vec<Search>, GetSearchesError>;
+    async fn get_saved_searches(&self, user: &User) -> Result<Vec<Search>, GetSearchesError>;
+    async fn get_saved_searches_page(&self, user: &User, page: PageRequest) -> Result<Page<Search>, GetSearchesError>;
+
+    async fn get_products_page(&self, page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    async fn get_products_by_search(&self, search_id: i32) -> Result<Vec<Product>, GetProductsError>;
+    async fn get_products_page_by_search(&self, search_id: i32, page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    async fn get_products_page_by_searches(&self, search_ids: &[i32], page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    async fn get_product(&self, product_id: i32) -> Result<Product, GetProductError>;
+    async fn get_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError>;
+}
+
+#[async_trait]
+pub trait MandarakeRepository: Clone + Send + Sync + 'static {
+    async fn save_mandarake_search(&self, user_id: i32, req: &SearchArgs) -> Result<(), SaveSearchError>;
+    async fn delete_mandarake_search(&self, user_id: i32, search_id: i32) -> Result<(), DeleteSearchError>;
+    async fn get_mandarake_searches(&self, user_id: i32) -> Result<Vec<Search>, GetSearchesError>;
+    async fn get_mandarake_saved_searches_page(&self, user_id: i32, page: PageRequest) -> Result<Page<Search>, GetSearchesError>;
+    async fn get_followed_mandarake_searches(&self) -> Result<Vec<FollowedSearch>, GetSearchesError>;
+
+    async fn create_mandarake_product(&self, req: &CreateProductArgs) -> Result<Product, CreateProductError>;
+    async fn update_mandarake_product(&self, req: &UpdateProductArgs) -> Result<Product, UpdateProductError>;
+    /// Links a listing that was first found by another search to the search.
+    async fn add_mandarake_product_search(&self, url: &str, search_id: i32) -> Result<(), AddProductSearchError>;
+    async fn get_mandarake_product(&self, product_id: i32) -> Result<Product, GetProductError>;
+    async fn get_mandarake_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError>;
+    async fn get_mandarake_products(&self) -> Result<Vec<Product>, GetProductsError>;
+    async fn get_mandarake_products_by_search(&self, search_id: i32) -> Result<Vec<Product>, GetProductsError>;
+    async fn get_mandarake_products_page(&self, page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    async fn get_mandarake_products_page_by_search(&self, search_id: i32, page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    /// Products found by any of the searches, the newest first.
+    async fn get_mandarake_products_page_by_searches(&self, search_ids: &[i32], page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+}
+
+#[async_trait]
+pub trait MandarakeScraper: Clone + Send + Sync + 'static {
+    async fn get_listings(&self, keyword: &str) -> Result<Vec<ListingData>, ScrapeProductsError>;
+}
@@ -0,0 +1,389 @@
+use crate::domain::image::ports::ImageCache;
+use crate::domain::availability::Availability;
+use crate::domain::mandarake::models::product::{CreateProductArgs, GetProductsError, Product, ProductHistoryEntry, ScrapeProductsError, UpdateProductArgs};
+use crate::domain::mandarake::models::search::{DeleteSearchError, GetSearchesError, SaveSearchError, Search, SearchArgs};
+use crate::domain::mandarake::ports::{MandarakeRepository, MandarakeScraper, MandarakeService};
+use crate::domain::mandarake::SITE;
+use crate::domain::pagination::{Page, PageRequest};
+use crate::domain::product_history::GetProductError;
+use crate::domain::site::{ScrapeGuard, ScrapeLock, ScrapeSiteError, Site, SiteCore, SiteNotifier, SiteRepository, SiteService};
+use crate::domain::user::models::user::User;
+use async_trait::async_trait;
+use log::info;
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(Debug, Clone)]
+pub struct MandarakeServiceImpl<R, N, S, I>
+where
+    R: MandarakeRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: MandarakeScraper,
+    I: ImageCache
+{
+    repo: R,
+    scraper: S,
+    core: SiteCore<N, I>,
+}
+
+impl<R, N, S, I> MandarakeServiceImpl<R, N, S, I>
+where
+    R: MandarakeRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: MandarakeScraper,
+    I: ImageCache
+{
+    pub fn new(repo: R, scraper: S, core: SiteCore<N, I>) -> Self {
+        Self { repo, scraper, core }
+    }
+}
+
+#[async_trait]
+impl<R, N, S, I> SiteService for MandarakeServiceImpl<R, N, S, I>
+where
+    R: MandarakeRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: MandarakeScraper,
+    I: ImageCache
+{
+    fn site(&self) -> Site {
+        SITE
+    }
+
+    fn scrape_lock(&self) -> &ScrapeLock {
+        self.core.scrape_lock()
+    }
+
+    async fn scrape(&self, _guard: &ScrapeGuard) -> Result<(), ScrapeSiteError> {
+        info!("scrape available products");
+        self.scrape_followed_searches().await
+            .map_err(|e| anyhow::Error::new(e).into())
+    }
+}
+
+#[async_trait]
+impl<R, N, S, I> MandarakeService for MandarakeServiceImpl<R, N, S, I>
+where
+    R: MandarakeRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: MandarakeScraper,
+    I: ImageCache
+{
+    async fn save_search(&self, user: &User, search_args: &SearchArgs) -> Result<(), SaveSearchError> {
+        info!("save search '{}' with max price {:?} for '{}'", search_args.keyword(), search_args.max_price(), user.username());
+        self.repo.save_mandarake_search(user.id(), search_args).await
+    }
+
+    async fn delete_search(&self, user: &User, search_id: i32) -> Result<(), DeleteSearchError> {
+        info!("delete search with id '{}' for '{}'", search_id, user.username());
+        self.repo.delete_mandarake_search(user.id(), search_id).await
+    }
+
+    async fn get_searches(&self, user: &User) -> Result<Vec<Search>, GetSearchesError> {
+        info!("get searches for '{}'", user.username());
+        self.repo.get_mandarake_searches(user.id()).await
+    }
+
+    async fn get_saved_searches(&self, user: &User) -> Result<Vec<Search>, GetSearchesError> {
+        info!("get saved searches for '{}'", user.username());
+        let searches = self.repo.get_mandarake_searches(user.id()).await?;
+        Ok(
+            searches.into_iter()
+                .filter(|s| s.following())
+                .collect()
+        )
+    }
+
+    async fn get_saved_searches_page(&self, user: &User, page: PageRequest) -> Result<Page<Search>, GetSearchesError> {
+        info!("get page {} of saved searches for '{}'", page.page(), user.username());
+        self.repo.get_mandarake_saved_searches_page(user.id(), page).await
+    }
+
+    async fn get_products_page(&self, page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        info!("get page {} of products", page.page());
+        self.repo.get_mandarake_products_page(page).await
+    }
+
+    async fn get_products_by_search(&self, search_id: i32) -> Result<Vec<Product>, GetProductsError> {
+        info!("get products by search with id '{}'", search_id);
+        self.repo.get_mandarake_products_by_search(search_id).await
+    }
+
+    async fn get_products_page_by_search(&self, search_id: i32, page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        info!("get page {} of products by search with id '{}'", page.page(), search_id);
+        self.repo.get_mandarake_products_page_by_search(search_id, page).await
+    }
+
+    async fn get_products_page_by_searches(&self, search_ids: &[i32], page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        info!("get page {} of products by searches with ids {:?}", page.page(), search_ids);
+        self.repo.get_mandarake_products_page_by_searches(search_ids, page).await
+    }
+
+    async fn get_product(&self, product_id: i32) -> Result<Product, GetProductError> {
+        info!("get product with id '{}'", product_id);
+        self.repo.get_mandarake_product(product_id).await
+    }
+
+    async fn get_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError> {
+        info!("get history of product with id '{}'", product_id);
+        self.repo.get_mandarake_product_history(product_id).await
+    }
+}
+
+impl<R, N, S, I> MandarakeServiceImpl<R, N, S, I>
+where
+    R: MandarakeRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: MandarakeScraper,
+    I: ImageCache
+{
+    /// Only new listings are notified about, and only to the users whose maximum price they are under.
+    async fn scrape_followed_searches(&self) -> Result<(), ScrapeProductsError> {
+        let followed_searches = self.repo.get_followed_mandarake_searches().await?;
+        // listings are shared between searches, a listing found by an earlier search is not new for a later one
+        let mut known_products = self.repo.get_mandarake_products().await?
+            .into_iter()
+            .map(|p| (p.url().to_owned(), p))
+            .collect::<HashMap<_, _>>();
+        for followed_search in followed_searches.iter() {
+            let search = followed_search.search();
+            let keyword = search.keyword();
+            info!("scrape available products for search '{}'", keyword);
+            let search_urls = self.repo.get_mandarake_products_by_search(search.id()).await?
+                .into_iter()
+                .map(|p| p.url().to_owned())
+                .collect::<BTreeSet<_>>();
+            let listings = self.scraper.get_listings(keyword).await?;
+            let listing_urls = listings.iter().map(|l| l.url()).collect::<BTreeSet<_>>();
+
+            let mut new_products = Vec::<Product>::new();
+            for listing in listings.iter() {
+                match known_products.get(listing.url()) {
+                    None => {
+                        if !listing.availability().is_available() {
+                            continue;
+                        }
+                        let product = self.repo.create_mandarake_product(&CreateProductArgs::new(search.id(), listing.clone())).await?;
+                        let product = self.core.cache_product_image(&self.repo, product).await;
+                        known_products.insert(product.url().to_owned(), product.clone());
+                        new_products.push(product);
+                    }
+                    Some(product) => {
+                        if !search_urls.contains(listing.url()) {
+                            self.repo.add_mandarake_product_search(listing.url(), search.id()).await?;
+                        }
+                        if product.price() != listing.price() || &product.availability() != listing.availability() {
+                            let args = UpdateProductArgs::new(listing.url().to_owned(), listing.price(), listing.availability().clone());
+                            let product = self.repo.update_mandarake_product(&args).await?;
+                            known_products.insert(product.url().to_owned(), product);
+                        }
+                    }
+                }
+            }
+            info!("found '{}' new products for search '{}'", new_products.len(), keyword);
+
+            self.core.notify(&self.repo, keyword, followed_search.followers(), &new_products, &[]).await?;
+
+            let sold_urls = search_urls.iter()
+                .filter(|u| !listing_urls.contains(u.as_str()))
+                .filter(|u| known_products.get(u.as_str()).is_some_and(|p| p.availability().is_available()))
+                .cloned()
+                .collect::<Vec<_>>();
+            info!("update '{}' products as now sold for search '{}'", sold_urls.len(), keyword);
+            for sold_url in sold_urls.into_iter() {
+                let price = known_products.get(&sold_url).map(|p| p.price()).unwrap_or_default();
+                let product = self.repo.update_mandarake_product(&UpdateProductArgs::new(sold_url, price, Availability::NotAvailable)).await?;
+                known_products.insert(product.url().to_owned(), product);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::domain::mandarake::models::product::{AddProductSearchError, CreateProductError, ListingData, UpdateProductError};
+    use crate::domain::mandarake::models::search::{FollowedSearch, SearchFollower};
+    use crate::domain::product_history::NotificationKind;
+    use crate::domain::test_util::{image_hash, user, TestImageCache, TestNotifier, TestRepo};
+    use chrono::Utc;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Default)]
+    struct Listings {
+        searches: Vec<FollowedSearch>,
+        /// The products with the ids of the searches that found them.
+        products: Vec<(Product, BTreeSet<i32>)>,
+    }
+
+    #[async_trait]
+    impl MandarakeRepository for TestRepo<Listings> {
+        async fn save_mandarake_search(&self, _user_id: i32, _req: &SearchArgs) -> Result<(), SaveSearchError> {
+            unimplemented!()
+        }
+
+        async fn delete_mandarake_search(&self, _user_id: i32, _search_id: i32) -> Result<(), DeleteSearchError> {
+            unimplemented!()
+        }
+
+        async fn get_mandarake_searches(&self, _user_id: i32) -> Result<Vec<Search>, GetSearchesError> {
+            unimplemented!()
+        }
+
+        async fn get_mandarake_saved_searches_page(&self, _user_id: i32, _page: PageRequest) -> Result<Page<Search>, GetSearchesError> {
+            unimplemented!()
+        }
+
+        async fn get_followed_mandarake_searches(&self) -> Result<Vec<FollowedSearch>, GetSearchesError> {
+            Ok(self.with_site(|s| s.searches.clone()))
+        }
+
+        async fn create_mandarake_product(&self, req: &CreateProductArgs) -> Result<Product, CreateProductError> {
+            self.with_site(|s| {
+                if s.products.iter().any(|(p, _)| p.url() == req.url()) {
+                    return Err(CreateProductError::DuplicateProduct { url: req.url().to_owned(), title: req.title().to_owned() });
+                }
+                let product = Product::new(
+                    s.products.len() as i32 + 1, Utc::now(), req.url().to_owned(), req.title().to_owned(), req.image_url().to_owned(),
+                    req.store().to_owned(), req.condition().to_owned(), req.price(), req.availability()
+                );
+                s.products.push((product.clone(), BTreeSet::from([req.search_id()])));
+                Ok(product)
+            })
+        }
+
+        async fn update_mandarake_product(&self, req: &UpdateProductArgs) -> Result<Product, UpdateProductError> {
+            self.with_site(|s| {
+                let (product, _) = s.products.iter_mut()
+                    .find(|(p, _)| p.url() == req.url())
+                    .ok_or(UpdateProductError::ProductMissing { url: req.url().to_owned() })?;
+                *product = Product::new(
+                    product.id(), product.date_added(), product.url().to_owned(), product.title().to_owned(), product.image_url().to_owned(),
+                    product.store().to_owned(), product.condition().to_owned(), req.price(), req.availability()
+                );
+                Ok(product.clone())
+            })
+        }
+
+        async fn add_mandarake_product_search(&self, url: &str, search_id: i32) -> Result<(), AddProductSearchError> {
+            self.with_site(|s| {
+                let (_, search_ids) = s.products.iter_mut()
+                    .find(|(p, _)| p.url() == url)
+                    .ok_or(AddProductSearchError::ProductMissing { url: url.to_owned() })?;
+                search_ids.insert(search_id);
+                Ok(())
+            })
+        }
+
+        async fn get_mandarake_product(&self, _product_id: i32) -> Result<Product, GetProductError> {
+            unimplemented!()
+        }
+
+        async fn get_mandarake_product_history(&self, _product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError> {
+            unimplemented!()
+        }
+
+        async fn get_mandarake_products(&self) -> Result<Vec<Product>, GetProductsError> {
+            Ok(self.with_site(|s| s.products.iter().map(|(p, _)| p.clone()).collect()))
+        }
+
+        async fn get_mandarake_products_by_search(&self, search_id: i32) -> Result<Vec<Product>, GetProductsError> {
+            Ok(self.with_site(|s| s.products.iter().filter(|(_, ids)| ids.contains(&search_id)).map(|(p, _)| p.clone()).collect()))
+        }
+
+        async fn get_mandarake_products_page(&self, _page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+            unimplemented!()
+        }
+
+        async fn get_mandarake_products_page_by_search(&self, _search_id: i32, _page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+            unimplemented!()
+        }
+
+        async fn get_mandarake_products_page_by_searches(&self, _search_ids: &[i32], _page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+            unimplemented!()
+        }
+    }
+
+    /// Finds the listings set for the keyword.
+    #[derive(Debug, Clone, Default)]
+    struct TestScraper {
+        listings: Arc<Mutex<HashMap<String, Vec<ListingData>>>>,
+    }
+
+    impl TestScraper {
+        fn set_listings(&self, keyword: &str, listings: Vec<ListingData>) {
+            self.listings.lock().unwrap().insert(keyword.to_owned(), listings);
+        }
+    }
+
+    #[async_trait]
+    impl MandarakeScraper for TestScraper {
+        async fn get_listings(&self, keyword: &str) -> Result<Vec<ListingData>, ScrapeProductsError> {
+            Ok(self.listings.lock().unwrap().get(keyword).cloned().unwrap_or_default())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scrape_notifies_followers_under_their_max_price() {
+        let followers = vec![SearchFollower::new(user(1, "alice"), Some(3000)), SearchFollower::new(user(2, "bob"), None)];
+        let repo = TestRepo::new(Listings { searches: vec![followed_search(1, "mafuyu", followers)], ..Default::default() });
+        let scraper = TestScraper::default();
+        scraper.set_listings("mafuyu", vec![listing("1", 2500), listing("2", 5000)]);
+        let (notifier, alice_notifier, bob_notifier) = (TestNotifier::default(), TestNotifier::default(), TestNotifier::default());
+        let user_notifiers = HashMap::from([("alice".to_owned(), alice_notifier.clone()), ("bob".to_owned(), bob_notifier.clone())]);
+        let service = MandarakeServiceImpl::new(repo.clone(), scraper, SiteCore::new(notifier.clone(), TestImageCache).with_user_notifiers(user_notifiers));
+
+        service.scrape(&service.scrape_lock().lock().await).await.unwrap();
+
+        assert_eq!(notifier.take(), vec![(NotificationKind::NewProduct, "mafuyu".to_owned(), vec![url("1"), url("2")])]);
+        assert_eq!(alice_notifier.take(), vec![(NotificationKind::NewProduct, "mafuyu".to_owned(), vec![url("1")])]);
+        assert_eq!(bob_notifier.take(), vec![(NotificationKind::NewProduct, "mafuyu".to_owned(), vec![url("1"), url("2")])]);
+        assert_eq!(repo.notifications(), vec![(1, NotificationKind::NewProduct, 1), (2, NotificationKind::NewProduct, 1), (2, NotificationKind::NewProduct, 2)]);
+        let images = repo.images().into_iter().map(|(id, image)| (id, image.hash().to_owned())).collect::<Vec<_>>();
+        assert_eq!(images, vec![(1, image_hash(&image_url("1"))), (2, image_hash(&image_url("2")))]);
+    }
+
+    #[tokio::test]
+    async fn test_scrape_notifies_listings_once() {
+        let repo = TestRepo::new(Listings {
+            searches: vec![
+                followed_search(1, "mafuyu", vec![SearchFollower::new(user(1, "alice"), None)]),
+                followed_search(2, "kantoku", vec![SearchFollower::new(user(1, "alice"), None)]),
+            ],
+            ..Default::default()
+        });
+        let scraper = TestScraper::default();
+        scraper.set_listings("mafuyu", vec![listing("1", 2500), listing("2", 5000)]);
+        scraper.set_listings("kantoku", vec![listing("1", 2500)]);
+        let notifier = TestNotifier::default();
+        let service = MandarakeServiceImpl::new(repo.clone(), scraper.clone(), SiteCore::new(notifier.clone(), TestImageCache));
+
+        service.scrape(&service.scrape_lock().lock().await).await.unwrap();
+        assert_eq!(notifier.take(), vec![(NotificationKind::NewProduct, "mafuyu".to_owned(), vec![url("1"), url("2")])]);
+        assert_eq!(repo.with_site(|s| s.products[0].1.clone()), BTreeSet::from([1, 2]));
+
+        scraper.set_listings("mafuyu", vec![listing("1", 2000), listing("3", 2800)]);
+        scraper.set_listings("kantoku", vec![listing("1", 2000)]);
+        service.scrape(&service.scrape_lock().lock().await).await.unwrap();
+        assert_eq!(notifier.take(), vec![(NotificationKind::NewProduct, "mafuyu".to_owned(), vec![url("3")])]);
+        assert_eq!(repo.notifications().len(), 3);
+        let products = repo.with_site(|s| s.products.iter().map(|(p, _)| (p.price(), p.availability())).collect::<Vec<_>>());
+        assert_eq!(products, vec![(2000, Availability::Available), (5000, Availability::NotAvailable), (2800, Availability::Available)]);
+    }
+
+    fn followed_search(id: i32, keyword: &str, followers: Vec<SearchFollower>) -> FollowedSearch {
+        FollowedSearch::new(Search::new(id, Utc::now(), keyword.to_owned(), true, None, Some(Utc::now())), followers)
+    }
+
+    fn listing(item_code: &str, price: i32) -> ListingData {
+        ListingData::new(url(item_code), "mafuyu_title".to_owned(), image_url(item_code), "中野店".to_owned(), "B".to_owned(), price, Availability::Available)
+    }
+
+    fn url(item_code: &str) -> String {
+        format!("https://order.mandarake.co.jp/order/detailPage/item?itemCode={}", item_code)
+    }
+
+    fn image_url(item_code: &str) -> String {
+        format!("https://img.mandarake.co.jp/webshopimg/{}.jpg", item_code)
+    }
+}
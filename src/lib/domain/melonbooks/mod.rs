@@ -1,3 +1,7 @@
+use crate::domain::site::Site;
+
 pub mod ports;
 pub mod models;
-pub mod service;
\ No newline at end of file
+pub mod service;
+
+pub const SITE: Site = Site::new("melonbooks", "Melonbooks", "https://www.melonbooks.co.jp/");
@@ -1,3 +1,5 @@
+use crate::domain::schedule::Schedule;
+use crate::domain::user::models::user::User;
 use chrono::{DateTime, Utc};
 use thiserror::Error;
 
@@ -8,11 +10,12 @@ pub struct Artist {
     name: String,
     following: bool,
     date_followed: Option<DateTime<Utc>>,
+    schedule: Option<Schedule>,
 }
 
 impl Artist {
-    pub fn new(id: i32, date_added: DateTime<Utc>, name: String, following: bool, date_followed: Option<DateTime<Utc>>) -> Self {
-        Artist { id, date_added, name, following, date_followed }
+    pub fn new(id: i32, date_added: DateTime<Utc>, name: String, following: bool, date_followed: Option<DateTime<Utc>>, schedule: Option<Schedule>) -> Self {
+        Artist { id, date_added, name, following, date_followed, schedule }
     }
    
     pub fn id(&self) -> i32 { self.id }
@@ -20,9 +23,35 @@ impl Artist {
     pub fn name(&self) -> &str { &self.name }
     pub fn following(&self) -> bool { self.following }
     pub fn date_followed(&self) -> Option<DateTime<Utc>> { self.date_followed.clone() }
+    /// Overrides the schedule of the site for this artist.
+    pub fn schedule(&self) -> Option<&Schedule> { self.schedule.as_ref() }
 }
 
-#[derive(Debug)]
+/// Artist followed by at least one user, scraped once for all of its followers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FollowedArtist {
+    artist: Artist,
+    followers: Vec<User>,
+    date_scraped: Option<DateTime<Utc>>,
+}
+
+impl FollowedArtist {
+    pub fn new(artist: Artist, followers: Vec<User>) -> Self {
+        FollowedArtist { artist, followers, date_scraped: None }
+    }
+
+    pub fn with_date_scraped(mut self, date_scraped: Option<DateTime<Utc>>) -> Self {
+        self.date_scraped = date_scraped;
+        self
+    }
+
+    pub fn artist(&self) -> &Artist { &self.artist }
+    pub fn followers(&self) -> &[User] { &self.followers }
+    /// Start of the last scrape of the artist.
+    pub fn date_scraped(&self) -> Option<DateTime<Utc>> { self.date_scraped }
+}
+
+#[derive(Debug, Clone)]
 pub struct ArtistArgs {
     name: String,
 }
@@ -55,6 +84,16 @@ pub enum UnfollowArtistError {
     Unknown(#[from] anyhow::Error),
 }
 
+#[derive(Debug, Error)]
+pub enum SetArtistScheduleError {
+    #[error("unknown artist with id '{id}'")]
+    UnknownArtist{ id: i32 },
+    #[error("artist '{name}' not followed")]
+    ArtistNotFollowed{ name: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
 #[derive(Debug, Error)]
 pub enum GetArtistsError {
     #[error(transparent)]
@@ -1,3 +1,3 @@
 pub mod product;
 pub mod artist;
-pub mod availability;
\ No newline at end of file
+pub mod query;
\ No newline at end of file
@@ -1,10 +1,16 @@
 use crate::domain::melonbooks::models::artist::{Artist, GetArtistsError};
-use crate::domain::melonbooks::models::availability::Availability;
+use crate::domain::availability::Availability;
+use crate::domain::product_history;
+use crate::domain::product_history::AddNotificationsError;
+use crate::domain::schedule::SetDateScrapedError;
+use crate::domain::melonbooks::SITE;
+use crate::domain::image::models::image::CachedImage;
+use crate::domain::site::{Site, SiteProduct};
 use crate::outbound::melonbooks_scraper::ParseError;
 use chrono::{DateTime, Utc};
 use thiserror::Error;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Product {
     id: i32,
     date_added: DateTime<Utc>,
@@ -18,11 +24,29 @@ pub struct Product {
     flags: Vec<String>,
     price: Option<String>,
     availability: Availability,
+    date_restocked: Option<DateTime<Utc>>,
+    image_hash: Option<String>,
+    image_phash: Option<u64>,
 }
 
 impl Product {
     pub fn new(id: i32, date_added: DateTime<Utc>, url: String, title: String, circle: Option<String>, artists: Vec<Artist>, image_url: String, category: String, tags: Vec<String>, flags: Vec<String>, price: Option<String>, availability: Availability) -> Self {
-        Self { id, date_added, url, title, circle, artists, image_url, category, tags, flags, price, availability }
+        Self { id, date_added, url, title, circle, artists, image_url, category, tags, flags, price, availability, date_restocked: None, image_hash: None, image_phash: None }
+    }
+
+    pub fn with_date_restocked(mut self, date_restocked: Option<DateTime<Utc>>) -> Self {
+        self.date_restocked = date_restocked;
+        self
+    }
+
+    pub fn with_image_hash(mut self, image_hash: Option<String>) -> Self {
+        self.image_hash = image_hash;
+        self
+    }
+
+    pub fn with_image_phash(mut self, image_phash: Option<u64>) -> Self {
+        self.image_phash = image_phash;
+        self
     }
 
     pub fn id(&self) -> i32 { self.id }
@@ -37,14 +61,47 @@ impl Product {
     pub fn flags(&self) -> &[String] { &self.flags }
     pub fn price(&self) -> Option<&str> { self.price.as_deref() }
     pub fn availability(&self) -> Availability { self.availability.clone() }
+    pub fn date_restocked(&self) -> Option<DateTime<Utc>> { self.date_restocked }
+    pub fn image_hash(&self) -> Option<&str> { self.image_hash.as_deref() }
+    pub fn image_phash(&self) -> Option<u64> { self.image_phash }
+
+    /// When the product was added or, if it was restocked since, restocked.
+    pub fn date_changed(&self) -> DateTime<Utc> {
+        self.date_restocked.unwrap_or(self.date_added)
+    }
 }
 
+/// Price changes are only known from the product page, which is scraped once when the product is added.
+pub type ProductHistoryEntry = product_history::ProductHistoryEntry<Availability, Option<String>>;
+
 impl AsRef<Product> for Product {
     fn as_ref(&self) -> &Self {
         self
     }
 }
 
+impl SiteProduct for Product {
+    const SITE: Site = SITE;
+
+    fn id(&self) -> i32 { self.id }
+    fn title(&self) -> &str { &self.title }
+    fn url(&self) -> &str { &self.url }
+    fn image_url(&self) -> &str { &self.image_url }
+    fn image_hash(&self) -> Option<&str> { self.image_hash.as_deref() }
+    fn image_phash(&self) -> Option<u64> { self.image_phash }
+
+    fn with_image(self, image: &CachedImage) -> Self {
+        self.with_image_hash(Some(image.hash().to_owned())).with_image_phash(image.perceptual_hash())
+    }
+
+    fn summary(&self) -> String {
+        match &self.price {
+            Some(price) => format!("{} [{}]\n{}", self.category, self.flags.join(" "), price),
+            None => format!("{} [{}]", self.category, self.flags.join(" "))
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ProductData {
     title: String,
@@ -74,7 +131,7 @@ impl ProductData {
     pub fn availability(&self) -> &Availability { &self.availability }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CreateProductArgs {
     url: String,
     title: String,
@@ -115,7 +172,7 @@ impl AsRef<CreateProductArgs> for CreateProductArgs {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct UpdateProductArgs {
     url: String,
     availability: Availability,
@@ -158,6 +215,19 @@ pub enum GetProductsError {
     #[error(transparent)]
     Unknown(#[from] anyhow::Error),
 }
+
+#[derive(Debug, Error)]
+pub enum GetCategoriesError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum GetFlagsError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
 #[derive(Debug, Error)]
 pub enum GetSkippingUrlsError {
     #[error(transparent)]
@@ -172,12 +242,16 @@ pub enum AddSkippingUrlError {
 
 #[derive(Debug, Error)]
 pub enum AddTitleSkipSequenceError {
+    #[error("title skip sequence '{sequence}' already exists")]
+    DuplicateSequence { sequence: String },
     #[error(transparent)]
     Unknown(#[from] anyhow::Error),
 }
 
 #[derive(Debug, Error)]
 pub enum DeleteTitleSkipSequenceError {
+    #[error("unknown title skip sequence '{sequence}'")]
+    UnknownSequence { sequence: String },
     #[error(transparent)]
     Unknown(#[from] anyhow::Error),
 }
@@ -188,6 +262,16 @@ pub enum GetTitleSkipSequencesError {
     Unknown(#[from] anyhow::Error),
 }
 
+#[derive(Debug, Error)]
+pub enum GetAvailabilityStatsError {
+    #[error(transparent)]
+    GetArtistsError(#[from] GetArtistsError),
+    #[error(transparent)]
+    GetProductsError(#[from] GetProductsError),
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
 #[derive(Debug, Error)]
 pub enum ScrapeProductsError {
     #[error(transparent)]
@@ -207,5 +291,9 @@ pub enum ScrapeProductsError {
     #[error(transparent)]
     GetTitleSkipSequencesError(#[from] GetTitleSkipSequencesError),
     #[error(transparent)]
+    AddNotificationsError(#[from] AddNotificationsError),
+    #[error(transparent)]
+    SetDateScrapedError(#[from] SetDateScrapedError),
+    #[error(transparent)]
     Unknown(#[from] anyhow::Error),
 }
\ No newline at end of file
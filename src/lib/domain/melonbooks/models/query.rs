@@ -0,0 +1,80 @@
+use crate::domain::availability::Availability;
+use crate::domain::pagination::{PageRequest, SortDirection};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter, EnumString};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Display, EnumString, EnumIter, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ProductSort {
+    #[default]
+    DateAdded,
+    /// Newest of the date added and the date restocked.
+    DateChanged,
+    Title,
+    Price,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProductQuery {
+    page: PageRequest,
+    sort: ProductSort,
+    direction: SortDirection,
+    artist_id: Option<i32>,
+    followed_by: Option<i32>,
+    category: Option<String>,
+    availability: Option<Availability>,
+    flag: Option<String>,
+    added_from: Option<NaiveDate>,
+    added_to: Option<NaiveDate>,
+}
+
+impl ProductQuery {
+    pub fn new(page: PageRequest, sort: ProductSort, direction: SortDirection) -> Self {
+        Self { page, sort, direction, ..Default::default() }
+    }
+
+    pub fn with_artist_id(mut self, artist_id: Option<i32>) -> Self {
+        self.artist_id = artist_id;
+        self
+    }
+
+    /// Restricts the products to artists followed by the user with this id.
+    pub fn with_followed_by(mut self, user_id: Option<i32>) -> Self {
+        self.followed_by = user_id;
+        self
+    }
+
+    pub fn with_category(mut self, category: Option<String>) -> Self {
+        self.category = category;
+        self
+    }
+
+    pub fn with_availability(mut self, availability: Option<Availability>) -> Self {
+        self.availability = availability;
+        self
+    }
+
+    pub fn with_flag(mut self, flag: Option<String>) -> Self {
+        self.flag = flag;
+        self
+    }
+
+    pub fn with_date_added_range(mut self, added_from: Option<NaiveDate>, added_to: Option<NaiveDate>) -> Self {
+        self.added_from = added_from;
+        self.added_to = added_to;
+        self
+    }
+
+    pub fn page(&self) -> PageRequest { self.page }
+    pub fn sort(&self) -> ProductSort { self.sort }
+    pub fn direction(&self) -> SortDirection { self.direction }
+    pub fn artist_id(&self) -> Option<i32> { self.artist_id }
+    pub fn followed_by(&self) -> Option<i32> { self.followed_by }
+    pub fn category(&self) -> Option<&str> { self.category.as_deref() }
+    pub fn availability(&self) -> Option<&Availability> { self.availability.as_ref() }
+    pub fn flag(&self) -> Option<&str> { self.flag.as_deref() }
+    pub fn added_from(&self) -> Option<NaiveDate> { self.added_from }
+    pub fn added_to(&self) -> Option<NaiveDate> { self.added_to }
+}
@@ -1,47 +1,84 @@
-use crate::domain::melonbooks::models::artist::{Artist, ArtistArgs, FollowArtistError, GetArtistsError, UnfollowArtistError};
-use crate::domain::melonbooks::models::product::{AddSkippingUrlError, AddTitleSkipSequenceError, CreateProductArgs, CreateProductError, DeleteTitleSkipSequenceError, GetProductsError, GetSkippingUrlsError, GetTitleSkipSequencesError, Product, ProductData, ScrapeProductsError, UpdateProductArgs, UpdateProductError};
+use crate::domain::melonbooks::models::artist::{Artist, ArtistArgs, FollowArtistError, FollowedArtist, GetArtistsError, SetArtistScheduleError, UnfollowArtistError};
+use crate::domain::melonbooks::models::product::{AddSkippingUrlError, AddTitleSkipSequenceError, CreateProductArgs, CreateProductError, DeleteTitleSkipSequenceError, GetAvailabilityStatsError, GetCategoriesError, GetFlagsError, GetProductsError, GetSkippingUrlsError, GetTitleSkipSequencesError, Product, ProductData, ProductHistoryEntry, ScrapeProductsError, UpdateProductArgs, UpdateProductError};
+use crate::domain::melonbooks::models::query::ProductQuery;
+use crate::domain::availability_stats::{AvailabilityEvent, AvailabilityStats};
+use crate::domain::pagination::{Page, PageRequest};
+use crate::domain::product_history::GetProductError;
+use crate::domain::schedule::{GetTargetSchedulesError, Schedule, SetDateScrapedError};
+use crate::domain::scrape_event::ScrapeEvent;
+use crate::domain::site::SiteService;
+use crate::domain::search::{SearchProductsError, SearchResult};
+use crate::domain::user::models::user::User;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use tokio::sync::broadcast;
 
 #[async_trait]
-pub trait MelonbooksService: Send + Sync + 'static {
-    async fn follow_artist(&self, req: &ArtistArgs) -> Result<(), FollowArtistError>;
-    async fn unfollow_artist(&self, artist_id: i32) -> Result<(), UnfollowArtistError>;
-    async fn get_artists(&self) -> Result<Vec<Artist>, GetArtistsError>;
-    async fn get_followed_artists(&self) -> Result<Vec<Artist>, GetArtistsError>;
+pub trait MelonbooksService: SiteService {
+    async fn follow_artist(&self, user: &User, req: &ArtistArgs) -> Result<(), FollowArtistError>;
+    async fn unfollow_artist(&self, user: &User, artist_id: i32) -> Result<(), UnfollowArtistError>;
+    async fn get_artists(&self, user: &User) -> Result<Vec<Artist>, GetArtistsError>;
+    async fn get_artists_page(&self, user: &User, following: Option<bool>, page: PageRequest) -> Result<Page<Artist>, GetArtistsError>;
+    async fn get_followed_artists(&self, user: &User) -> Result<Vec<Artist>, GetArtistsError>;
+    /// Chooses `schedule` instead of the schedule of the site for the artist, `None` goes back to the site's.
+    /// The artist is scraped on the choice of most of its followers.
+    async fn set_artist_schedule(&self, user: &User, artist_id: i32, schedule: Option<&Schedule>) -> Result<(), SetArtistScheduleError>;
 
     async fn get_products(&self) -> Result<Vec<Product>, GetProductsError>;
     async fn get_products_by_artist(&self, artist_id: i32) -> Result<Vec<Product>, GetProductsError>;
+    async fn get_products_page(&self, query: &ProductQuery) -> Result<Page<Product>, GetProductsError>;
+    async fn get_product(&self, product_id: i32) -> Result<Product, GetProductError>;
+    async fn get_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError>;
+    async fn get_availability_stats(&self, user: &User) -> Result<AvailabilityStats, GetAvailabilityStatsError>;
+    async fn search_products(&self, query: &str) -> Result<Vec<SearchResult<Product>>, SearchProductsError>;
+    async fn search_products_page(&self, query: &str, page: PageRequest) -> Result<Page<SearchResult<Product>>, SearchProductsError>;
+    async fn get_categories(&self) -> Result<Vec<String>, GetCategoriesError>;
+    async fn get_categories_page(&self, page: PageRequest) -> Result<Page<String>, GetCategoriesError>;
+    async fn get_flags(&self) -> Result<Vec<String>, GetFlagsError>;
+    async fn get_flags_page(&self, page: PageRequest) -> Result<Page<String>, GetFlagsError>;
 
-    async fn add_title_skip_sequence(&self, sequence: &str) -> Result<(), AddTitleSkipSequenceError>;
-    async fn delete_title_skip_sequence(&self, sequence: &str) -> Result<(), DeleteTitleSkipSequenceError>;
-    async fn get_title_skip_sequences(&self) -> Result<Vec<String>, GetTitleSkipSequencesError>;
-
-    async fn scrape_available_products(&self) -> Result<(), ScrapeProductsError>;
+    async fn add_title_skip_sequence(&self, user: &User, sequence: &str) -> Result<(), AddTitleSkipSequenceError>;
+    async fn delete_title_skip_sequence(&self, user: &User, sequence: &str) -> Result<(), DeleteTitleSkipSequenceError>;
+    async fn get_title_skip_sequences(&self, user: &User) -> Result<Vec<String>, GetTitleSkipSequencesError>;
+    async fn get_title_skip_sequences_page(&self, user: &User, page: PageRequest) -> Result<Page<String>, GetTitleSkipSequencesError>;
+    fn subscribe_scrape_events(&self) -> broadcast::Receiver<ScrapeEvent<Product>>;
 }
 
 #[async_trait]
 pub trait MelonbooksRepository: Clone + Send + Sync + 'static {
-    async fn follow_melonbooks_artist(&self, req: &ArtistArgs) -> Result<(), FollowArtistError>;
-    async fn unfollow_melonbooks_artist(&self, artist_id: i32) -> Result<(), UnfollowArtistError>;
-    async fn get_melonbooks_artists(&self) -> Result<Vec<Artist>, GetArtistsError>;
+    async fn follow_melonbooks_artist(&self, user_id: i32, req: &ArtistArgs) -> Result<(), FollowArtistError>;
+    async fn unfollow_melonbooks_artist(&self, user_id: i32, artist_id: i32) -> Result<(), UnfollowArtistError>;
+    async fn get_melonbooks_artists(&self, user_id: i32) -> Result<Vec<Artist>, GetArtistsError>;
+    async fn get_melonbooks_artists_page(&self, user_id: i32, following: Option<bool>, page: PageRequest) -> Result<Page<Artist>, GetArtistsError>;
+    async fn get_followed_melonbooks_artists(&self) -> Result<Vec<FollowedArtist>, GetArtistsError>;
+    async fn set_melonbooks_artist_schedule(&self, user_id: i32, artist_id: i32, schedule: Option<&Schedule>) -> Result<(), SetArtistScheduleError>;
+    async fn set_melonbooks_artist_scraped(&self, artist_id: i32, date_scraped: DateTime<Utc>) -> Result<(), SetDateScrapedError>;
+    /// Products added after the first scrape of their artist and restocks since `since` by the id of the artists.
+    async fn get_melonbooks_artist_activity(&self, since: DateTime<Utc>) -> Result<HashMap<i32, usize>, GetTargetSchedulesError>;
 
     async fn create_melonbooks_product(&self, req: &CreateProductArgs) -> Result<Product, CreateProductError>;
     async fn update_melonbooks_product(&self, req: &UpdateProductArgs) -> Result<Product, UpdateProductError>;
+    async fn get_melonbooks_product(&self, product_id: i32) -> Result<Product, GetProductError>;
+    async fn get_melonbooks_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError>;
+    async fn get_melonbooks_availability_events(&self) -> Result<Vec<AvailabilityEvent>, GetAvailabilityStatsError>;
     async fn get_melonbooks_products(&self) -> Result<Vec<Product>, GetProductsError>;
     async fn get_melonbooks_products_by_artist(&self, artist_id: i32) -> Result<Vec<Product>, GetProductsError>;
+    async fn get_melonbooks_products_page(&self, query: &ProductQuery) -> Result<Page<Product>, GetProductsError>;
+    async fn search_melonbooks_products(&self, query: &str) -> Result<Vec<SearchResult<Product>>, SearchProductsError>;
+    async fn search_melonbooks_products_page(&self, query: &str, page: PageRequest) -> Result<Page<SearchResult<Product>>, SearchProductsError>;
+    async fn get_melonbooks_categories(&self) -> Result<Vec<String>, GetCategoriesError>;
+    async fn get_melonbooks_categories_page(&self, page: PageRequest) -> Result<Page<String>, GetCategoriesError>;
+    async fn get_melonbooks_flags(&self) -> Result<Vec<String>, GetFlagsError>;
+    async fn get_melonbooks_flags_page(&self, page: PageRequest) -> Result<Page<String>, GetFlagsError>;
 
     async fn add_melonbooks_skipping_url<S: AsRef<str> + Sync>(&self, url: &str, artists: &[S]) -> Result<(), AddSkippingUrlError>;
     async fn get_melonbooks_skipping_urls(&self) -> Result<Vec<String>, GetSkippingUrlsError>;
 
-    async fn add_melonbooks_title_skip_sequence(&self, sequence: &str) -> Result<(), AddTitleSkipSequenceError>;
-    async fn delete_melonbooks_title_skip_sequence(&self, sequence: &str) -> Result<(), DeleteTitleSkipSequenceError>;
-    async fn get_melonbooks_title_skip_sequences(&self) -> Result<Vec<String>, GetTitleSkipSequencesError>;
-}
-
-#[async_trait]
-pub trait MelonbooksNotifier: Clone + Send + Sync + 'static {
-    async fn new_products<P: AsRef<Product> + Sync>(&self, artist: &str, products: &[P]) -> ();
-    async fn restocked_products<P: AsRef<Product> + Sync>(&self, artist: &str, products: &[P],) -> ();
+    async fn add_melonbooks_title_skip_sequence(&self, user_id: i32, sequence: &str) -> Result<(), AddTitleSkipSequenceError>;
+    async fn delete_melonbooks_title_skip_sequence(&self, user_id: i32, sequence: &str) -> Result<(), DeleteTitleSkipSequenceError>;
+    async fn get_melonbooks_title_skip_sequences(&self, user_id: i32) -> Result<Vec<String>, GetTitleSkipSequencesError>;
+    async fn get_melonbooks_title_skip_sequences_page(&self, user_id: i32, page: PageRequest) -> Result<Page<String>, GetTitleSkipSequencesError>;
 }
 
 #[async_trait]
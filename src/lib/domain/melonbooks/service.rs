@@ -1,59 +1,217 @@
-use crate::domain::melonbooks::models::artist::{Artist, ArtistArgs, FollowArtistError, GetArtistsError, UnfollowArtistError};
-use crate::domain::melonbooks::models::availability::Availability;
-use crate::domain::melonbooks::models::product::{AddTitleSkipSequenceError, CreateProductArgs, DeleteTitleSkipSequenceError, GetProductsError, GetTitleSkipSequencesError, Product, ScrapeProductsError, UpdateProductArgs};
-use crate::domain::melonbooks::ports::{MelonbooksNotifier, MelonbooksRepository, MelonbooksScraper, MelonbooksService};
+use crate::domain::melonbooks::models::artist::{Artist, ArtistArgs, FollowArtistError, GetArtistsError, SetArtistScheduleError, UnfollowArtistError};
+use crate::domain::availability::Availability;
+use crate::domain::melonbooks::models::product::{AddTitleSkipSequenceError, CreateProductArgs, DeleteTitleSkipSequenceError, GetAvailabilityStatsError, GetCategoriesError, GetFlagsError, GetProductsError, GetTitleSkipSequencesError, Product, ProductHistoryEntry, ScrapeProductsError, UpdateProductArgs};
+use crate::domain::melonbooks::models::query::ProductQuery;
+use crate::domain::availability_stats::{AvailabilityStats, StatsProduct};
+use crate::domain::melonbooks::SITE;
+use crate::domain::image::ports::ImageCache;
+use crate::domain::pagination::{Page, PageRequest};
+use crate::domain::product_history::GetProductError;
+use crate::domain::product_index::models::target::TargetId;
+use crate::domain::schedule::{AdaptiveInterval, AdaptiveTarget, GetTargetSchedulesError, Schedule, ScheduleChanges, TargetSchedule};
+use crate::domain::scrape_event::{ScrapeEvent, ScrapeEvents};
+use crate::domain::melonbooks::ports::{MelonbooksRepository, MelonbooksScraper, MelonbooksService};
+use crate::domain::site::{Follower, ScrapeGuard, ScrapeLock, ScrapeSiteError, Site, SiteCore, SiteNotifier, SiteRepository, SiteService};
+use crate::domain::search::{SearchProductsError, SearchResult};
+use crate::domain::user::models::user::User;
 use log::info;
-use std::collections::BTreeSet;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap};
 use async_trait::async_trait;
+use chrono::Utc;
+use tokio::sync::broadcast;
+
+/// A follower of an artist, who does not want the products with a title containing one of the user's skip sequences.
+struct SkippingFollower<'a> {
+    user: &'a User,
+    title_skip_sequences: &'a [String],
+}
+
+impl SkippingFollower<'_> {
+    fn skips(&self, title: &str) -> bool {
+        self.title_skip_sequences.iter().any(|s| title.contains(s))
+    }
+}
+
+impl Follower<Product> for SkippingFollower<'_> {
+    fn user(&self) -> &User {
+        self.user
+    }
+
+    fn wants(&self, product: &Product) -> bool {
+        !self.skips(product.title())
+    }
+}
 
 #[derive(Debug, Clone)]
-pub struct MelonbooksServiceImpl<R, N, S>
+pub struct MelonbooksServiceImpl<R, N, S, I>
 where
-    R: MelonbooksRepository,
-    N: MelonbooksNotifier,
-    S: MelonbooksScraper
+    R: MelonbooksRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: MelonbooksScraper,
+    I: ImageCache
 {
     repo: R,
-    notifier: N,
     scraper: S,
+    core: SiteCore<N, I>,
+    scrape_events: ScrapeEvents<Product>,
+    schedule_changes: ScheduleChanges,
+    adaptive_interval: Option<AdaptiveInterval>,
 }
 
-impl<R, N, S> MelonbooksServiceImpl<R, N, S>
+impl<R, N, S, I> MelonbooksServiceImpl<R, N, S, I>
 where
-    R: MelonbooksRepository,
-    N: MelonbooksNotifier,
-    S: MelonbooksScraper
+    R: MelonbooksRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: MelonbooksScraper,
+    I: ImageCache
 {
-    pub fn new(repo: R, notifier: N, scraper: S) -> Self {
-        Self { repo, notifier, scraper }
+    pub fn new(repo: R, scraper: S, core: SiteCore<N, I>) -> Self {
+        Self { repo, scraper, core, scrape_events: ScrapeEvents::new(), schedule_changes: ScheduleChanges::new(), adaptive_interval: None }
+    }
+
+    /// Scrapes the artists without their own schedule more often the more new and restocked products they had.
+    pub fn with_adaptive_interval(mut self, adaptive_interval: Option<AdaptiveInterval>) -> Self {
+        self.adaptive_interval = adaptive_interval;
+        self
+    }
+}
+
+#[async_trait]
+impl<R, N, S, I> SiteService for MelonbooksServiceImpl<R, N, S, I>
+where
+    R: MelonbooksRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: MelonbooksScraper,
+    I: ImageCache
+{
+    fn site(&self) -> Site {
+        SITE
+    }
+
+    fn scrape_lock(&self) -> &ScrapeLock {
+        self.core.scrape_lock()
+    }
+
+    async fn scrape(&self, _guard: &ScrapeGuard) -> Result<(), ScrapeSiteError> {
+        info!("scrape available products of artists without schedule");
+        let adaptive = self.adaptive_interval.is_some();
+        self.scrape_artists(|a| a.schedule().is_none() && !adaptive).await
+            .map_err(|e| anyhow::Error::new(e).into())
+    }
+
+    async fn scrape_all(&self, _guard: &ScrapeGuard) -> Result<(), ScrapeSiteError> {
+        info!("scrape available products");
+        self.scrape_artists(|_| true).await
+            .map_err(|e| anyhow::Error::new(e).into())
+    }
+
+    async fn get_target_schedules(&self) -> Result<Vec<TargetSchedule>, GetTargetSchedulesError> {
+        let artists = self.repo.get_followed_melonbooks_artists().await
+            .map_err(anyhow::Error::new)?;
+        Ok(
+            artists.into_iter()
+                .filter_map(|a| {
+                    let artist = a.artist();
+                    let schedule = artist.schedule()?.clone();
+                    Some(TargetSchedule::new(TargetId::new(SITE, artist.id()), artist.name().to_owned(), schedule))
+                })
+                .collect()
+        )
+    }
+
+    async fn scrape_target(&self, _guard: &ScrapeGuard, artist_id: i32) -> Result<(), ScrapeSiteError> {
+        info!("scrape available products of artist with id '{}'", artist_id);
+        self.scrape_artists(|a| a.id() == artist_id).await
+            .map_err(|e| anyhow::Error::new(e).into())
+    }
+
+    fn subscribe_schedule_changes(&self) -> Option<broadcast::Receiver<()>> {
+        Some(self.schedule_changes.subscribe())
+    }
+
+    fn adaptive_interval(&self) -> Option<AdaptiveInterval> {
+        self.adaptive_interval
+    }
+
+    async fn get_adaptive_targets(&self) -> Result<Vec<AdaptiveTarget>, GetTargetSchedulesError> {
+        let Some(adaptive_interval) = self.adaptive_interval else {
+            return Ok(Vec::new());
+        };
+        let artists = self.repo.get_followed_melonbooks_artists().await
+            .map_err(anyhow::Error::new)?;
+        let activity = self.repo.get_melonbooks_artist_activity(AdaptiveInterval::activity_since(Utc::now())).await?;
+        Ok(
+            artists.into_iter()
+                .filter(|a| a.artist().schedule().is_none())
+                .map(|a| {
+                    let artist = a.artist();
+                    let activity = activity.get(&artist.id()).copied().unwrap_or_default();
+                    AdaptiveTarget::new(TargetId::new(SITE, artist.id()), artist.name().to_owned(), activity, a.date_scraped(), &adaptive_interval)
+                })
+                .collect()
+        )
+    }
+
+    async fn scrape_due_targets(&self, _guard: &ScrapeGuard) -> Result<(), ScrapeSiteError> {
+        let now = Utc::now();
+        let due = self.get_adaptive_targets().await
+            .map_err(anyhow::Error::new)?
+            .into_iter()
+            .filter(|t| t.is_due(now))
+            .map(|t| t.target_id().id())
+            .collect::<BTreeSet<_>>();
+        if due.is_empty() {
+            return Ok(());
+        }
+        info!("scrape available products of '{}' artists due by their activity", due.len());
+        self.scrape_artists(|a| due.contains(&a.id())).await
+            .map_err(|e| anyhow::Error::new(e).into())
     }
 }
 
 #[async_trait]
-impl<R, N, S> MelonbooksService for MelonbooksServiceImpl<R, N, S>
+impl<R, N, S, I> MelonbooksService for MelonbooksServiceImpl<R, N, S, I>
 where
-    R: MelonbooksRepository,
-    N: MelonbooksNotifier,
-    S: MelonbooksScraper
+    R: MelonbooksRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: MelonbooksScraper,
+    I: ImageCache
 {
-    async fn follow_artist(&self, artist_args: &ArtistArgs) -> Result<(), FollowArtistError> {
-        info!("follow artist '{}'", artist_args.name());
-        self.repo.follow_melonbooks_artist(artist_args).await
+    async fn follow_artist(&self, user: &User, artist_args: &ArtistArgs) -> Result<(), FollowArtistError> {
+        info!("follow artist '{}' for '{}'", artist_args.name(), user.username());
+        self.repo.follow_melonbooks_artist(user.id(), artist_args).await?;
+        self.schedule_changes.publish();
+        Ok(())
+    }
+
+    async fn unfollow_artist(&self, user: &User, artist_id: i32) -> Result<(), UnfollowArtistError> {
+        info!("unfollow artist with id '{}' for '{}'", artist_id, user.username());
+        self.repo.unfollow_melonbooks_artist(user.id(), artist_id).await?;
+        self.schedule_changes.publish();
+        Ok(())
+    }
+
+    async fn set_artist_schedule(&self, user: &User, artist_id: i32, schedule: Option<&Schedule>) -> Result<(), SetArtistScheduleError> {
+        info!("set schedule of artist with id '{}' to {:?} for '{}'", artist_id, schedule.map(|s| s.expression()), user.username());
+        self.repo.set_melonbooks_artist_schedule(user.id(), artist_id, schedule).await?;
+        self.schedule_changes.publish();
+        Ok(())
     }
 
-    async fn unfollow_artist(&self, artist_id: i32) -> Result<(), UnfollowArtistError> {
-        info!("unfollow artist with id '{}'", artist_id);
-        self.repo.unfollow_melonbooks_artist(artist_id).await
+    async fn get_artists(&self, user: &User) -> Result<Vec<Artist>, GetArtistsError> {
+        info!("get artists for '{}'", user.username());
+        self.repo.get_melonbooks_artists(user.id()).await
     }
 
-    async fn get_artists(&self) -> Result<Vec<Artist>, GetArtistsError> {
-        info!("get artists");
-        self.repo.get_melonbooks_artists().await
+    async fn get_artists_page(&self, user: &User, following: Option<bool>, page: PageRequest) -> Result<Page<Artist>, GetArtistsError> {
+        info!("get page {} of artists for '{}'", page.page(), user.username());
+        self.repo.get_melonbooks_artists_page(user.id(), following, page).await
     }
 
-    async fn get_followed_artists(&self) -> Result<Vec<Artist>, GetArtistsError> {
-        info!("get followed artists");
-        let artists = self.repo.get_melonbooks_artists().await?;
+    async fn get_followed_artists(&self, user: &User) -> Result<Vec<Artist>, GetArtistsError> {
+        info!("get followed artists for '{}'", user.username());
+        let artists = self.repo.get_melonbooks_artists(user.id()).await?;
         Ok(
             artists.into_iter()
                 .filter(|a| a.following())
@@ -71,26 +229,129 @@ where
         self.repo.get_melonbooks_products_by_artist(artist_id).await
     }
 
-    async fn get_title_skip_sequences(&self) -> Result<Vec<String>, GetTitleSkipSequencesError> {
-        info!("get title skip sequences");
-        self.repo.get_melonbooks_title_skip_sequences().await
+    async fn get_products_page(&self, query: &ProductQuery) -> Result<Page<Product>, GetProductsError> {
+        info!("get products page for {:?}", query);
+        self.repo.get_melonbooks_products_page(query).await
     }
 
-    async fn add_title_skip_sequence(&self, sequence: &str) -> Result<(), AddTitleSkipSequenceError> {
-        info!("add title skip sequences");
-        self.repo.add_melonbooks_title_skip_sequence(sequence).await
+    async fn get_product(&self, product_id: i32) -> Result<Product, GetProductError> {
+        info!("get product with id '{}'", product_id);
+        self.repo.get_melonbooks_product(product_id).await
     }
 
-    async fn delete_title_skip_sequence(&self, sequence: &str) -> Result<(), DeleteTitleSkipSequenceError> {
-        info!("delete title skip sequences");
-        self.repo.delete_melonbooks_title_skip_sequence(sequence).await
+    async fn get_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError> {
+        info!("get history of product with id '{}'", product_id);
+        self.repo.get_melonbooks_product_history(product_id).await
     }
 
-    async fn scrape_available_products(&self) -> Result<(), ScrapeProductsError> {
-        info!("scrape available products");
-        let artists = self.get_artists().await?;
-        for artist in artists.iter().filter(|a| a.following()).collect::<Vec<&Artist>>() {
+    async fn get_availability_stats(&self, user: &User) -> Result<AvailabilityStats, GetAvailabilityStatsError> {
+        info!("get availability stats for '{}'", user.username());
+        let artists = self.get_followed_artists(user).await?
+            .into_iter()
+            .map(|a| a.name().to_owned())
+            .collect::<BTreeSet<_>>();
+        let products = self.repo.get_melonbooks_products().await?
+            .into_iter()
+            .map(|p| {
+                let groups = p.artists().iter()
+                    .map(|a| a.name().to_owned())
+                    .filter(|a| artists.contains(a))
+                    .collect::<Vec<_>>();
+                StatsProduct::new(p.id(), p.title().to_owned(), p.date_added(), groups)
+            })
+            .filter(|p| p.has_groups())
+            .collect::<Vec<_>>();
+        let events = self.repo.get_melonbooks_availability_events().await?;
+        Ok(AvailabilityStats::new(&products, &events, Utc::now()))
+    }
+
+    async fn search_products(&self, query: &str) -> Result<Vec<SearchResult<Product>>, SearchProductsError> {
+        info!("search products for '{}'", query);
+        self.repo.search_melonbooks_products(query).await
+    }
+
+    async fn search_products_page(&self, query: &str, page: PageRequest) -> Result<Page<SearchResult<Product>>, SearchProductsError> {
+        info!("search page {} of products for '{}'", page.page(), query);
+        self.repo.search_melonbooks_products_page(query, page).await
+    }
+
+    async fn get_categories(&self) -> Result<Vec<String>, GetCategoriesError> {
+        info!("get categories");
+        self.repo.get_melonbooks_categories().await
+    }
+
+    async fn get_categories_page(&self, page: PageRequest) -> Result<Page<String>, GetCategoriesError> {
+        info!("get page {} of categories", page.page());
+        self.repo.get_melonbooks_categories_page(page).await
+    }
+
+    async fn get_flags(&self) -> Result<Vec<String>, GetFlagsError> {
+        info!("get flags");
+        self.repo.get_melonbooks_flags().await
+    }
+
+    async fn get_flags_page(&self, page: PageRequest) -> Result<Page<String>, GetFlagsError> {
+        info!("get page {} of flags", page.page());
+        self.repo.get_melonbooks_flags_page(page).await
+    }
+
+    async fn get_title_skip_sequences(&self, user: &User) -> Result<Vec<String>, GetTitleSkipSequencesError> {
+        info!("get title skip sequences for '{}'", user.username());
+        self.repo.get_melonbooks_title_skip_sequences(user.id()).await
+    }
+
+    async fn get_title_skip_sequences_page(&self, user: &User, page: PageRequest) -> Result<Page<String>, GetTitleSkipSequencesError> {
+        info!("get page {} of title skip sequences for '{}'", page.page(), user.username());
+        self.repo.get_melonbooks_title_skip_sequences_page(user.id(), page).await
+    }
+
+    async fn add_title_skip_sequence(&self, user: &User, sequence: &str) -> Result<(), AddTitleSkipSequenceError> {
+        info!("add title skip sequences for '{}'", user.username());
+        self.repo.add_melonbooks_title_skip_sequence(user.id(), sequence).await
+    }
+
+    async fn delete_title_skip_sequence(&self, user: &User, sequence: &str) -> Result<(), DeleteTitleSkipSequenceError> {
+        info!("delete title skip sequences for '{}'", user.username());
+        self.repo.delete_melonbooks_title_skip_sequence(user.id(), sequence).await
+    }
+
+    fn subscribe_scrape_events(&self) -> broadcast::Receiver<ScrapeEvent<Product>> {
+        self.scrape_events.subscribe()
+    }
+}
+
+impl<R, N, S, I> MelonbooksServiceImpl<R, N, S, I>
+where
+    R: MelonbooksRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: MelonbooksScraper,
+    I: ImageCache
+{
+    /// Scrapes the followed artists matching `filter`, the caller holds the scrape lock.
+    async fn scrape_artists(&self, filter: impl Fn(&Artist) -> bool + Send + Sync) -> Result<(), ScrapeProductsError> {
+        let result = self.scrape_followed_artists(filter).await;
+        self.scrape_events.publish(ScrapeEvent::Finished);
+        result
+    }
+
+    async fn scrape_followed_artists(&self, filter: impl Fn(&Artist) -> bool + Send + Sync) -> Result<(), ScrapeProductsError> {
+        let followed_artists = self.repo.get_followed_melonbooks_artists().await?
+            .into_iter()
+            .filter(|a| filter(a.artist()))
+            .collect::<Vec<_>>();
+        let mut title_skip_sequences = HashMap::<i32, Vec<String>>::new();
+        for user in followed_artists.iter().flat_map(|a| a.followers()) {
+            if let Entry::Vacant(entry) = title_skip_sequences.entry(user.id()) {
+                entry.insert(self.repo.get_melonbooks_title_skip_sequences(user.id()).await?);
+            }
+        }
+        let targets = followed_artists.len();
+        self.scrape_events.publish(ScrapeEvent::Started { targets });
+        for (index, followed_artist) in followed_artists.iter().enumerate() {
+            let artist = followed_artist.artist();
             info!("scrape available products for '{}'", artist.name());
+            let progress = |pages_fetched: usize| ScrapeEvent::Progress { target: artist.name().to_owned(), index, targets, pages_fetched };
+            self.scrape_events.publish(progress(0));
             let products = self.repo.get_melonbooks_products_by_artist(artist.id()).await?;
             let (available_products, unavailable_products) = products.iter()
                 .partition::<Vec<_>, _>(|p| p.availability().is_available());
@@ -99,36 +360,53 @@ where
             let skip_urls = self.repo.get_melonbooks_skipping_urls().await?.into_iter().collect::<BTreeSet<_>>();
             let urls = self.scraper.get_potential_product_urls(artist.name()).await?
                 .into_iter().filter(|u| !skip_urls.contains(u)).collect::<Vec<_>>();
+            let mut pages_fetched = 1;
+            self.scrape_events.publish(progress(pages_fetched));
             let (new_urls, restocked_urls) = urls.iter()
                 .filter(|u| !available_urls.contains(u.as_str()))
                 .partition::<Vec<_>, _>(|u| !unavailable_urls.contains(u.as_str()));
-            
-            let title_skip_sequences = self.repo.get_melonbooks_title_skip_sequences().await?;
+
+            let followers = followed_artist.followers().iter()
+                .map(|user| SkippingFollower { user, title_skip_sequences: title_skip_sequences.get(&user.id()).map(Vec::as_slice).unwrap_or_default() })
+                .collect::<Vec<_>>();
+            let followers_for = |title: &str| followers.iter()
+                .filter(|f| !f.skips(title))
+                .map(|f| f.user.id())
+                .collect::<Vec<_>>();
+
             let mut restocked_products = Vec::<Product>::new();
             for restocked_url in restocked_urls.into_iter() {
                 let product = self.repo.update_melonbooks_product(&UpdateProductArgs::new(restocked_url.to_owned(), Availability::Available)).await?;
-                if title_skip_sequences.iter().all(|s| !product.title().contains(s)) {
+                let product = self.core.cache_product_image(&self.repo, product).await;
+                let followers = followers_for(product.title());
+                if !followers.is_empty() {
+                    self.scrape_events.publish(ScrapeEvent::RestockedProduct { product: product.clone(), followers });
                     restocked_products.push(product);
                 }
             }
             info!("found '{}' restocked products for '{}'", restocked_products.len(), artist.name());
-            self.notifier.restocked_products(artist.name(), &restocked_products).await;
 
             let mut new_products = Vec::<Product>::new();
             for new_url in new_urls.into_iter() {
                 let product_data = self.scraper.get_product(new_url).await?;
+                pages_fetched += 1;
+                self.scrape_events.publish(progress(pages_fetched));
                 if product_data.artists().iter().all(|n| n != artist.name()) {
                     self.repo.add_melonbooks_skipping_url(new_url, product_data.artists()).await?;
                     continue;
                 }
-                if title_skip_sequences.iter().all(|s| !product_data.title().contains(s)) {
+                let followers = followers_for(product_data.title());
+                if !followers.is_empty() {
                     let args = CreateProductArgs::new_from_data(new_url.to_owned(), product_data);
                     let product = self.repo.create_melonbooks_product(&args).await?;
+                    let product = self.core.cache_product_image(&self.repo, product).await;
+                    self.scrape_events.publish(ScrapeEvent::NewProduct { product: product.clone(), followers });
                     new_products.push(product);
                 }
             }
             info!("found '{}' new products for '{}'", new_products.len(), artist.name());
-            self.notifier.new_products(artist.name(), &new_products).await;
+
+            self.core.notify(&self.repo, artist.name(), &followers, &new_products, &restocked_products).await?;
 
             let newly_unavailable_products = available_products.iter()
                 .filter(|p| !urls.iter().any(|u| u.eq(p.url())))
@@ -137,6 +415,7 @@ where
             for newly_unavailable in newly_unavailable_products.into_iter() {
                 self.repo.update_melonbooks_product(&UpdateProductArgs::new(newly_unavailable.url().to_owned(), Availability::NotAvailable)).await?;
             }
+            self.repo.set_melonbooks_artist_scraped(artist.id(), Utc::now()).await?;
         }
         Ok(())
     }
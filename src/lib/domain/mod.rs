@@ -1,2 +1,22 @@
 pub mod amiami;
-pub mod melonbooks;
\ No newline at end of file
+pub mod availability;
+pub mod availability_stats;
+pub mod booth;
+pub mod digital;
+pub mod duplicate;
+pub mod figure;
+pub mod image;
+pub mod mandarake;
+pub mod melonbooks;
+pub mod pagination;
+pub mod product_history;
+pub mod product_index;
+pub mod schedule;
+pub mod scrape_event;
+pub mod search;
+pub mod site;
+pub mod surugaya;
+#[cfg(test)]
+pub mod test_util;
+pub mod toranoana;
+pub mod user;
\ No newline at end of file
@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter, EnumString};
+
+pub const DEFAULT_PAGE_SIZE: u32 = 50;
+pub const MAX_PAGE_SIZE: u32 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageRequest {
+    page: u32,
+    page_size: u32,
+}
+
+impl PageRequest {
+    /// Pages are 1-based, out of range values are clamped.
+    pub fn new(page: u32, page_size: u32) -> Self {
+        Self { page: page.max(1), page_size: page_size.clamp(1, MAX_PAGE_SIZE) }
+    }
+
+    pub fn page(&self) -> u32 { self.page }
+    pub fn page_size(&self) -> u32 { self.page_size }
+
+    pub fn offset(&self) -> i64 {
+        (self.page as i64 - 1) * self.page_size as i64
+    }
+}
+
+impl Default for PageRequest {
+    fn default() -> Self {
+        Self::new(1, DEFAULT_PAGE_SIZE)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Page<T> {
+    items: Vec<T>,
+    request: PageRequest,
+    total_items: i64,
+}
+
+impl<T> Page<T> {
+    pub fn new(items: Vec<T>, request: PageRequest, total_items: i64) -> Self {
+        Self { items, request, total_items }
+    }
+
+    pub fn items(&self) -> &[T] { &self.items }
+    pub fn page(&self) -> u32 { self.request.page() }
+    pub fn page_size(&self) -> u32 { self.request.page_size() }
+    pub fn total_items(&self) -> i64 { self.total_items }
+
+    pub fn total_pages(&self) -> u32 {
+        ((self.total_items.max(0) as u64).div_ceil(self.page_size() as u64) as u32).max(1)
+    }
+
+    pub fn into_items(self) -> Vec<T> {
+        self.items
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page::new(self.items.into_iter().map(f).collect(), self.request, self.total_items)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Display, EnumString, EnumIter, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum SortDirection {
+    Asc,
+    #[default]
+    Desc,
+}
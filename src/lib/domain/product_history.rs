@@ -0,0 +1,72 @@
+use chrono::{DateTime, Utc};
+use strum_macros::{Display, EnumString};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+pub enum NotificationKind {
+    NewProduct,
+    RestockedProduct,
+    /// A product that went on sale, for sites without stock.
+    DiscountedProduct,
+    /// Preorders of a product opened, for sites that announce preorder windows.
+    PreorderOpened,
+    /// Preorders of a product close soon.
+    PreorderClosing,
+}
+
+impl TryFrom<String> for NotificationKind {
+    type Error = strum::ParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::try_from(value.as_str())
+    }
+}
+
+impl From<NotificationKind> for String {
+    fn from(value: NotificationKind) -> Self {
+        value.to_string()
+    }
+}
+
+/// A recorded change of a product, `A` being the availability and `P` the price of the site.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProductChange<A, P> {
+    Availability(A),
+    Price(P),
+    /// `username` is the user notified about the product.
+    Notification { kind: NotificationKind, username: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProductHistoryEntry<A, P> {
+    date: DateTime<Utc>,
+    change: ProductChange<A, P>,
+}
+
+impl<A, P> ProductHistoryEntry<A, P> {
+    pub fn new(date: DateTime<Utc>, change: ProductChange<A, P>) -> Self {
+        Self { date, change }
+    }
+
+    pub fn date(&self) -> DateTime<Utc> { self.date }
+    pub fn change(&self) -> &ProductChange<A, P> { &self.change }
+}
+
+/// Oldest first, entries of the same date keep their order.
+pub fn sort_history<A, P>(entries: &mut [ProductHistoryEntry<A, P>]) {
+    entries.sort_by_key(|e| e.date);
+}
+
+#[derive(Debug, Error)]
+pub enum GetProductError {
+    #[error("Product {id} does not exist")]
+    ProductMissing { id: i32 },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum AddNotificationsError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
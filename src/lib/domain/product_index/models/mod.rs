@@ -0,0 +1,2 @@
+pub mod product;
+pub mod target;
\ No newline at end of file
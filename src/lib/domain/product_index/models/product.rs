@@ -0,0 +1,146 @@
+use crate::domain::availability::Availability;
+use crate::domain::product_index::models::target::TargetId;
+use crate::domain::site::Site;
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+/// A product of any site with the fields all sites have.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedProduct {
+    site: Site,
+    product_id: i32,
+    date_added: DateTime<Utc>,
+    url: String,
+    title: String,
+    image_url: String,
+    image_hash: Option<String>,
+    price: Option<i32>,
+    availability: Availability,
+    date_restocked: Option<DateTime<Utc>>,
+}
+
+impl IndexedProduct {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(site: Site, product_id: i32, date_added: DateTime<Utc>, url: String, title: String, image_url: String, image_hash: Option<String>, price: Option<i32>, availability: Availability) -> Self {
+        Self { site, product_id, date_added, url, title, image_url, image_hash, price, availability, date_restocked: None }
+    }
+
+    pub fn with_date_restocked(mut self, date_restocked: Option<DateTime<Utc>>) -> Self {
+        self.date_restocked = date_restocked;
+        self
+    }
+
+    pub fn site(&self) -> Site { self.site }
+    pub fn product_id(&self) -> i32 { self.product_id }
+    pub fn date_added(&self) -> DateTime<Utc> { self.date_added }
+    pub fn url(&self) -> &str { &self.url }
+    pub fn title(&self) -> &str { &self.title }
+    pub fn image_url(&self) -> &str { &self.image_url }
+    pub fn image_hash(&self) -> Option<&str> { self.image_hash.as_deref() }
+    /// In yen, the lowest price of products with several.
+    pub fn price(&self) -> Option<i32> { self.price }
+    pub fn availability(&self) -> &Availability { &self.availability }
+    pub fn date_restocked(&self) -> Option<DateTime<Utc>> { self.date_restocked }
+
+    /// The product's page on this server.
+    pub fn path(&self) -> String {
+        format!("/{}/product/{}", self.site.id(), self.product_id)
+    }
+}
+
+/// Prices of the index are text as Melonbooks keeps its prices as shown, e.g. `¥ 1,100`.
+pub fn parse_price(text: &str) -> Option<i32> {
+    let digits = text.chars().filter(|c| c.is_ascii_digit()).collect::<String>();
+    digits.parse().ok()
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IndexSort {
+    #[default]
+    DateAdded,
+    /// By the restock of restocked products, the feeds show restocks as new entries.
+    DateChanged,
+}
+
+/// Which products to show in which order, `followed` leaves out products of targets the user does not follow.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexFilter {
+    site: Option<Site>,
+    target: Option<TargetId>,
+    followed: bool,
+    sort: IndexSort,
+}
+
+impl IndexFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_site(mut self, site: Option<Site>) -> Self {
+        self.site = site;
+        self
+    }
+
+    pub fn with_target(mut self, target: Option<TargetId>) -> Self {
+        self.target = target;
+        self
+    }
+
+    pub fn with_followed(mut self, followed: bool) -> Self {
+        self.followed = followed;
+        self
+    }
+
+    pub fn with_sort(mut self, sort: IndexSort) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    pub fn site(&self) -> Option<Site> { self.site }
+    pub fn target(&self) -> Option<TargetId> { self.target }
+    pub fn followed(&self) -> bool { self.followed }
+    pub fn sort(&self) -> IndexSort { self.sort }
+
+    /// The target's id if it is of `site`.
+    pub fn target_of(&self, site: Site) -> Option<i32> {
+        self.target.filter(|t| t.site() == site).map(|t| t.id())
+    }
+
+    /// Whether products of `site` can match, a target limits the products to its site.
+    pub fn includes(&self, site: Site) -> bool {
+        self.site.is_none_or(|s| s == site) && self.target.is_none_or(|t| t.site() == site)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum GetIndexedProductsError {
+    #[error("follow target '{target}' is not of site '{site}'")]
+    TargetOfOtherSite { target: TargetId, site: Site },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::domain::{amiami, melonbooks};
+
+    #[test]
+    fn test_parse_price() {
+        assert_eq!(parse_price("¥ 1,100"), Some(1100));
+        assert_eq!(parse_price("? ?"), None);
+    }
+
+    #[test]
+    fn test_index_filter() {
+        let filter = IndexFilter::new().with_target(Some(TargetId::new(melonbooks::SITE, 12)));
+        assert!(filter.includes(melonbooks::SITE));
+        assert!(!filter.includes(amiami::SITE));
+        assert_eq!(filter.target_of(melonbooks::SITE), Some(12));
+        assert_eq!(filter.target_of(amiami::SITE), None);
+        let filter = IndexFilter::new().with_site(Some(amiami::SITE));
+        assert!(filter.includes(amiami::SITE));
+        assert!(!filter.includes(melonbooks::SITE));
+        assert!(IndexFilter::new().includes(melonbooks::SITE));
+    }
+}
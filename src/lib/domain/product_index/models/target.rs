@@ -0,0 +1,88 @@
+use crate::domain::site::find_site;
+use crate::domain::site::Site;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// What a site finds products for, e.g. a Melonbooks artist or a BOOTH shop, written as `<site>:<id>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TargetId {
+    site: Site,
+    id: i32,
+}
+
+impl TargetId {
+    pub fn new(site: Site, id: i32) -> Self {
+        Self { site, id }
+    }
+
+    pub fn site(&self) -> Site { self.site }
+    pub fn id(&self) -> i32 { self.id }
+}
+
+impl Display for TargetId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.site.id(), self.id)
+    }
+}
+
+impl FromStr for TargetId {
+    type Err = ParseTargetIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (site, id) = s.split_once(':')
+            .ok_or_else(|| ParseTargetIdError::Invalid(s.to_owned()))?;
+        let site = find_site(site)
+            .ok_or_else(|| ParseTargetIdError::UnknownSite(site.to_owned()))?;
+        let id = id.parse()
+            .map_err(|_| ParseTargetIdError::Invalid(s.to_owned()))?;
+        Ok(Self::new(site, id))
+    }
+}
+
+/// A target the user follows, `name` is the artist, category, search keyword, shop or circle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FollowTarget {
+    target_id: TargetId,
+    name: String,
+}
+
+impl FollowTarget {
+    pub fn new(target_id: TargetId, name: String) -> Self {
+        Self { target_id, name }
+    }
+
+    pub fn target_id(&self) -> TargetId { self.target_id }
+    pub fn site(&self) -> Site { self.target_id.site() }
+    pub fn name(&self) -> &str { &self.name }
+}
+
+#[derive(Debug, Error)]
+pub enum ParseTargetIdError {
+    #[error("invalid follow target '{0}', expected '<site>:<id>'")]
+    Invalid(String),
+    #[error("unknown site '{0}'")]
+    UnknownSite(String),
+}
+
+#[derive(Debug, Error)]
+pub enum GetFollowTargetsError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::domain::melonbooks;
+
+    #[test]
+    fn test_parse_target_id() {
+        let target_id = "melonbooks:12".parse::<TargetId>().unwrap();
+        assert_eq!(target_id, TargetId::new(melonbooks::SITE, 12));
+        assert_eq!(target_id.to_string(), "melonbooks:12");
+        assert!(matches!("melonbooks".parse::<TargetId>(), Err(ParseTargetIdError::Invalid(_))));
+        assert!(matches!("melonbooks:artist".parse::<TargetId>(), Err(ParseTargetIdError::Invalid(_))));
+        assert!(matches!("unknown:12".parse::<TargetId>(), Err(ParseTargetIdError::UnknownSite(_))));
+    }
+}
@@ -0,0 +1,25 @@
+use crate::domain::pagination::{Page, PageRequest};
+use crate::domain::product_index::models::product::{GetIndexedProductsError, IndexFilter, IndexedProduct};
+use crate::domain::product_index::models::target::{FollowTarget, GetFollowTargetsError};
+use crate::domain::site::Site;
+use crate::domain::user::models::user::User;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+#[async_trait]
+pub trait ProductIndexService: Send + Sync + 'static {
+    /// Products of all sites, the newest first.
+    async fn get_products(&self, user: &User, filter: &IndexFilter, page: PageRequest) -> Result<Page<IndexedProduct>, GetIndexedProductsError>;
+    async fn get_follow_targets(&self, user: &User) -> Result<Vec<FollowTarget>, GetFollowTargetsError>;
+    /// The latest availability or price change of each of the products by its id, products without changes are left out.
+    async fn get_dates_updated(&self, site: Site, product_ids: &[i32]) -> Result<HashMap<i32, DateTime<Utc>>, GetIndexedProductsError>;
+}
+
+#[async_trait]
+pub trait ProductIndexRepository: Clone + Send + Sync + 'static {
+    async fn get_indexed_products(&self, user_id: i32, filter: &IndexFilter, page: PageRequest) -> Result<Page<IndexedProduct>, GetIndexedProductsError>;
+    /// The targets of all sites the user follows, by site and name.
+    async fn get_follow_targets(&self, user_id: i32) -> Result<Vec<FollowTarget>, GetFollowTargetsError>;
+    async fn get_dates_updated(&self, site: Site, product_ids: &[i32]) -> Result<HashMap<i32, DateTime<Utc>>, GetIndexedProductsError>;
+}
@@ -0,0 +1,51 @@
+use crate::domain::pagination::{Page, PageRequest};
+use crate::domain::product_index::models::product::{GetIndexedProductsError, IndexFilter, IndexedProduct};
+use crate::domain::product_index::models::target::{FollowTarget, GetFollowTargetsError};
+use crate::domain::product_index::ports::{ProductIndexRepository, ProductIndexService};
+use crate::domain::site::Site;
+use crate::domain::user::models::user::User;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use log::info;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct ProductIndexServiceImpl<R>
+where
+    R: ProductIndexRepository
+{
+    repo: R,
+}
+
+impl<R> ProductIndexServiceImpl<R>
+where
+    R: ProductIndexRepository
+{
+    pub fn new(repo: R) -> Self {
+        Self { repo }
+    }
+}
+
+#[async_trait]
+impl<R> ProductIndexService for ProductIndexServiceImpl<R>
+where
+    R: ProductIndexRepository
+{
+    async fn get_products(&self, user: &User, filter: &IndexFilter, page: PageRequest) -> Result<Page<IndexedProduct>, GetIndexedProductsError> {
+        if let (Some(site), Some(target)) = (filter.site(), filter.target()) {
+            if target.site() != site {
+                return Err(GetIndexedProductsError::TargetOfOtherSite { target, site });
+            }
+        }
+        info!("get indexed products for '{}' with {:?}", user.username(), filter);
+        self.repo.get_indexed_products(user.id(), filter, page).await
+    }
+
+    async fn get_follow_targets(&self, user: &User) -> Result<Vec<FollowTarget>, GetFollowTargetsError> {
+        self.repo.get_follow_targets(user.id()).await
+    }
+
+    async fn get_dates_updated(&self, site: Site, product_ids: &[i32]) -> Result<HashMap<i32, DateTime<Utc>>, GetIndexedProductsError> {
+        self.repo.get_dates_updated(site, product_ids).await
+    }
+}
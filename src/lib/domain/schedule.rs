@@ -0,0 +1,240 @@
+use crate::domain::product_index::models::target::TargetId;
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+use croner::parser::{CronParser, Seconds};
+use croner::Cron;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use thiserror::Error;
+use tokio::sync::broadcast;
+
+const SCHEDULE_CHANGE_CAPACITY: usize = 16;
+
+/// Days of new products and restocks the interval of an adaptive target is based on.
+pub const ACTIVITY_DAYS: i64 = 14;
+
+/// A cron expression with seconds like the `schedule` of a site, e.g. `0 0 */6 * * *`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    expression: String,
+}
+
+impl Schedule {
+    pub fn parse(expression: &str) -> Result<Self, InvalidScheduleError> {
+        let expression = expression.trim();
+        parse_cron(expression).map_err(|_| InvalidScheduleError(expression.to_owned()))?;
+        Ok(Self { expression: expression.to_owned() })
+    }
+
+    /// An empty expression is no schedule, for clearing it in forms.
+    pub fn parse_optional(expression: &str) -> Result<Option<Self>, InvalidScheduleError> {
+        match expression.trim() {
+            "" => Ok(None),
+            expression => Self::parse(expression).map(Some),
+        }
+    }
+
+    pub fn expression(&self) -> &str { &self.expression }
+
+    /// The first run after `time`, `None` if the expression never matches again.
+    pub fn next_run<Tz: TimeZone>(&self, time: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        parse_cron(&self.expression).ok()?
+            .find_next_occurrence(time, false).ok()
+    }
+}
+
+/// Parses like the scheduler does, which requires the seconds.
+fn parse_cron(expression: &str) -> Result<Cron, croner::errors::CronError> {
+    CronParser::builder()
+        .seconds(Seconds::Required)
+        .dom_and_dow(true)
+        .build()
+        .parse(expression)
+}
+
+impl FromStr for Schedule {
+    type Err = InvalidScheduleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Display for Schedule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.expression)
+    }
+}
+
+/// An artist or category scraped on its own schedule, the scrape of its site leaves it out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetSchedule {
+    target_id: TargetId,
+    name: String,
+    schedule: Schedule,
+}
+
+impl TargetSchedule {
+    pub fn new(target_id: TargetId, name: String, schedule: Schedule) -> Self {
+        Self { target_id, name, schedule }
+    }
+
+    pub fn target_id(&self) -> TargetId { self.target_id }
+    pub fn name(&self) -> &str { &self.name }
+    pub fn schedule(&self) -> &Schedule { &self.schedule }
+}
+
+/// Bounds of the interval of the targets scraped by their activity instead of a schedule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdaptiveInterval {
+    min: TimeDelta,
+    max: TimeDelta,
+}
+
+impl AdaptiveInterval {
+    /// `max` is raised to `min` when it is lower.
+    pub fn new(min: TimeDelta, max: TimeDelta) -> Self {
+        Self { min, max: max.max(min) }
+    }
+
+    pub fn min(&self) -> TimeDelta { self.min }
+    pub fn max(&self) -> TimeDelta { self.max }
+
+    /// Start of the activity the intervals at `now` are based on.
+    pub fn activity_since(now: DateTime<Utc>) -> DateTime<Utc> {
+        now - TimeDelta::days(ACTIVITY_DAYS)
+    }
+
+    /// A quarter of the average time between the new products and restocks of the last `ACTIVITY_DAYS`,
+    /// so a target is checked a few times before its next one. Quiet targets back off to `max`.
+    pub fn interval(&self, activity: usize) -> TimeDelta {
+        if activity == 0 {
+            return self.max;
+        }
+        let divisor = i32::try_from(activity.saturating_mul(4)).unwrap_or(i32::MAX);
+        (TimeDelta::days(ACTIVITY_DAYS) / divisor).clamp(self.min, self.max)
+    }
+}
+
+/// An artist or category without its own schedule, scraped again once its interval passed since its last scrape.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdaptiveTarget {
+    target_id: TargetId,
+    name: String,
+    activity: usize,
+    interval: TimeDelta,
+    date_scraped: Option<DateTime<Utc>>,
+}
+
+impl AdaptiveTarget {
+    /// `activity` are the new products and restocks of the target since `AdaptiveInterval::activity_since`.
+    pub fn new(target_id: TargetId, name: String, activity: usize, date_scraped: Option<DateTime<Utc>>, bounds: &AdaptiveInterval) -> Self {
+        Self { target_id, name, activity, interval: bounds.interval(activity), date_scraped }
+    }
+
+    pub fn target_id(&self) -> TargetId { self.target_id }
+    pub fn name(&self) -> &str { &self.name }
+    /// New products and restocks of the last `ACTIVITY_DAYS`.
+    pub fn activity(&self) -> usize { self.activity }
+    pub fn interval(&self) -> TimeDelta { self.interval }
+    pub fn date_scraped(&self) -> Option<DateTime<Utc>> { self.date_scraped }
+
+    /// `None` for a target never scraped, which is due right away.
+    pub fn next_run(&self) -> Option<DateTime<Utc>> {
+        self.date_scraped.map(|d| d + self.interval)
+    }
+
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.next_run().is_none_or(|next_run| next_run <= now)
+    }
+}
+
+/// Tells the scheduler that the target schedules of a site may have changed, it reloads them instead of restarting.
+#[derive(Debug, Clone)]
+pub struct ScheduleChanges {
+    sender: broadcast::Sender<()>,
+}
+
+impl ScheduleChanges {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(SCHEDULE_CHANGE_CAPACITY);
+        Self { sender }
+    }
+
+    pub fn publish(&self) {
+        let _ = self.sender.send(());
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<()> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for ScheduleChanges {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("invalid schedule '{0}', expected a cron expression with seconds like '0 0 */6 * * *'")]
+pub struct InvalidScheduleError(String);
+
+#[derive(Debug, Error)]
+pub enum GetTargetSchedulesError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum SetDateScrapedError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::{Timelike, Utc};
+
+    #[test]
+    fn test_parse_schedule() {
+        let schedule = Schedule::parse(" 0 30 */6 * * * ").unwrap();
+        assert_eq!(schedule.to_string(), "0 30 */6 * * *");
+        let time = Utc::now().with_hour(7).unwrap().with_minute(0).unwrap();
+        let next_run = schedule.next_run(&time).unwrap();
+        assert_eq!((next_run.hour(), next_run.minute(), next_run.second()), (12, 30, 0));
+
+        assert!(Schedule::parse("30 */6 * * *").is_err());
+        assert!(Schedule::parse("0 61 * * * *").is_err());
+        assert!(Schedule::parse("").is_err());
+        assert_eq!(Schedule::parse_optional(" ").unwrap(), None);
+        assert_eq!(Schedule::parse_optional("0 0 * * * *").unwrap(), Some(Schedule::parse("0 0 * * * *").unwrap()));
+    }
+
+    #[test]
+    fn test_adaptive_interval() {
+        let bounds = AdaptiveInterval::new(TimeDelta::hours(1), TimeDelta::hours(24));
+        assert_eq!(bounds.interval(0), TimeDelta::hours(24));
+        assert_eq!(bounds.interval(1), TimeDelta::hours(24));
+        assert_eq!(bounds.interval(7), TimeDelta::hours(12));
+        assert_eq!(bounds.interval(1000), TimeDelta::hours(1));
+        assert_eq!(AdaptiveInterval::new(TimeDelta::hours(2), TimeDelta::hours(1)).max(), TimeDelta::hours(2));
+    }
+
+    #[test]
+    fn test_adaptive_target() {
+        let bounds = AdaptiveInterval::new(TimeDelta::hours(1), TimeDelta::hours(48));
+        let now = Utc::now();
+        let target_id = "melonbooks:1".parse().unwrap();
+        let target = AdaptiveTarget::new(target_id, "mafuyu".to_owned(), 2, Some(now - TimeDelta::hours(20)), &bounds);
+        assert_eq!(target.activity(), 2);
+        assert_eq!(target.interval(), TimeDelta::days(ACTIVITY_DAYS) / 8);
+        assert_eq!(target.next_run(), Some(now + TimeDelta::hours(22)));
+        assert!(!target.is_due(now));
+
+        let target = AdaptiveTarget::new(target_id, "mafuyu".to_owned(), 0, None, &bounds);
+        assert_eq!(target.interval(), TimeDelta::hours(48));
+        assert_eq!(target.next_run(), None);
+        assert!(target.is_due(now));
+    }
+}
@@ -0,0 +1,70 @@
+use tokio::sync::broadcast;
+
+const SCRAPE_EVENT_CAPACITY: usize = 256;
+
+/// Progress and results of a running scrape, `P` being the product of the site.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScrapeEvent<P> {
+    Started { targets: usize },
+    /// `target` is the artist or category being scraped, `pages_fetched` counts the scraper requests made for it so far.
+    Progress { target: String, index: usize, targets: usize, pages_fetched: usize },
+    /// `followers` are the ids of the users which get notified about the product.
+    NewProduct { product: P, followers: Vec<i32> },
+    RestockedProduct { product: P, followers: Vec<i32> },
+    Finished,
+}
+
+impl<P> ScrapeEvent<P> {
+    pub fn is_for_user(&self, user_id: i32) -> bool {
+        match self {
+            ScrapeEvent::NewProduct { followers, .. } | ScrapeEvent::RestockedProduct { followers, .. } => followers.contains(&user_id),
+            _ => true,
+        }
+    }
+}
+
+/// Broadcasts scrape events to every live subscriber, events are dropped when nobody is listening.
+#[derive(Debug, Clone)]
+pub struct ScrapeEvents<P> {
+    sender: broadcast::Sender<ScrapeEvent<P>>,
+}
+
+impl<P: Clone> ScrapeEvents<P> {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(SCRAPE_EVENT_CAPACITY);
+        Self { sender }
+    }
+
+    pub fn publish(&self, event: ScrapeEvent<P>) {
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ScrapeEvent<P>> {
+        self.sender.subscribe()
+    }
+}
+
+impl<P: Clone> Default for ScrapeEvents<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_publish_scrape_events() {
+        let events = ScrapeEvents::<String>::new();
+        events.publish(ScrapeEvent::Started { targets: 1 });
+        let mut receiver = events.subscribe();
+        events.publish(ScrapeEvent::NewProduct { product: "product".to_owned(), followers: vec![1] });
+        events.publish(ScrapeEvent::Finished);
+
+        let event = receiver.recv().await.unwrap();
+        assert!(event.is_for_user(1));
+        assert!(!event.is_for_user(2));
+        assert_eq!(receiver.recv().await.unwrap(), ScrapeEvent::Finished);
+    }
+}
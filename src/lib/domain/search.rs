@@ -0,0 +1,83 @@
+use thiserror::Error;
+
+#[derive(Debug, PartialEq)]
+pub struct SearchResult<P> {
+    product: P,
+    rank: f64,
+    highlights: Vec<FieldHighlight>,
+}
+
+impl<P> SearchResult<P> {
+    pub fn new(product: P, rank: f64, highlights: Vec<FieldHighlight>) -> Self {
+        Self { product, rank, highlights }
+    }
+
+    pub fn product(&self) -> &P { &self.product }
+    pub fn rank(&self) -> f64 { self.rank }
+    pub fn highlights(&self) -> &[FieldHighlight] { &self.highlights }
+
+    pub fn highlight(&self, field: &str) -> Option<&HighlightedText> {
+        self.highlights.iter()
+            .find(|h| h.field() == field)
+            .map(|h| h.text())
+    }
+
+    pub fn into_parts(self) -> (P, Vec<FieldHighlight>) {
+        (self.product, self.highlights)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldHighlight {
+    field: String,
+    text: HighlightedText,
+}
+
+impl FieldHighlight {
+    pub fn new(field: String, text: HighlightedText) -> Self {
+        Self { field, text }
+    }
+
+    pub fn field(&self) -> &str { &self.field }
+    pub fn text(&self) -> &HighlightedText { &self.text }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighlightedText {
+    segments: Vec<HighlightSegment>,
+}
+
+impl HighlightedText {
+    pub fn new(segments: Vec<HighlightSegment>) -> Self {
+        Self { segments }
+    }
+
+    pub fn segments(&self) -> &[HighlightSegment] { &self.segments }
+
+    pub fn has_match(&self) -> bool {
+        self.segments.iter().any(|s| s.matched())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighlightSegment {
+    text: String,
+    matched: bool,
+}
+
+impl HighlightSegment {
+    pub fn new(text: String, matched: bool) -> Self {
+        Self { text, matched }
+    }
+
+    pub fn text(&self) -> &str { &self.text }
+    pub fn matched(&self) -> bool { self.matched }
+}
+
+#[derive(Debug, Error)]
+pub enum SearchProductsError {
+    #[error("search terms need at least {min_length} characters: '{query}'")]
+    QueryTooShort { query: String, min_length: usize },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
@@ -0,0 +1,321 @@
+use crate::domain::duplicate::ports::DuplicateRepository;
+use crate::domain::image::models::image::{CachedImage, SetProductImageError};
+use crate::domain::image::ports::ImageCache;
+use crate::domain::product_history::{AddNotificationsError, NotificationKind};
+use crate::domain::schedule::{AdaptiveInterval, AdaptiveTarget, GetTargetSchedulesError, TargetSchedule};
+use crate::domain::user::models::user::User;
+use crate::domain::{amiami, booth, digital, figure, mandarake, melonbooks, surugaya, toranoana};
+use async_trait::async_trait;
+use log::warn;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::sync::{broadcast, Mutex, OwnedMutexGuard};
+
+/// A shop the server scrapes, `id` is its key in the config and its path in urls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Site {
+    id: &'static str,
+    name: &'static str,
+    url: &'static str,
+}
+
+impl Site {
+    pub const fn new(id: &'static str, name: &'static str, url: &'static str) -> Self {
+        Self { id, name, url }
+    }
+
+    pub fn id(&self) -> &'static str { self.id }
+    pub fn name(&self) -> &'static str { self.name }
+    /// The shop's start page.
+    pub fn url(&self) -> &'static str { self.url }
+}
+
+impl Display for Site {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
+/// Every site in the order of the header.
+pub const SITES: [Site; 8] = [
+    melonbooks::SITE,
+    toranoana::SITE,
+    mandarake::SITE,
+    surugaya::SITE,
+    booth::SITE,
+    digital::SITE,
+    figure::SITE,
+    amiami::SITE,
+];
+
+pub fn find_site(id: &str) -> Option<Site> {
+    SITES.into_iter().find(|s| s.id() == id)
+}
+
+/// The fields every site's product has, for notifications and views across sites.
+pub trait SiteProduct: AsRef<Self> + Clone + Send + Sync + 'static {
+    const SITE: Site;
+
+    fn id(&self) -> i32;
+    fn title(&self) -> &str;
+    fn url(&self) -> &str;
+    /// The shop's image.
+    fn image_url(&self) -> &str;
+    /// Hash of the image in the image cache, `None` until it was downloaded.
+    fn image_hash(&self) -> Option<&str>;
+    /// Perceptual hash of the image, to find the product listed under another url or site.
+    fn image_phash(&self) -> Option<u64>;
+    /// The product after `image` was stored as its cached image.
+    fn with_image(self, image: &CachedImage) -> Self;
+    /// A line or two below the title in notifications.
+    fn summary(&self) -> String;
+
+    /// How notifications name what the products were found for, e.g. the artist.
+    fn notification_target(target: &str) -> String {
+        target.to_owned()
+    }
+}
+
+/// What every site stores the same way, across the tables of all sites.
+#[async_trait]
+pub trait SiteRepository: DuplicateRepository {
+    async fn set_product_image(&self, site: Site, product_id: i32, image: &CachedImage) -> Result<(), SetProductImageError>;
+    async fn add_notifications(&self, site: Site, user_id: i32, kind: NotificationKind, product_ids: &[i32]) -> Result<(), AddNotificationsError>;
+}
+
+#[async_trait]
+pub trait SiteNotifier<P: SiteProduct>: Clone + Send + Sync + 'static {
+    async fn new_products<Q: AsRef<P> + Sync>(&self, target: &str, products: &[Q]);
+    async fn restocked_products<Q: AsRef<P> + Sync>(&self, target: &str, products: &[Q]);
+}
+
+/// A user following a target, who may only want some of its products.
+pub trait Follower<P>: Sync {
+    fn user(&self) -> &User;
+
+    fn wants(&self, _product: &P) -> bool {
+        true
+    }
+}
+
+impl<P> Follower<P> for User {
+    fn user(&self) -> &User {
+        self
+    }
+}
+
+/// The notifiers, the image cache and the scrape lock of a site, what every site does with the products it scraped.
+#[derive(Debug, Clone)]
+pub struct SiteCore<N, I> {
+    notifier: N,
+    user_notifiers: HashMap<String, N>,
+    images: I,
+    suppress_duplicates: bool,
+    scrape_lock: ScrapeLock,
+}
+
+impl<N, I> SiteCore<N, I>
+where
+    N: Send + Sync,
+    I: ImageCache
+{
+    pub fn new(notifier: N, images: I) -> Self {
+        Self { notifier, user_notifiers: HashMap::new(), images, suppress_duplicates: false, scrape_lock: ScrapeLock::new() }
+    }
+
+    /// Additional notifiers by username, which only get the products of the targets the user follows.
+    pub fn with_user_notifiers(mut self, user_notifiers: HashMap<String, N>) -> Self {
+        self.user_notifiers = user_notifiers;
+        self
+    }
+
+    /// Leaves out new products of users that were already notified about a product with a similar image.
+    pub fn with_duplicate_suppression(mut self, suppress_duplicates: bool) -> Self {
+        self.suppress_duplicates = suppress_duplicates;
+        self
+    }
+
+    pub fn scrape_lock(&self) -> &ScrapeLock { &self.scrape_lock }
+
+    /// Downloads the image of the product into the image cache, the shop's image stays in use when that fails.
+    pub async fn cache_product_image<P: SiteProduct>(&self, repo: &impl SiteRepository, product: P) -> P {
+        if product.image_hash().is_some() {
+            return product;
+        }
+        let image = match self.images.cache_image(product.image_url()).await {
+            Ok(image) => image,
+            Err(e) => {
+                warn!("cannot cache image of product '{}': {:?}", product.url(), e);
+                return product;
+            }
+        };
+        match repo.set_product_image(P::SITE, product.id(), &image).await {
+            Ok(()) => product.with_image(&image),
+            Err(e) => {
+                warn!("cannot set image of product '{}': {:?}", product.url(), e);
+                product
+            }
+        }
+    }
+
+    /// The notifier of the site with the products any follower wants,
+    /// then the notifier of every follower that configured one with the products the follower wants.
+    pub fn recipients<'a, P, F: Follower<P>>(&'a self, followers: &'a [F], products: &'a [P]) -> Vec<(&'a N, Vec<&'a P>)> {
+        let wanted = products.iter()
+            .filter(|p| followers.iter().any(|f| f.wants(p)))
+            .collect::<Vec<_>>();
+        let followed = followers.iter()
+            .filter_map(|f| self.user_notifiers.get(f.user().username()).map(|n| (n, products.iter().filter(|p| f.wants(p)).collect())));
+        std::iter::once((&self.notifier, wanted)).chain(followed).collect()
+    }
+
+    /// Records the notifications of every follower about the products the follower wants.
+    pub async fn add_notifications<P: SiteProduct, F: Follower<P>>(&self, repo: &impl SiteRepository, followers: &[F], kind: NotificationKind, products: &[P]) -> Result<(), AddNotificationsError> {
+        for follower in followers {
+            let ids = products.iter().filter(|p| follower.wants(p)).map(|p| p.id()).collect::<Vec<_>>();
+            repo.add_notifications(P::SITE, follower.user().id(), kind, &ids).await?;
+        }
+        Ok(())
+    }
+
+    /// Notifies the followers of `target` about its new and restocked products and records their notifications.
+    pub async fn notify<P, F>(&self, repo: &impl SiteRepository, target: &str, followers: &[F], new: &[P], restocked: &[P]) -> Result<(), AddNotificationsError>
+    where
+        P: SiteProduct,
+        F: Follower<P>,
+        N: SiteNotifier<P>
+    {
+        let notified_duplicates = self.get_notified_duplicates(repo, new).await;
+        let is_duplicate_for = |user: &User, product: &P| notified_duplicates.get(&product.id())
+            .is_some_and(|user_ids| user_ids.contains(&user.id()));
+        let is_notified_duplicate = |product: &P| notified_duplicates.get(&product.id())
+            .is_some_and(|user_ids| !user_ids.is_empty());
+
+        let restocked_wanted = restocked.iter().filter(|p| followers.iter().any(|f| f.wants(p))).collect::<Vec<_>>();
+        let unnotified_new = new.iter()
+            .filter(|p| followers.iter().any(|f| f.wants(p)) && !is_notified_duplicate(p))
+            .collect::<Vec<_>>();
+        self.notifier.restocked_products(target, &restocked_wanted).await;
+        self.notifier.new_products(target, &unnotified_new).await;
+        for follower in followers {
+            let user = follower.user();
+            let restocked = restocked.iter().filter(|p| follower.wants(p)).collect::<Vec<_>>();
+            let new = new.iter().filter(|p| follower.wants(p) && !is_duplicate_for(user, p)).collect::<Vec<_>>();
+            if let Some(notifier) = self.user_notifiers.get(user.username()) {
+                notifier.restocked_products(target, &restocked).await;
+                notifier.new_products(target, &new).await;
+            }
+            let restocked_ids = restocked.iter().map(|p| p.id()).collect::<Vec<_>>();
+            repo.add_notifications(P::SITE, user.id(), NotificationKind::RestockedProduct, &restocked_ids).await?;
+            let new_ids = new.iter().map(|p| p.id()).collect::<Vec<_>>();
+            repo.add_notifications(P::SITE, user.id(), NotificationKind::NewProduct, &new_ids).await?;
+        }
+        Ok(())
+    }
+
+    /// Ids of the users already notified about another listing of each product, by product id.
+    /// Empty unless duplicates are suppressed, products are never left out when the listings cannot be loaded.
+    async fn get_notified_duplicates<P: SiteProduct>(&self, repo: &impl SiteRepository, products: &[P]) -> HashMap<i32, BTreeSet<i32>> {
+        let mut notified_duplicates = HashMap::new();
+        if !self.suppress_duplicates {
+            return notified_duplicates;
+        }
+        for product in products {
+            let Some(image_phash) = product.image_phash() else {
+                continue;
+            };
+            match repo.get_listings_by_image(image_phash).await {
+                Ok(listings) => {
+                    let user_ids = listings.iter()
+                        .filter(|l| !l.is_product(P::SITE, product.id()))
+                        .flat_map(|l| l.notified_user_ids().iter().copied())
+                        .collect::<BTreeSet<_>>();
+                    notified_duplicates.insert(product.id(), user_ids);
+                }
+                Err(e) => warn!("cannot get duplicates of product '{}': {:?}", product.url(), e),
+            }
+        }
+        notified_duplicates
+    }
+}
+
+/// Lets one scrape of a site run at a time, whether the scheduler or the api started it.
+#[derive(Debug, Clone, Default)]
+pub struct ScrapeLock {
+    running: Arc<Mutex<()>>,
+}
+
+impl ScrapeLock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Waits for the running scrape to finish.
+    pub async fn lock(&self) -> ScrapeGuard {
+        ScrapeGuard { _running: self.running.clone().lock_owned().await }
+    }
+
+    /// `None` while a scrape runs.
+    pub fn try_lock(&self) -> Option<ScrapeGuard> {
+        self.running.clone().try_lock_owned().ok().map(|running| ScrapeGuard { _running: running })
+    }
+}
+
+/// Held for the duration of a scrape, the scrapes of `SiteService` take it to be only started through the lock.
+#[derive(Debug)]
+pub struct ScrapeGuard {
+    _running: OwnedMutexGuard<()>,
+}
+
+/// What the scheduler and the api need of every site.
+#[async_trait]
+pub trait SiteService: Send + Sync + 'static {
+    fn site(&self) -> Site;
+    fn scrape_lock(&self) -> &ScrapeLock;
+    /// Scrapes everything followed without its own schedule or adaptive interval and notifies about new and restocked products.
+    async fn scrape(&self, guard: &ScrapeGuard) -> Result<(), ScrapeSiteError>;
+
+    /// Scrapes everything followed, also the targets with their own schedule, as started through the api.
+    async fn scrape_all(&self, guard: &ScrapeGuard) -> Result<(), ScrapeSiteError> {
+        self.scrape(guard).await
+    }
+
+    /// Followed artists or categories which are scraped on their own schedule.
+    async fn get_target_schedules(&self) -> Result<Vec<TargetSchedule>, GetTargetSchedulesError> {
+        Ok(Vec::new())
+    }
+
+    /// Scrapes a single target of `get_target_schedules`.
+    async fn scrape_target(&self, _guard: &ScrapeGuard, _target_id: i32) -> Result<(), ScrapeSiteError> {
+        Ok(())
+    }
+
+    /// Notified whenever `get_target_schedules` may return something else, `None` if the site has no target schedules.
+    fn subscribe_schedule_changes(&self) -> Option<broadcast::Receiver<()>> {
+        None
+    }
+
+    /// Bounds of the intervals of the targets scraped by their activity, `None` if they are scraped with the site.
+    fn adaptive_interval(&self) -> Option<AdaptiveInterval> {
+        None
+    }
+
+    /// Followed artists or categories without their own schedule with their interval by activity,
+    /// empty without `adaptive_interval`.
+    async fn get_adaptive_targets(&self) -> Result<Vec<AdaptiveTarget>, GetTargetSchedulesError> {
+        Ok(Vec::new())
+    }
+
+    /// Scrapes the targets of `get_adaptive_targets` that are due.
+    async fn scrape_due_targets(&self, _guard: &ScrapeGuard) -> Result<(), ScrapeSiteError> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ScrapeSiteError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
@@ -0,0 +1,7 @@
+use crate::domain::site::Site;
+
+pub mod ports;
+pub mod models;
+pub mod service;
+
+pub const SITE: Site = Site::new("surugaya", "Suruga-ya", "https://www.suruga-ya.jp/");
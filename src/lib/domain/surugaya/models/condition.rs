@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter, EnumString};
+
+/// Whether Suruga-ya sells the listing new or used, the listing shows the offer with the lowest price.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString, EnumIter, Serialize, Deserialize)]
+pub enum Condition {
+    New,
+    Used,
+}
+
+impl TryFrom<String> for Condition {
+    type Error = strum::ParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::try_from(value.as_str())
+    }
+}
+
+impl From<Condition> for String {
+    fn from(value: Condition) -> Self {
+        value.to_string()
+    }
+}
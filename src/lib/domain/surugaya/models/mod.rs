@@ -0,0 +1,3 @@
+pub mod product;
+pub mod search;
+pub mod condition;
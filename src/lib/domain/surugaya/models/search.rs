@@ -0,0 +1,65 @@
+use crate::domain::user::models::user::User;
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+/// A keyword searched on Suruga-ya, `following` is whether the user saved it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Search {
+    id: i32,
+    date_added: DateTime<Utc>,
+    keyword: String,
+    following: bool,
+    date_followed: Option<DateTime<Utc>>,
+}
+
+impl Search {
+    pub fn new(id: i32, date_added: DateTime<Utc>, keyword: String, following: bool, date_followed: Option<DateTime<Utc>>) -> Self {
+        Search { id, date_added, keyword, following, date_followed }
+    }
+
+    pub fn id(&self) -> i32 { self.id }
+    pub fn date_added(&self) -> DateTime<Utc> { self.date_added }
+    pub fn keyword(&self) -> &str { &self.keyword }
+    pub fn following(&self) -> bool { self.following }
+    pub fn date_followed(&self) -> Option<DateTime<Utc>> { self.date_followed }
+}
+
+/// Search saved by at least one user, scraped once for all of its followers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FollowedSearch {
+    search: Search,
+    followers: Vec<User>,
+}
+
+impl FollowedSearch {
+    pub fn new(search: Search, followers: Vec<User>) -> Self {
+        FollowedSearch { search, followers }
+    }
+
+    pub fn search(&self) -> &Search { &self.search }
+    pub fn followers(&self) -> &[User] { &self.followers }
+}
+
+#[derive(Debug, Error)]
+pub enum SaveSearchError {
+    #[error("search '{0}' is already saved")]
+    AlreadySavedError(String),
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum DeleteSearchError {
+    #[error("unknown search with id '{id}'")]
+    UnknownSearch { id: i32 },
+    #[error("search '{keyword}' not saved")]
+    SearchNotFollowed { keyword: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum GetSearchesError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
@@ -0,0 +1,50 @@
+use crate::domain::pagination::{Page, PageRequest};
+use crate::domain::product_history::GetProductError;
+use crate::domain::site::SiteService;
+use crate::domain::surugaya::models::product::{AddProductSearchError, CreateProductArgs, CreateProductError, GetProductsError, ListingData, Product, ProductHistoryEntry, ScrapeProductsError, UpdateProductArgs, UpdateProductError};
+use crate::domain::surugaya::models::search::{DeleteSearchError, FollowedSearch, GetSearchesError, SaveSearchError, Search};
+use crate::domain::user::models::user::User;
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait SurugayaService: SiteService {
+    async fn save_search(&self, user: &User, keyword: &str) -> Result<(), SaveSearchError>;
+    async fn delete_search(&self, user: &User, search_id: i32) -> Result<(), DeleteSearchError>;
+    async fn get_searches(&self, user: &User) -> Result<Vec<Search>, GetSearchesError>;
+    async fn get_saved_searches(&self, user: &User) -> Result<Vec<Search>, GetSearchesError>;
+    async fn get_saved_searches_page(&self, user: &User, page: PageRequest) -> Result<Page<Search>, GetSearchesError>;
+
+    async fn get_products_page(&self, page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    async fn get_products_by_search(&self, search_id: i32) -> Result<Vec<Product>, GetProductsError>;
+    async fn get_products_page_by_search(&self, search_id: i32, page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    async fn get_products_page_by_searches(&self, search_ids: &[i32], page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    async fn get_product(&self, product_id: i32) -> Result<Product, GetProductError>;
+    async fn get_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError>;
+}
+
+#[async_trait]
+pub trait SurugayaRepository: Clone + Send + Sync + 'static {
+    async fn save_surugaya_search(&self, user_id: i32, keyword: &str) -> Result<(), SaveSearchError>;
+    async fn delete_surugaya_search(&self, user_id: i32, search_id: i32) -> Result<(), DeleteSearchError>;
+    async fn get_surugaya_searches(&self, user_id: i32) -> Result<Vec<Search>, GetSearchesError>;
+    async fn get_surugaya_saved_searches_page(&self, user_id: i32, page: PageRequest) -> Result<Page<Search>, GetSearchesError>;
+    async fn get_followed_surugaya_searches(&self) -> Result<Vec<FollowedSearch>, GetSearchesError>;
+
+    async fn create_surugaya_product(&self, req: &CreateProductArgs) -> Result<Product, CreateProductError>;
+    async fn update_surugaya_product(&self, req: &UpdateProductArgs) -> Result<Product, UpdateProductError>;
+    /// Links a listing that was first found by another search to the search.
+    async fn add_surugaya_product_search(&self, url: &str, search_id: i32) -> Result<(), AddProductSearchError>;
+    async fn get_surugaya_product(&self, product_id: i32) -> Result<Product, GetProductError>;
+    async fn get_surugaya_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError>;
+    async fn get_surugaya_products(&self) -> Result<Vec<Product>, GetProductsError>;
+    async fn get_surugaya_products_by_search(&self, search_id: i32) -> Result<Vec<Product>, GetProductsError>;
+    async fn get_surugaya_products_page(&self, page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    async fn get_surugaya_products_page_by_search(&self, search_id: i32, page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    /// Products found by any of the searches, the newest first.
+    async fn get_surugaya_products_page_by_searches(&self, search_ids: &[i32], page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+}
+
+#[async_trait]
+pub trait SurugayaScraper: Clone + Send + Sync + 'static {
+    async fn get_listings(&self, keyword: &str) -> Result<Vec<ListingData>, ScrapeProductsError>;
+}
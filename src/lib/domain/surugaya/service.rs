@@ -0,0 +1,213 @@
+use crate::domain::image::ports::ImageCache;
+use crate::domain::pagination::{Page, PageRequest};
+use crate::domain::product_history::GetProductError;
+use crate::domain::site::{ScrapeGuard, ScrapeLock, ScrapeSiteError, Site, SiteCore, SiteNotifier, SiteRepository, SiteService};
+use crate::domain::availability::Availability;
+use crate::domain::surugaya::models::product::{CreateProductArgs, GetProductsError, Product, ProductHistoryEntry, ScrapeProductsError, UpdateProductArgs};
+use crate::domain::surugaya::models::search::{DeleteSearchError, GetSearchesError, SaveSearchError, Search};
+use crate::domain::surugaya::ports::{SurugayaRepository, SurugayaScraper, SurugayaService};
+use crate::domain::surugaya::SITE;
+use crate::domain::user::models::user::User;
+use async_trait::async_trait;
+use log::info;
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(Debug, Clone)]
+pub struct SurugayaServiceImpl<R, N, S, I>
+where
+    R: SurugayaRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: SurugayaScraper,
+    I: ImageCache
+{
+    repo: R,
+    scraper: S,
+    core: SiteCore<N, I>,
+}
+
+impl<R, N, S, I> SurugayaServiceImpl<R, N, S, I>
+where
+    R: SurugayaRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: SurugayaScraper,
+    I: ImageCache
+{
+    pub fn new(repo: R, scraper: S, core: SiteCore<N, I>) -> Self {
+        Self { repo, scraper, core }
+    }
+}
+
+#[async_trait]
+impl<R, N, S, I> SiteService for SurugayaServiceImpl<R, N, S, I>
+where
+    R: SurugayaRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: SurugayaScraper,
+    I: ImageCache
+{
+    fn site(&self) -> Site {
+        SITE
+    }
+
+    fn scrape_lock(&self) -> &ScrapeLock {
+        self.core.scrape_lock()
+    }
+
+    async fn scrape(&self, _guard: &ScrapeGuard) -> Result<(), ScrapeSiteError> {
+        info!("scrape available products");
+        self.scrape_followed_searches().await
+            .map_err(|e| anyhow::Error::new(e).into())
+    }
+}
+
+#[async_trait]
+impl<R, N, S, I> SurugayaService for SurugayaServiceImpl<R, N, S, I>
+where
+    R: SurugayaRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: SurugayaScraper,
+    I: ImageCache
+{
+    async fn save_search(&self, user: &User, keyword: &str) -> Result<(), SaveSearchError> {
+        info!("save search '{}' for '{}'", keyword, user.username());
+        self.repo.save_surugaya_search(user.id(), keyword).await
+    }
+
+    async fn delete_search(&self, user: &User, search_id: i32) -> Result<(), DeleteSearchError> {
+        info!("delete search with id '{}' for '{}'", search_id, user.username());
+        self.repo.delete_surugaya_search(user.id(), search_id).await
+    }
+
+    async fn get_searches(&self, user: &User) -> Result<Vec<Search>, GetSearchesError> {
+        info!("get searches for '{}'", user.username());
+        self.repo.get_surugaya_searches(user.id()).await
+    }
+
+    async fn get_saved_searches(&self, user: &User) -> Result<Vec<Search>, GetSearchesError> {
+        info!("get saved searches for '{}'", user.username());
+        let searches = self.repo.get_surugaya_searches(user.id()).await?;
+        Ok(
+            searches.into_iter()
+                .filter(|s| s.following())
+                .collect()
+        )
+    }
+
+    async fn get_saved_searches_page(&self, user: &User, page: PageRequest) -> Result<Page<Search>, GetSearchesError> {
+        info!("get page {} of saved searches for '{}'", page.page(), user.username());
+        self.repo.get_surugaya_saved_searches_page(user.id(), page).await
+    }
+
+    async fn get_products_page(&self, page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        info!("get page {} of products", page.page());
+        self.repo.get_surugaya_products_page(page).await
+    }
+
+    async fn get_products_by_search(&self, search_id: i32) -> Result<Vec<Product>, GetProductsError> {
+        info!("get products by search with id '{}'", search_id);
+        self.repo.get_surugaya_products_by_search(search_id).await
+    }
+
+    async fn get_products_page_by_search(&self, search_id: i32, page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        info!("get page {} of products by search with id '{}'", page.page(), search_id);
+        self.repo.get_surugaya_products_page_by_search(search_id, page).await
+    }
+
+    async fn get_products_page_by_searches(&self, search_ids: &[i32], page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        info!("get page {} of products by searches with ids {:?}", page.page(), search_ids);
+        self.repo.get_surugaya_products_page_by_searches(search_ids, page).await
+    }
+
+    async fn get_product(&self, product_id: i32) -> Result<Product, GetProductError> {
+        info!("get product with id '{}'", product_id);
+        self.repo.get_surugaya_product(product_id).await
+    }
+
+    async fn get_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError> {
+        info!("get history of product with id '{}'", product_id);
+        self.repo.get_surugaya_product_history(product_id).await
+    }
+}
+
+impl<R, N, S, I> SurugayaServiceImpl<R, N, S, I>
+where
+    R: SurugayaRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: SurugayaScraper,
+    I: ImageCache
+{
+    /// Out of stock listings stay in the search results, they are stored without notification to notice their restock.
+    async fn scrape_followed_searches(&self) -> Result<(), ScrapeProductsError> {
+        let followed_searches = self.repo.get_followed_surugaya_searches().await?;
+        // listings are shared between searches, a listing found by an earlier search is not new for a later one
+        let mut known_products = self.repo.get_surugaya_products().await?
+            .into_iter()
+            .map(|p| (p.url().to_owned(), p))
+            .collect::<HashMap<_, _>>();
+        for followed_search in followed_searches.iter() {
+            let search = followed_search.search();
+            let keyword = search.keyword();
+            info!("scrape available products for search '{}'", keyword);
+            let search_urls = self.repo.get_surugaya_products_by_search(search.id()).await?
+                .into_iter()
+                .map(|p| p.url().to_owned())
+                .collect::<BTreeSet<_>>();
+            let listings = self.scraper.get_listings(keyword).await?;
+            let listing_urls = listings.iter().map(|l| l.url()).collect::<BTreeSet<_>>();
+
+            let mut new_products = Vec::<Product>::new();
+            let mut restocked_products = Vec::<Product>::new();
+            for listing in listings.iter() {
+                match known_products.get(listing.url()) {
+                    None => {
+                        let product = self.repo.create_surugaya_product(&CreateProductArgs::new(search.id(), listing.clone())).await?;
+                        let product = match product.availability().is_available() {
+                            true => {
+                                let product = self.core.cache_product_image(&self.repo, product).await;
+                                new_products.push(product.clone());
+                                product
+                            }
+                            false => product,
+                        };
+                        known_products.insert(product.url().to_owned(), product);
+                    }
+                    Some(product) => {
+                        if !search_urls.contains(listing.url()) {
+                            self.repo.add_surugaya_product_search(listing.url(), search.id()).await?;
+                        }
+                        if product.condition() == listing.condition() && product.price() == listing.price() && &product.availability() == listing.availability() {
+                            continue;
+                        }
+                        let restocked = !product.availability().is_available() && listing.availability().is_available();
+                        let product = self.repo.update_surugaya_product(&UpdateProductArgs::from_listing(listing)).await?;
+                        let product = match restocked {
+                            true => {
+                                let product = self.core.cache_product_image(&self.repo, product).await;
+                                restocked_products.push(product.clone());
+                                product
+                            }
+                            false => product,
+                        };
+                        known_products.insert(product.url().to_owned(), product);
+                    }
+                }
+            }
+            info!("found '{}' new and '{}' restocked products for search '{}'", new_products.len(), restocked_products.len(), keyword);
+
+            self.core.notify(&self.repo, keyword, followed_search.followers(), &new_products, &restocked_products).await?;
+
+            let gone_products = search_urls.iter()
+                .filter(|u| !listing_urls.contains(u.as_str()))
+                .filter_map(|u| known_products.get(u.as_str()))
+                .filter(|p| p.availability().is_available())
+                .map(|p| UpdateProductArgs::new(p.url().to_owned(), p.condition(), None, Availability::NotAvailable))
+                .collect::<Vec<_>>();
+            info!("update '{}' products as now unavailable for search '{}'", gone_products.len(), keyword);
+            for gone_product in gone_products.iter() {
+                let product = self.repo.update_surugaya_product(gone_product).await?;
+                known_products.insert(product.url().to_owned(), product);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+use crate::domain::duplicate::models::listing::{GetListingsError, Listing};
+use crate::domain::duplicate::ports::DuplicateRepository;
+use crate::domain::image::models::image::{CacheImageError, CachedImage, GetImageError, Image, ImageVariant, SetProductImageError};
+use crate::domain::image::ports::ImageCache;
+use crate::domain::product_history::{AddNotificationsError, NotificationKind};
+use crate::domain::site::{Site, SiteNotifier, SiteProduct, SiteRepository};
+use crate::domain::user::models::user::User;
+use async_trait::async_trait;
+use chrono::Utc;
+use std::sync::{Arc, Mutex};
+
+pub fn user(id: i32, username: &str) -> User {
+    User::new(id, Utc::now(), username.to_owned())
+}
+
+/// A notification as `(kind, target, product urls)`.
+pub type TestNotification = (NotificationKind, String, Vec<String>);
+
+/// Records every notification that has products.
+#[derive(Debug, Clone, Default)]
+pub struct TestNotifier {
+    notifications: Arc<Mutex<Vec<TestNotification>>>,
+}
+
+impl TestNotifier {
+    pub fn record<P: SiteProduct, Q: AsRef<P>>(&self, kind: NotificationKind, target: &str, products: &[Q]) {
+        if products.is_empty() {
+            return;
+        }
+        let urls = products.iter().map(|p| p.as_ref().url().to_owned()).collect();
+        self.notifications.lock().unwrap().push((kind, target.to_owned(), urls));
+    }
+
+    /// The notifications since the last call.
+    pub fn take(&self) -> Vec<TestNotification> {
+        std::mem::take(&mut *self.notifications.lock().unwrap())
+    }
+}
+
+#[async_trait]
+impl<P: SiteProduct> SiteNotifier<P> for TestNotifier {
+    async fn new_products<Q: AsRef<P> + Sync>(&self, target: &str, products: &[Q]) {
+        self.record::<P, Q>(NotificationKind::NewProduct, target, products);
+    }
+
+    async fn restocked_products<Q: AsRef<P> + Sync>(&self, target: &str, products: &[Q]) {
+        self.record::<P, Q>(NotificationKind::RestockedProduct, target, products);
+    }
+}
+
+/// Caches every image under the `image_hash` of its url, without a perceptual hash.
+#[derive(Debug, Clone, Default)]
+pub struct TestImageCache;
+
+pub fn image_hash(url: &str) -> String {
+    format!("hash of {}", url)
+}
+
+#[async_trait]
+impl ImageCache for TestImageCache {
+    async fn cache_image(&self, url: &str) -> Result<CachedImage, CacheImageError> {
+        Ok(CachedImage::new(image_hash(url), None))
+    }
+
+    async fn get_image(&self, hash: &str, _variant: ImageVariant) -> Result<Image, GetImageError> {
+        Err(GetImageError::ImageMissing { hash: hash.to_owned() })
+    }
+}
+
+/// What every site stores the same way, the site's repository is implemented for its own `T`.
+#[derive(Debug)]
+pub struct TestRepo<T> {
+    site: Arc<Mutex<T>>,
+    images: Arc<Mutex<Vec<(i32, CachedImage)>>>,
+    notifications: Arc<Mutex<Vec<(i32, NotificationKind, i32)>>>,
+}
+
+impl<T> TestRepo<T> {
+    pub fn new(site: T) -> Self {
+        Self { site: Arc::new(Mutex::new(site)), images: Arc::default(), notifications: Arc::default() }
+    }
+
+    pub fn with_site<U>(&self, f: impl FnOnce(&mut T) -> U) -> U {
+        f(&mut self.site.lock().unwrap())
+    }
+
+    /// The cached images by product id.
+    pub fn images(&self) -> Vec<(i32, CachedImage)> {
+        self.images.lock().unwrap().clone()
+    }
+
+    /// The recorded notifications as `(user id, kind, product id)`.
+    pub fn notifications(&self) -> Vec<(i32, NotificationKind, i32)> {
+        self.notifications.lock().unwrap().clone()
+    }
+}
+
+impl<T> Clone for TestRepo<T> {
+    fn clone(&self) -> Self {
+        Self { site: self.site.clone(), images: self.images.clone(), notifications: self.notifications.clone() }
+    }
+}
+
+#[async_trait]
+impl<T: Send + 'static> DuplicateRepository for TestRepo<T> {
+    async fn get_listings_by_image(&self, _image_phash: u64) -> Result<Vec<Listing>, GetListingsError> {
+        Ok(Vec::new())
+    }
+}
+
+#[async_trait]
+impl<T: Send + 'static> SiteRepository for TestRepo<T> {
+    async fn set_product_image(&self, _site: Site, product_id: i32, image: &CachedImage) -> Result<(), SetProductImageError> {
+        self.images.lock().unwrap().push((product_id, image.clone()));
+        Ok(())
+    }
+
+    async fn add_notifications(&self, _site: Site, user_id: i32, kind: NotificationKind, product_ids: &[i32]) -> Result<(), AddNotificationsError> {
+        let mut notifications = self.notifications.lock().unwrap();
+        notifications.extend(product_ids.iter().map(|&product_id| (user_id, kind, product_id)));
+        Ok(())
+    }
+}
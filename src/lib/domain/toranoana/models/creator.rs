@@ -0,0 +1,104 @@
+use crate::domain::user::models::user::User;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter, EnumString};
+use thiserror::Error;
+
+/// Toranoana searches authors and circles separately, a name can be both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumString, EnumIter, Serialize, Deserialize)]
+pub enum CreatorKind {
+    Artist,
+    Circle,
+}
+
+impl TryFrom<String> for CreatorKind {
+    type Error = strum::ParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::try_from(value.as_str())
+    }
+}
+
+impl From<CreatorKind> for String {
+    fn from(value: CreatorKind) -> Self {
+        value.to_string()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Creator {
+    id: i32,
+    date_added: DateTime<Utc>,
+    name: String,
+    kind: CreatorKind,
+    following: bool,
+    date_followed: Option<DateTime<Utc>>,
+}
+
+impl Creator {
+    pub fn new(id: i32, date_added: DateTime<Utc>, name: String, kind: CreatorKind, following: bool, date_followed: Option<DateTime<Utc>>) -> Self {
+        Creator { id, date_added, name, kind, following, date_followed }
+    }
+
+    pub fn id(&self) -> i32 { self.id }
+    pub fn date_added(&self) -> DateTime<Utc> { self.date_added }
+    pub fn name(&self) -> &str { &self.name }
+    pub fn kind(&self) -> CreatorKind { self.kind }
+    pub fn following(&self) -> bool { self.following }
+    pub fn date_followed(&self) -> Option<DateTime<Utc>> { self.date_followed }
+}
+
+/// Creator followed by at least one user, scraped once for all of its followers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FollowedCreator {
+    creator: Creator,
+    followers: Vec<User>,
+}
+
+impl FollowedCreator {
+    pub fn new(creator: Creator, followers: Vec<User>) -> Self {
+        FollowedCreator { creator, followers }
+    }
+
+    pub fn creator(&self) -> &Creator { &self.creator }
+    pub fn followers(&self) -> &[User] { &self.followers }
+}
+
+#[derive(Debug, Clone)]
+pub struct CreatorArgs {
+    name: String,
+    kind: CreatorKind,
+}
+
+impl CreatorArgs {
+    pub fn new(name: String, kind: CreatorKind) -> Self {
+        CreatorArgs { name, kind }
+    }
+
+    pub fn name(&self) -> &str { &self.name }
+    pub fn kind(&self) -> CreatorKind { self.kind }
+}
+
+#[derive(Debug, Error)]
+pub enum FollowCreatorError {
+    #[error("Creator already followed since {0}")]
+    AlreadyFollowedError(DateTime<Utc>),
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum UnfollowCreatorError {
+    #[error("unknown creator with id '{id}'")]
+    UnknownCreator { id: i32 },
+    #[error("creator '{name}' not followed")]
+    CreatorNotFollowed { name: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum GetCreatorsError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
@@ -0,0 +1,249 @@
+use crate::domain::product_history;
+use crate::domain::product_history::AddNotificationsError;
+use crate::domain::image::models::image::CachedImage;
+use crate::domain::site::{Site, SiteProduct};
+use crate::domain::availability::Availability;
+use crate::domain::toranoana::models::creator::{Creator, CreatorKind, GetCreatorsError};
+use crate::domain::toranoana::SITE;
+use crate::outbound::toranoana_scraper::ParseError;
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Product {
+    id: i32,
+    date_added: DateTime<Utc>,
+    url: String,
+    title: String,
+    creators: Vec<Creator>,
+    image_url: String,
+    category: String,
+    price: Option<i32>,
+    availability: Availability,
+    date_restocked: Option<DateTime<Utc>>,
+    image_hash: Option<String>,
+    image_phash: Option<u64>,
+}
+
+impl Product {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(id: i32, date_added: DateTime<Utc>, url: String, title: String, creators: Vec<Creator>, image_url: String, category: String, price: Option<i32>, availability: Availability) -> Self {
+        Self { id, date_added, url, title, creators, image_url, category, price, availability, date_restocked: None, image_hash: None, image_phash: None }
+    }
+
+    pub fn with_date_restocked(mut self, date_restocked: Option<DateTime<Utc>>) -> Self {
+        self.date_restocked = date_restocked;
+        self
+    }
+
+    pub fn with_image_hash(mut self, image_hash: Option<String>) -> Self {
+        self.image_hash = image_hash;
+        self
+    }
+
+    pub fn with_image_phash(mut self, image_phash: Option<u64>) -> Self {
+        self.image_phash = image_phash;
+        self
+    }
+
+    pub fn id(&self) -> i32 { self.id }
+    pub fn date_added(&self) -> DateTime<Utc> { self.date_added }
+    pub fn url(&self) -> &str { &self.url }
+    pub fn title(&self) -> &str { &self.title }
+    /// The circle and the artists of the product.
+    pub fn creators(&self) -> &[Creator] { &self.creators }
+    pub fn image_url(&self) -> &str { &self.image_url }
+    pub fn category(&self) -> &str { &self.category }
+    /// Price in yen including tax.
+    pub fn price(&self) -> Option<i32> { self.price }
+    pub fn availability(&self) -> Availability { self.availability.clone() }
+    pub fn date_restocked(&self) -> Option<DateTime<Utc>> { self.date_restocked }
+    pub fn image_hash(&self) -> Option<&str> { self.image_hash.as_deref() }
+    pub fn image_phash(&self) -> Option<u64> { self.image_phash }
+
+    pub fn circle(&self) -> Option<&Creator> {
+        self.creators.iter().find(|c| c.kind() == CreatorKind::Circle)
+    }
+
+    pub fn artists(&self) -> Vec<&Creator> {
+        self.creators.iter().filter(|c| c.kind() == CreatorKind::Artist).collect()
+    }
+
+    /// When the product was added or, if it was restocked since, restocked.
+    pub fn date_changed(&self) -> DateTime<Utc> {
+        self.date_restocked.unwrap_or(self.date_added)
+    }
+}
+
+/// Price changes are only known from the product page, which is scraped once when the product is added.
+pub type ProductHistoryEntry = product_history::ProductHistoryEntry<Availability, Option<i32>>;
+
+impl AsRef<Product> for Product {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+impl SiteProduct for Product {
+    const SITE: Site = SITE;
+
+    fn id(&self) -> i32 { self.id }
+    fn title(&self) -> &str { &self.title }
+    fn url(&self) -> &str { &self.url }
+    fn image_url(&self) -> &str { &self.image_url }
+    fn image_hash(&self) -> Option<&str> { self.image_hash.as_deref() }
+    fn image_phash(&self) -> Option<u64> { self.image_phash }
+
+    fn with_image(self, image: &CachedImage) -> Self {
+        self.with_image_hash(Some(image.hash().to_owned())).with_image_phash(image.perceptual_hash())
+    }
+
+    fn summary(&self) -> String {
+        let circle = self.circle().map(|c| c.name()).unwrap_or("-");
+        match self.price {
+            Some(price) => format!("{} — {}\n¥{}", self.category, circle, price),
+            None => format!("{} — {}", self.category, circle)
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ProductData {
+    title: String,
+    circle: Option<String>,
+    artists: Vec<String>,
+    image_url: String,
+    category: String,
+    price: Option<i32>,
+    availability: Availability,
+}
+
+impl ProductData {
+    pub fn new(title: String, circle: Option<String>, artists: Vec<String>, image_url: String, category: String, price: Option<i32>, availability: Availability) -> Self {
+        Self { title, circle, artists, image_url, category, price, availability }
+    }
+
+    pub fn title(&self) -> &str { &self.title }
+    pub fn circle(&self) -> Option<&str> { self.circle.as_deref() }
+    pub fn artists(&self) -> &[String] { &self.artists }
+    pub fn image_url(&self) -> &str { &self.image_url }
+    pub fn category(&self) -> &str { &self.category }
+    pub fn price(&self) -> Option<i32> { self.price }
+    pub fn availability(&self) -> &Availability { &self.availability }
+
+    /// Whether the product was found for the creator and not just for a similar name.
+    pub fn has_creator(&self, name: &str, kind: CreatorKind) -> bool {
+        match kind {
+            CreatorKind::Artist => self.artists.iter().any(|a| a == name),
+            CreatorKind::Circle => self.circle.as_deref() == Some(name),
+        }
+    }
+
+    /// Names of the circle and the artists.
+    pub fn creator_names(&self) -> Vec<&str> {
+        self.circle.iter().chain(self.artists.iter()).map(|c| c.as_str()).collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateProductArgs {
+    url: String,
+    title: String,
+    circle: Option<String>,
+    artists: Vec<String>,
+    image_url: String,
+    category: String,
+    price: Option<i32>,
+    availability: Availability,
+}
+
+impl CreateProductArgs {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(url: String, title: String, circle: Option<String>, artists: Vec<String>, image_url: String, category: String, price: Option<i32>, availability: Availability) -> Self {
+        Self { url, title, circle, artists, image_url, category, price, availability }
+    }
+
+    pub fn new_from_data(url: String, data: ProductData) -> Self {
+        Self::new(url, data.title, data.circle, data.artists, data.image_url, data.category, data.price, data.availability)
+    }
+
+    pub fn url(&self) -> &str { &self.url }
+    pub fn title(&self) -> &str { &self.title }
+    pub fn circle(&self) -> Option<&str> { self.circle.as_deref() }
+    pub fn artists(&self) -> &[String] { &self.artists }
+    pub fn image_url(&self) -> &str { &self.image_url }
+    pub fn category(&self) -> &str { &self.category }
+    pub fn price(&self) -> Option<i32> { self.price }
+    pub fn availability(&self) -> Availability { self.availability.clone() }
+}
+
+#[derive(Debug, Clone)]
+pub struct UpdateProductArgs {
+    url: String,
+    availability: Availability,
+}
+
+impl UpdateProductArgs {
+    pub fn new(url: String, availability: Availability) -> Self {
+        Self { url, availability }
+    }
+
+    pub fn url(&self) -> &str { &self.url }
+    pub fn availability(&self) -> Availability { self.availability.clone() }
+}
+
+#[derive(Debug, Error)]
+pub enum CreateProductError {
+    #[error("Product '{title}' ({url}) already exists")]
+    DuplicateProduct { url: String, title: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum UpdateProductError {
+    #[error("Product {url} does not exist")]
+    ProductMissing { url: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum GetProductsError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum GetSkippingUrlsError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum AddSkippingUrlError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum ScrapeProductsError {
+    #[error(transparent)]
+    ParseError(#[from] ParseError),
+    #[error(transparent)]
+    GetCreatorsError(#[from] GetCreatorsError),
+    #[error(transparent)]
+    GetProductError(#[from] GetProductsError),
+    #[error(transparent)]
+    CreateProductError(#[from] CreateProductError),
+    #[error(transparent)]
+    GetSkippingUrlsError(#[from] GetSkippingUrlsError),
+    #[error(transparent)]
+    UpdateProductError(#[from] UpdateProductError),
+    #[error(transparent)]
+    AddSkippingUrlError(#[from] AddSkippingUrlError),
+    #[error(transparent)]
+    AddNotificationsError(#[from] AddNotificationsError),
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
@@ -0,0 +1,53 @@
+use crate::domain::pagination::{Page, PageRequest};
+use crate::domain::product_history::GetProductError;
+use crate::domain::site::SiteService;
+use crate::domain::toranoana::models::creator::{Creator, CreatorArgs, FollowCreatorError, FollowedCreator, GetCreatorsError, UnfollowCreatorError};
+use crate::domain::toranoana::models::product::{AddSkippingUrlError, CreateProductArgs, CreateProductError, GetProductsError, GetSkippingUrlsError, Product, ProductData, ProductHistoryEntry, ScrapeProductsError, UpdateProductArgs, UpdateProductError};
+use crate::domain::user::models::user::User;
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait ToranoanaService: SiteService {
+    async fn follow_creator(&self, user: &User, req: &CreatorArgs) -> Result<(), FollowCreatorError>;
+    async fn unfollow_creator(&self, user: &User, creator_id: i32) -> Result<(), UnfollowCreatorError>;
+    async fn get_creators(&self, user: &User) -> Result<Vec<Creator>, GetCreatorsError>;
+    async fn get_followed_creators(&self, user: &User) -> Result<Vec<Creator>, GetCreatorsError>;
+    async fn get_creators_page(&self, user: &User, following: Option<bool>, page: PageRequest) -> Result<Page<Creator>, GetCreatorsError>;
+
+    async fn get_products(&self) -> Result<Vec<Product>, GetProductsError>;
+    async fn get_products_page(&self, page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    async fn get_products_by_creator(&self, creator_id: i32) -> Result<Vec<Product>, GetProductsError>;
+    async fn get_products_page_by_creator(&self, creator_id: i32, page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    async fn get_products_page_by_creators(&self, creator_ids: &[i32], page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    async fn get_product(&self, product_id: i32) -> Result<Product, GetProductError>;
+    async fn get_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError>;
+}
+
+#[async_trait]
+pub trait ToranoanaRepository: Clone + Send + Sync + 'static {
+    async fn follow_toranoana_creator(&self, user_id: i32, req: &CreatorArgs) -> Result<(), FollowCreatorError>;
+    async fn unfollow_toranoana_creator(&self, user_id: i32, creator_id: i32) -> Result<(), UnfollowCreatorError>;
+    async fn get_toranoana_creators(&self, user_id: i32) -> Result<Vec<Creator>, GetCreatorsError>;
+    async fn get_toranoana_creators_page(&self, user_id: i32, following: Option<bool>, page: PageRequest) -> Result<Page<Creator>, GetCreatorsError>;
+    async fn get_followed_toranoana_creators(&self) -> Result<Vec<FollowedCreator>, GetCreatorsError>;
+
+    async fn create_toranoana_product(&self, req: &CreateProductArgs) -> Result<Product, CreateProductError>;
+    async fn update_toranoana_product(&self, req: &UpdateProductArgs) -> Result<Product, UpdateProductError>;
+    async fn get_toranoana_product(&self, product_id: i32) -> Result<Product, GetProductError>;
+    async fn get_toranoana_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError>;
+    async fn get_toranoana_products(&self) -> Result<Vec<Product>, GetProductsError>;
+    async fn get_toranoana_products_by_creator(&self, creator_id: i32) -> Result<Vec<Product>, GetProductsError>;
+    async fn get_toranoana_products_page(&self, page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    async fn get_toranoana_products_page_by_creator(&self, creator_id: i32, page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+    /// Products found for any of the creators, the newest first.
+    async fn get_toranoana_products_page_by_creators(&self, creator_ids: &[i32], page: PageRequest) -> Result<Page<Product>, GetProductsError>;
+
+    async fn add_toranoana_skipping_url<S: AsRef<str> + Sync>(&self, url: &str, creators: &[S]) -> Result<(), AddSkippingUrlError>;
+    async fn get_toranoana_skipping_urls(&self) -> Result<Vec<String>, GetSkippingUrlsError>;
+}
+
+#[async_trait]
+pub trait ToranoanaScraper: Clone + Send + Sync + 'static {
+    async fn get_potential_product_urls(&self, creator: &CreatorArgs) -> Result<Vec<String>, ScrapeProductsError>;
+    async fn get_product(&self, url: &str) -> Result<ProductData, ScrapeProductsError>;
+}
@@ -0,0 +1,197 @@
+use crate::domain::image::ports::ImageCache;
+use crate::domain::pagination::{Page, PageRequest};
+use crate::domain::product_history::GetProductError;
+use crate::domain::site::{ScrapeGuard, ScrapeLock, ScrapeSiteError, Site, SiteCore, SiteNotifier, SiteRepository, SiteService};
+use crate::domain::availability::Availability;
+use crate::domain::toranoana::models::creator::{Creator, CreatorArgs, FollowCreatorError, GetCreatorsError, UnfollowCreatorError};
+use crate::domain::toranoana::models::product::{CreateProductArgs, GetProductsError, Product, ProductHistoryEntry, ScrapeProductsError, UpdateProductArgs};
+use crate::domain::toranoana::ports::{ToranoanaRepository, ToranoanaScraper, ToranoanaService};
+use crate::domain::toranoana::SITE;
+use crate::domain::user::models::user::User;
+use async_trait::async_trait;
+use log::info;
+use std::collections::BTreeSet;
+
+#[derive(Debug, Clone)]
+pub struct ToranoanaServiceImpl<R, N, S, I>
+where
+    R: ToranoanaRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: ToranoanaScraper,
+    I: ImageCache
+{
+    repo: R,
+    scraper: S,
+    core: SiteCore<N, I>,
+}
+
+impl<R, N, S, I> ToranoanaServiceImpl<R, N, S, I>
+where
+    R: ToranoanaRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: ToranoanaScraper,
+    I: ImageCache
+{
+    pub fn new(repo: R, scraper: S, core: SiteCore<N, I>) -> Self {
+        Self { repo, scraper, core }
+    }
+}
+
+#[async_trait]
+impl<R, N, S, I> SiteService for ToranoanaServiceImpl<R, N, S, I>
+where
+    R: ToranoanaRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: ToranoanaScraper,
+    I: ImageCache
+{
+    fn site(&self) -> Site {
+        SITE
+    }
+
+    fn scrape_lock(&self) -> &ScrapeLock {
+        self.core.scrape_lock()
+    }
+
+    async fn scrape(&self, _guard: &ScrapeGuard) -> Result<(), ScrapeSiteError> {
+        info!("scrape available products");
+        self.scrape_followed_creators().await
+            .map_err(|e| anyhow::Error::new(e).into())
+    }
+}
+
+#[async_trait]
+impl<R, N, S, I> ToranoanaService for ToranoanaServiceImpl<R, N, S, I>
+where
+    R: ToranoanaRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: ToranoanaScraper,
+    I: ImageCache
+{
+    async fn follow_creator(&self, user: &User, creator_args: &CreatorArgs) -> Result<(), FollowCreatorError> {
+        info!("follow {} '{}' for '{}'", creator_args.kind(), creator_args.name(), user.username());
+        self.repo.follow_toranoana_creator(user.id(), creator_args).await
+    }
+
+    async fn unfollow_creator(&self, user: &User, creator_id: i32) -> Result<(), UnfollowCreatorError> {
+        info!("unfollow creator with id '{}' for '{}'", creator_id, user.username());
+        self.repo.unfollow_toranoana_creator(user.id(), creator_id).await
+    }
+
+    async fn get_creators(&self, user: &User) -> Result<Vec<Creator>, GetCreatorsError> {
+        info!("get creators for '{}'", user.username());
+        self.repo.get_toranoana_creators(user.id()).await
+    }
+
+    async fn get_followed_creators(&self, user: &User) -> Result<Vec<Creator>, GetCreatorsError> {
+        info!("get followed creators for '{}'", user.username());
+        let creators = self.repo.get_toranoana_creators(user.id()).await?;
+        Ok(
+            creators.into_iter()
+                .filter(|c| c.following())
+                .collect()
+        )
+    }
+
+    async fn get_creators_page(&self, user: &User, following: Option<bool>, page: PageRequest) -> Result<Page<Creator>, GetCreatorsError> {
+        info!("get page {} of creators for '{}'", page.page(), user.username());
+        self.repo.get_toranoana_creators_page(user.id(), following, page).await
+    }
+
+    async fn get_products(&self) -> Result<Vec<Product>, GetProductsError> {
+        info!("get products");
+        self.repo.get_toranoana_products().await
+    }
+
+    async fn get_products_page(&self, page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        info!("get page {} of products", page.page());
+        self.repo.get_toranoana_products_page(page).await
+    }
+
+    async fn get_products_by_creator(&self, creator_id: i32) -> Result<Vec<Product>, GetProductsError> {
+        info!("get products by creator with id '{}'", creator_id);
+        self.repo.get_toranoana_products_by_creator(creator_id).await
+    }
+
+    async fn get_products_page_by_creator(&self, creator_id: i32, page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        info!("get page {} of products by creator with id '{}'", page.page(), creator_id);
+        self.repo.get_toranoana_products_page_by_creator(creator_id, page).await
+    }
+
+    async fn get_products_page_by_creators(&self, creator_ids: &[i32], page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        info!("get page {} of products by creators with ids {:?}", page.page(), creator_ids);
+        self.repo.get_toranoana_products_page_by_creators(creator_ids, page).await
+    }
+
+    async fn get_product(&self, product_id: i32) -> Result<Product, GetProductError> {
+        info!("get product with id '{}'", product_id);
+        self.repo.get_toranoana_product(product_id).await
+    }
+
+    async fn get_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError> {
+        info!("get history of product with id '{}'", product_id);
+        self.repo.get_toranoana_product_history(product_id).await
+    }
+}
+
+impl<R, N, S, I> ToranoanaServiceImpl<R, N, S, I>
+where
+    R: ToranoanaRepository + SiteRepository,
+    N: SiteNotifier<Product>,
+    S: ToranoanaScraper,
+    I: ImageCache
+{
+    async fn scrape_followed_creators(&self) -> Result<(), ScrapeProductsError> {
+        let followed_creators = self.repo.get_followed_toranoana_creators().await?;
+        for followed_creator in followed_creators.iter() {
+            let creator = followed_creator.creator();
+            let target = format!("{} {}", creator.kind(), creator.name());
+            info!("scrape available products for {}", target);
+            let products = self.repo.get_toranoana_products_by_creator(creator.id()).await?;
+            let (available_products, unavailable_products) = products.iter()
+                .partition::<Vec<_>, _>(|p| p.availability().is_available());
+            let available_urls = available_products.iter().map(|p| p.url()).collect::<BTreeSet<_>>();
+            let unavailable_urls = unavailable_products.iter().map(|p| p.url()).collect::<BTreeSet<_>>();
+            let skip_urls = self.repo.get_toranoana_skipping_urls().await?.into_iter().collect::<BTreeSet<_>>();
+            let creator_args = CreatorArgs::new(creator.name().to_owned(), creator.kind());
+            let urls = self.scraper.get_potential_product_urls(&creator_args).await?
+                .into_iter().filter(|u| !skip_urls.contains(u)).collect::<Vec<_>>();
+            let (new_urls, restocked_urls) = urls.iter()
+                .filter(|u| !available_urls.contains(u.as_str()))
+                .partition::<Vec<_>, _>(|u| !unavailable_urls.contains(u.as_str()));
+
+            let mut restocked_products = Vec::<Product>::new();
+            for restocked_url in restocked_urls.into_iter() {
+                let product = self.repo.update_toranoana_product(&UpdateProductArgs::new(restocked_url.to_owned(), Availability::Available)).await?;
+                let product = self.core.cache_product_image(&self.repo, product).await;
+                restocked_products.push(product);
+            }
+            info!("found '{}' restocked products for {}", restocked_products.len(), target);
+
+            let mut new_products = Vec::<Product>::new();
+            for new_url in new_urls.into_iter() {
+                let product_data = self.scraper.get_product(new_url).await?;
+                if !product_data.has_creator(creator.name(), creator.kind()) {
+                    self.repo.add_toranoana_skipping_url(new_url, &product_data.creator_names()).await?;
+                    continue;
+                }
+                let args = CreateProductArgs::new_from_data(new_url.to_owned(), product_data);
+                let product = self.repo.create_toranoana_product(&args).await?;
+                let product = self.core.cache_product_image(&self.repo, product).await;
+                new_products.push(product);
+            }
+            info!("found '{}' new products for {}", new_products.len(), target);
+
+            self.core.notify(&self.repo, &target, followed_creator.followers(), &new_products, &restocked_products).await?;
+
+            let newly_unavailable_products = available_products.iter()
+                .filter(|p| !urls.iter().any(|u| u.eq(p.url())))
+                .collect::<Vec<_>>();
+            info!("update '{}' products as now unavailable for {}", newly_unavailable_products.len(), target);
+            for newly_unavailable in newly_unavailable_products.into_iter() {
+                self.repo.update_toranoana_product(&UpdateProductArgs::new(newly_unavailable.url().to_owned(), Availability::NotAvailable)).await?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,3 @@
+pub mod ports;
+pub mod models;
+pub mod service;
\ No newline at end of file
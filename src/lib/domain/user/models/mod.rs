@@ -0,0 +1 @@
+pub mod user;
\ No newline at end of file
@@ -0,0 +1,42 @@
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+/// Owner of the follows created before user accounts existed, renamed to the configured default user on startup.
+pub const DEFAULT_USERNAME: &str = "default";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct User {
+    id: i32,
+    date_added: DateTime<Utc>,
+    username: String,
+}
+
+impl User {
+    pub fn new(id: i32, date_added: DateTime<Utc>, username: String) -> Self {
+        Self { id, date_added, username }
+    }
+
+    pub fn id(&self) -> i32 { self.id }
+    pub fn date_added(&self) -> DateTime<Utc> { self.date_added }
+    pub fn username(&self) -> &str { &self.username }
+}
+
+#[derive(Debug, Error)]
+pub enum SetupUsersError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum GetUserError {
+    #[error("unknown user '{username}'")]
+    UnknownUser { username: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum GetUsersError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
@@ -0,0 +1,16 @@
+use crate::domain::user::models::user::{GetUserError, GetUsersError, SetupUsersError, User};
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait UserService: Send + Sync + 'static {
+    async fn setup_users(&self, default_username: &str, usernames: &[String]) -> Result<Vec<User>, SetupUsersError>;
+    async fn get_user(&self, username: &str) -> Result<User, GetUserError>;
+    async fn get_users(&self) -> Result<Vec<User>, GetUsersError>;
+}
+
+#[async_trait]
+pub trait UserRepository: Clone + Send + Sync + 'static {
+    async fn setup_users(&self, default_username: &str, usernames: &[String]) -> Result<Vec<User>, SetupUsersError>;
+    async fn get_user_by_name(&self, username: &str) -> Result<Option<User>, GetUserError>;
+    async fn get_users(&self) -> Result<Vec<User>, GetUsersError>;
+}
@@ -0,0 +1,42 @@
+use crate::domain::user::models::user::{GetUserError, GetUsersError, SetupUsersError, User};
+use crate::domain::user::ports::{UserRepository, UserService};
+use async_trait::async_trait;
+use log::info;
+
+#[derive(Debug, Clone)]
+pub struct UserServiceImpl<R>
+where
+    R: UserRepository
+{
+    repo: R,
+}
+
+impl<R> UserServiceImpl<R>
+where
+    R: UserRepository
+{
+    pub fn new(repo: R) -> Self {
+        Self { repo }
+    }
+}
+
+#[async_trait]
+impl<R> UserService for UserServiceImpl<R>
+where
+    R: UserRepository
+{
+    async fn setup_users(&self, default_username: &str, usernames: &[String]) -> Result<Vec<User>, SetupUsersError> {
+        info!("setup users with default user '{}'", default_username);
+        self.repo.setup_users(default_username, usernames).await
+    }
+
+    async fn get_user(&self, username: &str) -> Result<User, GetUserError> {
+        self.repo.get_user_by_name(username).await?
+            .ok_or_else(|| GetUserError::UnknownUser { username: username.to_owned() })
+    }
+
+    async fn get_users(&self) -> Result<Vec<User>, GetUsersError> {
+        info!("get users");
+        self.repo.get_users().await
+    }
+}
@@ -0,0 +1,319 @@
+use crate::domain::user::models::user::{GetUserError, User};
+use crate::inbound::http::handlers::api::ApiError;
+use crate::inbound::http::AppState;
+use argon2::password_hash::PasswordHash;
+use argon2::{Argon2, PasswordVerifier};
+use axum::body::Body;
+use axum::extract::{FromRequestParts, OriginalUri, Request, State};
+use axum::http::request::Parts;
+use axum::http::{header, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Redirect, Response};
+use axum_extra::extract::cookie::{Cookie, SameSite};
+use axum_extra::extract::CookieJar;
+use chrono::{DateTime, Duration, Utc};
+use debug_ignore::DebugIgnore;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use subtle::ConstantTimeEq;
+
+pub const SESSION_COOKIE: &str = "moe_session";
+pub const CSRF_FIELD: &str = "csrf_token";
+const TOKEN_LENGTH: usize = 43;
+const MAX_FORM_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpAuthConfig {
+    pub users: Vec<HttpUser>,
+    pub session_ttl: Duration,
+    pub secure_cookie: bool,
+}
+
+/// A user without password hash can only use the api.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpUser {
+    pub username: String,
+    pub password_hash: Option<DebugIgnore<String>>,
+    pub api_tokens: DebugIgnore<Vec<String>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Session {
+    username: String,
+    csrf_token: String,
+    expires_at: DateTime<Utc>,
+}
+
+/// Login sessions are only kept in memory, a restart logs everybody out.
+#[derive(Debug)]
+pub struct Authenticator {
+    config: HttpAuthConfig,
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl Authenticator {
+    pub fn new(config: HttpAuthConfig) -> Result<Self, anyhow::Error> {
+        for user in config.users.iter() {
+            if let Some(password_hash) = &user.password_hash {
+                PasswordHash::new(password_hash)
+                    .map_err(|e| anyhow::anyhow!("invalid password hash for user '{}': {}", user.username, e))?;
+            }
+        }
+        Ok(Self { config, sessions: Mutex::new(HashMap::new()) })
+    }
+
+    pub fn verify_login(&self, username: &str, password: &str) -> bool {
+        let hash = self.config.users.iter()
+            .find(|u| u.username == username)
+            .and_then(|u| u.password_hash.as_ref())
+            .and_then(|h| PasswordHash::new(h).ok());
+        let Some(hash) = hash else {
+            return false;
+        };
+        Argon2::default().verify_password(password.as_bytes(), &hash).is_ok()
+    }
+
+    /// Returns the name of the user owning the token.
+    pub fn verify_api_token(&self, token: &str) -> Option<&str> {
+        self.config.users.iter()
+            .find(|u| u.api_tokens.iter().any(|t| bool::from(t.as_bytes().ct_eq(token.as_bytes()))))
+            .map(|u| u.username.as_str())
+    }
+
+    /// Returns the id of the new session.
+    pub fn create_session(&self, username: &str) -> String {
+        let now = Utc::now();
+        let session_id = random_token();
+        let session = Session {
+            username: username.to_owned(),
+            csrf_token: random_token(),
+            expires_at: now + self.config.session_ttl,
+        };
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.retain(|_, s| s.expires_at > now);
+        sessions.insert(session_id.clone(), session);
+        session_id
+    }
+
+    pub fn get_session(&self, session_id: &str) -> Option<Session> {
+        let mut sessions = self.sessions.lock().unwrap();
+        match sessions.get(session_id) {
+            Some(session) if session.expires_at > Utc::now() => Some(session.clone()),
+            Some(_) => {
+                sessions.remove(session_id);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn delete_session(&self, session_id: &str) {
+        self.sessions.lock().unwrap().remove(session_id);
+    }
+
+    pub fn session_cookie(&self, session_id: String) -> Cookie<'static> {
+        Cookie::build((SESSION_COOKIE, session_id))
+            .path("/")
+            .http_only(true)
+            .secure(self.config.secure_cookie)
+            .same_site(SameSite::Lax)
+            .max_age(time::Duration::seconds(self.config.session_ttl.num_seconds()))
+            .build()
+    }
+}
+
+fn random_token() -> String {
+    rand::thread_rng().sample_iter(&Alphanumeric).take(TOKEN_LENGTH).map(char::from).collect()
+}
+
+/// User of the current request, the default user when authentication is disabled.
+#[derive(Debug, Clone)]
+pub struct AuthContext {
+    user: User,
+    csrf_token: Option<String>,
+}
+
+impl AuthContext {
+    pub fn user(&self) -> &User {
+        &self.user
+    }
+
+    /// Name of the logged-in user, `None` when authentication is disabled or for api tokens.
+    pub fn username(&self) -> Option<&str> {
+        self.csrf_token.as_ref().map(|_| self.user.username())
+    }
+
+    pub fn csrf_token(&self) -> Option<&str> {
+        self.csrf_token.as_deref()
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for AuthContext {
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts.extensions.get::<AuthContext>().cloned()
+            .ok_or((StatusCode::INTERNAL_SERVER_ERROR, "missing auth context"))
+    }
+}
+
+/// Guards the web ui: requires a login session and a matching csrf token on `POST` forms.
+pub(super) async fn require_session(State(state): State<AppState>, jar: CookieJar, request: Request, next: Next) -> Response {
+    let Some(authenticator) = state.authenticator.clone() else {
+        return match state.user_service.get_user(&state.default_user).await {
+            Ok(user) => run_with_context(AuthContext { user, csrf_token: None }, request, next).await,
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        };
+    };
+    let session_id = jar.get(SESSION_COOKIE).map(|c| c.value().to_owned());
+    let session = session_id.as_ref().and_then(|id| authenticator.get_session(id));
+    let user = match &session {
+        Some(session) => match state.user_service.get_user(&session.username).await {
+            Ok(user) => Some(user),
+            Err(GetUserError::UnknownUser { .. }) => None,
+            Err(GetUserError::Unknown(e)) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        },
+        None => None,
+    };
+    let (Some(session), Some(user)) = (session, user) else {
+        // sessions of users that were removed from the config are dropped as well
+        if let Some(session_id) = session_id {
+            authenticator.delete_session(&session_id);
+        }
+        let uri = request.extensions().get::<OriginalUri>().map(|u| &u.0).unwrap_or(request.uri()).to_string();
+        let next_url = serde_urlencoded::to_string([("next", uri.as_str())]).unwrap_or_default();
+        return Redirect::to(&format!("/login?{}", next_url)).into_response();
+    };
+    let request = if request.method() == Method::POST {
+        match verify_csrf_token(request, &session.csrf_token).await {
+            Ok(request) => request,
+            Err(response) => return response,
+        }
+    } else {
+        request
+    };
+    run_with_context(AuthContext { user, csrf_token: Some(session.csrf_token) }, request, next).await
+}
+
+async fn run_with_context(context: AuthContext, mut request: Request, next: Next) -> Response {
+    request.extensions_mut().insert(context);
+    next.run(request).await
+}
+
+async fn verify_csrf_token(request: Request, expected: &str) -> Result<Request, Response> {
+    let (parts, body) = request.into_parts();
+    let bytes = axum::body::to_bytes(body, MAX_FORM_SIZE).await
+        .map_err(|_| (StatusCode::PAYLOAD_TOO_LARGE, "form too large").into_response())?;
+    let fields = serde_urlencoded::from_bytes::<Vec<(String, String)>>(&bytes).unwrap_or_default();
+    let token_matches = fields.iter()
+        .find(|(name, _)| name == CSRF_FIELD)
+        .is_some_and(|(_, token)| bool::from(token.as_bytes().ct_eq(expected.as_bytes())));
+    if !token_matches {
+        return Err((StatusCode::FORBIDDEN, "invalid csrf token").into_response());
+    }
+    Ok(Request::from_parts(parts, Body::from(bytes)))
+}
+
+/// Guards `/api`: requires `Authorization: Bearer <token>` with one of the api tokens of a user.
+pub(super) async fn require_api_token(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let token = bearer_token(&request).map(|t| t.to_owned());
+    run_with_api_token(state, token, request, next).await
+}
+
+/// Guards the feeds: feed readers rarely send headers, so the api token may also be passed as `?token=<token>`.
+pub(super) async fn require_feed_token(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let token = bearer_token(&request).map(|t| t.to_owned()).or_else(|| {
+        let query = request.uri().query().unwrap_or_default();
+        serde_urlencoded::from_str::<Vec<(String, String)>>(query).unwrap_or_default()
+            .into_iter()
+            .find_map(|(name, value)| (name == "token").then_some(value))
+    });
+    run_with_api_token(state, token, request, next).await
+}
+
+fn bearer_token(request: &Request) -> Option<&str> {
+    request.headers().get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+}
+
+async fn run_with_api_token(state: AppState, token: Option<String>, request: Request, next: Next) -> Response {
+    let username = match &state.authenticator {
+        Some(authenticator) => match token.and_then(|t| authenticator.verify_api_token(t.trim())) {
+            Some(username) => username.to_owned(),
+            None => return unauthorized(),
+        },
+        None => state.default_user.clone(),
+    };
+    match state.user_service.get_user(&username).await {
+        Ok(user) => run_with_context(AuthContext { user, csrf_token: None }, request, next).await,
+        Err(GetUserError::UnknownUser { .. }) => unauthorized(),
+        Err(GetUserError::Unknown(e)) => ApiError::internal(e).into_response(),
+    }
+}
+
+fn unauthorized() -> Response {
+    (
+        [(header::WWW_AUTHENTICATE, "Bearer")],
+        ApiError::new(StatusCode::UNAUTHORIZED, "unauthorized", "missing or invalid api token"),
+    ).into_response()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use argon2::password_hash::{PasswordHasher, SaltString};
+
+    fn test_authenticator(ttl: Duration) -> Authenticator {
+        let salt = SaltString::from_b64("c29tZXNhbHRzb21lc2FsdA").unwrap();
+        let hash = Argon2::default().hash_password(b"secret", &salt).unwrap().to_string();
+        Authenticator::new(HttpAuthConfig {
+            users: vec![
+                HttpUser { username: "admin".to_owned(), password_hash: Some(hash.into()), api_tokens: vec!["token-1".to_owned()].into() },
+                HttpUser { username: "bot".to_owned(), password_hash: None, api_tokens: vec!["token-2".to_owned()].into() },
+            ],
+            session_ttl: ttl,
+            secure_cookie: false,
+        }).unwrap()
+    }
+
+    #[test]
+    fn test_verify_login() {
+        let authenticator = test_authenticator(Duration::hours(1));
+        assert!(authenticator.verify_login("admin", "secret"));
+        assert!(!authenticator.verify_login("admin", "wrong"));
+        assert!(!authenticator.verify_login("other", "secret"));
+        assert!(!authenticator.verify_login("bot", ""));
+        assert_eq!(authenticator.verify_api_token("token-1"), Some("admin"));
+        assert_eq!(authenticator.verify_api_token("token-2"), Some("bot"));
+        assert_eq!(authenticator.verify_api_token("token-3"), None);
+    }
+
+    #[test]
+    fn test_sessions() {
+        let authenticator = test_authenticator(Duration::hours(1));
+        let session_id = authenticator.create_session("admin");
+        let session = authenticator.get_session(&session_id).unwrap();
+        assert_eq!(session.username, "admin");
+        assert_eq!(session.csrf_token.len(), TOKEN_LENGTH);
+        assert!(authenticator.get_session("unknown").is_none());
+        authenticator.delete_session(&session_id);
+        assert!(authenticator.get_session(&session_id).is_none());
+
+        let expired = test_authenticator(Duration::seconds(-1));
+        let session_id = expired.create_session("admin");
+        assert!(expired.get_session(&session_id).is_none());
+    }
+
+    #[test]
+    fn test_invalid_password_hash() {
+        let config = HttpAuthConfig {
+            users: vec![HttpUser { username: "admin".to_owned(), password_hash: Some("secret".to_owned().into()), api_tokens: vec![].into() }],
+            session_ttl: Duration::hours(1),
+            secure_cookie: false,
+        };
+        assert!(Authenticator::new(config).is_err());
+    }
+}
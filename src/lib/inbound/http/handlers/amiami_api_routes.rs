@@ -0,0 +1,277 @@
+use crate::domain::amiami::ports::AmiamiService;
+use crate::domain::availability::Availability;
+use crate::domain::amiami::models::product::{FollowCategoryError, GetCategoriesError, GetMakersError, GetProductsError, Product, SetCategoryScheduleError, UnfollowCategoryError};
+use crate::domain::amiami::models::query::{ProductQuery, ProductSort};
+use crate::domain::amiami::models::release::ReleaseFilter;
+use crate::domain::pagination::{PageRequest, SortDirection, DEFAULT_PAGE_SIZE};
+use crate::domain::schedule::Schedule;
+use crate::inbound::http::auth::AuthContext;
+use crate::inbound::http::handlers::api::{ApiError, ApiErrorBody, ApiJson, ApiPath, ApiQuery, PageParams, PageResponse, SearchParams, SearchResultResponse};
+use axum::Extension;
+use axum::http::StatusCode;
+use axum::Json;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProductResponse {
+    id: i32,
+    date_added: DateTime<Utc>,
+    url: String,
+    title: String,
+    image_url: String,
+    category: String,
+    maker: String,
+    full_price: i32,
+    min_price: i32,
+    release_date: NaiveDate,
+    #[schema(value_type = String)]
+    availability: Availability,
+}
+
+impl From<Product> for ProductResponse {
+    fn from(p: Product) -> Self {
+        Self {
+            id: p.id(),
+            date_added: p.date_added(),
+            url: p.url().to_owned(),
+            title: p.title().to_owned(),
+            image_url: p.image_url().to_owned(),
+            category: p.category().to_owned(),
+            maker: p.maker().to_owned(),
+            full_price: p.full_price(),
+            min_price: p.min_price(),
+            release_date: p.release_date(),
+            availability: p.availability(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ProductListParams {
+    pub category: Option<String>,
+    #[param(value_type = Option<String>)]
+    pub availability: Option<Availability>,
+    pub added_from: Option<NaiveDate>,
+    pub added_to: Option<NaiveDate>,
+    #[param(value_type = Option<String>)]
+    pub sort: Option<ProductSort>,
+    #[param(value_type = Option<String>)]
+    pub direction: Option<SortDirection>,
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+impl ProductListParams {
+    fn product_query(self) -> ProductQuery {
+        let page = PageRequest::new(self.page.unwrap_or(1), self.page_size.unwrap_or(DEFAULT_PAGE_SIZE));
+        ProductQuery::new(page, self.sort.unwrap_or_default(), self.direction.unwrap_or_default())
+            .with_category(self.category)
+            .with_availability(self.availability)
+            .with_date_added_range(self.added_from, self.added_to)
+    }
+}
+
+#[utoipa::path(get, path = "/products", tag = "amiami", params(ProductListParams), responses(
+    (status = 200, description = "Matching products", body = PageResponse<ProductResponse>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_products(Extension(service): Extension<Arc<dyn AmiamiService>>, ApiQuery(params): ApiQuery<ProductListParams>) -> Result<Json<PageResponse<ProductResponse>>, ApiError> {
+    let page = service.get_products_page(&params.product_query()).await?;
+    Ok(Json(page.into()))
+}
+
+#[utoipa::path(get, path = "/products/search", tag = "amiami", params(SearchParams), responses(
+    (status = 200, description = "Products ranked by relevance", body = PageResponse<SearchResultResponse<ProductResponse>>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn search_products(Extension(service): Extension<Arc<dyn AmiamiService>>, ApiQuery(params): ApiQuery<SearchParams>) -> Result<Json<PageResponse<SearchResultResponse<ProductResponse>>>, ApiError> {
+    let results = service.search_products_page(&params.q, params.page_request()).await?
+        .map(|r| {
+            let rank = r.rank();
+            let (product, highlights) = r.into_parts();
+            SearchResultResponse::new(product.into(), rank, &highlights)
+        });
+    Ok(Json(results.into()))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReleaseListParams {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub category: Option<String>,
+    pub maker: Option<String>,
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+#[utoipa::path(get, path = "/releases", tag = "amiami", params(ReleaseListParams), responses(
+    (status = 200, description = "Products ordered by release date", body = PageResponse<ProductResponse>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_releases(Extension(service): Extension<Arc<dyn AmiamiService>>, ApiQuery(params): ApiQuery<ReleaseListParams>) -> Result<Json<PageResponse<ProductResponse>>, ApiError> {
+    let page = PageParams { page: params.page, page_size: params.page_size }.page_request();
+    let filter = ReleaseFilter::new(params.from, params.to, params.category, params.maker);
+    let products = service.get_releases_page(&filter, page).await?;
+    Ok(Json(products.into()))
+}
+
+#[utoipa::path(get, path = "/categories", tag = "amiami", params(PageParams), responses(
+    (status = 200, description = "All categories", body = PageResponse<String>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_categories(Extension(service): Extension<Arc<dyn AmiamiService>>, ApiQuery(params): ApiQuery<PageParams>) -> Result<Json<PageResponse<String>>, ApiError> {
+    let categories = service.get_categories_page(params.page_request()).await?;
+    Ok(Json(categories.into()))
+}
+
+#[utoipa::path(get, path = "/categories/followed", tag = "amiami", params(PageParams), responses(
+    (status = 200, description = "Followed categories", body = PageResponse<String>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_followed_categories(Extension(service): Extension<Arc<dyn AmiamiService>>, auth: AuthContext, ApiQuery(params): ApiQuery<PageParams>) -> Result<Json<PageResponse<String>>, ApiError> {
+    let categories = service.get_followed_categories_page(auth.user(), params.page_request()).await?;
+    Ok(Json(categories.into()))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct FollowCategoryRequest {
+    pub category: String,
+}
+
+#[utoipa::path(post, path = "/categories/followed", tag = "amiami", request_body = FollowCategoryRequest, responses(
+    (status = 204, description = "Category is followed"),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 409, description = "Category is already followed", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn follow_category(Extension(service): Extension<Arc<dyn AmiamiService>>, auth: AuthContext, ApiJson(body): ApiJson<FollowCategoryRequest>) -> Result<StatusCode, ApiError> {
+    let category = body.category.trim();
+    if category.is_empty() {
+        return Err(ApiError::bad_request("category must not be empty"));
+    }
+    service.follow_category(auth.user(), category).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(delete, path = "/categories/followed/{category}", tag = "amiami", params(("category" = String, Path)), responses(
+    (status = 204, description = "Category is no longer followed"),
+    (status = 404, description = "Unknown category", body = ApiErrorBody),
+    (status = 409, description = "Category is not followed", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn unfollow_category(Extension(service): Extension<Arc<dyn AmiamiService>>, auth: AuthContext, ApiPath(category): ApiPath<String>) -> Result<StatusCode, ApiError> {
+    service.unfollow_category(auth.user(), &category).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CategoryScheduleResponse {
+    category: String,
+    schedule: String,
+}
+
+#[utoipa::path(get, path = "/categories/schedules", tag = "amiami", responses(
+    (status = 200, description = "Followed categories which are scraped on their own schedule", body = Vec<CategoryScheduleResponse>),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_category_schedules(Extension(service): Extension<Arc<dyn AmiamiService>>, auth: AuthContext) -> Result<Json<Vec<CategoryScheduleResponse>>, ApiError> {
+    let categories = service.get_followed_categories(auth.user()).await?;
+    let schedules = service.get_target_schedules().await?
+        .into_iter()
+        .filter(|s| categories.iter().any(|c| c == s.name()))
+        .map(|s| CategoryScheduleResponse { category: s.name().to_owned(), schedule: s.schedule().expression().to_owned() })
+        .collect();
+    Ok(Json(schedules))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CategoryScheduleRequest {
+    /// Cron expression with seconds, e.g. `0 0 */6 * * *`, `null` scrapes the category with the site again.
+    pub schedule: Option<String>,
+}
+
+#[utoipa::path(put, path = "/categories/followed/{category}/schedule", tag = "amiami", params(("category" = String, Path)), request_body = CategoryScheduleRequest, responses(
+    (status = 204, description = "Schedule of the category is set"),
+    (status = 400, description = "Invalid schedule", body = ApiErrorBody),
+    (status = 404, description = "Unknown category", body = ApiErrorBody),
+    (status = 409, description = "Category is not followed", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn set_category_schedule(Extension(service): Extension<Arc<dyn AmiamiService>>, auth: AuthContext, ApiPath(category): ApiPath<String>, ApiJson(body): ApiJson<CategoryScheduleRequest>) -> Result<StatusCode, ApiError> {
+    let schedule = Schedule::parse_optional(body.schedule.as_deref().unwrap_or_default())?;
+    service.set_category_schedule(auth.user(), &category, schedule.as_ref()).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(get, path = "/makers", tag = "amiami", params(PageParams), responses(
+    (status = 200, description = "All makers", body = PageResponse<String>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_makers(Extension(service): Extension<Arc<dyn AmiamiService>>, ApiQuery(params): ApiQuery<PageParams>) -> Result<Json<PageResponse<String>>, ApiError> {
+    let makers = service.get_makers_page(params.page_request()).await?;
+    Ok(Json(makers.into()))
+}
+
+impl From<GetProductsError> for ApiError {
+    fn from(e: GetProductsError) -> Self {
+        match e {
+            GetProductsError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<GetCategoriesError> for ApiError {
+    fn from(e: GetCategoriesError) -> Self {
+        match e {
+            GetCategoriesError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<SetCategoryScheduleError> for ApiError {
+    fn from(e: SetCategoryScheduleError) -> Self {
+        match e {
+            e @ SetCategoryScheduleError::UnknownCategory { .. } => ApiError::not_found(e),
+            e @ SetCategoryScheduleError::CategoryNotFollowed { .. } => ApiError::conflict(e),
+            SetCategoryScheduleError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<GetMakersError> for ApiError {
+    fn from(e: GetMakersError) -> Self {
+        match e {
+            GetMakersError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<FollowCategoryError> for ApiError {
+    fn from(e: FollowCategoryError) -> Self {
+        match e {
+            e @ FollowCategoryError::AlreadyFollowed { .. } => ApiError::conflict(e),
+            FollowCategoryError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<UnfollowCategoryError> for ApiError {
+    fn from(e: UnfollowCategoryError) -> Self {
+        match e {
+            e @ UnfollowCategoryError::UnknownCategory { .. } => ApiError::not_found(e),
+            e @ UnfollowCategoryError::CategoryNotFollowed { .. } => ApiError::conflict(e),
+            UnfollowCategoryError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
@@ -1,60 +1,656 @@
-use crate::domain::amiami::models::availability::Availability;
-use crate::domain::amiami::models::product::{GetProductsError, Product};
+use crate::domain::availability::Availability;
+use crate::domain::amiami::models::product::{GetAvailabilityStatsError, GetCategoriesError, GetMakersError, GetProductsError, Price, Product, ProductHistoryEntry, SetCategoryScheduleError};
+use crate::domain::amiami::models::query::{ProductQuery, ProductSort};
+use crate::domain::amiami::models::release::ReleaseFilter;
 use crate::domain::amiami::ports::AmiamiService;
+use crate::domain::duplicate::models::listing::Listing;
+use crate::domain::amiami::SITE;
+use crate::domain::pagination::{PageRequest, SortDirection, DEFAULT_PAGE_SIZE};
+use crate::domain::product_history::ProductChange;
+use crate::domain::schedule::{Schedule, TargetSchedule};
+use crate::domain::search::{FieldHighlight, HighlightedText};
+use crate::inbound::http::handlers::feeds::{feed_response, Feed, FeedEntry, FeedFormat, FEED_SIZE};
+use crate::inbound::http::handlers::stats::AvailabilityStatsTemplate;
+use crate::inbound::http::handlers::{scrape_event_stream, Pagination, TargetRuns};
+use crate::inbound::http::auth::AuthContext;
 use crate::inbound::http::AppState;
 use askama::Template;
 use askama_axum::{IntoResponse, Response};
-use axum::extract::{Query, State};
-use axum::http::StatusCode;
-use chrono::{DateTime, Utc};
-use serde::Deserialize;
+use axum::extract::{Path, Query, State};
+use axum::Extension;
+use axum::http::{header, HeaderMap, StatusCode, Uri};
+use axum::Form;
+use chrono::{DateTime, Datelike, Days, Local, Months, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, NoneAsEmptyString};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use strum::IntoEnumIterator;
 
+const CALENDAR_MONTH_FORMAT: &str = "%Y-%m";
+const ICAL_LINE_LIMIT: usize = 75;
+
 #[derive(Template)]
 #[template(path = "amiami.html")]
 struct AmiamiTemplate {
+    auth: AuthContext,
     products: Vec<Product>,
+    search: Option<String>,
+    highlights: HashMap<i32, Vec<FieldHighlight>>,
+    params: OverviewParams,
+    categories: Vec<String>,
+    schedules: Vec<TargetSchedule>,
+    target_runs: TargetRuns,
     availabilities: Vec<Availability>,
-    selected_availability: Option<Availability>,
+    sorts: Vec<ProductSort>,
+    directions: Vec<SortDirection>,
+    page_sizes: Vec<u32>,
+    pagination: Pagination,
 }
 
 impl AmiamiTemplate {
     fn format_date(date: DateTime<Utc>) -> String {
         date.format("%Y-%m-%d %H:%M").to_string()
     }
+
+    fn search_action(&self) -> &'static str {
+        "/amiami"
+    }
+
+    fn events_action(&self) -> &'static str {
+        "/amiami/events"
+    }
+
+    fn category_schedule(&self, category: &str) -> &str {
+        self.schedules.iter()
+            .find(|s| s.name() == category)
+            .map(|s| s.schedule().expression())
+            .unwrap_or_default()
+    }
+
+    fn highlight(&self, product_id: i32, field: &str) -> Option<&HighlightedText> {
+        self.highlights.get(&product_id)
+            .and_then(|h| h.iter().find(|h| h.field() == field))
+            .map(|h| h.text())
+    }
+
+    fn is_selected(&self, field: &str, value: &str) -> bool {
+        let params = &self.params;
+        let current = match field {
+            "category" => params.category.clone(),
+            "availability" => params.availability.as_ref().map(|a| a.to_string()),
+            "sort" => Some(params.sort.unwrap_or_default().to_string()),
+            "direction" => Some(params.direction.unwrap_or_default().to_string()),
+            "page_size" => Some(params.page_size.unwrap_or(DEFAULT_PAGE_SIZE).to_string()),
+            _ => None,
+        };
+        current.as_deref() == Some(value)
+    }
+
+    fn date_value(date: &Option<NaiveDate>) -> String {
+        date.map(|d| d.to_string()).unwrap_or_default()
+    }
 }
 
-#[derive(Debug, Deserialize)]
+#[serde_as]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
 pub struct OverviewParams {
-    pub selected_availability: Option<Availability>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search: Option<String>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub availability: Option<Availability>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub added_from: Option<NaiveDate>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub added_to: Option<NaiveDate>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort: Option<ProductSort>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direction: Option<SortDirection>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<u32>,
 }
 
-pub async fn get_overview(State(state): State<AppState>, Query(params): Query<OverviewParams>) -> Response {
-    get_overview_response(state.amiami_service, params.selected_availability).await
+impl OverviewParams {
+    fn pagination(&self, page: u32, total_pages: u32, total_items: i64) -> Pagination {
+        let params = OverviewParams { page: None, ..self.clone() };
+        let query = serde_urlencoded::to_string(&params).unwrap_or_default();
+        Pagination::new("/amiami", &query, page, total_pages, total_items)
+    }
+
+    /// Live products are only inserted into the unfiltered first page with the newest products first.
+    fn is_live(&self) -> bool {
+        let latest = OverviewParams {
+            sort: Some(ProductSort::default()),
+            direction: Some(SortDirection::default()),
+            page: Some(1),
+            page_size: self.page_size,
+            ..OverviewParams::default()
+        };
+        let current = OverviewParams {
+            sort: Some(self.sort.unwrap_or_default()),
+            direction: Some(self.direction.unwrap_or_default()),
+            page: Some(self.page.unwrap_or(1)),
+            ..self.clone()
+        };
+        current == latest
+    }
+
+    fn product_query(&self) -> ProductQuery {
+        let page = PageRequest::new(self.page.unwrap_or(1), self.page_size.unwrap_or(DEFAULT_PAGE_SIZE));
+        ProductQuery::new(page, self.sort.unwrap_or_default(), self.direction.unwrap_or_default())
+            .with_category(self.category.clone())
+            .with_availability(self.availability.clone())
+            .with_date_added_range(self.added_from, self.added_to)
+    }
 }
 
-pub async fn get_overview_response(service: Arc<dyn AmiamiService>, selected_availability: Option<Availability>) -> Response {
-    let products = match service.get_products().await {
-        Ok(a) => a,
+pub async fn get_overview(Extension(service): Extension<Arc<dyn AmiamiService>>, auth: AuthContext, Query(params): Query<OverviewParams>) -> Response {
+    get_overview_response(service, auth, params).await
+}
+
+pub async fn get_category_feed(State(state): State<AppState>, Extension(service): Extension<Arc<dyn AmiamiService>>, Path(category): Path<String>, uri: Uri, headers: HeaderMap) -> Response {
+    let query = ProductQuery::new(PageRequest::new(1, FEED_SIZE), ProductSort::DateChanged, SortDirection::Desc)
+        .with_category(Some(category.clone()));
+    let products = match service.get_products_page(&query).await {
+        Ok(p) => p.into_items(),
         Err(e) => return e.into_response()
     };
-    let filtered_products = match &selected_availability {
-        Some(availability) => products.into_iter().filter(|p| &p.availability() == availability).collect(),
-        None => products
+    let product_ids = products.iter().map(|p| p.id()).collect::<Vec<_>>();
+    let dates_updated = match state.product_index_service.get_dates_updated(SITE, &product_ids).await {
+        Ok(d) => d,
+        Err(e) => return e.into_response()
     };
+    let entries = products.iter()
+        .map(|p| FeedEntry::new(
+            format!("amiami:{}", p.id()),
+            p.title().to_owned(),
+            p.url().to_owned(),
+            p.image_url().to_owned(),
+            p.availability().to_string(),
+            p.date_added(),
+        ).with_price(Some(format!("¥{}", p.min_price()))).with_date_restocked(p.date_restocked()).with_date_updated(dates_updated.get(&p.id()).copied()))
+        .collect();
+    let feed = Feed::new(format!("urn:moe-scraper:amiami:category:{}", category), format!("AmiAmi - {}", category), SITE.url().to_owned(), entries);
+    feed_response(&feed, FeedFormat::from_path(uri.path()), &headers)
+}
+
+#[derive(Template)]
+#[template(path = "amiami-product-card.html")]
+struct AmiamiProductCardTemplate<'a> {
+    product: &'a Product,
+}
+
+impl AmiamiProductCardTemplate<'_> {
+    fn format_date(date: DateTime<Utc>) -> String {
+        AmiamiTemplate::format_date(date)
+    }
+
+    fn highlight(&self, _product_id: i32, _field: &str) -> Option<&HighlightedText> {
+        None
+    }
+}
+
+#[derive(Template)]
+#[template(path = "amiami-product.html")]
+struct AmiamiProductTemplate {
+    auth: AuthContext,
+    product: Product,
+    history: Vec<ProductHistoryEntry>,
+    listings: Vec<Listing>,
+}
+
+impl AmiamiProductTemplate {
+    fn format_date(date: DateTime<Utc>) -> String {
+        AmiamiTemplate::format_date(date)
+    }
+
+    fn format_prices(full_price: i32, min_price: i32) -> String {
+        match full_price == min_price {
+            true => format!("¥{}", min_price),
+            false => format!("¥{} (¥{})", min_price, full_price),
+        }
+    }
+
+    fn format_price(&self, price: &Price) -> String {
+        Self::format_prices(price.full_price(), price.min_price())
+    }
+}
+
+pub async fn get_product(State(state): State<AppState>, Extension(service): Extension<Arc<dyn AmiamiService>>, auth: AuthContext, Path(product_id): Path<i32>) -> Response {
+    let product = match service.get_product(product_id).await {
+        Ok(p) => p,
+        Err(e) => return e.into_response()
+    };
+    let history = match service.get_product_history(product_id).await {
+        Ok(h) => h,
+        Err(e) => return e.into_response()
+    };
+    let listings = match state.duplicate_service.get_duplicate_listings(SITE, product_id, product.image_phash()).await {
+        Ok(l) => l,
+        Err(e) => return e.into_response()
+    };
+    AmiamiProductTemplate { auth, product, history, listings }.into_response()
+}
+
+pub async fn get_stats(Extension(service): Extension<Arc<dyn AmiamiService>>, auth: AuthContext) -> Response {
+    let stats = match service.get_availability_stats().await {
+        Ok(s) => s,
+        Err(e) => return e.into_response()
+    };
+    AvailabilityStatsTemplate { auth, site: "AmiAmi", base_path: "/amiami", group_label: "Maker", stats }.into_response()
+}
+
+pub async fn get_events(Extension(service): Extension<Arc<dyn AmiamiService>>, auth: AuthContext) -> axum::response::Response {
+    let receiver = service.subscribe_scrape_events();
+    scrape_event_stream(receiver, auth.user().id(), |product| {
+        AmiamiProductCardTemplate { product }.render().unwrap_or_default()
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CategoryScheduleForm {
+    category: String,
+    schedule: String,
+}
+
+pub async fn post_category_schedule(Extension(service): Extension<Arc<dyn AmiamiService>>, auth: AuthContext, Form(input): Form<CategoryScheduleForm>) -> Response {
+    let schedule = match Schedule::parse_optional(&input.schedule) {
+        Ok(s) => s,
+        Err(e) => return e.into_response()
+    };
+    if let Err(e) = service.set_category_schedule(auth.user(), &input.category, schedule.as_ref()).await {
+        return e.into_response();
+    }
+    get_overview_response(service, auth, OverviewParams { category: Some(input.category), ..OverviewParams::default() }).await
+}
+
+pub async fn get_overview_response(service: Arc<dyn AmiamiService>, auth: AuthContext, params: OverviewParams) -> Response {
+    let categories = match service.get_followed_categories(auth.user()).await {
+        Ok(c) => c,
+        Err(e) => return e.into_response()
+    };
+    let schedules = match service.get_target_schedules().await {
+        Ok(s) => s,
+        Err(e) => return e.into_response()
+    };
+    let adaptive_targets = match service.get_adaptive_targets().await {
+        Ok(t) => t,
+        Err(e) => return e.into_response()
+    };
+    let target_runs = TargetRuns::new(&schedules, &adaptive_targets, Utc::now(), |name| categories.iter().any(|c| c == name));
+    let mut highlights = HashMap::new();
+    let (products, page, total_pages, total_items) = match params.search.as_ref().filter(|s| !s.trim().is_empty()) {
+        Some(search) => {
+            let products = match service.search_products(search).await {
+                Ok(results) => results.into_iter()
+                    .map(|r| r.into_parts())
+                    .filter(|(p, _)| categories.iter().any(|c| c == p.category()))
+                    .filter(|(p, _)| params.availability.as_ref().is_none_or(|a| &p.availability() == a))
+                    .map(|(p, h)| {
+                        highlights.insert(p.id(), h);
+                        p
+                    })
+                    .collect::<Vec<_>>(),
+                Err(e) => return e.into_response()
+            };
+            let total_items = products.len() as i64;
+            (products, 1, 1, total_items)
+        }
+        None => match service.get_products_page(&params.product_query().with_followed_by(Some(auth.user().id()))).await {
+            Ok(p) => {
+                let (page, total_pages, total_items) = (p.page(), p.total_pages(), p.total_items());
+                (p.into_items(), page, total_pages, total_items)
+            }
+            Err(e) => return e.into_response()
+        }
+    };
+    let pagination = params.pagination(page, total_pages, total_items);
     let template = AmiamiTemplate {
-        products: filtered_products,
+        auth,
+        products,
+        search: params.search.clone(),
+        highlights,
+        params,
+        categories,
+        schedules,
+        target_runs,
         availabilities: Availability::iter().collect(),
-        selected_availability
+        sorts: ProductSort::iter().collect(),
+        directions: SortDirection::iter().collect(),
+        page_sizes: vec![25, DEFAULT_PAGE_SIZE, 100, 200],
+        pagination,
     };
     template.into_response()
 }
 
+#[derive(Template)]
+#[template(path = "amiami-calendar.html")]
+struct AmiamiCalendarTemplate {
+    auth: AuthContext,
+    month: NaiveDate,
+    weeks: Vec<Vec<CalendarDay>>,
+    categories: Vec<String>,
+    makers: Vec<String>,
+    selected_category: Option<String>,
+    selected_maker: Option<String>,
+}
+
+impl AmiamiCalendarTemplate {
+    fn format_month(date: &NaiveDate) -> String {
+        date.format("%B %Y").to_string()
+    }
+
+    fn month_param(date: &NaiveDate) -> String {
+        date.format(CALENDAR_MONTH_FORMAT).to_string()
+    }
+
+    fn previous_month(&self) -> String {
+        Self::month_param(&(self.month - Months::new(1)))
+    }
+
+    fn next_month(&self) -> String {
+        Self::month_param(&(self.month + Months::new(1)))
+    }
+
+    fn filter_params(&self) -> String {
+        let params = [("category", &self.selected_category), ("maker", &self.selected_maker)].into_iter()
+            .filter_map(|(name, value)| value.as_deref().map(|v| (name, v)))
+            .collect::<Vec<_>>();
+        match serde_urlencoded::to_string(params).unwrap_or_default() {
+            query if query.is_empty() => query,
+            query => format!("&{}", query),
+        }
+    }
+
+    fn ical_url(&self) -> String {
+        match self.filter_params().strip_prefix('&') {
+            Some(params) => format!("/amiami/calendar.ics?{}", params),
+            None => "/amiami/calendar.ics".to_owned(),
+        }
+    }
+}
+
+struct CalendarDay {
+    date: NaiveDate,
+    in_month: bool,
+    products: Vec<Product>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CalendarParams {
+    pub month: Option<String>,
+    pub category: Option<String>,
+    pub maker: Option<String>,
+}
+
+impl CalendarParams {
+    fn category(&self) -> Option<String> {
+        self.category.as_ref().filter(|c| !c.is_empty()).cloned()
+    }
+
+    fn maker(&self) -> Option<String> {
+        self.maker.as_ref().filter(|m| !m.is_empty()).cloned()
+    }
+}
+
+pub async fn get_calendar(Extension(service): Extension<Arc<dyn AmiamiService>>, auth: AuthContext, Query(params): Query<CalendarParams>) -> Response {
+    let service = service;
+    let today = Local::now().date_naive();
+    let month = params.month.as_ref()
+        .and_then(|m| NaiveDate::parse_from_str(&format!("{}-01", m), "%Y-%m-%d").ok())
+        .unwrap_or_else(|| today.with_day(1).unwrap());
+    let month_end = month + Months::new(1) - Days::new(1);
+    let filter = ReleaseFilter::new(Some(month), Some(month_end), params.category(), params.maker());
+    let products = match service.get_releases(&filter).await {
+        Ok(p) => p,
+        Err(e) => return e.into_response()
+    };
+    let categories = match service.get_categories().await {
+        Ok(c) => c,
+        Err(e) => return e.into_response()
+    };
+    let makers = match service.get_makers().await {
+        Ok(m) => m,
+        Err(e) => return e.into_response()
+    };
+    let template = AmiamiCalendarTemplate {
+        auth,
+        month,
+        weeks: calendar_weeks(month, products),
+        categories,
+        makers,
+        selected_category: params.category(),
+        selected_maker: params.maker(),
+    };
+    template.into_response()
+}
+
+pub async fn get_calendar_ical(Extension(service): Extension<Arc<dyn AmiamiService>>, Query(params): Query<CalendarParams>) -> Response {
+    let from = Local::now().date_naive().with_day(1).unwrap() - Months::new(1);
+    let filter = ReleaseFilter::new(Some(from), None, params.category(), params.maker());
+    let products = match service.get_releases(&filter).await {
+        Ok(p) => p,
+        Err(e) => return e.into_response()
+    };
+    (
+        [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+        products_to_ical(&products),
+    ).into_response()
+}
+
+fn calendar_weeks(month: NaiveDate, products: Vec<Product>) -> Vec<Vec<CalendarDay>> {
+    let month_end = month + Months::new(1) - Days::new(1);
+    let grid_start = month - Days::new(month.weekday().num_days_from_monday() as u64);
+    let grid_end = month_end + Days::new(6 - month_end.weekday().num_days_from_monday() as u64);
+    let mut products_by_date = BTreeMap::<NaiveDate, Vec<Product>>::new();
+    for product in products {
+        products_by_date.entry(product.release_date()).or_default().push(product);
+    }
+    let days = grid_start.iter_days()
+        .take_while(|d| d <= &grid_end)
+        .map(|date| CalendarDay {
+            date,
+            in_month: date.month() == month.month(),
+            products: products_by_date.remove(&date).unwrap_or_default(),
+        })
+        .collect::<Vec<_>>();
+    let mut weeks = Vec::new();
+    let mut days = days.into_iter().peekable();
+    while days.peek().is_some() {
+        weeks.push(days.by_ref().take(7).collect());
+    }
+    weeks
+}
+
+fn products_to_ical(products: &[Product]) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_owned(),
+        "VERSION:2.0".to_owned(),
+        "PRODID:-//moe-scraper//amiami releases//EN".to_owned(),
+        "CALSCALE:GREGORIAN".to_owned(),
+        "X-WR-CALNAME:AmiAmi releases".to_owned(),
+    ];
+    for product in products {
+        let release_date = product.release_date();
+        let description = format!(
+            "{}\\n¥{}\\n{}",
+            escape_ical_text(product.maker()),
+            product.min_price(),
+            product.availability()
+        );
+        lines.extend([
+            "BEGIN:VEVENT".to_owned(),
+            format!("UID:amiami-product-{}@moe-scraper", product.id()),
+            format!("DTSTAMP:{}", product.date_added().format("%Y%m%dT%H%M%SZ")),
+            format!("DTSTART;VALUE=DATE:{}", release_date.format("%Y%m%d")),
+            format!("DTEND;VALUE=DATE:{}", (release_date + Days::new(1)).format("%Y%m%d")),
+            format!("SUMMARY:{}", escape_ical_text(product.title())),
+            format!("DESCRIPTION:{}", description),
+            format!("CATEGORIES:{}", escape_ical_text(product.category())),
+            format!("URL:{}", product.url()),
+            "TRANSP:TRANSPARENT".to_owned(),
+            "END:VEVENT".to_owned(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_owned());
+    lines.iter()
+        .map(|l| fold_ical_line(l))
+        .collect::<Vec<_>>()
+        .join("")
+}
+
+fn escape_ical_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Lines longer than 75 octets have to be folded, continuation lines start with a single space.
+fn fold_ical_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut line_length = 0;
+    for c in line.chars() {
+        if line_length + c.len_utf8() > ICAL_LINE_LIMIT {
+            folded.push_str("\r\n ");
+            line_length = 1;
+        }
+        folded.push(c);
+        line_length += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+impl IntoResponse for GetAvailabilityStatsError {
+    fn into_response(self) -> Response {
+        match self {
+            GetAvailabilityStatsError::GetProductsError(e) => e.into_response(),
+            GetAvailabilityStatsError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
 impl IntoResponse for GetProductsError {
     fn into_response(self) -> Response {
         match self {
             GetProductsError::Unknown(cause) => (StatusCode::INTERNAL_SERVER_ERROR, cause.to_string()).into_response(),
         }
     }
-}
\ No newline at end of file
+}
+
+impl IntoResponse for GetCategoriesError {
+    fn into_response(self) -> Response {
+        match self {
+            GetCategoriesError::Unknown(cause) => (StatusCode::INTERNAL_SERVER_ERROR, cause.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for SetCategoryScheduleError {
+    fn into_response(self) -> Response {
+        match self {
+            e @ SetCategoryScheduleError::UnknownCategory { .. } => (StatusCode::NOT_FOUND, e.to_string()).into_response(),
+            e @ SetCategoryScheduleError::CategoryNotFollowed { .. } => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            SetCategoryScheduleError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for GetMakersError {
+    fn into_response(self) -> Response {
+        match self {
+            GetMakersError::Unknown(cause) => (StatusCode::INTERNAL_SERVER_ERROR, cause.to_string()).into_response(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_products_to_ical() {
+        let product = Product::new(
+            7,
+            DateTime::parse_from_rfc3339("2025-11-02T10:15:00Z").unwrap().to_utc(),
+            "https://www.amiami.com/eng/detail/?gcode=FIGURE-1".to_owned(),
+            "Figure, 1/7 Scale; Complete".to_owned(),
+            "https://img.amiami.com/figure-1.jpg".to_owned(),
+            "459".to_owned(),
+            "Alter".to_owned(),
+            20000,
+            18000,
+            NaiveDate::from_ymd_opt(2026, 3, 31).unwrap(),
+            Availability::Preorder,
+        );
+        let ical = products_to_ical(&[product]);
+        assert!(ical.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ical.ends_with("END:VCALENDAR\r\n"));
+        assert!(ical.contains("UID:amiami-product-7@moe-scraper\r\n"));
+        assert!(ical.contains("DTSTART;VALUE=DATE:20260331\r\nDTEND;VALUE=DATE:20260401\r\n"));
+        assert!(ical.contains("SUMMARY:Figure\\, 1/7 Scale\\; Complete\r\n"));
+    }
+
+    #[test]
+    fn test_fold_ical_line() {
+        let line = "SUMMARY:".to_owned() + &"あ".repeat(30);
+        let folded = fold_ical_line(&line);
+        assert!(folded.split("\r\n").all(|l| l.len() <= ICAL_LINE_LIMIT));
+        assert_eq!(folded.replace("\r\n ", "").trim_end(), line);
+    }
+
+    #[test]
+    fn test_calendar_weeks() {
+        let month = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let weeks = calendar_weeks(month, vec![]);
+        assert_eq!(weeks.len(), 6);
+        assert!(weeks.iter().all(|w| w.len() == 7));
+        assert_eq!(weeks.first().unwrap().first().unwrap().date, NaiveDate::from_ymd_opt(2026, 2, 23).unwrap());
+        assert_eq!(weeks.last().unwrap().last().unwrap().date, NaiveDate::from_ymd_opt(2026, 4, 5).unwrap());
+    }
+
+    #[test]
+    fn test_overview_params_is_live() {
+        assert!(OverviewParams::default().is_live());
+        assert!(OverviewParams { page: Some(1), page_size: Some(100), sort: Some(ProductSort::DateAdded), ..OverviewParams::default() }.is_live());
+        assert!(!OverviewParams { page: Some(2), ..OverviewParams::default() }.is_live());
+        assert!(!OverviewParams { category: Some("459".to_owned()), ..OverviewParams::default() }.is_live());
+        assert!(!OverviewParams { direction: Some(SortDirection::Asc), ..OverviewParams::default() }.is_live());
+    }
+
+    #[test]
+    fn test_product_card_template() {
+        let product = Product::new(
+            7,
+            DateTime::parse_from_rfc3339("2025-11-02T10:15:00Z").unwrap().to_utc(),
+            "https://www.amiami.com/eng/detail/?gcode=FIGURE-1".to_owned(),
+            "Figure".to_owned(),
+            "https://img.amiami.com/figure-1.jpg".to_owned(),
+            "459".to_owned(),
+            "Alter".to_owned(),
+            20000,
+            18000,
+            NaiveDate::from_ymd_opt(2026, 3, 31).unwrap(),
+            Availability::Preorder,
+        );
+        let html = AmiamiProductCardTemplate { product: &product }.render().unwrap();
+        assert!(html.starts_with("<div class=\"product-grid-item\" data-product-id=\"7\">"));
+        assert!(html.contains("2025-11-02 10:15"));
+    }
+}
@@ -0,0 +1,278 @@
+use crate::domain::pagination::{Page, PageRequest, DEFAULT_PAGE_SIZE};
+use crate::domain::schedule::{GetTargetSchedulesError, InvalidScheduleError};
+use crate::domain::search::{FieldHighlight, SearchProductsError};
+use axum::response::{IntoResponse, Response};
+use axum::extract::rejection::{JsonRejection, PathRejection, QueryRejection};
+use axum::extract::{FromRequest, FromRequestParts, Path, Query, Request};
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum::Json;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// Error body shared by all `/api/v1` endpoints.
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiErrorBody {
+    error: ApiErrorDetails,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiErrorDetails {
+    status: u16,
+    code: &'static str,
+    message: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: &'static str, message: impl ToString) -> Self {
+        Self { status, code, message: message.to_string() }
+    }
+
+    pub fn bad_request(message: impl ToString) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "bad_request", message)
+    }
+
+    pub fn not_found(message: impl ToString) -> Self {
+        Self::new(StatusCode::NOT_FOUND, "not_found", message)
+    }
+
+    pub fn conflict(message: impl ToString) -> Self {
+        Self::new(StatusCode::CONFLICT, "conflict", message)
+    }
+
+    pub fn internal(error: anyhow::Error) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, "internal_error", format!("{:#}", error))
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ApiErrorBody {
+            error: ApiErrorDetails { status: self.status.as_u16(), code: self.code, message: self.message },
+        };
+        (self.status, Json(body)).into_response()
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        Self::new(rejection.status(), "invalid_body", rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(rejection: QueryRejection) -> Self {
+        Self::new(rejection.status(), "invalid_query", rejection.body_text())
+    }
+}
+
+impl From<PathRejection> for ApiError {
+    fn from(rejection: PathRejection) -> Self {
+        Self::new(rejection.status(), "invalid_path", rejection.body_text())
+    }
+}
+
+impl From<SearchProductsError> for ApiError {
+    fn from(e: SearchProductsError) -> Self {
+        match e {
+            e @ SearchProductsError::QueryTooShort { .. } => Self::bad_request(e),
+            SearchProductsError::Unknown(e) => Self::internal(e),
+        }
+    }
+}
+
+impl From<InvalidScheduleError> for ApiError {
+    fn from(e: InvalidScheduleError) -> Self {
+        Self::bad_request(e)
+    }
+}
+
+impl From<GetTargetSchedulesError> for ApiError {
+    fn from(e: GetTargetSchedulesError) -> Self {
+        match e {
+            GetTargetSchedulesError::Unknown(e) => Self::internal(e),
+        }
+    }
+}
+
+/// `Json` extractor that reports rejections as [`ApiError`].
+pub struct ApiJson<T>(pub T);
+
+impl<S, T> FromRequest<S> for ApiJson<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state).await?;
+        Ok(Self(value))
+    }
+}
+
+/// `Query` extractor that reports rejections as [`ApiError`].
+pub struct ApiQuery<T>(pub T);
+
+impl<S, T> FromRequestParts<S> for ApiQuery<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(value) = Query::<T>::from_request_parts(parts, state).await?;
+        Ok(Self(value))
+    }
+}
+
+/// `Path` extractor that reports rejections as [`ApiError`].
+pub struct ApiPath<T>(pub T);
+
+impl<S, T> FromRequestParts<S> for ApiPath<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned + Send,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Path(value) = Path::<T>::from_request_parts(parts, state).await?;
+        Ok(Self(value))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PageParams {
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+impl PageParams {
+    pub fn page_request(&self) -> PageRequest {
+        PageRequest::new(self.page.unwrap_or(1), self.page_size.unwrap_or(DEFAULT_PAGE_SIZE))
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PageResponse<T> {
+    items: Vec<T>,
+    page: u32,
+    page_size: u32,
+    total_items: i64,
+    total_pages: u32,
+}
+
+impl<T, P: Into<T>> From<Page<P>> for PageResponse<T> {
+    fn from(page: Page<P>) -> Self {
+        let (page_number, page_size, total_items, total_pages) = (page.page(), page.page_size(), page.total_items(), page.total_pages());
+        Self {
+            items: page.into_items().into_iter().map(|i| i.into()).collect(),
+            page: page_number,
+            page_size,
+            total_items,
+            total_pages,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchParams {
+    pub q: String,
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+impl SearchParams {
+    pub fn page_request(&self) -> PageRequest {
+        PageParams { page: self.page, page_size: self.page_size }.page_request()
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchResultResponse<P> {
+    product: P,
+    rank: f64,
+    highlights: Vec<FieldHighlightResponse>,
+}
+
+impl<P> SearchResultResponse<P> {
+    pub fn new(product: P, rank: f64, highlights: &[FieldHighlight]) -> Self {
+        Self {
+            product,
+            rank,
+            highlights: highlights.iter().map(|h| h.into()).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FieldHighlightResponse {
+    field: String,
+    segments: Vec<HighlightSegmentResponse>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HighlightSegmentResponse {
+    text: String,
+    matched: bool,
+}
+
+impl From<&FieldHighlight> for FieldHighlightResponse {
+    fn from(h: &FieldHighlight) -> Self {
+        Self {
+            field: h.field().to_owned(),
+            segments: h.text().segments().iter()
+                .map(|s| HighlightSegmentResponse { text: s.text().to_owned(), matched: s.matched() })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::domain::search::HighlightedText;
+
+    #[tokio::test]
+    async fn test_api_error_response() {
+        let response = ApiError::conflict("artist already followed").into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = serde_json::from_slice::<serde_json::Value>(&body).unwrap();
+        assert_eq!(body, serde_json::json!({
+            "error": { "status": 409, "code": "conflict", "message": "artist already followed" }
+        }));
+    }
+
+    #[test]
+    fn test_search_error_mapping() {
+        let error = ApiError::from(SearchProductsError::QueryTooShort { query: "a".to_owned(), min_length: 3 });
+        assert_eq!(error.status, StatusCode::BAD_REQUEST);
+        let error = ApiError::from(SearchProductsError::Unknown(anyhow::anyhow!("db gone")));
+        assert_eq!(error.status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(error.code, "internal_error");
+    }
+
+    #[test]
+    fn test_page_response() {
+        let page = Page::new(vec![4, 5, 6], PageRequest::new(2, 3), 7);
+        let response: PageResponse<i64> = page.into();
+        assert_eq!(serde_json::to_value(&response).unwrap(), serde_json::json!({
+            "items": [4, 5, 6], "page": 2, "page_size": 3, "total_items": 7, "total_pages": 3
+        }));
+        let highlight = FieldHighlightResponse::from(&FieldHighlight::new("title".to_owned(), HighlightedText::new(vec![])));
+        assert_eq!(highlight.field, "title");
+    }
+}
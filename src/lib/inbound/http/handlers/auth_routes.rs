@@ -0,0 +1,93 @@
+use crate::inbound::http::auth::SESSION_COOKIE;
+use crate::inbound::http::AppState;
+use askama::Template;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse, Redirect, Response};
+use axum::Form;
+use axum_extra::extract::cookie::Cookie;
+use axum_extra::extract::CookieJar;
+use serde::Deserialize;
+
+#[derive(Template)]
+#[template(path = "login.html")]
+struct LoginTemplate {
+    next: String,
+    error: Option<String>,
+}
+
+impl LoginTemplate {
+    fn into_html_response(self, status: StatusCode) -> Response {
+        match self.render() {
+            Ok(html) => (status, Html(html)).into_response(),
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LoginParams {
+    next: Option<String>,
+}
+
+pub async fn get_login(State(state): State<AppState>, Query(params): Query<LoginParams>) -> Response {
+    let next = safe_redirect_target(params.next);
+    if state.authenticator.is_none() {
+        return Redirect::to(&next).into_response();
+    }
+    LoginTemplate { next, error: None }.into_html_response(StatusCode::OK)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LoginForm {
+    username: String,
+    password: String,
+    next: Option<String>,
+}
+
+pub async fn post_login(State(state): State<AppState>, jar: CookieJar, Form(input): Form<LoginForm>) -> Response {
+    let next = safe_redirect_target(input.next);
+    let Some(authenticator) = state.authenticator else {
+        return Redirect::to(&next).into_response();
+    };
+    let username = input.username.clone();
+    let verified = tokio::task::spawn_blocking({
+        let authenticator = authenticator.clone();
+        move || authenticator.verify_login(&input.username, &input.password)
+    }).await.unwrap_or(false);
+    if !verified {
+        let template = LoginTemplate { next, error: Some("Invalid username or password".to_owned()) };
+        return template.into_html_response(StatusCode::UNAUTHORIZED);
+    }
+    let session_id = authenticator.create_session(&username);
+    let jar = jar.add(authenticator.session_cookie(session_id));
+    (jar, Redirect::to(&next)).into_response()
+}
+
+pub async fn post_logout(State(state): State<AppState>, jar: CookieJar) -> Response {
+    if let (Some(authenticator), Some(cookie)) = (state.authenticator, jar.get(SESSION_COOKIE)) {
+        authenticator.delete_session(cookie.value());
+    }
+    let jar = jar.remove(Cookie::build(SESSION_COOKIE).path("/"));
+    (jar, Redirect::to("/login")).into_response()
+}
+
+/// Only allows redirects to local paths, so the login can't be abused as an open redirect.
+fn safe_redirect_target(next: Option<String>) -> String {
+    next.filter(|n| n.starts_with('/') && !n.starts_with("//") && !n.starts_with("/\\"))
+        .unwrap_or_else(|| "/".to_owned())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_safe_redirect_target() {
+        assert_eq!(safe_redirect_target(Some("/amiami?page=2".to_owned())), "/amiami?page=2");
+        assert_eq!(safe_redirect_target(Some("https://example.com".to_owned())), "/");
+        assert_eq!(safe_redirect_target(Some("//example.com".to_owned())), "/");
+        assert_eq!(safe_redirect_target(Some("/\\example.com".to_owned())), "/");
+        assert_eq!(safe_redirect_target(None), "/");
+    }
+}
@@ -0,0 +1,224 @@
+use crate::domain::availability::Availability;
+use crate::domain::booth::models::product::{GetProductsError, Product, Variation};
+use crate::domain::booth::models::source::{FollowSourceError, GetSourcesError, LinkMelonbooksArtistError, Source, SourceArgs, SourceKind, UnfollowSourceError};
+use crate::domain::booth::ports::BoothService;
+use crate::inbound::http::auth::AuthContext;
+use crate::inbound::http::handlers::api::{ApiError, ApiErrorBody, ApiJson, ApiPath, ApiQuery, PageParams, PageResponse};
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SourceResponse {
+    id: i32,
+    date_added: DateTime<Utc>,
+    name: String,
+    #[schema(value_type = String)]
+    kind: SourceKind,
+    url: String,
+    melonbooks_artist_id: Option<i32>,
+    following: bool,
+}
+
+impl From<Source> for SourceResponse {
+    fn from(s: Source) -> Self {
+        Self {
+            id: s.id(),
+            date_added: s.date_added(),
+            name: s.name().to_owned(),
+            kind: s.kind(),
+            url: s.url(),
+            melonbooks_artist_id: s.melonbooks_artist_id(),
+            following: s.following(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VariationResponse {
+    name: String,
+    price: i32,
+    #[schema(value_type = String)]
+    availability: Availability,
+}
+
+impl From<&Variation> for VariationResponse {
+    fn from(v: &Variation) -> Self {
+        Self {
+            name: v.name().to_owned(),
+            price: v.price(),
+            availability: v.availability(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProductResponse {
+    id: i32,
+    date_added: DateTime<Utc>,
+    url: String,
+    title: String,
+    shop_name: String,
+    image_url: String,
+    variations: Vec<VariationResponse>,
+    price: Option<i32>,
+    #[schema(value_type = String)]
+    availability: Availability,
+}
+
+impl From<Product> for ProductResponse {
+    fn from(p: Product) -> Self {
+        Self {
+            id: p.id(),
+            date_added: p.date_added(),
+            url: p.url().to_owned(),
+            title: p.title().to_owned(),
+            shop_name: p.shop_name().to_owned(),
+            image_url: p.image_url().to_owned(),
+            variations: p.variations().iter().map(|v| v.into()).collect(),
+            price: p.price(),
+            availability: p.availability(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SourceListParams {
+    pub following: Option<bool>,
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+#[utoipa::path(get, path = "/sources", tag = "booth", params(SourceListParams), responses(
+    (status = 200, description = "Known shops and tags", body = PageResponse<SourceResponse>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_sources(Extension(service): Extension<Arc<dyn BoothService>>, auth: AuthContext, ApiQuery(params): ApiQuery<SourceListParams>) -> Result<Json<PageResponse<SourceResponse>>, ApiError> {
+    let page = PageParams { page: params.page, page_size: params.page_size }.page_request();
+    let sources = service.get_sources_page(auth.user(), params.following, page).await?;
+    Ok(Json(sources.into()))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct FollowSourceRequest {
+    /// Subdomain or url of a shop, or name of a tag.
+    pub name: String,
+    #[schema(value_type = String)]
+    pub kind: SourceKind,
+}
+
+#[utoipa::path(post, path = "/sources", tag = "booth", request_body = FollowSourceRequest, responses(
+    (status = 204, description = "Shop or tag is followed"),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 409, description = "Shop or tag is already followed", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn follow_source(Extension(service): Extension<Arc<dyn BoothService>>, auth: AuthContext, ApiJson(body): ApiJson<FollowSourceRequest>) -> Result<StatusCode, ApiError> {
+    service.follow_source(auth.user(), &SourceArgs::new(body.name, body.kind)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(delete, path = "/sources/{source_id}", tag = "booth", params(("source_id" = i32, Path)), responses(
+    (status = 204, description = "Shop or tag is unfollowed"),
+    (status = 404, description = "Unknown shop or tag", body = ApiErrorBody),
+    (status = 409, description = "Shop or tag is not followed", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn unfollow_source(Extension(service): Extension<Arc<dyn BoothService>>, auth: AuthContext, ApiPath(source_id): ApiPath<i32>) -> Result<StatusCode, ApiError> {
+    service.unfollow_source(auth.user(), source_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct LinkMelonbooksArtistRequest {
+    /// `null` removes the link.
+    pub melonbooks_artist_id: Option<i32>,
+}
+
+#[utoipa::path(put, path = "/sources/{source_id}/melonbooks-artist", tag = "booth", params(("source_id" = i32, Path)), request_body = LinkMelonbooksArtistRequest, responses(
+    (status = 204, description = "Shop is linked to the melonbooks artist"),
+    (status = 400, description = "Invalid request, the source is a tag or the artist is unknown", body = ApiErrorBody),
+    (status = 404, description = "Unknown shop or tag", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn link_melonbooks_artist(Extension(service): Extension<Arc<dyn BoothService>>, ApiPath(source_id): ApiPath<i32>, ApiJson(body): ApiJson<LinkMelonbooksArtistRequest>) -> Result<StatusCode, ApiError> {
+    service.link_melonbooks_artist(source_id, body.melonbooks_artist_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(get, path = "/sources/{source_id}/products", tag = "booth", params(("source_id" = i32, Path), PageParams), responses(
+    (status = 200, description = "Items of the shop or tag", body = PageResponse<ProductResponse>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 404, description = "Unknown shop or tag", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_source_products(Extension(service): Extension<Arc<dyn BoothService>>, auth: AuthContext, ApiPath(source_id): ApiPath<i32>, ApiQuery(params): ApiQuery<PageParams>) -> Result<Json<PageResponse<ProductResponse>>, ApiError> {
+    let sources = service.get_sources(auth.user()).await?;
+    if !sources.iter().any(|s| s.id() == source_id) {
+        return Err(ApiError::not_found(format!("unknown shop or tag with id '{}'", source_id)));
+    }
+    let products = service.get_products_page_by_source(source_id, params.page_request()).await?;
+    Ok(Json(products.into()))
+}
+
+#[utoipa::path(get, path = "/products", tag = "booth", params(PageParams), responses(
+    (status = 200, description = "All items", body = PageResponse<ProductResponse>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_products(Extension(service): Extension<Arc<dyn BoothService>>, ApiQuery(params): ApiQuery<PageParams>) -> Result<Json<PageResponse<ProductResponse>>, ApiError> {
+    let products = service.get_products_page(params.page_request()).await?;
+    Ok(Json(products.into()))
+}
+
+impl From<FollowSourceError> for ApiError {
+    fn from(e: FollowSourceError) -> Self {
+        match e {
+            e @ FollowSourceError::AlreadyFollowedError { .. } => ApiError::conflict(e),
+            e @ FollowSourceError::EmptyName => ApiError::bad_request(e),
+            FollowSourceError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<UnfollowSourceError> for ApiError {
+    fn from(e: UnfollowSourceError) -> Self {
+        match e {
+            e @ UnfollowSourceError::UnknownSource { .. } => ApiError::not_found(e),
+            e @ UnfollowSourceError::SourceNotFollowed { .. } => ApiError::conflict(e),
+            UnfollowSourceError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<LinkMelonbooksArtistError> for ApiError {
+    fn from(e: LinkMelonbooksArtistError) -> Self {
+        match e {
+            e @ LinkMelonbooksArtistError::UnknownSource { .. } => ApiError::not_found(e),
+            e @ LinkMelonbooksArtistError::NotAShop { .. } => ApiError::bad_request(e),
+            e @ LinkMelonbooksArtistError::UnknownArtist { .. } => ApiError::bad_request(e),
+            LinkMelonbooksArtistError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<GetSourcesError> for ApiError {
+    fn from(e: GetSourcesError) -> Self {
+        match e {
+            GetSourcesError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<GetProductsError> for ApiError {
+    fn from(e: GetProductsError) -> Self {
+        match e {
+            GetProductsError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use crate::domain::booth::models::product::{GetProductsError, Product, ProductHistoryEntry};
+use crate::domain::booth::models::source::{FollowSourceError, GetSourcesError, LinkMelonbooksArtistError, Source, SourceArgs, SourceKind, UnfollowSourceError};
+use crate::domain::booth::ports::BoothService;
+use crate::domain::booth::SITE;
+use crate::domain::duplicate::models::listing::Listing;
+use crate::domain::melonbooks::models::artist::Artist;
+use crate::domain::melonbooks::ports::MelonbooksService;
+use crate::domain::melonbooks::models::product::Product as MelonbooksProduct;
+use crate::domain::product_history::ProductChange;
+use crate::domain::search::{FieldHighlight, HighlightedText};
+use crate::inbound::http::auth::AuthContext;
+use crate::inbound::http::handlers::{target_products_page, Pagination};
+use crate::inbound::http::AppState;
+use askama::Template;
+use askama_axum::{IntoResponse, Response};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::{Extension, Form};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_with::{serde_as, NoneAsEmptyString};
+use std::sync::Arc;
+use strum::IntoEnumIterator;
+
+#[derive(Template)]
+#[template(path = "booth.html")]
+struct BoothTemplate {
+    auth: AuthContext,
+    products: Vec<Product>,
+    sources: Vec<Source>,
+    selected_source: Option<Source>,
+    kinds: Vec<SourceKind>,
+    melonbooks_artists: Vec<Artist>,
+    linked_artist: Option<Artist>,
+    melonbooks_products: Vec<MelonbooksProduct>,
+    pagination: Pagination,
+}
+
+impl BoothTemplate {
+    fn format_date(date: DateTime<Utc>) -> String {
+        date.format("%Y-%m-%d %H:%M").to_string()
+    }
+
+    fn highlight(&self, _product_id: i32, _field: &str) -> Option<&HighlightedText> {
+        None
+    }
+
+    fn matched_fields(&self, _product_id: i32) -> Vec<&FieldHighlight> {
+        Vec::new()
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OverviewParams {
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    pub selected_source: Option<i32>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    pub page: Option<u32>,
+}
+
+pub async fn get_overview(Extension(service): Extension<Arc<dyn BoothService>>, Extension(melonbooks): Extension<Arc<dyn MelonbooksService>>, auth: AuthContext, Query(params): Query<OverviewParams>) -> Response {
+    get_overview_response(service, melonbooks, auth, params).await
+}
+
+#[derive(Template)]
+#[template(path = "booth-product.html")]
+struct BoothProductTemplate {
+    auth: AuthContext,
+    product: Product,
+    history: Vec<ProductHistoryEntry>,
+    listings: Vec<Listing>,
+}
+
+impl BoothProductTemplate {
+    fn format_date(date: DateTime<Utc>) -> String {
+        BoothTemplate::format_date(date)
+    }
+
+    fn format_price(&self, price: &Option<i32>) -> String {
+        price.map(|p| format!("¥{}", p)).unwrap_or_else(|| "-".to_owned())
+    }
+}
+
+pub async fn get_product(State(state): State<AppState>, Extension(service): Extension<Arc<dyn BoothService>>, auth: AuthContext, Path(product_id): Path<i32>) -> Response {
+    let product = match service.get_product(product_id).await {
+        Ok(p) => p,
+        Err(e) => return e.into_response()
+    };
+    let history = match service.get_product_history(product_id).await {
+        Ok(h) => h,
+        Err(e) => return e.into_response()
+    };
+    let listings = match state.duplicate_service.get_duplicate_listings(SITE, product_id, product.image_phash()).await {
+        Ok(l) => l,
+        Err(e) => return e.into_response()
+    };
+    BoothProductTemplate { auth, product, history, listings }.into_response()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PostSourceForm {
+    name: String,
+    kind: SourceKind,
+}
+
+pub async fn post_source(Extension(service): Extension<Arc<dyn BoothService>>, Extension(melonbooks): Extension<Arc<dyn MelonbooksService>>, auth: AuthContext, Form(input): Form<PostSourceForm>) -> Response {
+    if let Err(e) = service.follow_source(auth.user(), &SourceArgs::new(input.name, input.kind)).await {
+        return e.into_response();
+    }
+    get_overview_response(service, melonbooks, auth, OverviewParams::default()).await
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DeleteSourceForm {
+    selected_source_id: i32
+}
+
+pub async fn delete_source(Extension(service): Extension<Arc<dyn BoothService>>, Extension(melonbooks): Extension<Arc<dyn MelonbooksService>>, auth: AuthContext, Form(input): Form<DeleteSourceForm>) -> Response {
+    if let Err(e) = service.unfollow_source(auth.user(), input.selected_source_id).await {
+        return e.into_response();
+    }
+    get_overview_response(service, melonbooks, auth, OverviewParams::default()).await
+}
+
+#[serde_as]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct LinkSourceForm {
+    selected_source_id: i32,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    melonbooks_artist_id: Option<i32>,
+}
+
+pub async fn post_source_link(Extension(service): Extension<Arc<dyn BoothService>>, Extension(melonbooks): Extension<Arc<dyn MelonbooksService>>, auth: AuthContext, Form(input): Form<LinkSourceForm>) -> Response {
+    if let Err(e) = service.link_melonbooks_artist(input.selected_source_id, input.melonbooks_artist_id).await {
+        return e.into_response();
+    }
+    get_overview_response(service, melonbooks, auth, OverviewParams { selected_source: Some(input.selected_source_id), page: None }).await
+}
+
+async fn get_overview_response(service: Arc<dyn BoothService>, melonbooks: Arc<dyn MelonbooksService>, auth: AuthContext, params: OverviewParams) -> Response {
+    let sources = match service.get_followed_sources(auth.user()).await {
+        Ok(s) => s,
+        Err(e) => return e.into_response()
+    };
+    let selected_source = match params.selected_source {
+        Some(id) => sources.iter().find(|s| s.id() == id).cloned(),
+        None => None
+    };
+    let followed_ids = sources.iter().map(|s| s.id()).collect();
+    let selected = selected_source.as_ref().map(|s| ("selected_source", s.id()));
+    let page = target_products_page("/booth", followed_ids, selected, params.page, |ids, page| async move {
+        service.get_products_page_by_sources(&ids, page).await
+    }).await;
+    let (products, pagination) = match page {
+        Ok(p) => p,
+        Err(e) => return e.into_response()
+    };
+    let melonbooks_artists = match melonbooks.get_artists(auth.user()).await {
+        Ok(a) => a,
+        Err(e) => return e.into_response()
+    };
+    let linked_artist = selected_source.as_ref()
+        .and_then(|s| s.melonbooks_artist_id())
+        .and_then(|id| melonbooks_artists.iter().find(|a| a.id() == id).cloned());
+    let melonbooks_products = match &linked_artist {
+        Some(artist) => match melonbooks.get_products_by_artist(artist.id()).await {
+            Ok(mut p) => {
+                p.sort_by_key(|p| std::cmp::Reverse(p.date_added()));
+                p
+            }
+            Err(e) => return e.into_response()
+        },
+        None => Vec::new(),
+    };
+    let template = BoothTemplate {
+        auth,
+        products,
+        sources,
+        selected_source,
+        kinds: SourceKind::iter().collect(),
+        melonbooks_artists: melonbooks_artists.into_iter().filter(|a| a.following()).collect(),
+        linked_artist,
+        melonbooks_products,
+        pagination,
+    };
+    template.into_response()
+}
+
+impl IntoResponse for GetProductsError {
+    fn into_response(self) -> Response {
+        match self {
+            GetProductsError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for GetSourcesError {
+    fn into_response(self) -> Response {
+        match self {
+            GetSourcesError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for FollowSourceError {
+    fn into_response(self) -> Response {
+        match self {
+            e @ FollowSourceError::AlreadyFollowedError { .. } => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            e @ FollowSourceError::EmptyName => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            FollowSourceError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for UnfollowSourceError {
+    fn into_response(self) -> Response {
+        match self {
+            e @ UnfollowSourceError::UnknownSource { .. } => (StatusCode::NOT_FOUND, e.to_string()).into_response(),
+            e @ UnfollowSourceError::SourceNotFollowed { .. } => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            UnfollowSourceError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for LinkMelonbooksArtistError {
+    fn into_response(self) -> Response {
+        match self {
+            e @ LinkMelonbooksArtistError::UnknownSource { .. } => (StatusCode::NOT_FOUND, e.to_string()).into_response(),
+            e @ LinkMelonbooksArtistError::NotAShop { .. } => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            e @ LinkMelonbooksArtistError::UnknownArtist { .. } => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            LinkMelonbooksArtistError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use crate::domain::digital::models::circle::{Circle, CircleArgs, FollowCircleError, GetCirclesError, Store, UnfollowCircleError};
+use crate::domain::digital::models::product::{GetProductsError, Product, Sale};
+use crate::domain::digital::ports::DigitalService;
+use crate::inbound::http::auth::AuthContext;
+use crate::inbound::http::handlers::api::{ApiError, ApiErrorBody, ApiJson, ApiPath, ApiQuery, PageParams, PageResponse};
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CircleResponse {
+    id: i32,
+    date_added: DateTime<Utc>,
+    #[schema(value_type = String)]
+    store: Store,
+    code: String,
+    name: String,
+    url: String,
+    following: bool,
+}
+
+impl From<Circle> for CircleResponse {
+    fn from(c: Circle) -> Self {
+        Self {
+            id: c.id(),
+            date_added: c.date_added(),
+            store: c.store(),
+            code: c.code().to_owned(),
+            name: c.name().to_owned(),
+            url: c.url(),
+            following: c.following(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SaleResponse {
+    price: i32,
+    /// In the store's local time, Japan Standard Time.
+    end: Option<NaiveDateTime>,
+}
+
+impl From<&Sale> for SaleResponse {
+    fn from(s: &Sale) -> Self {
+        Self {
+            price: s.price(),
+            end: s.end(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProductResponse {
+    id: i32,
+    date_added: DateTime<Utc>,
+    circle_id: i32,
+    #[schema(value_type = String)]
+    store: Store,
+    url: String,
+    title: String,
+    circle_name: String,
+    image_url: String,
+    list_price: i32,
+    sale: Option<SaleResponse>,
+    date_discounted: Option<DateTime<Utc>>,
+}
+
+impl From<Product> for ProductResponse {
+    fn from(p: Product) -> Self {
+        Self {
+            id: p.id(),
+            date_added: p.date_added(),
+            circle_id: p.circle_id(),
+            store: p.store(),
+            url: p.url().to_owned(),
+            title: p.title().to_owned(),
+            circle_name: p.circle_name().to_owned(),
+            image_url: p.image_url().to_owned(),
+            list_price: p.list_price(),
+            sale: p.sale().map(|s| s.into()),
+            date_discounted: p.date_discounted(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CircleListParams {
+    pub following: Option<bool>,
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+#[utoipa::path(get, path = "/circles", tag = "digital", params(CircleListParams), responses(
+    (status = 200, description = "Known circles", body = PageResponse<CircleResponse>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_circles(Extension(service): Extension<Arc<dyn DigitalService>>, auth: AuthContext, ApiQuery(params): ApiQuery<CircleListParams>) -> Result<Json<PageResponse<CircleResponse>>, ApiError> {
+    let page = PageParams { page: params.page, page_size: params.page_size }.page_request();
+    let circles = service.get_circles_page(auth.user(), params.following, page).await?;
+    Ok(Json(circles.into()))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct FollowCircleRequest {
+    #[schema(value_type = String)]
+    pub store: Store,
+    /// Id or url of the circle in the store.
+    pub code: String,
+}
+
+#[utoipa::path(post, path = "/circles", tag = "digital", request_body = FollowCircleRequest, responses(
+    (status = 204, description = "Circle is followed"),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 409, description = "Circle is already followed", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn follow_circle(Extension(service): Extension<Arc<dyn DigitalService>>, auth: AuthContext, ApiJson(body): ApiJson<FollowCircleRequest>) -> Result<StatusCode, ApiError> {
+    service.follow_circle(auth.user(), &CircleArgs::new(body.store, body.code)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(delete, path = "/circles/{circle_id}", tag = "digital", params(("circle_id" = i32, Path)), responses(
+    (status = 204, description = "Circle is unfollowed"),
+    (status = 404, description = "Unknown circle", body = ApiErrorBody),
+    (status = 409, description = "Circle is not followed", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn unfollow_circle(Extension(service): Extension<Arc<dyn DigitalService>>, auth: AuthContext, ApiPath(circle_id): ApiPath<i32>) -> Result<StatusCode, ApiError> {
+    service.unfollow_circle(auth.user(), circle_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(get, path = "/circles/{circle_id}/products", tag = "digital", params(("circle_id" = i32, Path), PageParams), responses(
+    (status = 200, description = "Works of the circle", body = PageResponse<ProductResponse>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 404, description = "Unknown circle", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_circle_products(Extension(service): Extension<Arc<dyn DigitalService>>, auth: AuthContext, ApiPath(circle_id): ApiPath<i32>, ApiQuery(params): ApiQuery<PageParams>) -> Result<Json<PageResponse<ProductResponse>>, ApiError> {
+    let circles = service.get_circles(auth.user()).await?;
+    if !circles.iter().any(|c| c.id() == circle_id) {
+        return Err(ApiError::not_found(format!("unknown circle with id '{}'", circle_id)));
+    }
+    let products = service.get_products_page_by_circle(circle_id, params.page_request()).await?;
+    Ok(Json(products.into()))
+}
+
+#[utoipa::path(get, path = "/products", tag = "digital", params(PageParams), responses(
+    (status = 200, description = "All works", body = PageResponse<ProductResponse>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_products(Extension(service): Extension<Arc<dyn DigitalService>>, ApiQuery(params): ApiQuery<PageParams>) -> Result<Json<PageResponse<ProductResponse>>, ApiError> {
+    let products = service.get_products_page(params.page_request()).await?;
+    Ok(Json(products.into()))
+}
+
+impl From<FollowCircleError> for ApiError {
+    fn from(e: FollowCircleError) -> Self {
+        match e {
+            e @ FollowCircleError::AlreadyFollowedError { .. } => ApiError::conflict(e),
+            e @ FollowCircleError::EmptyCode => ApiError::bad_request(e),
+            FollowCircleError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<UnfollowCircleError> for ApiError {
+    fn from(e: UnfollowCircleError) -> Self {
+        match e {
+            e @ UnfollowCircleError::UnknownCircle { .. } => ApiError::not_found(e),
+            e @ UnfollowCircleError::CircleNotFollowed { .. } => ApiError::conflict(e),
+            UnfollowCircleError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<GetCirclesError> for ApiError {
+    fn from(e: GetCirclesError) -> Self {
+        match e {
+            GetCirclesError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<GetProductsError> for ApiError {
+    fn from(e: GetProductsError) -> Self {
+        match e {
+            GetProductsError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use crate::domain::digital::models::circle::{Circle, CircleArgs, FollowCircleError, GetCirclesError, Store, UnfollowCircleError};
+use crate::domain::digital::models::product::{GetProductsError, Product, ProductHistoryEntry, Sale};
+use crate::domain::digital::ports::DigitalService;
+use crate::domain::digital::SITE;
+use crate::domain::duplicate::models::listing::Listing;
+use crate::domain::product_history::ProductChange;
+use crate::inbound::http::auth::AuthContext;
+use crate::inbound::http::handlers::{target_products_page, Pagination};
+use crate::inbound::http::AppState;
+use askama::Template;
+use askama_axum::{IntoResponse, Response};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::{Extension, Form};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Deserialize;
+use serde_with::{serde_as, NoneAsEmptyString};
+use std::sync::Arc;
+use strum::IntoEnumIterator;
+
+#[derive(Template)]
+#[template(path = "digital.html")]
+struct DigitalTemplate {
+    auth: AuthContext,
+    products: Vec<Product>,
+    circles: Vec<Circle>,
+    selected_circle: Option<Circle>,
+    stores: Vec<Store>,
+    pagination: Pagination,
+}
+
+impl DigitalTemplate {
+    fn format_date(date: DateTime<Utc>) -> String {
+        date.format("%Y-%m-%d %H:%M").to_string()
+    }
+
+    /// Sale ends are shown as the store announces them, in Japan Standard Time.
+    fn format_sale_end(end: NaiveDateTime) -> String {
+        format!("{} JST", end.format("%Y-%m-%d %H:%M"))
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OverviewParams {
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    pub selected_circle: Option<i32>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    pub page: Option<u32>,
+}
+
+pub async fn get_overview(Extension(service): Extension<Arc<dyn DigitalService>>, auth: AuthContext, Query(params): Query<OverviewParams>) -> Response {
+    get_overview_response(service, auth, params).await
+}
+
+#[derive(Template)]
+#[template(path = "digital-product.html")]
+struct DigitalProductTemplate {
+    auth: AuthContext,
+    product: Product,
+    history: Vec<ProductHistoryEntry>,
+    listings: Vec<Listing>,
+}
+
+impl DigitalProductTemplate {
+    fn format_date(date: DateTime<Utc>) -> String {
+        DigitalTemplate::format_date(date)
+    }
+
+    fn format_sale_end(end: NaiveDateTime) -> String {
+        DigitalTemplate::format_sale_end(end)
+    }
+
+    fn format_sale(&self, sale: &Option<Sale>) -> String {
+        match sale {
+            Some(sale) => match sale.end() {
+                Some(end) => format!("¥{} until {}", sale.price(), Self::format_sale_end(end)),
+                None => format!("¥{}", sale.price()),
+            },
+            None => "ended".to_owned(),
+        }
+    }
+}
+
+pub async fn get_product(State(state): State<AppState>, Extension(service): Extension<Arc<dyn DigitalService>>, auth: AuthContext, Path(product_id): Path<i32>) -> Response {
+    let product = match service.get_product(product_id).await {
+        Ok(p) => p,
+        Err(e) => return e.into_response()
+    };
+    let history = match service.get_product_history(product_id).await {
+        Ok(h) => h,
+        Err(e) => return e.into_response()
+    };
+    let listings = match state.duplicate_service.get_duplicate_listings(SITE, product_id, product.image_phash()).await {
+        Ok(l) => l,
+        Err(e) => return e.into_response()
+    };
+    DigitalProductTemplate { auth, product, history, listings }.into_response()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PostCircleForm {
+    store: Store,
+    code: String,
+}
+
+pub async fn post_circle(Extension(service): Extension<Arc<dyn DigitalService>>, auth: AuthContext, Form(input): Form<PostCircleForm>) -> Response {
+    if let Err(e) = service.follow_circle(auth.user(), &CircleArgs::new(input.store, input.code)).await {
+        return e.into_response();
+    }
+    get_overview_response(service, auth, OverviewParams::default()).await
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DeleteCircleForm {
+    selected_circle_id: i32
+}
+
+pub async fn delete_circle(Extension(service): Extension<Arc<dyn DigitalService>>, auth: AuthContext, Form(input): Form<DeleteCircleForm>) -> Response {
+    if let Err(e) = service.unfollow_circle(auth.user(), input.selected_circle_id).await {
+        return e.into_response();
+    }
+    get_overview_response(service, auth, OverviewParams::default()).await
+}
+
+async fn get_overview_response(service: Arc<dyn DigitalService>, auth: AuthContext, params: OverviewParams) -> Response {
+    let circles = match service.get_followed_circles(auth.user()).await {
+        Ok(c) => c,
+        Err(e) => return e.into_response()
+    };
+    let selected_circle = match params.selected_circle {
+        Some(id) => circles.iter().find(|c| c.id() == id).cloned(),
+        None => None
+    };
+    let followed_ids = circles.iter().map(|c| c.id()).collect();
+    let selected = selected_circle.as_ref().map(|c| ("selected_circle", c.id()));
+    let page = target_products_page("/digital", followed_ids, selected, params.page, |ids, page| async move {
+        service.get_products_page_by_circles(&ids, page).await
+    }).await;
+    let (products, pagination) = match page {
+        Ok(p) => p,
+        Err(e) => return e.into_response()
+    };
+    let template = DigitalTemplate {
+        auth,
+        products,
+        circles,
+        selected_circle,
+        stores: Store::iter().collect(),
+        pagination,
+    };
+    template.into_response()
+}
+
+impl IntoResponse for GetProductsError {
+    fn into_response(self) -> Response {
+        match self {
+            GetProductsError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for GetCirclesError {
+    fn into_response(self) -> Response {
+        match self {
+            GetCirclesError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for FollowCircleError {
+    fn into_response(self) -> Response {
+        match self {
+            e @ FollowCircleError::AlreadyFollowedError { .. } => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            e @ FollowCircleError::EmptyCode => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            FollowCircleError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for UnfollowCircleError {
+    fn into_response(self) -> Response {
+        match self {
+            e @ UnfollowCircleError::UnknownCircle { .. } => (StatusCode::NOT_FOUND, e.to_string()).into_response(),
+            e @ UnfollowCircleError::CircleNotFollowed { .. } => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            UnfollowCircleError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
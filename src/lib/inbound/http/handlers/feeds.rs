@@ -0,0 +1,293 @@
+use askama::Template;
+use askama_axum::{IntoResponse, Response};
+use axum::http::{header, HeaderMap, StatusCode};
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// Number of latest products in a feed.
+pub const FEED_SIZE: u32 = 50;
+
+const HTTP_DATE_FORMAT: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedFormat {
+    Atom,
+    Rss,
+}
+
+impl FeedFormat {
+    /// Format by the extension of the requested path, `feed.atom` or `feed.rss`.
+    pub fn from_path(path: &str) -> Self {
+        match path.ends_with(".rss") {
+            true => FeedFormat::Rss,
+            false => FeedFormat::Atom,
+        }
+    }
+
+    fn content_type(&self) -> &'static str {
+        match self {
+            FeedFormat::Atom => "application/atom+xml; charset=utf-8",
+            FeedFormat::Rss => "application/rss+xml; charset=utf-8",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Feed {
+    id: String,
+    title: String,
+    link: String,
+    entries: Vec<FeedEntry>,
+}
+
+impl Feed {
+    pub fn new(id: String, title: String, link: String, entries: Vec<FeedEntry>) -> Self {
+        Self { id, title, link, entries }
+    }
+
+    pub fn id(&self) -> &str { &self.id }
+    pub fn title(&self) -> &str { &self.title }
+    pub fn link(&self) -> &str { &self.link }
+    pub fn entries(&self) -> &[FeedEntry] { &self.entries }
+
+    /// Changes with the entries, also when only the price or availability of one changed.
+    fn etag(&self, format: FeedFormat) -> String {
+        let mut hasher = DefaultHasher::new();
+        (format.content_type(), &self.id, &self.title, &self.link).hash(&mut hasher);
+        for entry in &self.entries {
+            (entry.id(), &entry.price, &entry.availability, entry.last_updated()).hash(&mut hasher);
+        }
+        format!("\"{:016x}\"", hasher.finish())
+    }
+
+    /// Latest change of the entries, the epoch for an empty feed so it stays cacheable.
+    pub fn updated(&self) -> DateTime<Utc> {
+        self.entries.iter()
+            .map(|e| e.last_updated())
+            .max()
+            .unwrap_or(DateTime::UNIX_EPOCH)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedEntry {
+    product_id: String,
+    title: String,
+    link: String,
+    image_url: String,
+    price: Option<String>,
+    availability: String,
+    restocked: bool,
+    updated: DateTime<Utc>,
+    date_updated: Option<DateTime<Utc>>,
+}
+
+impl FeedEntry {
+    /// `product_id` is unique per site, e.g. `melonbooks:12`.
+    pub fn new(product_id: String, title: String, link: String, image_url: String, availability: String, date_added: DateTime<Utc>) -> Self {
+        Self { product_id, title, link, image_url, price: None, availability, restocked: false, updated: date_added, date_updated: None }
+    }
+
+    pub fn with_price(mut self, price: Option<String>) -> Self {
+        self.price = price;
+        self
+    }
+
+    pub fn with_date_restocked(mut self, date_restocked: Option<DateTime<Utc>>) -> Self {
+        if let Some(date_restocked) = date_restocked {
+            self.restocked = true;
+            self.updated = date_restocked;
+        }
+        self
+    }
+
+    /// The latest availability or price change, it does not change the id.
+    pub fn with_date_updated(mut self, date_updated: Option<DateTime<Utc>>) -> Self {
+        self.date_updated = date_updated;
+        self
+    }
+
+    /// Restocks get a new id, so feed readers show the product again.
+    pub fn id(&self) -> String {
+        format!("urn:moe-scraper:{}:{}", self.product_id, self.updated.timestamp())
+    }
+
+    pub fn title(&self) -> String {
+        match self.restocked {
+            true => format!("Restocked: {}", self.title),
+            false => self.title.clone(),
+        }
+    }
+
+    pub fn link(&self) -> &str { &self.link }
+    pub fn image_url(&self) -> &str { &self.image_url }
+    pub fn price(&self) -> Option<&str> { self.price.as_deref() }
+    pub fn availability(&self) -> &str { &self.availability }
+    pub fn updated(&self) -> DateTime<Utc> { self.updated }
+
+    /// When the entry was added, restocked or its availability or price changed.
+    pub fn last_updated(&self) -> DateTime<Utc> {
+        self.date_updated.map_or(self.updated, |d| d.max(self.updated))
+    }
+
+    fn content(&self) -> String {
+        FeedEntryContentTemplate { entry: self }.render().unwrap_or_default()
+    }
+}
+
+#[derive(Template)]
+#[template(path = "feed-atom.xml")]
+struct AtomTemplate<'a> {
+    feed: &'a Feed,
+}
+
+#[derive(Template)]
+#[template(path = "feed-rss.xml")]
+struct RssTemplate<'a> {
+    feed: &'a Feed,
+}
+
+#[derive(Template)]
+#[template(path = "feed-entry-content.html")]
+struct FeedEntryContentTemplate<'a> {
+    entry: &'a FeedEntry,
+}
+
+impl AtomTemplate<'_> {
+    fn format_date(date: DateTime<Utc>) -> String {
+        date.to_rfc3339_opts(SecondsFormat::Secs, true)
+    }
+}
+
+impl RssTemplate<'_> {
+    fn format_date(date: DateTime<Utc>) -> String {
+        date.to_rfc2822()
+    }
+}
+
+/// Answers `304 Not Modified` when the `If-None-Match` or `If-Modified-Since` header is still current,
+/// the ETag and Last-Modified come from the entries so unchanged feeds are not rendered.
+pub fn feed_response(feed: &Feed, format: FeedFormat, headers: &HeaderMap) -> Response {
+    let etag = feed.etag(format);
+    let cache_headers = [(header::ETAG, etag.clone()), (header::LAST_MODIFIED, feed.updated().format(HTTP_DATE_FORMAT).to_string())];
+    if not_modified(headers, &etag, feed.updated()) {
+        return (StatusCode::NOT_MODIFIED, cache_headers).into_response();
+    }
+    let body = match format {
+        FeedFormat::Atom => AtomTemplate { feed }.render(),
+        FeedFormat::Rss => RssTemplate { feed }.render(),
+    };
+    match body {
+        Ok(body) => ([(header::CONTENT_TYPE, format.content_type())], cache_headers, body).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// `If-None-Match` wins over `If-Modified-Since` when a request has both.
+fn not_modified(headers: &HeaderMap, etag: &str, updated: DateTime<Utc>) -> bool {
+    if let Some(if_none_match) = headers.get(header::IF_NONE_MATCH).and_then(|h| h.to_str().ok()) {
+        return if_none_match.split(',').map(|t| t.trim().trim_start_matches("W/")).any(|t| t == etag || t == "*");
+    }
+    headers.get(header::IF_MODIFIED_SINCE)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| DateTime::parse_from_rfc2822(h).ok())
+        .is_some_and(|since| updated.timestamp() <= since.timestamp())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn feed() -> Feed {
+        let date_added = DateTime::parse_from_rfc3339("2026-10-01T10:00:00Z").unwrap().to_utc();
+        let date_restocked = DateTime::parse_from_rfc3339("2026-10-02T12:30:00Z").unwrap().to_utc();
+        Feed::new(
+            "urn:moe-scraper:melonbooks".to_owned(),
+            "Melonbooks".to_owned(),
+            "https://www.melonbooks.co.jp/".to_owned(),
+            vec![
+                FeedEntry::new("melonbooks:1".to_owned(), "Book & <Title>".to_owned(), "https://www.melonbooks.co.jp/detail/detail.php?product_id=1".to_owned(),
+                    "https://melonbooks.akamaized.net/1.jpg".to_owned(), "Available".to_owned(), date_added)
+                    .with_price(Some("¥1,000".to_owned()))
+                    .with_date_restocked(Some(date_restocked)),
+                FeedEntry::new("melonbooks:2".to_owned(), "Other".to_owned(), "https://www.melonbooks.co.jp/detail/detail.php?product_id=2".to_owned(),
+                    "https://melonbooks.akamaized.net/2.jpg".to_owned(), "NotAvailable".to_owned(), date_added),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_atom_feed() {
+        let atom = AtomTemplate { feed: &feed() }.render().unwrap();
+        assert!(atom.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\">"));
+        assert!(atom.contains("<updated>2026-10-02T12:30:00Z</updated>"));
+        assert!(atom.contains("<id>urn:moe-scraper:melonbooks:1:1790944200</id>"));
+        assert!(atom.contains("<title>Restocked: Book &amp; &lt;Title&gt;</title>"));
+        assert!(atom.contains("&lt;img src=&quot;https://melonbooks.akamaized.net/1.jpg&quot;"));
+        assert!(atom.contains("Price: ¥1,000&lt;/p&gt;</content>"));
+    }
+
+    #[test]
+    fn test_rss_feed() {
+        let rss = RssTemplate { feed: &feed() }.render().unwrap();
+        assert!(rss.contains("<rss version=\"2.0\">"));
+        assert!(rss.contains("<lastBuildDate>Fri, 2 Oct 2026 12:30:00 +0000</lastBuildDate>"));
+        assert!(rss.contains("<guid isPermaLink=\"false\">urn:moe-scraper:melonbooks:2:1790848800</guid>"));
+        assert!(rss.contains("<title>Other</title>"));
+    }
+
+    #[tokio::test]
+    async fn test_feed_response_etag() {
+        let response = feed_response(&feed(), FeedFormat::Rss, &HeaderMap::new());
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get(header::CONTENT_TYPE).unwrap(), "application/rss+xml; charset=utf-8");
+        let etag = response.headers().get(header::ETAG).unwrap().clone();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, etag.clone());
+        let response = feed_response(&feed(), FeedFormat::Rss, &headers);
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers().get(header::ETAG).unwrap(), &etag);
+
+        let response = feed_response(&feed(), FeedFormat::Atom, &headers);
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_feed_response_etag_changes_with_availability() {
+        let etag = |feed: &Feed| feed_response(feed, FeedFormat::Atom, &HeaderMap::new()).headers().get(header::ETAG).unwrap().clone();
+        let mut changed = feed();
+        changed.entries[1].availability = "Available".to_owned();
+        assert_ne!(etag(&changed), etag(&feed()));
+        let mut changed = feed();
+        changed.entries[1].price = Some("¥900".to_owned());
+        assert_ne!(etag(&changed), etag(&feed()));
+    }
+
+    #[test]
+    fn test_feed_updated_by_latest_change() {
+        let date_updated = DateTime::parse_from_rfc3339("2026-10-03T08:00:00Z").unwrap().to_utc();
+        let mut feed = feed();
+        let entry = feed.entries.remove(1).with_date_updated(Some(date_updated));
+        assert_eq!(entry.id(), "urn:moe-scraper:melonbooks:2:1790848800");
+        feed.entries.push(entry);
+        assert_eq!(feed.updated(), date_updated);
+        let atom = AtomTemplate { feed: &feed }.render().unwrap();
+        assert!(atom.contains("<updated>2026-10-03T08:00:00Z</updated>"));
+    }
+
+    #[tokio::test]
+    async fn test_feed_response_last_modified() {
+        let response = feed_response(&feed(), FeedFormat::Atom, &HeaderMap::new());
+        assert_eq!(response.headers().get(header::LAST_MODIFIED).unwrap(), "Fri, 02 Oct 2026 12:30:00 GMT");
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_MODIFIED_SINCE, "Fri, 02 Oct 2026 12:30:00 GMT".parse().unwrap());
+        assert_eq!(feed_response(&feed(), FeedFormat::Atom, &headers).status(), StatusCode::NOT_MODIFIED);
+        headers.insert(header::IF_MODIFIED_SINCE, "Fri, 02 Oct 2026 12:29:59 GMT".parse().unwrap());
+        assert_eq!(feed_response(&feed(), FeedFormat::Atom, &headers).status(), StatusCode::OK);
+        headers.insert(header::IF_NONE_MATCH, "\"other\"".parse().unwrap());
+        headers.insert(header::IF_MODIFIED_SINCE, "Fri, 02 Oct 2026 12:30:00 GMT".parse().unwrap());
+        assert_eq!(feed_response(&feed(), FeedFormat::Atom, &headers).status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,205 @@
+use crate::domain::figure::models::product::{GetProductsError, PreorderWindow, Product};
+use crate::domain::figure::models::source::{FollowSourceError, GetSourcesError, Source, SourceArgs, SourceKind, Store, UnfollowSourceError};
+use crate::domain::figure::ports::FigureService;
+use crate::inbound::http::auth::AuthContext;
+use crate::inbound::http::handlers::api::{ApiError, ApiErrorBody, ApiJson, ApiPath, ApiQuery, PageParams, PageResponse};
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SourceResponse {
+    id: i32,
+    date_added: DateTime<Utc>,
+    #[schema(value_type = String)]
+    store: Store,
+    #[schema(value_type = String)]
+    kind: SourceKind,
+    code: String,
+    name: String,
+    url: String,
+    following: bool,
+}
+
+impl From<Source> for SourceResponse {
+    fn from(s: Source) -> Self {
+        Self {
+            id: s.id(),
+            date_added: s.date_added(),
+            store: s.store(),
+            kind: s.kind(),
+            code: s.code().to_owned(),
+            name: s.name().to_owned(),
+            url: s.url(),
+            following: s.following(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PreorderResponse {
+    /// In the store's local time, Japan Standard Time.
+    start: Option<NaiveDateTime>,
+    /// In the store's local time, Japan Standard Time.
+    end: Option<NaiveDateTime>,
+}
+
+impl From<&PreorderWindow> for PreorderResponse {
+    fn from(p: &PreorderWindow) -> Self {
+        Self {
+            start: p.start(),
+            end: p.end(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProductResponse {
+    id: i32,
+    date_added: DateTime<Utc>,
+    #[schema(value_type = String)]
+    store: Store,
+    url: String,
+    title: String,
+    maker_name: String,
+    image_url: String,
+    price: Option<i32>,
+    release_date: Option<String>,
+    preorder: Option<PreorderResponse>,
+    date_preorder_opened: Option<DateTime<Utc>>,
+    date_preorder_closing: Option<DateTime<Utc>>,
+}
+
+impl From<Product> for ProductResponse {
+    fn from(p: Product) -> Self {
+        Self {
+            id: p.id(),
+            date_added: p.date_added(),
+            store: p.store(),
+            url: p.url().to_owned(),
+            title: p.title().to_owned(),
+            maker_name: p.maker_name().to_owned(),
+            image_url: p.image_url().to_owned(),
+            price: p.price(),
+            release_date: p.release_date().map(|r| r.to_owned()),
+            preorder: p.preorder().map(|w| w.into()),
+            date_preorder_opened: p.date_preorder_opened(),
+            date_preorder_closing: p.date_preorder_closing(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SourceListParams {
+    pub following: Option<bool>,
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+#[utoipa::path(get, path = "/sources", tag = "figure", params(SourceListParams), responses(
+    (status = 200, description = "Known makers and series", body = PageResponse<SourceResponse>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_sources(Extension(service): Extension<Arc<dyn FigureService>>, auth: AuthContext, ApiQuery(params): ApiQuery<SourceListParams>) -> Result<Json<PageResponse<SourceResponse>>, ApiError> {
+    let page = PageParams { page: params.page, page_size: params.page_size }.page_request();
+    let sources = service.get_sources_page(auth.user(), params.following, page).await?;
+    Ok(Json(sources.into()))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct FollowSourceRequest {
+    #[schema(value_type = String)]
+    pub store: Store,
+    #[schema(value_type = String)]
+    pub kind: SourceKind,
+    /// Id of the maker or series in the store's search, or the url of the search.
+    pub code: String,
+}
+
+#[utoipa::path(post, path = "/sources", tag = "figure", request_body = FollowSourceRequest, responses(
+    (status = 204, description = "Maker or series is followed"),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 409, description = "Maker or series is already followed", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn follow_source(Extension(service): Extension<Arc<dyn FigureService>>, auth: AuthContext, ApiJson(body): ApiJson<FollowSourceRequest>) -> Result<StatusCode, ApiError> {
+    service.follow_source(auth.user(), &SourceArgs::new(body.store, body.kind, body.code)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(delete, path = "/sources/{source_id}", tag = "figure", params(("source_id" = i32, Path)), responses(
+    (status = 204, description = "Maker or series is unfollowed"),
+    (status = 404, description = "Unknown maker or series", body = ApiErrorBody),
+    (status = 409, description = "Maker or series is not followed", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn unfollow_source(Extension(service): Extension<Arc<dyn FigureService>>, auth: AuthContext, ApiPath(source_id): ApiPath<i32>) -> Result<StatusCode, ApiError> {
+    service.unfollow_source(auth.user(), source_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(get, path = "/sources/{source_id}/products", tag = "figure", params(("source_id" = i32, Path), PageParams), responses(
+    (status = 200, description = "Figures of the maker or series", body = PageResponse<ProductResponse>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 404, description = "Unknown maker or series", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_source_products(Extension(service): Extension<Arc<dyn FigureService>>, auth: AuthContext, ApiPath(source_id): ApiPath<i32>, ApiQuery(params): ApiQuery<PageParams>) -> Result<Json<PageResponse<ProductResponse>>, ApiError> {
+    let sources = service.get_sources(auth.user()).await?;
+    if !sources.iter().any(|s| s.id() == source_id) {
+        return Err(ApiError::not_found(format!("unknown maker or series with id '{}'", source_id)));
+    }
+    let products = service.get_products_page_by_source(source_id, params.page_request()).await?;
+    Ok(Json(products.into()))
+}
+
+#[utoipa::path(get, path = "/products", tag = "figure", params(PageParams), responses(
+    (status = 200, description = "All figures", body = PageResponse<ProductResponse>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_products(Extension(service): Extension<Arc<dyn FigureService>>, ApiQuery(params): ApiQuery<PageParams>) -> Result<Json<PageResponse<ProductResponse>>, ApiError> {
+    let products = service.get_products_page(params.page_request()).await?;
+    Ok(Json(products.into()))
+}
+
+impl From<FollowSourceError> for ApiError {
+    fn from(e: FollowSourceError) -> Self {
+        match e {
+            e @ FollowSourceError::AlreadyFollowedError { .. } => ApiError::conflict(e),
+            e @ FollowSourceError::EmptyCode => ApiError::bad_request(e),
+            FollowSourceError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<UnfollowSourceError> for ApiError {
+    fn from(e: UnfollowSourceError) -> Self {
+        match e {
+            e @ UnfollowSourceError::UnknownSource { .. } => ApiError::not_found(e),
+            e @ UnfollowSourceError::SourceNotFollowed { .. } => ApiError::conflict(e),
+            UnfollowSourceError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<GetSourcesError> for ApiError {
+    fn from(e: GetSourcesError) -> Self {
+        match e {
+            GetSourcesError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<GetProductsError> for ApiError {
+    fn from(e: GetProductsError) -> Self {
+        match e {
+            GetProductsError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
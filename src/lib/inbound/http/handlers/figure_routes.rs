@@ -0,0 +1,211 @@
+use crate::domain::duplicate::models::listing::Listing;
+use crate::domain::figure::models::product::{store_now, GetProductsError, PreorderWindow, Product, ProductHistoryEntry};
+use crate::domain::figure::models::source::{FollowSourceError, GetSourcesError, Source, SourceArgs, SourceKind, Store, UnfollowSourceError};
+use crate::domain::figure::ports::FigureService;
+use crate::domain::figure::SITE;
+use crate::domain::product_history::ProductChange;
+use crate::inbound::http::auth::AuthContext;
+use crate::inbound::http::handlers::{target_products_page, Pagination};
+use crate::inbound::http::AppState;
+use askama::Template;
+use askama_axum::{IntoResponse, Response};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::{Extension, Form};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Deserialize;
+use serde_with::{serde_as, NoneAsEmptyString};
+use std::sync::Arc;
+use strum::IntoEnumIterator;
+
+#[derive(Template)]
+#[template(path = "figure.html")]
+struct FigureTemplate {
+    auth: AuthContext,
+    products: Vec<Product>,
+    sources: Vec<Source>,
+    selected_source: Option<Source>,
+    stores: Vec<Store>,
+    kinds: Vec<SourceKind>,
+    now: NaiveDateTime,
+    pagination: Pagination,
+}
+
+impl FigureTemplate {
+    fn format_date(date: DateTime<Utc>) -> String {
+        date.format("%Y-%m-%d %H:%M").to_string()
+    }
+
+    /// Preorder windows are shown as the store announces them, in Japan Standard Time.
+    fn format_preorder_date(date: NaiveDateTime) -> String {
+        format!("{} JST", date.format("%Y-%m-%d %H:%M"))
+    }
+
+    fn format_preorder(preorder: &PreorderWindow) -> String {
+        match (preorder.start(), preorder.end()) {
+            (Some(start), Some(end)) => format!("{} – {}", Self::format_preorder_date(start), Self::format_preorder_date(end)),
+            (Some(start), None) => format!("from {}", Self::format_preorder_date(start)),
+            (None, Some(end)) => format!("until {}", Self::format_preorder_date(end)),
+            (None, None) => "open".to_owned(),
+        }
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OverviewParams {
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    pub selected_source: Option<i32>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    pub page: Option<u32>,
+}
+
+pub async fn get_overview(Extension(service): Extension<Arc<dyn FigureService>>, auth: AuthContext, Query(params): Query<OverviewParams>) -> Response {
+    get_overview_response(service, auth, params).await
+}
+
+#[derive(Template)]
+#[template(path = "figure-product.html")]
+struct FigureProductTemplate {
+    auth: AuthContext,
+    product: Product,
+    history: Vec<ProductHistoryEntry>,
+    listings: Vec<Listing>,
+    now: NaiveDateTime,
+}
+
+impl FigureProductTemplate {
+    fn format_date(date: DateTime<Utc>) -> String {
+        FigureTemplate::format_date(date)
+    }
+
+    fn format_preorder(preorder: &PreorderWindow) -> String {
+        FigureTemplate::format_preorder(preorder)
+    }
+
+    fn format_preorder_change(&self, preorder: &Option<PreorderWindow>) -> String {
+        match preorder {
+            Some(preorder) => Self::format_preorder(preorder),
+            None => "closed".to_owned(),
+        }
+    }
+
+    fn format_price(&self, price: &Option<i32>) -> String {
+        match price {
+            Some(price) => format!("¥{}", price),
+            None => "TBA".to_owned(),
+        }
+    }
+}
+
+pub async fn get_product(State(state): State<AppState>, Extension(service): Extension<Arc<dyn FigureService>>, auth: AuthContext, Path(product_id): Path<i32>) -> Response {
+    let product = match service.get_product(product_id).await {
+        Ok(p) => p,
+        Err(e) => return e.into_response()
+    };
+    let history = match service.get_product_history(product_id).await {
+        Ok(h) => h,
+        Err(e) => return e.into_response()
+    };
+    let listings = match state.duplicate_service.get_duplicate_listings(SITE, product_id, product.image_phash()).await {
+        Ok(l) => l,
+        Err(e) => return e.into_response()
+    };
+    FigureProductTemplate { auth, product, history, listings, now: store_now() }.into_response()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PostSourceForm {
+    store: Store,
+    kind: SourceKind,
+    code: String,
+}
+
+pub async fn post_source(Extension(service): Extension<Arc<dyn FigureService>>, auth: AuthContext, Form(input): Form<PostSourceForm>) -> Response {
+    if let Err(e) = service.follow_source(auth.user(), &SourceArgs::new(input.store, input.kind, input.code)).await {
+        return e.into_response();
+    }
+    get_overview_response(service, auth, OverviewParams::default()).await
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DeleteSourceForm {
+    selected_source_id: i32
+}
+
+pub async fn delete_source(Extension(service): Extension<Arc<dyn FigureService>>, auth: AuthContext, Form(input): Form<DeleteSourceForm>) -> Response {
+    if let Err(e) = service.unfollow_source(auth.user(), input.selected_source_id).await {
+        return e.into_response();
+    }
+    get_overview_response(service, auth, OverviewParams::default()).await
+}
+
+async fn get_overview_response(service: Arc<dyn FigureService>, auth: AuthContext, params: OverviewParams) -> Response {
+    let sources = match service.get_followed_sources(auth.user()).await {
+        Ok(s) => s,
+        Err(e) => return e.into_response()
+    };
+    let selected_source = match params.selected_source {
+        Some(id) => sources.iter().find(|s| s.id() == id).cloned(),
+        None => None
+    };
+    let followed_ids = sources.iter().map(|s| s.id()).collect();
+    let selected = selected_source.as_ref().map(|s| ("selected_source", s.id()));
+    let page = target_products_page("/figure", followed_ids, selected, params.page, |ids, page| async move {
+        service.get_products_page_by_sources(&ids, page).await
+    }).await;
+    let (products, pagination) = match page {
+        Ok(p) => p,
+        Err(e) => return e.into_response()
+    };
+    let template = FigureTemplate {
+        auth,
+        products,
+        sources,
+        selected_source,
+        stores: Store::iter().collect(),
+        kinds: SourceKind::iter().collect(),
+        now: store_now(),
+        pagination,
+    };
+    template.into_response()
+}
+
+impl IntoResponse for GetProductsError {
+    fn into_response(self) -> Response {
+        match self {
+            GetProductsError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for GetSourcesError {
+    fn into_response(self) -> Response {
+        match self {
+            GetSourcesError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for FollowSourceError {
+    fn into_response(self) -> Response {
+        match self {
+            e @ FollowSourceError::AlreadyFollowedError { .. } => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            e @ FollowSourceError::EmptyCode => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            FollowSourceError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for UnfollowSourceError {
+    fn into_response(self) -> Response {
+        match self {
+            e @ UnfollowSourceError::UnknownSource { .. } => (StatusCode::NOT_FOUND, e.to_string()).into_response(),
+            e @ UnfollowSourceError::SourceNotFollowed { .. } => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            UnfollowSourceError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use crate::domain::image::models::image::{GetImageError, ImageVariant};
+use crate::inbound::http::AppState;
+use askama_axum::{IntoResponse, Response};
+use axum::extract::{Path, State};
+use axum::http::{header, StatusCode};
+
+/// Images never change under their hash, so clients may keep them for good.
+const IMAGE_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+pub async fn get_image(State(state): State<AppState>, Path(hash): Path<String>) -> Response {
+    get_image_response(state, &hash, ImageVariant::Original).await
+}
+
+pub async fn get_thumbnail(State(state): State<AppState>, Path(hash): Path<String>) -> Response {
+    get_image_response(state, &hash, ImageVariant::Thumbnail).await
+}
+
+async fn get_image_response(state: AppState, hash: &str, variant: ImageVariant) -> Response {
+    match state.image_service.get_image(hash, variant).await {
+        Ok(image) => (
+            [(header::CONTENT_TYPE, image.content_type().to_owned()), (header::CACHE_CONTROL, IMAGE_CACHE_CONTROL.to_owned())],
+            image.into_data(),
+        ).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+impl IntoResponse for GetImageError {
+    fn into_response(self) -> Response {
+        match self {
+            e @ GetImageError::ImageMissing { .. } => (StatusCode::NOT_FOUND, e.to_string()).into_response(),
+            GetImageError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use crate::domain::duplicate::models::listing::Listing;
+use crate::domain::mandarake::models::product::{GetProductsError, Product, ProductHistoryEntry};
+use crate::domain::mandarake::models::search::{DeleteSearchError, GetSearchesError, SaveSearchError, Search, SearchArgs};
+use crate::domain::mandarake::ports::MandarakeService;
+use crate::domain::mandarake::SITE;
+use crate::domain::product_history::ProductChange;
+use crate::inbound::http::auth::AuthContext;
+use crate::inbound::http::handlers::{target_products_page, Pagination};
+use crate::inbound::http::AppState;
+use askama::Template;
+use askama_axum::{IntoResponse, Response};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::{Extension, Form};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_with::{serde_as, NoneAsEmptyString};
+use std::sync::Arc;
+
+#[derive(Template)]
+#[template(path = "mandarake.html")]
+struct MandarakeTemplate {
+    auth: AuthContext,
+    products: Vec<Product>,
+    searches: Vec<Search>,
+    selected_search: Option<Search>,
+    pagination: Pagination,
+}
+
+impl MandarakeTemplate {
+    fn format_date(date: DateTime<Utc>) -> String {
+        date.format("%Y-%m-%d %H:%M").to_string()
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OverviewParams {
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    pub selected_search: Option<i32>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    pub page: Option<u32>,
+}
+
+pub async fn get_overview(Extension(service): Extension<Arc<dyn MandarakeService>>, auth: AuthContext, Query(params): Query<OverviewParams>) -> Response {
+    get_overview_response(service, auth, params).await
+}
+
+#[derive(Template)]
+#[template(path = "mandarake-product.html")]
+struct MandarakeProductTemplate {
+    auth: AuthContext,
+    product: Product,
+    history: Vec<ProductHistoryEntry>,
+    listings: Vec<Listing>,
+}
+
+impl MandarakeProductTemplate {
+    fn format_date(date: DateTime<Utc>) -> String {
+        MandarakeTemplate::format_date(date)
+    }
+
+    fn format_price(&self, price: &i32) -> String {
+        format!("¥{}", price)
+    }
+}
+
+pub async fn get_product(State(state): State<AppState>, Extension(service): Extension<Arc<dyn MandarakeService>>, auth: AuthContext, Path(product_id): Path<i32>) -> Response {
+    let product = match service.get_product(product_id).await {
+        Ok(p) => p,
+        Err(e) => return e.into_response()
+    };
+    let history = match service.get_product_history(product_id).await {
+        Ok(h) => h,
+        Err(e) => return e.into_response()
+    };
+    let listings = match state.duplicate_service.get_duplicate_listings(SITE, product_id, product.image_phash()).await {
+        Ok(l) => l,
+        Err(e) => return e.into_response()
+    };
+    MandarakeProductTemplate { auth, product, history, listings }.into_response()
+}
+
+#[serde_as]
+#[derive(Debug, Deserialize)]
+pub struct PostSearchForm {
+    keyword: String,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    max_price: Option<i32>,
+}
+
+pub async fn post_search(Extension(service): Extension<Arc<dyn MandarakeService>>, auth: AuthContext, Form(input): Form<PostSearchForm>) -> Response {
+    let keyword = input.keyword.trim();
+    if keyword.is_empty() {
+        return (StatusCode::BAD_REQUEST, "search keyword must not be empty").into_response();
+    }
+    if input.max_price.is_some_and(|p| p < 0) {
+        return (StatusCode::BAD_REQUEST, "max price must not be negative").into_response();
+    }
+    if let Err(e) = service.save_search(auth.user(), &SearchArgs::new(keyword.to_owned(), input.max_price)).await {
+        return e.into_response();
+    }
+    get_overview_response(service, auth, OverviewParams::default()).await
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DeleteSearchForm {
+    selected_search_id: i32
+}
+
+pub async fn delete_search(Extension(service): Extension<Arc<dyn MandarakeService>>, auth: AuthContext, Form(input): Form<DeleteSearchForm>) -> Response {
+    if let Err(e) = service.delete_search(auth.user(), input.selected_search_id).await {
+        return e.into_response();
+    }
+    get_overview_response(service, auth, OverviewParams::default()).await
+}
+
+async fn get_overview_response(service: Arc<dyn MandarakeService>, auth: AuthContext, params: OverviewParams) -> Response {
+    let searches = match service.get_saved_searches(auth.user()).await {
+        Ok(s) => s,
+        Err(e) => return e.into_response()
+    };
+    let selected_search = match params.selected_search {
+        Some(id) => searches.iter().find(|s| s.id() == id).cloned(),
+        None => None
+    };
+    let saved_ids = searches.iter().map(|s| s.id()).collect();
+    let selected = selected_search.as_ref().map(|s| ("selected_search", s.id()));
+    let page = target_products_page("/mandarake", saved_ids, selected, params.page, |ids, page| async move {
+        service.get_products_page_by_searches(&ids, page).await
+    }).await;
+    let (products, pagination) = match page {
+        Ok(p) => p,
+        Err(e) => return e.into_response()
+    };
+    let template = MandarakeTemplate {
+        auth,
+        products,
+        searches,
+        selected_search,
+        pagination,
+    };
+    template.into_response()
+}
+
+impl IntoResponse for GetProductsError {
+    fn into_response(self) -> Response {
+        match self {
+            GetProductsError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for GetSearchesError {
+    fn into_response(self) -> Response {
+        match self {
+            GetSearchesError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for SaveSearchError {
+    fn into_response(self) -> Response {
+        match self {
+            SaveSearchError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for DeleteSearchError {
+    fn into_response(self) -> Response {
+        match self {
+            e @ DeleteSearchError::UnknownSearch { .. } => (StatusCode::NOT_FOUND, e.to_string()).into_response(),
+            e @ DeleteSearchError::SearchNotFollowed { .. } => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            DeleteSearchError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
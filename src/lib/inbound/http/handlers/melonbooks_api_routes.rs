@@ -0,0 +1,378 @@
+use crate::domain::melonbooks::ports::MelonbooksService;
+use crate::domain::melonbooks::models::artist::{Artist, ArtistArgs, FollowArtistError, GetArtistsError, SetArtistScheduleError, UnfollowArtistError};
+use crate::domain::availability::Availability;
+use crate::domain::melonbooks::models::product::{AddTitleSkipSequenceError, DeleteTitleSkipSequenceError, GetCategoriesError, GetFlagsError, GetProductsError, GetTitleSkipSequencesError, Product};
+use crate::domain::melonbooks::models::query::{ProductQuery, ProductSort};
+use crate::domain::pagination::{PageRequest, SortDirection, DEFAULT_PAGE_SIZE};
+use crate::domain::schedule::Schedule;
+use crate::inbound::http::auth::AuthContext;
+use crate::inbound::http::handlers::api::{ApiError, ApiErrorBody, ApiJson, ApiPath, ApiQuery, PageParams, PageResponse, SearchParams, SearchResultResponse};
+use axum::Extension;
+use axum::http::StatusCode;
+use axum::Json;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GetArtistsResponseBody {
+    artists: Vec<ArtistResponse>
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ArtistResponse {
+    id: i32,
+    date_added: DateTime<Utc>,
+    name: String,
+    following: bool,
+    /// Cron expression the artist is scraped on instead of the schedule of the site.
+    schedule: Option<String>,
+}
+
+impl From<Artist> for ArtistResponse {
+    fn from(a: Artist) -> Self {
+        Self {
+            id: a.id(),
+            date_added: a.date_added(),
+            name: a.name().to_owned(),
+            following: a.following(),
+            schedule: a.schedule().map(|s| s.expression().to_owned()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProductResponse {
+    id: i32,
+    date_added: DateTime<Utc>,
+    url: String,
+    title: String,
+    circle: Option<String>,
+    artists: Vec<ArtistResponse>,
+    image_url: String,
+    category: String,
+    tags: Vec<String>,
+    flags: Vec<String>,
+    price: Option<String>,
+    #[schema(value_type = String)]
+    availability: Availability,
+}
+
+impl From<Product> for ProductResponse {
+    fn from(p: Product) -> Self {
+        Self {
+            id: p.id(),
+            date_added: p.date_added(),
+            url: p.url().to_owned(),
+            title: p.title().to_owned(),
+            circle: p.circle().map(|c| c.to_owned()),
+            artists: p.artists().iter().cloned().map(|a| a.into()).collect(),
+            image_url: p.image_url().to_owned(),
+            category: p.category().to_owned(),
+            tags: p.tags().to_vec(),
+            flags: p.flags().to_vec(),
+            price: p.price().map(|p| p.to_owned()),
+            availability: p.availability(),
+        }
+    }
+}
+
+/// Kept for existing scripts, lists the followed artists without pagination.
+#[utoipa::path(get, path = "/artists", tag = "melonbooks", responses(
+    (status = 200, description = "All followed artists", body = GetArtistsResponseBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_followed_artists_legacy(Extension(service): Extension<Arc<dyn MelonbooksService>>, auth: AuthContext) -> Result<Json<GetArtistsResponseBody>, ApiError> {
+    let artists = service.get_followed_artists(auth.user()).await?
+        .into_iter()
+        .map(|a| a.into())
+        .collect::<Vec<ArtistResponse>>();
+    Ok(Json(GetArtistsResponseBody { artists }))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ArtistListParams {
+    pub following: Option<bool>,
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+#[utoipa::path(get, path = "/artists", tag = "melonbooks", params(ArtistListParams), responses(
+    (status = 200, description = "Known artists", body = PageResponse<ArtistResponse>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_artists(Extension(service): Extension<Arc<dyn MelonbooksService>>, auth: AuthContext, ApiQuery(params): ApiQuery<ArtistListParams>) -> Result<Json<PageResponse<ArtistResponse>>, ApiError> {
+    let page = PageParams { page: params.page, page_size: params.page_size }.page_request();
+    let artists = service.get_artists_page(auth.user(), params.following, page).await?;
+    Ok(Json(artists.into()))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct FollowArtistRequest {
+    pub name: String,
+}
+
+#[utoipa::path(post, path = "/artists", tag = "melonbooks", request_body = FollowArtistRequest, responses(
+    (status = 204, description = "Artist is followed"),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 409, description = "Artist is already followed", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn follow_artist(Extension(service): Extension<Arc<dyn MelonbooksService>>, auth: AuthContext, ApiJson(body): ApiJson<FollowArtistRequest>) -> Result<StatusCode, ApiError> {
+    let name = body.name.trim();
+    if name.is_empty() {
+        return Err(ApiError::bad_request("artist name must not be empty"));
+    }
+    service.follow_artist(auth.user(), &ArtistArgs::new(name.to_owned())).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(delete, path = "/artists/{artist_id}", tag = "melonbooks", params(("artist_id" = i32, Path)), responses(
+    (status = 204, description = "Artist is no longer followed"),
+    (status = 404, description = "Unknown artist", body = ApiErrorBody),
+    (status = 409, description = "Artist is not followed", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn unfollow_artist(Extension(service): Extension<Arc<dyn MelonbooksService>>, auth: AuthContext, ApiPath(artist_id): ApiPath<i32>) -> Result<StatusCode, ApiError> {
+    service.unfollow_artist(auth.user(), artist_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ArtistScheduleRequest {
+    /// Cron expression with seconds, e.g. `0 0 */6 * * *`, `null` scrapes the artist with the site again.
+    pub schedule: Option<String>,
+}
+
+#[utoipa::path(put, path = "/artists/{artist_id}/schedule", tag = "melonbooks", params(("artist_id" = i32, Path)), request_body = ArtistScheduleRequest, responses(
+    (status = 204, description = "Schedule of the artist is set"),
+    (status = 400, description = "Invalid schedule", body = ApiErrorBody),
+    (status = 404, description = "Unknown artist", body = ApiErrorBody),
+    (status = 409, description = "Artist is not followed", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn set_artist_schedule(Extension(service): Extension<Arc<dyn MelonbooksService>>, auth: AuthContext, ApiPath(artist_id): ApiPath<i32>, ApiJson(body): ApiJson<ArtistScheduleRequest>) -> Result<StatusCode, ApiError> {
+    let schedule = Schedule::parse_optional(body.schedule.as_deref().unwrap_or_default())?;
+    service.set_artist_schedule(auth.user(), artist_id, schedule.as_ref()).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ProductListParams {
+    pub artist_id: Option<i32>,
+    pub category: Option<String>,
+    #[param(value_type = Option<String>)]
+    pub availability: Option<Availability>,
+    pub flag: Option<String>,
+    pub added_from: Option<NaiveDate>,
+    pub added_to: Option<NaiveDate>,
+    #[param(value_type = Option<String>)]
+    pub sort: Option<ProductSort>,
+    #[param(value_type = Option<String>)]
+    pub direction: Option<SortDirection>,
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+impl ProductListParams {
+    fn product_query(self) -> ProductQuery {
+        let page = PageRequest::new(self.page.unwrap_or(1), self.page_size.unwrap_or(DEFAULT_PAGE_SIZE));
+        ProductQuery::new(page, self.sort.unwrap_or_default(), self.direction.unwrap_or_default())
+            .with_artist_id(self.artist_id)
+            .with_category(self.category)
+            .with_availability(self.availability)
+            .with_flag(self.flag)
+            .with_date_added_range(self.added_from, self.added_to)
+    }
+}
+
+#[utoipa::path(get, path = "/products", tag = "melonbooks", params(ProductListParams), responses(
+    (status = 200, description = "Matching products", body = PageResponse<ProductResponse>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_products(Extension(service): Extension<Arc<dyn MelonbooksService>>, ApiQuery(params): ApiQuery<ProductListParams>) -> Result<Json<PageResponse<ProductResponse>>, ApiError> {
+    let page = service.get_products_page(&params.product_query()).await?;
+    Ok(Json(page.into()))
+}
+
+#[utoipa::path(get, path = "/artists/{artist_id}/products", tag = "melonbooks", params(("artist_id" = i32, Path), ProductListParams), responses(
+    (status = 200, description = "Products of the artist", body = PageResponse<ProductResponse>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 404, description = "Unknown artist", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_artist_products(Extension(service): Extension<Arc<dyn MelonbooksService>>, auth: AuthContext, ApiPath(artist_id): ApiPath<i32>, ApiQuery(params): ApiQuery<ProductListParams>) -> Result<Json<PageResponse<ProductResponse>>, ApiError> {
+    let artists = service.get_artists(auth.user()).await?;
+    if !artists.iter().any(|a| a.id() == artist_id) {
+        return Err(ApiError::not_found(format!("unknown artist with id '{}'", artist_id)));
+    }
+    let params = ProductListParams { artist_id: Some(artist_id), ..params };
+    let page = service.get_products_page(&params.product_query()).await?;
+    Ok(Json(page.into()))
+}
+
+#[utoipa::path(get, path = "/products/search", tag = "melonbooks", params(SearchParams), responses(
+    (status = 200, description = "Products ranked by relevance", body = PageResponse<SearchResultResponse<ProductResponse>>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn search_products(Extension(service): Extension<Arc<dyn MelonbooksService>>, ApiQuery(params): ApiQuery<SearchParams>) -> Result<Json<PageResponse<SearchResultResponse<ProductResponse>>>, ApiError> {
+    let results = service.search_products_page(&params.q, params.page_request()).await?
+        .map(|r| {
+            let rank = r.rank();
+            let (product, highlights) = r.into_parts();
+            SearchResultResponse::new(product.into(), rank, &highlights)
+        });
+    Ok(Json(results.into()))
+}
+
+#[utoipa::path(get, path = "/categories", tag = "melonbooks", params(PageParams), responses(
+    (status = 200, description = "All categories", body = PageResponse<String>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_categories(Extension(service): Extension<Arc<dyn MelonbooksService>>, ApiQuery(params): ApiQuery<PageParams>) -> Result<Json<PageResponse<String>>, ApiError> {
+    let categories = service.get_categories_page(params.page_request()).await?;
+    Ok(Json(categories.into()))
+}
+
+#[utoipa::path(get, path = "/flags", tag = "melonbooks", params(PageParams), responses(
+    (status = 200, description = "All flags", body = PageResponse<String>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_flags(Extension(service): Extension<Arc<dyn MelonbooksService>>, ApiQuery(params): ApiQuery<PageParams>) -> Result<Json<PageResponse<String>>, ApiError> {
+    let flags = service.get_flags_page(params.page_request()).await?;
+    Ok(Json(flags.into()))
+}
+
+#[utoipa::path(get, path = "/title-skip-sequences", tag = "melonbooks", params(PageParams), responses(
+    (status = 200, description = "Title skip sequences", body = PageResponse<String>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_title_skip_sequences(Extension(service): Extension<Arc<dyn MelonbooksService>>, auth: AuthContext, ApiQuery(params): ApiQuery<PageParams>) -> Result<Json<PageResponse<String>>, ApiError> {
+    let sequences = service.get_title_skip_sequences_page(auth.user(), params.page_request()).await?;
+    Ok(Json(sequences.into()))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TitleSkipSequenceRequest {
+    pub sequence: String,
+}
+
+#[utoipa::path(post, path = "/title-skip-sequences", tag = "melonbooks", request_body = TitleSkipSequenceRequest, responses(
+    (status = 204, description = "Sequence is added"),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 409, description = "Sequence already exists", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn add_title_skip_sequence(Extension(service): Extension<Arc<dyn MelonbooksService>>, auth: AuthContext, ApiJson(body): ApiJson<TitleSkipSequenceRequest>) -> Result<StatusCode, ApiError> {
+    if body.sequence.is_empty() {
+        return Err(ApiError::bad_request("title skip sequence must not be empty"));
+    }
+    service.add_title_skip_sequence(auth.user(), &body.sequence).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(delete, path = "/title-skip-sequences/{sequence}", tag = "melonbooks", params(("sequence" = String, Path)), responses(
+    (status = 204, description = "Sequence is deleted"),
+    (status = 404, description = "Unknown sequence", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn delete_title_skip_sequence(Extension(service): Extension<Arc<dyn MelonbooksService>>, auth: AuthContext, ApiPath(sequence): ApiPath<String>) -> Result<StatusCode, ApiError> {
+    service.delete_title_skip_sequence(auth.user(), &sequence).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+impl From<FollowArtistError> for ApiError {
+    fn from(e: FollowArtistError) -> Self {
+        match e {
+            e @ FollowArtistError::AlreadyFollowedError(_) => ApiError::conflict(e),
+            FollowArtistError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<UnfollowArtistError> for ApiError {
+    fn from(e: UnfollowArtistError) -> Self {
+        match e {
+            e @ UnfollowArtistError::UnknownArtist { .. } => ApiError::not_found(e),
+            e @ UnfollowArtistError::ArtistNotFollowed { .. } => ApiError::conflict(e),
+            UnfollowArtistError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<SetArtistScheduleError> for ApiError {
+    fn from(e: SetArtistScheduleError) -> Self {
+        match e {
+            e @ SetArtistScheduleError::UnknownArtist { .. } => ApiError::not_found(e),
+            e @ SetArtistScheduleError::ArtistNotFollowed { .. } => ApiError::conflict(e),
+            SetArtistScheduleError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<GetArtistsError> for ApiError {
+    fn from(e: GetArtistsError) -> Self {
+        match e {
+            GetArtistsError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<GetProductsError> for ApiError {
+    fn from(e: GetProductsError) -> Self {
+        match e {
+            GetProductsError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<GetCategoriesError> for ApiError {
+    fn from(e: GetCategoriesError) -> Self {
+        match e {
+            GetCategoriesError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<GetFlagsError> for ApiError {
+    fn from(e: GetFlagsError) -> Self {
+        match e {
+            GetFlagsError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<GetTitleSkipSequencesError> for ApiError {
+    fn from(e: GetTitleSkipSequencesError) -> Self {
+        match e {
+            GetTitleSkipSequencesError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<AddTitleSkipSequenceError> for ApiError {
+    fn from(e: AddTitleSkipSequenceError) -> Self {
+        match e {
+            e @ AddTitleSkipSequenceError::DuplicateSequence { .. } => ApiError::conflict(e),
+            AddTitleSkipSequenceError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<DeleteTitleSkipSequenceError> for ApiError {
+    fn from(e: DeleteTitleSkipSequenceError) -> Self {
+        match e {
+            e @ DeleteTitleSkipSequenceError::UnknownSequence { .. } => ApiError::not_found(e),
+            DeleteTitleSkipSequenceError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
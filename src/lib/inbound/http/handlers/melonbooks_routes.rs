@@ -1,72 +1,247 @@
-use crate::domain::melonbooks::models::artist::{Artist, ArtistArgs, FollowArtistError, GetArtistsError, UnfollowArtistError};
-use crate::domain::melonbooks::models::product::{AddTitleSkipSequenceError, DeleteTitleSkipSequenceError, GetProductsError, GetTitleSkipSequencesError, Product};
+use crate::domain::availability::Availability;
+use crate::domain::melonbooks::models::artist::{Artist, ArtistArgs, FollowArtistError, GetArtistsError, SetArtistScheduleError, UnfollowArtistError};
+use crate::domain::melonbooks::models::product::{AddTitleSkipSequenceError, DeleteTitleSkipSequenceError, GetAvailabilityStatsError, GetCategoriesError, GetFlagsError, GetProductsError, GetTitleSkipSequencesError, Product, ProductHistoryEntry};
 use crate::domain::melonbooks::ports::MelonbooksService;
+use crate::domain::melonbooks::models::query::{ProductQuery, ProductSort};
+use crate::domain::duplicate::models::listing::Listing;
+use crate::domain::melonbooks::SITE;
+use crate::domain::pagination::{PageRequest, SortDirection, DEFAULT_PAGE_SIZE};
+use crate::domain::product_history::ProductChange;
+use crate::domain::schedule::Schedule;
+use crate::domain::search::{FieldHighlight, HighlightedText};
+use crate::inbound::http::auth::AuthContext;
+use crate::inbound::http::handlers::stats::AvailabilityStatsTemplate;
+use crate::inbound::http::handlers::{scrape_event_stream, Pagination, TargetRuns};
 use crate::inbound::http::AppState;
 use askama::Template;
 use askama_axum::{IntoResponse, Response};
-use axum::extract::{Query, State};
+use axum::extract::{Path, Query, State};
+use axum::Extension;
 use axum::http::StatusCode;
-use axum::{Form, Json};
-use chrono::{DateTime, Utc};
+use axum::Form;
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, NoneAsEmptyString};
+use std::collections::HashMap;
 use std::sync::Arc;
+use strum::IntoEnumIterator;
 
-#[derive(Debug, Serialize)]
-pub struct GetArtistsResponseBody {
-    artists: Vec<ArtistResponse>
-}
-
-#[derive(Debug, Serialize)]
-pub struct ArtistResponse {
-    id: i32,
-    date_added: DateTime<Utc>,
-    name: String,
-    following: bool,
-}
-
-impl From<Artist> for ArtistResponse {
-    fn from(a: Artist) -> Self {
-        Self {
-            id: a.id(),
-            date_added: a.date_added(),
-            name: a.name().to_owned(),
-            following: a.following(),
-        }
-    }
-}
-
-pub async fn get_artists(State(state): State<AppState>) -> Json<GetArtistsResponseBody> {
-    let artists = state.melonbooks_service.get_artists().await
-        .unwrap()
-        .into_iter()
-        .filter(|a| a.following())
-        .map(|a| a.into())
-        .collect::<Vec<ArtistResponse>>();
-    Json(GetArtistsResponseBody { artists } )
-}
 
 #[derive(Template)]
 #[template(path = "melonbooks.html")]
 struct MelonbooksTemplate {
+    auth: AuthContext,
     products: Vec<Product>,
     artists: Vec<Artist>,
     selected_artist: Option<Artist>,
     skip_sequences: Vec<String>,
+    search: Option<String>,
+    highlights: HashMap<i32, Vec<FieldHighlight>>,
+    params: OverviewParams,
+    categories: Vec<String>,
+    flags: Vec<String>,
+    availabilities: Vec<Availability>,
+    sorts: Vec<ProductSort>,
+    directions: Vec<SortDirection>,
+    page_sizes: Vec<u32>,
+    pagination: Pagination,
+    target_runs: TargetRuns,
 }
 
 impl MelonbooksTemplate {
     fn format_date(date: DateTime<Utc>) -> String {
         date.format("%Y-%m-%d %H:%M").to_string()
     }
+
+    fn search_action(&self) -> &'static str {
+        "/melonbooks"
+    }
+
+    fn schedule_value(schedule: Option<&Schedule>) -> &str {
+        schedule.map(|s| s.expression()).unwrap_or_default()
+    }
+
+    fn events_action(&self) -> &'static str {
+        "/melonbooks/events"
+    }
+
+    fn highlight(&self, product_id: i32, field: &str) -> Option<&HighlightedText> {
+        self.highlights.get(&product_id)
+            .and_then(|h| h.iter().find(|h| h.field() == field))
+            .map(|h| h.text())
+    }
+
+    fn matched_fields(&self, product_id: i32) -> Vec<&FieldHighlight> {
+        self.highlights.get(&product_id)
+            .map(|h| h.iter().filter(|h| h.field() != "title" && h.text().has_match()).collect())
+            .unwrap_or_default()
+    }
+
+    fn is_selected(&self, field: &str, value: &str) -> bool {
+        let params = &self.params;
+        let current = match field {
+            "category" => params.category.clone(),
+            "availability" => params.availability.as_ref().map(|a| a.to_string()),
+            "flag" => params.flag.clone(),
+            "sort" => Some(params.sort.unwrap_or_default().to_string()),
+            "direction" => Some(params.direction.unwrap_or_default().to_string()),
+            "page_size" => Some(params.page_size.unwrap_or(DEFAULT_PAGE_SIZE).to_string()),
+            _ => None,
+        };
+        current.as_deref() == Some(value)
+    }
+
+    fn date_value(date: &Option<NaiveDate>) -> String {
+        date.map(|d| d.to_string()).unwrap_or_default()
+    }
 }
 
-#[derive(Debug, Deserialize)]
+#[serde_as]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
 pub struct OverviewParams {
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub selected_artist: Option<i32>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search: Option<String>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub availability: Option<Availability>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flag: Option<String>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub added_from: Option<NaiveDate>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub added_to: Option<NaiveDate>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort: Option<ProductSort>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direction: Option<SortDirection>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<u32>,
+}
+
+impl OverviewParams {
+    fn pagination(&self, page: u32, total_pages: u32, total_items: i64) -> Pagination {
+        let params = OverviewParams { page: None, ..self.clone() };
+        let query = serde_urlencoded::to_string(&params).unwrap_or_default();
+        Pagination::new("/melonbooks", &query, page, total_pages, total_items)
+    }
+
+    /// Live products are only inserted into the unfiltered first page with the newest products first.
+    fn is_live(&self) -> bool {
+        let latest = OverviewParams {
+            sort: Some(ProductSort::default()),
+            direction: Some(SortDirection::default()),
+            page: Some(1),
+            page_size: self.page_size,
+            ..OverviewParams::default()
+        };
+        let current = OverviewParams {
+            sort: Some(self.sort.unwrap_or_default()),
+            direction: Some(self.direction.unwrap_or_default()),
+            page: Some(self.page.unwrap_or(1)),
+            ..self.clone()
+        };
+        current == latest
+    }
+
+    fn product_query(&self) -> ProductQuery {
+        let page = PageRequest::new(self.page.unwrap_or(1), self.page_size.unwrap_or(DEFAULT_PAGE_SIZE));
+        ProductQuery::new(page, self.sort.unwrap_or_default(), self.direction.unwrap_or_default())
+            .with_artist_id(self.selected_artist)
+            .with_category(self.category.clone())
+            .with_availability(self.availability.clone())
+            .with_flag(self.flag.clone())
+            .with_date_added_range(self.added_from, self.added_to)
+    }
+}
+
+pub async fn get_overview(Extension(service): Extension<Arc<dyn MelonbooksService>>, auth: AuthContext, Query(params): Query<OverviewParams>) -> Response {
+    get_overview_response(service, auth, params).await
+}
+
+#[derive(Template)]
+#[template(path = "melonbooks-product-card.html")]
+struct MelonbooksProductCardTemplate<'a> {
+    product: &'a Product,
+}
+
+impl MelonbooksProductCardTemplate<'_> {
+    fn format_date(date: DateTime<Utc>) -> String {
+        MelonbooksTemplate::format_date(date)
+    }
+
+    fn highlight(&self, _product_id: i32, _field: &str) -> Option<&HighlightedText> {
+        None
+    }
+
+    fn matched_fields(&self, _product_id: i32) -> Vec<&FieldHighlight> {
+        Vec::new()
+    }
+}
+
+#[derive(Template)]
+#[template(path = "melonbooks-product.html")]
+struct MelonbooksProductTemplate {
+    auth: AuthContext,
+    product: Product,
+    history: Vec<ProductHistoryEntry>,
+    listings: Vec<Listing>,
+}
+
+impl MelonbooksProductTemplate {
+    fn format_date(date: DateTime<Utc>) -> String {
+        MelonbooksTemplate::format_date(date)
+    }
+
+    fn format_price(&self, price: &Option<String>) -> String {
+        price.clone().unwrap_or_else(|| "-".to_owned())
+    }
+}
+
+pub async fn get_product(State(state): State<AppState>, Extension(service): Extension<Arc<dyn MelonbooksService>>, auth: AuthContext, Path(product_id): Path<i32>) -> Response {
+    let product = match service.get_product(product_id).await {
+        Ok(p) => p,
+        Err(e) => return e.into_response()
+    };
+    let history = match service.get_product_history(product_id).await {
+        Ok(h) => h,
+        Err(e) => return e.into_response()
+    };
+    let listings = match state.duplicate_service.get_duplicate_listings(SITE, product_id, product.image_phash()).await {
+        Ok(l) => l,
+        Err(e) => return e.into_response()
+    };
+    MelonbooksProductTemplate { auth, product, history, listings }.into_response()
+}
+
+pub async fn get_stats(Extension(service): Extension<Arc<dyn MelonbooksService>>, auth: AuthContext) -> Response {
+    let stats = match service.get_availability_stats(auth.user()).await {
+        Ok(s) => s,
+        Err(e) => return e.into_response()
+    };
+    AvailabilityStatsTemplate { auth, site: "Melonbooks", base_path: "/melonbooks", group_label: "Artist", stats }.into_response()
 }
 
-pub async fn get_overview(State(state): State<AppState>, Query(params): Query<OverviewParams>) -> Response {
-    get_overview_response(state.melonbooks_service, params.selected_artist).await
+pub async fn get_events(Extension(service): Extension<Arc<dyn MelonbooksService>>, auth: AuthContext) -> axum::response::Response {
+    let receiver = service.subscribe_scrape_events();
+    scrape_event_stream(receiver, auth.user().id(), |product| {
+        MelonbooksProductCardTemplate { product }.render().unwrap_or_default()
+    })
 }
 
 #[derive(Debug, Deserialize)]
@@ -74,11 +249,11 @@ pub struct PostArtistForm {
     name: String
 }
 
-pub async fn post_artist(State(state): State<AppState>, Form(input): Form<PostArtistForm>) -> Response {
-    if let Err(e) = state.melonbooks_service.follow_artist(&ArtistArgs::new(input.name)).await {
+pub async fn post_artist(Extension(service): Extension<Arc<dyn MelonbooksService>>, auth: AuthContext, Form(input): Form<PostArtistForm>) -> Response {
+    if let Err(e) = service.follow_artist(auth.user(), &ArtistArgs::new(input.name)).await {
         return e.into_response();
     }
-    get_overview_response(state.melonbooks_service, None).await
+    get_overview_response(service, auth, OverviewParams::default()).await
 }
 
 #[derive(Debug, Deserialize)]
@@ -87,11 +262,29 @@ pub struct DeleteArtistForm {
     selected_artist_id: i32
 }
 
-pub async fn delete_artist(State(state): State<AppState>, Form(input): Form<DeleteArtistForm>) -> Response {
-    if let Err(e) = state.melonbooks_service.unfollow_artist(input.selected_artist_id).await {
+pub async fn delete_artist(Extension(service): Extension<Arc<dyn MelonbooksService>>, auth: AuthContext, Form(input): Form<DeleteArtistForm>) -> Response {
+    if let Err(e) = service.unfollow_artist(auth.user(), input.selected_artist_id).await {
+        return e.into_response();
+    }
+    get_overview_response(service, auth, OverviewParams::default()).await
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ArtistScheduleForm {
+    artist_id: i32,
+    schedule: String,
+}
+
+pub async fn post_artist_schedule(Extension(service): Extension<Arc<dyn MelonbooksService>>, auth: AuthContext, Form(input): Form<ArtistScheduleForm>) -> Response {
+    let schedule = match Schedule::parse_optional(&input.schedule) {
+        Ok(s) => s,
+        Err(e) => return e.into_response()
+    };
+    if let Err(e) = service.set_artist_schedule(auth.user(), input.artist_id, schedule.as_ref()).await {
         return e.into_response();
     }
-    get_overview_response(state.melonbooks_service, None).await
+    get_overview_response(service, auth, OverviewParams { selected_artist: Some(input.artist_id), ..OverviewParams::default() }).await
 }
 
 #[derive(Debug, Deserialize)]
@@ -100,11 +293,11 @@ pub struct AddTitleSkipSequenceForm {
     title_skip_sequence: String
 }
 
-pub async fn post_title_skip_sequence(State(state): State<AppState>, Form(input): Form<AddTitleSkipSequenceForm>) -> Response {
-    if let Err(e) = state.melonbooks_service.add_title_skip_sequence(&input.title_skip_sequence).await {
+pub async fn post_title_skip_sequence(Extension(service): Extension<Arc<dyn MelonbooksService>>, auth: AuthContext, Form(input): Form<AddTitleSkipSequenceForm>) -> Response {
+    if let Err(e) = service.add_title_skip_sequence(auth.user(), &input.title_skip_sequence).await {
         return e.into_response();
     }
-    get_overview_response(state.melonbooks_service, None).await
+    get_overview_response(service, auth, OverviewParams::default()).await
 }
 
 #[derive(Debug, Deserialize)]
@@ -113,45 +306,106 @@ pub struct DeleteTitleSkipSequenceForm {
     title_skip_sequence: String
 }
 
-pub async fn delete_title_skip_sequence(State(state): State<AppState>, Form(input): Form<DeleteTitleSkipSequenceForm>) -> Response {
-    if let Err(e) = state.melonbooks_service.delete_title_skip_sequence(&input.title_skip_sequence).await {
+pub async fn delete_title_skip_sequence(Extension(service): Extension<Arc<dyn MelonbooksService>>, auth: AuthContext, Form(input): Form<DeleteTitleSkipSequenceForm>) -> Response {
+    if let Err(e) = service.delete_title_skip_sequence(auth.user(), &input.title_skip_sequence).await {
         return e.into_response();
     }
-    get_overview_response(state.melonbooks_service, None).await
+    get_overview_response(service, auth, OverviewParams::default()).await
 }
 
-pub async fn get_overview_response(service: Arc<dyn MelonbooksService>, selected_artist_id: Option<i32>) -> Response {
-    let artists = match service.get_followed_artists().await {
+pub async fn get_overview_response(service: Arc<dyn MelonbooksService>, auth: AuthContext, params: OverviewParams) -> Response {
+    let artists = match service.get_followed_artists(auth.user()).await {
         Ok(a) => a,
         Err(e) => return e.into_response()
     };
-    let selected_artist = match selected_artist_id {
+    let schedules = match service.get_target_schedules().await {
+        Ok(s) => s,
+        Err(e) => return e.into_response()
+    };
+    let adaptive_targets = match service.get_adaptive_targets().await {
+        Ok(t) => t,
+        Err(e) => return e.into_response()
+    };
+    let target_runs = TargetRuns::new(&schedules, &adaptive_targets, Utc::now(), |name| artists.iter().any(|a| a.name() == name));
+    let selected_artist = match params.selected_artist {
         Some(id) => artists.iter().find(|a| a.id() == id).cloned(),
         None => None
     };
-    let products = match selected_artist.as_ref() {
-        Some(artist) => match service.get_products_by_artist(artist.id()).await {
-            Ok(p) => p,
-            Err(e) => return e.into_response()
+    let mut highlights = HashMap::new();
+    let (products, page, total_pages, total_items) = match params.search.as_ref().filter(|s| !s.trim().is_empty()) {
+        Some(search) => {
+            let products = match service.search_products(search).await {
+                Ok(results) => results.into_iter()
+                    .map(|r| r.into_parts())
+                    .filter(|(p, _)| p.artists().iter().any(|pa| artists.iter().any(|a| a.id() == pa.id())))
+                    .filter(|(p, _)| selected_artist.as_ref().is_none_or(|a| p.artists().iter().any(|pa| pa.id() == a.id())))
+                    .map(|(p, h)| {
+                        highlights.insert(p.id(), h);
+                        p
+                    })
+                    .collect::<Vec<_>>(),
+                Err(e) => return e.into_response()
+            };
+            let total_items = products.len() as i64;
+            (products, 1, 1, total_items)
         }
-        None => match service.get_products().await {
-            Ok(p) => p,
-            Err(e) => return e.into_response()
+        None => {
+            let query = params.product_query()
+                .with_artist_id(selected_artist.as_ref().map(|a| a.id()))
+                .with_followed_by(Some(auth.user().id()));
+            match service.get_products_page(&query).await {
+                Ok(p) => {
+                    let (page, total_pages, total_items) = (p.page(), p.total_pages(), p.total_items());
+                    (p.into_items(), page, total_pages, total_items)
+                }
+                Err(e) => return e.into_response()
+            }
         }
     };
-    let skip_sequences = match service.get_title_skip_sequences().await {
+    let skip_sequences = match service.get_title_skip_sequences(auth.user()).await {
         Ok(s) => s,
         Err(e) => return e.into_response()
     };
+    let categories = match service.get_categories().await {
+        Ok(c) => c,
+        Err(e) => return e.into_response()
+    };
+    let flags = match service.get_flags().await {
+        Ok(f) => f,
+        Err(e) => return e.into_response()
+    };
+    let pagination = params.pagination(page, total_pages, total_items);
     let template = MelonbooksTemplate {
+        auth,
         products,
         artists,
         selected_artist,
-        skip_sequences
+        skip_sequences,
+        search: params.search.clone(),
+        highlights,
+        params,
+        categories,
+        flags,
+        availabilities: Availability::iter().collect(),
+        sorts: ProductSort::iter().collect(),
+        directions: SortDirection::iter().collect(),
+        page_sizes: vec![25, DEFAULT_PAGE_SIZE, 100, 200],
+        pagination,
+        target_runs,
     };
     template.into_response()
 }
 
+impl IntoResponse for GetAvailabilityStatsError {
+    fn into_response(self) -> Response {
+        match self {
+            GetAvailabilityStatsError::GetArtistsError(e) => e.into_response(),
+            GetAvailabilityStatsError::GetProductsError(e) => e.into_response(),
+            GetAvailabilityStatsError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
 impl IntoResponse for GetProductsError {
     fn into_response(self) -> Response {
         match self {
@@ -187,6 +441,32 @@ impl IntoResponse for UnfollowArtistError {
     }
 }
 
+impl IntoResponse for SetArtistScheduleError {
+    fn into_response(self) -> Response {
+        match self {
+            e @ SetArtistScheduleError::UnknownArtist { .. } => (StatusCode::NOT_FOUND, e.to_string()).into_response(),
+            e @ SetArtistScheduleError::ArtistNotFollowed { .. } => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            SetArtistScheduleError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for GetCategoriesError {
+    fn into_response(self) -> Response {
+        match self {
+            GetCategoriesError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for GetFlagsError {
+    fn into_response(self) -> Response {
+        match self {
+            GetFlagsError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
 impl IntoResponse for GetTitleSkipSequencesError {
     fn into_response(self) -> Response {
         match self { 
@@ -198,6 +478,7 @@ impl IntoResponse for GetTitleSkipSequencesError {
 impl IntoResponse for AddTitleSkipSequenceError {
     fn into_response(self) -> Response {
         match self {
+            e @ AddTitleSkipSequenceError::DuplicateSequence { .. } => (StatusCode::CONFLICT, e.to_string()).into_response(),
             AddTitleSkipSequenceError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
         }
     }
@@ -206,6 +487,7 @@ impl IntoResponse for AddTitleSkipSequenceError {
 impl IntoResponse for DeleteTitleSkipSequenceError {
     fn into_response(self) -> Response {
         match self {
+            e @ DeleteTitleSkipSequenceError::UnknownSequence { .. } => (StatusCode::NOT_FOUND, e.to_string()).into_response(),
             DeleteTitleSkipSequenceError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
         }
     }
@@ -1,2 +1,304 @@
+use crate::domain::duplicate::models::listing::GetListingsError;
+use crate::domain::pagination::{Page, PageRequest, DEFAULT_PAGE_SIZE};
+use crate::domain::product_history::GetProductError;
+use crate::domain::schedule::{AdaptiveTarget, GetTargetSchedulesError, InvalidScheduleError, TargetSchedule};
+use crate::domain::scrape_event::ScrapeEvent;
+use crate::domain::search::SearchProductsError;
+use askama_axum::{IntoResponse, Response};
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use chrono::{DateTime, Local, TimeDelta, Utc};
+use futures_util::{stream, StreamExt};
+use serde_json::json;
+use std::convert::Infallible;
+use std::future::Future;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+
+pub mod amiami_api_routes;
 pub mod amiami_routes;
-pub mod melonbooks_routes;
\ No newline at end of file
+pub mod api;
+pub mod auth_routes;
+pub mod booth_api_routes;
+pub mod booth_routes;
+pub mod digital_api_routes;
+pub mod digital_routes;
+pub mod feeds;
+pub mod figure_api_routes;
+pub mod figure_routes;
+pub mod image_routes;
+pub mod mandarake_api_routes;
+pub mod mandarake_routes;
+pub mod melonbooks_api_routes;
+pub mod melonbooks_routes;
+pub mod product_index_api_routes;
+pub mod product_index_routes;
+pub mod site_api_routes;
+pub mod site_routes;
+pub mod stats;
+pub mod surugaya_api_routes;
+pub mod surugaya_routes;
+pub mod toranoana_api_routes;
+pub mod toranoana_routes;
+
+pub struct Pagination {
+    page: u32,
+    total_pages: u32,
+    total_items: i64,
+    base_url: String,
+}
+
+impl Pagination {
+    /// `query` is the encoded query string of the current view without the page parameter.
+    pub fn new(path: &str, query: &str, page: u32, total_pages: u32, total_items: i64) -> Self {
+        let base_url = match query.is_empty() {
+            true => format!("{}?", path),
+            false => format!("{}?{}&", path, query),
+        };
+        Self { page, total_pages, total_items, base_url }
+    }
+
+    pub fn page(&self) -> u32 { self.page }
+    pub fn total_pages(&self) -> u32 { self.total_pages }
+    pub fn total_items(&self) -> i64 { self.total_items }
+
+    pub fn has_previous(&self) -> bool {
+        self.page > 1
+    }
+
+    pub fn has_next(&self) -> bool {
+        self.page < self.total_pages
+    }
+
+    pub fn first_url(&self) -> String {
+        self.page_url(1)
+    }
+
+    pub fn previous_url(&self) -> String {
+        self.page_url(self.page.saturating_sub(1).max(1))
+    }
+
+    pub fn next_url(&self) -> String {
+        self.page_url((self.page + 1).min(self.total_pages))
+    }
+
+    pub fn last_url(&self) -> String {
+        self.page_url(self.total_pages)
+    }
+
+    fn page_url(&self, page: u32) -> String {
+        format!("{}page={}", self.base_url, page)
+    }
+}
+
+/// A page of the overview of a site that shows the products of the followed targets, or only of the selected one.
+/// `selected` is the name of its query parameter and the id of the selected target, the page links keep it.
+pub async fn target_products_page<P, E, F, Fut>(path: &str, followed_ids: Vec<i32>, selected: Option<(&str, i32)>, page: Option<u32>, get_page: F) -> Result<(Vec<P>, Pagination), E>
+where
+    F: FnOnce(Vec<i32>, PageRequest) -> Fut,
+    Fut: Future<Output = Result<Page<P>, E>>,
+{
+    let target_ids = match selected {
+        Some((_, id)) => vec![id],
+        None => followed_ids,
+    };
+    let products = get_page(target_ids, PageRequest::new(page.unwrap_or(1), DEFAULT_PAGE_SIZE)).await?;
+    let query = selected
+        .map(|(name, id)| serde_urlencoded::to_string([(name, id)]).unwrap_or_default())
+        .unwrap_or_default();
+    let pagination = Pagination::new(path, &query, products.page(), products.total_pages(), products.total_items());
+    Ok((products.into_items(), pagination))
+}
+
+/// When the followed artists or categories of a site are scraped next, for `target-runs.html`.
+pub struct TargetRuns {
+    runs: Vec<TargetRun>,
+}
+
+pub struct TargetRun {
+    name: String,
+    interval: String,
+    next_run: String,
+}
+
+impl TargetRuns {
+    /// Targets on their own schedule and by their activity, only those the user follows by `is_followed` of their name.
+    pub fn new(schedules: &[TargetSchedule], adaptive_targets: &[AdaptiveTarget], now: DateTime<Utc>, is_followed: impl Fn(&str) -> bool) -> Self {
+        let scheduled = schedules.iter()
+            .filter(|s| is_followed(s.name()))
+            .map(|s| TargetRun {
+                name: s.name().to_owned(),
+                interval: s.schedule().to_string(),
+                next_run: s.schedule().next_run(&now.with_timezone(&Local))
+                    .map(|d| format_date(d.to_utc()))
+                    .unwrap_or_else(|| "-".to_owned()),
+            });
+        let adaptive = adaptive_targets.iter()
+            .filter(|t| is_followed(t.name()))
+            .map(|t| TargetRun {
+                name: t.name().to_owned(),
+                interval: format!("{} ({} new or restocked)", format_duration(t.interval()), t.activity()),
+                next_run: match t.next_run() {
+                    Some(next_run) if next_run > now => format_date(next_run),
+                    _ => "due".to_owned(),
+                },
+            });
+        let mut runs = scheduled.chain(adaptive).collect::<Vec<_>>();
+        runs.sort_by(|a, b| a.name.cmp(&b.name));
+        Self { runs }
+    }
+
+    pub fn runs(&self) -> &[TargetRun] { &self.runs }
+    pub fn is_empty(&self) -> bool { self.runs.is_empty() }
+}
+
+impl TargetRun {
+    pub fn name(&self) -> &str { &self.name }
+    /// The cron expression or the current interval by activity.
+    pub fn interval(&self) -> &str { &self.interval }
+    pub fn next_run(&self) -> &str { &self.next_run }
+}
+
+fn format_date(date: DateTime<Utc>) -> String {
+    date.format("%Y-%m-%d %H:%M").to_string()
+}
+
+pub fn format_duration(duration: TimeDelta) -> String {
+    match (duration.num_days(), duration.num_hours() % 24, duration.num_minutes() % 60) {
+        (0, 0, minutes) => format!("{}m", minutes),
+        (0, hours, minutes) => format!("{}h {}m", hours, minutes),
+        (days, hours, _) => format!("{}d {}h", days, hours),
+    }
+}
+
+impl IntoResponse for SearchProductsError {
+    fn into_response(self) -> Response {
+        match self {
+            e @ SearchProductsError::QueryTooShort { .. } => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            SearchProductsError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for GetProductError {
+    fn into_response(self) -> Response {
+        match self {
+            e @ GetProductError::ProductMissing { .. } => (StatusCode::NOT_FOUND, e.to_string()).into_response(),
+            GetProductError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for GetListingsError {
+    fn into_response(self) -> Response {
+        match self {
+            GetListingsError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for InvalidScheduleError {
+    fn into_response(self) -> Response {
+        (StatusCode::BAD_REQUEST, self.to_string()).into_response()
+    }
+}
+
+impl IntoResponse for GetTargetSchedulesError {
+    fn into_response(self) -> Response {
+        match self {
+            GetTargetSchedulesError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+/// Streams the scrape events relevant for the user, products are sent as the html of their card.
+pub fn scrape_event_stream<P, F>(receiver: broadcast::Receiver<ScrapeEvent<P>>, user_id: i32, render_product: F) -> axum::response::Response
+where
+    P: Clone + Send + 'static,
+    F: Fn(&P) -> String + Send + 'static,
+{
+    let events = stream::unfold(receiver, move |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) if event.is_for_user(user_id) => return Some((event, receiver)),
+                Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    let events = events.map(move |event| Ok::<_, Infallible>(match event {
+        ScrapeEvent::Started { targets } => Event::default().event("started").data(json!({ "targets": targets }).to_string()),
+        ScrapeEvent::Progress { target, index, targets, pages_fetched } => Event::default().event("progress")
+            .data(json!({ "target": target, "index": index, "targets": targets, "pages_fetched": pages_fetched }).to_string()),
+        ScrapeEvent::NewProduct { product, .. } => Event::default().event("new-product").data(render_product(&product)),
+        ScrapeEvent::RestockedProduct { product, .. } => Event::default().event("restocked-product").data(render_product(&product)),
+        ScrapeEvent::Finished => Event::default().event("finished").data("{}"),
+    }));
+    axum::response::IntoResponse::into_response(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::domain::schedule::{AdaptiveInterval, Schedule};
+
+    #[tokio::test]
+    async fn test_scrape_event_stream() {
+        let (sender, receiver) = broadcast::channel(8);
+        sender.send(ScrapeEvent::Progress { target: "artist".to_owned(), index: 0, targets: 2, pages_fetched: 3 }).unwrap();
+        sender.send(ScrapeEvent::NewProduct { product: 1, followers: vec![2] }).unwrap();
+        sender.send(ScrapeEvent::RestockedProduct { product: 2, followers: vec![1] }).unwrap();
+        sender.send(ScrapeEvent::Finished).unwrap();
+        drop(sender);
+
+        let response = scrape_event_stream(receiver, 1, |product: &i32| format!("<div>{}</div>", product));
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(body, concat!(
+            "event: progress\ndata: {\"index\":0,\"pages_fetched\":3,\"target\":\"artist\",\"targets\":2}\n\n",
+            "event: restocked-product\ndata: <div>2</div>\n\n",
+            "event: finished\ndata: {}\n\n",
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_target_products_page() {
+        let get_page = |ids: Vec<i32>, page: PageRequest| async move { Ok::<_, Infallible>(Page::new(ids, page, 120)) };
+        let (ids, pagination) = target_products_page("/toranoana", vec![1, 2], None, Some(2), get_page).await.unwrap();
+        assert_eq!(ids, vec![1, 2]);
+        assert_eq!(pagination.page(), 2);
+        assert_eq!(pagination.total_pages(), 3);
+        assert_eq!(pagination.next_url(), "/toranoana?page=3");
+
+        let (ids, pagination) = target_products_page("/toranoana", vec![1, 2], Some(("selected_creator", 2)), None, get_page).await.unwrap();
+        assert_eq!(ids, vec![2]);
+        assert_eq!(pagination.next_url(), "/toranoana?selected_creator=2&page=2");
+    }
+
+    #[test]
+    fn test_target_runs() {
+        let now = DateTime::parse_from_rfc3339("2026-10-19T12:00:00Z").unwrap().to_utc();
+        let bounds = AdaptiveInterval::new(TimeDelta::hours(1), TimeDelta::hours(24));
+        let schedules = vec![
+            TargetSchedule::new("melonbooks:1".parse().unwrap(), "mafuyu".to_owned(), Schedule::parse("0 */15 * * * *").unwrap()),
+            TargetSchedule::new("melonbooks:4".parse().unwrap(), "unfollowed".to_owned(), Schedule::parse("0 0 0 1 1 *").unwrap()),
+        ];
+        let adaptive_targets = vec![
+            AdaptiveTarget::new("melonbooks:2".parse().unwrap(), "kantoku".to_owned(), 7, Some(now - TimeDelta::hours(2)), &bounds),
+            AdaptiveTarget::new("melonbooks:3".parse().unwrap(), "atelier".to_owned(), 0, None, &bounds),
+        ];
+        let target_runs = TargetRuns::new(&schedules, &adaptive_targets, now, |name| name != "unfollowed");
+        let runs = target_runs.runs().iter().map(|r| (r.name(), r.interval(), r.next_run())).collect::<Vec<_>>();
+        assert_eq!(runs, vec![
+            ("atelier", "1d 0h (0 new or restocked)", "due"),
+            ("kantoku", "12h 0m (7 new or restocked)", "2026-10-19 22:00"),
+            ("mafuyu", "0 */15 * * * *", "2026-10-19 12:15"),
+        ]);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(TimeDelta::minutes(42)), "42m");
+        assert_eq!(format_duration(TimeDelta::hours(50)), "2d 2h");
+    }
+}
@@ -0,0 +1,129 @@
+use crate::domain::site::find_site;
+use crate::domain::availability::Availability;
+use crate::domain::product_index::models::product::{GetIndexedProductsError, IndexFilter, IndexedProduct};
+use crate::domain::product_index::models::target::{FollowTarget, GetFollowTargetsError, TargetId};
+use crate::inbound::http::auth::AuthContext;
+use crate::inbound::http::handlers::api::{ApiError, ApiErrorBody, ApiQuery, PageParams, PageResponse};
+use crate::inbound::http::AppState;
+use axum::extract::State;
+use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct IndexedProductResponse {
+    site: String,
+    id: i32,
+    date_added: DateTime<Utc>,
+    url: String,
+    title: String,
+    image_url: String,
+    /// The cached image below `/images/{hash}`, `None` until it was downloaded.
+    image_hash: Option<String>,
+    price: Option<i32>,
+    #[schema(value_type = String)]
+    availability: Availability,
+}
+
+impl From<IndexedProduct> for IndexedProductResponse {
+    fn from(p: IndexedProduct) -> Self {
+        Self {
+            site: p.site().id().to_owned(),
+            id: p.product_id(),
+            date_added: p.date_added(),
+            url: p.url().to_owned(),
+            title: p.title().to_owned(),
+            image_url: p.image_url().to_owned(),
+            image_hash: p.image_hash().map(|h| h.to_owned()),
+            price: p.price(),
+            availability: p.availability().clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FollowTargetResponse {
+    /// `<site>:<id>`, the `target` of `/api/v1/products`.
+    target: String,
+    site: String,
+    name: String,
+}
+
+impl From<FollowTarget> for FollowTargetResponse {
+    fn from(t: FollowTarget) -> Self {
+        Self {
+            target: t.target_id().to_string(),
+            site: t.site().id().to_owned(),
+            name: t.name().to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct IndexParams {
+    /// Id of a site, e.g. `melonbooks`.
+    pub site: Option<String>,
+    /// A follow target as `<site>:<id>`, see `/api/v1/products/targets`.
+    pub target: Option<String>,
+    /// Only products of targets the user follows.
+    pub followed: Option<bool>,
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+impl IndexParams {
+    fn filter(&self) -> Result<IndexFilter, ApiError> {
+        let site = match &self.site {
+            Some(site) => Some(find_site(site).ok_or_else(|| ApiError::bad_request(format!("unknown site '{}'", site)))?),
+            None => None,
+        };
+        let target = match &self.target {
+            Some(target) => Some(target.parse::<TargetId>().map_err(ApiError::bad_request)?),
+            None => None,
+        };
+        Ok(IndexFilter::new()
+            .with_site(site)
+            .with_target(target)
+            .with_followed(self.followed.unwrap_or(false)))
+    }
+}
+
+#[utoipa::path(get, path = "/v1/products", tag = "products", params(IndexParams), responses(
+    (status = 200, description = "Products of all sites, the newest first", body = PageResponse<IndexedProductResponse>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_products(State(state): State<AppState>, auth: AuthContext, ApiQuery(params): ApiQuery<IndexParams>) -> Result<Json<PageResponse<IndexedProductResponse>>, ApiError> {
+    let filter = params.filter()?;
+    let page = PageParams { page: params.page, page_size: params.page_size }.page_request();
+    let products = state.product_index_service.get_products(auth.user(), &filter, page).await?;
+    Ok(Json(products.into()))
+}
+
+#[utoipa::path(get, path = "/v1/products/targets", tag = "products", responses(
+    (status = 200, description = "Artists, categories, searches, shops and circles the user follows on all sites", body = Vec<FollowTargetResponse>),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_follow_targets(State(state): State<AppState>, auth: AuthContext) -> Result<Json<Vec<FollowTargetResponse>>, ApiError> {
+    let targets = state.product_index_service.get_follow_targets(auth.user()).await?;
+    Ok(Json(targets.into_iter().map(|t| t.into()).collect()))
+}
+
+impl From<GetIndexedProductsError> for ApiError {
+    fn from(e: GetIndexedProductsError) -> Self {
+        match e {
+            e @ GetIndexedProductsError::TargetOfOtherSite { .. } => ApiError::bad_request(e),
+            GetIndexedProductsError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<GetFollowTargetsError> for ApiError {
+    fn from(e: GetFollowTargetsError) -> Self {
+        match e {
+            GetFollowTargetsError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use crate::domain::pagination::{PageRequest, DEFAULT_PAGE_SIZE};
+use crate::domain::product_index::models::product::{GetIndexedProductsError, IndexFilter, IndexedProduct};
+use crate::domain::product_index::models::target::{FollowTarget, GetFollowTargetsError, TargetId};
+use crate::domain::site::{find_site, SITES};
+use crate::domain::site::Site;
+use crate::inbound::http::auth::AuthContext;
+use crate::inbound::http::handlers::Pagination;
+use crate::inbound::http::AppState;
+use askama::Template;
+use askama_axum::{IntoResponse, Response};
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, NoneAsEmptyString};
+
+#[derive(Template)]
+#[template(path = "products.html")]
+struct ProductsTemplate {
+    auth: AuthContext,
+    products: Vec<IndexedProduct>,
+    params: IndexParams,
+    sites: Vec<Site>,
+    targets: Vec<FollowTarget>,
+    page_sizes: Vec<u32>,
+    pagination: Pagination,
+}
+
+impl ProductsTemplate {
+    fn format_date(date: DateTime<Utc>) -> String {
+        date.format("%Y-%m-%d %H:%M").to_string()
+    }
+
+    fn is_selected(&self, field: &str, value: &str) -> bool {
+        let params = &self.params;
+        let current = match field {
+            "site" => params.site.clone(),
+            "target" => params.target.map(|t| t.to_string()),
+            "page_size" => Some(params.page_size.unwrap_or(DEFAULT_PAGE_SIZE).to_string()),
+            _ => None,
+        };
+        current.as_deref() == Some(value)
+    }
+
+    fn is_followed(&self) -> bool {
+        self.params.followed.unwrap_or(false)
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct IndexParams {
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub site: Option<String>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<TargetId>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub followed: Option<bool>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<u32>,
+}
+
+impl IndexParams {
+    fn pagination(&self, page: u32, total_pages: u32, total_items: i64) -> Pagination {
+        let params = IndexParams { page: None, ..self.clone() };
+        let query = serde_urlencoded::to_string(&params).unwrap_or_default();
+        Pagination::new("/products", &query, page, total_pages, total_items)
+    }
+
+    fn page_request(&self) -> PageRequest {
+        PageRequest::new(self.page.unwrap_or(1), self.page_size.unwrap_or(DEFAULT_PAGE_SIZE))
+    }
+}
+
+pub async fn get_products(State(state): State<AppState>, auth: AuthContext, Query(params): Query<IndexParams>) -> Response {
+    let site = match params.site.as_deref().map(|s| (s, find_site(s))) {
+        Some((site, None)) => return (StatusCode::BAD_REQUEST, format!("unknown site '{}'", site)).into_response(),
+        Some((_, site)) => site,
+        None => None,
+    };
+    let targets = match state.product_index_service.get_follow_targets(auth.user()).await {
+        Ok(t) => t,
+        Err(e) => return e.into_response()
+    };
+    let filter = IndexFilter::new()
+        .with_site(site)
+        .with_target(params.target)
+        .with_followed(params.followed.unwrap_or(false));
+    let page = match state.product_index_service.get_products(auth.user(), &filter, params.page_request()).await {
+        Ok(p) => p,
+        Err(e) => return e.into_response()
+    };
+    let pagination = params.pagination(page.page(), page.total_pages(), page.total_items());
+    let template = ProductsTemplate {
+        auth,
+        products: page.into_items(),
+        params,
+        sites: SITES.to_vec(),
+        targets,
+        page_sizes: vec![25, DEFAULT_PAGE_SIZE, 100, 200],
+        pagination,
+    };
+    template.into_response()
+}
+
+impl IntoResponse for GetIndexedProductsError {
+    fn into_response(self) -> Response {
+        match self {
+            e @ GetIndexedProductsError::TargetOfOtherSite { .. } => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            GetIndexedProductsError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for GetFollowTargetsError {
+    fn into_response(self) -> Response {
+        match self {
+            GetFollowTargetsError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use crate::domain::site::SiteService;
+use crate::inbound::http::handlers::api::{ApiError, ApiErrorBody};
+use axum::http::StatusCode;
+use axum::Extension;
+use log::error;
+use std::sync::Arc;
+
+/// Starts a scrape in the background, scrapes take far longer than a request should.
+#[utoipa::path(post, path = "/scrape", responses(
+    (status = 202, description = "Scrape is started"),
+    (status = 409, description = "A scrape of the site is running", body = ApiErrorBody),
+))]
+pub async fn scrape(Extension(service): Extension<Arc<dyn SiteService>>) -> Result<StatusCode, ApiError> {
+    let site = service.site();
+    let guard = service.scrape_lock().try_lock()
+        .ok_or_else(|| ApiError::conflict(format!("a scrape of {} is running", site)))?;
+    tokio::spawn(async move {
+        if let Err(e) = service.scrape_all(&guard).await {
+            error!("error scraping {} products: {:#}", site.id(), e);
+        }
+    });
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::domain::site::{ScrapeGuard, ScrapeLock, ScrapeSiteError, Site};
+    use async_trait::async_trait;
+    use axum::response::IntoResponse;
+
+    struct TestSiteService {
+        scrape_lock: ScrapeLock,
+    }
+
+    #[async_trait]
+    impl SiteService for TestSiteService {
+        fn site(&self) -> Site { Site::new("test", "Test", "https://test.moe/") }
+        fn scrape_lock(&self) -> &ScrapeLock { &self.scrape_lock }
+
+        async fn scrape(&self, _guard: &ScrapeGuard) -> Result<(), ScrapeSiteError> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scrape_while_running() {
+        let service: Arc<dyn SiteService> = Arc::new(TestSiteService { scrape_lock: ScrapeLock::new() });
+        let running = service.scrape_lock().lock().await;
+
+        let response = scrape(Extension(service.clone())).await.unwrap_err().into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        drop(running);
+        assert_eq!(scrape(Extension(service)).await.unwrap(), StatusCode::ACCEPTED);
+    }
+}
@@ -0,0 +1,62 @@
+use crate::domain::pagination::PageRequest;
+use crate::domain::product_index::models::product::{IndexFilter, IndexSort};
+use crate::domain::product_index::models::target::TargetId;
+use crate::domain::site::{Site, SiteService};
+use crate::inbound::http::auth::AuthContext;
+use crate::inbound::http::handlers::feeds::{feed_response, Feed, FeedEntry, FeedFormat, FEED_SIZE};
+use crate::inbound::http::AppState;
+use askama_axum::{IntoResponse, Response};
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, Uri};
+use axum::Extension;
+use std::sync::Arc;
+
+pub async fn get_feed(State(state): State<AppState>, Extension(service): Extension<Arc<dyn SiteService>>, auth: AuthContext, uri: Uri, headers: HeaderMap) -> Response {
+    get_feed_response(state, service.site(), None, auth, &uri, &headers).await
+}
+
+pub async fn get_target_feed(State(state): State<AppState>, Extension(service): Extension<Arc<dyn SiteService>>, auth: AuthContext, Path(target_id): Path<i32>, uri: Uri, headers: HeaderMap) -> Response {
+    get_feed_response(state, service.site(), Some(TargetId::new(service.site(), target_id)), auth, &uri, &headers).await
+}
+
+/// The latest new and restocked products of the site, or of one of its artists, searches, shops...
+async fn get_feed_response(state: AppState, site: Site, target: Option<TargetId>, auth: AuthContext, uri: &Uri, headers: &HeaderMap) -> Response {
+    let filter = IndexFilter::new()
+        .with_site(Some(site))
+        .with_target(target)
+        .with_sort(IndexSort::DateChanged);
+    let products = match state.product_index_service.get_products(auth.user(), &filter, PageRequest::new(1, FEED_SIZE)).await {
+        Ok(p) => p.into_items(),
+        Err(e) => return e.into_response()
+    };
+    let (id, title) = match target {
+        Some(target) => {
+            let targets = match state.product_index_service.get_follow_targets(auth.user()).await {
+                Ok(t) => t,
+                Err(e) => return e.into_response()
+            };
+            let name = targets.iter()
+                .find(|t| t.target_id() == target)
+                .map(|t| t.name().to_owned())
+                .unwrap_or_else(|| target.id().to_string());
+            (format!("urn:moe-scraper:{}:target:{}", site.id(), target.id()), format!("{} - {}", site.name(), name))
+        }
+        None => (format!("urn:moe-scraper:{}", site.id()), site.name().to_owned()),
+    };
+    let product_ids = products.iter().map(|p| p.product_id()).collect::<Vec<_>>();
+    let dates_updated = match state.product_index_service.get_dates_updated(site, &product_ids).await {
+        Ok(d) => d,
+        Err(e) => return e.into_response()
+    };
+    let entries = products.iter()
+        .map(|p| FeedEntry::new(
+            format!("{}:{}", site.id(), p.product_id()),
+            p.title().to_owned(),
+            p.url().to_owned(),
+            p.image_url().to_owned(),
+            p.availability().to_string(),
+            p.date_added(),
+        ).with_price(p.price().map(|p| format!("¥{}", p))).with_date_restocked(p.date_restocked()).with_date_updated(dates_updated.get(&p.product_id()).copied()))
+        .collect();
+    feed_response(&Feed::new(id, title, site.url().to_owned(), entries), FeedFormat::from_path(uri.path()), headers)
+}
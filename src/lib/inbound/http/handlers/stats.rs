@@ -0,0 +1,44 @@
+use crate::domain::availability_stats::AvailabilityStats;
+use crate::inbound::http::auth::AuthContext;
+use crate::inbound::http::handlers::format_duration;
+use askama::Template;
+use chrono::TimeDelta;
+
+#[derive(Template)]
+#[template(path = "availability-stats.html")]
+pub struct AvailabilityStatsTemplate {
+    pub auth: AuthContext,
+    /// Name of the site in the heading.
+    pub site: &'static str,
+    /// Path the product detail pages are nested under, e.g. `/melonbooks`.
+    pub base_path: &'static str,
+    /// What the products are grouped by, e.g. `Artist`.
+    pub group_label: &'static str,
+    pub stats: AvailabilityStats,
+}
+
+impl AvailabilityStatsTemplate {
+    fn format_duration(duration: TimeDelta) -> String {
+        format_duration(duration)
+    }
+
+    /// Height of a chart bar in percent of the busiest day.
+    fn bar_height(&self, changes: usize) -> usize {
+        match self.stats.max_day_changes() {
+            0 => 0,
+            max => changes * 100 / max,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(AvailabilityStatsTemplate::format_duration(TimeDelta::minutes(42)), "42m");
+        assert_eq!(AvailabilityStatsTemplate::format_duration(TimeDelta::minutes(125)), "2h 5m");
+        assert_eq!(AvailabilityStatsTemplate::format_duration(TimeDelta::hours(50)), "2d 2h");
+    }
+}
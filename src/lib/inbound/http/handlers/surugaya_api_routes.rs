@@ -0,0 +1,162 @@
+use crate::domain::availability::Availability;
+use crate::domain::surugaya::models::condition::Condition;
+use crate::domain::surugaya::models::product::{GetProductsError, Product};
+use crate::domain::surugaya::models::search::{DeleteSearchError, GetSearchesError, SaveSearchError, Search};
+use crate::domain::surugaya::ports::SurugayaService;
+use crate::inbound::http::auth::AuthContext;
+use crate::inbound::http::handlers::api::{ApiError, ApiErrorBody, ApiJson, ApiPath, ApiQuery, PageParams, PageResponse};
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchResponse {
+    id: i32,
+    date_added: DateTime<Utc>,
+    keyword: String,
+    date_saved: Option<DateTime<Utc>>,
+}
+
+impl From<Search> for SearchResponse {
+    fn from(s: Search) -> Self {
+        Self {
+            id: s.id(),
+            date_added: s.date_added(),
+            keyword: s.keyword().to_owned(),
+            date_saved: s.date_followed(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProductResponse {
+    id: i32,
+    date_added: DateTime<Utc>,
+    url: String,
+    title: String,
+    image_url: String,
+    #[schema(value_type = String)]
+    condition: Condition,
+    price: Option<i32>,
+    #[schema(value_type = String)]
+    availability: Availability,
+}
+
+impl From<Product> for ProductResponse {
+    fn from(p: Product) -> Self {
+        Self {
+            id: p.id(),
+            date_added: p.date_added(),
+            url: p.url().to_owned(),
+            title: p.title().to_owned(),
+            image_url: p.image_url().to_owned(),
+            condition: p.condition(),
+            price: p.price(),
+            availability: p.availability(),
+        }
+    }
+}
+
+#[utoipa::path(get, path = "/searches", tag = "surugaya", params(PageParams), responses(
+    (status = 200, description = "Searches saved by the user", body = PageResponse<SearchResponse>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_searches(Extension(service): Extension<Arc<dyn SurugayaService>>, auth: AuthContext, ApiQuery(params): ApiQuery<PageParams>) -> Result<Json<PageResponse<SearchResponse>>, ApiError> {
+    let searches = service.get_saved_searches_page(auth.user(), params.page_request()).await?;
+    Ok(Json(searches.into()))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SaveSearchRequest {
+    pub keyword: String,
+}
+
+#[utoipa::path(post, path = "/searches", tag = "surugaya", request_body = SaveSearchRequest, responses(
+    (status = 204, description = "Search is saved"),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 409, description = "Search is already saved", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn save_search(Extension(service): Extension<Arc<dyn SurugayaService>>, auth: AuthContext, ApiJson(body): ApiJson<SaveSearchRequest>) -> Result<StatusCode, ApiError> {
+    let keyword = body.keyword.trim();
+    if keyword.is_empty() {
+        return Err(ApiError::bad_request("search keyword must not be empty"));
+    }
+    service.save_search(auth.user(), keyword).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(delete, path = "/searches/{search_id}", tag = "surugaya", params(("search_id" = i32, Path)), responses(
+    (status = 204, description = "Search is deleted"),
+    (status = 404, description = "Unknown search", body = ApiErrorBody),
+    (status = 409, description = "Search is not saved", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn delete_search(Extension(service): Extension<Arc<dyn SurugayaService>>, auth: AuthContext, ApiPath(search_id): ApiPath<i32>) -> Result<StatusCode, ApiError> {
+    service.delete_search(auth.user(), search_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(get, path = "/searches/{search_id}/products", tag = "surugaya", params(("search_id" = i32, Path), PageParams), responses(
+    (status = 200, description = "Listings found by the search", body = PageResponse<ProductResponse>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 404, description = "Unknown search", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_search_products(Extension(service): Extension<Arc<dyn SurugayaService>>, auth: AuthContext, ApiPath(search_id): ApiPath<i32>, ApiQuery(params): ApiQuery<PageParams>) -> Result<Json<PageResponse<ProductResponse>>, ApiError> {
+    let searches = service.get_searches(auth.user()).await?;
+    if !searches.iter().any(|s| s.id() == search_id) {
+        return Err(ApiError::not_found(format!("unknown search with id '{}'", search_id)));
+    }
+    let products = service.get_products_page_by_search(search_id, params.page_request()).await?;
+    Ok(Json(products.into()))
+}
+
+#[utoipa::path(get, path = "/products", tag = "surugaya", params(PageParams), responses(
+    (status = 200, description = "All listings", body = PageResponse<ProductResponse>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_products(Extension(service): Extension<Arc<dyn SurugayaService>>, ApiQuery(params): ApiQuery<PageParams>) -> Result<Json<PageResponse<ProductResponse>>, ApiError> {
+    let products = service.get_products_page(params.page_request()).await?;
+    Ok(Json(products.into()))
+}
+
+impl From<SaveSearchError> for ApiError {
+    fn from(e: SaveSearchError) -> Self {
+        match e {
+            e @ SaveSearchError::AlreadySavedError(_) => ApiError::conflict(e),
+            SaveSearchError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<DeleteSearchError> for ApiError {
+    fn from(e: DeleteSearchError) -> Self {
+        match e {
+            e @ DeleteSearchError::UnknownSearch { .. } => ApiError::not_found(e),
+            e @ DeleteSearchError::SearchNotFollowed { .. } => ApiError::conflict(e),
+            DeleteSearchError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<GetSearchesError> for ApiError {
+    fn from(e: GetSearchesError) -> Self {
+        match e {
+            GetSearchesError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<GetProductsError> for ApiError {
+    fn from(e: GetProductsError) -> Self {
+        match e {
+            GetProductsError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use crate::domain::duplicate::models::listing::Listing;
+use crate::domain::surugaya::models::product::{GetProductsError, Product, ProductHistoryEntry};
+use crate::domain::surugaya::models::search::{DeleteSearchError, GetSearchesError, SaveSearchError, Search};
+use crate::domain::surugaya::ports::SurugayaService;
+use crate::domain::surugaya::SITE;
+use crate::domain::product_history::ProductChange;
+use crate::inbound::http::auth::AuthContext;
+use crate::inbound::http::handlers::{target_products_page, Pagination};
+use crate::inbound::http::AppState;
+use askama::Template;
+use askama_axum::{IntoResponse, Response};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::{Extension, Form};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_with::{serde_as, NoneAsEmptyString};
+use std::sync::Arc;
+
+#[derive(Template)]
+#[template(path = "surugaya.html")]
+struct SurugayaTemplate {
+    auth: AuthContext,
+    products: Vec<Product>,
+    searches: Vec<Search>,
+    selected_search: Option<Search>,
+    pagination: Pagination,
+}
+
+impl SurugayaTemplate {
+    fn format_date(date: DateTime<Utc>) -> String {
+        date.format("%Y-%m-%d %H:%M").to_string()
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OverviewParams {
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    pub selected_search: Option<i32>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    pub page: Option<u32>,
+}
+
+pub async fn get_overview(Extension(service): Extension<Arc<dyn SurugayaService>>, auth: AuthContext, Query(params): Query<OverviewParams>) -> Response {
+    get_overview_response(service, auth, params).await
+}
+
+#[derive(Template)]
+#[template(path = "surugaya-product.html")]
+struct SurugayaProductTemplate {
+    auth: AuthContext,
+    product: Product,
+    history: Vec<ProductHistoryEntry>,
+    listings: Vec<Listing>,
+}
+
+impl SurugayaProductTemplate {
+    fn format_date(date: DateTime<Utc>) -> String {
+        SurugayaTemplate::format_date(date)
+    }
+
+    fn format_price(&self, price: &Option<i32>) -> String {
+        price.map(|p| format!("¥{}", p)).unwrap_or_else(|| "-".to_owned())
+    }
+}
+
+pub async fn get_product(State(state): State<AppState>, Extension(service): Extension<Arc<dyn SurugayaService>>, auth: AuthContext, Path(product_id): Path<i32>) -> Response {
+    let product = match service.get_product(product_id).await {
+        Ok(p) => p,
+        Err(e) => return e.into_response()
+    };
+    let history = match service.get_product_history(product_id).await {
+        Ok(h) => h,
+        Err(e) => return e.into_response()
+    };
+    let listings = match state.duplicate_service.get_duplicate_listings(SITE, product_id, product.image_phash()).await {
+        Ok(l) => l,
+        Err(e) => return e.into_response()
+    };
+    SurugayaProductTemplate { auth, product, history, listings }.into_response()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PostSearchForm {
+    keyword: String,
+}
+
+pub async fn post_search(Extension(service): Extension<Arc<dyn SurugayaService>>, auth: AuthContext, Form(input): Form<PostSearchForm>) -> Response {
+    let keyword = input.keyword.trim();
+    if keyword.is_empty() {
+        return (StatusCode::BAD_REQUEST, "search keyword must not be empty").into_response();
+    }
+    if let Err(e) = service.save_search(auth.user(), keyword).await {
+        return e.into_response();
+    }
+    get_overview_response(service, auth, OverviewParams::default()).await
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DeleteSearchForm {
+    selected_search_id: i32
+}
+
+pub async fn delete_search(Extension(service): Extension<Arc<dyn SurugayaService>>, auth: AuthContext, Form(input): Form<DeleteSearchForm>) -> Response {
+    if let Err(e) = service.delete_search(auth.user(), input.selected_search_id).await {
+        return e.into_response();
+    }
+    get_overview_response(service, auth, OverviewParams::default()).await
+}
+
+async fn get_overview_response(service: Arc<dyn SurugayaService>, auth: AuthContext, params: OverviewParams) -> Response {
+    let searches = match service.get_saved_searches(auth.user()).await {
+        Ok(s) => s,
+        Err(e) => return e.into_response()
+    };
+    let selected_search = match params.selected_search {
+        Some(id) => searches.iter().find(|s| s.id() == id).cloned(),
+        None => None
+    };
+    let saved_ids = searches.iter().map(|s| s.id()).collect();
+    let selected = selected_search.as_ref().map(|s| ("selected_search", s.id()));
+    let page = target_products_page("/surugaya", saved_ids, selected, params.page, |ids, page| async move {
+        service.get_products_page_by_searches(&ids, page).await
+    }).await;
+    let (products, pagination) = match page {
+        Ok(p) => p,
+        Err(e) => return e.into_response()
+    };
+    let template = SurugayaTemplate {
+        auth,
+        products,
+        searches,
+        selected_search,
+        pagination,
+    };
+    template.into_response()
+}
+
+impl IntoResponse for GetProductsError {
+    fn into_response(self) -> Response {
+        match self {
+            GetProductsError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for GetSearchesError {
+    fn into_response(self) -> Response {
+        match self {
+            GetSearchesError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for SaveSearchError {
+    fn into_response(self) -> Response {
+        match self {
+            e @ SaveSearchError::AlreadySavedError(_) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            SaveSearchError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for DeleteSearchError {
+    fn into_response(self) -> Response {
+        match self {
+            e @ DeleteSearchError::UnknownSearch { .. } => (StatusCode::NOT_FOUND, e.to_string()).into_response(),
+            e @ DeleteSearchError::SearchNotFollowed { .. } => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            DeleteSearchError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use crate::domain::availability::Availability;
+use crate::domain::toranoana::models::creator::{Creator, CreatorArgs, CreatorKind, FollowCreatorError, GetCreatorsError, UnfollowCreatorError};
+use crate::domain::toranoana::models::product::{GetProductsError, Product};
+use crate::domain::toranoana::ports::ToranoanaService;
+use crate::inbound::http::auth::AuthContext;
+use crate::inbound::http::handlers::api::{ApiError, ApiErrorBody, ApiJson, ApiPath, ApiQuery, PageParams, PageResponse};
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreatorResponse {
+    id: i32,
+    date_added: DateTime<Utc>,
+    name: String,
+    #[schema(value_type = String)]
+    kind: CreatorKind,
+    following: bool,
+}
+
+impl From<Creator> for CreatorResponse {
+    fn from(c: Creator) -> Self {
+        Self {
+            id: c.id(),
+            date_added: c.date_added(),
+            name: c.name().to_owned(),
+            kind: c.kind(),
+            following: c.following(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProductResponse {
+    id: i32,
+    date_added: DateTime<Utc>,
+    url: String,
+    title: String,
+    creators: Vec<CreatorResponse>,
+    image_url: String,
+    category: String,
+    price: Option<i32>,
+    #[schema(value_type = String)]
+    availability: Availability,
+}
+
+impl From<Product> for ProductResponse {
+    fn from(p: Product) -> Self {
+        Self {
+            id: p.id(),
+            date_added: p.date_added(),
+            url: p.url().to_owned(),
+            title: p.title().to_owned(),
+            creators: p.creators().iter().cloned().map(|c| c.into()).collect(),
+            image_url: p.image_url().to_owned(),
+            category: p.category().to_owned(),
+            price: p.price(),
+            availability: p.availability(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CreatorListParams {
+    pub following: Option<bool>,
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+#[utoipa::path(get, path = "/creators", tag = "toranoana", params(CreatorListParams), responses(
+    (status = 200, description = "Known artists and circles", body = PageResponse<CreatorResponse>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_creators(Extension(service): Extension<Arc<dyn ToranoanaService>>, auth: AuthContext, ApiQuery(params): ApiQuery<CreatorListParams>) -> Result<Json<PageResponse<CreatorResponse>>, ApiError> {
+    let page = PageParams { page: params.page, page_size: params.page_size }.page_request();
+    let creators = service.get_creators_page(auth.user(), params.following, page).await?;
+    Ok(Json(creators.into()))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct FollowCreatorRequest {
+    pub name: String,
+    #[schema(value_type = String)]
+    pub kind: CreatorKind,
+}
+
+#[utoipa::path(post, path = "/creators", tag = "toranoana", request_body = FollowCreatorRequest, responses(
+    (status = 204, description = "Creator is followed"),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 409, description = "Creator is already followed", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn follow_creator(Extension(service): Extension<Arc<dyn ToranoanaService>>, auth: AuthContext, ApiJson(body): ApiJson<FollowCreatorRequest>) -> Result<StatusCode, ApiError> {
+    let name = body.name.trim();
+    if name.is_empty() {
+        return Err(ApiError::bad_request("creator name must not be empty"));
+    }
+    service.follow_creator(auth.user(), &CreatorArgs::new(name.to_owned(), body.kind)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(delete, path = "/creators/{creator_id}", tag = "toranoana", params(("creator_id" = i32, Path)), responses(
+    (status = 204, description = "Creator is no longer followed"),
+    (status = 404, description = "Unknown creator", body = ApiErrorBody),
+    (status = 409, description = "Creator is not followed", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn unfollow_creator(Extension(service): Extension<Arc<dyn ToranoanaService>>, auth: AuthContext, ApiPath(creator_id): ApiPath<i32>) -> Result<StatusCode, ApiError> {
+    service.unfollow_creator(auth.user(), creator_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(get, path = "/creators/{creator_id}/products", tag = "toranoana", params(("creator_id" = i32, Path), PageParams), responses(
+    (status = 200, description = "Products of the creator", body = PageResponse<ProductResponse>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 404, description = "Unknown creator", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_creator_products(Extension(service): Extension<Arc<dyn ToranoanaService>>, auth: AuthContext, ApiPath(creator_id): ApiPath<i32>, ApiQuery(params): ApiQuery<PageParams>) -> Result<Json<PageResponse<ProductResponse>>, ApiError> {
+    let creators = service.get_creators(auth.user()).await?;
+    if !creators.iter().any(|c| c.id() == creator_id) {
+        return Err(ApiError::not_found(format!("unknown creator with id '{}'", creator_id)));
+    }
+    let products = service.get_products_page_by_creator(creator_id, params.page_request()).await?;
+    Ok(Json(products.into()))
+}
+
+#[utoipa::path(get, path = "/products", tag = "toranoana", params(PageParams), responses(
+    (status = 200, description = "All products", body = PageResponse<ProductResponse>),
+    (status = 400, description = "Invalid request", body = ApiErrorBody),
+    (status = 500, description = "Unexpected error", body = ApiErrorBody),
+))]
+pub async fn get_products(Extension(service): Extension<Arc<dyn ToranoanaService>>, ApiQuery(params): ApiQuery<PageParams>) -> Result<Json<PageResponse<ProductResponse>>, ApiError> {
+    let products = service.get_products_page(params.page_request()).await?;
+    Ok(Json(products.into()))
+}
+
+impl From<FollowCreatorError> for ApiError {
+    fn from(e: FollowCreatorError) -> Self {
+        match e {
+            e @ FollowCreatorError::AlreadyFollowedError(_) => ApiError::conflict(e),
+            FollowCreatorError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<UnfollowCreatorError> for ApiError {
+    fn from(e: UnfollowCreatorError) -> Self {
+        match e {
+            e @ UnfollowCreatorError::UnknownCreator { .. } => ApiError::not_found(e),
+            e @ UnfollowCreatorError::CreatorNotFollowed { .. } => ApiError::conflict(e),
+            UnfollowCreatorError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<GetCreatorsError> for ApiError {
+    fn from(e: GetCreatorsError) -> Self {
+        match e {
+            GetCreatorsError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
+
+impl From<GetProductsError> for ApiError {
+    fn from(e: GetProductsError) -> Self {
+        match e {
+            GetProductsError::Unknown(e) => ApiError::internal(e),
+        }
+    }
+}
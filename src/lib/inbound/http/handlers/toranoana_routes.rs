@@ -0,0 +1,180 @@
+use crate::domain::duplicate::models::listing::Listing;
+use crate::domain::product_history::ProductChange;
+use crate::domain::toranoana::models::creator::{Creator, CreatorArgs, CreatorKind, FollowCreatorError, GetCreatorsError, UnfollowCreatorError};
+use crate::domain::toranoana::models::product::{GetProductsError, Product, ProductHistoryEntry};
+use crate::domain::toranoana::ports::ToranoanaService;
+use crate::domain::toranoana::SITE;
+use crate::inbound::http::auth::AuthContext;
+use crate::inbound::http::handlers::{target_products_page, Pagination};
+use crate::inbound::http::AppState;
+use askama::Template;
+use askama_axum::{IntoResponse, Response};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::{Extension, Form};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_with::{serde_as, NoneAsEmptyString};
+use std::sync::Arc;
+use strum::IntoEnumIterator;
+
+#[derive(Template)]
+#[template(path = "toranoana.html")]
+struct ToranoanaTemplate {
+    auth: AuthContext,
+    products: Vec<Product>,
+    creators: Vec<Creator>,
+    selected_creator: Option<Creator>,
+    kinds: Vec<CreatorKind>,
+    pagination: Pagination,
+}
+
+impl ToranoanaTemplate {
+    fn format_date(date: DateTime<Utc>) -> String {
+        date.format("%Y-%m-%d %H:%M").to_string()
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OverviewParams {
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    pub selected_creator: Option<i32>,
+    #[serde_as(as = "NoneAsEmptyString")]
+    #[serde(default)]
+    pub page: Option<u32>,
+}
+
+pub async fn get_overview(Extension(service): Extension<Arc<dyn ToranoanaService>>, auth: AuthContext, Query(params): Query<OverviewParams>) -> Response {
+    get_overview_response(service, auth, params).await
+}
+
+#[derive(Template)]
+#[template(path = "toranoana-product.html")]
+struct ToranoanaProductTemplate {
+    auth: AuthContext,
+    product: Product,
+    history: Vec<ProductHistoryEntry>,
+    listings: Vec<Listing>,
+}
+
+impl ToranoanaProductTemplate {
+    fn format_date(date: DateTime<Utc>) -> String {
+        ToranoanaTemplate::format_date(date)
+    }
+
+    fn format_price(&self, price: &Option<i32>) -> String {
+        price.map(|p| format!("¥{}", p)).unwrap_or_else(|| "-".to_owned())
+    }
+}
+
+pub async fn get_product(State(state): State<AppState>, Extension(service): Extension<Arc<dyn ToranoanaService>>, auth: AuthContext, Path(product_id): Path<i32>) -> Response {
+    let product = match service.get_product(product_id).await {
+        Ok(p) => p,
+        Err(e) => return e.into_response()
+    };
+    let history = match service.get_product_history(product_id).await {
+        Ok(h) => h,
+        Err(e) => return e.into_response()
+    };
+    let listings = match state.duplicate_service.get_duplicate_listings(SITE, product_id, product.image_phash()).await {
+        Ok(l) => l,
+        Err(e) => return e.into_response()
+    };
+    ToranoanaProductTemplate { auth, product, history, listings }.into_response()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PostCreatorForm {
+    name: String,
+    kind: CreatorKind,
+}
+
+pub async fn post_creator(Extension(service): Extension<Arc<dyn ToranoanaService>>, auth: AuthContext, Form(input): Form<PostCreatorForm>) -> Response {
+    let name = input.name.trim();
+    if name.is_empty() {
+        return (StatusCode::BAD_REQUEST, "creator name must not be empty").into_response();
+    }
+    if let Err(e) = service.follow_creator(auth.user(), &CreatorArgs::new(name.to_owned(), input.kind)).await {
+        return e.into_response();
+    }
+    get_overview_response(service, auth, OverviewParams::default()).await
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DeleteCreatorForm {
+    selected_creator_id: i32
+}
+
+pub async fn delete_creator(Extension(service): Extension<Arc<dyn ToranoanaService>>, auth: AuthContext, Form(input): Form<DeleteCreatorForm>) -> Response {
+    if let Err(e) = service.unfollow_creator(auth.user(), input.selected_creator_id).await {
+        return e.into_response();
+    }
+    get_overview_response(service, auth, OverviewParams::default()).await
+}
+
+async fn get_overview_response(service: Arc<dyn ToranoanaService>, auth: AuthContext, params: OverviewParams) -> Response {
+    let creators = match service.get_followed_creators(auth.user()).await {
+        Ok(c) => c,
+        Err(e) => return e.into_response()
+    };
+    let selected_creator = match params.selected_creator {
+        Some(id) => creators.iter().find(|c| c.id() == id).cloned(),
+        None => None
+    };
+    let followed_ids = creators.iter().map(|c| c.id()).collect();
+    let selected = selected_creator.as_ref().map(|c| ("selected_creator", c.id()));
+    let page = target_products_page("/toranoana", followed_ids, selected, params.page, |ids, page| async move {
+        service.get_products_page_by_creators(&ids, page).await
+    }).await;
+    let (products, pagination) = match page {
+        Ok(p) => p,
+        Err(e) => return e.into_response()
+    };
+    let template = ToranoanaTemplate {
+        auth,
+        products,
+        creators,
+        selected_creator,
+        kinds: CreatorKind::iter().collect(),
+        pagination,
+    };
+    template.into_response()
+}
+
+impl IntoResponse for GetProductsError {
+    fn into_response(self) -> Response {
+        match self {
+            GetProductsError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for GetCreatorsError {
+    fn into_response(self) -> Response {
+        match self {
+            GetCreatorsError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for FollowCreatorError {
+    fn into_response(self) -> Response {
+        match self {
+            e @ FollowCreatorError::AlreadyFollowedError { .. } => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            FollowCreatorError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
+impl IntoResponse for UnfollowCreatorError {
+    fn into_response(self) -> Response {
+        match self {
+            e @ UnfollowCreatorError::UnknownCreator { .. } => (StatusCode::NOT_FOUND, e.to_string()).into_response(),
+            e @ UnfollowCreatorError::CreatorNotFollowed { .. } => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            UnfollowCreatorError::Unknown(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
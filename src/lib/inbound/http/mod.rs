@@ -1,29 +1,51 @@
 use std::fmt::Debug;
-use crate::domain::amiami::ports::AmiamiService;
-use crate::domain::melonbooks::ports::MelonbooksService;
-use crate::inbound::http::handlers::amiami_routes;
+use crate::domain::duplicate::ports::DuplicateService;
+use crate::domain::image::ports::ImageService;
+use crate::domain::product_index::ports::ProductIndexService;
+use crate::domain::user::ports::UserService;
+use crate::inbound::http::handlers::api::ApiError;
+use crate::inbound::http::auth::{Authenticator, HttpAuthConfig};
+use crate::inbound::http::handlers::{auth_routes, image_routes, product_index_api_routes, product_index_routes, site_api_routes, site_routes};
+use crate::inbound::http::openapi::ApiDoc;
+use crate::inbound::http::site::HttpSite;
+use crate::domain::site::Site;
 use anyhow::Context;
+use axum::{middleware, Extension};
 use axum::response::Redirect;
 use axum::routing::{get, post};
-use handlers::melonbooks_routes;
 use log::info;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::net;
 use tower_http::services::ServeDir;
+use utoipa::openapi::tag::TagBuilder;
+use utoipa::OpenApi;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+use utoipa_swagger_ui::SwaggerUi;
 
+pub mod auth;
 mod handlers;
+mod openapi;
+pub mod site;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct HttpServerConfig {
     pub port: u16,
-    pub assets_dir: Option<PathBuf>
+    pub assets_dir: Option<PathBuf>,
+    pub auth: Option<HttpAuthConfig>,
+    /// User of every request when authentication is disabled.
+    pub default_user: String,
 }
 
 #[derive(Clone)]
-struct AppState {
-    melonbooks_service: Arc<dyn MelonbooksService>,
-    amiami_service: Arc<dyn AmiamiService>
+pub struct AppState {
+    user_service: Arc<dyn UserService>,
+    image_service: Arc<dyn ImageService>,
+    duplicate_service: Arc<dyn DuplicateService>,
+    product_index_service: Arc<dyn ProductIndexService>,
+    authenticator: Option<Arc<Authenticator>>,
+    default_user: String,
 }
 
 pub struct HttpServer {
@@ -32,19 +54,56 @@ pub struct HttpServer {
 }
 
 impl HttpServer {
-    pub async fn new<MS: MelonbooksService, AS: AmiamiService>( config: HttpServerConfig, melonbooks_service: Arc<MS>, amiami_service: Arc<AS>) -> Result<Self, anyhow::Error> {
+    /// The first of `sites` is the start page.
+    pub async fn new<US: UserService, IS: ImageService, DS: DuplicateService, PS: ProductIndexService>(
+        config: HttpServerConfig,
+        sites: Vec<Arc<dyn HttpSite>>,
+        user_service: Arc<US>,
+        image_service: Arc<IS>,
+        duplicate_service: Arc<DS>,
+        product_index_service: Arc<PS>,
+    ) -> Result<Self, anyhow::Error> {
         let trace_layer = tower_http::trace::TraceLayer::new_for_http().make_span_with(
             |request: &axum::extract::Request<_>| {
-                let uri = request.uri().to_string();
-                tracing::info_span!("http_request", method = ?request.method(), uri)
+                // only the path, the query may have the feed token
+                let path = request.uri().path();
+                tracing::info_span!("http_request", method = ?request.method(), path)
             },
         );
-        let state = AppState { melonbooks_service, amiami_service };
+        let authenticator = match config.auth {
+            Some(auth) => Some(Arc::new(Authenticator::new(auth)?)),
+            None => None,
+        };
+        let state = AppState { user_service, image_service, duplicate_service, product_index_service, authenticator, default_user: config.default_user };
+        let require_session = middleware::from_fn_with_state(state.clone(), auth::require_session);
+        let require_feed_token = middleware::from_fn_with_state(state.clone(), auth::require_feed_token);
+        let (api_router, api_doc) = api_routes(&sites).split_for_parts();
+        let docs: axum::Router<AppState> = SwaggerUi::new("/api/docs").url("/api/openapi.json", api_doc).into();
+        let start_page = sites.first().map(|s| format!("/{}", s.site().id())).context("no site registered")?;
         let mut router = axum::Router::new()
-            .route("/", get(|| async { Redirect::temporary("/melonbooks") }))
-            .nest("/melonbooks", melonbooks_routes())
-            .nest("/amiami", amiami_routes())
-            .nest("/api", api_routes());
+            .route("/", get(move || {
+                let redirect = Redirect::temporary(&start_page);
+                async move { redirect }
+            }));
+        for site in &sites {
+            let feed_routes = site_feed_routes()
+                .merge(site.feed_routes())
+                .layer(Extension(site.service()))
+                .route_layer(require_feed_token.clone());
+            let site_router = site.page_routes()
+                .route_layer(require_session.clone())
+                .merge(feed_routes);
+            router = router.nest(&format!("/{}", site.site().id()), site_router);
+        }
+        router = router
+            .nest("/products", product_index_page_routes().route_layer(require_session.clone()))
+            .route("/logout", post(auth_routes::post_logout).route_layer(require_session.clone()))
+            .merge(docs.route_layer(require_session))
+            .merge(api_router.route_layer(middleware::from_fn_with_state(state.clone(), auth::require_api_token)))
+            .route("/login", get(auth_routes::get_login).post(auth_routes::post_login))
+            .route("/images/{hash}", get(image_routes::get_image))
+            .route("/images/{hash}/thumbnail", get(image_routes::get_thumbnail))
+            .route("/health", get(|| async { "ok" }));
         if let Some(assets_dir) = config.assets_dir {
             router = router.nest_service("/assets", ServeDir::new(assets_dir));
         }
@@ -66,20 +125,200 @@ impl HttpServer {
     }
 }
 
-fn melonbooks_routes() -> axum::Router<AppState> {
+/// Routes of the api below `/api`, the spec served at `/api/openapi.json` is built from them.
+fn api_routes(sites: &[Arc<dyn HttpSite>]) -> OpenApiRouter<AppState> {
+    let mut router = OpenApiRouter::new()
+        .merge(product_index_api_v1_routes());
+    for site in sites {
+        router = router
+            .merge(site.legacy_api_routes())
+            .nest(&format!("/v1/{}", site.site().id()), site.api_routes()
+                .merge(site_api_v1_routes(site.site()))
+                .layer(Extension(site.service())));
+        router.get_openapi_mut().tags.get_or_insert_with(Vec::new)
+            .push(TagBuilder::new().name(site.site().id()).description(Some(site.api_description())).build());
+    }
+    OpenApiRouter::with_openapi(ApiDoc::openapi())
+        .nest("/api", router.fallback(|| async { ApiError::not_found("unknown api endpoint") }))
+}
+
+/// Routes below `/api/v1/{id}` that every site has.
+fn site_api_v1_routes(site: Site) -> OpenApiRouter<AppState> {
+    let mut router = OpenApiRouter::new()
+        .routes(routes!(site_api_routes::scrape));
+    for operation in router.get_openapi_mut().paths.paths.values_mut().filter_map(|item| item.post.as_mut()) {
+        operation.operation_id = Some(format!("scrape_{}", site.id()));
+        operation.tags = Some(vec![site.id().to_owned()]);
+    }
+    router
+}
+
+/// Routes below `/{id}` of the feeds every site has.
+fn site_feed_routes() -> axum::Router<AppState> {
     axum::Router::new()
-        .route("/", get(melonbooks_routes::get_overview))
-        .route("/artist", post(melonbooks_routes::post_artist))
-        .route("/artist/delete", post(melonbooks_routes::delete_artist))
-        .route("/title-skip-sequence", post(melonbooks_routes::post_title_skip_sequence))
-        .route("/title-skip-sequence/delete", post(melonbooks_routes::delete_title_skip_sequence))
+        .route("/feed.atom", get(site_routes::get_feed))
+        .route("/feed.rss", get(site_routes::get_feed))
+        .route("/target/{target_id}/feed.atom", get(site_routes::get_target_feed))
+        .route("/target/{target_id}/feed.rss", get(site_routes::get_target_feed))
 }
 
-fn amiami_routes() -> axum::Router<AppState> {
+fn product_index_page_routes() -> axum::Router<AppState> {
     axum::Router::new()
-        .route("/", get(amiami_routes::get_overview))
+        .route("/", get(product_index_routes::get_products))
+}
+
+/// Routes below `/api` of the products of all sites.
+fn product_index_api_v1_routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(product_index_api_routes::get_products))
+        .routes(routes!(product_index_api_routes::get_follow_targets))
 }
 
-fn api_routes() -> axum::Router<AppState> {
-    axum::Router::new().route("/artists", get(melonbooks_routes::get_artists))
-}
\ No newline at end of file
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::{HttpSettings, ServerConfiguration};
+    use crate::domain::amiami::service::AmiamiServiceImpl;
+    use crate::domain::duplicate::service::DuplicateServiceImpl;
+    use crate::domain::image::service::ImageServiceImpl;
+    use crate::domain::product_index::service::ProductIndexServiceImpl;
+    use crate::domain::site::{ScrapeGuard, ScrapeLock, ScrapeSiteError, SiteCore, SiteService, SITES};
+    use crate::domain::test_util::{TestImageCache, TestNotifier};
+    use crate::domain::user::models::user::DEFAULT_USERNAME;
+    use crate::domain::user::service::UserServiceImpl;
+    use crate::inbound::http::auth::HttpUser;
+    use crate::inbound::http::site::AmiamiHttpSite;
+    use crate::outbound::amiami_scraper::AmiamiScraperImpl;
+    use crate::outbound::sqlite::Sqlite;
+    use crate::sites::http_sites;
+    use async_trait::async_trait;
+    use axum::http::{header, StatusCode};
+    use std::collections::HashMap;
+    use tracing::level_filters::LevelFilter;
+
+    struct TestSiteService {
+        scrape_lock: ScrapeLock,
+    }
+
+    #[async_trait]
+    impl SiteService for TestSiteService {
+        fn site(&self) -> Site { Site::new("test", "Test", "https://test.moe/") }
+        fn scrape_lock(&self) -> &ScrapeLock { &self.scrape_lock }
+
+        async fn scrape(&self, _guard: &ScrapeGuard) -> Result<(), ScrapeSiteError> {
+            Ok(())
+        }
+    }
+
+    struct TestHttpSite {
+        service: Arc<TestSiteService>,
+    }
+
+    impl HttpSite for TestHttpSite {
+        fn service(&self) -> Arc<dyn SiteService> { self.service.clone() }
+        fn page_routes(&self) -> axum::Router<AppState> { axum::Router::new() }
+        fn feed_routes(&self) -> axum::Router<AppState> { axum::Router::new() }
+        fn api_routes(&self) -> OpenApiRouter<AppState> { OpenApiRouter::new() }
+        fn api_description(&self) -> &'static str { "Test products" }
+    }
+
+    #[test]
+    fn test_api_doc_of_routes() {
+        let sites: Vec<Arc<dyn HttpSite>> = vec![Arc::new(TestHttpSite { service: Arc::new(TestSiteService { scrape_lock: ScrapeLock::new() }) })];
+        let (_, api_doc) = api_routes(&sites).split_for_parts();
+
+        assert_eq!(api_doc.paths.paths.keys().collect::<Vec<_>>(), vec!["/api/v1/products", "/api/v1/products/targets", "/api/v1/test/scrape"]);
+        let scrape = api_doc.paths.paths["/api/v1/test/scrape"].post.as_ref().unwrap();
+        assert_eq!(scrape.operation_id.as_deref(), Some("scrape_test"));
+        assert_eq!(scrape.tags, Some(vec!["test".to_owned()]));
+        let tags = api_doc.tags.unwrap().into_iter().map(|t| (t.name, t.description)).collect::<Vec<_>>();
+        assert_eq!(tags, vec![
+            ("products".to_owned(), Some("Products of all sites in one list".to_owned())),
+            ("test".to_owned(), Some("Test products".to_owned())),
+        ]);
+        assert!(api_doc.components.unwrap().security_schemes.contains_key("api_token"));
+    }
+
+    #[tokio::test]
+    async fn test_calendar_with_feed_token() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_service = Arc::new(UserServiceImpl::new(db.clone()));
+        user_service.setup_users(DEFAULT_USERNAME, &[DEFAULT_USERNAME.to_owned()]).await.unwrap();
+        let amiami_service = AmiamiServiceImpl::new(db.clone(), AmiamiScraperImpl::new().unwrap(), SiteCore::new(TestNotifier::default(), TestImageCache));
+        let sites: Vec<Arc<dyn HttpSite>> = vec![Arc::new(AmiamiHttpSite::new(Arc::new(amiami_service)))];
+        let config = HttpServerConfig {
+            port: 0,
+            assets_dir: None,
+            auth: Some(HttpAuthConfig {
+                users: vec![HttpUser { username: DEFAULT_USERNAME.to_owned(), password_hash: None, api_tokens: vec!["token".to_owned()].into() }],
+                session_ttl: chrono::Duration::hours(1),
+                secure_cookie: false,
+            }),
+            default_user: DEFAULT_USERNAME.to_owned(),
+        };
+        let server = HttpServer::new(
+            config,
+            sites,
+            user_service,
+            Arc::new(ImageServiceImpl::new(TestImageCache)),
+            Arc::new(DuplicateServiceImpl::new(db.clone())),
+            Arc::new(ProductIndexServiceImpl::new(db)),
+        ).await.unwrap();
+        let url = format!("http://{}/amiami/calendar.ics", server.listener.local_addr().unwrap());
+        tokio::spawn(server.run());
+        let client = reqwest::Client::builder().redirect(reqwest::redirect::Policy::none()).build().unwrap();
+
+        let response = client.get(format!("{}?token=token&category=459", url)).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "text/calendar; charset=utf-8");
+        let response = client.get(format!("{}?token=wrong", url)).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_routes_of_all_sites() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_service = Arc::new(UserServiceImpl::new(db.clone()));
+        user_service.setup_users(DEFAULT_USERNAME, &[]).await.unwrap();
+        let config = ServerConfiguration {
+            db_path: ":memory:".into(),
+            image_dir: "images".into(),
+            log_level: LevelFilter::INFO,
+            sites: HashMap::new(),
+            openssl_config: None,
+            http_settings: HttpSettings::default(),
+            users: vec![],
+            default_user: DEFAULT_USERNAME.to_owned(),
+        };
+        let sites = http_sites(&config, db.clone(), TestImageCache).unwrap();
+
+        assert_eq!(sites.iter().map(|s| s.site()).collect::<Vec<_>>(), SITES.to_vec());
+        let (_, api_doc) = api_routes(&sites).split_for_parts();
+        let tags = api_doc.tags.unwrap().into_iter().map(|t| t.name).collect::<Vec<_>>();
+        for site in SITES {
+            assert!(tags.contains(&site.id().to_owned()), "no api tag of {}", site.id());
+            assert!(api_doc.paths.paths.contains_key(&format!("/api/v1/{}/scrape", site.id())), "no scrape endpoint of {}", site.id());
+        }
+
+        let server = HttpServer::new(
+            HttpServerConfig { port: 0, assets_dir: None, auth: None, default_user: DEFAULT_USERNAME.to_owned() },
+            sites,
+            user_service,
+            Arc::new(ImageServiceImpl::new(TestImageCache)),
+            Arc::new(DuplicateServiceImpl::new(db.clone())),
+            Arc::new(ProductIndexServiceImpl::new(db)),
+        ).await.unwrap();
+        let url = format!("http://{}", server.listener.local_addr().unwrap());
+        tokio::spawn(server.run());
+        let client = reqwest::Client::builder().redirect(reqwest::redirect::Policy::none()).build().unwrap();
+
+        for site in SITES {
+            let response = client.get(format!("{}/{}", url, site.id())).send().await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "overview of {}", site.id());
+            let response = client.get(format!("{}/{}/feed.atom", url, site.id())).send().await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "feed of {}", site.id());
+        }
+    }
+}
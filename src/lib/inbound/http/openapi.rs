@@ -0,0 +1,29 @@
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityRequirement, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+const API_TOKEN_SECURITY_SCHEME: &str = "api_token";
+
+/// What the routes don't document, `HttpServer` adds the operations of the routes it serves and a tag per site.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "moe-scraper", description = "Scraped products of the followed artists, circles, shops and searches"),
+    tags(
+        (name = "products", description = "Products of all sites in one list"),
+    ),
+    modifiers(&ApiTokenSecurity)
+)]
+pub struct ApiDoc;
+
+/// Documents the bearer token that is required when `http.auth` is configured.
+struct ApiTokenSecurity;
+
+impl Modify for ApiTokenSecurity {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            API_TOKEN_SECURITY_SCHEME,
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+        openapi.security = Some(vec![SecurityRequirement::new(API_TOKEN_SECURITY_SCHEME, Vec::<String>::new())]);
+    }
+}
@@ -0,0 +1,481 @@
+use crate::domain::amiami::ports::AmiamiService;
+use crate::domain::booth::ports::BoothService;
+use crate::domain::digital::ports::DigitalService;
+use crate::domain::figure::ports::FigureService;
+use crate::domain::mandarake::ports::MandarakeService;
+use crate::domain::melonbooks::ports::MelonbooksService;
+use crate::domain::site::{Site, SiteService};
+use crate::domain::surugaya::ports::SurugayaService;
+use crate::domain::toranoana::ports::ToranoanaService;
+use crate::inbound::http::handlers::{amiami_api_routes, amiami_routes, booth_api_routes, booth_routes, digital_api_routes, digital_routes, figure_api_routes, figure_routes, mandarake_api_routes, mandarake_routes, melonbooks_api_routes, melonbooks_routes, site_routes, surugaya_api_routes, surugaya_routes, toranoana_api_routes, toranoana_routes};
+use crate::inbound::http::AppState;
+use axum::routing::{get, post};
+use axum::{Extension, Router};
+use std::sync::Arc;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+/// The pages, feeds and api of a site, `HttpServer` nests them below the site's id.
+/// The handlers get the site's service as `Extension`.
+pub trait HttpSite: Send + Sync + 'static {
+    fn service(&self) -> Arc<dyn SiteService>;
+
+    fn site(&self) -> Site {
+        self.service().site()
+    }
+
+    /// Routes below `/{id}` that require a session.
+    fn page_routes(&self) -> Router<AppState>;
+    /// Routes below `/{id}` that feed readers open with a feed token, besides the feeds of every site.
+    fn feed_routes(&self) -> Router<AppState> {
+        Router::new()
+    }
+    /// Routes below `/api/v1/{id}`, their operations are tagged with the site's id.
+    fn api_routes(&self) -> OpenApiRouter<AppState>;
+    /// Description of the site's tag in the api docs.
+    fn api_description(&self) -> &'static str;
+
+    /// Routes below `/api` from before the api was versioned.
+    fn legacy_api_routes(&self) -> OpenApiRouter<AppState> {
+        OpenApiRouter::new()
+    }
+}
+
+pub struct MelonbooksHttpSite {
+    service: Arc<dyn MelonbooksService>,
+}
+
+impl MelonbooksHttpSite {
+    pub fn new<S: MelonbooksService>(service: Arc<S>) -> Self {
+        Self { service }
+    }
+}
+
+impl HttpSite for MelonbooksHttpSite {
+    fn service(&self) -> Arc<dyn SiteService> {
+        self.service.clone()
+    }
+
+    fn page_routes(&self) -> Router<AppState> {
+        melonbooks_page_routes().layer(Extension(self.service.clone()))
+    }
+
+    fn feed_routes(&self) -> Router<AppState> {
+        melonbooks_feed_routes()
+    }
+
+    fn api_routes(&self) -> OpenApiRouter<AppState> {
+        melonbooks_api_v1_routes().layer(Extension(self.service.clone()))
+    }
+
+    fn api_description(&self) -> &'static str {
+        "Melonbooks artists and products"
+    }
+
+    fn legacy_api_routes(&self) -> OpenApiRouter<AppState> {
+        melonbooks_legacy_api_routes().layer(Extension(self.service.clone()))
+    }
+}
+
+pub struct AmiamiHttpSite {
+    service: Arc<dyn AmiamiService>,
+}
+
+impl AmiamiHttpSite {
+    pub fn new<S: AmiamiService>(service: Arc<S>) -> Self {
+        Self { service }
+    }
+}
+
+impl HttpSite for AmiamiHttpSite {
+    fn service(&self) -> Arc<dyn SiteService> {
+        self.service.clone()
+    }
+
+    fn page_routes(&self) -> Router<AppState> {
+        amiami_page_routes().layer(Extension(self.service.clone()))
+    }
+
+    fn feed_routes(&self) -> Router<AppState> {
+        amiami_feed_routes().layer(Extension(self.service.clone()))
+    }
+
+    fn api_routes(&self) -> OpenApiRouter<AppState> {
+        amiami_api_v1_routes().layer(Extension(self.service.clone()))
+    }
+
+    fn api_description(&self) -> &'static str {
+        "AmiAmi products and releases"
+    }
+}
+
+pub struct ToranoanaHttpSite {
+    service: Arc<dyn ToranoanaService>,
+}
+
+impl ToranoanaHttpSite {
+    pub fn new<S: ToranoanaService>(service: Arc<S>) -> Self {
+        Self { service }
+    }
+}
+
+impl HttpSite for ToranoanaHttpSite {
+    fn service(&self) -> Arc<dyn SiteService> {
+        self.service.clone()
+    }
+
+    fn page_routes(&self) -> Router<AppState> {
+        toranoana_page_routes().layer(Extension(self.service.clone()))
+    }
+
+    fn api_routes(&self) -> OpenApiRouter<AppState> {
+        toranoana_api_v1_routes().layer(Extension(self.service.clone()))
+    }
+
+    fn api_description(&self) -> &'static str {
+        "Toranoana artists, circles and products"
+    }
+}
+
+pub struct MandarakeHttpSite {
+    service: Arc<dyn MandarakeService>,
+}
+
+impl MandarakeHttpSite {
+    pub fn new<S: MandarakeService>(service: Arc<S>) -> Self {
+        Self { service }
+    }
+}
+
+impl HttpSite for MandarakeHttpSite {
+    fn service(&self) -> Arc<dyn SiteService> {
+        self.service.clone()
+    }
+
+    fn page_routes(&self) -> Router<AppState> {
+        mandarake_page_routes().layer(Extension(self.service.clone()))
+    }
+
+    fn api_routes(&self) -> OpenApiRouter<AppState> {
+        mandarake_api_v1_routes().layer(Extension(self.service.clone()))
+    }
+
+    fn api_description(&self) -> &'static str {
+        "Mandarake saved searches and second-hand listings"
+    }
+}
+
+pub struct SurugayaHttpSite {
+    service: Arc<dyn SurugayaService>,
+}
+
+impl SurugayaHttpSite {
+    pub fn new<S: SurugayaService>(service: Arc<S>) -> Self {
+        Self { service }
+    }
+}
+
+impl HttpSite for SurugayaHttpSite {
+    fn service(&self) -> Arc<dyn SiteService> {
+        self.service.clone()
+    }
+
+    fn page_routes(&self) -> Router<AppState> {
+        surugaya_page_routes().layer(Extension(self.service.clone()))
+    }
+
+    fn api_routes(&self) -> OpenApiRouter<AppState> {
+        surugaya_api_v1_routes().layer(Extension(self.service.clone()))
+    }
+
+    fn api_description(&self) -> &'static str {
+        "Suruga-ya saved searches and listings"
+    }
+}
+
+/// The BOOTH pages also show the melonbooks products of the artist a shop is linked to.
+pub struct BoothHttpSite {
+    service: Arc<dyn BoothService>,
+    melonbooks_service: Arc<dyn MelonbooksService>,
+}
+
+impl BoothHttpSite {
+    pub fn new<S: BoothService, M: MelonbooksService>(service: Arc<S>, melonbooks_service: Arc<M>) -> Self {
+        Self { service, melonbooks_service }
+    }
+}
+
+impl HttpSite for BoothHttpSite {
+    fn service(&self) -> Arc<dyn SiteService> {
+        self.service.clone()
+    }
+
+    fn page_routes(&self) -> Router<AppState> {
+        booth_page_routes()
+            .layer(Extension(self.service.clone()))
+            .layer(Extension(self.melonbooks_service.clone()))
+    }
+
+    fn api_routes(&self) -> OpenApiRouter<AppState> {
+        booth_api_v1_routes().layer(Extension(self.service.clone()))
+    }
+
+    fn api_description(&self) -> &'static str {
+        "BOOTH shops, tags and items"
+    }
+}
+
+pub struct DigitalHttpSite {
+    service: Arc<dyn DigitalService>,
+}
+
+impl DigitalHttpSite {
+    pub fn new<S: DigitalService>(service: Arc<S>) -> Self {
+        Self { service }
+    }
+}
+
+impl HttpSite for DigitalHttpSite {
+    fn service(&self) -> Arc<dyn SiteService> {
+        self.service.clone()
+    }
+
+    fn page_routes(&self) -> Router<AppState> {
+        digital_page_routes().layer(Extension(self.service.clone()))
+    }
+
+    fn api_routes(&self) -> OpenApiRouter<AppState> {
+        digital_api_v1_routes().layer(Extension(self.service.clone()))
+    }
+
+    fn api_description(&self) -> &'static str {
+        "DLsite and FANZA circles and their discounted works"
+    }
+}
+
+pub struct FigureHttpSite {
+    service: Arc<dyn FigureService>,
+}
+
+impl FigureHttpSite {
+    pub fn new<S: FigureService>(service: Arc<S>) -> Self {
+        Self { service }
+    }
+}
+
+impl HttpSite for FigureHttpSite {
+    fn service(&self) -> Arc<dyn SiteService> {
+        self.service.clone()
+    }
+
+    fn page_routes(&self) -> Router<AppState> {
+        figure_page_routes().layer(Extension(self.service.clone()))
+    }
+
+    fn api_routes(&self) -> OpenApiRouter<AppState> {
+        figure_api_v1_routes().layer(Extension(self.service.clone()))
+    }
+
+    fn api_description(&self) -> &'static str {
+        "HobbySearch and Good Smile makers, series and their preorders"
+    }
+}
+
+fn melonbooks_page_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(melonbooks_routes::get_overview))
+        .route("/events", get(melonbooks_routes::get_events))
+        .route("/product/{product_id}", get(melonbooks_routes::get_product))
+        .route("/stats", get(melonbooks_routes::get_stats))
+        .route("/artist", post(melonbooks_routes::post_artist))
+        .route("/artist/delete", post(melonbooks_routes::delete_artist))
+        .route("/artist/schedule", post(melonbooks_routes::post_artist_schedule))
+        .route("/title-skip-sequence", post(melonbooks_routes::post_title_skip_sequence))
+        .route("/title-skip-sequence/delete", post(melonbooks_routes::delete_title_skip_sequence))
+}
+
+/// The artist feeds from before every site had feeds of its targets.
+fn melonbooks_feed_routes() -> Router<AppState> {
+    Router::new()
+        .route("/artist/{artist_id}/feed.atom", get(site_routes::get_target_feed))
+        .route("/artist/{artist_id}/feed.rss", get(site_routes::get_target_feed))
+}
+
+fn melonbooks_legacy_api_routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(melonbooks_api_routes::get_followed_artists_legacy))
+}
+
+fn melonbooks_api_v1_routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(melonbooks_api_routes::get_artists, melonbooks_api_routes::follow_artist))
+        .routes(routes!(melonbooks_api_routes::unfollow_artist))
+        .routes(routes!(melonbooks_api_routes::get_artist_products))
+        .routes(routes!(melonbooks_api_routes::set_artist_schedule))
+        .routes(routes!(melonbooks_api_routes::get_products))
+        .routes(routes!(melonbooks_api_routes::search_products))
+        .routes(routes!(melonbooks_api_routes::get_categories))
+        .routes(routes!(melonbooks_api_routes::get_flags))
+        .routes(routes!(melonbooks_api_routes::get_title_skip_sequences, melonbooks_api_routes::add_title_skip_sequence))
+        .routes(routes!(melonbooks_api_routes::delete_title_skip_sequence))
+}
+
+fn amiami_page_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(amiami_routes::get_overview))
+        .route("/events", get(amiami_routes::get_events))
+        .route("/product/{product_id}", get(amiami_routes::get_product))
+        .route("/stats", get(amiami_routes::get_stats))
+        .route("/calendar", get(amiami_routes::get_calendar))
+        .route("/category/schedule", post(amiami_routes::post_category_schedule))
+}
+
+/// The calendar is subscribed to by calendar apps, which like feed readers cannot log in.
+fn amiami_feed_routes() -> Router<AppState> {
+    Router::new()
+        .route("/calendar.ics", get(amiami_routes::get_calendar_ical))
+        .route("/category/{category}/feed.atom", get(amiami_routes::get_category_feed))
+        .route("/category/{category}/feed.rss", get(amiami_routes::get_category_feed))
+}
+
+fn amiami_api_v1_routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(amiami_api_routes::get_products))
+        .routes(routes!(amiami_api_routes::search_products))
+        .routes(routes!(amiami_api_routes::get_releases))
+        .routes(routes!(amiami_api_routes::get_categories))
+        .routes(routes!(amiami_api_routes::get_followed_categories, amiami_api_routes::follow_category))
+        .routes(routes!(amiami_api_routes::unfollow_category))
+        .routes(routes!(amiami_api_routes::set_category_schedule))
+        .routes(routes!(amiami_api_routes::get_category_schedules))
+        .routes(routes!(amiami_api_routes::get_makers))
+}
+
+fn toranoana_page_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(toranoana_routes::get_overview))
+        .route("/product/{product_id}", get(toranoana_routes::get_product))
+        .route("/creator", post(toranoana_routes::post_creator))
+        .route("/creator/delete", post(toranoana_routes::delete_creator))
+}
+
+fn toranoana_api_v1_routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(toranoana_api_routes::get_creators, toranoana_api_routes::follow_creator))
+        .routes(routes!(toranoana_api_routes::unfollow_creator))
+        .routes(routes!(toranoana_api_routes::get_creator_products))
+        .routes(routes!(toranoana_api_routes::get_products))
+}
+
+fn mandarake_page_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(mandarake_routes::get_overview))
+        .route("/product/{product_id}", get(mandarake_routes::get_product))
+        .route("/search", post(mandarake_routes::post_search))
+        .route("/search/delete", post(mandarake_routes::delete_search))
+}
+
+fn mandarake_api_v1_routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(mandarake_api_routes::get_searches, mandarake_api_routes::save_search))
+        .routes(routes!(mandarake_api_routes::delete_search))
+        .routes(routes!(mandarake_api_routes::get_search_products))
+        .routes(routes!(mandarake_api_routes::get_products))
+}
+
+fn surugaya_page_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(surugaya_routes::get_overview))
+        .route("/product/{product_id}", get(surugaya_routes::get_product))
+        .route("/search", post(surugaya_routes::post_search))
+        .route("/search/delete", post(surugaya_routes::delete_search))
+}
+
+fn surugaya_api_v1_routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(surugaya_api_routes::get_searches, surugaya_api_routes::save_search))
+        .routes(routes!(surugaya_api_routes::delete_search))
+        .routes(routes!(surugaya_api_routes::get_search_products))
+        .routes(routes!(surugaya_api_routes::get_products))
+}
+
+fn booth_page_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(booth_routes::get_overview))
+        .route("/product/{product_id}", get(booth_routes::get_product))
+        .route("/source", post(booth_routes::post_source))
+        .route("/source/delete", post(booth_routes::delete_source))
+        .route("/source/link", post(booth_routes::post_source_link))
+}
+
+fn booth_api_v1_routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(booth_api_routes::get_sources, booth_api_routes::follow_source))
+        .routes(routes!(booth_api_routes::unfollow_source))
+        .routes(routes!(booth_api_routes::link_melonbooks_artist))
+        .routes(routes!(booth_api_routes::get_source_products))
+        .routes(routes!(booth_api_routes::get_products))
+}
+
+fn digital_page_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(digital_routes::get_overview))
+        .route("/product/{product_id}", get(digital_routes::get_product))
+        .route("/circle", post(digital_routes::post_circle))
+        .route("/circle/delete", post(digital_routes::delete_circle))
+}
+
+fn digital_api_v1_routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(digital_api_routes::get_circles, digital_api_routes::follow_circle))
+        .routes(routes!(digital_api_routes::unfollow_circle))
+        .routes(routes!(digital_api_routes::get_circle_products))
+        .routes(routes!(digital_api_routes::get_products))
+}
+
+fn figure_page_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(figure_routes::get_overview))
+        .route("/product/{product_id}", get(figure_routes::get_product))
+        .route("/source", post(figure_routes::post_source))
+        .route("/source/delete", post(figure_routes::delete_source))
+}
+
+fn figure_api_v1_routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(figure_api_routes::get_sources, figure_api_routes::follow_source))
+        .routes(routes!(figure_api_routes::unfollow_source))
+        .routes(routes!(figure_api_routes::get_source_products))
+        .routes(routes!(figure_api_routes::get_products))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::domain::{amiami, booth, digital, figure, mandarake, melonbooks, surugaya, toranoana};
+
+    #[test]
+    fn test_api_operations_are_tagged_with_site() {
+        let routers = [
+            (melonbooks::SITE, melonbooks_legacy_api_routes()),
+            (melonbooks::SITE, melonbooks_api_v1_routes()),
+            (amiami::SITE, amiami_api_v1_routes()),
+            (toranoana::SITE, toranoana_api_v1_routes()),
+            (mandarake::SITE, mandarake_api_v1_routes()),
+            (surugaya::SITE, surugaya_api_v1_routes()),
+            (booth::SITE, booth_api_v1_routes()),
+            (digital::SITE, digital_api_v1_routes()),
+            (figure::SITE, figure_api_v1_routes()),
+        ];
+        for (site, router) in routers {
+            let api_doc = router.into_openapi();
+            assert!(!api_doc.paths.paths.is_empty());
+            for (path, item) in api_doc.paths.paths {
+                let operations = [&item.get, &item.post, &item.put, &item.patch, &item.delete];
+                for operation in operations.into_iter().flatten() {
+                    assert_eq!(operation.tags, Some(vec![site.id().to_owned()]), "tags of {} {}", site, path);
+                }
+            }
+        }
+    }
+}
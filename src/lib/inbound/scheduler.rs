@@ -0,0 +1,180 @@
+use crate::domain::product_index::models::target::TargetId;
+use crate::domain::schedule::{Schedule, TargetSchedule};
+use crate::domain::site::SiteService;
+use chrono::Local;
+use log::{error, info};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::Mutex;
+use tokio_cron_scheduler::{Job, JobScheduler};
+use uuid::Uuid;
+
+/// How often the targets scraped by their activity are checked for being due.
+const ADAPTIVE_CHECK_SCHEDULE: &str = "0 * * * * *";
+
+/// Runs the scrapes of the sites, and of the artists and categories with their own schedule or adaptive interval.
+#[derive(Clone)]
+pub struct Scheduler {
+    scheduler: JobScheduler,
+    target_jobs: Arc<Mutex<HashMap<TargetId, (Schedule, Uuid)>>>,
+}
+
+impl Scheduler {
+    pub async fn new() -> Result<Self, anyhow::Error> {
+        Ok(Self { scheduler: JobScheduler::new().await?, target_jobs: Arc::default() })
+    }
+
+    /// Scrapes the site on `schedule`, each target with its own schedule on that and, if the site has an
+    /// adaptive interval, the other targets when they are due. The jobs of the targets follow the changes
+    /// of their schedules while running.
+    pub async fn schedule_site(&self, schedule: Option<&str>, service: Arc<dyn SiteService>) -> Result<(), anyhow::Error> {
+        if let Some(schedule) = schedule {
+            let site_service = service.clone();
+            self.scheduler.add(
+                Job::new_async_tz(schedule, Local, move |_uuid, _l| {
+                    Box::pin({
+                        let service = site_service.clone();
+                        async move {
+                            let guard = service.scrape_lock().lock().await;
+                            match service.scrape(&guard).await {
+                                Ok(_) => info!("Successfully scraped {}", service.site().id()),
+                                Err(e) => error!("{:?}", e),
+                            };
+                        }
+                    })
+                })?
+            ).await?;
+        }
+        if service.adaptive_interval().is_some() {
+            self.scheduler.add(adaptive_job(service.clone())?).await?;
+        }
+        let Some(mut changes) = service.subscribe_schedule_changes() else {
+            return Ok(());
+        };
+        self.update_target_jobs(&service).await?;
+        let scheduler = self.clone();
+        tokio::spawn(async move {
+            while let Ok(_) | Err(RecvError::Lagged(_)) = changes.recv().await {
+                if let Err(e) = scheduler.update_target_jobs(&service).await {
+                    error!("cannot update target schedules of {}: {:?}", service.site().id(), e);
+                }
+            }
+        });
+        Ok(())
+    }
+
+    pub async fn start(&self) -> Result<(), anyhow::Error> {
+        self.scheduler.start().await?;
+        Ok(())
+    }
+
+    /// Removes the jobs of targets whose schedule changed or was removed and adds the missing ones.
+    async fn update_target_jobs(&self, service: &Arc<dyn SiteService>) -> Result<(), anyhow::Error> {
+        let site = service.site();
+        let schedules = service.get_target_schedules().await?;
+        let mut jobs = self.target_jobs.lock().await;
+        let outdated = jobs.iter()
+            .filter(|(target_id, (schedule, _))| target_id.site() == site
+                && !schedules.iter().any(|s| s.target_id() == **target_id && s.schedule() == schedule))
+            .map(|(target_id, _)| *target_id)
+            .collect::<Vec<_>>();
+        for target_id in outdated {
+            if let Some((_, job_id)) = jobs.remove(&target_id) {
+                self.scheduler.remove(&job_id).await?;
+                info!("unscheduled target '{}'", target_id);
+            }
+        }
+        for target in schedules {
+            if jobs.contains_key(&target.target_id()) {
+                continue;
+            }
+            let job_id = self.scheduler.add(target_job(&target, service.clone())?).await?;
+            jobs.insert(target.target_id(), (target.schedule().clone(), job_id));
+            info!("scheduled '{}' of {} on '{}'", target.name(), site.id(), target.schedule());
+        }
+        Ok(())
+    }
+}
+
+/// Checks every minute which targets are due, a check is skipped while another scrape of the site runs.
+fn adaptive_job(service: Arc<dyn SiteService>) -> Result<Job, anyhow::Error> {
+    let job = Job::new_async_tz(ADAPTIVE_CHECK_SCHEDULE, Local, move |_uuid, _l| {
+        Box::pin({
+            let service = service.clone();
+            async move {
+                let Some(guard) = service.scrape_lock().try_lock() else {
+                    return;
+                };
+                if let Err(e) = service.scrape_due_targets(&guard).await {
+                    error!("{:?}", e);
+                }
+            }
+        })
+    })?;
+    Ok(job)
+}
+
+fn target_job(target: &TargetSchedule, service: Arc<dyn SiteService>) -> Result<Job, anyhow::Error> {
+    let (target_id, name) = (target.target_id(), target.name().to_owned());
+    let job = Job::new_async_tz(target.schedule().expression(), Local, move |_uuid, _l| {
+        Box::pin({
+            let service = service.clone();
+            let name = name.clone();
+            async move {
+                let guard = service.scrape_lock().lock().await;
+                match service.scrape_target(&guard, target_id.id()).await {
+                    Ok(_) => info!("Successfully scraped '{}' of {}", name, service.site().id()),
+                    Err(e) => error!("{:?}", e),
+                };
+            }
+        })
+    })?;
+    Ok(job)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::domain::schedule::{GetTargetSchedulesError, ScheduleChanges};
+    use crate::domain::site::{ScrapeGuard, ScrapeLock, ScrapeSiteError, Site};
+    use async_trait::async_trait;
+    use tokio::sync::broadcast;
+
+    const SITE: Site = Site::new("test", "Test", "https://test.moe/");
+
+    struct TargetScheduleService {
+        changes: ScheduleChanges,
+        scrape_lock: ScrapeLock,
+    }
+
+    #[async_trait]
+    impl SiteService for TargetScheduleService {
+        fn site(&self) -> Site { SITE }
+        fn scrape_lock(&self) -> &ScrapeLock { &self.scrape_lock }
+
+        async fn scrape(&self, _guard: &ScrapeGuard) -> Result<(), ScrapeSiteError> {
+            Ok(())
+        }
+
+        async fn get_target_schedules(&self) -> Result<Vec<TargetSchedule>, GetTargetSchedulesError> {
+            let schedule = Schedule::parse("0 0 * * * *").unwrap();
+            Ok(vec![TargetSchedule::new(TargetId::new(SITE, 1), "artist".to_owned(), schedule)])
+        }
+
+        fn subscribe_schedule_changes(&self) -> Option<broadcast::Receiver<()>> {
+            Some(self.changes.subscribe())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_schedule_targets_without_site_schedule() {
+        let scheduler = Scheduler::new().await.unwrap();
+        let service = Arc::new(TargetScheduleService { changes: ScheduleChanges::new(), scrape_lock: ScrapeLock::new() });
+
+        scheduler.schedule_site(None, service).await.unwrap();
+
+        let jobs = scheduler.target_jobs.lock().await;
+        assert!(jobs.contains_key(&TargetId::new(SITE, 1)));
+    }
+}
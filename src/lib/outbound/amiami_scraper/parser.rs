@@ -1,4 +1,4 @@
-use crate::domain::amiami::models::availability::Availability;
+use crate::domain::availability::Availability;
 use crate::domain::amiami::models::product::ProductData;
 use crate::outbound::amiami_scraper::{PRODUCT_DETAILS_URL, PRODUCT_IMAGE_BASE_URL};
 use chrono::NaiveDateTime;
@@ -0,0 +1,118 @@
+use crate::domain::booth::models::product::{ItemData, ScrapeProductsError};
+use crate::domain::booth::models::source::{SourceArgs, SourceKind};
+use crate::domain::booth::ports::BoothScraper;
+use crate::outbound::booth_scraper::parser::{parse_item, parse_item_urls};
+use crate::outbound::scraper_client::ScraperClient;
+use anyhow::Context;
+use async_trait::async_trait;
+use log::info;
+pub use parser::ParseError;
+use reqwest::Url;
+use select::document::Document;
+
+mod parser;
+
+const ITEM_URL: &str = "https://booth.pm/ja/items/{id}";
+/// A shop lists all of its items, a tag only its newest ones are scraped.
+const MAX_SHOP_PAGES: u32 = 20;
+const MAX_TAG_PAGES: u32 = 3;
+
+#[derive(Debug, Clone)]
+pub struct BoothScraperImpl {
+    client: ScraperClient,
+}
+
+impl BoothScraperImpl {
+    pub fn new() -> Result<Self, anyhow::Error> {
+        let client = ScraperClient::new("BoothScraper", &[])?;
+        Ok(BoothScraperImpl { client })
+    }
+
+    fn list_url(source: &SourceArgs, page_no: u32) -> Result<Url, anyhow::Error> {
+        let mut url = Url::parse(&source.url())?;
+        if source.kind() == SourceKind::Tag {
+            url.query_pairs_mut().append_pair("sort", "new");
+        }
+        url.query_pairs_mut().append_pair("page", page_no.to_string().as_str());
+        Ok(url)
+    }
+
+    fn item_json_url(url: &str) -> Result<Url, anyhow::Error> {
+        let url = Url::parse(&format!("{}.json", url))?;
+        Ok(url)
+    }
+
+    async fn get_item_url_page(&self, source: &SourceArgs, page_no: u32) -> Result<Vec<String>, ScrapeProductsError> {
+        let url = Self::list_url(source, page_no)
+            .with_context(|| format!("Error building item list url for {} '{}'", source.kind(), source.name()))?;
+        let body = self.client.get_text(url).await
+            .with_context(|| format!("Error getting items of {} '{}'", source.kind(), source.name()))?;
+        let urls = parse_item_urls(Document::from(body.as_str()))?;
+        info!("Found {} items on page {} for {} '{}'", urls.len(), page_no, source.kind(), source.name());
+        Ok(urls)
+    }
+
+    async fn get_item_urls(&self, source: &SourceArgs) -> Result<Vec<String>, ScrapeProductsError> {
+        let max_pages = match source.kind() {
+            SourceKind::Shop => MAX_SHOP_PAGES,
+            SourceKind::Tag => MAX_TAG_PAGES,
+        };
+        let mut urls = Vec::<String>::new();
+        for page_no in 1..=max_pages {
+            let page_urls = self.get_item_url_page(source, page_no).await?;
+            // pages past the last one repeat it or are empty, depending on the list
+            let new_urls = page_urls.into_iter().filter(|u| !urls.contains(u)).collect::<Vec<_>>();
+            if new_urls.is_empty() {
+                break;
+            }
+            urls.extend(new_urls);
+        }
+        info!("Found {} total items for {} '{}'", urls.len(), source.kind(), source.name());
+        Ok(urls)
+    }
+
+    async fn get_item(&self, url: &str) -> Result<ItemData, ScrapeProductsError> {
+        let json_url = Self::item_json_url(url)
+            .with_context(|| format!("Error building json url of item '{}'", url))?;
+        let json = self.client.get_text(json_url).await
+            .with_context(|| format!("Error getting item '{}'", url))?;
+        let item = parse_item(url, &json)?;
+        Ok(item)
+    }
+}
+
+#[async_trait]
+impl BoothScraper for BoothScraperImpl {
+    async fn get_item_urls(&self, source: &SourceArgs) -> Result<Vec<String>, ScrapeProductsError> {
+        self.get_item_urls(source).await
+    }
+
+    async fn get_item(&self, url: &str) -> Result<ItemData, ScrapeProductsError> {
+        self.get_item(url).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_list_url() {
+        assert_eq!(
+            BoothScraperImpl::list_url(&SourceArgs::new("mafuyu".to_owned(), SourceKind::Shop), 2).unwrap().as_str(),
+            "https://mafuyu.booth.pm/items?page=2"
+        );
+        assert_eq!(
+            BoothScraperImpl::list_url(&SourceArgs::new("まふゆ".to_owned(), SourceKind::Tag), 1).unwrap().as_str(),
+            "https://booth.pm/ja/items?tags%5B%5D=%E3%81%BE%E3%81%B5%E3%82%86&sort=new&page=1"
+        );
+    }
+
+    #[test]
+    fn test_item_json_url() {
+        assert_eq!(
+            BoothScraperImpl::item_json_url("https://booth.pm/ja/items/5123456").unwrap().as_str(),
+            "https://booth.pm/ja/items/5123456.json"
+        );
+    }
+}
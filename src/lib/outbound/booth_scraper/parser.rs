@@ -0,0 +1,145 @@
+use crate::domain::availability::Availability;
+use crate::domain::booth::models::product::{ItemData, VariationData};
+use crate::outbound::booth_scraper::ITEM_URL;
+use itertools::Itertools;
+use select::document::Document;
+use select::predicate::Class;
+use serde::Deserialize;
+use thiserror::Error;
+
+/// Urls of the items on a shop or tag page, shops link to the item on their subdomain, so the url is built from the id.
+pub fn parse_item_urls(document: Document) -> Result<Vec<String>, ParseError> {
+    let cards = document.find(Class("item-card")).collect::<Vec<_>>();
+    if cards.is_empty() {
+        // pages without items have no item list at all
+        if document.find(Class("no-items")).next().is_some() {
+            return Ok(Vec::new());
+        }
+        return Err(ParseError::ItemListNotFound);
+    }
+    let urls = cards.into_iter()
+        .map(|card| {
+            let id = card.attr("data-product-id")
+                .ok_or_else(|| ParseError::ItemIdNotFound(card.text().trim().to_owned()))?;
+            id.parse::<u64>()
+                .map(|id| ITEM_URL.replace("{id}", &id.to_string()))
+                .map_err(|_| ParseError::ItemIdUnknown(id.to_owned()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(urls.into_iter().unique().collect())
+}
+
+#[derive(Debug, Deserialize)]
+struct ItemJson {
+    name: String,
+    images: Vec<ImageJson>,
+    shop: ShopJson,
+    variations: Vec<VariationJson>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ImageJson {
+    original: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ShopJson {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct VariationJson {
+    name: Option<String>,
+    price: i32,
+    status: String,
+}
+
+/// Parses the json BOOTH serves for an item page, only variations `on_sale` can be bought.
+pub fn parse_item(url: &str, json: &str) -> Result<ItemData, ParseError> {
+    let item = serde_json::from_str::<ItemJson>(json)
+        .map_err(|e| ParseError::ItemJsonInvalid(url.to_owned(), e.to_string()))?;
+    let image_url = item.images.into_iter().next()
+        .map(|i| i.original)
+        .ok_or_else(|| ParseError::ItemImageUrlNotFound(url.to_owned()))?;
+    if item.variations.is_empty() {
+        return Err(ParseError::ItemVariationsNotFound(url.to_owned()));
+    }
+    let variations = item.variations.into_iter()
+        .map(|v| {
+            let availability = match v.status.as_str() {
+                "on_sale" => Availability::Available,
+                _ => Availability::NotAvailable,
+            };
+            VariationData::new(v.name.unwrap_or_default(), v.price, availability)
+        })
+        .collect();
+    Ok(ItemData::new(url.to_owned(), item.name, item.shop.name, image_url, variations))
+}
+
+#[derive(Debug, Error)]
+pub enum ParseError {
+    #[error("Could not find item list")]
+    ItemListNotFound,
+    #[error("Could not find id of item {0}")]
+    ItemIdNotFound(String),
+    #[error("Unknown item id: {0}")]
+    ItemIdUnknown(String),
+    #[error("Invalid json of item {0}: {1}")]
+    ItemJsonInvalid(String, String),
+    #[error("Could not find image url of item {0}")]
+    ItemImageUrlNotFound(String),
+    #[error("Could not find variations of item {0}")]
+    ItemVariationsNotFound(String),
+}
+
+#[cfg(test)]
+mod test {
+    use crate::domain::availability::Availability;
+    use crate::outbound::booth_scraper::parser::{parse_item, parse_item_urls};
+    use select::document::Document;
+
+    #[test]
+    fn test_parse_shop_item_urls() {
+        let document = Document::from(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/test-data/booth/shop-items.html")));
+        assert_eq!(parse_item_urls(document).unwrap(), vec![
+            "https://booth.pm/ja/items/5123456",
+            "https://booth.pm/ja/items/4987654",
+        ]);
+    }
+
+    #[test]
+    fn test_parse_tag_item_urls() {
+        let document = Document::from(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/test-data/booth/tag-items.html")));
+        assert_eq!(parse_item_urls(document).unwrap(), vec![
+            "https://booth.pm/ja/items/5123456",
+            "https://booth.pm/ja/items/5011223",
+        ]);
+    }
+
+    #[test]
+    fn test_parse_no_item_urls() {
+        let document = Document::from(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/test-data/booth/tag-items-empty.html")));
+        assert!(parse_item_urls(document).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_item() {
+        let json = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/test-data/booth/item.json"));
+        let item = parse_item("https://booth.pm/ja/items/5123456", json).unwrap();
+        assert_eq!(item.url(), "https://booth.pm/ja/items/5123456");
+        assert_eq!(item.title(), "冬の画集");
+        assert_eq!(item.shop_name(), "まふゆ工房");
+        assert_eq!(item.image_url(), "https://booth.pximg.net/5123456/main.jpg");
+        let variations = item.variations().iter()
+            .map(|v| (v.name(), v.price(), v.availability().clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(variations, vec![
+            ("ダウンロード版", 1500, Availability::Available),
+            ("書籍版", 2500, Availability::NotAvailable),
+            ("", 3000, Availability::Available),
+        ]);
+        assert_eq!(item.availability(), Availability::Available);
+        assert_eq!(item.price(), Some(1500));
+        assert!(parse_item("https://booth.pm/ja/items/5123456", "<html></html>").is_err());
+    }
+}
@@ -0,0 +1,87 @@
+use crate::domain::digital::models::circle::{CircleArgs, Store};
+use crate::domain::digital::models::product::{CircleData, ScrapeProductsError};
+use crate::domain::digital::ports::DigitalScraper;
+use crate::outbound::digital_scraper::parser::{parse_dlsite_circle, parse_dlsite_works, parse_fanza_circle};
+use crate::outbound::scraper_client::ScraperClient;
+use anyhow::Context;
+use async_trait::async_trait;
+use log::info;
+pub use parser::ParseError;
+use reqwest::Url;
+use select::document::Document;
+
+mod parser;
+
+const DLSITE_BASE_URL: &str = "https://www.dlsite.com";
+const DLSITE_WORK_URL: &str = "https://www.dlsite.com/maniax/work/=/product_id/{id}.html";
+const DLSITE_WORK_JSON_URL: &str = "https://www.dlsite.com/maniax/product/info/ajax";
+const FANZA_BASE_URL: &str = "https://www.dmm.co.jp";
+
+#[derive(Debug, Clone)]
+pub struct DigitalScraperImpl {
+    client: ScraperClient,
+}
+
+impl DigitalScraperImpl {
+    pub fn new() -> Result<Self, anyhow::Error> {
+        let client = ScraperClient::new("DigitalScraper", &[("adultchecked=1", DLSITE_BASE_URL), ("age_check_done=1", FANZA_BASE_URL)])?;
+        Ok(DigitalScraperImpl { client })
+    }
+
+    fn dlsite_work_json_url(ids: &[String]) -> Result<Url, anyhow::Error> {
+        let url = Url::parse_with_params(DLSITE_WORK_JSON_URL, &[("product_id", ids.join(","))])?;
+        Ok(url)
+    }
+
+    /// The profile page only lists the works, their prices come from the json of the works.
+    async fn get_dlsite_circle(&self, circle: &CircleArgs) -> Result<CircleData, ScrapeProductsError> {
+        let url = Url::parse(&circle.url())
+            .with_context(|| format!("Error building url of DLsite circle '{}'", circle.code()))?;
+        let body = self.client.get_text(url).await
+            .with_context(|| format!("Error getting DLsite circle '{}'", circle.code()))?;
+        let (name, ids) = parse_dlsite_circle(Document::from(body.as_str()))?;
+        if ids.is_empty() {
+            return Ok(CircleData::new(name, Vec::new()));
+        }
+        let json_url = Self::dlsite_work_json_url(&ids)
+            .with_context(|| format!("Error building work json url of DLsite circle '{}'", circle.code()))?;
+        let json = self.client.get_text(json_url).await
+            .with_context(|| format!("Error getting works of DLsite circle '{}'", circle.code()))?;
+        let works = parse_dlsite_works(&ids, &json)?;
+        info!("Found {} works for DLsite circle '{}'", works.len(), circle.code());
+        Ok(CircleData::new(name, works))
+    }
+
+    async fn get_fanza_circle(&self, circle: &CircleArgs) -> Result<CircleData, ScrapeProductsError> {
+        let url = Url::parse(&circle.url())
+            .with_context(|| format!("Error building url of FANZA circle '{}'", circle.code()))?;
+        let body = self.client.get_text(url).await
+            .with_context(|| format!("Error getting FANZA circle '{}'", circle.code()))?;
+        let (name, works) = parse_fanza_circle(Document::from(body.as_str()))?;
+        info!("Found {} works for FANZA circle '{}'", works.len(), circle.code());
+        Ok(CircleData::new(name, works))
+    }
+}
+
+#[async_trait]
+impl DigitalScraper for DigitalScraperImpl {
+    async fn get_circle(&self, circle: &CircleArgs) -> Result<CircleData, ScrapeProductsError> {
+        match circle.store() {
+            Store::Dlsite => self.get_dlsite_circle(circle).await,
+            Store::Fanza => self.get_fanza_circle(circle).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dlsite_work_json_url() {
+        assert_eq!(
+            DigitalScraperImpl::dlsite_work_json_url(&["RJ01234567".to_owned(), "RJ01100001".to_owned()]).unwrap().as_str(),
+            "https://www.dlsite.com/maniax/product/info/ajax?product_id=RJ01234567%2CRJ01100001"
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use crate::domain::digital::models::product::{Sale, WorkData};
+use crate::outbound::digital_scraper::DLSITE_WORK_URL;
+use chrono::NaiveDateTime;
+use itertools::Itertools;
+use select::document::Document;
+use select::node::Node;
+use select::predicate::{Class, Name, Predicate};
+use serde::Deserialize;
+use std::collections::HashMap;
+use thiserror::Error;
+
+const DLSITE_SALE_END_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+const FANZA_SALE_END_FORMAT: &str = "%Y/%m/%d %H:%M";
+
+/// The name of a DLsite circle and the ids of the works on its profile page, e.g. `RJ01234567`.
+pub fn parse_dlsite_circle(document: Document) -> Result<(String, Vec<String>), ParseError> {
+    let name = document.find(Class("prof_maker_name")).next()
+        .map(|n| n.text().trim().to_owned())
+        .filter(|n| !n.is_empty())
+        .ok_or(ParseError::CircleNameNotFound)?;
+    let ids = document.find(Class("work_thumb"))
+        .map(|node| {
+            node.attr("data-product_id")
+                .map(|id| id.to_owned())
+                .ok_or_else(|| ParseError::WorkIdNotFound(node.text().trim().to_owned()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((name, ids.into_iter().unique().collect()))
+}
+
+#[derive(Debug, Deserialize)]
+struct DlsiteWorkJson {
+    work_name: String,
+    maker_name: String,
+    work_image: String,
+    price: i32,
+    official_price: i32,
+    discount_end_date: Option<String>,
+}
+
+/// Parses the json DLsite serves for a list of work ids, works the json leaves out were taken down and are skipped.
+pub fn parse_dlsite_works(ids: &[String], json: &str) -> Result<Vec<WorkData>, ParseError> {
+    let mut works = serde_json::from_str::<HashMap<String, DlsiteWorkJson>>(json)
+        .map_err(|e| ParseError::WorkJsonInvalid(e.to_string()))?;
+    ids.iter()
+        .filter_map(|id| works.remove(id).map(|work| (id, work)))
+        .map(|(id, work)| {
+            let sale = match work.price < work.official_price {
+                true => {
+                    let end = work.discount_end_date
+                        .map(|end| NaiveDateTime::parse_from_str(&end, DLSITE_SALE_END_FORMAT).map_err(|_| ParseError::SaleEndUnknown(end)))
+                        .transpose()?;
+                    Some(Sale::new(work.price, end))
+                }
+                false => None,
+            };
+            // images are linked without a scheme
+            let image_url = match work.work_image.starts_with("//") {
+                true => format!("https:{}", work.work_image),
+                false => work.work_image,
+            };
+            Ok(WorkData::new(DLSITE_WORK_URL.replace("{id}", id), work.work_name, work.maker_name, image_url, work.official_price, sale))
+        })
+        .collect()
+}
+
+/// The name of a FANZA circle and its works, which the list shows with their prices.
+pub fn parse_fanza_circle(document: Document) -> Result<(String, Vec<WorkData>), ParseError> {
+    let name = document.find(Class("circleName__txt")).next()
+        .map(|n| n.text().trim().to_owned())
+        .filter(|n| !n.is_empty())
+        .ok_or(ParseError::CircleNameNotFound)?;
+    let works = document.find(Class("productList__item"))
+        .map(|item| parse_fanza_work(item, &name))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((name, works.into_iter().unique_by(|w| w.url().to_owned()).collect()))
+}
+
+fn parse_fanza_work(item: Node, circle_name: &str) -> Result<WorkData, ParseError> {
+    let a = item.find(Class("tileListTtl__txt")).next()
+        .ok_or(ParseError::WorkLinkNodeNotFound)?;
+    let href = a.attr("href")
+        .ok_or_else(|| ParseError::WorkUrlNotFound(a.text()))?;
+    // links carry the tracking parameters of the list
+    let url = href.split('?').next().unwrap_or(href).to_owned();
+    let title = a.text().trim().to_owned();
+    if title.is_empty() {
+        return Err(ParseError::WorkTitleNotFound(url));
+    }
+    let image_url = item.find(Class("tileListImg__tmb").descendant(Name("img"))).next()
+        .and_then(|i| i.attr("src"))
+        .map(|src| src.to_owned())
+        .ok_or_else(|| ParseError::WorkImageUrlNotFound(url.clone()))?;
+    let price = item.find(Class("c_txt_price")).next()
+        .map(|n| parse_fanza_price(&n.text()))
+        .ok_or_else(|| ParseError::WorkPriceNotFound(url.clone()))??;
+    // discounted works show the list price struck through next to the sale price
+    let list_price = item.find(Class("c_txt_price--del")).next()
+        .map(|n| parse_fanza_price(&n.text()))
+        .transpose()?;
+    let (list_price, sale) = match list_price {
+        Some(list_price) if price < list_price => {
+            let end = item.find(Class("c_txt_campaignEnd")).next()
+                .map(|n| {
+                    let text = n.text().trim().trim_end_matches("まで").trim().to_owned();
+                    NaiveDateTime::parse_from_str(&text, FANZA_SALE_END_FORMAT).map_err(|_| ParseError::SaleEndUnknown(text))
+                })
+                .transpose()?;
+            (list_price, Some(Sale::new(price, end)))
+        }
+        _ => (price, None),
+    };
+    Ok(WorkData::new(url, title, circle_name.to_owned(), image_url, list_price, sale))
+}
+
+fn parse_fanza_price(text: &str) -> Result<i32, ParseError> {
+    let value = text.trim().replace([',', '円'], "");
+    value.parse::<i32>()
+        .map_err(|_| ParseError::WorkPriceUnknown(text.trim().to_owned()))
+}
+
+#[derive(Debug, Error)]
+pub enum ParseError {
+    #[error("Could not find circle name")]
+    CircleNameNotFound,
+    #[error("Could not find id of work {0}")]
+    WorkIdNotFound(String),
+    #[error("Invalid json of works: {0}")]
+    WorkJsonInvalid(String),
+    #[error("Could not find work link node")]
+    WorkLinkNodeNotFound,
+    #[error("Could not find work url in link node: {0}")]
+    WorkUrlNotFound(String),
+    #[error("Could not find title of work {0}")]
+    WorkTitleNotFound(String),
+    #[error("Could not find image url of work {0}")]
+    WorkImageUrlNotFound(String),
+    #[error("Could not find price of work {0}")]
+    WorkPriceNotFound(String),
+    #[error("Unknown work price: {0}")]
+    WorkPriceUnknown(String),
+    #[error("Unknown sale end: {0}")]
+    SaleEndUnknown(String),
+}
+
+#[cfg(test)]
+mod test {
+    use crate::domain::digital::models::product::Sale;
+    use crate::outbound::digital_scraper::parser::{parse_dlsite_circle, parse_dlsite_works, parse_fanza_circle};
+    use chrono::NaiveDateTime;
+    use select::document::Document;
+
+    #[test]
+    fn test_parse_dlsite_circle() {
+        let document = Document::from(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/test-data/digital/dlsite-circle.html")));
+        let (name, ids) = parse_dlsite_circle(document).unwrap();
+        assert_eq!(name, "まふゆ工房");
+        assert_eq!(ids, vec!["RJ01234567", "RJ01100001"]);
+
+        let document = Document::from(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/test-data/digital/dlsite-circle-empty.html")));
+        let (name, ids) = parse_dlsite_circle(document).unwrap();
+        assert_eq!(name, "新しいサークル");
+        assert!(ids.is_empty());
+        assert!(parse_dlsite_circle(Document::from("<html></html>")).is_err());
+    }
+
+    #[test]
+    fn test_parse_dlsite_works() {
+        let json = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/test-data/digital/dlsite-works.json"));
+        let ids = vec!["RJ01234567".to_owned(), "RJ09999999".to_owned(), "RJ01100001".to_owned()];
+        let works = parse_dlsite_works(&ids, json).unwrap();
+        let urls = works.iter().map(|w| w.url()).collect::<Vec<_>>();
+        assert_eq!(urls, vec![
+            "https://www.dlsite.com/maniax/work/=/product_id/RJ01234567.html",
+            "https://www.dlsite.com/maniax/work/=/product_id/RJ01100001.html",
+        ]);
+
+        let discounted = works.first().unwrap();
+        assert_eq!(discounted.title(), "冬のアトリエ");
+        assert_eq!(discounted.circle_name(), "まふゆ工房");
+        assert_eq!(discounted.image_url(), "https://img.dlsite.jp/modpub/images2/work/doujin/RJ01235000/RJ01234567_img_main.jpg");
+        assert_eq!(discounted.list_price(), 1100);
+        let end = NaiveDateTime::parse_from_str("2026-10-31 23:59:59", "%Y-%m-%d %H:%M:%S").unwrap();
+        assert_eq!(discounted.sale(), Some(&Sale::new(770, Some(end))));
+
+        let regular = works.last().unwrap();
+        assert_eq!(regular.list_price(), 880);
+        assert_eq!(regular.sale(), None);
+        assert!(parse_dlsite_works(&ids, "<html></html>").is_err());
+    }
+
+    #[test]
+    fn test_parse_fanza_circle() {
+        let document = Document::from(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/test-data/digital/fanza-circle.html")));
+        let (name, works) = parse_fanza_circle(document).unwrap();
+        assert_eq!(name, "まふゆ工房");
+        let urls = works.iter().map(|w| w.url()).collect::<Vec<_>>();
+        assert_eq!(urls, vec![
+            "https://www.dmm.co.jp/dc/doujin/-/detail/=/cid=d_123456/",
+            "https://www.dmm.co.jp/dc/doujin/-/detail/=/cid=d_100001/",
+        ]);
+
+        let discounted = works.first().unwrap();
+        assert_eq!(discounted.title(), "冬のアトリエ");
+        assert_eq!(discounted.circle_name(), "まふゆ工房");
+        assert_eq!(discounted.image_url(), "https://doujin-assets.dmm.co.jp/digital/comic/d_123456/d_123456pt.jpg");
+        assert_eq!(discounted.list_price(), 1100);
+        let end = NaiveDateTime::parse_from_str("2026-10-31 23:59", "%Y-%m-%d %H:%M").unwrap();
+        assert_eq!(discounted.sale(), Some(&Sale::new(770, Some(end))));
+
+        let regular = works.last().unwrap();
+        assert_eq!(regular.list_price(), 880);
+        assert_eq!(regular.sale(), None);
+    }
+}
@@ -0,0 +1,119 @@
+use crate::config::DiscordSettings;
+use crate::domain::digital::models::product::Product as DigitalProduct;
+use crate::domain::digital::ports::DigitalNotifier;
+use crate::domain::figure::models::product::Product as FigureProduct;
+use crate::domain::figure::ports::FigureNotifier;
+use crate::domain::site::{SiteNotifier, SiteProduct};
+use async_trait::async_trait;
+use log::error;
+use std::marker::PhantomData;
+use webhook::client::WebhookClient;
+
+const DISCORD_URL: &str = "https://discord.com/api/webhooks/";
+
+/// Sends the products of any site as embeds of a webhook, the site formats the embed description.
+#[derive(Debug, Clone)]
+pub struct DiscordNotifier<P> {
+    settings: Option<DiscordSettings>,
+    public_url: Option<String>,
+    product: PhantomData<fn() -> P>,
+}
+
+impl<P: SiteProduct> DiscordNotifier<P> {
+    pub fn new(settings: Option<DiscordSettings>) -> Self {
+        Self {
+            settings,
+            public_url: None,
+            product: PhantomData,
+        }
+    }
+
+    /// Embeds link the cached thumbnails under this url instead of the shop's images.
+    pub fn with_public_url(mut self, public_url: Option<String>) -> Self {
+        self.public_url = public_url;
+        self
+    }
+
+    fn thumbnail_url(&self, product: &P) -> String {
+        match (&self.public_url, product.image_hash()) {
+            (Some(public_url), Some(image_hash)) => format!("{}/images/{}/thumbnail", public_url, image_hash),
+            _ => product.image_url().to_owned(),
+        }
+    }
+
+    async fn send_products_notifications<Q: AsRef<P>>(&self, content: &str, products: &[Q]) -> Result<(), anyhow::Error> {
+        if self.settings.is_none() {
+            return Ok(());
+        }
+        let settings = self.settings.as_ref().unwrap();
+        let url = format!("{}{}", DISCORD_URL, &settings.api_key);
+        let client = WebhookClient::new(&url);
+        for product_chunk in products.chunks(settings.chunk_size as usize) {
+            client.send(|mut message| {
+                message = message
+                    .content(content)
+                    .username(settings.username.as_str());
+                if let Some(image_url) = &settings.image_url {
+                    message = message.avatar_url(image_url);
+                }
+                for product in product_chunk {
+                    let product = product.as_ref();
+                    message = message
+                        .embed(|embed| embed
+                            .title(product.title())
+                            .url(product.url())
+                            .description(&product.summary())
+                            .thumbnail(&self.thumbnail_url(product))
+                        );
+                }
+                message
+            }).await.map_err(|e| anyhow::anyhow!("{:?}", e))?;
+            tokio::time::sleep(core::time::Duration::from_secs(1)).await;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<P: SiteProduct> SiteNotifier<P> for DiscordNotifier<P> {
+    async fn new_products<Q: AsRef<P> + Sync>(&self, target: &str, products: &[Q]) {
+        let content = format!("{}: new products available", P::notification_target(target));
+        if let Err(e) = self.send_products_notifications(&content, products).await {
+            error!("Unable to send new product notifications: {}", e);
+        }
+    }
+
+    async fn restocked_products<Q: AsRef<P> + Sync>(&self, target: &str, products: &[Q]) {
+        let content = format!("{}: products available again", P::notification_target(target));
+        if let Err(e) = self.send_products_notifications(&content, products).await {
+            error!("Unable to send restocked product notifications: {}", e);
+        }
+    }
+}
+
+#[async_trait]
+impl DigitalNotifier for DiscordNotifier<DigitalProduct> {
+    async fn discounted_products<Q: AsRef<DigitalProduct> + Sync>(&self, target: &str, products: &[Q]) {
+        let content = format!("{}: products on sale", DigitalProduct::notification_target(target));
+        if let Err(e) = self.send_products_notifications(&content, products).await {
+            error!("Unable to send discounted product notifications: {}", e);
+        }
+    }
+}
+
+#[async_trait]
+impl FigureNotifier for DiscordNotifier<FigureProduct> {
+    async fn preorders_opened<Q: AsRef<FigureProduct> + Sync>(&self, target: &str, products: &[Q]) {
+        let content = format!("{}: preorders opened", FigureProduct::notification_target(target));
+        if let Err(e) = self.send_products_notifications(&content, products).await {
+            error!("Unable to send opened preorder notifications: {}", e);
+        }
+    }
+
+    async fn preorders_closing<Q: AsRef<FigureProduct> + Sync>(&self, target: &str, products: &[Q]) {
+        let content = format!("{}: preorders closing soon", FigureProduct::notification_target(target));
+        if let Err(e) = self.send_products_notifications(&content, products).await {
+            error!("Unable to send closing preorder notifications: {}", e);
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use crate::domain::figure::models::product::{ScrapeProductsError, SourceData};
+use crate::domain::figure::models::source::{SourceArgs, Store};
+use crate::domain::figure::ports::FigureScraper;
+use crate::outbound::figure_scraper::parser::{parse_gsc_list, parse_hobbysearch_list};
+use crate::outbound::scraper_client::ScraperClient;
+use anyhow::Context;
+use async_trait::async_trait;
+use log::info;
+pub use parser::ParseError;
+use reqwest::Url;
+use select::document::Document;
+
+mod parser;
+
+const HOBBYSEARCH_BASE_URL: &str = "https://www.1999.co.jp";
+const GSC_BASE_URL: &str = "https://www.goodsmile.com";
+
+#[derive(Debug, Clone)]
+pub struct FigureScraperImpl {
+    client: ScraperClient,
+}
+
+impl FigureScraperImpl {
+    pub fn new() -> Result<Self, anyhow::Error> {
+        let client = ScraperClient::new("FigureScraper", &[])?;
+        Ok(FigureScraperImpl { client })
+    }
+
+}
+
+#[async_trait]
+impl FigureScraper for FigureScraperImpl {
+    /// Only the first page of the search is loaded, new figures are listed first.
+    async fn get_source(&self, source: &SourceArgs) -> Result<SourceData, ScrapeProductsError> {
+        let url = Url::parse(&source.url())
+            .with_context(|| format!("Error building url of {} {} '{}'", source.store(), source.kind(), source.code()))?;
+        let body = self.client.get_text(url).await
+            .with_context(|| format!("Error getting {} {} '{}'", source.store(), source.kind(), source.code()))?;
+        let document = Document::from(body.as_str());
+        let (name, items) = match source.store() {
+            Store::HobbySearch => parse_hobbysearch_list(document)?,
+            Store::Gsc => parse_gsc_list(document)?,
+        };
+        info!("Found {} items for {} {} '{}'", items.len(), source.store(), source.kind(), source.code());
+        Ok(SourceData::new(name, items))
+    }
+}
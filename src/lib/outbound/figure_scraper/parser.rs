@@ -0,0 +1,212 @@
+use crate::domain::figure::models::product::{ItemData, PreorderWindow};
+use crate::outbound::figure_scraper::{GSC_BASE_URL, HOBBYSEARCH_BASE_URL};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use itertools::Itertools;
+use select::document::Document;
+use select::node::Node;
+use select::predicate::{Class, Name, Predicate};
+use thiserror::Error;
+
+const HOBBYSEARCH_DEADLINE_FORMAT: &str = "%Y/%m/%d";
+const GSC_PERIOD_FORMAT: &str = "%Y/%m/%d %H:%M";
+
+/// The maker or series and its figures as listed by a HobbySearch search, newest first.
+pub fn parse_hobbysearch_list(document: Document) -> Result<(String, Vec<ItemData>), ParseError> {
+    let name = node_text(document.find(Class("SearchCondition__name")).next())
+        .ok_or(ParseError::SourceNameNotFound)?;
+    let items = document.find(Class("ListItem"))
+        .map(parse_hobbysearch_item)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((name, items.into_iter().unique_by(|i| i.url().to_owned()).collect()))
+}
+
+fn parse_hobbysearch_item(item: Node) -> Result<ItemData, ParseError> {
+    let a = item.find(Class("ListItem__name").descendant(Name("a"))).next()
+        .ok_or(ParseError::ItemLinkNodeNotFound)?;
+    let href = a.attr("href")
+        .ok_or_else(|| ParseError::ItemUrlNotFound(a.text()))?;
+    let url = absolute_url(HOBBYSEARCH_BASE_URL, href);
+    let title = a.text().trim().to_owned();
+    if title.is_empty() {
+        return Err(ParseError::ItemTitleNotFound(url));
+    }
+    let image_url = item.find(Class("ListItem__thumbnail").descendant(Name("img"))).next()
+        .and_then(|i| i.attr("src"))
+        .map(|src| absolute_url(HOBBYSEARCH_BASE_URL, src))
+        .ok_or_else(|| ParseError::ItemImageUrlNotFound(url.clone()))?;
+    let maker_name = node_text(item.find(Class("ListItem__maker")).next()).unwrap_or_default();
+    let price = node_text(item.find(Class("ListItem__price")).next())
+        .map(|p| parse_price(&p))
+        .transpose()?
+        .flatten();
+    let release_date = node_text(item.find(Class("ListItem__release")).next());
+    // HobbySearch takes preorders from the listing until the deadline, which is the last day of the window
+    let preorder = match node_text(item.find(Class("ListItem__status")).next()).as_deref() {
+        Some("予約受付中") => {
+            let end = node_text(item.find(Class("ListItem__deadline")).next())
+                .map(|d| {
+                    let text = d.trim_start_matches("予約締切").trim().to_owned();
+                    NaiveDate::parse_from_str(&text, HOBBYSEARCH_DEADLINE_FORMAT)
+                        .map(|date| date.and_time(NaiveTime::from_hms_opt(23, 59, 59).unwrap()))
+                        .map_err(|_| ParseError::PreorderPeriodUnknown(d))
+                })
+                .transpose()?;
+            Some(PreorderWindow::new(None, end))
+        }
+        _ => None,
+    };
+    Ok(ItemData::new(url, title, maker_name, image_url, price, release_date, preorder))
+}
+
+/// The brand or series and its figures as listed by a Good Smile search, newest first.
+pub fn parse_gsc_list(document: Document) -> Result<(String, Vec<ItemData>), ParseError> {
+    let name = node_text(document.find(Class("c-search-title__name")).next())
+        .ok_or(ParseError::SourceNameNotFound)?;
+    let items = document.find(Class("c-product-card"))
+        .map(parse_gsc_item)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((name, items.into_iter().unique_by(|i| i.url().to_owned()).collect()))
+}
+
+fn parse_gsc_item(item: Node) -> Result<ItemData, ParseError> {
+    let a = item.find(Class("c-product-card__link")).next()
+        .ok_or(ParseError::ItemLinkNodeNotFound)?;
+    let href = a.attr("href")
+        .ok_or_else(|| ParseError::ItemUrlNotFound(a.text()))?;
+    let url = absolute_url(GSC_BASE_URL, href);
+    let title = node_text(item.find(Class("c-product-card__name")).next())
+        .ok_or_else(|| ParseError::ItemTitleNotFound(url.clone()))?;
+    let image_url = item.find(Class("c-product-card__image")).next()
+        .and_then(|i| i.attr("src"))
+        .map(|src| absolute_url(GSC_BASE_URL, src))
+        .ok_or_else(|| ParseError::ItemImageUrlNotFound(url.clone()))?;
+    let maker_name = node_text(item.find(Class("c-product-card__brand")).next()).unwrap_or_default();
+    let price = node_text(item.find(Class("c-product-card__price")).next())
+        .map(|p| parse_price(&p))
+        .transpose()?
+        .flatten();
+    let release_date = node_text(item.find(Class("c-product-card__release")).next());
+    // only figures taking preorders show their period
+    let preorder = node_text(item.find(Class("c-product-card__period")).next())
+        .map(|p| parse_gsc_period(&p))
+        .transpose()?;
+    Ok(ItemData::new(url, title, maker_name, image_url, price, release_date, preorder))
+}
+
+/// Parses e.g. `予約受付期間：2026/10/01 12:00～2026/11/13 21:00`, either end may be left out.
+fn parse_gsc_period(text: &str) -> Result<PreorderWindow, ParseError> {
+    let period = text.split_once('：').map(|(_, p)| p).unwrap_or(text);
+    let (start, end) = period.split_once('～')
+        .ok_or_else(|| ParseError::PreorderPeriodUnknown(text.to_owned()))?;
+    let parse = |date: &str| match date.trim() {
+        "" => Ok(None),
+        date => NaiveDateTime::parse_from_str(date, GSC_PERIOD_FORMAT)
+            .map(Some)
+            .map_err(|_| ParseError::PreorderPeriodUnknown(text.to_owned())),
+    };
+    Ok(PreorderWindow::new(parse(start)?, parse(end)?))
+}
+
+/// Prices are announced later for some figures, which is shown instead of the price.
+fn parse_price(text: &str) -> Result<Option<i32>, ParseError> {
+    if text.contains("未定") {
+        return Ok(None);
+    }
+    let value = text.split('円').next().unwrap_or(text).replace(',', "");
+    value.trim().parse::<i32>()
+        .map(Some)
+        .map_err(|_| ParseError::ItemPriceUnknown(text.to_owned()))
+}
+
+fn node_text(node: Option<Node>) -> Option<String> {
+    node.map(|n| n.text().trim().to_owned())
+        .filter(|t| !t.is_empty())
+}
+
+fn absolute_url(base_url: &str, url: &str) -> String {
+    match url.starts_with('/') {
+        true => format!("{}{}", base_url, url),
+        false => url.to_owned(),
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ParseError {
+    #[error("Could not find name of maker or series")]
+    SourceNameNotFound,
+    #[error("Could not find item link node")]
+    ItemLinkNodeNotFound,
+    #[error("Could not find item url in link node: {0}")]
+    ItemUrlNotFound(String),
+    #[error("Could not find title of item {0}")]
+    ItemTitleNotFound(String),
+    #[error("Could not find image url of item {0}")]
+    ItemImageUrlNotFound(String),
+    #[error("Unknown item price: {0}")]
+    ItemPriceUnknown(String),
+    #[error("Unknown preorder period: {0}")]
+    PreorderPeriodUnknown(String),
+}
+
+#[cfg(test)]
+mod test {
+    use crate::domain::figure::models::product::PreorderWindow;
+    use crate::outbound::figure_scraper::parser::{parse_gsc_list, parse_gsc_period, parse_hobbysearch_list};
+    use chrono::NaiveDateTime;
+    use select::document::Document;
+
+    #[test]
+    fn test_parse_hobbysearch_list() {
+        let document = Document::from(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/test-data/figure/hobbysearch-list.html")));
+        let (name, items) = parse_hobbysearch_list(document).unwrap();
+        assert_eq!(name, "グッドスマイルカンパニー");
+        let urls = items.iter().map(|i| i.url()).collect::<Vec<_>>();
+        assert_eq!(urls, vec!["https://www.1999.co.jp/10123456", "https://www.1999.co.jp/10123400", "https://www.1999.co.jp/10100001"]);
+
+        let preorder = items.first().unwrap();
+        assert_eq!(preorder.title(), "ねんどろいど まふゆ");
+        assert_eq!(preorder.maker_name(), "グッドスマイルカンパニー");
+        assert_eq!(preorder.image_url(), "https://www.1999.co.jp/itbig12/10123456.jpg");
+        assert_eq!(preorder.price(), Some(5800));
+        assert_eq!(preorder.release_date(), Some("2027年03月"));
+        let end = NaiveDateTime::parse_from_str("2026-11-20 23:59:59", "%Y-%m-%d %H:%M:%S").unwrap();
+        assert_eq!(preorder.preorder(), Some(&PreorderWindow::new(None, Some(end))));
+
+        let unannounced = &items[1];
+        assert_eq!(unannounced.price(), None);
+        assert_eq!(unannounced.preorder(), Some(&PreorderWindow::new(None, None)));
+        assert_eq!(items.last().unwrap().preorder(), None);
+        assert!(parse_hobbysearch_list(Document::from("<html></html>")).is_err());
+    }
+
+    #[test]
+    fn test_parse_gsc_list() {
+        let document = Document::from(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/test-data/figure/gsc-list.html")));
+        let (name, items) = parse_gsc_list(document).unwrap();
+        assert_eq!(name, "ねんどろいど");
+        let urls = items.iter().map(|i| i.url()).collect::<Vec<_>>();
+        assert_eq!(urls, vec!["https://www.goodsmile.com/ja/product/12345", "https://www.goodsmile.com/ja/product/12000"]);
+
+        let preorder = items.first().unwrap();
+        assert_eq!(preorder.title(), "ねんどろいど まふゆ");
+        assert_eq!(preorder.maker_name(), "グッドスマイルカンパニー");
+        assert_eq!(preorder.image_url(), "https://images.goodsmile.info/cgm/images/product/12345/main.jpg");
+        assert_eq!(preorder.price(), Some(5800));
+        let date = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+        assert_eq!(preorder.preorder(), Some(&PreorderWindow::new(Some(date("2026-10-01 12:00")), Some(date("2026-11-13 21:00")))));
+        assert_eq!(items.last().unwrap().preorder(), None);
+
+        let document = Document::from(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/test-data/figure/gsc-list-empty.html")));
+        let (name, items) = parse_gsc_list(document).unwrap();
+        assert_eq!(name, "新しいブランド");
+        assert!(items.is_empty());
+    }
+
+    #[test]
+    fn test_parse_gsc_period() {
+        let date = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+        assert_eq!(parse_gsc_period("予約受付期間：2026/10/01 12:00～").unwrap(), PreorderWindow::new(Some(date("2026-10-01 12:00")), None));
+        assert_eq!(parse_gsc_period("～2026/11/13 21:00").unwrap(), PreorderWindow::new(None, Some(date("2026-11-13 21:00"))));
+        assert!(parse_gsc_period("予約受付期間：未定").is_err());
+    }
+}
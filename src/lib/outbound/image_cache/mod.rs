@@ -0,0 +1,193 @@
+use crate::domain::image::models::image::{CacheImageError, CachedImage, GetImageError, Image, ImageVariant};
+use crate::domain::image::ports::ImageCache;
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::DynamicImage;
+use log::info;
+use reqwest::Client;
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tempfile::NamedTempFile;
+
+const USER_AGENT_VALUE: &str = "Mozilla/5.0 (X11; Linux x86_64; rv:144.0) Gecko/20100101 Firefox/144.0";
+const THUMBNAIL_HEIGHT: u32 = 250;
+const THUMBNAIL_QUALITY: u8 = 85;
+
+mod phash;
+
+/// Stores the images as `<dir>/<first two hash chars>/<sha256 of the original>[.thumbnail]`.
+#[derive(Debug, Clone)]
+pub struct FsImageCache {
+    dir: PathBuf,
+    client: Client,
+}
+
+impl FsImageCache {
+    pub fn new(dir: PathBuf) -> Result<Self, anyhow::Error> {
+        let client = Client::builder()
+            .user_agent(USER_AGENT_VALUE)
+            .build()
+            .context("Failed to build FsImageCache client")?;
+        Ok(FsImageCache { dir, client })
+    }
+
+    /// `None` for anything but a sha256 hex digest, so a hash never escapes the cache dir.
+    fn image_path(&self, hash: &str, variant: ImageVariant) -> Option<PathBuf> {
+        if hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b)) {
+            return None;
+        }
+        let file_name = match variant {
+            ImageVariant::Original => hash.to_owned(),
+            ImageVariant::Thumbnail => format!("{}.thumbnail", hash),
+        };
+        Some(self.dir.join(&hash[..2]).join(file_name))
+    }
+
+    async fn download(&self, url: &str) -> Result<Vec<u8>, anyhow::Error> {
+        let response = self.client.get(url).send().await?;
+        info!("request GET '{}' returned with status {}", url, response.status());
+        let response = response.error_for_status()?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    async fn store_image(&self, image: &[u8], thumbnail: &[u8]) -> Result<String, anyhow::Error> {
+        let hash = hex::encode(Sha256::digest(image));
+        let original_path = self.image_path(&hash, ImageVariant::Original).unwrap();
+        let thumbnail_path = self.image_path(&hash, ImageVariant::Thumbnail).unwrap();
+        if let Some(parent) = original_path.parent() {
+            tokio::fs::create_dir_all(parent).await
+                .with_context(|| format!("cannot create image dir '{}'", parent.display()))?;
+        }
+        write_file(&thumbnail_path, thumbnail).await?;
+        write_file(&original_path, image).await?;
+        Ok(hash)
+    }
+}
+
+/// Writes to a temporary file next to `path` first, so a partially written image is never served.
+async fn write_file(path: &Path, data: &[u8]) -> Result<(), anyhow::Error> {
+    let (path, data) = (path.to_owned(), data.to_vec());
+    tokio::task::spawn_blocking(move || {
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let mut file = NamedTempFile::new_in(dir)
+            .with_context(|| format!("cannot create temporary image in '{}'", dir.display()))?;
+        file.write_all(&data)
+            .with_context(|| format!("cannot write image '{}'", file.path().display()))?;
+        file.persist(&path)
+            .with_context(|| format!("cannot move image to '{}'", path.display()))?;
+        Ok(())
+    }).await?
+}
+
+/// The image scaled down to `THUMBNAIL_HEIGHT` as jpeg, `None` for images already that small.
+fn scale_down(image: &DynamicImage) -> Option<Vec<u8>> {
+    if image.height() <= THUMBNAIL_HEIGHT {
+        return None;
+    }
+    let thumbnail = image.resize(u32::MAX, THUMBNAIL_HEIGHT, FilterType::Triangle).into_rgb8();
+    let mut jpeg = Vec::new();
+    thumbnail.write_with_encoder(JpegEncoder::new_with_quality(&mut jpeg, THUMBNAIL_QUALITY))
+        .inspect_err(|e| info!("cannot encode thumbnail: {}", e))
+        .ok()?;
+    Some(jpeg)
+}
+
+fn content_type(data: &[u8]) -> &'static str {
+    match data {
+        [0xFF, 0xD8, 0xFF, ..] => "image/jpeg",
+        [0x89, b'P', b'N', b'G', ..] => "image/png",
+        [b'G', b'I', b'F', b'8', ..] => "image/gif",
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => "image/webp",
+        _ => "application/octet-stream",
+    }
+}
+
+#[async_trait]
+impl ImageCache for FsImageCache {
+    async fn cache_image(&self, url: &str) -> Result<CachedImage, CacheImageError> {
+        let image = self.download(url).await
+            .with_context(|| format!("cannot download image '{}'", url))?;
+        // an image that cannot be decoded is still cached, without perceptual hash and as its own thumbnail
+        let (image, thumbnail, phash) = tokio::task::spawn_blocking(move || {
+            match image::load_from_memory(&image) {
+                Ok(decoded) => (image, scale_down(&decoded), Some(phash::perceptual_hash(&decoded))),
+                Err(e) => {
+                    info!("cannot decode image: {}", e);
+                    (image, None, None)
+                }
+            }
+        }).await.context("cannot decode image")?;
+        let hash = self.store_image(&image, thumbnail.as_deref().unwrap_or(&image)).await?;
+        Ok(CachedImage::new(hash, phash))
+    }
+
+    async fn get_image(&self, hash: &str, variant: ImageVariant) -> Result<Image, GetImageError> {
+        let path = self.image_path(hash, variant)
+            .ok_or_else(|| GetImageError::ImageMissing { hash: hash.to_owned() })?;
+        let data = match tokio::fs::read(&path).await {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Err(GetImageError::ImageMissing { hash: hash.to_owned() }),
+            Err(e) => return Err(anyhow!(e).context(format!("cannot read image '{}'", path.display())).into()),
+        };
+        Ok(Image::new(content_type(&data).to_owned(), data))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const JPEG: &[u8] = &[0xFF, 0xD8, 0xFF, 0xE0, 0x00];
+    const PNG: &[u8] = &[0x89, b'P', b'N', b'G', 0x0D];
+
+    #[tokio::test]
+    async fn test_store_and_get_image() {
+        let dir = std::env::temp_dir().join(format!("moe-scraper-images-{}", std::process::id()));
+        let cache = FsImageCache::new(dir.clone()).unwrap();
+        let hash = cache.store_image(JPEG, PNG).await.unwrap();
+        assert_eq!(hash, hex::encode(Sha256::digest(JPEG)));
+        assert!(dir.join(&hash[..2]).join(&hash).exists());
+
+        let original = cache.get_image(&hash, ImageVariant::Original).await.unwrap();
+        assert_eq!((original.content_type(), original.data()), ("image/jpeg", JPEG));
+        let thumbnail = cache.get_image(&hash, ImageVariant::Thumbnail).await.unwrap();
+        assert_eq!((thumbnail.content_type(), thumbnail.data()), ("image/png", PNG));
+
+        let missing = "0".repeat(64);
+        assert!(matches!(cache.get_image(&missing, ImageVariant::Original).await, Err(GetImageError::ImageMissing { .. })));
+        assert!(matches!(cache.get_image("../../etc/passwd", ImageVariant::Original).await, Err(GetImageError::ImageMissing { .. })));
+        tokio::fs::remove_dir_all(dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_write_image_and_thumbnail() {
+        let dir = std::env::temp_dir().join(format!("moe-scraper-write-{}", std::process::id()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        write_file(&dir.join("abc"), JPEG).await.unwrap();
+        write_file(&dir.join("abc.thumbnail"), PNG).await.unwrap();
+        assert_eq!(tokio::fs::read(dir.join("abc")).await.unwrap(), JPEG);
+        assert_eq!(tokio::fs::read(dir.join("abc.thumbnail")).await.unwrap(), PNG);
+        let mut entries = tokio::fs::read_dir(&dir).await.unwrap();
+        let mut files = 0;
+        while entries.next_entry().await.unwrap().is_some() {
+            files += 1;
+        }
+        assert_eq!(files, 2);
+        tokio::fs::remove_dir_all(dir).await.unwrap();
+    }
+
+    #[test]
+    fn test_scale_down() {
+        let image = DynamicImage::ImageRgb8(image::RgbImage::from_pixel(400, 500, image::Rgb([200, 30, 30])));
+        let thumbnail = scale_down(&image).unwrap();
+        assert_eq!(content_type(&thumbnail), "image/jpeg");
+        let thumbnail = image::load_from_memory(&thumbnail).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (200, 250));
+
+        let small = DynamicImage::ImageRgb8(image::RgbImage::new(100, 100));
+        assert_eq!(scale_down(&small), None);
+    }
+}
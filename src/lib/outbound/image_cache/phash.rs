@@ -0,0 +1,76 @@
+//! Perceptual hash of images.
+
+use image::imageops::FilterType;
+use image::DynamicImage;
+use std::f64::consts::PI;
+
+const HASH_IMAGE_SIZE: usize = 32;
+const HASH_SIZE: usize = 8;
+
+/// 64 bit pHash of the image: it is scaled down to a 32x32 grayscale image and the bits are its low frequency
+/// DCT coefficients above their median, so they barely change when the image is scaled, re-encoded or recolored.
+pub fn perceptual_hash(image: &DynamicImage) -> u64 {
+    let size = HASH_IMAGE_SIZE as u32;
+    let small = image.resize_exact(size, size, FilterType::Triangle).into_luma8()
+        .into_raw()
+        .into_iter()
+        .map(f64::from)
+        .collect::<Vec<_>>();
+    let cosines = (0..HASH_SIZE)
+        .map(|u| (0..HASH_IMAGE_SIZE).map(|x| ((2 * x + 1) as f64 * u as f64 * PI / (2 * HASH_IMAGE_SIZE) as f64).cos()).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let mut coefficients = Vec::with_capacity(HASH_SIZE * HASH_SIZE);
+    for v in 0..HASH_SIZE {
+        for u in 0..HASH_SIZE {
+            let mut sum = 0.0;
+            for y in 0..HASH_IMAGE_SIZE {
+                for x in 0..HASH_IMAGE_SIZE {
+                    sum += small[y * HASH_IMAGE_SIZE + x] * cosines[u][x] * cosines[v][y];
+                }
+            }
+            coefficients.push(sum);
+        }
+    }
+    // the DC term is the overall brightness, it would dominate the median
+    let mut sorted = coefficients[1..].to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let median = sorted[sorted.len() / 2];
+    coefficients.iter()
+        .enumerate()
+        .filter(|(_, c)| **c > median)
+        .fold(0_u64, |hash, (i, _)| hash | (1 << i))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const FIGURE: &[u8] = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/test-data/images/figure.jpg"));
+    // smaller, grayscale and lower quality
+    const FIGURE_RELISTED: &[u8] = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/test-data/images/figure-relisted.jpg"));
+    const OTHER: &[u8] = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/test-data/images/other.jpg"));
+
+    fn hash(data: &[u8]) -> u64 {
+        perceptual_hash(&image::load_from_memory(data).unwrap())
+    }
+
+    #[test]
+    fn test_perceptual_hash() {
+        let figure = hash(FIGURE);
+        let relisted = hash(FIGURE_RELISTED);
+        let other = hash(OTHER);
+
+        assert!((figure ^ relisted).count_ones() <= 4);
+        assert!((figure ^ other).count_ones() > 16);
+        assert!((relisted ^ other).count_ones() > 16);
+    }
+
+    #[test]
+    fn test_perceptual_hash_ignores_format() {
+        let figure = image::load_from_memory(FIGURE).unwrap();
+        let mut png = Vec::new();
+        figure.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png).unwrap();
+        assert_eq!(hash(&png), perceptual_hash(&figure));
+    }
+
+}
@@ -0,0 +1,124 @@
+use crate::domain::availability::Availability;
+use crate::domain::mandarake::models::product::ListingData;
+use crate::outbound::mandarake_scraper::PRODUCT_URL;
+use itertools::Itertools;
+use select::document::Document;
+use select::node::Node;
+use select::predicate::{Class, Name, Predicate};
+use thiserror::Error;
+
+pub fn parse_listings(document: Document) -> Result<Vec<ListingData>, ParseError> {
+    let entries = match document.find(Class("entry")).next() {
+        Some(entries) => entries,
+        // searches without results have no list at all
+        None if document.find(Class("noresult")).next().is_some() => return Ok(Vec::new()),
+        None => return Err(ParseError::ListingListNotFound),
+    };
+    let listings = entries.find(Class("block"))
+        .map(parse_listing)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(listings.into_iter().unique_by(|l| l.url().to_owned()).collect())
+}
+
+fn parse_listing(block: Node) -> Result<ListingData, ParseError> {
+    let a = block.find(Class("title").descendant(Name("a"))).next()
+        .ok_or(ParseError::ListingLinkNodeNotFound)?;
+    let href = a.attr("href")
+        .ok_or_else(|| ParseError::ListingUrlNotFound(a.text()))?;
+    let url = PRODUCT_URL.replace("{relative_url}", href);
+    let title = a.text().trim().to_owned();
+    if title.is_empty() {
+        return Err(ParseError::ListingTitleNotFound(url));
+    }
+    let image_url = block.find(Class("pic").descendant(Name("img"))).next()
+        .and_then(|i| i.attr("src"))
+        .map(|src| src.to_owned())
+        .ok_or_else(|| ParseError::ListingImageUrlNotFound(url.clone()))?;
+    let store = block.find(Class("shop")).next()
+        .map(|n| n.text().trim().to_owned())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| ParseError::ListingStoreNotFound(url.clone()))?;
+    // not every listing is graded, those without a condition are simply used
+    let condition = block.find(Class("condition")).next()
+        .map(|n| n.text().trim().trim_start_matches("状態").trim().to_owned())
+        .filter(|c| !c.is_empty())
+        .unwrap_or_else(|| "-".to_owned());
+    let price = parse_listing_price(block)?;
+    let availability = match block.find(Class("soldout")).next() {
+        Some(_) => Availability::NotAvailable,
+        None => Availability::Available,
+    };
+    Ok(ListingData::new(url, title, image_url, store, condition, price, availability))
+}
+
+fn parse_listing_price(block: Node) -> Result<i32, ParseError> {
+    let text = block.find(Class("price").descendant(Name("p"))).next()
+        .map(|n| n.text())
+        .ok_or(ParseError::ListingPriceNotFound)?;
+    // the tax included price follows in parentheses
+    let value = text.split('(').next().unwrap_or_default().trim().replace([',', '円'], "");
+    let price = value.parse::<i32>()
+        .map_err(|_| ParseError::ListingPriceUnknown(text.trim().to_owned()))?;
+    Ok(price)
+}
+
+#[derive(Debug, Error)]
+pub enum ParseError {
+    #[error("Could not find listing list")]
+    ListingListNotFound,
+    #[error("Could not find listing link node")]
+    ListingLinkNodeNotFound,
+    #[error("Could not find listing url in link node: {0}")]
+    ListingUrlNotFound(String),
+    #[error("Could not find title of listing {0}")]
+    ListingTitleNotFound(String),
+    #[error("Could not find image url of listing {0}")]
+    ListingImageUrlNotFound(String),
+    #[error("Could not find store of listing {0}")]
+    ListingStoreNotFound(String),
+    #[error("Could not find listing price")]
+    ListingPriceNotFound,
+    #[error("Unknown listing price: {0}")]
+    ListingPriceUnknown(String),
+}
+
+#[cfg(test)]
+mod test {
+    use crate::domain::availability::Availability;
+    use crate::outbound::mandarake_scraper::parser::parse_listings;
+    use select::document::Document;
+
+    #[test]
+    fn test_parse_listings() {
+        let document = Document::from(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/test-data/mandarake/listing-list.html")));
+        let listings = parse_listings(document).unwrap();
+        let urls = listings.iter().map(|l| l.url()).collect::<Vec<_>>();
+        assert_eq!(urls, vec![
+            "https://order.mandarake.co.jp/order/detailPage/item?itemCode=1240012345&ref=list",
+            "https://order.mandarake.co.jp/order/detailPage/item?itemCode=1240023456&ref=list",
+            "https://order.mandarake.co.jp/order/detailPage/item?itemCode=1240034567&ref=list",
+        ]);
+
+        let first = listings.first().unwrap();
+        assert_eq!(first.title(), "ほしまくら/まふゆ まふゆの冬休み");
+        assert_eq!(first.image_url(), "https://img.mandarake.co.jp/webshopimg/01/00/345/0100012345.jpg");
+        assert_eq!(first.store(), "中野店");
+        assert_eq!(first.condition(), "B");
+        assert_eq!(first.price(), 2500);
+        assert_eq!(first.availability(), &Availability::Available);
+
+        let ungraded = listings.get(1).unwrap();
+        assert_eq!(ungraded.store(), "コンプレックス");
+        assert_eq!(ungraded.condition(), "-");
+        assert_eq!(ungraded.price(), 12000);
+
+        let sold_out = listings.last().unwrap();
+        assert_eq!(sold_out.availability(), &Availability::NotAvailable);
+    }
+
+    #[test]
+    fn test_parse_no_listings() {
+        let document = Document::from(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/test-data/mandarake/listing-list-empty.html")));
+        assert!(parse_listings(document).unwrap().is_empty());
+    }
+}
@@ -1,13 +1,11 @@
 use crate::domain::melonbooks::models::product::{ProductData, ScrapeProductsError};
 use crate::domain::melonbooks::ports::MelonbooksScraper;
 use crate::outbound::melonbooks_scraper::parser::{parse_product_details, parse_product_list};
+use crate::outbound::scraper_client::ScraperClient;
 use anyhow::Context;
 use log::info;
 pub use parser::ParseError;
-use reqwest::cookie::Jar;
-use reqwest::{Client, Url};
 use select::document::Document;
-use std::sync::Arc;
 use async_trait::async_trait;
 
 mod parser;
@@ -18,19 +16,12 @@ const PRODUCT_URL: &str = "https://www.melonbooks.co.jp{relative_url}";
 
 #[derive(Debug, Clone)]
 pub struct MelonbooksScraperImpl {
-    client: Client,
+    client: ScraperClient,
 }
 
 impl MelonbooksScraperImpl {
     pub fn new() -> Result<Self, anyhow::Error> {
-        let jar = Jar::default();
-        jar.add_cookie_str("AUTH_ADULT=1", &BASE_URL.parse::<Url>()?);
-        let client = Client::builder()
-            //.use_rustls_tls()
-            .cookie_provider(Arc::new(jar))
-            .pool_max_idle_per_host(0)
-            .build()
-            .context("Failed to build MelonbooksScraper client")?;
+        let client = ScraperClient::new("MelonbooksScraper", &[("AUTH_ADULT=1", BASE_URL)])?;
         Ok(MelonbooksScraperImpl { client })
     }
 
@@ -38,11 +29,7 @@ impl MelonbooksScraperImpl {
         let url = ARTIST_URL
             .replace("{artist}", artist)
             .replace("{page}", page_no.to_string().as_str());
-        let response = self.client.get(&url).send().await?;
-        info!("request GET '{}' returned with status {}", url, response.status());
-        let body = response.text().await?;
-        let document = Document::from(body.as_str());
-        Ok(document)
+        self.client.get_document(url).await
     }
 
     async fn get_product_list_urls(&self, artist: &str, page_no: u32) -> Result<Vec<String>, ScrapeProductsError> {
@@ -70,16 +57,8 @@ impl MelonbooksScraperImpl {
         Ok(urls)
     }
 
-    async fn get_product_page(&self, url: &str) -> Result<Document, reqwest::Error> {
-        let response = self.client.get(url).send().await?;
-        info!("request GET '{}' returned with status {}", url, response.status());
-        let body = response.text().await?;
-        let document = Document::from(body.as_str());
-        Ok(document)
-    }
-
     async fn get_product(&self, url: &str) -> Result<ProductData, ScrapeProductsError> {
-        let document = self.get_product_page(&url).await
+        let document = self.client.get_document(url).await
             .with_context(|| format!("Error getting product details for url '{}'", url))?;
         let product = parse_product_details(document)?;
         info!("Parsed product '{}' ({})", product.title(), url);
@@ -1,4 +1,4 @@
-use crate::domain::melonbooks::models::availability::Availability;
+use crate::domain::availability::Availability;
 use crate::outbound::melonbooks_scraper::{ProductData, PRODUCT_URL};
 use itertools::Itertools;
 use regex::Regex;
@@ -201,7 +201,7 @@ pub enum ParseError {
 
 #[cfg(test)]
 mod test {
-    use crate::{domain::melonbooks::models::availability::Availability, outbound::melonbooks_scraper::parser::{parse_product_details, parse_product_list}};
+    use crate::{domain::availability::Availability, outbound::melonbooks_scraper::parser::{parse_product_details, parse_product_list}};
     use select::document::Document;
 
     #[test]
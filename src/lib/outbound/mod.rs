@@ -1,5 +1,12 @@
 pub mod amiami_scraper;
-pub mod amiami_discord_notifier;
+pub mod booth_scraper;
+pub mod digital_scraper;
+pub mod discord_notifier;
+pub mod figure_scraper;
+pub mod image_cache;
+pub mod mandarake_scraper;
+pub mod scraper_client;
 pub mod melonbooks_scraper;
-pub mod melonbooks_discord_notifier;
 pub mod sqlite;
+pub mod surugaya_scraper;
+pub mod toranoana_scraper;
\ No newline at end of file
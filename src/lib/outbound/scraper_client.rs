@@ -0,0 +1,47 @@
+use anyhow::Context;
+use log::info;
+use reqwest::cookie::Jar;
+use reqwest::{Client, IntoUrl, Url};
+use select::document::Document;
+use std::fmt::Display;
+use std::sync::Arc;
+
+/// The http client the scrapers fetch the shops' pages with.
+#[derive(Debug, Clone)]
+pub struct ScraperClient {
+    client: Client,
+}
+
+impl ScraperClient {
+    /// `cookies` are `(cookie, url)` pairs that confirm the age check of the shop,
+    /// without them adult products redirect to the age gate.
+    pub fn new(name: &str, cookies: &[(&str, &str)]) -> Result<Self, anyhow::Error> {
+        let jar = Jar::default();
+        for (cookie, url) in cookies {
+            jar.add_cookie_str(cookie, &url.parse::<Url>()?);
+        }
+        let client = Client::builder()
+            .cookie_provider(Arc::new(jar))
+            .pool_max_idle_per_host(0)
+            .build()
+            .with_context(|| format!("Failed to build {} client", name))?;
+        Ok(Self { client })
+    }
+
+    /// Fails for error statuses.
+    pub async fn get_text<U: IntoUrl + Display>(&self, url: U) -> Result<String, reqwest::Error> {
+        let log_url = url.to_string();
+        let response = self.client.get(url).send().await?;
+        info!("request GET '{}' returned with status {}", log_url, response.status());
+        response.error_for_status()?.text().await
+    }
+
+    /// The page of any status, the parsers find nothing on error pages.
+    pub async fn get_document<U: IntoUrl + Display>(&self, url: U) -> Result<Document, reqwest::Error> {
+        let log_url = url.to_string();
+        let response = self.client.get(url).send().await?;
+        info!("request GET '{}' returned with status {}", log_url, response.status());
+        let body = response.text().await?;
+        Ok(Document::from(body.as_str()))
+    }
+}
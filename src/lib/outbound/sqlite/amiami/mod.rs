@@ -1,17 +1,39 @@
-use crate::domain::amiami::models::product::{CreateProductArgs, CreateProductError, GetCategoriesError, GetProductsError, Product, UpdateProductArgs, UpdateProductError};
+use crate::domain::availability::Availability;
+use crate::domain::amiami::models::product::{CreateProductArgs, CreateProductError, FollowCategoryError, FollowedCategory, GetCategoriesError, GetMakersError, GetAvailabilityStatsError, GetProductsError, Product, ProductHistoryEntry, SetCategoryScheduleError, UnfollowCategoryError, UpdateProductArgs, UpdateProductError};
+use crate::domain::amiami::models::query::{ProductQuery, ProductSort};
+use crate::domain::amiami::models::release::ReleaseFilter;
 use crate::domain::amiami::ports::AmiamiRepository;
-use crate::outbound::sqlite::amiami::models::{CategoryRow, CategoryRowInsert, ProductRow, ProductRowInsert};
+use crate::domain::availability_stats::AvailabilityEvent;
+use crate::domain::pagination::{Page, PageRequest, SortDirection};
+use crate::domain::product_history::{sort_history, GetProductError};
+use crate::domain::schedule::{GetTargetSchedulesError, Schedule, SetDateScrapedError};
+use crate::domain::search::{FieldHighlight, SearchProductsError, SearchResult};
+use crate::outbound::sqlite::amiami::models::{AvailabilityEventRow, AvailabilityEventRowInsert, CategoryFollowerRow, CategoryFollowerRowInsert, CategoryRow, CategoryRowInsert, NotificationRow, PriceEventRow, PriceEventRowInsert, ProductRow, ProductRowInsert, ProductSearchRow};
+use crate::outbound::sqlite::schema::amiami_availability_event::dsl as availability_event_dsl;
 use crate::outbound::sqlite::schema::amiami_category::dsl as category_dsl;
+use crate::outbound::sqlite::schema::amiami_category_follower::dsl as category_follower_dsl;
+use crate::outbound::sqlite::schema::amiami_notification::dsl as notification_dsl;
+use crate::outbound::sqlite::schema::amiami_price_event::dsl as price_event_dsl;
 use crate::outbound::sqlite::schema::amiami_product::dsl as product_dsl;
-use crate::outbound::sqlite::Sqlite;
+use crate::outbound::sqlite::schema::app_user::dsl as user_dsl;
+use crate::outbound::sqlite::search::{count_matches, match_expression, parse_highlight, MAX_SEARCH_RESULTS};
+use crate::outbound::sqlite::{schema, Sqlite};
 use anyhow::Context;
 use async_trait::async_trait;
+use chrono::{DateTime, Days, NaiveDateTime, NaiveTime, Utc};
+use diesel::dsl::{count, sql};
 use diesel::prelude::*;
 use diesel::r2d2::ConnectionManager;
+use diesel::sql_types::{BigInt, Integer, Nullable, Text, Timestamp};
+use diesel::sqlite::Sqlite as SqliteBackend;
+use itertools::Itertools;
 use r2d2::PooledConnection;
+use std::collections::HashMap;
 
 mod models;
 
+const DATE_CHANGED_SORT_EXPRESSION: &str = "COALESCE(amiami_product.date_restocked, amiami_product.date_added)";
+
 impl Sqlite {
     fn get_amiami_product_row_by_url(
         &self,
@@ -27,6 +49,20 @@ impl Sqlite {
         Ok(product)
     }
 
+    fn get_amiami_product_row_by_id(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32
+    ) -> Result<Option<ProductRow>, anyhow::Error> {
+        let product = product_dsl::amiami_product
+            .select(ProductRow::as_select())
+            .find(product_id)
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get product with id '{}'", product_id))?;
+        Ok(product)
+    }
+
     fn get_amiami_product_rows(
         &self,
         connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
@@ -39,6 +75,157 @@ impl Sqlite {
         Ok(products)
     }
 
+    fn filtered_amiami_release_query<'a>(
+        &self,
+        filter: &'a ReleaseFilter,
+    ) -> schema::amiami_product::BoxedQuery<'a, SqliteBackend> {
+        let mut products = product_dsl::amiami_product.into_boxed();
+        if let Some(from) = filter.from() {
+            products = products.filter(product_dsl::release_date.ge(from));
+        }
+        if let Some(to) = filter.to() {
+            products = products.filter(product_dsl::release_date.le(to));
+        }
+        if let Some(category) = filter.category() {
+            products = products.filter(product_dsl::category_id.eq_any(
+                category_dsl::amiami_category
+                    .filter(category_dsl::category.eq(category))
+                    .select(category_dsl::id)
+            ));
+        }
+        if let Some(maker) = filter.maker() {
+            products = products.filter(product_dsl::maker.eq(maker));
+        }
+        products
+    }
+
+    fn get_amiami_product_rows_by_release(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        filter: &ReleaseFilter,
+    ) -> Result<Vec<ProductRow>, anyhow::Error> {
+        let products = self.filtered_amiami_release_query(filter)
+            .select(ProductRow::as_select())
+            .order_by((product_dsl::release_date.asc(), product_dsl::title.asc()))
+            .get_results(connection)
+            .with_context(|| format!("cannot get products for {:?}", filter))?;
+        Ok(products)
+    }
+
+    fn get_amiami_product_rows_page_by_release(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        filter: &ReleaseFilter,
+        page: PageRequest,
+    ) -> Result<(Vec<ProductRow>, i64), anyhow::Error> {
+        let total = self.filtered_amiami_release_query(filter)
+            .count()
+            .get_result::<i64>(connection)
+            .with_context(|| format!("cannot count products for {:?}", filter))?;
+        let products = self.filtered_amiami_release_query(filter)
+            .select(ProductRow::as_select())
+            .order_by((product_dsl::release_date.asc(), product_dsl::title.asc(), product_dsl::id.asc()))
+            .limit(page.page_size() as i64)
+            .offset(page.offset())
+            .get_results(connection)
+            .with_context(|| format!("cannot get products for {:?}", filter))?;
+        Ok((products, total))
+    }
+
+    fn filtered_amiami_product_query<'a>(
+        &self,
+        query: &'a ProductQuery,
+    ) -> schema::amiami_product::BoxedQuery<'a, SqliteBackend> {
+        let mut products = product_dsl::amiami_product.into_boxed();
+        if let Some(user_id) = query.followed_by() {
+            products = products.filter(product_dsl::category_id.eq_any(
+                category_follower_dsl::amiami_category_follower
+                    .filter(category_follower_dsl::user_id.eq(user_id))
+                    .select(category_follower_dsl::category_id)
+            ));
+        }
+        if let Some(category) = query.category() {
+            products = products.filter(product_dsl::category_id.eq_any(
+                category_dsl::amiami_category
+                    .filter(category_dsl::category.eq(category))
+                    .select(category_dsl::id)
+            ));
+        }
+        if let Some(availability) = query.availability() {
+            products = products.filter(product_dsl::availability.eq(availability.to_string()));
+        }
+        if let Some(added_from) = query.added_from() {
+            products = products.filter(product_dsl::date_added.ge(added_from.and_time(NaiveTime::MIN)));
+        }
+        if let Some(added_to) = query.added_to() {
+            products = products.filter(product_dsl::date_added.lt((added_to + Days::new(1)).and_time(NaiveTime::MIN)));
+        }
+        products
+    }
+
+    fn get_amiami_product_rows_page(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        query: &ProductQuery,
+    ) -> Result<(Vec<ProductRow>, i64), anyhow::Error> {
+        let total = self.filtered_amiami_product_query(query)
+            .count()
+            .get_result::<i64>(connection)
+            .with_context(|| format!("cannot count products for {:?}", query))?;
+        let products = self.filtered_amiami_product_query(query).select(ProductRow::as_select());
+        let products = match (query.sort(), query.direction()) {
+            (ProductSort::DateAdded, SortDirection::Asc) => products.order_by(product_dsl::date_added.asc()),
+            (ProductSort::DateAdded, SortDirection::Desc) => products.order_by(product_dsl::date_added.desc()),
+            (ProductSort::DateChanged, SortDirection::Asc) => products.order_by(sql::<Timestamp>(DATE_CHANGED_SORT_EXPRESSION).asc()),
+            (ProductSort::DateChanged, SortDirection::Desc) => products.order_by(sql::<Timestamp>(DATE_CHANGED_SORT_EXPRESSION).desc()),
+            (ProductSort::Title, SortDirection::Asc) => products.order_by(product_dsl::title.asc()),
+            (ProductSort::Title, SortDirection::Desc) => products.order_by(product_dsl::title.desc()),
+            (ProductSort::Price, SortDirection::Asc) => products.order_by(product_dsl::min_price.asc()),
+            (ProductSort::Price, SortDirection::Desc) => products.order_by(product_dsl::min_price.desc()),
+            (ProductSort::ReleaseDate, SortDirection::Asc) => products.order_by(product_dsl::release_date.asc()),
+            (ProductSort::ReleaseDate, SortDirection::Desc) => products.order_by(product_dsl::release_date.desc()),
+        };
+        let products = products
+            .then_order_by(product_dsl::id.desc())
+            .limit(query.page().page_size() as i64)
+            .offset(query.page().offset())
+            .get_results(connection)
+            .with_context(|| format!("cannot get products for {:?}", query))?;
+        Ok((products, total))
+    }
+
+    fn get_amiami_maker_names(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+    ) -> Result<Vec<String>, anyhow::Error> {
+        let makers = product_dsl::amiami_product
+            .select(product_dsl::maker)
+            .distinct()
+            .order_by(product_dsl::maker.asc())
+            .get_results(connection)
+            .with_context(|| "cannot get makers")?;
+        Ok(makers)
+    }
+
+    fn get_amiami_maker_names_page(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        page: PageRequest,
+    ) -> Result<(Vec<String>, i64), anyhow::Error> {
+        let total = product_dsl::amiami_product
+            .select(count(product_dsl::maker).aggregate_distinct())
+            .get_result::<i64>(connection)
+            .with_context(|| "cannot count makers")?;
+        let makers = product_dsl::amiami_product
+            .select(product_dsl::maker)
+            .distinct()
+            .order_by(product_dsl::maker.asc())
+            .limit(page.page_size() as i64)
+            .offset(page.offset())
+            .get_results(connection)
+            .with_context(|| "cannot get makers")?;
+        Ok((makers, total))
+    }
 
     fn insert_amiami_product_row(
         &self,
@@ -70,12 +257,23 @@ impl Sqlite {
         product: &ProductRow,
         args: &UpdateProductArgs,
     ) -> Result<ProductRow, anyhow::Error> {
+        let date_restocked = match !product.availability.is_available() && args.availability().is_available() {
+            true => Some(Utc::now().naive_utc()),
+            false => product.date_restocked,
+        };
+        if product.availability != args.availability() {
+            self.insert_amiami_availability_event_row(connection, product.id, Some(product.availability.clone()), args.availability())?;
+        }
+        if product.full_price != args.full_price() || product.min_price != args.min_price() {
+            self.insert_amiami_price_event_row(connection, product.id, args.full_price(), args.min_price())?;
+        }
         let product = diesel::update(&product)
             .set((
                 product_dsl::availability.eq(args.availability().to_string()),
                 product_dsl::full_price.eq(args.full_price()),
                 product_dsl::min_price.eq(args.min_price()),
-                product_dsl::release_date.eq(args.release_date())
+                product_dsl::release_date.eq(args.release_date()),
+                product_dsl::date_restocked.eq(date_restocked),
             ))
             .returning(ProductRow::as_returning())
             .get_result(connection)
@@ -83,24 +281,212 @@ impl Sqlite {
         Ok(product)
     }
 
-    fn get_following_amiami_category_rows(
+    fn insert_amiami_availability_event_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+        previous_availability: Option<Availability>,
+        availability: Availability,
+    ) -> Result<(), anyhow::Error> {
+        diesel::insert_into(availability_event_dsl::amiami_availability_event)
+            .values(AvailabilityEventRowInsert { product_id, availability, previous_availability: previous_availability.map(|a| a.to_string()) })
+            .execute(connection)
+            .with_context(|| format!("cannot insert availability event for product '{}'", product_id))?;
+        Ok(())
+    }
+
+    fn get_amiami_availability_event_rows(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+    ) -> Result<Vec<AvailabilityEventRow>, anyhow::Error> {
+        let events = availability_event_dsl::amiami_availability_event
+            .select(AvailabilityEventRow::as_select())
+            .order_by(availability_event_dsl::id.asc())
+            .get_results(connection)
+            .with_context(|| "cannot get availability events")?;
+        Ok(events)
+    }
+
+    fn get_amiami_category_availability_event_rows(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        since: NaiveDateTime,
+    ) -> Result<Vec<(i32, AvailabilityEventRow)>, anyhow::Error> {
+        let events = availability_event_dsl::amiami_availability_event
+            .inner_join(product_dsl::amiami_product)
+            .select((product_dsl::category_id, AvailabilityEventRow::as_select()))
+            .filter(availability_event_dsl::date_added.ge(since))
+            .order_by(availability_event_dsl::id.asc())
+            .get_results(connection)
+            .with_context(|| "cannot get availability events of categories")?;
+        Ok(events)
+    }
+
+    /// The id of the category of each product added since `since` after the first scrape of the category.
+    fn get_amiami_category_added_product_rows(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        since: NaiveDateTime,
+    ) -> Result<Vec<i32>, anyhow::Error> {
+        let category_ids = product_dsl::amiami_product
+            .inner_join(category_dsl::amiami_category)
+            .select(product_dsl::category_id)
+            .filter(product_dsl::date_added.ge(since))
+            .filter(product_dsl::date_added.nullable().gt(category_dsl::date_first_scraped))
+            .get_results(connection)
+            .with_context(|| "cannot get added products of categories")?;
+        Ok(category_ids)
+    }
+
+    fn insert_amiami_price_event_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+        full_price: i32,
+        min_price: i32,
+    ) -> Result<(), anyhow::Error> {
+        diesel::insert_into(price_event_dsl::amiami_price_event)
+            .values(PriceEventRowInsert { product_id, full_price, min_price })
+            .execute(connection)
+            .with_context(|| format!("cannot insert price event for product '{}'", product_id))?;
+        Ok(())
+    }
+
+    fn get_amiami_product_history_entries(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+    ) -> Result<Vec<ProductHistoryEntry>, anyhow::Error> {
+        let availability_events = availability_event_dsl::amiami_availability_event
+            .select(AvailabilityEventRow::as_select())
+            .filter(availability_event_dsl::product_id.eq(product_id))
+            .order_by(availability_event_dsl::id.asc())
+            .get_results(connection)
+            .with_context(|| format!("cannot get availability events for product '{}'", product_id))?;
+        let price_events = price_event_dsl::amiami_price_event
+            .select(PriceEventRow::as_select())
+            .filter(price_event_dsl::product_id.eq(product_id))
+            .order_by(price_event_dsl::id.asc())
+            .get_results(connection)
+            .with_context(|| format!("cannot get price events for product '{}'", product_id))?;
+        let notifications = notification_dsl::amiami_notification
+            .inner_join(user_dsl::app_user)
+            .select((NotificationRow::as_select(), user_dsl::username))
+            .filter(notification_dsl::product_id.eq(product_id))
+            .order_by(notification_dsl::id.asc())
+            .get_results::<(NotificationRow, String)>(connection)
+            .with_context(|| format!("cannot get notifications for product '{}'", product_id))?;
+        let mut history = availability_events.into_iter().map(|e| e.into_domain())
+            .chain(price_events.into_iter().map(|e| e.into_domain()))
+            .chain(notifications.into_iter().map(|(n, username)| n.into_domain(username)))
+            .collect::<Vec<_>>();
+        sort_history(&mut history);
+        Ok(history)
+    }
+
+    fn get_amiami_category_rows(
         &self,
         connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
     ) -> Result<Vec<CategoryRow>, anyhow::Error> {
         let categories = category_dsl::amiami_category
             .select(CategoryRow::as_select())
-            .filter(category_dsl::following.eq(true))
+            .order_by(category_dsl::category.asc())
+            .get_results(connection)
+            .with_context(|| "cannot get categories")?;
+        Ok(categories)
+    }
+
+    fn get_amiami_category_names_page(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        page: PageRequest,
+    ) -> Result<(Vec<String>, i64), anyhow::Error> {
+        let total = category_dsl::amiami_category
+            .count()
+            .get_result::<i64>(connection)
+            .with_context(|| "cannot count categories")?;
+        let categories = category_dsl::amiami_category
+            .select(category_dsl::category)
+            .order_by(category_dsl::category.asc())
+            .limit(page.page_size() as i64)
+            .offset(page.offset())
             .get_results(connection)
             .with_context(|| "cannot get categories")?;
+        Ok((categories, total))
+    }
+
+    fn get_following_amiami_category_names_page(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        user_id: i32,
+        page: PageRequest,
+    ) -> Result<(Vec<String>, i64), anyhow::Error> {
+        let total = category_follower_dsl::amiami_category_follower
+            .filter(category_follower_dsl::user_id.eq(user_id))
+            .count()
+            .get_result::<i64>(connection)
+            .with_context(|| format!("cannot count categories followed by user '{}'", user_id))?;
+        let categories = category_follower_dsl::amiami_category_follower
+            .inner_join(category_dsl::amiami_category)
+            .select(category_dsl::category)
+            .filter(category_follower_dsl::user_id.eq(user_id))
+            .order_by(category_dsl::category.asc())
+            .limit(page.page_size() as i64)
+            .offset(page.offset())
+            .get_results(connection)
+            .with_context(|| format!("cannot get categories followed by user '{}'", user_id))?;
+        Ok((categories, total))
+    }
+
+    fn get_following_amiami_category_rows(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        user_id: i32,
+    ) -> Result<Vec<CategoryRow>, anyhow::Error> {
+        let categories = category_follower_dsl::amiami_category_follower
+            .inner_join(category_dsl::amiami_category)
+            .select(CategoryRow::as_select())
+            .filter(category_follower_dsl::user_id.eq(user_id))
+            .order_by(category_dsl::category.asc())
+            .get_results(connection)
+            .with_context(|| format!("cannot get categories followed by user '{}'", user_id))?;
         Ok(categories)
     }
 
+    fn get_amiami_category_follower_rows(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+    ) -> Result<Vec<CategoryFollowerRow>, anyhow::Error> {
+        let followers = category_follower_dsl::amiami_category_follower
+            .select(CategoryFollowerRow::as_select())
+            .get_results(connection)
+            .with_context(|| "cannot get category followers")?;
+        Ok(followers)
+    }
+
+    fn is_amiami_category_followed_by(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        category: &CategoryRow,
+        user_id: i32,
+    ) -> Result<bool, anyhow::Error> {
+        let followed = diesel::select(diesel::dsl::exists(
+            category_follower_dsl::amiami_category_follower
+                .filter(category_follower_dsl::category_id.eq(category.id))
+                .filter(category_follower_dsl::user_id.eq(user_id))
+        ))
+            .get_result(connection)
+            .with_context(|| format!("cannot get follower '{}' of category '{}'", user_id, category.category))?;
+        Ok(followed)
+    }
+
     fn get_amiami_category_by_name(
         &self,
         connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
         category_name: &str
     ) -> Result<Option<CategoryRow>, anyhow::Error> {
         let category = category_dsl::amiami_category
+            .select(CategoryRow::as_select())
             .filter(category_dsl::category.eq(category_name))
             .first(connection)
             .optional()
@@ -127,18 +513,176 @@ impl Sqlite {
         }
     }
 
+    fn insert_amiami_category_follower_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        category: &CategoryRow,
+        user_id: i32,
+    ) -> Result<(), anyhow::Error> {
+        diesel::insert_into(category_follower_dsl::amiami_category_follower)
+            .values(CategoryFollowerRowInsert { category_id: category.id, user_id })
+            .execute(connection)
+            .with_context(|| format!("cannot follow category '{}' for user '{}'", category.category, user_id))?;
+        Ok(())
+    }
+
+    fn delete_amiami_category_follower_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        category: &CategoryRow,
+        user_id: i32,
+    ) -> Result<(), anyhow::Error> {
+        diesel::delete(category_follower_dsl::amiami_category_follower)
+            .filter(category_follower_dsl::category_id.eq(category.id))
+            .filter(category_follower_dsl::user_id.eq(user_id))
+            .execute(connection)
+            .with_context(|| format!("cannot unfollow category '{}' for user '{}'", category.category, user_id))?;
+        Ok(())
+    }
+
+    fn update_amiami_category_follower_row_schedule(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        category: &CategoryRow,
+        user_id: i32,
+        schedule: Option<&str>,
+    ) -> Result<(), anyhow::Error> {
+        diesel::update(category_follower_dsl::amiami_category_follower)
+            .filter(category_follower_dsl::category_id.eq(category.id))
+            .filter(category_follower_dsl::user_id.eq(user_id))
+            .set(category_follower_dsl::schedule.eq(schedule))
+            .execute(connection)
+            .with_context(|| format!("cannot update schedule of category '{}' for user '{}'", category.category, user_id))?;
+        Ok(())
+    }
+
+    /// Keeps `schedule` of the category at the one chosen by most of its followers, having none counts as a choice
+    /// and ties go to the earliest follower. A category nobody follows has none.
+    fn update_amiami_category_row_schedule(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        category: &CategoryRow,
+    ) -> Result<(), anyhow::Error> {
+        diesel::sql_query(
+            "UPDATE amiami_category SET schedule = ( \
+                SELECT f.schedule FROM amiami_category_follower f WHERE f.category_id = amiami_category.id \
+                GROUP BY f.schedule ORDER BY COUNT(*) DESC, MIN(f.date_followed) LIMIT 1 \
+            ) WHERE id = ?"
+        )
+            .bind::<Integer, _>(category.id)
+            .execute(connection)
+            .with_context(|| format!("cannot update schedule of category '{}'", category.category))?;
+        Ok(())
+    }
+
+    fn update_amiami_category_row_date_scraped(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        category_id: i32,
+        date_scraped: NaiveDateTime,
+    ) -> Result<(), anyhow::Error> {
+        diesel::update(category_dsl::amiami_category)
+            .filter(category_dsl::id.eq(category_id))
+            .set((
+                category_dsl::date_scraped.eq(date_scraped),
+                category_dsl::date_first_scraped.eq(sql::<Nullable<Timestamp>>("COALESCE(date_first_scraped, ").bind::<Timestamp, _>(date_scraped).sql(")")),
+            ))
+            .execute(connection)
+            .with_context(|| format!("cannot update date scraped of category with id {}", category_id))?;
+        Ok(())
+    }
+
+    /// Keeps `following` of the category in sync with its followers, it means followed by anybody.
+    fn update_amiami_category_row_following(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        category: &CategoryRow,
+    ) -> Result<(), anyhow::Error> {
+        diesel::update(category_dsl::amiami_category)
+            .filter(category_dsl::id.eq(category.id))
+            .set(category_dsl::following.eq(diesel::dsl::exists(
+                category_follower_dsl::amiami_category_follower.filter(category_follower_dsl::category_id.eq(category.id))
+            )))
+            .execute(connection)
+            .with_context(|| format!("cannot update following of category '{}'", category.category))?;
+        Ok(())
+    }
+
     fn get_amiami_product_category(
         &self,
         connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
         product: &ProductRow,
     ) -> Result<CategoryRow, anyhow::Error> {
         let category = category_dsl::amiami_category
+            .select(CategoryRow::as_select())
             .find(product.category_id)
             .first(connection)
             .with_context(|| format!("cannot find category with id '{}'", product.category_id))?;
         Ok(category)
     }
 
+    fn update_amiami_product_search_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product: &Product,
+    ) -> Result<(), anyhow::Error> {
+        diesel::sql_query("DELETE FROM amiami_product_search WHERE rowid = ?")
+            .bind::<Integer, _>(product.id())
+            .execute(connection)
+            .with_context(|| format!("cannot delete search entry for product '{}'", product.url()))?;
+        diesel::sql_query("INSERT INTO amiami_product_search (rowid, title, maker) VALUES (?, ?, ?)")
+            .bind::<Integer, _>(product.id())
+            .bind::<Text, _>(product.title())
+            .bind::<Text, _>(product.maker())
+            .execute(connection)
+            .with_context(|| format!("cannot insert search entry for product '{}'", product.url()))?;
+        Ok(())
+    }
+
+    fn search_amiami_product_rows(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        expression: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<ProductSearchRow>, anyhow::Error> {
+        let rows = diesel::sql_query(
+            "SELECT rowid AS id, \
+                bm25(amiami_product_search, 10.0, 5.0) AS rank, \
+                highlight(amiami_product_search, 0, char(2), char(3)) AS title, \
+                highlight(amiami_product_search, 1, char(2), char(3)) AS maker \
+            FROM amiami_product_search \
+            WHERE amiami_product_search MATCH ? \
+            ORDER BY rank, rowid \
+            LIMIT ? OFFSET ?"
+        )
+            .bind::<Text, _>(expression)
+            .bind::<BigInt, _>(limit)
+            .bind::<BigInt, _>(offset)
+            .load(connection)
+            .with_context(|| format!("cannot search products with '{}'", expression))?;
+        Ok(rows)
+    }
+
+    fn load_amiami_search_results(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        search_rows: Vec<ProductSearchRow>,
+    ) -> Result<Vec<SearchResult<Product>>, anyhow::Error> {
+        let mut results = Vec::new();
+        for search_row in search_rows {
+            let product_row = self.get_amiami_product_row_by_id(connection, search_row.id)?
+                .with_context(|| format!("cannot find product with id '{}'", search_row.id))?;
+            let product = self.load_amiami_product(connection, &product_row)?;
+            let highlights = vec![
+                FieldHighlight::new("title".to_owned(), parse_highlight(&search_row.title)),
+                FieldHighlight::new("maker".to_owned(), parse_highlight(&search_row.maker)),
+            ];
+            results.push(SearchResult::new(product, search_row.rank, highlights));
+        }
+        Ok(results)
+    }
+
     fn load_amiami_product(
         &self,
         connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
@@ -157,7 +701,9 @@ impl Sqlite {
             product.min_price.to_owned(),
             product.release_date,
             product.availability.to_owned(),
-        );
+        ).with_date_restocked(product.date_restocked.map(|d| d.and_utc()))
+            .with_image_hash(product.image_hash.to_owned())
+            .with_image_phash(product.image_phash.map(|h| h as u64));
         Ok(product)
     }
 }
@@ -166,67 +712,648 @@ impl Sqlite {
 impl AmiamiRepository for Sqlite {
 
     async fn create_amiami_product(&self, args: &CreateProductArgs) -> Result<Product, CreateProductError> {
-        let mut connection = self.get_connection()?;
-        let product_row = self.get_amiami_product_row_by_url(&mut connection, args.url())?;
-        match product_row {
-            Some(product_row) => {
-                Err(CreateProductError::DuplicateProduct { url: product_row.url, title: product_row.title })
-            },
-            None => {
-                let product = connection.transaction(|connection| -> Result<Product, anyhow::Error> {
-                    let category_row = self.insert_amiami_category_row(connection, args.category())?;
-                    let product_row = self.insert_amiami_product_row(connection, &args, &category_row)?;
-                    let product = Product::new(
-                        product_row.id,
-                        product_row.date_added.and_utc(),
-                        product_row.url,
-                        product_row.title,
-                        product_row.image_url,
-                        category_row.category,
-                        product_row.maker,
-                        product_row.full_price,
-                        product_row.min_price,
-                        product_row.release_date,
-                        product_row.availability
-                    );
+        let args = args.clone();
+        self.write(move |db, connection| {
+            let product_row = db.get_amiami_product_row_by_url(connection, args.url())?;
+            match product_row {
+                Some(product_row) => {
+                    Err(CreateProductError::DuplicateProduct { url: product_row.url, title: product_row.title })
+                },
+                None => {
+                    let product = connection.transaction(|connection| -> Result<Product, anyhow::Error> {
+                        let category_row = db.insert_amiami_category_row(connection, args.category())?;
+                        let product_row = db.insert_amiami_product_row(connection, &args, &category_row)?;
+                        db.insert_amiami_availability_event_row(connection, product_row.id, None, args.availability())?;
+                        db.insert_amiami_price_event_row(connection, product_row.id, args.full_price(), args.min_price())?;
+                        let product = Product::new(
+                            product_row.id,
+                            product_row.date_added.and_utc(),
+                            product_row.url,
+                            product_row.title,
+                            product_row.image_url,
+                            category_row.category,
+                            product_row.maker,
+                            product_row.full_price,
+                            product_row.min_price,
+                            product_row.release_date,
+                            product_row.availability
+                        );
+                        db.update_amiami_product_search_row(connection, &product)?;
+                        Ok(product)
+                    })?;
                     Ok(product)
-                })?;
-                Ok(product)
+                }
             }
-        }
+        }).await
     }
 
     async fn update_amiami_product(&self, args: &UpdateProductArgs) -> Result<Product, UpdateProductError> {
-        let mut connection = self.get_connection()?;
-        let product_row = self.get_amiami_product_row_by_url(&mut connection, args.url())?;
-        match product_row {
-            Some(product_row) => {
-                let product_row = self.update_amiami_product_row(&mut connection, &product_row, &args)?;
-                let product = self.load_amiami_product(&mut connection, &product_row)?;
-                Ok(product)
-            },
-            None => Err(UpdateProductError::ProductMissing { url: args.url().to_owned() }),
-        }
+        let args = args.clone();
+        self.write(move |db, connection| {
+            let product_row = db.get_amiami_product_row_by_url(connection, args.url())?;
+            match product_row {
+                Some(product_row) => {
+                    let product = connection.transaction(|connection| -> Result<Product, anyhow::Error> {
+                        let product_row = db.update_amiami_product_row(connection, &product_row, &args)?;
+                        let product = db.load_amiami_product(connection, &product_row)?;
+                        db.update_amiami_product_search_row(connection, &product)?;
+                        Ok(product)
+                    })?;
+                    Ok(product)
+                },
+                None => Err(UpdateProductError::ProductMissing { url: args.url().to_owned() }),
+            }
+        }).await
+    }
+
+    async fn get_amiami_product(&self, product_id: i32) -> Result<Product, GetProductError> {
+        self.read(move |db, connection| {
+            let product_row = db.get_amiami_product_row_by_id(connection, product_id)?
+                .ok_or(GetProductError::ProductMissing { id: product_id })?;
+            let product = db.load_amiami_product(connection, &product_row)?;
+            Ok(product)
+        }).await
+    }
+
+    async fn get_amiami_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError> {
+        self.read(move |db, connection| {
+            if db.get_amiami_product_row_by_id(connection, product_id)?.is_none() {
+                return Err(GetProductError::ProductMissing { id: product_id });
+            }
+            let history = db.get_amiami_product_history_entries(connection, product_id)?;
+            Ok(history)
+        }).await
+    }
+
+    async fn get_amiami_availability_events(&self) -> Result<Vec<AvailabilityEvent>, GetAvailabilityStatsError> {
+        self.read(move |db, connection| {
+            let events = db.get_amiami_availability_event_rows(connection)?
+                .into_iter()
+                .map(|e| e.into_stats_event())
+                .collect::<Result<Vec<_>, _>>()
+                .with_context(|| "cannot parse availability events")?;
+            Ok(events)
+        }).await
     }
 
     async fn get_amiami_products(&self) -> Result<Vec<Product>, GetProductsError> {
-        let mut connection = self.get_connection()?;
-        let product_rows = self.get_amiami_product_rows(&mut connection)?;
-        let mut products = Vec::new();
-        for product_row in product_rows {
-            let product = self.load_amiami_product(&mut connection, &product_row)?;
-            products.push(product);
-        }
-        Ok(products)
+        self.read(move |db, connection| {
+            let product_rows = db.get_amiami_product_rows(connection)?;
+            let mut products = Vec::new();
+            for product_row in product_rows {
+                let product = db.load_amiami_product(connection, &product_row)?;
+                products.push(product);
+            }
+            Ok(products)
+        }).await
     }
 
-    async fn get_following_amiami_categories(&self) -> Result<Vec<String>, GetCategoriesError> {
-        let mut connection = self.get_connection()?;
-        let category_rows = self.get_following_amiami_category_rows(&mut connection)?;
-        let categories = category_rows.into_iter()
-            .map(|c| c.category)
-            .collect();
-        Ok(categories)
+    async fn get_amiami_products_by_release(&self, filter: &ReleaseFilter) -> Result<Vec<Product>, GetProductsError> {
+        let filter = filter.clone();
+        self.read(move |db, connection| {
+            let product_rows = db.get_amiami_product_rows_by_release(connection, &filter)?;
+            let mut products = Vec::new();
+            for product_row in product_rows {
+                let product = db.load_amiami_product(connection, &product_row)?;
+                products.push(product);
+            }
+            Ok(products)
+        }).await
+    }
+
+    async fn get_amiami_products_page_by_release(&self, filter: &ReleaseFilter, page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        let filter = filter.clone();
+        self.read(move |db, connection| {
+            let (product_rows, total) = db.get_amiami_product_rows_page_by_release(connection, &filter, page)?;
+            let mut products = Vec::new();
+            for product_row in product_rows {
+                let product = db.load_amiami_product(connection, &product_row)?;
+                products.push(product);
+            }
+            Ok(Page::new(products, page, total))
+        }).await
+    }
+
+    async fn get_amiami_products_page(&self, query: &ProductQuery) -> Result<Page<Product>, GetProductsError> {
+        let query = query.clone();
+        self.read(move |db, connection| {
+            let (product_rows, total) = db.get_amiami_product_rows_page(connection, &query)?;
+            let mut products = Vec::new();
+            for product_row in product_rows {
+                let product = db.load_amiami_product(connection, &product_row)?;
+                products.push(product);
+            }
+            Ok(Page::new(products, query.page(), total))
+        }).await
+    }
+
+    async fn search_amiami_products(&self, query: &str) -> Result<Vec<SearchResult<Product>>, SearchProductsError> {
+        let expression = match_expression(query)?;
+        self.read(move |db, connection| {
+            let search_rows = db.search_amiami_product_rows(connection, &expression, MAX_SEARCH_RESULTS, 0)?;
+            let results = db.load_amiami_search_results(connection, search_rows)?;
+            Ok(results)
+        }).await
+    }
+
+    async fn search_amiami_products_page(&self, query: &str, page: PageRequest) -> Result<Page<SearchResult<Product>>, SearchProductsError> {
+        let expression = match_expression(query)?;
+        self.read(move |db, connection| {
+            let total = count_matches(connection, "amiami_product_search", &expression)?;
+            let search_rows = db.search_amiami_product_rows(connection, &expression, page.page_size() as i64, page.offset())?;
+            let results = db.load_amiami_search_results(connection, search_rows)?;
+            Ok(Page::new(results, page, total))
+        }).await
+    }
+
+    async fn get_amiami_categories(&self) -> Result<Vec<String>, GetCategoriesError> {
+        self.read(move |db, connection| {
+            let category_rows = db.get_amiami_category_rows(connection)?;
+            let categories = category_rows.into_iter()
+                .map(|c| c.category)
+                .collect();
+            Ok(categories)
+        }).await
+    }
+
+    async fn get_amiami_categories_page(&self, page: PageRequest) -> Result<Page<String>, GetCategoriesError> {
+        self.read(move |db, connection| {
+            let (categories, total) = db.get_amiami_category_names_page(connection, page)?;
+            Ok(Page::new(categories, page, total))
+        }).await
+    }
+
+    async fn get_following_amiami_categories(&self, user_id: i32) -> Result<Vec<String>, GetCategoriesError> {
+        self.read(move |db, connection| {
+            let category_rows = db.get_following_amiami_category_rows(connection, user_id)?;
+            let categories = category_rows.into_iter()
+                .map(|c| c.category)
+                .collect();
+            Ok(categories)
+        }).await
+    }
+
+    async fn get_following_amiami_categories_page(&self, user_id: i32, page: PageRequest) -> Result<Page<String>, GetCategoriesError> {
+        self.read(move |db, connection| {
+            let (categories, total) = db.get_following_amiami_category_names_page(connection, user_id, page)?;
+            Ok(Page::new(categories, page, total))
+        }).await
+    }
+
+    async fn get_followed_amiami_categories(&self) -> Result<Vec<FollowedCategory>, GetCategoriesError> {
+        self.read(move |db, connection| {
+            let follower_rows = db.get_amiami_category_follower_rows(connection)?;
+            let user_ids = follower_rows.iter().map(|f| f.user_id).unique().collect::<Vec<_>>();
+            let users = db.get_user_rows_by_ids(connection, &user_ids)?
+                .into_iter()
+                .map(|u| (u.id, u.into_domain()))
+                .collect::<HashMap<_, _>>();
+            let mut followers = follower_rows.into_iter().into_group_map_by(|f| f.category_id);
+            let categories = db.get_amiami_category_rows(connection)?
+                .into_iter()
+                .filter_map(|category| {
+                    let mut category_followers = followers.remove(&category.id)?
+                        .into_iter()
+                        .filter_map(|f| users.get(&f.user_id).cloned())
+                        .collect::<Vec<_>>();
+                    category_followers.sort_by(|a, b| a.username().cmp(b.username()));
+                    let schedule = category.schedule();
+                    Some(FollowedCategory::new(category.id, category.category, schedule, category_followers).with_date_scraped(category.date_scraped.map(|d| d.and_utc())))
+                })
+                .collect();
+            Ok(categories)
+        }).await
+    }
+
+    async fn follow_amiami_category(&self, user_id: i32, category: &str) -> Result<(), FollowCategoryError> {
+        let category = category.to_owned();
+        self.write(move |db, connection| {
+            let category_row = db.insert_amiami_category_row(connection, &category)?;
+            if db.is_amiami_category_followed_by(connection, &category_row, user_id)? {
+                return Err(FollowCategoryError::AlreadyFollowed { category });
+            }
+            connection.transaction(|connection| -> Result<(), anyhow::Error> {
+                db.insert_amiami_category_follower_row(connection, &category_row, user_id)?;
+                db.update_amiami_category_row_following(connection, &category_row)?;
+                db.update_amiami_category_row_schedule(connection, &category_row)
+            })?;
+            Ok(())
+        }).await
+    }
+
+    async fn unfollow_amiami_category(&self, user_id: i32, category: &str) -> Result<(), UnfollowCategoryError> {
+        let category = category.to_owned();
+        self.write(move |db, connection| {
+            match db.get_amiami_category_by_name(connection, &category)? {
+                Some(category_row) if db.is_amiami_category_followed_by(connection, &category_row, user_id)? => {
+                    connection.transaction(|connection| -> Result<(), anyhow::Error> {
+                        db.delete_amiami_category_follower_row(connection, &category_row, user_id)?;
+                        db.update_amiami_category_row_following(connection, &category_row)?;
+                        db.update_amiami_category_row_schedule(connection, &category_row)
+                    })?;
+                    Ok(())
+                },
+                Some(_) => Err(UnfollowCategoryError::CategoryNotFollowed { category }),
+                None => Err(UnfollowCategoryError::UnknownCategory { category }),
+            }
+        }).await
+    }
+
+    async fn set_amiami_category_schedule(&self, user_id: i32, category: &str, schedule: Option<&Schedule>) -> Result<(), SetCategoryScheduleError> {
+        let category = category.to_owned();
+        let schedule = schedule.map(|s| s.expression().to_owned());
+        self.write(move |db, connection| {
+            match db.get_amiami_category_by_name(connection, &category)? {
+                Some(category_row) if db.is_amiami_category_followed_by(connection, &category_row, user_id)? => {
+                    connection.transaction(|connection| -> Result<(), anyhow::Error> {
+                        db.update_amiami_category_follower_row_schedule(connection, &category_row, user_id, schedule.as_deref())?;
+                        db.update_amiami_category_row_schedule(connection, &category_row)
+                    })?;
+                    Ok(())
+                },
+                Some(_) => Err(SetCategoryScheduleError::CategoryNotFollowed { category }),
+                None => Err(SetCategoryScheduleError::UnknownCategory { category }),
+            }
+        }).await
+    }
+
+    async fn set_amiami_category_scraped(&self, category_id: i32, date_scraped: DateTime<Utc>) -> Result<(), SetDateScrapedError> {
+        self.write(move |db, connection| {
+            db.update_amiami_category_row_date_scraped(connection, category_id, date_scraped.naive_utc())?;
+            Ok(())
+        }).await
+    }
+
+    async fn get_amiami_category_activity(&self, since: DateTime<Utc>) -> Result<HashMap<i32, usize>, GetTargetSchedulesError> {
+        self.read(move |db, connection| {
+            let mut activity = db.get_amiami_category_added_product_rows(connection, since.naive_utc())?.into_iter().counts();
+            for (category_id, event) in db.get_amiami_category_availability_event_rows(connection, since.naive_utc())? {
+                let event = event.into_stats_event().with_context(|| "cannot parse availability events")?;
+                if event.is_restock() {
+                    *activity.entry(category_id).or_default() += 1;
+                }
+            }
+            Ok(activity)
+        }).await
+    }
+
+    async fn get_amiami_makers(&self) -> Result<Vec<String>, GetMakersError> {
+        self.read(move |db, connection| {
+            let makers = db.get_amiami_maker_names(connection)?;
+            Ok(makers)
+        }).await
+    }
+
+    async fn get_amiami_makers_page(&self, page: PageRequest) -> Result<Page<String>, GetMakersError> {
+        self.read(move |db, connection| {
+            let (makers, total) = db.get_amiami_maker_names_page(connection, page)?;
+            Ok(Page::new(makers, page, total))
+        }).await
     }
 }
 
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::domain::amiami::SITE;
+    use crate::domain::availability::Availability;
+    use crate::domain::amiami::models::product::Price;
+    use crate::domain::pagination::PageRequest;
+    use crate::domain::product_history::{NotificationKind, ProductChange};
+    use crate::domain::site::SiteRepository;
+    use crate::domain::user::models::user::DEFAULT_USERNAME;
+    use crate::domain::user::ports::UserRepository;
+    use crate::outbound::sqlite::test_util::default_user_id;
+    use chrono::{NaiveDate, TimeDelta};
+
+    #[tokio::test]
+    async fn test_get_amiami_products_by_release() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let product1 = db.create_amiami_product(&product_args()).await.unwrap();
+        let product2 = db.create_amiami_product(&product_args2()).await.unwrap();
+
+        let products = db.get_amiami_products_by_release(&ReleaseFilter::default()).await.unwrap();
+        assert_eq!(products.iter().map(|p| p.id()).collect::<Vec<_>>(), vec![product2.id(), product1.id()]);
+
+        let filter = ReleaseFilter::new(Some(NaiveDate::from_ymd_opt(2026, 1, 1).unwrap()), None, None, None);
+        let products = db.get_amiami_products_by_release(&filter).await.unwrap();
+        assert_eq!(products.len(), 1);
+        assert_eq!(products.first().unwrap().id(), product1.id());
+
+        let filter = ReleaseFilter::new(None, None, Some("459".to_owned()), Some("Alter".to_owned()));
+        let products = db.get_amiami_products_by_release(&filter).await.unwrap();
+        assert_eq!(products.len(), 1);
+        assert_eq!(products.first().unwrap().id(), product2.id());
+    }
+
+    #[tokio::test]
+    async fn test_get_amiami_products_page_by_release() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let product1 = db.create_amiami_product(&product_args()).await.unwrap();
+        let product2 = db.create_amiami_product(&product_args2()).await.unwrap();
+
+        let page = db.get_amiami_products_page_by_release(&ReleaseFilter::default(), PageRequest::new(1, 1)).await.unwrap();
+        assert_eq!(page.total_items(), 2);
+        assert_eq!(page.items().iter().map(|p| p.id()).collect::<Vec<_>>(), vec![product2.id()]);
+        let page = db.get_amiami_products_page_by_release(&ReleaseFilter::default(), PageRequest::new(2, 1)).await.unwrap();
+        assert_eq!(page.items().iter().map(|p| p.id()).collect::<Vec<_>>(), vec![product1.id()]);
+
+        let filter = ReleaseFilter::new(None, None, Some("9708".to_owned()), None);
+        let page = db.get_amiami_products_page_by_release(&filter, PageRequest::new(1, 10)).await.unwrap();
+        assert_eq!(page.total_items(), 1);
+        assert_eq!(page.items().iter().map(|p| p.id()).collect::<Vec<_>>(), vec![product1.id()]);
+    }
+
+    #[tokio::test]
+    async fn test_get_amiami_categories_and_makers() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        db.create_amiami_product(&product_args()).await.unwrap();
+        db.create_amiami_product(&product_args2()).await.unwrap();
+
+        let categories = db.get_amiami_categories().await.unwrap();
+        assert_eq!(categories, vec!["459".to_owned(), "9708".to_owned()]);
+        let makers = db.get_amiami_makers().await.unwrap();
+        assert_eq!(makers, vec!["Alter".to_owned(), "Good Smile Company".to_owned()]);
+
+        let page = db.get_amiami_makers_page(PageRequest::new(2, 1)).await.unwrap();
+        assert_eq!(page.total_items(), 2);
+        assert_eq!(page.items(), &["Good Smile Company".to_owned()]);
+    }
+
+    #[tokio::test]
+    async fn test_update_amiami_product_sets_date_restocked() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let product1 = db.create_amiami_product(&product_args()).await.unwrap();
+        let product2 = db.create_amiami_product(&product_args2()).await.unwrap();
+        let update_args = |availability| UpdateProductArgs::new(product1.url().to_owned(), 20000, 18000, product1.release_date(), availability);
+
+        let updated = db.update_amiami_product(&update_args(Availability::Available)).await.unwrap();
+        assert_eq!(updated.date_restocked(), None);
+        db.update_amiami_product(&update_args(Availability::NotAvailable)).await.unwrap();
+        let updated = db.update_amiami_product(&update_args(Availability::Preorder)).await.unwrap();
+        assert!(updated.date_restocked().is_some());
+
+        let query = ProductQuery::new(PageRequest::new(1, 10), ProductSort::DateChanged, SortDirection::Desc);
+        let page = db.get_amiami_products_page(&query).await.unwrap();
+        assert_eq!(page.items().iter().map(|p| p.id()).collect::<Vec<_>>(), vec![product1.id(), product2.id()]);
+    }
+
+    #[tokio::test]
+    async fn test_get_amiami_product_history() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        let product = db.create_amiami_product(&product_args()).await.unwrap();
+        let update_args = |min_price, availability| UpdateProductArgs::new(product.url().to_owned(), 20000, min_price, product.release_date(), availability);
+        db.update_amiami_product(&update_args(18000, Availability::Preorder)).await.unwrap();
+        db.update_amiami_product(&update_args(16000, Availability::NotAvailable)).await.unwrap();
+        db.add_notifications(SITE, user_id, NotificationKind::RestockedProduct, &[product.id()]).await.unwrap();
+
+        let loaded = db.get_amiami_product(product.id()).await.unwrap();
+        assert_eq!(loaded.min_price(), 16000);
+        let history = db.get_amiami_product_history(product.id()).await.unwrap();
+        let availabilities = history.iter()
+            .filter_map(|e| match e.change() { ProductChange::Availability(a) => Some(a.clone()), _ => None })
+            .collect::<Vec<_>>();
+        assert_eq!(availabilities, vec![Availability::Preorder, Availability::NotAvailable]);
+        let prices = history.iter()
+            .filter_map(|e| match e.change() { ProductChange::Price(p) => Some(*p), _ => None })
+            .collect::<Vec<_>>();
+        assert_eq!(prices, vec![Price::new(20000, 18000), Price::new(20000, 16000)]);
+        let notifications = history.iter()
+            .filter(|e| matches!(e.change(), ProductChange::Notification { .. }))
+            .map(|e| e.change().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(notifications, vec![ProductChange::Notification { kind: NotificationKind::RestockedProduct, username: DEFAULT_USERNAME.to_owned() }]);
+
+        assert!(matches!(db.get_amiami_product_history(product.id() + 1).await, Err(GetProductError::ProductMissing { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_get_amiami_availability_events() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let product = db.create_amiami_product(&product_args()).await.unwrap();
+        let update_args = |availability| UpdateProductArgs::new(product.url().to_owned(), 20000, 18000, product.release_date(), availability);
+        db.update_amiami_product(&update_args(Availability::NotAvailable)).await.unwrap();
+        db.update_amiami_product(&update_args(Availability::Available)).await.unwrap();
+
+        let events = db.get_amiami_availability_events().await.unwrap();
+        assert_eq!(events.len(), 3);
+        assert!(events.iter().all(|e| e.product_id() == product.id()));
+        let changes = events.iter().map(|e| (e.is_sellout(), e.is_restock())).collect::<Vec<_>>();
+        assert_eq!(changes, vec![(false, false), (true, false), (false, true)]);
+    }
+
+    #[tokio::test]
+    async fn test_search_amiami_products() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        db.create_amiami_product(&product_args()).await.unwrap();
+        let product2 = db.create_amiami_product(&product_args2()).await.unwrap();
+        db.update_amiami_product(&UpdateProductArgs::new(product2.url().to_owned(), 24000, 23000, product2.release_date(), Availability::NotAvailable)).await.unwrap();
+
+        let results = db.search_amiami_products("alter").await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results.first().unwrap().product().id(), product2.id());
+        assert_eq!(results.first().unwrap().product().min_price(), 23000);
+        assert!(results.first().unwrap().highlight("maker").unwrap().has_match());
+        assert!(!results.first().unwrap().highlight("title").unwrap().has_match());
+
+        let results = db.search_amiami_products("figure_title").await.unwrap();
+        assert_eq!(results.len(), 2);
+
+        let page = db.search_amiami_products_page("figure_title", PageRequest::new(2, 1)).await.unwrap();
+        assert_eq!(page.total_items(), 2);
+        assert_eq!(page.items().iter().map(|r| r.product().id()).collect::<Vec<_>>(), vec![results[1].product().id()]);
+    }
+
+    #[tokio::test]
+    async fn test_get_amiami_products_page() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let product1 = db.create_amiami_product(&product_args()).await.unwrap();
+        let product2 = db.create_amiami_product(&product_args2()).await.unwrap();
+
+        let query = ProductQuery::new(PageRequest::new(1, 1), ProductSort::ReleaseDate, SortDirection::Asc);
+        let page = db.get_amiami_products_page(&query).await.unwrap();
+        assert_eq!(page.total_items(), 2);
+        assert_eq!(page.total_pages(), 2);
+        assert_eq!(page.items().iter().map(|p| p.id()).collect::<Vec<_>>(), vec![product2.id()]);
+
+        let query = ProductQuery::new(PageRequest::new(1, 10), ProductSort::Price, SortDirection::Asc);
+        let page = db.get_amiami_products_page(&query).await.unwrap();
+        assert_eq!(page.items().iter().map(|p| p.id()).collect::<Vec<_>>(), vec![product1.id(), product2.id()]);
+
+        let query = ProductQuery::default().with_category(Some("459".to_owned()));
+        let page = db.get_amiami_products_page(&query).await.unwrap();
+        assert_eq!(page.items().iter().map(|p| p.id()).collect::<Vec<_>>(), vec![product2.id()]);
+
+        let query = ProductQuery::default().with_availability(Some(Availability::Preorder));
+        let page = db.get_amiami_products_page(&query).await.unwrap();
+        assert_eq!(page.items().iter().map(|p| p.id()).collect::<Vec<_>>(), vec![product1.id()]);
+    }
+
+    #[tokio::test]
+    async fn test_follow_amiami_category() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        db.create_amiami_product(&product_args()).await.unwrap();
+
+        db.follow_amiami_category(user_id, "9708").await.unwrap();
+        db.follow_amiami_category(user_id, "459").await.unwrap();
+        assert!(matches!(db.follow_amiami_category(user_id, "459").await, Err(FollowCategoryError::AlreadyFollowed { .. })));
+        let mut categories = db.get_following_amiami_categories(user_id).await.unwrap();
+        categories.sort();
+        assert_eq!(categories, vec!["459".to_owned(), "9708".to_owned()]);
+
+        db.unfollow_amiami_category(user_id, "9708").await.unwrap();
+        assert!(matches!(db.unfollow_amiami_category(user_id, "9708").await, Err(UnfollowCategoryError::CategoryNotFollowed { .. })));
+        assert!(matches!(db.unfollow_amiami_category(user_id, "1234").await, Err(UnfollowCategoryError::UnknownCategory { .. })));
+        assert_eq!(db.get_following_amiami_categories(user_id).await.unwrap(), vec!["459".to_owned()]);
+    }
+
+    #[tokio::test]
+    async fn test_set_amiami_category_schedule() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        db.create_amiami_product(&product_args()).await.unwrap();
+        db.create_amiami_product(&product_args2()).await.unwrap();
+        db.follow_amiami_category(user_id, "9708").await.unwrap();
+
+        let schedule = Schedule::parse("0 15 * * * *").unwrap();
+        db.set_amiami_category_schedule(user_id, "9708", Some(&schedule)).await.unwrap();
+        let followed = db.get_followed_amiami_categories().await.unwrap();
+        assert_eq!(followed.iter().map(|f| (f.category(), f.schedule())).collect::<Vec<_>>(), vec![("9708", Some(&schedule))]);
+
+        assert!(matches!(db.set_amiami_category_schedule(user_id, "459", Some(&schedule)).await, Err(SetCategoryScheduleError::CategoryNotFollowed { .. })));
+        assert!(matches!(db.set_amiami_category_schedule(user_id, "1234", None).await, Err(SetCategoryScheduleError::UnknownCategory { .. })));
+
+        db.set_amiami_category_schedule(user_id, "9708", None).await.unwrap();
+        assert_eq!(db.get_followed_amiami_categories().await.unwrap().first().unwrap().schedule(), None);
+    }
+
+    #[tokio::test]
+    async fn test_amiami_category_schedule_of_most_followers() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let users = db.setup_users(DEFAULT_USERNAME, &["alice".to_owned(), "bob".to_owned()]).await.unwrap();
+        for user in users.iter() {
+            db.follow_amiami_category(user.id(), "9708").await.unwrap();
+        }
+        let (alice, bob) = (&users[0], &users[1]);
+        let followed_schedule = async || db.get_followed_amiami_categories().await.unwrap().first().unwrap().schedule().cloned();
+
+        let schedule = Schedule::parse("0 15 * * * *").unwrap();
+        db.set_amiami_category_schedule(alice.id(), "9708", Some(&schedule)).await.unwrap();
+        assert_eq!(followed_schedule().await, None);
+        db.set_amiami_category_schedule(bob.id(), "9708", Some(&schedule)).await.unwrap();
+        assert_eq!(followed_schedule().await, Some(schedule));
+
+        db.unfollow_amiami_category(alice.id(), "9708").await.unwrap();
+        db.follow_amiami_category(alice.id(), "9708").await.unwrap();
+        assert_eq!(followed_schedule().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_set_amiami_category_scraped() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        db.create_amiami_product(&product_args()).await.unwrap();
+        db.follow_amiami_category(user_id, "9708").await.unwrap();
+        let category = db.get_followed_amiami_categories().await.unwrap().into_iter().next().unwrap();
+        assert_eq!(category.date_scraped(), None);
+
+        let date_scraped = DateTime::parse_from_rfc3339("2026-10-19T12:00:00Z").unwrap().to_utc();
+        db.set_amiami_category_scraped(category.id(), date_scraped).await.unwrap();
+        let category = db.get_followed_amiami_categories().await.unwrap().into_iter().next().unwrap();
+        assert_eq!(category.date_scraped(), Some(date_scraped));
+    }
+
+    #[tokio::test]
+    async fn test_get_amiami_category_activity() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        db.create_amiami_product(&product_args()).await.unwrap();
+        let product2 = db.create_amiami_product(&product_args2()).await.unwrap();
+        let update_args = |availability| UpdateProductArgs::new(product2.url().to_owned(), 25000, 25000, product2.release_date(), availability);
+        db.update_amiami_product(&update_args(Availability::NotAvailable)).await.unwrap();
+        db.update_amiami_product(&update_args(Availability::Available)).await.unwrap();
+        db.follow_amiami_category(user_id, "9708").await.unwrap();
+        db.follow_amiami_category(user_id, "459").await.unwrap();
+        let categories = db.get_followed_amiami_categories().await.unwrap();
+        let category_id = |name: &str| categories.iter().find(|c| c.category() == name).unwrap().id();
+
+        // the products of a category not scraped yet are its backfill
+        db.set_amiami_category_scraped(category_id("459"), Utc::now() - TimeDelta::hours(1)).await.unwrap();
+        db.set_amiami_category_scraped(category_id("459"), Utc::now() + TimeDelta::hours(1)).await.unwrap();
+
+        let activity = db.get_amiami_category_activity(Utc::now() - TimeDelta::hours(1)).await.unwrap();
+        assert_eq!(activity.get(&category_id("9708")), None);
+        assert_eq!(activity.get(&category_id("459")), Some(&2));
+
+        assert!(db.get_amiami_category_activity(Utc::now() + TimeDelta::hours(1)).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_follow_amiami_category_per_user() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        db.create_amiami_product(&product_args()).await.unwrap();
+        db.create_amiami_product(&product_args2()).await.unwrap();
+        let users = db.setup_users(DEFAULT_USERNAME, &["alice".to_owned()]).await.unwrap();
+        let (alice, default) = (users.first().unwrap(), users.last().unwrap());
+        db.follow_amiami_category(alice.id(), "9708").await.unwrap();
+        db.follow_amiami_category(default.id(), "9708").await.unwrap();
+        db.follow_amiami_category(default.id(), "459").await.unwrap();
+        db.unfollow_amiami_category(default.id(), "9708").await.unwrap();
+
+        assert_eq!(db.get_following_amiami_categories(alice.id()).await.unwrap(), vec!["9708".to_owned()]);
+        assert_eq!(db.get_following_amiami_categories(default.id()).await.unwrap(), vec!["459".to_owned()]);
+
+        let mut followed = db.get_followed_amiami_categories().await.unwrap();
+        followed.sort_by(|a, b| a.category().cmp(b.category()));
+        assert_eq!(followed.iter().map(|f| f.category()).collect::<Vec<_>>(), vec!["459", "9708"]);
+        assert_eq!(followed[1].followers().iter().map(|u| u.username()).collect::<Vec<_>>(), vec!["alice"]);
+
+        let query = ProductQuery::default().with_followed_by(Some(alice.id()));
+        let page = db.get_amiami_products_page(&query).await.unwrap();
+        assert_eq!(page.items().iter().map(|p| p.category()).collect::<Vec<_>>(), vec!["9708"]);
+    }
+
+    fn product_args() -> CreateProductArgs {
+        CreateProductArgs::new(
+            "https://www.amiami.com/eng/detail/?gcode=FIGURE-1".to_owned(),
+            "figure_title".to_owned(),
+            "https://img.amiami.com/figure-1.jpg".to_owned(),
+            "9708".to_owned(),
+            "Good Smile Company".to_owned(),
+            20000,
+            18000,
+            NaiveDate::from_ymd_opt(2026, 3, 31).unwrap(),
+            Availability::Preorder
+        )
+    }
+
+    fn product_args2() -> CreateProductArgs {
+        CreateProductArgs::new(
+            "https://www.amiami.com/eng/detail/?gcode=FIGURE-2".to_owned(),
+            "figure_title2".to_owned(),
+            "https://img.amiami.com/figure-2.jpg".to_owned(),
+            "459".to_owned(),
+            "Alter".to_owned(),
+            25000,
+            25000,
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+            Availability::Available
+        )
+    }
+}
@@ -1,7 +1,12 @@
-use crate::domain::amiami::models::availability::Availability;
+use crate::domain::availability::Availability;
+use crate::domain::amiami::models::product::{Price, ProductHistoryEntry};
+use crate::domain::availability_stats::AvailabilityEvent;
+use crate::domain::product_history::{NotificationKind, ProductChange};
+use crate::domain::schedule::Schedule;
 use crate::outbound::sqlite::schema;
 use chrono::{NaiveDate, NaiveDateTime};
-use diesel::{AsChangeset, ExpressionMethods, Identifiable, Insertable, Queryable, Selectable};
+use diesel::sql_types::{Double, Integer, Text};
+use diesel::{AsChangeset, ExpressionMethods, Identifiable, Insertable, Queryable, QueryableByName, Selectable};
 
 #[derive(Debug, Queryable, Selectable, Identifiable, AsChangeset)]
 #[diesel(table_name = schema::amiami_product)]
@@ -19,6 +24,9 @@ pub struct ProductRow {
     pub release_date: NaiveDate,
     #[diesel(serialize_as = String, deserialize_as = String)]
     pub availability: Availability,
+    pub date_restocked: Option<NaiveDateTime>,
+    pub image_hash: Option<String>,
+    pub image_phash: Option<i64>,
 }
 
 #[derive(Debug, Insertable)]
@@ -37,6 +45,54 @@ pub struct ProductRowInsert<'a> {
     pub availability: Availability,
 }
 
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::amiami_availability_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct AvailabilityEventRow {
+    pub date_added: NaiveDateTime,
+    pub product_id: i32,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub availability: Availability,
+    pub previous_availability: Option<String>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::amiami_availability_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct AvailabilityEventRowInsert {
+    pub product_id: i32,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub availability: Availability,
+    pub previous_availability: Option<String>,
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::amiami_price_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct PriceEventRow {
+    pub date_added: NaiveDateTime,
+    pub full_price: i32,
+    pub min_price: i32,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::amiami_price_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct PriceEventRowInsert {
+    pub product_id: i32,
+    pub full_price: i32,
+    pub min_price: i32,
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::amiami_notification)]
+#[diesel(treat_none_as_null = true)]
+pub struct NotificationRow {
+    pub date_added: NaiveDateTime,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub kind: NotificationKind,
+}
+
 #[derive(Debug, Queryable, Selectable)]
 #[diesel(table_name = schema::amiami_category)]
 #[diesel(treat_none_as_null = true)]
@@ -44,7 +100,8 @@ pub struct CategoryRow {
     pub id: i32,
     pub date_added: NaiveDateTime,
     pub category: String,
-    pub following: bool
+    pub schedule: Option<String>,
+    pub date_scraped: Option<NaiveDateTime>,
 }
 
 #[derive(Debug, Insertable)]
@@ -54,3 +111,64 @@ pub struct CategoryRowInsert<'a> {
     pub category: &'a str,
     pub following: bool
 }
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::amiami_category_follower)]
+#[diesel(treat_none_as_null = true)]
+pub struct CategoryFollowerRow {
+    pub category_id: i32,
+    pub user_id: i32,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::amiami_category_follower)]
+#[diesel(treat_none_as_null = true)]
+pub struct CategoryFollowerRowInsert {
+    pub category_id: i32,
+    pub user_id: i32,
+}
+
+#[derive(Debug, QueryableByName)]
+pub struct ProductSearchRow {
+    #[diesel(sql_type = Integer)]
+    pub id: i32,
+    #[diesel(sql_type = Double)]
+    pub rank: f64,
+    #[diesel(sql_type = Text)]
+    pub title: String,
+    #[diesel(sql_type = Text)]
+    pub maker: String,
+}
+
+impl CategoryRow {
+    /// Schedules are validated before they are stored.
+    pub fn schedule(&self) -> Option<Schedule> {
+        self.schedule.as_deref().and_then(|s| Schedule::parse(s).ok())
+    }
+}
+
+impl AvailabilityEventRow {
+    pub fn into_domain(self) -> ProductHistoryEntry {
+        ProductHistoryEntry::new(self.date_added.and_utc(), ProductChange::Availability(self.availability))
+    }
+
+    pub fn into_stats_event(self) -> Result<AvailabilityEvent, strum::ParseError> {
+        let was_available = self.previous_availability
+            .map(Availability::try_from)
+            .transpose()?
+            .map(|a| a.is_available());
+        Ok(AvailabilityEvent::new(self.product_id, self.date_added.and_utc(), was_available, self.availability.is_available()))
+    }
+}
+
+impl PriceEventRow {
+    pub fn into_domain(self) -> ProductHistoryEntry {
+        ProductHistoryEntry::new(self.date_added.and_utc(), ProductChange::Price(Price::new(self.full_price, self.min_price)))
+    }
+}
+
+impl NotificationRow {
+    pub fn into_domain(self, username: String) -> ProductHistoryEntry {
+        ProductHistoryEntry::new(self.date_added.and_utc(), ProductChange::Notification { kind: self.kind, username })
+    }
+}
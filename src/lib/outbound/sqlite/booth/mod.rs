@@ -0,0 +1,885 @@
+use crate::domain::availability::Availability;
+use crate::domain::booth::models::product::{AddProductSourceError, CreateProductArgs, CreateProductError, GetProductsError, ItemData, Product, ProductHistoryEntry, UpdateProductArgs, UpdateProductError};
+use crate::domain::booth::models::source::{FollowSourceError, FollowedSource, GetSourcesError, LinkMelonbooksArtistError, Source, SourceArgs, SourceKind, UnfollowSourceError};
+use crate::domain::booth::ports::BoothRepository;
+use crate::domain::pagination::{Page, PageRequest};
+use crate::domain::product_history::{sort_history, GetProductError};
+use crate::outbound::sqlite::booth::models::{AvailabilityEventRow, AvailabilityEventRowInsert, NotificationRow, PriceEventRow, PriceEventRowInsert, ProductRow, ProductRowInsert, SourceFollowerRow, SourceFollowerRowInsert, SourceRow, SourceRowInsert, VariationRow, VariationRowInsert};
+use crate::outbound::sqlite::{schema, Sqlite};
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::r2d2::ConnectionManager;
+use diesel::sqlite::Sqlite as SqliteBackend;
+use itertools::Itertools;
+use r2d2::PooledConnection;
+use std::collections::HashMap;
+use schema::app_user::dsl as user_dsl;
+use schema::booth_availability_event::dsl as availability_event_dsl;
+use schema::booth_notification::dsl as notification_dsl;
+use schema::booth_price_event::dsl as price_event_dsl;
+use schema::booth_product::dsl as product_dsl;
+use schema::booth_product_source::dsl as product_source_dsl;
+use schema::booth_source::dsl as source_dsl;
+use schema::booth_source_follower::dsl as source_follower_dsl;
+use schema::booth_variation::dsl as variation_dsl;
+use schema::melonbooks_artist::dsl as melonbooks_artist_dsl;
+
+mod models;
+
+impl Sqlite {
+    fn get_booth_source_row_by_id(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        source_id: i32
+    ) -> Result<Option<SourceRow>, anyhow::Error> {
+        let source = source_dsl::booth_source
+            .select(SourceRow::as_select())
+            .filter(source_dsl::id.eq(source_id))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get source with id '{}'", source_id))?;
+        Ok(source)
+    }
+
+    fn get_or_insert_booth_source_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        args: &SourceArgs,
+    ) -> Result<SourceRow, anyhow::Error> {
+        let source = source_dsl::booth_source
+            .select(SourceRow::as_select())
+            .filter(source_dsl::name.eq(args.name()))
+            .filter(source_dsl::kind.eq(args.kind().to_string()))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get {} '{}'", args.kind(), args.name()))?;
+        if let Some(source) = source {
+            return Ok(source);
+        }
+        let source = diesel::insert_into(source_dsl::booth_source)
+            .values(SourceRowInsert { name: args.name(), kind: args.kind() })
+            .returning(SourceRow::as_returning())
+            .get_result(connection)
+            .with_context(|| format!("cannot insert {} '{}'", args.kind(), args.name()))?;
+        Ok(source)
+    }
+
+    fn get_booth_source_rows(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+    ) -> Result<Vec<SourceRow>, anyhow::Error> {
+        let sources = source_dsl::booth_source
+            .select(SourceRow::as_select())
+            .order_by((source_dsl::kind, source_dsl::name))
+            .get_results(connection)
+            .with_context(|| "cannot select sources")?;
+        Ok(sources)
+    }
+
+    fn filtered_booth_source_query<'a>(
+        &self,
+        user_id: i32,
+        following: Option<bool>,
+    ) -> schema::booth_source::BoxedQuery<'a, SqliteBackend> {
+        let followed_ids = source_follower_dsl::booth_source_follower
+            .filter(source_follower_dsl::user_id.eq(user_id))
+            .select(source_follower_dsl::source_id);
+        match following {
+            Some(true) => source_dsl::booth_source.filter(source_dsl::id.eq_any(followed_ids)).into_boxed(),
+            Some(false) => source_dsl::booth_source.filter(diesel::dsl::not(source_dsl::id.eq_any(followed_ids))).into_boxed(),
+            None => source_dsl::booth_source.into_boxed(),
+        }
+    }
+
+    fn get_booth_source_rows_page(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        user_id: i32,
+        following: Option<bool>,
+        page: PageRequest,
+    ) -> Result<(Vec<SourceRow>, i64), anyhow::Error> {
+        let total = self.filtered_booth_source_query(user_id, following)
+            .count()
+            .get_result::<i64>(connection)
+            .with_context(|| "cannot count sources")?;
+        let sources = self.filtered_booth_source_query(user_id, following)
+            .select(SourceRow::as_select())
+            .order_by((source_dsl::kind, source_dsl::name))
+            .limit(page.page_size() as i64)
+            .offset(page.offset())
+            .get_results(connection)
+            .with_context(|| "cannot select sources")?;
+        Ok((sources, total))
+    }
+
+    fn get_booth_source_follower_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        source: &SourceRow,
+        user_id: i32,
+    ) -> Result<Option<SourceFollowerRow>, anyhow::Error> {
+        let follower = source_follower_dsl::booth_source_follower
+            .select(SourceFollowerRow::as_select())
+            .filter(source_follower_dsl::source_id.eq(source.id))
+            .filter(source_follower_dsl::user_id.eq(user_id))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get follower '{}' of {} '{}'", user_id, source.kind, source.name))?;
+        Ok(follower)
+    }
+
+    fn get_booth_source_follower_rows(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+    ) -> Result<Vec<SourceFollowerRow>, anyhow::Error> {
+        let followers = source_follower_dsl::booth_source_follower
+            .select(SourceFollowerRow::as_select())
+            .get_results(connection)
+            .with_context(|| "cannot get source followers")?;
+        Ok(followers)
+    }
+
+    fn get_booth_source_follower_rows_by_user(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        user_id: i32,
+    ) -> Result<Vec<SourceFollowerRow>, anyhow::Error> {
+        let followers = source_follower_dsl::booth_source_follower
+            .select(SourceFollowerRow::as_select())
+            .filter(source_follower_dsl::user_id.eq(user_id))
+            .get_results(connection)
+            .with_context(|| format!("cannot get sources followed by user '{}'", user_id))?;
+        Ok(followers)
+    }
+
+    /// Products found for any of the sources, once each as an item can be listed by its shop and by any number of tags.
+    fn get_booth_product_rows_page_by_sources(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        source_ids: &[i32],
+        page: PageRequest,
+    ) -> Result<(Vec<ProductRow>, i64), anyhow::Error> {
+        let product_ids = || product_source_dsl::booth_product_source
+            .filter(product_source_dsl::source_id.eq_any(source_ids))
+            .select(product_source_dsl::product_id);
+        let total = product_dsl::booth_product
+            .filter(product_dsl::id.eq_any(product_ids()))
+            .count()
+            .get_result::<i64>(connection)
+            .with_context(|| format!("cannot count products of sources with ids {:?}", source_ids))?;
+        let products = product_dsl::booth_product
+            .select(ProductRow::as_select())
+            .filter(product_dsl::id.eq_any(product_ids()))
+            .order_by((product_dsl::date_added.desc(), product_dsl::id.desc()))
+            .limit(page.page_size() as i64)
+            .offset(page.offset())
+            .get_results(connection)
+            .with_context(|| format!("cannot get products of sources with ids {:?}", source_ids))?;
+        Ok((products, total))
+    }
+
+    fn get_booth_product_row_by_url(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        url: &str
+    ) -> Result<Option<ProductRow>, anyhow::Error> {
+        let product = product_dsl::booth_product
+            .select(ProductRow::as_select())
+            .filter(product_dsl::url.eq(url))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get product with url '{}'", url))?;
+        Ok(product)
+    }
+
+    fn get_booth_product_row_by_id(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32
+    ) -> Result<Option<ProductRow>, anyhow::Error> {
+        let product = product_dsl::booth_product
+            .select(ProductRow::as_select())
+            .find(product_id)
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get product with id '{}'", product_id))?;
+        Ok(product)
+    }
+
+    /// Loads the variations of the products, in the order BOOTH listed them first.
+    fn load_booth_products(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_rows: Vec<ProductRow>,
+    ) -> Result<Vec<Product>, anyhow::Error> {
+        let product_ids = product_rows.iter().map(|p| p.id).collect::<Vec<_>>();
+        let mut variations = variation_dsl::booth_variation
+            .select(VariationRow::as_select())
+            .filter(variation_dsl::product_id.eq_any(&product_ids))
+            .order_by(variation_dsl::id)
+            .get_results(connection)
+            .with_context(|| "cannot get variations")?
+            .into_iter()
+            .into_group_map_by(|v| v.product_id);
+        let products = product_rows.into_iter()
+            .map(|p| {
+                let product_variations = variations.remove(&p.id).unwrap_or_default();
+                p.into_domain(product_variations)
+            })
+            .collect();
+        Ok(products)
+    }
+
+    fn load_booth_product(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_row: ProductRow,
+    ) -> Result<Product, anyhow::Error> {
+        let product = self.load_booth_products(connection, vec![product_row])?
+            .into_iter()
+            .next()
+            .with_context(|| "cannot load variations of product")?;
+        Ok(product)
+    }
+
+    fn insert_booth_product_source_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+        source_id: i32,
+    ) -> Result<(), anyhow::Error> {
+        diesel::insert_or_ignore_into(product_source_dsl::booth_product_source)
+            .values((product_source_dsl::product_id.eq(product_id), product_source_dsl::source_id.eq(source_id)))
+            .execute(connection)
+            .with_context(|| format!("cannot link product '{}' to source '{}'", product_id, source_id))?;
+        Ok(())
+    }
+
+    /// Updates the variations of the item by name, variations BOOTH no longer lists are kept as sold out.
+    fn upsert_booth_variation_rows(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+        item: &ItemData,
+    ) -> Result<(), anyhow::Error> {
+        let variations = variation_dsl::booth_variation
+            .select(VariationRow::as_select())
+            .filter(variation_dsl::product_id.eq(product_id))
+            .get_results(connection)
+            .with_context(|| format!("cannot get variations of product '{}'", product_id))?;
+        for variation in variations.iter() {
+            let (price, availability) = match item.variations().iter().find(|v| v.name() == variation.name) {
+                Some(v) => (v.price(), v.availability().clone()),
+                None => (variation.price, Availability::NotAvailable),
+            };
+            if variation.price == price && variation.availability == availability {
+                continue;
+            }
+            diesel::update(variation)
+                .set((variation_dsl::price.eq(price), variation_dsl::availability.eq(availability.to_string())))
+                .execute(connection)
+                .with_context(|| format!("cannot update variation '{}' of product '{}'", variation.name, product_id))?;
+        }
+        let new_variations = item.variations().iter()
+            .filter(|v| !variations.iter().any(|pv| pv.name == v.name()))
+            .unique_by(|v| v.name())
+            .map(|v| VariationRowInsert { product_id, name: v.name(), price: v.price(), availability: v.availability().clone() })
+            .collect::<Vec<_>>();
+        diesel::insert_into(variation_dsl::booth_variation)
+            .values(new_variations)
+            .execute(connection)
+            .with_context(|| format!("cannot insert variations of product '{}'", product_id))?;
+        Ok(())
+    }
+
+    fn update_booth_product_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product: &ProductRow,
+        args: &UpdateProductArgs,
+    ) -> Result<ProductRow, anyhow::Error> {
+        let item = args.item();
+        self.upsert_booth_variation_rows(connection, product.id, item)?;
+        let date_restocked = match !product.availability.is_available() && item.availability().is_available() {
+            true => Some(Utc::now().naive_utc()),
+            false => product.date_restocked,
+        };
+        if product.availability != item.availability() {
+            self.insert_booth_availability_event_row(connection, product.id, Some(product.availability.clone()), item.availability())?;
+        }
+        if product.price != item.price() {
+            self.insert_booth_price_event_row(connection, product.id, item.price())?;
+        }
+        let product = diesel::update(&product)
+            .set((
+                product_dsl::title.eq(item.title()),
+                product_dsl::price.eq(item.price()),
+                product_dsl::availability.eq(item.availability().to_string()),
+                product_dsl::date_restocked.eq(date_restocked),
+            ))
+            .returning(ProductRow::as_returning())
+            .get_result(connection)
+            .with_context(|| format!("cannot update product with url '{}'", product.url))?;
+        Ok(product)
+    }
+
+    fn insert_booth_availability_event_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+        previous_availability: Option<Availability>,
+        availability: Availability,
+    ) -> Result<(), anyhow::Error> {
+        diesel::insert_into(availability_event_dsl::booth_availability_event)
+            .values(AvailabilityEventRowInsert { product_id, availability, previous_availability: previous_availability.map(|a| a.to_string()) })
+            .execute(connection)
+            .with_context(|| format!("cannot insert availability event for product '{}'", product_id))?;
+        Ok(())
+    }
+
+    fn insert_booth_price_event_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+        price: Option<i32>,
+    ) -> Result<(), anyhow::Error> {
+        diesel::insert_into(price_event_dsl::booth_price_event)
+            .values(PriceEventRowInsert { product_id, price })
+            .execute(connection)
+            .with_context(|| format!("cannot insert price event for product '{}'", product_id))?;
+        Ok(())
+    }
+
+    fn get_booth_product_history_entries(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+    ) -> Result<Vec<ProductHistoryEntry>, anyhow::Error> {
+        let availability_events = availability_event_dsl::booth_availability_event
+            .select(AvailabilityEventRow::as_select())
+            .filter(availability_event_dsl::product_id.eq(product_id))
+            .order_by(availability_event_dsl::id.asc())
+            .get_results(connection)
+            .with_context(|| format!("cannot get availability events for product '{}'", product_id))?;
+        let price_events = price_event_dsl::booth_price_event
+            .select(PriceEventRow::as_select())
+            .filter(price_event_dsl::product_id.eq(product_id))
+            .order_by(price_event_dsl::id.asc())
+            .get_results(connection)
+            .with_context(|| format!("cannot get price events for product '{}'", product_id))?;
+        let notifications = notification_dsl::booth_notification
+            .inner_join(user_dsl::app_user)
+            .select((NotificationRow::as_select(), user_dsl::username))
+            .filter(notification_dsl::product_id.eq(product_id))
+            .order_by(notification_dsl::id.asc())
+            .get_results::<(NotificationRow, String)>(connection)
+            .with_context(|| format!("cannot get notifications for product '{}'", product_id))?;
+        let mut history = availability_events.into_iter().map(|e| e.into_domain())
+            .chain(price_events.into_iter().map(|e| e.into_domain()))
+            .chain(notifications.into_iter().map(|(n, username)| n.into_domain(username)))
+            .collect::<Vec<_>>();
+        sort_history(&mut history);
+        Ok(history)
+    }
+}
+
+#[async_trait]
+impl BoothRepository for Sqlite {
+    async fn follow_booth_source(&self, user_id: i32, args: &SourceArgs) -> Result<(), FollowSourceError> {
+        let args = args.clone();
+        self.write(move |db, connection| {
+            let source = db.get_or_insert_booth_source_row(connection, &args)?;
+            if db.get_booth_source_follower_row(connection, &source, user_id)?.is_some() {
+                return Err(FollowSourceError::AlreadyFollowedError { name: source.name, kind: source.kind });
+            }
+            diesel::insert_into(source_follower_dsl::booth_source_follower)
+                .values(SourceFollowerRowInsert { source_id: source.id, user_id })
+                .execute(connection)
+                .with_context(|| format!("cannot follow {} '{}' for user '{}'", source.kind, source.name, user_id))?;
+            Ok(())
+        }).await
+    }
+
+    async fn unfollow_booth_source(&self, user_id: i32, source_id: i32) -> Result<(), UnfollowSourceError> {
+        self.write(move |db, connection| {
+            let source = db.get_booth_source_row_by_id(connection, source_id)?
+                .ok_or(UnfollowSourceError::UnknownSource { id: source_id })?;
+            if db.get_booth_source_follower_row(connection, &source, user_id)?.is_none() {
+                return Err(UnfollowSourceError::SourceNotFollowed { name: source.name, kind: source.kind });
+            }
+            diesel::delete(source_follower_dsl::booth_source_follower)
+                .filter(source_follower_dsl::source_id.eq(source.id))
+                .filter(source_follower_dsl::user_id.eq(user_id))
+                .execute(connection)
+                .with_context(|| format!("cannot unfollow {} '{}' for user '{}'", source.kind, source.name, user_id))?;
+            Ok(())
+        }).await
+    }
+
+    async fn get_booth_sources(&self, user_id: i32) -> Result<Vec<Source>, GetSourcesError> {
+        self.read(move |db, connection| {
+            let source_rows = db.get_booth_source_rows(connection)?;
+            let followers = db.get_booth_source_follower_rows_by_user(connection, user_id)?
+                .into_iter()
+                .map(|f| (f.source_id, f))
+                .collect::<HashMap<_, _>>();
+            let sources = source_rows.into_iter()
+                .map(|s| {
+                    let follower = followers.get(&s.id);
+                    s.into_domain_for(follower)
+                })
+                .collect();
+            Ok(sources)
+        }).await
+    }
+
+    async fn get_booth_sources_page(&self, user_id: i32, following: Option<bool>, page: PageRequest) -> Result<Page<Source>, GetSourcesError> {
+        self.read(move |db, connection| {
+            let (source_rows, total) = db.get_booth_source_rows_page(connection, user_id, following, page)?;
+            let followers = db.get_booth_source_follower_rows_by_user(connection, user_id)?
+                .into_iter()
+                .map(|f| (f.source_id, f))
+                .collect::<HashMap<_, _>>();
+            let sources = source_rows.into_iter()
+                .map(|s| {
+                    let follower = followers.get(&s.id);
+                    s.into_domain_for(follower)
+                })
+                .collect();
+            Ok(Page::new(sources, page, total))
+        }).await
+    }
+
+    async fn get_followed_booth_sources(&self) -> Result<Vec<FollowedSource>, GetSourcesError> {
+        self.read(move |db, connection| {
+            let follower_rows = db.get_booth_source_follower_rows(connection)?;
+            let user_ids = follower_rows.iter().map(|f| f.user_id).unique().collect::<Vec<_>>();
+            let users = db.get_user_rows_by_ids(connection, &user_ids)?
+                .into_iter()
+                .map(|u| (u.id, u.into_domain()))
+                .collect::<HashMap<_, _>>();
+            let mut followers = follower_rows.into_iter().into_group_map_by(|f| f.source_id);
+            let sources = db.get_booth_source_rows(connection)?
+                .into_iter()
+                .filter_map(|source| {
+                    let source_followers = followers.remove(&source.id)?;
+                    let first_follower = source_followers.iter().min_by_key(|f| f.date_followed);
+                    let mut source_followers = source_followers.iter()
+                        .filter_map(|f| users.get(&f.user_id).cloned())
+                        .collect::<Vec<_>>();
+                    source_followers.sort_by(|a, b| a.username().cmp(b.username()));
+                    let source = Source::new(source.id, source.date_added.and_utc(), source.name, source.kind, source.melonbooks_artist_id, true, first_follower.map(|f| f.date_followed.and_utc()));
+                    Some(FollowedSource::new(source, source_followers))
+                })
+                .collect();
+            Ok(sources)
+        }).await
+    }
+
+    async fn link_booth_source_to_melonbooks_artist(&self, source_id: i32, artist_id: Option<i32>) -> Result<(), LinkMelonbooksArtistError> {
+        self.write(move |db, connection| {
+            let source = db.get_booth_source_row_by_id(connection, source_id)?
+                .ok_or(LinkMelonbooksArtistError::UnknownSource { id: source_id })?;
+            if source.kind != SourceKind::Shop {
+                return Err(LinkMelonbooksArtistError::NotAShop { name: source.name });
+            }
+            if let Some(artist_id) = artist_id {
+                let artist = melonbooks_artist_dsl::melonbooks_artist
+                    .select(melonbooks_artist_dsl::id)
+                    .find(artist_id)
+                    .first::<i32>(connection)
+                    .optional()
+                    .with_context(|| format!("cannot get melonbooks artist with id '{}'", artist_id))?;
+                if artist.is_none() {
+                    return Err(LinkMelonbooksArtistError::UnknownArtist { id: artist_id });
+                }
+            }
+            diesel::update(&source)
+                .set(source_dsl::melonbooks_artist_id.eq(artist_id))
+                .execute(connection)
+                .with_context(|| format!("cannot link shop '{}' to melonbooks artist {:?}", source.name, artist_id))?;
+            Ok(())
+        }).await
+    }
+
+    async fn create_booth_product(&self, args: &CreateProductArgs) -> Result<Product, CreateProductError> {
+        let args = args.clone();
+        self.write(move |db, connection| {
+            let item = args.item();
+            if let Some(product_row) = db.get_booth_product_row_by_url(connection, item.url())? {
+                return Err(CreateProductError::DuplicateProduct { url: product_row.url, title: product_row.title });
+            }
+            let product = connection.transaction(|connection| -> Result<Product, anyhow::Error> {
+                let product_row = diesel::insert_into(product_dsl::booth_product)
+                    .values(ProductRowInsert {
+                        url: item.url(),
+                        title: item.title(),
+                        shop_name: item.shop_name(),
+                        image_url: item.image_url(),
+                        price: item.price(),
+                        availability: item.availability(),
+                    })
+                    .returning(ProductRow::as_returning())
+                    .get_result(connection)
+                    .with_context(|| format!("cannot insert product with url '{}'", item.url()))?;
+                db.upsert_booth_variation_rows(connection, product_row.id, item)?;
+                db.insert_booth_availability_event_row(connection, product_row.id, None, item.availability())?;
+                db.insert_booth_price_event_row(connection, product_row.id, item.price())?;
+                db.insert_booth_product_source_row(connection, product_row.id, args.source_id())?;
+                db.load_booth_product(connection, product_row)
+            })?;
+            Ok(product)
+        }).await
+    }
+
+    async fn update_booth_product(&self, args: &UpdateProductArgs) -> Result<Product, UpdateProductError> {
+        let args = args.clone();
+        self.write(move |db, connection| {
+            let product_row = db.get_booth_product_row_by_url(connection, args.url())?
+                .ok_or_else(|| UpdateProductError::ProductMissing { url: args.url().to_owned() })?;
+            let product = connection.transaction(|connection| {
+                let product_row = db.update_booth_product_row(connection, &product_row, &args)?;
+                db.load_booth_product(connection, product_row)
+            })?;
+            Ok(product)
+        }).await
+    }
+
+    async fn add_booth_product_source(&self, url: &str, source_id: i32) -> Result<(), AddProductSourceError> {
+        let url = url.to_owned();
+        self.write(move |db, connection| {
+            let product_row = db.get_booth_product_row_by_url(connection, &url)?
+                .ok_or_else(|| AddProductSourceError::ProductMissing { url: url.clone() })?;
+            db.insert_booth_product_source_row(connection, product_row.id, source_id)?;
+            Ok(())
+        }).await
+    }
+
+    async fn get_booth_product(&self, product_id: i32) -> Result<Product, GetProductError> {
+        self.read(move |db, connection| {
+            let product_row = db.get_booth_product_row_by_id(connection, product_id)?
+                .ok_or(GetProductError::ProductMissing { id: product_id })?;
+            Ok(db.load_booth_product(connection, product_row)?)
+        }).await
+    }
+
+    async fn get_booth_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError> {
+        self.read(move |db, connection| {
+            if db.get_booth_product_row_by_id(connection, product_id)?.is_none() {
+                return Err(GetProductError::ProductMissing { id: product_id });
+            }
+            let history = db.get_booth_product_history_entries(connection, product_id)?;
+            Ok(history)
+        }).await
+    }
+
+    async fn get_booth_products(&self) -> Result<Vec<Product>, GetProductsError> {
+        self.read(move |db, connection| {
+            let product_rows = product_dsl::booth_product
+                .select(ProductRow::as_select())
+                .order_by(product_dsl::date_added.desc())
+                .get_results(connection)
+                .with_context(|| "cannot get products")?;
+            Ok(db.load_booth_products(connection, product_rows)?)
+        }).await
+    }
+
+    async fn get_booth_products_by_source(&self, source_id: i32) -> Result<Vec<Product>, GetProductsError> {
+        self.read(move |db, connection| {
+            let product_rows = product_source_dsl::booth_product_source
+                .inner_join(product_dsl::booth_product)
+                .select(ProductRow::as_select())
+                .filter(product_source_dsl::source_id.eq(source_id))
+                .order_by(product_dsl::date_added.desc())
+                .get_results(connection)
+                .with_context(|| format!("cannot get products of source with id {}", source_id))?;
+            Ok(db.load_booth_products(connection, product_rows)?)
+        }).await
+    }
+
+    async fn get_booth_products_page(&self, page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        self.read(move |db, connection| {
+            let total = product_dsl::booth_product
+                .count()
+                .get_result::<i64>(connection)
+                .with_context(|| "cannot count products")?;
+            let product_rows = product_dsl::booth_product
+                .select(ProductRow::as_select())
+                .order_by((product_dsl::date_added.desc(), product_dsl::id.desc()))
+                .limit(page.page_size() as i64)
+                .offset(page.offset())
+                .get_results(connection)
+                .with_context(|| "cannot get products")?;
+            Ok(Page::new(db.load_booth_products(connection, product_rows)?, page, total))
+        }).await
+    }
+
+    async fn get_booth_products_page_by_source(&self, source_id: i32, page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        self.read(move |db, connection| {
+            let (product_rows, total) = db.get_booth_product_rows_page_by_sources(connection, &[source_id], page)?;
+            Ok(Page::new(db.load_booth_products(connection, product_rows)?, page, total))
+        }).await
+    }
+
+    async fn get_booth_products_page_by_sources(&self, source_ids: &[i32], page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        let source_ids = source_ids.to_vec();
+        self.read(move |db, connection| {
+            let (product_rows, total) = db.get_booth_product_rows_page_by_sources(connection, &source_ids, page)?;
+            Ok(Page::new(db.load_booth_products(connection, product_rows)?, page, total))
+        }).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::domain::booth::models::product::VariationData;
+    use crate::domain::booth::SITE;
+    use crate::domain::image::models::image::{CachedImage, SetProductImageError};
+    use crate::domain::melonbooks::models::artist::ArtistArgs;
+    use crate::domain::melonbooks::ports::MelonbooksRepository;
+    use crate::domain::product_history::{NotificationKind, ProductChange};
+    use crate::domain::site::SiteRepository;
+    use crate::domain::user::models::user::DEFAULT_USERNAME;
+    use crate::domain::user::ports::UserRepository;
+    use crate::outbound::sqlite::test_util::default_user_id;
+
+    #[tokio::test]
+    async fn test_follow_booth_source() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        db.follow_booth_source(user_id, &SourceArgs::new("mafuyu".to_owned(), SourceKind::Shop)).await.unwrap();
+        db.follow_booth_source(user_id, &SourceArgs::new("mafuyu".to_owned(), SourceKind::Tag)).await.unwrap();
+
+        let sources = db.get_booth_sources(user_id).await.unwrap();
+        assert_eq!(sources.len(), 2);
+        assert!(sources.iter().all(|s| s.name() == "mafuyu" && s.following() && s.date_followed().is_some()));
+        assert!(matches!(
+            db.follow_booth_source(user_id, &SourceArgs::new("https://mafuyu.booth.pm/".to_owned(), SourceKind::Shop)).await,
+            Err(FollowSourceError::AlreadyFollowedError { kind: SourceKind::Shop, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unfollow_booth_source() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let users = db.setup_users(DEFAULT_USERNAME, &["alice".to_owned()]).await.unwrap();
+        let (alice, default) = (users.first().unwrap(), users.last().unwrap());
+        let args = SourceArgs::new("mafuyu".to_owned(), SourceKind::Shop);
+        db.follow_booth_source(alice.id(), &args).await.unwrap();
+        db.follow_booth_source(default.id(), &args).await.unwrap();
+        let source = db.get_booth_sources(alice.id()).await.unwrap().into_iter().next().unwrap();
+
+        let followed = db.get_followed_booth_sources().await.unwrap();
+        assert_eq!(followed.len(), 1);
+        let usernames = followed.first().unwrap().followers().iter().map(|u| u.username()).collect::<Vec<_>>();
+        assert_eq!(usernames, vec!["alice", DEFAULT_USERNAME]);
+
+        db.unfollow_booth_source(default.id(), source.id()).await.unwrap();
+        assert!(db.get_booth_sources(alice.id()).await.unwrap().first().unwrap().following());
+        assert!(!db.get_booth_sources(default.id()).await.unwrap().first().unwrap().following());
+        assert!(matches!(db.unfollow_booth_source(default.id(), source.id()).await, Err(UnfollowSourceError::SourceNotFollowed { .. })));
+        assert!(matches!(db.unfollow_booth_source(default.id(), source.id() + 1).await, Err(UnfollowSourceError::UnknownSource { .. })));
+
+        db.unfollow_booth_source(alice.id(), source.id()).await.unwrap();
+        assert!(db.get_followed_booth_sources().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_link_booth_source_to_melonbooks_artist() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        let shop_id = source_id(&db, SourceKind::Shop).await;
+        let tag_id = source_id(&db, SourceKind::Tag).await;
+        db.follow_melonbooks_artist(user_id, &ArtistArgs::new("mafuyu".to_owned())).await.unwrap();
+        let artist_id = db.get_melonbooks_artists(user_id).await.unwrap().first().unwrap().id();
+
+        db.link_booth_source_to_melonbooks_artist(shop_id, Some(artist_id)).await.unwrap();
+        let shop = db.get_booth_sources(user_id).await.unwrap().into_iter().find(|s| s.id() == shop_id).unwrap();
+        assert_eq!(shop.melonbooks_artist_id(), Some(artist_id));
+        assert!(matches!(db.link_booth_source_to_melonbooks_artist(tag_id, Some(artist_id)).await, Err(LinkMelonbooksArtistError::NotAShop { .. })));
+        assert!(matches!(db.link_booth_source_to_melonbooks_artist(shop_id, Some(artist_id + 1)).await, Err(LinkMelonbooksArtistError::UnknownArtist { .. })));
+        assert!(matches!(db.link_booth_source_to_melonbooks_artist(tag_id + 1, None).await, Err(LinkMelonbooksArtistError::UnknownSource { .. })));
+
+        db.link_booth_source_to_melonbooks_artist(shop_id, None).await.unwrap();
+        let shop = db.get_booth_sources(user_id).await.unwrap().into_iter().find(|s| s.id() == shop_id).unwrap();
+        assert_eq!(shop.melonbooks_artist_id(), None);
+    }
+
+    #[tokio::test]
+    async fn test_create_booth_product() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let source_id = source_id(&db, SourceKind::Shop).await;
+        let args = CreateProductArgs::new(source_id, item(vec![
+            variation("Download", 500, Availability::NotAvailable),
+            variation("Printed", 1200, Availability::Available),
+        ]));
+        let product = db.create_booth_product(&args).await.unwrap();
+
+        assert_eq!(product.url(), args.item().url());
+        assert_eq!(product.shop_name(), "mafuyu_shop");
+        assert_eq!(product.price(), Some(1200));
+        assert_eq!(product.availability(), Availability::Available);
+        let names = product.variations().iter().map(|v| v.name()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["Download", "Printed"]);
+        assert_eq!(db.get_booth_products_by_source(source_id).await.unwrap(), vec![product]);
+        assert!(matches!(db.create_booth_product(&args).await, Err(CreateProductError::DuplicateProduct { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_add_booth_product_source() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let shop_id = source_id(&db, SourceKind::Shop).await;
+        let tag_id = source_id(&db, SourceKind::Tag).await;
+        let product = db.create_booth_product(&CreateProductArgs::new(shop_id, item(vec![variation("", 500, Availability::Available)]))).await.unwrap();
+
+        db.add_booth_product_source(product.url(), tag_id).await.unwrap();
+        db.add_booth_product_source(product.url(), tag_id).await.unwrap();
+        assert_eq!(db.get_booth_products_by_source(tag_id).await.unwrap(), vec![product]);
+        assert_eq!(db.get_booth_products().await.unwrap().len(), 1);
+        assert!(matches!(db.add_booth_product_source("https://missing", tag_id).await, Err(AddProductSourceError::ProductMissing { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_get_booth_products_page() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let shop_id = source_id(&db, SourceKind::Shop).await;
+        let tag_id = source_id(&db, SourceKind::Tag).await;
+        let mut products = Vec::new();
+        for item_id in ["1", "2", "3"] {
+            products.push(db.create_booth_product(&CreateProductArgs::new(shop_id, item_with_id(item_id, vec![variation("", 500, Availability::Available)]))).await.unwrap());
+        }
+        db.create_booth_product(&CreateProductArgs::new(tag_id, item_with_id("4", vec![variation("", 500, Availability::Available)]))).await.unwrap();
+
+        let page = db.get_booth_products_page(PageRequest::new(2, 3)).await.unwrap();
+        assert_eq!(page.total_items(), 4);
+        assert_eq!(page.items().len(), 1);
+        let page = db.get_booth_products_page_by_source(shop_id, PageRequest::new(1, 2)).await.unwrap();
+        assert_eq!(page.total_items(), 3);
+        assert_eq!(page.items(), &[products[2].clone(), products[1].clone()]);
+        let page = db.get_booth_products_page_by_source(shop_id, PageRequest::new(2, 2)).await.unwrap();
+        assert_eq!(page.items(), &[products[0].clone()]);
+    }
+
+    #[tokio::test]
+    async fn test_get_booth_products_page_by_sources() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        let shop_id = source_id(&db, SourceKind::Shop).await;
+        let tag_id = source_id(&db, SourceKind::Tag).await;
+        let product = db.create_booth_product(&CreateProductArgs::new(shop_id, item_with_id("1", vec![variation("", 500, Availability::Available)]))).await.unwrap();
+        let product2 = db.create_booth_product(&CreateProductArgs::new(tag_id, item_with_id("2", vec![variation("", 500, Availability::Available)]))).await.unwrap();
+        db.add_booth_product_source(product.url(), tag_id).await.unwrap();
+
+        let page = db.get_booth_products_page_by_sources(&[shop_id, tag_id], PageRequest::new(1, 10)).await.unwrap();
+        assert_eq!(page.total_items(), 2);
+        assert_eq!(page.items(), &[product2.clone(), product.clone()]);
+        let page = db.get_booth_products_page_by_sources(&[shop_id, tag_id], PageRequest::new(2, 1)).await.unwrap();
+        assert_eq!(page.items(), &[product]);
+        assert!(db.get_booth_products_page_by_sources(&[], PageRequest::default()).await.unwrap().items().is_empty());
+
+        db.unfollow_booth_source(user_id, tag_id).await.unwrap();
+        let page = db.get_booth_sources_page(user_id, Some(true), PageRequest::new(1, 10)).await.unwrap();
+        assert_eq!(page.items().iter().map(|s| s.id()).collect::<Vec<_>>(), vec![shop_id]);
+        let page = db.get_booth_sources_page(user_id, Some(false), PageRequest::new(1, 10)).await.unwrap();
+        assert_eq!(page.items().iter().map(|s| s.id()).collect::<Vec<_>>(), vec![tag_id]);
+        assert_eq!(db.get_booth_sources_page(user_id, None, PageRequest::new(1, 10)).await.unwrap().total_items(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_update_booth_product() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        db.create_booth_product(&CreateProductArgs::new(source_id(&db, SourceKind::Shop).await, item(vec![
+            variation("Download", 500, Availability::Available),
+            variation("Printed", 1200, Availability::NotAvailable),
+        ]))).await.unwrap();
+
+        let product = db.update_booth_product(&UpdateProductArgs::new(item(vec![
+            variation("Printed", 1000, Availability::Available),
+            variation("Set", 1500, Availability::Available),
+        ]))).await.unwrap();
+        let variations = product.variations().iter().map(|v| (v.name(), v.price(), v.availability())).collect::<Vec<_>>();
+        assert_eq!(variations, vec![
+            ("Download", 500, Availability::NotAvailable),
+            ("Printed", 1000, Availability::Available),
+            ("Set", 1500, Availability::Available),
+        ]);
+        assert_eq!(product.price(), Some(1000));
+        assert_eq!(product.date_restocked(), None);
+
+        let product = db.update_booth_product(&UpdateProductArgs::new(ItemData::sold_out(&product))).await.unwrap();
+        assert_eq!(product.availability(), Availability::NotAvailable);
+        let product = db.update_booth_product(&UpdateProductArgs::new(item(vec![variation("Set", 1500, Availability::Available)]))).await.unwrap();
+        assert_eq!(product.availability(), Availability::Available);
+        assert_ne!(product.date_restocked(), None);
+        assert!(matches!(db.update_booth_product(&UpdateProductArgs::new(ItemData::new("https://missing".to_owned(), "".to_owned(), "".to_owned(), "".to_owned(), vec![]))).await, Err(UpdateProductError::ProductMissing { .. })));
+
+        db.add_notifications(SITE, user_id, NotificationKind::RestockedProduct, &[product.id()]).await.unwrap();
+        let history = db.get_booth_product_history(product.id()).await.unwrap();
+        let prices = history.iter()
+            .filter_map(|e| match e.change() { ProductChange::Price(p) => Some(*p), _ => None })
+            .collect::<Vec<_>>();
+        assert_eq!(prices, vec![Some(500), Some(1000), Some(500), Some(1500)]);
+        let availabilities = history.iter()
+            .filter_map(|e| match e.change() { ProductChange::Availability(a) => Some(a.clone()), _ => None })
+            .collect::<Vec<_>>();
+        assert_eq!(availabilities, vec![Availability::Available, Availability::NotAvailable, Availability::Available]);
+        let notifications = history.iter()
+            .filter(|e| matches!(e.change(), ProductChange::Notification { .. }))
+            .map(|e| e.change().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(notifications, vec![ProductChange::Notification { kind: NotificationKind::RestockedProduct, username: DEFAULT_USERNAME.to_owned() }]);
+        assert!(matches!(db.get_booth_product_history(product.id() + 1).await, Err(GetProductError::ProductMissing { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_set_booth_product_image() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let product = db.create_booth_product(&CreateProductArgs::new(source_id(&db, SourceKind::Shop).await, item(vec![variation("", 500, Availability::Available)]))).await.unwrap();
+
+        let hash = "a".repeat(64);
+        let image = CachedImage::new(hash.clone(), Some(0xF000_0000_0000_0001));
+        db.set_product_image(SITE, product.id(), &image).await.unwrap();
+        let loaded = db.get_booth_product(product.id()).await.unwrap();
+        assert_eq!(loaded.image_hash(), Some(hash.as_str()));
+        assert_eq!(loaded.image_phash(), Some(0xF000_0000_0000_0001));
+        assert_eq!(loaded.variations().len(), 1);
+        assert!(matches!(db.set_product_image(SITE, product.id() + 1, &image).await, Err(SetProductImageError::ProductMissing { .. })));
+    }
+
+    fn item(variations: Vec<VariationData>) -> ItemData {
+        item_with_id("1234567", variations)
+    }
+
+    fn item_with_id(item_id: &str, variations: Vec<VariationData>) -> ItemData {
+        ItemData::new(
+            format!("https://booth.pm/ja/items/{}", item_id),
+            "mafuyu_title".to_owned(),
+            "mafuyu_shop".to_owned(),
+            format!("https://booth.pximg.net/c/300x300_a2_g5/{}.jpg", item_id),
+            variations
+        )
+    }
+
+    fn variation(name: &str, price: i32, availability: Availability) -> VariationData {
+        VariationData::new(name.to_owned(), price, availability)
+    }
+
+    async fn source_id(db: &Sqlite, kind: SourceKind) -> i32 {
+        let user_id = default_user_id(db).await;
+        db.follow_booth_source(user_id, &SourceArgs::new("mafuyu".to_owned(), kind)).await.unwrap();
+        db.get_booth_sources(user_id).await.unwrap().into_iter().find(|s| s.kind() == kind).unwrap().id()
+    }
+}
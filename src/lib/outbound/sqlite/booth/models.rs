@@ -0,0 +1,192 @@
+use crate::domain::availability::Availability;
+use crate::domain::booth::models::product::{Product, ProductHistoryEntry, Variation};
+use crate::domain::booth::models::source::{Source, SourceKind};
+use crate::domain::product_history::{NotificationKind, ProductChange};
+use crate::outbound::sqlite::schema;
+use chrono::NaiveDateTime;
+use diesel::{Identifiable, Insertable, Queryable, Selectable};
+
+#[derive(Debug, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = schema::booth_product)]
+#[diesel(treat_none_as_null = true)]
+pub struct ProductRow {
+    pub id: i32,
+    pub date_added: NaiveDateTime,
+    pub url: String,
+    pub title: String,
+    pub shop_name: String,
+    pub image_url: String,
+    pub price: Option<i32>,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub availability: Availability,
+    pub date_restocked: Option<NaiveDateTime>,
+    pub image_hash: Option<String>,
+    pub image_phash: Option<i64>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::booth_product)]
+#[diesel(treat_none_as_null = true)]
+pub struct ProductRowInsert<'a> {
+    pub url: &'a str,
+    pub title: &'a str,
+    pub shop_name: &'a str,
+    pub image_url: &'a str,
+    pub price: Option<i32>,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub availability: Availability,
+}
+
+#[derive(Debug, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = schema::booth_variation)]
+#[diesel(treat_none_as_null = true)]
+pub struct VariationRow {
+    pub id: i32,
+    pub product_id: i32,
+    pub name: String,
+    pub price: i32,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub availability: Availability,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::booth_variation)]
+#[diesel(treat_none_as_null = true)]
+pub struct VariationRowInsert<'a> {
+    pub product_id: i32,
+    pub name: &'a str,
+    pub price: i32,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub availability: Availability,
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::booth_availability_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct AvailabilityEventRow {
+    pub date_added: NaiveDateTime,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub availability: Availability,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::booth_availability_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct AvailabilityEventRowInsert {
+    pub product_id: i32,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub availability: Availability,
+    pub previous_availability: Option<String>,
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::booth_price_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct PriceEventRow {
+    pub date_added: NaiveDateTime,
+    pub price: Option<i32>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::booth_price_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct PriceEventRowInsert {
+    pub product_id: i32,
+    pub price: Option<i32>,
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::booth_notification)]
+#[diesel(treat_none_as_null = true)]
+pub struct NotificationRow {
+    pub date_added: NaiveDateTime,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub kind: NotificationKind,
+}
+
+#[derive(Debug, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = schema::booth_source)]
+#[diesel(treat_none_as_null = true)]
+pub struct SourceRow {
+    pub id: i32,
+    pub date_added: NaiveDateTime,
+    pub name: String,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub kind: SourceKind,
+    pub melonbooks_artist_id: Option<i32>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::booth_source)]
+#[diesel(treat_none_as_null = true)]
+pub struct SourceRowInsert<'a> {
+    pub name: &'a str,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub kind: SourceKind,
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::booth_source_follower)]
+#[diesel(treat_none_as_null = true)]
+pub struct SourceFollowerRow {
+    pub source_id: i32,
+    pub user_id: i32,
+    pub date_followed: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::booth_source_follower)]
+#[diesel(treat_none_as_null = true)]
+pub struct SourceFollowerRowInsert {
+    pub source_id: i32,
+    pub user_id: i32,
+}
+
+impl SourceRow {
+    /// The source as followed by a single user, not followed when `follower` is `None`.
+    pub fn into_domain_for(self, follower: Option<&SourceFollowerRow>) -> Source {
+        Source::new(
+            self.id,
+            self.date_added.and_utc(),
+            self.name,
+            self.kind,
+            self.melonbooks_artist_id,
+            follower.is_some(),
+            follower.map(|f| f.date_followed.and_utc())
+        )
+    }
+}
+
+impl ProductRow {
+    pub fn into_domain(self, variations: Vec<VariationRow>) -> Product {
+        let variations = variations.into_iter().map(|v| v.into_domain()).collect();
+        Product::new(self.id, self.date_added.and_utc(), self.url, self.title, self.shop_name, self.image_url, variations, self.price, self.availability)
+            .with_date_restocked(self.date_restocked.map(|d| d.and_utc()))
+            .with_image_hash(self.image_hash)
+            .with_image_phash(self.image_phash.map(|h| h as u64))
+    }
+}
+
+impl VariationRow {
+    pub fn into_domain(self) -> Variation {
+        Variation::new(self.id, self.name, self.price, self.availability)
+    }
+}
+
+impl AvailabilityEventRow {
+    pub fn into_domain(self) -> ProductHistoryEntry {
+        ProductHistoryEntry::new(self.date_added.and_utc(), ProductChange::Availability(self.availability))
+    }
+}
+
+impl PriceEventRow {
+    pub fn into_domain(self) -> ProductHistoryEntry {
+        ProductHistoryEntry::new(self.date_added.and_utc(), ProductChange::Price(self.price))
+    }
+}
+
+impl NotificationRow {
+    pub fn into_domain(self, username: String) -> ProductHistoryEntry {
+        ProductHistoryEntry::new(self.date_added.and_utc(), ProductChange::Notification { kind: self.kind, username })
+    }
+}
@@ -0,0 +1,746 @@
+use crate::domain::digital::models::circle::{Circle, CircleArgs, FollowCircleError, FollowedCircle, GetCirclesError, SetCircleNameError, UnfollowCircleError};
+use crate::domain::digital::models::product::{CreateProductArgs, CreateProductError, GetProductsError, Product, ProductHistoryEntry, Sale, UpdateProductArgs, UpdateProductError};
+use crate::domain::digital::ports::DigitalRepository;
+use crate::domain::pagination::{Page, PageRequest};
+use crate::domain::product_history::{sort_history, GetProductError};
+use crate::outbound::sqlite::digital::models::{CircleFollowerRow, CircleFollowerRowInsert, CircleRow, CircleRowInsert, NotificationRow, PriceEventRow, PriceEventRowInsert, ProductRow, ProductRowInsert, SaleEventRow, SaleEventRowInsert};
+use crate::outbound::sqlite::{schema, Sqlite};
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::r2d2::ConnectionManager;
+use diesel::sqlite::Sqlite as SqliteBackend;
+use itertools::Itertools;
+use r2d2::PooledConnection;
+use std::collections::HashMap;
+use schema::app_user::dsl as user_dsl;
+use schema::digital_circle::dsl as circle_dsl;
+use schema::digital_circle_follower::dsl as circle_follower_dsl;
+use schema::digital_notification::dsl as notification_dsl;
+use schema::digital_price_event::dsl as price_event_dsl;
+use schema::digital_product::dsl as product_dsl;
+use schema::digital_sale_event::dsl as sale_event_dsl;
+
+mod models;
+
+impl Sqlite {
+    fn get_digital_circle_row_by_id(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        circle_id: i32
+    ) -> Result<Option<CircleRow>, anyhow::Error> {
+        let circle = circle_dsl::digital_circle
+            .select(CircleRow::as_select())
+            .filter(circle_dsl::id.eq(circle_id))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get circle with id '{}'", circle_id))?;
+        Ok(circle)
+    }
+
+    /// New circles are named by their code until they are scraped.
+    fn get_or_insert_digital_circle_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        args: &CircleArgs,
+    ) -> Result<CircleRow, anyhow::Error> {
+        let circle = circle_dsl::digital_circle
+            .select(CircleRow::as_select())
+            .filter(circle_dsl::store.eq(args.store().to_string()))
+            .filter(circle_dsl::code.eq(args.code()))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get {} circle '{}'", args.store(), args.code()))?;
+        if let Some(circle) = circle {
+            return Ok(circle);
+        }
+        let circle = diesel::insert_into(circle_dsl::digital_circle)
+            .values(CircleRowInsert { store: args.store(), code: args.code(), name: args.code() })
+            .returning(CircleRow::as_returning())
+            .get_result(connection)
+            .with_context(|| format!("cannot insert {} circle '{}'", args.store(), args.code()))?;
+        Ok(circle)
+    }
+
+    fn get_digital_circle_rows(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+    ) -> Result<Vec<CircleRow>, anyhow::Error> {
+        let circles = circle_dsl::digital_circle
+            .select(CircleRow::as_select())
+            .order_by((circle_dsl::store, circle_dsl::name))
+            .get_results(connection)
+            .with_context(|| "cannot select circles")?;
+        Ok(circles)
+    }
+
+    fn filtered_digital_circle_query<'a>(
+        &self,
+        user_id: i32,
+        following: Option<bool>,
+    ) -> schema::digital_circle::BoxedQuery<'a, SqliteBackend> {
+        let followed_ids = circle_follower_dsl::digital_circle_follower
+            .filter(circle_follower_dsl::user_id.eq(user_id))
+            .select(circle_follower_dsl::circle_id);
+        match following {
+            Some(true) => circle_dsl::digital_circle.filter(circle_dsl::id.eq_any(followed_ids)).into_boxed(),
+            Some(false) => circle_dsl::digital_circle.filter(diesel::dsl::not(circle_dsl::id.eq_any(followed_ids))).into_boxed(),
+            None => circle_dsl::digital_circle.into_boxed(),
+        }
+    }
+
+    fn get_digital_circle_rows_page(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        user_id: i32,
+        following: Option<bool>,
+        page: PageRequest,
+    ) -> Result<(Vec<CircleRow>, i64), anyhow::Error> {
+        let total = self.filtered_digital_circle_query(user_id, following)
+            .count()
+            .get_result::<i64>(connection)
+            .with_context(|| "cannot count circles")?;
+        let circles = self.filtered_digital_circle_query(user_id, following)
+            .select(CircleRow::as_select())
+            .order_by((circle_dsl::store, circle_dsl::name, circle_dsl::id))
+            .limit(page.page_size() as i64)
+            .offset(page.offset())
+            .get_results(connection)
+            .with_context(|| "cannot select circles")?;
+        Ok((circles, total))
+    }
+
+    fn get_digital_circle_follower_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        circle: &CircleRow,
+        user_id: i32,
+    ) -> Result<Option<CircleFollowerRow>, anyhow::Error> {
+        let follower = circle_follower_dsl::digital_circle_follower
+            .select(CircleFollowerRow::as_select())
+            .filter(circle_follower_dsl::circle_id.eq(circle.id))
+            .filter(circle_follower_dsl::user_id.eq(user_id))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get follower '{}' of {} circle '{}'", user_id, circle.store, circle.code))?;
+        Ok(follower)
+    }
+
+    fn get_digital_circle_follower_rows(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+    ) -> Result<Vec<CircleFollowerRow>, anyhow::Error> {
+        let followers = circle_follower_dsl::digital_circle_follower
+            .select(CircleFollowerRow::as_select())
+            .get_results(connection)
+            .with_context(|| "cannot get circle followers")?;
+        Ok(followers)
+    }
+
+    fn get_digital_circle_follower_rows_by_user(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        user_id: i32,
+    ) -> Result<Vec<CircleFollowerRow>, anyhow::Error> {
+        let followers = circle_follower_dsl::digital_circle_follower
+            .select(CircleFollowerRow::as_select())
+            .filter(circle_follower_dsl::user_id.eq(user_id))
+            .get_results(connection)
+            .with_context(|| format!("cannot get circles followed by user '{}'", user_id))?;
+        Ok(followers)
+    }
+
+    fn get_digital_product_rows_page_by_circles(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        circle_ids: &[i32],
+        page: PageRequest,
+    ) -> Result<(Vec<ProductRow>, i64), anyhow::Error> {
+        let total = product_dsl::digital_product
+            .filter(product_dsl::circle_id.eq_any(circle_ids))
+            .count()
+            .get_result::<i64>(connection)
+            .with_context(|| format!("cannot count products of circles with ids {:?}", circle_ids))?;
+        let products = product_dsl::digital_product
+            .select(ProductRow::as_select())
+            .filter(product_dsl::circle_id.eq_any(circle_ids))
+            .order_by((product_dsl::date_added.desc(), product_dsl::id.desc()))
+            .limit(page.page_size() as i64)
+            .offset(page.offset())
+            .get_results(connection)
+            .with_context(|| format!("cannot get products of circles with ids {:?}", circle_ids))?;
+        Ok((products, total))
+    }
+
+    fn get_digital_product_row_by_url(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        url: &str
+    ) -> Result<Option<ProductRow>, anyhow::Error> {
+        let product = product_dsl::digital_product
+            .select(ProductRow::as_select())
+            .filter(product_dsl::url.eq(url))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get product with url '{}'", url))?;
+        Ok(product)
+    }
+
+    fn get_digital_product_row_by_id(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32
+    ) -> Result<Option<ProductRow>, anyhow::Error> {
+        let product = product_dsl::digital_product
+            .select(ProductRow::as_select())
+            .find(product_id)
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get product with id '{}'", product_id))?;
+        Ok(product)
+    }
+
+    fn update_digital_product_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product: &ProductRow,
+        args: &UpdateProductArgs,
+    ) -> Result<ProductRow, anyhow::Error> {
+        let work = args.work();
+        let sale = product.sale_price.map(|price| Sale::new(price, product.sale_end));
+        let date_discounted = match sale.is_none() && work.sale().is_some() {
+            true => Some(Utc::now().naive_utc()),
+            false => product.date_discounted,
+        };
+        if sale.as_ref() != work.sale() {
+            self.insert_digital_sale_event_row(connection, product.id, work.sale())?;
+        }
+        if product.list_price != work.list_price() {
+            self.insert_digital_price_event_row(connection, product.id, work.list_price())?;
+        }
+        let product = diesel::update(&product)
+            .set((
+                product_dsl::title.eq(work.title()),
+                product_dsl::circle_name.eq(work.circle_name()),
+                product_dsl::list_price.eq(work.list_price()),
+                product_dsl::sale_price.eq(work.sale().map(|s| s.price())),
+                product_dsl::sale_end.eq(work.sale().and_then(|s| s.end())),
+                product_dsl::date_discounted.eq(date_discounted),
+            ))
+            .returning(ProductRow::as_returning())
+            .get_result(connection)
+            .with_context(|| format!("cannot update product with url '{}'", product.url))?;
+        Ok(product)
+    }
+
+    fn insert_digital_sale_event_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+        sale: Option<&Sale>,
+    ) -> Result<(), anyhow::Error> {
+        diesel::insert_into(sale_event_dsl::digital_sale_event)
+            .values(SaleEventRowInsert { product_id, sale_price: sale.map(|s| s.price()), sale_end: sale.and_then(|s| s.end()) })
+            .execute(connection)
+            .with_context(|| format!("cannot insert sale event for product '{}'", product_id))?;
+        Ok(())
+    }
+
+    fn insert_digital_price_event_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+        list_price: i32,
+    ) -> Result<(), anyhow::Error> {
+        diesel::insert_into(price_event_dsl::digital_price_event)
+            .values(PriceEventRowInsert { product_id, list_price })
+            .execute(connection)
+            .with_context(|| format!("cannot insert price event for product '{}'", product_id))?;
+        Ok(())
+    }
+
+    fn get_digital_product_history_entries(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+    ) -> Result<Vec<ProductHistoryEntry>, anyhow::Error> {
+        let sale_events = sale_event_dsl::digital_sale_event
+            .select(SaleEventRow::as_select())
+            .filter(sale_event_dsl::product_id.eq(product_id))
+            .order_by(sale_event_dsl::id.asc())
+            .get_results(connection)
+            .with_context(|| format!("cannot get sale events for product '{}'", product_id))?;
+        let price_events = price_event_dsl::digital_price_event
+            .select(PriceEventRow::as_select())
+            .filter(price_event_dsl::product_id.eq(product_id))
+            .order_by(price_event_dsl::id.asc())
+            .get_results(connection)
+            .with_context(|| format!("cannot get price events for product '{}'", product_id))?;
+        let notifications = notification_dsl::digital_notification
+            .inner_join(user_dsl::app_user)
+            .select((NotificationRow::as_select(), user_dsl::username))
+            .filter(notification_dsl::product_id.eq(product_id))
+            .order_by(notification_dsl::id.asc())
+            .get_results::<(NotificationRow, String)>(connection)
+            .with_context(|| format!("cannot get notifications for product '{}'", product_id))?;
+        let mut history = sale_events.into_iter().map(|e| e.into_domain())
+            .chain(price_events.into_iter().map(|e| e.into_domain()))
+            .chain(notifications.into_iter().map(|(n, username)| n.into_domain(username)))
+            .collect::<Vec<_>>();
+        sort_history(&mut history);
+        Ok(history)
+    }
+}
+
+#[async_trait]
+impl DigitalRepository for Sqlite {
+    async fn follow_digital_circle(&self, user_id: i32, args: &CircleArgs) -> Result<(), FollowCircleError> {
+        let args = args.clone();
+        self.write(move |db, connection| {
+            let circle = db.get_or_insert_digital_circle_row(connection, &args)?;
+            if db.get_digital_circle_follower_row(connection, &circle, user_id)?.is_some() {
+                return Err(FollowCircleError::AlreadyFollowedError { store: circle.store, code: circle.code });
+            }
+            diesel::insert_into(circle_follower_dsl::digital_circle_follower)
+                .values(CircleFollowerRowInsert { circle_id: circle.id, user_id })
+                .execute(connection)
+                .with_context(|| format!("cannot follow {} circle '{}' for user '{}'", circle.store, circle.code, user_id))?;
+            Ok(())
+        }).await
+    }
+
+    async fn unfollow_digital_circle(&self, user_id: i32, circle_id: i32) -> Result<(), UnfollowCircleError> {
+        self.write(move |db, connection| {
+            let circle = db.get_digital_circle_row_by_id(connection, circle_id)?
+                .ok_or(UnfollowCircleError::UnknownCircle { id: circle_id })?;
+            if db.get_digital_circle_follower_row(connection, &circle, user_id)?.is_none() {
+                return Err(UnfollowCircleError::CircleNotFollowed { store: circle.store, code: circle.code });
+            }
+            diesel::delete(circle_follower_dsl::digital_circle_follower)
+                .filter(circle_follower_dsl::circle_id.eq(circle.id))
+                .filter(circle_follower_dsl::user_id.eq(user_id))
+                .execute(connection)
+                .with_context(|| format!("cannot unfollow {} circle '{}' for user '{}'", circle.store, circle.code, user_id))?;
+            Ok(())
+        }).await
+    }
+
+    async fn get_digital_circles(&self, user_id: i32) -> Result<Vec<Circle>, GetCirclesError> {
+        self.read(move |db, connection| {
+            let circle_rows = db.get_digital_circle_rows(connection)?;
+            let followers = db.get_digital_circle_follower_rows_by_user(connection, user_id)?
+                .into_iter()
+                .map(|f| (f.circle_id, f))
+                .collect::<HashMap<_, _>>();
+            let circles = circle_rows.into_iter()
+                .map(|c| {
+                    let follower = followers.get(&c.id);
+                    c.into_domain_for(follower)
+                })
+                .collect();
+            Ok(circles)
+        }).await
+    }
+
+    async fn get_digital_circles_page(&self, user_id: i32, following: Option<bool>, page: PageRequest) -> Result<Page<Circle>, GetCirclesError> {
+        self.read(move |db, connection| {
+            let (circle_rows, total) = db.get_digital_circle_rows_page(connection, user_id, following, page)?;
+            let followers = db.get_digital_circle_follower_rows_by_user(connection, user_id)?
+                .into_iter()
+                .map(|f| (f.circle_id, f))
+                .collect::<HashMap<_, _>>();
+            let circles = circle_rows.into_iter()
+                .map(|c| {
+                    let follower = followers.get(&c.id);
+                    c.into_domain_for(follower)
+                })
+                .collect();
+            Ok(Page::new(circles, page, total))
+        }).await
+    }
+
+    async fn get_followed_digital_circles(&self) -> Result<Vec<FollowedCircle>, GetCirclesError> {
+        self.read(move |db, connection| {
+            let follower_rows = db.get_digital_circle_follower_rows(connection)?;
+            let user_ids = follower_rows.iter().map(|f| f.user_id).unique().collect::<Vec<_>>();
+            let users = db.get_user_rows_by_ids(connection, &user_ids)?
+                .into_iter()
+                .map(|u| (u.id, u.into_domain()))
+                .collect::<HashMap<_, _>>();
+            let mut followers = follower_rows.into_iter().into_group_map_by(|f| f.circle_id);
+            let circles = db.get_digital_circle_rows(connection)?
+                .into_iter()
+                .filter_map(|circle| {
+                    let circle_followers = followers.remove(&circle.id)?;
+                    let first_follower = circle_followers.iter().min_by_key(|f| f.date_followed);
+                    let mut circle_followers = circle_followers.iter()
+                        .filter_map(|f| users.get(&f.user_id).cloned())
+                        .collect::<Vec<_>>();
+                    circle_followers.sort_by(|a, b| a.username().cmp(b.username()));
+                    let circle = Circle::new(circle.id, circle.date_added.and_utc(), circle.store, circle.code, circle.name, true, first_follower.map(|f| f.date_followed.and_utc()));
+                    Some(FollowedCircle::new(circle, circle_followers))
+                })
+                .collect();
+            Ok(circles)
+        }).await
+    }
+
+    async fn set_digital_circle_name(&self, circle_id: i32, name: &str) -> Result<(), SetCircleNameError> {
+        let name = name.to_owned();
+        self.write(move |db, connection| {
+            let circle = db.get_digital_circle_row_by_id(connection, circle_id)?
+                .ok_or(SetCircleNameError::UnknownCircle { id: circle_id })?;
+            diesel::update(&circle)
+                .set(circle_dsl::name.eq(&name))
+                .execute(connection)
+                .with_context(|| format!("cannot rename {} circle '{}' to '{}'", circle.store, circle.code, name))?;
+            Ok(())
+        }).await
+    }
+
+    async fn create_digital_product(&self, args: &CreateProductArgs) -> Result<Product, CreateProductError> {
+        let args = args.clone();
+        self.write(move |db, connection| {
+            let work = args.work();
+            if let Some(product_row) = db.get_digital_product_row_by_url(connection, work.url())? {
+                return Err(CreateProductError::DuplicateProduct { url: product_row.url, title: product_row.title });
+            }
+            let product = connection.transaction(|connection| -> Result<Product, anyhow::Error> {
+                let product_row = diesel::insert_into(product_dsl::digital_product)
+                    .values(ProductRowInsert {
+                        circle_id: args.circle_id(),
+                        store: args.store(),
+                        url: work.url(),
+                        title: work.title(),
+                        circle_name: work.circle_name(),
+                        image_url: work.image_url(),
+                        list_price: work.list_price(),
+                        sale_price: work.sale().map(|s| s.price()),
+                        sale_end: work.sale().and_then(|s| s.end()),
+                        date_discounted: work.sale().map(|_| Utc::now().naive_utc()),
+                    })
+                    .returning(ProductRow::as_returning())
+                    .get_result(connection)
+                    .with_context(|| format!("cannot insert product with url '{}'", work.url()))?;
+                db.insert_digital_price_event_row(connection, product_row.id, work.list_price())?;
+                if work.sale().is_some() {
+                    db.insert_digital_sale_event_row(connection, product_row.id, work.sale())?;
+                }
+                Ok(product_row.into_domain())
+            })?;
+            Ok(product)
+        }).await
+    }
+
+    async fn update_digital_product(&self, args: &UpdateProductArgs) -> Result<Product, UpdateProductError> {
+        let args = args.clone();
+        self.write(move |db, connection| {
+            let product_row = db.get_digital_product_row_by_url(connection, args.url())?
+                .ok_or_else(|| UpdateProductError::ProductMissing { url: args.url().to_owned() })?;
+            let product = connection.transaction(|connection| -> Result<Product, anyhow::Error> {
+                let product_row = db.update_digital_product_row(connection, &product_row, &args)?;
+                Ok(product_row.into_domain())
+            })?;
+            Ok(product)
+        }).await
+    }
+
+    async fn get_digital_product(&self, product_id: i32) -> Result<Product, GetProductError> {
+        self.read(move |db, connection| {
+            let product_row = db.get_digital_product_row_by_id(connection, product_id)?
+                .ok_or(GetProductError::ProductMissing { id: product_id })?;
+            Ok(product_row.into_domain())
+        }).await
+    }
+
+    async fn get_digital_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError> {
+        self.read(move |db, connection| {
+            if db.get_digital_product_row_by_id(connection, product_id)?.is_none() {
+                return Err(GetProductError::ProductMissing { id: product_id });
+            }
+            let history = db.get_digital_product_history_entries(connection, product_id)?;
+            Ok(history)
+        }).await
+    }
+
+    async fn get_digital_products(&self) -> Result<Vec<Product>, GetProductsError> {
+        self.read(move |_, connection| {
+            let products = product_dsl::digital_product
+                .select(ProductRow::as_select())
+                .order_by(product_dsl::date_added.desc())
+                .get_results(connection)
+                .with_context(|| "cannot get products")?
+                .into_iter()
+                .map(|p| p.into_domain())
+                .collect();
+            Ok(products)
+        }).await
+    }
+
+    async fn get_digital_products_by_circle(&self, circle_id: i32) -> Result<Vec<Product>, GetProductsError> {
+        self.read(move |_, connection| {
+            let products = product_dsl::digital_product
+                .select(ProductRow::as_select())
+                .filter(product_dsl::circle_id.eq(circle_id))
+                .order_by(product_dsl::date_added.desc())
+                .get_results(connection)
+                .with_context(|| format!("cannot get products of circle with id {}", circle_id))?
+                .into_iter()
+                .map(|p| p.into_domain())
+                .collect();
+            Ok(products)
+        }).await
+    }
+
+    async fn get_digital_products_page(&self, page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        self.read(move |_, connection| {
+            let total = product_dsl::digital_product
+                .count()
+                .get_result::<i64>(connection)
+                .with_context(|| "cannot count products")?;
+            let products = product_dsl::digital_product
+                .select(ProductRow::as_select())
+                .order_by((product_dsl::date_added.desc(), product_dsl::id.desc()))
+                .limit(page.page_size() as i64)
+                .offset(page.offset())
+                .get_results(connection)
+                .with_context(|| "cannot get products")?
+                .into_iter()
+                .map(|p| p.into_domain())
+                .collect();
+            Ok(Page::new(products, page, total))
+        }).await
+    }
+
+    async fn get_digital_products_page_by_circle(&self, circle_id: i32, page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        self.read(move |db, connection| {
+            let (product_rows, total) = db.get_digital_product_rows_page_by_circles(connection, &[circle_id], page)?;
+            let products = product_rows.into_iter().map(|p| p.into_domain()).collect();
+            Ok(Page::new(products, page, total))
+        }).await
+    }
+
+    async fn get_digital_products_page_by_circles(&self, circle_ids: &[i32], page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        let circle_ids = circle_ids.to_vec();
+        self.read(move |db, connection| {
+            let (product_rows, total) = db.get_digital_product_rows_page_by_circles(connection, &circle_ids, page)?;
+            let products = product_rows.into_iter().map(|p| p.into_domain()).collect();
+            Ok(Page::new(products, page, total))
+        }).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::domain::digital::models::circle::Store;
+    use crate::domain::digital::models::product::WorkData;
+    use crate::domain::digital::SITE;
+    use crate::domain::image::models::image::{CachedImage, SetProductImageError};
+    use crate::domain::product_history::{NotificationKind, ProductChange};
+    use crate::domain::site::SiteRepository;
+    use crate::domain::user::models::user::DEFAULT_USERNAME;
+    use crate::domain::user::ports::UserRepository;
+    use crate::outbound::sqlite::test_util::default_user_id;
+    use chrono::NaiveDateTime;
+
+    #[tokio::test]
+    async fn test_follow_digital_circle() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        db.follow_digital_circle(user_id, &CircleArgs::new(Store::Dlsite, "RG12345".to_owned())).await.unwrap();
+        db.follow_digital_circle(user_id, &CircleArgs::new(Store::Fanza, "12345".to_owned())).await.unwrap();
+
+        let circles = db.get_digital_circles(user_id).await.unwrap();
+        assert_eq!(circles.len(), 2);
+        assert!(circles.iter().all(|c| c.following() && c.date_followed().is_some() && c.name() == c.code()));
+        assert!(matches!(
+            db.follow_digital_circle(user_id, &CircleArgs::new(Store::Dlsite, "https://www.dlsite.com/maniax/circle/profile/=/maker_id/RG12345.html".to_owned())).await,
+            Err(FollowCircleError::AlreadyFollowedError { store: Store::Dlsite, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unfollow_digital_circle() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let users = db.setup_users(DEFAULT_USERNAME, &["alice".to_owned()]).await.unwrap();
+        let (alice, default) = (users.first().unwrap(), users.last().unwrap());
+        let args = CircleArgs::new(Store::Dlsite, "RG12345".to_owned());
+        db.follow_digital_circle(alice.id(), &args).await.unwrap();
+        db.follow_digital_circle(default.id(), &args).await.unwrap();
+        let circle = db.get_digital_circles(alice.id()).await.unwrap().into_iter().next().unwrap();
+
+        let followed = db.get_followed_digital_circles().await.unwrap();
+        assert_eq!(followed.len(), 1);
+        let usernames = followed.first().unwrap().followers().iter().map(|u| u.username()).collect::<Vec<_>>();
+        assert_eq!(usernames, vec!["alice", DEFAULT_USERNAME]);
+
+        db.unfollow_digital_circle(default.id(), circle.id()).await.unwrap();
+        assert!(db.get_digital_circles(alice.id()).await.unwrap().first().unwrap().following());
+        assert!(!db.get_digital_circles(default.id()).await.unwrap().first().unwrap().following());
+        assert!(matches!(db.unfollow_digital_circle(default.id(), circle.id()).await, Err(UnfollowCircleError::CircleNotFollowed { .. })));
+        assert!(matches!(db.unfollow_digital_circle(default.id(), circle.id() + 1).await, Err(UnfollowCircleError::UnknownCircle { .. })));
+
+        db.unfollow_digital_circle(alice.id(), circle.id()).await.unwrap();
+        assert!(db.get_followed_digital_circles().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_set_digital_circle_name() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        let circle_id = circle_id(&db).await;
+
+        db.set_digital_circle_name(circle_id, "mafuyu").await.unwrap();
+        assert_eq!(db.get_digital_circles(user_id).await.unwrap().first().unwrap().name(), "mafuyu");
+        assert!(matches!(db.set_digital_circle_name(circle_id + 1, "mafuyu").await, Err(SetCircleNameError::UnknownCircle { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_create_digital_product() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let circle_id = circle_id(&db).await;
+        let args = CreateProductArgs::new(circle_id, Store::Dlsite, work(None));
+        let product = db.create_digital_product(&args).await.unwrap();
+
+        assert_eq!(product.url(), args.work().url());
+        assert_eq!(product.circle_name(), "mafuyu");
+        assert_eq!(product.list_price(), 1100);
+        assert_eq!(product.sale(), None);
+        assert_eq!(product.date_discounted(), None);
+        assert_eq!(db.get_digital_products_by_circle(circle_id).await.unwrap(), vec![product]);
+        assert!(matches!(db.create_digital_product(&args).await, Err(CreateProductError::DuplicateProduct { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_get_digital_products_page() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        let circle_id = circle_id(&db).await;
+        db.follow_digital_circle(user_id, &CircleArgs::new(Store::Dlsite, "RG54321".to_owned())).await.unwrap();
+        let other_circle_id = db.get_digital_circles(user_id).await.unwrap().into_iter().find(|c| c.id() != circle_id).unwrap().id();
+        let mut products = Vec::new();
+        for work_id in ["RJ01234561", "RJ01234562", "RJ01234563"] {
+            products.push(db.create_digital_product(&CreateProductArgs::new(circle_id, Store::Dlsite, work_with_id(work_id, None))).await.unwrap());
+        }
+        db.create_digital_product(&CreateProductArgs::new(other_circle_id, Store::Dlsite, work_with_id("RJ01234564", None))).await.unwrap();
+
+        let page = db.get_digital_products_page(PageRequest::new(2, 3)).await.unwrap();
+        assert_eq!(page.total_items(), 4);
+        assert_eq!(page.items().len(), 1);
+        let page = db.get_digital_products_page_by_circle(circle_id, PageRequest::new(1, 2)).await.unwrap();
+        assert_eq!(page.total_items(), 3);
+        assert_eq!(page.items(), &[products[2].clone(), products[1].clone()]);
+        let page = db.get_digital_products_page_by_circle(circle_id, PageRequest::new(2, 2)).await.unwrap();
+        assert_eq!(page.items(), &[products[0].clone()]);
+    }
+
+    #[tokio::test]
+    async fn test_get_digital_products_page_by_circles() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        let circle_id = circle_id(&db).await;
+        db.follow_digital_circle(user_id, &CircleArgs::new(Store::Dlsite, "RG54321".to_owned())).await.unwrap();
+        let other_circle_id = db.get_digital_circles(user_id).await.unwrap().into_iter().find(|c| c.id() != circle_id).unwrap().id();
+        let product = db.create_digital_product(&CreateProductArgs::new(circle_id, Store::Dlsite, work_with_id("RJ01234561", None))).await.unwrap();
+        let product2 = db.create_digital_product(&CreateProductArgs::new(other_circle_id, Store::Dlsite, work_with_id("RJ01234562", None))).await.unwrap();
+
+        let page = db.get_digital_products_page_by_circles(&[circle_id, other_circle_id], PageRequest::new(1, 10)).await.unwrap();
+        assert_eq!(page.total_items(), 2);
+        assert_eq!(page.items(), &[product2.clone(), product.clone()]);
+        let page = db.get_digital_products_page_by_circles(&[circle_id, other_circle_id], PageRequest::new(2, 1)).await.unwrap();
+        assert_eq!(page.items(), &[product]);
+        assert!(db.get_digital_products_page_by_circles(&[], PageRequest::default()).await.unwrap().items().is_empty());
+
+        db.unfollow_digital_circle(user_id, other_circle_id).await.unwrap();
+        let page = db.get_digital_circles_page(user_id, Some(true), PageRequest::new(1, 10)).await.unwrap();
+        assert_eq!(page.items().iter().map(|c| c.id()).collect::<Vec<_>>(), vec![circle_id]);
+        let page = db.get_digital_circles_page(user_id, Some(false), PageRequest::new(1, 10)).await.unwrap();
+        assert_eq!(page.items().iter().map(|c| c.id()).collect::<Vec<_>>(), vec![other_circle_id]);
+        assert_eq!(db.get_digital_circles_page(user_id, None, PageRequest::new(1, 10)).await.unwrap().total_items(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_update_digital_product() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        db.create_digital_product(&CreateProductArgs::new(circle_id(&db).await, Store::Dlsite, work(None))).await.unwrap();
+
+        let end = NaiveDateTime::parse_from_str("2026-10-31 23:59:59", "%Y-%m-%d %H:%M:%S").unwrap();
+        let product = db.update_digital_product(&UpdateProductArgs::new(work(Some(Sale::new(770, Some(end)))))).await.unwrap();
+        assert_eq!(product.sale(), Some(&Sale::new(770, Some(end))));
+        assert_eq!(product.price(), 770);
+        let date_discounted = product.date_discounted();
+        assert_ne!(date_discounted, None);
+
+        let product = db.update_digital_product(&UpdateProductArgs::new(work(Some(Sale::new(550, None))))).await.unwrap();
+        assert_eq!(product.date_discounted(), date_discounted);
+        let product = db.update_digital_product(&UpdateProductArgs::new(work(None))).await.unwrap();
+        assert_eq!(product.sale(), None);
+        assert!(matches!(
+            db.update_digital_product(&UpdateProductArgs::new(WorkData::new("https://missing".to_owned(), "".to_owned(), "".to_owned(), "".to_owned(), 0, None))).await,
+            Err(UpdateProductError::ProductMissing { .. })
+        ));
+
+        db.add_notifications(SITE, user_id, NotificationKind::DiscountedProduct, &[product.id()]).await.unwrap();
+        let history = db.get_digital_product_history(product.id()).await.unwrap();
+        let prices = history.iter()
+            .filter_map(|e| match e.change() { ProductChange::Price(p) => Some(*p), _ => None })
+            .collect::<Vec<_>>();
+        assert_eq!(prices, vec![1100]);
+        let sales = history.iter()
+            .filter_map(|e| match e.change() { ProductChange::Availability(s) => Some(s.as_ref().map(|s| s.price())), _ => None })
+            .collect::<Vec<_>>();
+        assert_eq!(sales, vec![Some(770), Some(550), None]);
+        let notifications = history.iter()
+            .filter(|e| matches!(e.change(), ProductChange::Notification { .. }))
+            .map(|e| e.change().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(notifications, vec![ProductChange::Notification { kind: NotificationKind::DiscountedProduct, username: DEFAULT_USERNAME.to_owned() }]);
+        assert!(matches!(db.get_digital_product_history(product.id() + 1).await, Err(GetProductError::ProductMissing { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_set_digital_product_image() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let product = db.create_digital_product(&CreateProductArgs::new(circle_id(&db).await, Store::Dlsite, work(None))).await.unwrap();
+
+        let hash = "a".repeat(64);
+        let image = CachedImage::new(hash.clone(), Some(0xF000_0000_0000_0001));
+        db.set_product_image(SITE, product.id(), &image).await.unwrap();
+        let loaded = db.get_digital_product(product.id()).await.unwrap();
+        assert_eq!(loaded.image_hash(), Some(hash.as_str()));
+        assert_eq!(loaded.image_phash(), Some(0xF000_0000_0000_0001));
+        assert!(matches!(db.set_product_image(SITE, product.id() + 1, &image).await, Err(SetProductImageError::ProductMissing { .. })));
+    }
+
+    fn work(sale: Option<Sale>) -> WorkData {
+        work_with_id("RJ01234567", sale)
+    }
+
+    fn work_with_id(work_id: &str, sale: Option<Sale>) -> WorkData {
+        WorkData::new(
+            format!("https://www.dlsite.com/maniax/work/=/product_id/{}.html", work_id),
+            "mafuyu_title".to_owned(),
+            "mafuyu".to_owned(),
+            format!("https://img.dlsite.jp/modpub/images2/work/doujin/RJ01235000/{}_img_main.jpg", work_id),
+            1100,
+            sale
+        )
+    }
+
+    async fn circle_id(db: &Sqlite) -> i32 {
+        let user_id = default_user_id(db).await;
+        db.follow_digital_circle(user_id, &CircleArgs::new(Store::Dlsite, "RG12345".to_owned())).await.unwrap();
+        db.get_digital_circles(user_id).await.unwrap().first().unwrap().id()
+    }
+}
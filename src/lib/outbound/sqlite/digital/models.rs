@@ -0,0 +1,170 @@
+use crate::domain::digital::models::circle::{Circle, Store};
+use crate::domain::digital::models::product::{Product, ProductHistoryEntry, Sale};
+use crate::domain::product_history::{NotificationKind, ProductChange};
+use crate::outbound::sqlite::schema;
+use chrono::NaiveDateTime;
+use diesel::{Identifiable, Insertable, Queryable, Selectable};
+
+#[derive(Debug, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = schema::digital_product)]
+#[diesel(treat_none_as_null = true)]
+pub struct ProductRow {
+    pub id: i32,
+    pub date_added: NaiveDateTime,
+    pub circle_id: i32,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub store: Store,
+    pub url: String,
+    pub title: String,
+    pub circle_name: String,
+    pub image_url: String,
+    pub list_price: i32,
+    pub sale_price: Option<i32>,
+    pub sale_end: Option<NaiveDateTime>,
+    pub date_discounted: Option<NaiveDateTime>,
+    pub image_hash: Option<String>,
+    pub image_phash: Option<i64>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::digital_product)]
+#[diesel(treat_none_as_null = true)]
+pub struct ProductRowInsert<'a> {
+    pub circle_id: i32,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub store: Store,
+    pub url: &'a str,
+    pub title: &'a str,
+    pub circle_name: &'a str,
+    pub image_url: &'a str,
+    pub list_price: i32,
+    pub sale_price: Option<i32>,
+    pub sale_end: Option<NaiveDateTime>,
+    pub date_discounted: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::digital_price_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct PriceEventRow {
+    pub date_added: NaiveDateTime,
+    pub list_price: i32,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::digital_price_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct PriceEventRowInsert {
+    pub product_id: i32,
+    pub list_price: i32,
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::digital_sale_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct SaleEventRow {
+    pub date_added: NaiveDateTime,
+    pub sale_price: Option<i32>,
+    pub sale_end: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::digital_sale_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct SaleEventRowInsert {
+    pub product_id: i32,
+    pub sale_price: Option<i32>,
+    pub sale_end: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::digital_notification)]
+#[diesel(treat_none_as_null = true)]
+pub struct NotificationRow {
+    pub date_added: NaiveDateTime,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub kind: NotificationKind,
+}
+
+#[derive(Debug, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = schema::digital_circle)]
+#[diesel(treat_none_as_null = true)]
+pub struct CircleRow {
+    pub id: i32,
+    pub date_added: NaiveDateTime,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub store: Store,
+    pub code: String,
+    pub name: String,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::digital_circle)]
+#[diesel(treat_none_as_null = true)]
+pub struct CircleRowInsert<'a> {
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub store: Store,
+    pub code: &'a str,
+    pub name: &'a str,
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::digital_circle_follower)]
+#[diesel(treat_none_as_null = true)]
+pub struct CircleFollowerRow {
+    pub circle_id: i32,
+    pub user_id: i32,
+    pub date_followed: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::digital_circle_follower)]
+#[diesel(treat_none_as_null = true)]
+pub struct CircleFollowerRowInsert {
+    pub circle_id: i32,
+    pub user_id: i32,
+}
+
+impl CircleRow {
+    /// The circle as followed by a single user, not followed when `follower` is `None`.
+    pub fn into_domain_for(self, follower: Option<&CircleFollowerRow>) -> Circle {
+        Circle::new(
+            self.id,
+            self.date_added.and_utc(),
+            self.store,
+            self.code,
+            self.name,
+            follower.is_some(),
+            follower.map(|f| f.date_followed.and_utc())
+        )
+    }
+}
+
+impl ProductRow {
+    pub fn into_domain(self) -> Product {
+        let sale = self.sale_price.map(|price| Sale::new(price, self.sale_end));
+        Product::new(self.id, self.date_added.and_utc(), self.circle_id, self.store, self.url, self.title, self.circle_name, self.image_url, self.list_price, sale)
+            .with_date_discounted(self.date_discounted.map(|d| d.and_utc()))
+            .with_image_hash(self.image_hash)
+            .with_image_phash(self.image_phash.map(|h| h as u64))
+    }
+}
+
+impl PriceEventRow {
+    pub fn into_domain(self) -> ProductHistoryEntry {
+        ProductHistoryEntry::new(self.date_added.and_utc(), ProductChange::Price(self.list_price))
+    }
+}
+
+impl SaleEventRow {
+    pub fn into_domain(self) -> ProductHistoryEntry {
+        let sale = self.sale_price.map(|price| Sale::new(price, self.sale_end));
+        ProductHistoryEntry::new(self.date_added.and_utc(), ProductChange::Availability(sale))
+    }
+}
+
+impl NotificationRow {
+    pub fn into_domain(self, username: String) -> ProductHistoryEntry {
+        ProductHistoryEntry::new(self.date_added.and_utc(), ProductChange::Notification { kind: self.kind, username })
+    }
+}
@@ -0,0 +1,156 @@
+use crate::domain::duplicate::models::listing::{is_duplicate_image, GetListingsError, Listing, MAX_DUPLICATE_DISTANCE};
+use crate::domain::duplicate::ports::DuplicateRepository;
+use crate::domain::site::{find_site, SITES};
+use crate::outbound::sqlite::schema::image_phash_band::dsl as band_dsl;
+use crate::outbound::sqlite::site_schema::site_notification::dsl as notification_dsl;
+use crate::outbound::sqlite::site_schema::site_product::dsl as product_dsl;
+use crate::outbound::sqlite::Sqlite;
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use diesel::prelude::*;
+use itertools::Itertools;
+
+/// The perceptual hash is stored in 8 bands of 7 bits and one of 8 bits by the trigger of `site_product`.
+const IMAGE_PHASH_BANDS: u32 = 9;
+// two hashes differing by at most the distance have at least one equal band
+const _: () = assert!(IMAGE_PHASH_BANDS > MAX_DUPLICATE_DISTANCE);
+
+fn image_phash_bands(image_phash: u64) -> impl Iterator<Item = (i32, i32)> {
+    (0..IMAGE_PHASH_BANDS).map(move |band| {
+        let mask = if band == IMAGE_PHASH_BANDS - 1 { 0xFF } else { 0x7F };
+        (band as i32, ((image_phash >> (7 * band)) & mask) as i32)
+    })
+}
+
+#[async_trait]
+impl DuplicateRepository for Sqlite {
+    async fn get_listings_by_image(&self, image_phash: u64) -> Result<Vec<Listing>, GetListingsError> {
+        self.read(move |_, connection| {
+            let mut candidates = band_dsl::image_phash_band.into_boxed();
+            for (band, value) in image_phash_bands(image_phash) {
+                candidates = candidates.or_filter(band_dsl::band.eq(band).and(band_dsl::value.eq(value)));
+            }
+            let mut candidates = candidates
+                .select((band_dsl::site, band_dsl::product_id))
+                .distinct()
+                .load::<(String, i32)>(connection)
+                .with_context(|| "cannot load products with similar images")?;
+            candidates.sort_by_key(|(site_id, product_id)| (SITES.iter().position(|s| s.id() == site_id), *product_id));
+            let mut listings = Vec::new();
+            for (site_id, product_id) in candidates {
+                let (title, url, phash) = product_dsl::site_product
+                    .filter(product_dsl::site.eq(&site_id))
+                    .filter(product_dsl::product_id.eq(product_id))
+                    .select((product_dsl::title, product_dsl::url, product_dsl::image_phash.assume_not_null()))
+                    .first::<(String, String, i64)>(connection)
+                    .with_context(|| format!("cannot load {} product with id '{}'", site_id, product_id))?;
+                // sqlite has no popcount, so the distance of the perceptual hashes is compared here
+                if !is_duplicate_image(image_phash, phash as u64) {
+                    continue;
+                }
+                let site = find_site(&site_id).ok_or_else(|| anyhow!("unknown site '{}'", site_id))?;
+                let notified_user_ids = notification_dsl::site_notification
+                    .filter(notification_dsl::site.eq(&site_id))
+                    .filter(notification_dsl::product_id.eq(product_id))
+                    .select(notification_dsl::user_id)
+                    .distinct()
+                    .load::<i32>(connection)
+                    .with_context(|| format!("cannot load notifications of {} product with id '{}'", site_id, product_id))?
+                    .into_iter()
+                    .sorted()
+                    .collect();
+                listings.push(Listing::new(site, product_id, title, url, phash as u64, notified_user_ids));
+            }
+            Ok(listings)
+        }).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::domain::amiami;
+    use crate::domain::availability::Availability as AmiamiAvailability;
+    use crate::domain::amiami::models::product::CreateProductArgs as AmiamiCreateProductArgs;
+    use crate::domain::amiami::ports::AmiamiRepository;
+    use crate::domain::image::models::image::CachedImage;
+    use crate::domain::availability::Availability as MelonbooksAvailability;
+    use crate::domain::melonbooks::models::product::CreateProductArgs as MelonbooksCreateProductArgs;
+    use crate::domain::melonbooks;
+    use crate::domain::melonbooks::ports::MelonbooksRepository;
+    use crate::domain::product_history::NotificationKind;
+    use crate::domain::site::SiteRepository;
+    use crate::outbound::sqlite::test_util::default_user_id;
+    use chrono::NaiveDate;
+
+    const FIGURE_PHASH: u64 = 0x63638c934c5cf2e3;
+    const FIGURE_RELISTED_PHASH: u64 = 0x73638c934c1cf2e3;
+    const OTHER_PHASH: u64 = 0xbf00bfa0bf00bf41;
+
+    #[tokio::test]
+    async fn test_get_listings_by_image() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        let melonbooks_product = db.create_melonbooks_product(&melonbooks_product_args("https://mafuyu.moe")).await.unwrap();
+        db.set_product_image(melonbooks::SITE, melonbooks_product.id(), &CachedImage::new("a".repeat(64), Some(FIGURE_PHASH))).await.unwrap();
+        db.add_notifications(melonbooks::SITE, user_id, NotificationKind::NewProduct, &[melonbooks_product.id()]).await.unwrap();
+        let other_product = db.create_melonbooks_product(&melonbooks_product_args("https://kantoku.moe")).await.unwrap();
+        db.set_product_image(melonbooks::SITE, other_product.id(), &CachedImage::new("b".repeat(64), Some(OTHER_PHASH))).await.unwrap();
+        db.create_melonbooks_product(&melonbooks_product_args("https://no-image.moe")).await.unwrap();
+        let amiami_product = db.create_amiami_product(&amiami_product_args()).await.unwrap();
+        db.set_product_image(amiami::SITE, amiami_product.id(), &CachedImage::new("c".repeat(64), Some(FIGURE_RELISTED_PHASH))).await.unwrap();
+
+        let listings = db.get_listings_by_image(FIGURE_PHASH).await.unwrap();
+        assert_eq!(listings, vec![
+            Listing::new(melonbooks::SITE, melonbooks_product.id(), "title".to_owned(), "https://mafuyu.moe".to_owned(), FIGURE_PHASH, vec![user_id]),
+            Listing::new(amiami::SITE, amiami_product.id(), "figure_title".to_owned(), amiami_product.url().to_owned(), FIGURE_RELISTED_PHASH, vec![]),
+        ]);
+        let listings = db.get_listings_by_image(OTHER_PHASH).await.unwrap();
+        assert_eq!(listings.len(), 1);
+        assert!(listings[0].is_product(melonbooks::SITE, other_product.id()));
+        assert!(db.get_listings_by_image(!FIGURE_PHASH).await.unwrap().is_empty());
+        // a bit differs in each band but the last
+        let listings = db.get_listings_by_image(OTHER_PHASH ^ 0x0002_0408_1020_4081).await.unwrap();
+        assert!(listings[0].is_product(melonbooks::SITE, other_product.id()));
+
+        db.set_product_image(melonbooks::SITE, other_product.id(), &CachedImage::new("d".repeat(64), Some(!OTHER_PHASH))).await.unwrap();
+        assert!(db.get_listings_by_image(OTHER_PHASH).await.unwrap().is_empty());
+        assert_eq!(db.get_listings_by_image(!OTHER_PHASH).await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_image_phash_bands() {
+        let bands = image_phash_bands(0xFF00_0000_0000_0081).collect::<Vec<_>>();
+        assert_eq!(bands, vec![(0, 1), (1, 1), (2, 0), (3, 0), (4, 0), (5, 0), (6, 0), (7, 0), (8, 0xFF)]);
+    }
+
+    fn melonbooks_product_args(url: &str) -> MelonbooksCreateProductArgs {
+        MelonbooksCreateProductArgs::new(
+            url.to_owned(),
+            "title".to_owned(),
+            None,
+            vec!["mafuyu".to_owned()],
+            "https://mafuyu.png".to_owned(),
+            "category".to_owned(),
+            vec![],
+            vec![],
+            None,
+            MelonbooksAvailability::Available
+        )
+    }
+
+    fn amiami_product_args() -> AmiamiCreateProductArgs {
+        AmiamiCreateProductArgs::new(
+            "https://www.amiami.com/eng/detail/?gcode=FIGURE-1".to_owned(),
+            "figure_title".to_owned(),
+            "https://img.amiami.com/figure-1.jpg".to_owned(),
+            "9708".to_owned(),
+            "Good Smile Company".to_owned(),
+            20000,
+            18000,
+            NaiveDate::from_ymd_opt(2026, 3, 31).unwrap(),
+            AmiamiAvailability::Preorder
+        )
+    }
+}
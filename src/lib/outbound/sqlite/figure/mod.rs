@@ -0,0 +1,851 @@
+use crate::domain::figure::models::product::{AddProductSourceError, CreateProductArgs, CreateProductError, GetProductsError, PreorderWindow, Product, ProductHistoryEntry, SetPreorderNoticeError, UpdateProductArgs, UpdateProductError};
+use crate::domain::figure::models::source::{FollowSourceError, FollowedSource, GetSourcesError, SetSourceNameError, Source, SourceArgs, UnfollowSourceError};
+use crate::domain::figure::ports::FigureRepository;
+use crate::domain::pagination::{Page, PageRequest};
+use crate::domain::product_history::{sort_history, GetProductError};
+use crate::outbound::sqlite::figure::models::{NotificationRow, PreorderEventRow, PreorderEventRowInsert, PriceEventRow, PriceEventRowInsert, ProductRow, ProductRowInsert, SourceFollowerRow, SourceFollowerRowInsert, SourceRow, SourceRowInsert};
+use crate::outbound::sqlite::{schema, Sqlite};
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::r2d2::ConnectionManager;
+use diesel::sqlite::Sqlite as SqliteBackend;
+use itertools::Itertools;
+use r2d2::PooledConnection;
+use std::collections::HashMap;
+use schema::app_user::dsl as user_dsl;
+use schema::figure_notification::dsl as notification_dsl;
+use schema::figure_preorder_event::dsl as preorder_event_dsl;
+use schema::figure_price_event::dsl as price_event_dsl;
+use schema::figure_product::dsl as product_dsl;
+use schema::figure_product_source::dsl as product_source_dsl;
+use schema::figure_source::dsl as source_dsl;
+use schema::figure_source_follower::dsl as source_follower_dsl;
+
+mod models;
+
+impl Sqlite {
+    fn get_figure_source_row_by_id(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        source_id: i32
+    ) -> Result<Option<SourceRow>, anyhow::Error> {
+        let source = source_dsl::figure_source
+            .select(SourceRow::as_select())
+            .filter(source_dsl::id.eq(source_id))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get source with id '{}'", source_id))?;
+        Ok(source)
+    }
+
+    /// New sources are named by their code until they are scraped.
+    fn get_or_insert_figure_source_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        args: &SourceArgs,
+    ) -> Result<SourceRow, anyhow::Error> {
+        let source = source_dsl::figure_source
+            .select(SourceRow::as_select())
+            .filter(source_dsl::store.eq(args.store().to_string()))
+            .filter(source_dsl::kind.eq(args.kind().to_string()))
+            .filter(source_dsl::code.eq(args.code()))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get {} {} '{}'", args.store(), args.kind(), args.code()))?;
+        if let Some(source) = source {
+            return Ok(source);
+        }
+        let source = diesel::insert_into(source_dsl::figure_source)
+            .values(SourceRowInsert { store: args.store(), kind: args.kind(), code: args.code(), name: args.code() })
+            .returning(SourceRow::as_returning())
+            .get_result(connection)
+            .with_context(|| format!("cannot insert {} {} '{}'", args.store(), args.kind(), args.code()))?;
+        Ok(source)
+    }
+
+    fn get_figure_source_rows(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+    ) -> Result<Vec<SourceRow>, anyhow::Error> {
+        let sources = source_dsl::figure_source
+            .select(SourceRow::as_select())
+            .order_by((source_dsl::store, source_dsl::kind, source_dsl::name))
+            .get_results(connection)
+            .with_context(|| "cannot select sources")?;
+        Ok(sources)
+    }
+
+    fn filtered_figure_source_query<'a>(
+        &self,
+        user_id: i32,
+        following: Option<bool>,
+    ) -> schema::figure_source::BoxedQuery<'a, SqliteBackend> {
+        let followed_ids = source_follower_dsl::figure_source_follower
+            .filter(source_follower_dsl::user_id.eq(user_id))
+            .select(source_follower_dsl::source_id);
+        match following {
+            Some(true) => source_dsl::figure_source.filter(source_dsl::id.eq_any(followed_ids)).into_boxed(),
+            Some(false) => source_dsl::figure_source.filter(diesel::dsl::not(source_dsl::id.eq_any(followed_ids))).into_boxed(),
+            None => source_dsl::figure_source.into_boxed(),
+        }
+    }
+
+    fn get_figure_source_rows_page(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        user_id: i32,
+        following: Option<bool>,
+        page: PageRequest,
+    ) -> Result<(Vec<SourceRow>, i64), anyhow::Error> {
+        let total = self.filtered_figure_source_query(user_id, following)
+            .count()
+            .get_result::<i64>(connection)
+            .with_context(|| "cannot count sources")?;
+        let sources = self.filtered_figure_source_query(user_id, following)
+            .select(SourceRow::as_select())
+            .order_by((source_dsl::store, source_dsl::kind, source_dsl::name, source_dsl::id))
+            .limit(page.page_size() as i64)
+            .offset(page.offset())
+            .get_results(connection)
+            .with_context(|| "cannot select sources")?;
+        Ok((sources, total))
+    }
+
+    fn get_figure_source_follower_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        source: &SourceRow,
+        user_id: i32,
+    ) -> Result<Option<SourceFollowerRow>, anyhow::Error> {
+        let follower = source_follower_dsl::figure_source_follower
+            .select(SourceFollowerRow::as_select())
+            .filter(source_follower_dsl::source_id.eq(source.id))
+            .filter(source_follower_dsl::user_id.eq(user_id))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get follower '{}' of {} {} '{}'", user_id, source.store, source.kind, source.code))?;
+        Ok(follower)
+    }
+
+    fn get_figure_source_follower_rows(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+    ) -> Result<Vec<SourceFollowerRow>, anyhow::Error> {
+        let followers = source_follower_dsl::figure_source_follower
+            .select(SourceFollowerRow::as_select())
+            .get_results(connection)
+            .with_context(|| "cannot get source followers")?;
+        Ok(followers)
+    }
+
+    fn get_figure_source_follower_rows_by_user(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        user_id: i32,
+    ) -> Result<Vec<SourceFollowerRow>, anyhow::Error> {
+        let followers = source_follower_dsl::figure_source_follower
+            .select(SourceFollowerRow::as_select())
+            .filter(source_follower_dsl::user_id.eq(user_id))
+            .get_results(connection)
+            .with_context(|| format!("cannot get sources followed by user '{}'", user_id))?;
+        Ok(followers)
+    }
+
+    /// Products found for any of the sources, once each as a figure is listed by its maker and its series.
+    fn get_figure_product_rows_page_by_sources(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        source_ids: &[i32],
+        page: PageRequest,
+    ) -> Result<(Vec<ProductRow>, i64), anyhow::Error> {
+        let product_ids = || product_source_dsl::figure_product_source
+            .filter(product_source_dsl::source_id.eq_any(source_ids))
+            .select(product_source_dsl::product_id);
+        let total = product_dsl::figure_product
+            .filter(product_dsl::id.eq_any(product_ids()))
+            .count()
+            .get_result::<i64>(connection)
+            .with_context(|| format!("cannot count products of sources with ids {:?}", source_ids))?;
+        let products = product_dsl::figure_product
+            .select(ProductRow::as_select())
+            .filter(product_dsl::id.eq_any(product_ids()))
+            .order_by((product_dsl::date_added.desc(), product_dsl::id.desc()))
+            .limit(page.page_size() as i64)
+            .offset(page.offset())
+            .get_results(connection)
+            .with_context(|| format!("cannot get products of sources with ids {:?}", source_ids))?;
+        Ok((products, total))
+    }
+
+    fn get_figure_product_row_by_url(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        url: &str
+    ) -> Result<Option<ProductRow>, anyhow::Error> {
+        let product = product_dsl::figure_product
+            .select(ProductRow::as_select())
+            .filter(product_dsl::url.eq(url))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get product with url '{}'", url))?;
+        Ok(product)
+    }
+
+    fn get_figure_product_row_by_id(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32
+    ) -> Result<Option<ProductRow>, anyhow::Error> {
+        let product = product_dsl::figure_product
+            .select(ProductRow::as_select())
+            .find(product_id)
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get product with id '{}'", product_id))?;
+        Ok(product)
+    }
+
+    fn insert_figure_product_source_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+        source_id: i32,
+    ) -> Result<(), anyhow::Error> {
+        diesel::insert_or_ignore_into(product_source_dsl::figure_product_source)
+            .values((product_source_dsl::product_id.eq(product_id), product_source_dsl::source_id.eq(source_id)))
+            .execute(connection)
+            .with_context(|| format!("cannot link product '{}' to source '{}'", product_id, source_id))?;
+        Ok(())
+    }
+
+    /// A new preorder window, or one with another start, is notified again when it opens and when it closes,
+    /// a window with another end only when it closes.
+    fn update_figure_product_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product: &ProductRow,
+        args: &UpdateProductArgs,
+    ) -> Result<ProductRow, anyhow::Error> {
+        let item = args.item();
+        let preorder = product.has_preorder.then(|| PreorderWindow::new(product.preorder_start, product.preorder_end));
+        let (new_window, moved_end) = match (&preorder, item.preorder()) {
+            (None, Some(_)) => (true, true),
+            (Some(stored), Some(scraped)) => (stored.start() != scraped.start(), stored.end() != scraped.end()),
+            _ => (false, false),
+        };
+        let date_preorder_opened = match new_window {
+            true => None,
+            false => product.date_preorder_opened,
+        };
+        let date_preorder_closing = match new_window || moved_end {
+            true => None,
+            false => product.date_preorder_closing,
+        };
+        if preorder.as_ref() != item.preorder() {
+            self.insert_figure_preorder_event_row(connection, product.id, item.preorder())?;
+        }
+        if product.price != item.price() {
+            self.insert_figure_price_event_row(connection, product.id, item.price())?;
+        }
+        let product = diesel::update(&product)
+            .set((
+                product_dsl::title.eq(item.title()),
+                product_dsl::maker_name.eq(item.maker_name()),
+                product_dsl::price.eq(item.price()),
+                product_dsl::release_date.eq(item.release_date()),
+                product_dsl::has_preorder.eq(item.preorder().is_some()),
+                product_dsl::preorder_start.eq(item.preorder().and_then(|p| p.start())),
+                product_dsl::preorder_end.eq(item.preorder().and_then(|p| p.end())),
+                product_dsl::date_preorder_opened.eq(date_preorder_opened),
+                product_dsl::date_preorder_closing.eq(date_preorder_closing),
+            ))
+            .returning(ProductRow::as_returning())
+            .get_result(connection)
+            .with_context(|| format!("cannot update product with url '{}'", product.url))?;
+        Ok(product)
+    }
+
+    fn insert_figure_preorder_event_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+        preorder: Option<&PreorderWindow>,
+    ) -> Result<(), anyhow::Error> {
+        diesel::insert_into(preorder_event_dsl::figure_preorder_event)
+            .values(PreorderEventRowInsert {
+                product_id,
+                has_preorder: preorder.is_some(),
+                preorder_start: preorder.and_then(|p| p.start()),
+                preorder_end: preorder.and_then(|p| p.end()),
+            })
+            .execute(connection)
+            .with_context(|| format!("cannot insert preorder event for product '{}'", product_id))?;
+        Ok(())
+    }
+
+    fn insert_figure_price_event_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+        price: Option<i32>,
+    ) -> Result<(), anyhow::Error> {
+        diesel::insert_into(price_event_dsl::figure_price_event)
+            .values(PriceEventRowInsert { product_id, price })
+            .execute(connection)
+            .with_context(|| format!("cannot insert price event for product '{}'", product_id))?;
+        Ok(())
+    }
+
+    fn get_figure_product_history_entries(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+    ) -> Result<Vec<ProductHistoryEntry>, anyhow::Error> {
+        let preorder_events = preorder_event_dsl::figure_preorder_event
+            .select(PreorderEventRow::as_select())
+            .filter(preorder_event_dsl::product_id.eq(product_id))
+            .order_by(preorder_event_dsl::id.asc())
+            .get_results(connection)
+            .with_context(|| format!("cannot get preorder events for product '{}'", product_id))?;
+        let price_events = price_event_dsl::figure_price_event
+            .select(PriceEventRow::as_select())
+            .filter(price_event_dsl::product_id.eq(product_id))
+            .order_by(price_event_dsl::id.asc())
+            .get_results(connection)
+            .with_context(|| format!("cannot get price events for product '{}'", product_id))?;
+        let notifications = notification_dsl::figure_notification
+            .inner_join(user_dsl::app_user)
+            .select((NotificationRow::as_select(), user_dsl::username))
+            .filter(notification_dsl::product_id.eq(product_id))
+            .order_by(notification_dsl::id.asc())
+            .get_results::<(NotificationRow, String)>(connection)
+            .with_context(|| format!("cannot get notifications for product '{}'", product_id))?;
+        let mut history = preorder_events.into_iter().map(|e| e.into_domain())
+            .chain(price_events.into_iter().map(|e| e.into_domain()))
+            .chain(notifications.into_iter().map(|(n, username)| n.into_domain(username)))
+            .collect::<Vec<_>>();
+        sort_history(&mut history);
+        Ok(history)
+    }
+}
+
+#[async_trait]
+impl FigureRepository for Sqlite {
+    async fn follow_figure_source(&self, user_id: i32, args: &SourceArgs) -> Result<(), FollowSourceError> {
+        let args = args.clone();
+        self.write(move |db, connection| {
+            let source = db.get_or_insert_figure_source_row(connection, &args)?;
+            if db.get_figure_source_follower_row(connection, &source, user_id)?.is_some() {
+                return Err(FollowSourceError::AlreadyFollowedError { store: source.store, kind: source.kind, code: source.code });
+            }
+            diesel::insert_into(source_follower_dsl::figure_source_follower)
+                .values(SourceFollowerRowInsert { source_id: source.id, user_id })
+                .execute(connection)
+                .with_context(|| format!("cannot follow {} {} '{}' for user '{}'", source.store, source.kind, source.code, user_id))?;
+            Ok(())
+        }).await
+    }
+
+    async fn unfollow_figure_source(&self, user_id: i32, source_id: i32) -> Result<(), UnfollowSourceError> {
+        self.write(move |db, connection| {
+            let source = db.get_figure_source_row_by_id(connection, source_id)?
+                .ok_or(UnfollowSourceError::UnknownSource { id: source_id })?;
+            if db.get_figure_source_follower_row(connection, &source, user_id)?.is_none() {
+                return Err(UnfollowSourceError::SourceNotFollowed { store: source.store, kind: source.kind, code: source.code });
+            }
+            diesel::delete(source_follower_dsl::figure_source_follower)
+                .filter(source_follower_dsl::source_id.eq(source.id))
+                .filter(source_follower_dsl::user_id.eq(user_id))
+                .execute(connection)
+                .with_context(|| format!("cannot unfollow {} {} '{}' for user '{}'", source.store, source.kind, source.code, user_id))?;
+            Ok(())
+        }).await
+    }
+
+    async fn get_figure_sources(&self, user_id: i32) -> Result<Vec<Source>, GetSourcesError> {
+        self.read(move |db, connection| {
+            let source_rows = db.get_figure_source_rows(connection)?;
+            let followers = db.get_figure_source_follower_rows_by_user(connection, user_id)?
+                .into_iter()
+                .map(|f| (f.source_id, f))
+                .collect::<HashMap<_, _>>();
+            let sources = source_rows.into_iter()
+                .map(|s| {
+                    let follower = followers.get(&s.id);
+                    s.into_domain_for(follower)
+                })
+                .collect();
+            Ok(sources)
+        }).await
+    }
+
+    async fn get_figure_sources_page(&self, user_id: i32, following: Option<bool>, page: PageRequest) -> Result<Page<Source>, GetSourcesError> {
+        self.read(move |db, connection| {
+            let (source_rows, total) = db.get_figure_source_rows_page(connection, user_id, following, page)?;
+            let followers = db.get_figure_source_follower_rows_by_user(connection, user_id)?
+                .into_iter()
+                .map(|f| (f.source_id, f))
+                .collect::<HashMap<_, _>>();
+            let sources = source_rows.into_iter()
+                .map(|s| {
+                    let follower = followers.get(&s.id);
+                    s.into_domain_for(follower)
+                })
+                .collect();
+            Ok(Page::new(sources, page, total))
+        }).await
+    }
+
+    async fn get_followed_figure_sources(&self) -> Result<Vec<FollowedSource>, GetSourcesError> {
+        self.read(move |db, connection| {
+            let follower_rows = db.get_figure_source_follower_rows(connection)?;
+            let user_ids = follower_rows.iter().map(|f| f.user_id).unique().collect::<Vec<_>>();
+            let users = db.get_user_rows_by_ids(connection, &user_ids)?
+                .into_iter()
+                .map(|u| (u.id, u.into_domain()))
+                .collect::<HashMap<_, _>>();
+            let mut followers = follower_rows.into_iter().into_group_map_by(|f| f.source_id);
+            let sources = db.get_figure_source_rows(connection)?
+                .into_iter()
+                .filter_map(|source| {
+                    let source_followers = followers.remove(&source.id)?;
+                    let first_follower = source_followers.iter().min_by_key(|f| f.date_followed);
+                    let mut source_followers = source_followers.iter()
+                        .filter_map(|f| users.get(&f.user_id).cloned())
+                        .collect::<Vec<_>>();
+                    source_followers.sort_by(|a, b| a.username().cmp(b.username()));
+                    let source = Source::new(source.id, source.date_added.and_utc(), source.store, source.kind, source.code, source.name, true, first_follower.map(|f| f.date_followed.and_utc()));
+                    Some(FollowedSource::new(source, source_followers))
+                })
+                .collect();
+            Ok(sources)
+        }).await
+    }
+
+    async fn set_figure_source_name(&self, source_id: i32, name: &str) -> Result<(), SetSourceNameError> {
+        let name = name.to_owned();
+        self.write(move |db, connection| {
+            let source = db.get_figure_source_row_by_id(connection, source_id)?
+                .ok_or(SetSourceNameError::UnknownSource { id: source_id })?;
+            diesel::update(&source)
+                .set(source_dsl::name.eq(&name))
+                .execute(connection)
+                .with_context(|| format!("cannot rename {} {} '{}' to '{}'", source.store, source.kind, source.code, name))?;
+            Ok(())
+        }).await
+    }
+
+    async fn create_figure_product(&self, args: &CreateProductArgs) -> Result<Product, CreateProductError> {
+        let args = args.clone();
+        self.write(move |db, connection| {
+            let item = args.item();
+            if let Some(product_row) = db.get_figure_product_row_by_url(connection, item.url())? {
+                return Err(CreateProductError::DuplicateProduct { url: product_row.url, title: product_row.title });
+            }
+            let product = connection.transaction(|connection| -> Result<Product, anyhow::Error> {
+                let product_row = diesel::insert_into(product_dsl::figure_product)
+                    .values(ProductRowInsert {
+                        store: args.store(),
+                        url: item.url(),
+                        title: item.title(),
+                        maker_name: item.maker_name(),
+                        image_url: item.image_url(),
+                        price: item.price(),
+                        release_date: item.release_date(),
+                        has_preorder: item.preorder().is_some(),
+                        preorder_start: item.preorder().and_then(|p| p.start()),
+                        preorder_end: item.preorder().and_then(|p| p.end()),
+                    })
+                    .returning(ProductRow::as_returning())
+                    .get_result(connection)
+                    .with_context(|| format!("cannot insert product with url '{}'", item.url()))?;
+                db.insert_figure_price_event_row(connection, product_row.id, item.price())?;
+                if item.preorder().is_some() {
+                    db.insert_figure_preorder_event_row(connection, product_row.id, item.preorder())?;
+                }
+                db.insert_figure_product_source_row(connection, product_row.id, args.source_id())?;
+                Ok(product_row.into_domain())
+            })?;
+            Ok(product)
+        }).await
+    }
+
+    async fn update_figure_product(&self, args: &UpdateProductArgs) -> Result<Product, UpdateProductError> {
+        let args = args.clone();
+        self.write(move |db, connection| {
+            let product_row = db.get_figure_product_row_by_url(connection, args.url())?
+                .ok_or_else(|| UpdateProductError::ProductMissing { url: args.url().to_owned() })?;
+            let product = connection.transaction(|connection| -> Result<Product, anyhow::Error> {
+                let product_row = db.update_figure_product_row(connection, &product_row, &args)?;
+                Ok(product_row.into_domain())
+            })?;
+            Ok(product)
+        }).await
+    }
+
+    async fn add_figure_product_source(&self, url: &str, source_id: i32) -> Result<(), AddProductSourceError> {
+        let url = url.to_owned();
+        self.write(move |db, connection| {
+            let product_row = db.get_figure_product_row_by_url(connection, &url)?
+                .ok_or_else(|| AddProductSourceError::ProductMissing { url: url.clone() })?;
+            db.insert_figure_product_source_row(connection, product_row.id, source_id)?;
+            Ok(())
+        }).await
+    }
+
+    async fn set_figure_preorder_opened(&self, product_id: i32) -> Result<Product, SetPreorderNoticeError> {
+        self.write(move |db, connection| {
+            let product_row = db.get_figure_product_row_by_id(connection, product_id)?
+                .ok_or(SetPreorderNoticeError::ProductMissing { id: product_id })?;
+            let product_row = diesel::update(&product_row)
+                .set(product_dsl::date_preorder_opened.eq(Utc::now().naive_utc()))
+                .returning(ProductRow::as_returning())
+                .get_result(connection)
+                .with_context(|| format!("cannot set preorder opened for product with id '{}'", product_id))?;
+            Ok(product_row.into_domain())
+        }).await
+    }
+
+    async fn set_figure_preorder_closing(&self, product_id: i32) -> Result<Product, SetPreorderNoticeError> {
+        self.write(move |db, connection| {
+            let product_row = db.get_figure_product_row_by_id(connection, product_id)?
+                .ok_or(SetPreorderNoticeError::ProductMissing { id: product_id })?;
+            let product_row = diesel::update(&product_row)
+                .set(product_dsl::date_preorder_closing.eq(Utc::now().naive_utc()))
+                .returning(ProductRow::as_returning())
+                .get_result(connection)
+                .with_context(|| format!("cannot set preorder closing for product with id '{}'", product_id))?;
+            Ok(product_row.into_domain())
+        }).await
+    }
+
+    async fn get_figure_product(&self, product_id: i32) -> Result<Product, GetProductError> {
+        self.read(move |db, connection| {
+            let product_row = db.get_figure_product_row_by_id(connection, product_id)?
+                .ok_or(GetProductError::ProductMissing { id: product_id })?;
+            Ok(product_row.into_domain())
+        }).await
+    }
+
+    async fn get_figure_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError> {
+        self.read(move |db, connection| {
+            if db.get_figure_product_row_by_id(connection, product_id)?.is_none() {
+                return Err(GetProductError::ProductMissing { id: product_id });
+            }
+            let history = db.get_figure_product_history_entries(connection, product_id)?;
+            Ok(history)
+        }).await
+    }
+
+    async fn get_figure_products(&self) -> Result<Vec<Product>, GetProductsError> {
+        self.read(move |_, connection| {
+            let products = product_dsl::figure_product
+                .select(ProductRow::as_select())
+                .order_by(product_dsl::date_added.desc())
+                .get_results(connection)
+                .with_context(|| "cannot get products")?
+                .into_iter()
+                .map(|p| p.into_domain())
+                .collect();
+            Ok(products)
+        }).await
+    }
+
+    async fn get_figure_products_by_source(&self, source_id: i32) -> Result<Vec<Product>, GetProductsError> {
+        self.read(move |_, connection| {
+            let products = product_source_dsl::figure_product_source
+                .inner_join(product_dsl::figure_product)
+                .select(ProductRow::as_select())
+                .filter(product_source_dsl::source_id.eq(source_id))
+                .order_by(product_dsl::date_added.desc())
+                .get_results(connection)
+                .with_context(|| format!("cannot get products of source with id {}", source_id))?
+                .into_iter()
+                .map(|p| p.into_domain())
+                .collect();
+            Ok(products)
+        }).await
+    }
+
+    async fn get_figure_products_page(&self, page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        self.read(move |_, connection| {
+            let total = product_dsl::figure_product
+                .count()
+                .get_result::<i64>(connection)
+                .with_context(|| "cannot count products")?;
+            let products = product_dsl::figure_product
+                .select(ProductRow::as_select())
+                .order_by((product_dsl::date_added.desc(), product_dsl::id.desc()))
+                .limit(page.page_size() as i64)
+                .offset(page.offset())
+                .get_results(connection)
+                .with_context(|| "cannot get products")?
+                .into_iter()
+                .map(|p| p.into_domain())
+                .collect();
+            Ok(Page::new(products, page, total))
+        }).await
+    }
+
+    async fn get_figure_products_page_by_source(&self, source_id: i32, page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        self.read(move |db, connection| {
+            let (product_rows, total) = db.get_figure_product_rows_page_by_sources(connection, &[source_id], page)?;
+            let products = product_rows.into_iter().map(|p| p.into_domain()).collect();
+            Ok(Page::new(products, page, total))
+        }).await
+    }
+
+    async fn get_figure_products_page_by_sources(&self, source_ids: &[i32], page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        let source_ids = source_ids.to_vec();
+        self.read(move |db, connection| {
+            let (product_rows, total) = db.get_figure_product_rows_page_by_sources(connection, &source_ids, page)?;
+            let products = product_rows.into_iter().map(|p| p.into_domain()).collect();
+            Ok(Page::new(products, page, total))
+        }).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::domain::figure::models::product::ItemData;
+    use crate::domain::figure::models::source::{SourceKind, Store};
+    use crate::domain::figure::SITE;
+    use crate::domain::image::models::image::{CachedImage, SetProductImageError};
+    use crate::domain::product_history::{NotificationKind, ProductChange};
+    use crate::domain::site::SiteRepository;
+    use crate::domain::user::models::user::DEFAULT_USERNAME;
+    use crate::domain::user::ports::UserRepository;
+    use crate::outbound::sqlite::test_util::default_user_id;
+    use chrono::NaiveDateTime;
+
+    #[tokio::test]
+    async fn test_follow_figure_source() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        db.follow_figure_source(user_id, &SourceArgs::new(Store::HobbySearch, SourceKind::Maker, "123".to_owned())).await.unwrap();
+        db.follow_figure_source(user_id, &SourceArgs::new(Store::HobbySearch, SourceKind::Series, "123".to_owned())).await.unwrap();
+        db.follow_figure_source(user_id, &SourceArgs::new(Store::Gsc, SourceKind::Series, "nendoroid".to_owned())).await.unwrap();
+
+        let sources = db.get_figure_sources(user_id).await.unwrap();
+        assert_eq!(sources.len(), 3);
+        assert!(sources.iter().all(|s| s.following() && s.date_followed().is_some() && s.name() == s.code()));
+        assert!(matches!(
+            db.follow_figure_source(user_id, &SourceArgs::new(Store::HobbySearch, SourceKind::Maker, "https://www.1999.co.jp/search?maker=123".to_owned())).await,
+            Err(FollowSourceError::AlreadyFollowedError { store: Store::HobbySearch, kind: SourceKind::Maker, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unfollow_figure_source() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let users = db.setup_users(DEFAULT_USERNAME, &["alice".to_owned()]).await.unwrap();
+        let (alice, default) = (users.first().unwrap(), users.last().unwrap());
+        let args = SourceArgs::new(Store::Gsc, SourceKind::Series, "nendoroid".to_owned());
+        db.follow_figure_source(alice.id(), &args).await.unwrap();
+        db.follow_figure_source(default.id(), &args).await.unwrap();
+        let source = db.get_figure_sources(alice.id()).await.unwrap().into_iter().next().unwrap();
+
+        let followed = db.get_followed_figure_sources().await.unwrap();
+        assert_eq!(followed.len(), 1);
+        let usernames = followed.first().unwrap().followers().iter().map(|u| u.username()).collect::<Vec<_>>();
+        assert_eq!(usernames, vec!["alice", DEFAULT_USERNAME]);
+
+        db.unfollow_figure_source(default.id(), source.id()).await.unwrap();
+        assert!(db.get_figure_sources(alice.id()).await.unwrap().first().unwrap().following());
+        assert!(!db.get_figure_sources(default.id()).await.unwrap().first().unwrap().following());
+        assert!(matches!(db.unfollow_figure_source(default.id(), source.id()).await, Err(UnfollowSourceError::SourceNotFollowed { .. })));
+        assert!(matches!(db.unfollow_figure_source(default.id(), source.id() + 1).await, Err(UnfollowSourceError::UnknownSource { .. })));
+
+        db.unfollow_figure_source(alice.id(), source.id()).await.unwrap();
+        assert!(db.get_followed_figure_sources().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_set_figure_source_name() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        let source_id = source_id(&db, SourceKind::Maker).await;
+
+        db.set_figure_source_name(source_id, "Good Smile Company").await.unwrap();
+        assert_eq!(db.get_figure_sources(user_id).await.unwrap().first().unwrap().name(), "Good Smile Company");
+        assert!(matches!(db.set_figure_source_name(source_id + 1, "Max Factory").await, Err(SetSourceNameError::UnknownSource { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_create_figure_product() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let source_id = source_id(&db, SourceKind::Maker).await;
+        let args = CreateProductArgs::new(source_id, Store::Gsc, item(Some(window("2026-11-13 21:00"))));
+        let product = db.create_figure_product(&args).await.unwrap();
+
+        assert_eq!(product.url(), args.item().url());
+        assert_eq!(product.maker_name(), "Good Smile Company");
+        assert_eq!(product.price(), Some(5800));
+        assert_eq!(product.release_date(), Some("2027年03月"));
+        assert_eq!(product.preorder(), Some(&window("2026-11-13 21:00")));
+        assert_eq!(product.date_preorder_opened(), None);
+        assert_eq!(db.get_figure_products_by_source(source_id).await.unwrap(), vec![product]);
+        assert!(matches!(db.create_figure_product(&args).await, Err(CreateProductError::DuplicateProduct { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_add_figure_product_source() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let maker_id = source_id(&db, SourceKind::Maker).await;
+        let series_id = source_id(&db, SourceKind::Series).await;
+        let product = db.create_figure_product(&CreateProductArgs::new(maker_id, Store::Gsc, item(None))).await.unwrap();
+
+        db.add_figure_product_source(product.url(), series_id).await.unwrap();
+        db.add_figure_product_source(product.url(), series_id).await.unwrap();
+        assert_eq!(db.get_figure_products_by_source(series_id).await.unwrap(), vec![product]);
+        assert_eq!(db.get_figure_products().await.unwrap().len(), 1);
+        assert!(matches!(db.add_figure_product_source("https://missing", series_id).await, Err(AddProductSourceError::ProductMissing { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_get_figure_products_page() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let maker_id = source_id(&db, SourceKind::Maker).await;
+        let series_id = source_id(&db, SourceKind::Series).await;
+        let mut products = Vec::new();
+        for product_id in ["1", "2", "3"] {
+            products.push(db.create_figure_product(&CreateProductArgs::new(maker_id, Store::Gsc, item_with_id(product_id, None))).await.unwrap());
+        }
+        db.create_figure_product(&CreateProductArgs::new(series_id, Store::Gsc, item_with_id("4", None))).await.unwrap();
+
+        let page = db.get_figure_products_page(PageRequest::new(2, 3)).await.unwrap();
+        assert_eq!(page.total_items(), 4);
+        assert_eq!(page.items().len(), 1);
+        let page = db.get_figure_products_page_by_source(maker_id, PageRequest::new(1, 2)).await.unwrap();
+        assert_eq!(page.total_items(), 3);
+        assert_eq!(page.items(), &[products[2].clone(), products[1].clone()]);
+        let page = db.get_figure_products_page_by_source(maker_id, PageRequest::new(2, 2)).await.unwrap();
+        assert_eq!(page.items(), &[products[0].clone()]);
+    }
+
+    #[tokio::test]
+    async fn test_get_figure_products_page_by_sources() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        let maker_id = source_id(&db, SourceKind::Maker).await;
+        let series_id = source_id(&db, SourceKind::Series).await;
+        let product = db.create_figure_product(&CreateProductArgs::new(maker_id, Store::Gsc, item_with_id("1", None))).await.unwrap();
+        let product2 = db.create_figure_product(&CreateProductArgs::new(series_id, Store::Gsc, item_with_id("2", None))).await.unwrap();
+        db.add_figure_product_source(product.url(), series_id).await.unwrap();
+
+        let page = db.get_figure_products_page_by_sources(&[maker_id, series_id], PageRequest::new(1, 10)).await.unwrap();
+        assert_eq!(page.total_items(), 2);
+        assert_eq!(page.items(), &[product2.clone(), product.clone()]);
+        let page = db.get_figure_products_page_by_sources(&[maker_id, series_id], PageRequest::new(2, 1)).await.unwrap();
+        assert_eq!(page.items(), &[product]);
+        assert!(db.get_figure_products_page_by_sources(&[], PageRequest::default()).await.unwrap().items().is_empty());
+
+        db.unfollow_figure_source(user_id, series_id).await.unwrap();
+        let page = db.get_figure_sources_page(user_id, Some(true), PageRequest::new(1, 10)).await.unwrap();
+        assert_eq!(page.items().iter().map(|s| s.id()).collect::<Vec<_>>(), vec![maker_id]);
+        let page = db.get_figure_sources_page(user_id, Some(false), PageRequest::new(1, 10)).await.unwrap();
+        assert_eq!(page.items().iter().map(|s| s.id()).collect::<Vec<_>>(), vec![series_id]);
+        assert_eq!(db.get_figure_sources_page(user_id, None, PageRequest::new(1, 10)).await.unwrap().total_items(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_update_figure_product() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        db.create_figure_product(&CreateProductArgs::new(source_id(&db, SourceKind::Maker).await, Store::Gsc, item(None))).await.unwrap();
+
+        let product = db.update_figure_product(&UpdateProductArgs::new(item(Some(window("2026-11-13 21:00"))))).await.unwrap();
+        assert_eq!(product.preorder(), Some(&window("2026-11-13 21:00")));
+        db.set_figure_preorder_opened(product.id()).await.unwrap();
+        let product = db.set_figure_preorder_closing(product.id()).await.unwrap();
+        assert_ne!(product.date_preorder_opened(), None);
+        assert_ne!(product.date_preorder_closing(), None);
+
+        // an extended window is notified again when it closes, but not when it opens
+        let product = db.update_figure_product(&UpdateProductArgs::new(item(Some(window("2026-11-20 21:00"))))).await.unwrap();
+        assert_ne!(product.date_preorder_opened(), None);
+        assert_eq!(product.date_preorder_closing(), None);
+        let product = db.update_figure_product(&UpdateProductArgs::new(item(None))).await.unwrap();
+        assert_eq!(product.preorder(), None);
+        let product = db.update_figure_product(&UpdateProductArgs::new(item(Some(window("2027-01-15 21:00"))))).await.unwrap();
+        assert_eq!(product.date_preorder_opened(), None);
+        assert!(matches!(
+            db.update_figure_product(&UpdateProductArgs::new(ItemData::new("https://missing".to_owned(), "".to_owned(), "".to_owned(), "".to_owned(), None, None, None))).await,
+            Err(UpdateProductError::ProductMissing { .. })
+        ));
+        assert!(matches!(db.set_figure_preorder_opened(product.id() + 1).await, Err(SetPreorderNoticeError::ProductMissing { .. })));
+
+        db.add_notifications(SITE, user_id, NotificationKind::PreorderOpened, &[product.id()]).await.unwrap();
+        let history = db.get_figure_product_history(product.id()).await.unwrap();
+        let prices = history.iter()
+            .filter_map(|e| match e.change() { ProductChange::Price(p) => Some(*p), _ => None })
+            .collect::<Vec<_>>();
+        assert_eq!(prices, vec![Some(5800)]);
+        let preorder_ends = history.iter()
+            .filter_map(|e| match e.change() { ProductChange::Availability(p) => Some(p.as_ref().and_then(|p| p.end())), _ => None })
+            .collect::<Vec<_>>();
+        assert_eq!(preorder_ends, vec![Some(date("2026-11-13 21:00")), Some(date("2026-11-20 21:00")), None, Some(date("2027-01-15 21:00"))]);
+        let notifications = history.iter()
+            .filter(|e| matches!(e.change(), ProductChange::Notification { .. }))
+            .map(|e| e.change().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(notifications, vec![ProductChange::Notification { kind: NotificationKind::PreorderOpened, username: DEFAULT_USERNAME.to_owned() }]);
+        assert!(matches!(db.get_figure_product_history(product.id() + 1).await, Err(GetProductError::ProductMissing { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_set_figure_product_image() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let product = db.create_figure_product(&CreateProductArgs::new(source_id(&db, SourceKind::Maker).await, Store::Gsc, item(None))).await.unwrap();
+
+        let hash = "a".repeat(64);
+        let image = CachedImage::new(hash.clone(), Some(0xF000_0000_0000_0001));
+        db.set_product_image(SITE, product.id(), &image).await.unwrap();
+        let loaded = db.get_figure_product(product.id()).await.unwrap();
+        assert_eq!(loaded.image_hash(), Some(hash.as_str()));
+        assert_eq!(loaded.image_phash(), Some(0xF000_0000_0000_0001));
+        assert!(matches!(db.set_product_image(SITE, product.id() + 1, &image).await, Err(SetProductImageError::ProductMissing { .. })));
+    }
+
+    fn date(date: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn window(end: &str) -> PreorderWindow {
+        PreorderWindow::new(Some(date("2026-10-01 12:00")), Some(date(end)))
+    }
+
+    fn item(preorder: Option<PreorderWindow>) -> ItemData {
+        item_with_id("12345", preorder)
+    }
+
+    fn item_with_id(product_id: &str, preorder: Option<PreorderWindow>) -> ItemData {
+        ItemData::new(
+            format!("https://www.goodsmile.com/ja/product/{}", product_id),
+            "Nendoroid Mafuyu".to_owned(),
+            "Good Smile Company".to_owned(),
+            format!("https://images.goodsmile.info/cgm/images/product/{}/main.jpg", product_id),
+            Some(5800),
+            Some("2027年03月".to_owned()),
+            preorder
+        )
+    }
+
+    async fn source_id(db: &Sqlite, kind: SourceKind) -> i32 {
+        let user_id = default_user_id(db).await;
+        db.follow_figure_source(user_id, &SourceArgs::new(Store::Gsc, kind, "goodsmile".to_owned())).await.unwrap();
+        db.get_figure_sources(user_id).await.unwrap().into_iter().find(|s| s.kind() == kind).unwrap().id()
+    }
+}
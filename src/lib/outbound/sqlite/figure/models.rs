@@ -0,0 +1,183 @@
+use crate::domain::figure::models::product::{PreorderWindow, Product, ProductHistoryEntry};
+use crate::domain::figure::models::source::{Source, SourceKind, Store};
+use crate::domain::product_history::{NotificationKind, ProductChange};
+use crate::outbound::sqlite::schema;
+use chrono::NaiveDateTime;
+use diesel::{Identifiable, Insertable, Queryable, Selectable};
+
+#[derive(Debug, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = schema::figure_product)]
+#[diesel(treat_none_as_null = true)]
+pub struct ProductRow {
+    pub id: i32,
+    pub date_added: NaiveDateTime,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub store: Store,
+    pub url: String,
+    pub title: String,
+    pub maker_name: String,
+    pub image_url: String,
+    pub price: Option<i32>,
+    pub release_date: Option<String>,
+    pub has_preorder: bool,
+    pub preorder_start: Option<NaiveDateTime>,
+    pub preorder_end: Option<NaiveDateTime>,
+    pub date_preorder_opened: Option<NaiveDateTime>,
+    pub date_preorder_closing: Option<NaiveDateTime>,
+    pub image_hash: Option<String>,
+    pub image_phash: Option<i64>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::figure_product)]
+#[diesel(treat_none_as_null = true)]
+pub struct ProductRowInsert<'a> {
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub store: Store,
+    pub url: &'a str,
+    pub title: &'a str,
+    pub maker_name: &'a str,
+    pub image_url: &'a str,
+    pub price: Option<i32>,
+    pub release_date: Option<&'a str>,
+    pub has_preorder: bool,
+    pub preorder_start: Option<NaiveDateTime>,
+    pub preorder_end: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::figure_price_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct PriceEventRow {
+    pub date_added: NaiveDateTime,
+    pub price: Option<i32>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::figure_price_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct PriceEventRowInsert {
+    pub product_id: i32,
+    pub price: Option<i32>,
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::figure_preorder_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct PreorderEventRow {
+    pub date_added: NaiveDateTime,
+    pub has_preorder: bool,
+    pub preorder_start: Option<NaiveDateTime>,
+    pub preorder_end: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::figure_preorder_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct PreorderEventRowInsert {
+    pub product_id: i32,
+    pub has_preorder: bool,
+    pub preorder_start: Option<NaiveDateTime>,
+    pub preorder_end: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::figure_notification)]
+#[diesel(treat_none_as_null = true)]
+pub struct NotificationRow {
+    pub date_added: NaiveDateTime,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub kind: NotificationKind,
+}
+
+#[derive(Debug, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = schema::figure_source)]
+#[diesel(treat_none_as_null = true)]
+pub struct SourceRow {
+    pub id: i32,
+    pub date_added: NaiveDateTime,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub store: Store,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub kind: SourceKind,
+    pub code: String,
+    pub name: String,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::figure_source)]
+#[diesel(treat_none_as_null = true)]
+pub struct SourceRowInsert<'a> {
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub store: Store,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub kind: SourceKind,
+    pub code: &'a str,
+    pub name: &'a str,
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::figure_source_follower)]
+#[diesel(treat_none_as_null = true)]
+pub struct SourceFollowerRow {
+    pub source_id: i32,
+    pub user_id: i32,
+    pub date_followed: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::figure_source_follower)]
+#[diesel(treat_none_as_null = true)]
+pub struct SourceFollowerRowInsert {
+    pub source_id: i32,
+    pub user_id: i32,
+}
+
+impl SourceRow {
+    /// The source as followed by a single user, not followed when `follower` is `None`.
+    pub fn into_domain_for(self, follower: Option<&SourceFollowerRow>) -> Source {
+        Source::new(
+            self.id,
+            self.date_added.and_utc(),
+            self.store,
+            self.kind,
+            self.code,
+            self.name,
+            follower.is_some(),
+            follower.map(|f| f.date_followed.and_utc())
+        )
+    }
+}
+
+fn preorder_window(has_preorder: bool, start: Option<NaiveDateTime>, end: Option<NaiveDateTime>) -> Option<PreorderWindow> {
+    has_preorder.then(|| PreorderWindow::new(start, end))
+}
+
+impl ProductRow {
+    pub fn into_domain(self) -> Product {
+        let preorder = preorder_window(self.has_preorder, self.preorder_start, self.preorder_end);
+        Product::new(self.id, self.date_added.and_utc(), self.store, self.url, self.title, self.maker_name, self.image_url, self.price, self.release_date, preorder)
+            .with_preorder_notices(self.date_preorder_opened.map(|d| d.and_utc()), self.date_preorder_closing.map(|d| d.and_utc()))
+            .with_image_hash(self.image_hash)
+            .with_image_phash(self.image_phash.map(|h| h as u64))
+    }
+}
+
+impl PriceEventRow {
+    pub fn into_domain(self) -> ProductHistoryEntry {
+        ProductHistoryEntry::new(self.date_added.and_utc(), ProductChange::Price(self.price))
+    }
+}
+
+impl PreorderEventRow {
+    pub fn into_domain(self) -> ProductHistoryEntry {
+        let preorder = preorder_window(self.has_preorder, self.preorder_start, self.preorder_end);
+        ProductHistoryEntry::new(self.date_added.and_utc(), ProductChange::Availability(preorder))
+    }
+}
+
+impl NotificationRow {
+    pub fn into_domain(self, username: String) -> ProductHistoryEntry {
+        ProductHistoryEntry::new(self.date_added.and_utc(), ProductChange::Notification { kind: self.kind, username })
+    }
+}
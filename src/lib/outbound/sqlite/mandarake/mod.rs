@@ -0,0 +1,734 @@
+use crate::domain::availability::Availability;
+use crate::domain::mandarake::models::product::{AddProductSearchError, CreateProductArgs, CreateProductError, GetProductsError, Product, ProductHistoryEntry, UpdateProductArgs, UpdateProductError};
+use crate::domain::mandarake::models::search::{DeleteSearchError, FollowedSearch, GetSearchesError, SaveSearchError, Search, SearchArgs, SearchFollower};
+use crate::domain::mandarake::ports::MandarakeRepository;
+use crate::domain::pagination::{Page, PageRequest};
+use crate::domain::product_history::{sort_history, GetProductError};
+use crate::outbound::sqlite::mandarake::models::{AvailabilityEventRow, AvailabilityEventRowInsert, NotificationRow, PriceEventRow, PriceEventRowInsert, ProductRow, ProductRowInsert, SearchFollowerRow, SearchFollowerRowInsert, SearchRow, SearchRowInsert};
+use crate::outbound::sqlite::{schema, Sqlite};
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::r2d2::ConnectionManager;
+use itertools::Itertools;
+use r2d2::PooledConnection;
+use std::collections::HashMap;
+use schema::app_user::dsl as user_dsl;
+use schema::mandarake_availability_event::dsl as availability_event_dsl;
+use schema::mandarake_notification::dsl as notification_dsl;
+use schema::mandarake_price_event::dsl as price_event_dsl;
+use schema::mandarake_product::dsl as product_dsl;
+use schema::mandarake_product_search::dsl as product_search_dsl;
+use schema::mandarake_search::dsl as search_dsl;
+use schema::mandarake_search_follower::dsl as search_follower_dsl;
+
+mod models;
+
+impl Sqlite {
+    fn get_mandarake_search_row_by_id(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        search_id: i32
+    ) -> Result<Option<SearchRow>, anyhow::Error> {
+        let search = search_dsl::mandarake_search
+            .select(SearchRow::as_select())
+            .filter(search_dsl::id.eq(search_id))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get search with id '{}'", search_id))?;
+        Ok(search)
+    }
+
+    fn get_or_insert_mandarake_search_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        keyword: &str,
+    ) -> Result<SearchRow, anyhow::Error> {
+        let search = search_dsl::mandarake_search
+            .select(SearchRow::as_select())
+            .filter(search_dsl::keyword.eq(keyword))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get search '{}'", keyword))?;
+        if let Some(search) = search {
+            return Ok(search);
+        }
+        let search = diesel::insert_into(search_dsl::mandarake_search)
+            .values(SearchRowInsert { keyword })
+            .returning(SearchRow::as_returning())
+            .get_result(connection)
+            .with_context(|| format!("cannot insert search '{}'", keyword))?;
+        Ok(search)
+    }
+
+    fn get_mandarake_search_rows(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+    ) -> Result<Vec<SearchRow>, anyhow::Error> {
+        let searches = search_dsl::mandarake_search
+            .select(SearchRow::as_select())
+            .order_by(search_dsl::keyword)
+            .get_results(connection)
+            .with_context(|| "cannot select searches")?;
+        Ok(searches)
+    }
+
+    fn get_mandarake_saved_search_rows_page(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        user_id: i32,
+        page: PageRequest,
+    ) -> Result<(Vec<SearchRow>, i64), anyhow::Error> {
+        let saved_ids = || search_follower_dsl::mandarake_search_follower
+            .filter(search_follower_dsl::user_id.eq(user_id))
+            .select(search_follower_dsl::search_id);
+        let total = search_dsl::mandarake_search
+            .filter(search_dsl::id.eq_any(saved_ids()))
+            .count()
+            .get_result::<i64>(connection)
+            .with_context(|| format!("cannot count searches saved by user '{}'", user_id))?;
+        let searches = search_dsl::mandarake_search
+            .select(SearchRow::as_select())
+            .filter(search_dsl::id.eq_any(saved_ids()))
+            .order_by((search_dsl::keyword, search_dsl::id))
+            .limit(page.page_size() as i64)
+            .offset(page.offset())
+            .get_results(connection)
+            .with_context(|| format!("cannot select searches saved by user '{}'", user_id))?;
+        Ok((searches, total))
+    }
+
+    fn get_mandarake_search_follower_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        search: &SearchRow,
+        user_id: i32,
+    ) -> Result<Option<SearchFollowerRow>, anyhow::Error> {
+        let follower = search_follower_dsl::mandarake_search_follower
+            .select(SearchFollowerRow::as_select())
+            .filter(search_follower_dsl::search_id.eq(search.id))
+            .filter(search_follower_dsl::user_id.eq(user_id))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get follower '{}' of search '{}'", user_id, search.keyword))?;
+        Ok(follower)
+    }
+
+    fn get_mandarake_search_follower_rows(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+    ) -> Result<Vec<SearchFollowerRow>, anyhow::Error> {
+        let followers = search_follower_dsl::mandarake_search_follower
+            .select(SearchFollowerRow::as_select())
+            .get_results(connection)
+            .with_context(|| "cannot get search followers")?;
+        Ok(followers)
+    }
+
+    fn get_mandarake_search_follower_rows_by_user(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        user_id: i32,
+    ) -> Result<Vec<SearchFollowerRow>, anyhow::Error> {
+        let followers = search_follower_dsl::mandarake_search_follower
+            .select(SearchFollowerRow::as_select())
+            .filter(search_follower_dsl::user_id.eq(user_id))
+            .get_results(connection)
+            .with_context(|| format!("cannot get searches saved by user '{}'", user_id))?;
+        Ok(followers)
+    }
+
+    /// Products found by any of the searches, once each.
+    fn get_mandarake_product_rows_page_by_searches(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        search_ids: &[i32],
+        page: PageRequest,
+    ) -> Result<(Vec<ProductRow>, i64), anyhow::Error> {
+        let product_ids = || product_search_dsl::mandarake_product_search
+            .filter(product_search_dsl::search_id.eq_any(search_ids))
+            .select(product_search_dsl::product_id);
+        let total = product_dsl::mandarake_product
+            .filter(product_dsl::id.eq_any(product_ids()))
+            .count()
+            .get_result::<i64>(connection)
+            .with_context(|| format!("cannot count products of searches with ids {:?}", search_ids))?;
+        let products = product_dsl::mandarake_product
+            .select(ProductRow::as_select())
+            .filter(product_dsl::id.eq_any(product_ids()))
+            .order_by((product_dsl::date_added.desc(), product_dsl::id.desc()))
+            .limit(page.page_size() as i64)
+            .offset(page.offset())
+            .get_results(connection)
+            .with_context(|| format!("cannot get products of searches with ids {:?}", search_ids))?;
+        Ok((products, total))
+    }
+
+    fn get_mandarake_product_row_by_url(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        url: &str
+    ) -> Result<Option<ProductRow>, anyhow::Error> {
+        let product = product_dsl::mandarake_product
+            .select(ProductRow::as_select())
+            .filter(product_dsl::url.eq(url))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get product with url '{}'", url))?;
+        Ok(product)
+    }
+
+    fn get_mandarake_product_row_by_id(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32
+    ) -> Result<Option<ProductRow>, anyhow::Error> {
+        let product = product_dsl::mandarake_product
+            .select(ProductRow::as_select())
+            .find(product_id)
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get product with id '{}'", product_id))?;
+        Ok(product)
+    }
+
+    fn insert_mandarake_product_search_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+        search_id: i32,
+    ) -> Result<(), anyhow::Error> {
+        diesel::insert_or_ignore_into(product_search_dsl::mandarake_product_search)
+            .values((product_search_dsl::product_id.eq(product_id), product_search_dsl::search_id.eq(search_id)))
+            .execute(connection)
+            .with_context(|| format!("cannot link product '{}' to search '{}'", product_id, search_id))?;
+        Ok(())
+    }
+
+    fn update_mandarake_product_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product: &ProductRow,
+        args: &UpdateProductArgs,
+    ) -> Result<ProductRow, anyhow::Error> {
+        let date_restocked = match !product.availability.is_available() && args.availability().is_available() {
+            true => Some(Utc::now().naive_utc()),
+            false => product.date_restocked,
+        };
+        if product.availability != args.availability() {
+            self.insert_mandarake_availability_event_row(connection, product.id, Some(product.availability.clone()), args.availability())?;
+        }
+        if product.price != args.price() {
+            self.insert_mandarake_price_event_row(connection, product.id, args.price())?;
+        }
+        let product = diesel::update(&product)
+            .set((
+                product_dsl::price.eq(args.price()),
+                product_dsl::availability.eq(args.availability().to_string()),
+                product_dsl::date_restocked.eq(date_restocked),
+            ))
+            .returning(ProductRow::as_returning())
+            .get_result(connection)
+            .with_context(|| format!("cannot update product with url '{}'", product.url))?;
+        Ok(product)
+    }
+
+    fn insert_mandarake_availability_event_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+        previous_availability: Option<Availability>,
+        availability: Availability,
+    ) -> Result<(), anyhow::Error> {
+        diesel::insert_into(availability_event_dsl::mandarake_availability_event)
+            .values(AvailabilityEventRowInsert { product_id, availability, previous_availability: previous_availability.map(|a| a.to_string()) })
+            .execute(connection)
+            .with_context(|| format!("cannot insert availability event for product '{}'", product_id))?;
+        Ok(())
+    }
+
+    fn insert_mandarake_price_event_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+        price: i32,
+    ) -> Result<(), anyhow::Error> {
+        diesel::insert_into(price_event_dsl::mandarake_price_event)
+            .values(PriceEventRowInsert { product_id, price })
+            .execute(connection)
+            .with_context(|| format!("cannot insert price event for product '{}'", product_id))?;
+        Ok(())
+    }
+
+    fn get_mandarake_product_history_entries(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+    ) -> Result<Vec<ProductHistoryEntry>, anyhow::Error> {
+        let availability_events = availability_event_dsl::mandarake_availability_event
+            .select(AvailabilityEventRow::as_select())
+            .filter(availability_event_dsl::product_id.eq(product_id))
+            .order_by(availability_event_dsl::id.asc())
+            .get_results(connection)
+            .with_context(|| format!("cannot get availability events for product '{}'", product_id))?;
+        let price_events = price_event_dsl::mandarake_price_event
+            .select(PriceEventRow::as_select())
+            .filter(price_event_dsl::product_id.eq(product_id))
+            .order_by(price_event_dsl::id.asc())
+            .get_results(connection)
+            .with_context(|| format!("cannot get price events for product '{}'", product_id))?;
+        let notifications = notification_dsl::mandarake_notification
+            .inner_join(user_dsl::app_user)
+            .select((NotificationRow::as_select(), user_dsl::username))
+            .filter(notification_dsl::product_id.eq(product_id))
+            .order_by(notification_dsl::id.asc())
+            .get_results::<(NotificationRow, String)>(connection)
+            .with_context(|| format!("cannot get notifications for product '{}'", product_id))?;
+        let mut history = availability_events.into_iter().map(|e| e.into_domain())
+            .chain(price_events.into_iter().map(|e| e.into_domain()))
+            .chain(notifications.into_iter().map(|(n, username)| n.into_domain(username)))
+            .collect::<Vec<_>>();
+        sort_history(&mut history);
+        Ok(history)
+    }
+}
+
+#[async_trait]
+impl MandarakeRepository for Sqlite {
+    async fn save_mandarake_search(&self, user_id: i32, args: &SearchArgs) -> Result<(), SaveSearchError> {
+        let args = args.clone();
+        self.write(move |db, connection| {
+            connection.transaction(|connection| -> Result<(), anyhow::Error> {
+                let search = db.get_or_insert_mandarake_search_row(connection, args.keyword())?;
+                match db.get_mandarake_search_follower_row(connection, &search, user_id)? {
+                    Some(_) => {
+                        diesel::update(search_follower_dsl::mandarake_search_follower)
+                            .filter(search_follower_dsl::search_id.eq(search.id))
+                            .filter(search_follower_dsl::user_id.eq(user_id))
+                            .set(search_follower_dsl::max_price.eq(args.max_price()))
+                            .execute(connection)
+                            .with_context(|| format!("cannot update max price of search '{}' for user '{}'", search.keyword, user_id))?;
+                    }
+                    None => {
+                        diesel::insert_into(search_follower_dsl::mandarake_search_follower)
+                            .values(SearchFollowerRowInsert { search_id: search.id, user_id, max_price: args.max_price() })
+                            .execute(connection)
+                            .with_context(|| format!("cannot save search '{}' for user '{}'", search.keyword, user_id))?;
+                    }
+                }
+                Ok(())
+            })?;
+            Ok(())
+        }).await
+    }
+
+    async fn delete_mandarake_search(&self, user_id: i32, search_id: i32) -> Result<(), DeleteSearchError> {
+        self.write(move |db, connection| {
+            let search = db.get_mandarake_search_row_by_id(connection, search_id)?
+                .ok_or(DeleteSearchError::UnknownSearch { id: search_id })?;
+            if db.get_mandarake_search_follower_row(connection, &search, user_id)?.is_none() {
+                return Err(DeleteSearchError::SearchNotFollowed { keyword: search.keyword });
+            }
+            diesel::delete(search_follower_dsl::mandarake_search_follower)
+                .filter(search_follower_dsl::search_id.eq(search.id))
+                .filter(search_follower_dsl::user_id.eq(user_id))
+                .execute(connection)
+                .with_context(|| format!("cannot delete search '{}' for user '{}'", search.keyword, user_id))?;
+            Ok(())
+        }).await
+    }
+
+    async fn get_mandarake_searches(&self, user_id: i32) -> Result<Vec<Search>, GetSearchesError> {
+        self.read(move |db, connection| {
+            let search_rows = db.get_mandarake_search_rows(connection)?;
+            let followers = db.get_mandarake_search_follower_rows_by_user(connection, user_id)?
+                .into_iter()
+                .map(|f| (f.search_id, f))
+                .collect::<HashMap<_, _>>();
+            let searches = search_rows.into_iter()
+                .map(|s| {
+                    let follower = followers.get(&s.id);
+                    s.into_domain_for(follower)
+                })
+                .collect();
+            Ok(searches)
+        }).await
+    }
+
+    async fn get_mandarake_saved_searches_page(&self, user_id: i32, page: PageRequest) -> Result<Page<Search>, GetSearchesError> {
+        self.read(move |db, connection| {
+            let (search_rows, total) = db.get_mandarake_saved_search_rows_page(connection, user_id, page)?;
+            let followers = db.get_mandarake_search_follower_rows_by_user(connection, user_id)?
+                .into_iter()
+                .map(|f| (f.search_id, f))
+                .collect::<HashMap<_, _>>();
+            let searches = search_rows.into_iter()
+                .map(|s| {
+                    let follower = followers.get(&s.id);
+                    s.into_domain_for(follower)
+                })
+                .collect();
+            Ok(Page::new(searches, page, total))
+        }).await
+    }
+
+    async fn get_followed_mandarake_searches(&self) -> Result<Vec<FollowedSearch>, GetSearchesError> {
+        self.read(move |db, connection| {
+            let follower_rows = db.get_mandarake_search_follower_rows(connection)?;
+            let user_ids = follower_rows.iter().map(|f| f.user_id).unique().collect::<Vec<_>>();
+            let users = db.get_user_rows_by_ids(connection, &user_ids)?
+                .into_iter()
+                .map(|u| (u.id, u.into_domain()))
+                .collect::<HashMap<_, _>>();
+            let mut followers = follower_rows.into_iter().into_group_map_by(|f| f.search_id);
+            let searches = db.get_mandarake_search_rows(connection)?
+                .into_iter()
+                .filter_map(|search| {
+                    let search_followers = followers.remove(&search.id)?;
+                    let first_follower = search_followers.iter().min_by_key(|f| f.date_followed);
+                    let mut search_followers = search_followers.iter()
+                        .filter_map(|f| users.get(&f.user_id).map(|u| SearchFollower::new(u.clone(), f.max_price)))
+                        .collect::<Vec<_>>();
+                    search_followers.sort_by(|a, b| a.user().username().cmp(b.user().username()));
+                    // the max price of a followed search is per follower, the search itself has none
+                    let search = Search::new(search.id, search.date_added.and_utc(), search.keyword, true, None, first_follower.map(|f| f.date_followed.and_utc()));
+                    Some(FollowedSearch::new(search, search_followers))
+                })
+                .collect();
+            Ok(searches)
+        }).await
+    }
+
+    async fn create_mandarake_product(&self, args: &CreateProductArgs) -> Result<Product, CreateProductError> {
+        let args = args.clone();
+        self.write(move |db, connection| {
+            if let Some(product_row) = db.get_mandarake_product_row_by_url(connection, args.url())? {
+                return Err(CreateProductError::DuplicateProduct { url: product_row.url, title: product_row.title });
+            }
+            let product = connection.transaction(|connection| -> Result<Product, anyhow::Error> {
+                let product_row = diesel::insert_into(product_dsl::mandarake_product)
+                    .values(ProductRowInsert {
+                        url: args.url(),
+                        title: args.title(),
+                        image_url: args.image_url(),
+                        store: args.store(),
+                        condition: args.condition(),
+                        price: args.price(),
+                        availability: args.availability(),
+                    })
+                    .returning(ProductRow::as_returning())
+                    .get_result(connection)
+                    .with_context(|| format!("cannot insert product with url '{}'", args.url()))?;
+                db.insert_mandarake_availability_event_row(connection, product_row.id, None, args.availability())?;
+                db.insert_mandarake_price_event_row(connection, product_row.id, args.price())?;
+                db.insert_mandarake_product_search_row(connection, product_row.id, args.search_id())?;
+                Ok(product_row.into_domain())
+            })?;
+            Ok(product)
+        }).await
+    }
+
+    async fn update_mandarake_product(&self, args: &UpdateProductArgs) -> Result<Product, UpdateProductError> {
+        let args = args.clone();
+        self.write(move |db, connection| {
+            let product_row = db.get_mandarake_product_row_by_url(connection, args.url())?
+                .ok_or_else(|| UpdateProductError::ProductMissing { url: args.url().to_owned() })?;
+            let product_row = connection.transaction(|connection| db.update_mandarake_product_row(connection, &product_row, &args))?;
+            Ok(product_row.into_domain())
+        }).await
+    }
+
+    async fn add_mandarake_product_search(&self, url: &str, search_id: i32) -> Result<(), AddProductSearchError> {
+        let url = url.to_owned();
+        self.write(move |db, connection| {
+            let product_row = db.get_mandarake_product_row_by_url(connection, &url)?
+                .ok_or_else(|| AddProductSearchError::ProductMissing { url: url.clone() })?;
+            db.insert_mandarake_product_search_row(connection, product_row.id, search_id)?;
+            Ok(())
+        }).await
+    }
+
+    async fn get_mandarake_product(&self, product_id: i32) -> Result<Product, GetProductError> {
+        self.read(move |db, connection| {
+            let product_row = db.get_mandarake_product_row_by_id(connection, product_id)?
+                .ok_or(GetProductError::ProductMissing { id: product_id })?;
+            Ok(product_row.into_domain())
+        }).await
+    }
+
+    async fn get_mandarake_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError> {
+        self.read(move |db, connection| {
+            if db.get_mandarake_product_row_by_id(connection, product_id)?.is_none() {
+                return Err(GetProductError::ProductMissing { id: product_id });
+            }
+            let history = db.get_mandarake_product_history_entries(connection, product_id)?;
+            Ok(history)
+        }).await
+    }
+
+    async fn get_mandarake_products(&self) -> Result<Vec<Product>, GetProductsError> {
+        self.read(move |_, connection| {
+            let products = product_dsl::mandarake_product
+                .select(ProductRow::as_select())
+                .order_by(product_dsl::date_added.desc())
+                .get_results(connection)
+                .with_context(|| "cannot get products")?
+                .into_iter()
+                .map(|p| p.into_domain())
+                .collect();
+            Ok(products)
+        }).await
+    }
+
+    async fn get_mandarake_products_by_search(&self, search_id: i32) -> Result<Vec<Product>, GetProductsError> {
+        self.read(move |_, connection| {
+            let products = product_search_dsl::mandarake_product_search
+                .inner_join(product_dsl::mandarake_product)
+                .select(ProductRow::as_select())
+                .filter(product_search_dsl::search_id.eq(search_id))
+                .order_by(product_dsl::date_added.desc())
+                .get_results(connection)
+                .with_context(|| format!("cannot get products of search with id {}", search_id))?
+                .into_iter()
+                .map(|p| p.into_domain())
+                .collect();
+            Ok(products)
+        }).await
+    }
+
+    async fn get_mandarake_products_page(&self, page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        self.read(move |_, connection| {
+            let total = product_dsl::mandarake_product
+                .count()
+                .get_result::<i64>(connection)
+                .with_context(|| "cannot count products")?;
+            let products = product_dsl::mandarake_product
+                .select(ProductRow::as_select())
+                .order_by((product_dsl::date_added.desc(), product_dsl::id.desc()))
+                .limit(page.page_size() as i64)
+                .offset(page.offset())
+                .get_results(connection)
+                .with_context(|| "cannot get products")?
+                .into_iter()
+                .map(|p| p.into_domain())
+                .collect();
+            Ok(Page::new(products, page, total))
+        }).await
+    }
+
+    async fn get_mandarake_products_page_by_search(&self, search_id: i32, page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        self.read(move |db, connection| {
+            let (product_rows, total) = db.get_mandarake_product_rows_page_by_searches(connection, &[search_id], page)?;
+            let products = product_rows.into_iter().map(|p| p.into_domain()).collect();
+            Ok(Page::new(products, page, total))
+        }).await
+    }
+
+    async fn get_mandarake_products_page_by_searches(&self, search_ids: &[i32], page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        let search_ids = search_ids.to_vec();
+        self.read(move |db, connection| {
+            let (product_rows, total) = db.get_mandarake_product_rows_page_by_searches(connection, &search_ids, page)?;
+            let products = product_rows.into_iter().map(|p| p.into_domain()).collect();
+            Ok(Page::new(products, page, total))
+        }).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::domain::image::models::image::{CachedImage, SetProductImageError};
+    use crate::domain::mandarake::models::product::ListingData;
+    use crate::domain::mandarake::SITE;
+    use crate::domain::product_history::{NotificationKind, ProductChange};
+    use crate::domain::site::SiteRepository;
+    use crate::domain::user::models::user::DEFAULT_USERNAME;
+    use crate::domain::user::ports::UserRepository;
+    use crate::outbound::sqlite::test_util::default_user_id;
+
+    #[tokio::test]
+    async fn test_save_mandarake_search() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        db.save_mandarake_search(user_id, &SearchArgs::new("mafuyu".to_owned(), Some(3000))).await.unwrap();
+
+        let searches = db.get_mandarake_searches(user_id).await.unwrap();
+        assert_eq!(searches.len(), 1);
+        let search = searches.first().unwrap();
+        assert_eq!(search.keyword(), "mafuyu");
+        assert!(search.following());
+        assert_eq!(search.max_price(), Some(3000));
+
+        db.save_mandarake_search(user_id, &SearchArgs::new("mafuyu".to_owned(), None)).await.unwrap();
+        let searches = db.get_mandarake_searches(user_id).await.unwrap();
+        assert_eq!(searches.len(), 1);
+        assert_eq!(searches.first().unwrap().max_price(), None);
+    }
+
+    #[tokio::test]
+    async fn test_delete_mandarake_search() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let users = db.setup_users(DEFAULT_USERNAME, &["alice".to_owned()]).await.unwrap();
+        let (alice, default) = (users.first().unwrap(), users.last().unwrap());
+        db.save_mandarake_search(alice.id(), &SearchArgs::new("mafuyu".to_owned(), Some(3000))).await.unwrap();
+        db.save_mandarake_search(default.id(), &SearchArgs::new("mafuyu".to_owned(), Some(5000))).await.unwrap();
+        let search = db.get_mandarake_searches(alice.id()).await.unwrap().into_iter().next().unwrap();
+
+        let followed = db.get_followed_mandarake_searches().await.unwrap();
+        assert_eq!(followed.len(), 1);
+        let max_prices = followed.first().unwrap().followers().iter().map(|f| (f.user().username(), f.max_price())).collect::<Vec<_>>();
+        assert_eq!(max_prices, vec![("alice", Some(3000)), (DEFAULT_USERNAME, Some(5000))]);
+
+        db.delete_mandarake_search(default.id(), search.id()).await.unwrap();
+        assert!(db.get_mandarake_searches(alice.id()).await.unwrap().first().unwrap().following());
+        assert!(!db.get_mandarake_searches(default.id()).await.unwrap().first().unwrap().following());
+        assert!(matches!(db.delete_mandarake_search(default.id(), search.id()).await, Err(DeleteSearchError::SearchNotFollowed { .. })));
+        assert!(matches!(db.delete_mandarake_search(default.id(), search.id() + 1).await, Err(DeleteSearchError::UnknownSearch { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_create_mandarake_product() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let search_id = search_id(&db).await;
+        let args = CreateProductArgs::new(search_id, listing("1234", 2500));
+        let product = db.create_mandarake_product(&args).await.unwrap();
+
+        assert_eq!(product.url(), args.url());
+        assert_eq!(product.store(), "中野店");
+        assert_eq!(product.condition(), "B");
+        assert_eq!(product.price(), 2500);
+        assert_eq!(db.get_mandarake_products_by_search(search_id).await.unwrap(), vec![product]);
+        assert!(matches!(db.create_mandarake_product(&args).await, Err(CreateProductError::DuplicateProduct { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_add_mandarake_product_search() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        let search_id = search_id(&db).await;
+        db.save_mandarake_search(user_id, &SearchArgs::new("kantoku".to_owned(), None)).await.unwrap();
+        let other_search_id = db.get_mandarake_searches(user_id).await.unwrap().into_iter().find(|s| s.keyword() == "kantoku").unwrap().id();
+        let product = db.create_mandarake_product(&CreateProductArgs::new(search_id, listing("1234", 2500))).await.unwrap();
+
+        db.add_mandarake_product_search(product.url(), other_search_id).await.unwrap();
+        db.add_mandarake_product_search(product.url(), other_search_id).await.unwrap();
+        assert_eq!(db.get_mandarake_products_by_search(other_search_id).await.unwrap(), vec![product]);
+        assert_eq!(db.get_mandarake_products().await.unwrap().len(), 1);
+        assert!(matches!(db.add_mandarake_product_search("https://missing", other_search_id).await, Err(AddProductSearchError::ProductMissing { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_get_mandarake_products_page() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        let search_id = search_id(&db).await;
+        db.save_mandarake_search(user_id, &SearchArgs::new("kantoku".to_owned(), None)).await.unwrap();
+        let other_search_id = db.get_mandarake_searches(user_id).await.unwrap().into_iter().find(|s| s.keyword() == "kantoku").unwrap().id();
+        let mut products = Vec::new();
+        for item_code in ["1", "2", "3"] {
+            products.push(db.create_mandarake_product(&CreateProductArgs::new(search_id, listing(item_code, 2500))).await.unwrap());
+        }
+        db.create_mandarake_product(&CreateProductArgs::new(other_search_id, listing("4", 2500))).await.unwrap();
+
+        let page = db.get_mandarake_products_page(PageRequest::new(2, 3)).await.unwrap();
+        assert_eq!(page.total_items(), 4);
+        assert_eq!(page.items().len(), 1);
+        let page = db.get_mandarake_products_page_by_search(search_id, PageRequest::new(1, 2)).await.unwrap();
+        assert_eq!(page.total_items(), 3);
+        assert_eq!(page.items(), &[products[2].clone(), products[1].clone()]);
+        let page = db.get_mandarake_products_page_by_search(search_id, PageRequest::new(2, 2)).await.unwrap();
+        assert_eq!(page.items(), &[products[0].clone()]);
+    }
+
+    #[tokio::test]
+    async fn test_get_mandarake_products_page_by_searches() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        let search_id = search_id(&db).await;
+        db.save_mandarake_search(user_id, &SearchArgs::new("kantoku".to_owned(), None)).await.unwrap();
+        let other_search_id = db.get_mandarake_searches(user_id).await.unwrap().into_iter().find(|s| s.keyword() == "kantoku").unwrap().id();
+        let product = db.create_mandarake_product(&CreateProductArgs::new(search_id, listing("1", 2500))).await.unwrap();
+        let product2 = db.create_mandarake_product(&CreateProductArgs::new(other_search_id, listing("2", 2500))).await.unwrap();
+        db.add_mandarake_product_search(product.url(), other_search_id).await.unwrap();
+
+        let page = db.get_mandarake_products_page_by_searches(&[search_id, other_search_id], PageRequest::new(1, 10)).await.unwrap();
+        assert_eq!(page.total_items(), 2);
+        assert_eq!(page.items(), &[product2.clone(), product.clone()]);
+        let page = db.get_mandarake_products_page_by_searches(&[search_id, other_search_id], PageRequest::new(2, 1)).await.unwrap();
+        assert_eq!(page.items(), &[product]);
+        assert!(db.get_mandarake_products_page_by_searches(&[], PageRequest::default()).await.unwrap().items().is_empty());
+
+        db.delete_mandarake_search(user_id, search_id).await.unwrap();
+        let page = db.get_mandarake_saved_searches_page(user_id, PageRequest::new(1, 10)).await.unwrap();
+        assert_eq!(page.total_items(), 1);
+        assert_eq!(page.items().iter().map(|s| (s.keyword(), s.following())).collect::<Vec<_>>(), vec![("kantoku", true)]);
+    }
+
+    #[tokio::test]
+    async fn test_update_mandarake_product() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        let product = db.create_mandarake_product(&CreateProductArgs::new(search_id(&db).await, listing("1234", 2500))).await.unwrap();
+
+        let product = db.update_mandarake_product(&UpdateProductArgs::new(product.url().to_owned(), 2000, Availability::NotAvailable)).await.unwrap();
+        assert_eq!(product.price(), 2000);
+        assert_eq!(product.availability(), Availability::NotAvailable);
+        assert_eq!(product.date_restocked(), None);
+        let product = db.update_mandarake_product(&UpdateProductArgs::new(product.url().to_owned(), 2000, Availability::Available)).await.unwrap();
+        assert_ne!(product.date_restocked(), None);
+        assert!(matches!(db.update_mandarake_product(&UpdateProductArgs::new("https://missing".to_owned(), 0, Availability::Available)).await, Err(UpdateProductError::ProductMissing { .. })));
+
+        db.add_notifications(SITE, user_id, NotificationKind::NewProduct, &[product.id()]).await.unwrap();
+        let history = db.get_mandarake_product_history(product.id()).await.unwrap();
+        let prices = history.iter()
+            .filter_map(|e| match e.change() { ProductChange::Price(p) => Some(*p), _ => None })
+            .collect::<Vec<_>>();
+        assert_eq!(prices, vec![2500, 2000]);
+        let availabilities = history.iter()
+            .filter_map(|e| match e.change() { ProductChange::Availability(a) => Some(a.clone()), _ => None })
+            .collect::<Vec<_>>();
+        assert_eq!(availabilities, vec![Availability::Available, Availability::NotAvailable, Availability::Available]);
+        assert!(history.iter().any(|e| matches!(e.change(), ProductChange::Notification { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_set_mandarake_product_image() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let product = db.create_mandarake_product(&CreateProductArgs::new(search_id(&db).await, listing("1234", 2500))).await.unwrap();
+
+        let hash = "a".repeat(64);
+        let image = CachedImage::new(hash.clone(), Some(0xF000_0000_0000_0001));
+        db.set_product_image(SITE, product.id(), &image).await.unwrap();
+        let loaded = db.get_mandarake_product(product.id()).await.unwrap();
+        assert_eq!(loaded.image_hash(), Some(hash.as_str()));
+        assert_eq!(loaded.image_phash(), Some(0xF000_0000_0000_0001));
+        assert!(matches!(db.set_product_image(SITE, product.id() + 1, &image).await, Err(SetProductImageError::ProductMissing { .. })));
+    }
+
+    fn listing(item_code: &str, price: i32) -> ListingData {
+        ListingData::new(
+            format!("https://order.mandarake.co.jp/order/detailPage/item?itemCode={}", item_code),
+            "mafuyu_title".to_owned(),
+            format!("https://img.mandarake.co.jp/webshopimg/{}.jpg", item_code),
+            "中野店".to_owned(),
+            "B".to_owned(),
+            price,
+            Availability::Available
+        )
+    }
+
+    async fn search_id(db: &Sqlite) -> i32 {
+        let user_id = default_user_id(db).await;
+        db.save_mandarake_search(user_id, &SearchArgs::new("mafuyu".to_owned(), None)).await.unwrap();
+        db.get_mandarake_searches(user_id).await.unwrap().into_iter().find(|s| s.keyword() == "mafuyu").unwrap().id()
+    }
+}
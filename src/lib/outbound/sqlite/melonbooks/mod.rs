@@ -1,19 +1,35 @@
-use crate::domain::melonbooks::models::artist::{Artist, ArtistArgs, FollowArtistError, GetArtistsError, UnfollowArtistError};
-use crate::domain::melonbooks::models::availability::Availability;
-use crate::domain::melonbooks::models::product::{AddSkippingUrlError, AddTitleSkipSequenceError, CreateProductArgs, CreateProductError, DeleteTitleSkipSequenceError, GetProductsError, GetSkippingUrlsError, GetTitleSkipSequencesError, Product, UpdateProductArgs, UpdateProductError};
+use crate::domain::melonbooks::models::artist::{Artist, ArtistArgs, FollowArtistError, FollowedArtist, GetArtistsError, SetArtistScheduleError, UnfollowArtistError};
+use crate::domain::availability::Availability;
+use crate::domain::melonbooks::models::product::{AddSkippingUrlError, AddTitleSkipSequenceError, CreateProductArgs, CreateProductError, DeleteTitleSkipSequenceError, GetAvailabilityStatsError, GetCategoriesError, GetFlagsError, GetProductsError, GetSkippingUrlsError, GetTitleSkipSequencesError, Product, ProductHistoryEntry, UpdateProductArgs, UpdateProductError};
+use crate::domain::melonbooks::models::query::{ProductQuery, ProductSort};
 use crate::domain::melonbooks::ports::MelonbooksRepository;
-use crate::outbound::sqlite::melonbooks::models::{ArtistRow, ArtistRowInsert, CategoryRow, CategoryRowInsert, FlagRow, FlagRowInsert, ProductRow, ProductRowInsert, SkipProductArtistRowInsert, SkipProductRow, SkipProductRowInsert, TagRow, TagRowInsert, TitleSkipSequenceRow, TitleSkipSequenceRowInsert};
+use crate::domain::availability_stats::AvailabilityEvent;
+use crate::domain::pagination::{Page, PageRequest, SortDirection};
+use crate::domain::product_history::{sort_history, GetProductError};
+use crate::domain::schedule::{GetTargetSchedulesError, Schedule, SetDateScrapedError};
+use crate::domain::search::{FieldHighlight, SearchProductsError, SearchResult};
+use crate::outbound::sqlite::melonbooks::models::{ArtistFollowerRow, ArtistFollowerRowInsert, ArtistRow, ArtistRowInsert, AvailabilityEventRow, AvailabilityEventRowInsert, CategoryRow, CategoryRowInsert, FlagRow, FlagRowInsert, NotificationRow, PriceEventRow, PriceEventRowInsert, ProductRow, ProductRowInsert, ProductSearchRow, SkipProductArtistRowInsert, SkipProductRow, SkipProductRowInsert, TagRow, TagRowInsert, TitleSkipSequenceRow, TitleSkipSequenceRowInsert};
+use crate::outbound::sqlite::search::{count_matches, match_expression, parse_highlight, MAX_SEARCH_RESULTS};
 use crate::outbound::sqlite::{schema, Sqlite};
 use anyhow::Context;
 use async_trait::async_trait;
-use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono::{DateTime, Days, NaiveDateTime, NaiveTime, Utc};
+use diesel::dsl::sql;
 use diesel::prelude::*;
 use diesel::r2d2::ConnectionManager;
-use diesel::sql_types::Text;
+use diesel::sql_types::{BigInt, Integer, Nullable, Text, Timestamp};
+use diesel::sqlite::Sqlite as SqliteBackend;
+use itertools::Itertools;
 use r2d2::PooledConnection;
+use std::collections::HashMap;
+use schema::app_user::dsl as user_dsl;
 use schema::melonbooks_artist::dsl as artist_dsl;
+use schema::melonbooks_artist_follower::dsl as artist_follower_dsl;
+use schema::melonbooks_availability_event::dsl as availability_event_dsl;
 use schema::melonbooks_category::dsl as category_dsl;
 use schema::melonbooks_flag::dsl as flag_dsl;
+use schema::melonbooks_notification::dsl as notification_dsl;
+use schema::melonbooks_price_event::dsl as price_event_dsl;
 use schema::melonbooks_product::dsl as product_dsl;
 use schema::melonbooks_product_artist::dsl as product_artist_dsl;
 use schema::melonbooks_product_flag::dsl as product_flag_dsl;
@@ -25,6 +41,12 @@ use schema::melonbooks_title_skip_sequence::dsl as title_skip_dsl;
 
 mod models;
 
+/// Keeps the `IN (...)` lists of a batch below SQLite's limit of 999 bound variables in older versions.
+const LOAD_BATCH_SIZE: usize = 500;
+const DATE_CHANGED_SORT_EXPRESSION: &str = "COALESCE(melonbooks_product.date_restocked, melonbooks_product.date_added)";
+/// Melonbooks prices are stored as displayed, e.g. "¥ 3,960".
+const PRICE_SORT_EXPRESSION: &str = "CAST(REPLACE(REPLACE(REPLACE(melonbooks_product.price, '¥', ''), ',', ''), ' ', '') AS INTEGER)";
+
 impl Sqlite {
     fn get_artist_row_by_id(
         &self,
@@ -58,11 +80,9 @@ impl Sqlite {
         &self,
         connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
         artist_args: &ArtistArgs,
-        following: bool,
-        date_followed: Option<DateTime<Utc>>,
     ) -> Result<ArtistRow, anyhow::Error> {
         let artist = diesel::insert_into(artist_dsl::melonbooks_artist)
-            .values(ArtistRowInsert { name: artist_args.name(), following, date_followed: date_followed.map(|d| d.naive_utc()) })
+            .values(ArtistRowInsert { name: artist_args.name(), following: false, date_followed: None })
             .returning(ArtistRow::as_returning())
             .get_result(connection)
             .with_context(|| format!("cannot insert artist with name '{}'", artist_args.name()))?;
@@ -82,30 +102,141 @@ impl Sqlite {
         Ok(())
     }
 
-    fn update_artist_row_follow(
+    fn get_artist_follower_row(
         &self,
         connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
         artist: &ArtistRow,
-    ) -> Result<ArtistRow, anyhow::Error> {
-        let artist = diesel::update(&artist)
-            .set((artist_dsl::following.eq(true), artist_dsl::date_followed.eq(Some(Utc::now().naive_utc()))))
-            .returning(ArtistRow::as_returning())
-            .get_result(connection)
-            .with_context(|| format!("cannot update artist with name '{}' to followed", artist.name))?;
-        Ok(artist)
+        user_id: i32,
+    ) -> Result<Option<ArtistFollowerRow>, anyhow::Error> {
+        let follower = artist_follower_dsl::melonbooks_artist_follower
+            .select(ArtistFollowerRow::as_select())
+            .filter(artist_follower_dsl::artist_id.eq(artist.id))
+            .filter(artist_follower_dsl::user_id.eq(user_id))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get follower '{}' of artist '{}'", user_id, artist.name))?;
+        Ok(follower)
+    }
+
+    fn get_artist_follower_rows(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+    ) -> Result<Vec<ArtistFollowerRow>, anyhow::Error> {
+        let followers = artist_follower_dsl::melonbooks_artist_follower
+            .select(ArtistFollowerRow::as_select())
+            .get_results(connection)
+            .with_context(|| "cannot get artist followers")?;
+        Ok(followers)
+    }
+
+    fn get_artist_follower_rows_by_user(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        user_id: i32,
+    ) -> Result<Vec<ArtistFollowerRow>, anyhow::Error> {
+        let followers = artist_follower_dsl::melonbooks_artist_follower
+            .select(ArtistFollowerRow::as_select())
+            .filter(artist_follower_dsl::user_id.eq(user_id))
+            .get_results(connection)
+            .with_context(|| format!("cannot get artists followed by user '{}'", user_id))?;
+        Ok(followers)
     }
 
-    fn update_artist_row_unfollow(
+    fn insert_artist_follower_row(
         &self,
         connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
         artist: &ArtistRow,
-    ) -> Result<ArtistRow, anyhow::Error> {
-        let artist = diesel::update(&artist)
-            .set((artist_dsl::following.eq(false), artist_dsl::date_followed.eq(Option::<NaiveDateTime>::None)))
-            .returning(ArtistRow::as_returning())
-            .get_result(connection)
-            .with_context(|| format!("cannot update artist with name '{}' to unfollowed", artist.name))?;
-        Ok(artist)
+        user_id: i32,
+    ) -> Result<(), anyhow::Error> {
+        diesel::insert_into(artist_follower_dsl::melonbooks_artist_follower)
+            .values(ArtistFollowerRowInsert { artist_id: artist.id, user_id })
+            .execute(connection)
+            .with_context(|| format!("cannot follow artist '{}' for user '{}'", artist.name, user_id))?;
+        Ok(())
+    }
+
+    fn delete_artist_follower_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        artist: &ArtistRow,
+        user_id: i32,
+    ) -> Result<(), anyhow::Error> {
+        diesel::delete(artist_follower_dsl::melonbooks_artist_follower)
+            .filter(artist_follower_dsl::artist_id.eq(artist.id))
+            .filter(artist_follower_dsl::user_id.eq(user_id))
+            .execute(connection)
+            .with_context(|| format!("cannot unfollow artist '{}' for user '{}'", artist.name, user_id))?;
+        Ok(())
+    }
+
+    /// Keeps `following` and `date_followed` of the artist in sync with its followers, they mean followed by anybody.
+    fn update_artist_row_following(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        artist: &ArtistRow,
+    ) -> Result<(), anyhow::Error> {
+        diesel::sql_query(
+            "UPDATE melonbooks_artist SET \
+                following = EXISTS (SELECT 1 FROM melonbooks_artist_follower f WHERE f.artist_id = melonbooks_artist.id), \
+                date_followed = (SELECT MIN(f.date_followed) FROM melonbooks_artist_follower f WHERE f.artist_id = melonbooks_artist.id) \
+            WHERE id = ?"
+        )
+            .bind::<Integer, _>(artist.id)
+            .execute(connection)
+            .with_context(|| format!("cannot update following of artist '{}'", artist.name))?;
+        Ok(())
+    }
+
+    fn update_artist_follower_row_schedule(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        artist: &ArtistRow,
+        user_id: i32,
+        schedule: Option<&str>,
+    ) -> Result<(), anyhow::Error> {
+        diesel::update(artist_follower_dsl::melonbooks_artist_follower)
+            .filter(artist_follower_dsl::artist_id.eq(artist.id))
+            .filter(artist_follower_dsl::user_id.eq(user_id))
+            .set(artist_follower_dsl::schedule.eq(schedule))
+            .execute(connection)
+            .with_context(|| format!("cannot update schedule of artist '{}' for user '{}'", artist.name, user_id))?;
+        Ok(())
+    }
+
+    /// Keeps `schedule` of the artist at the one chosen by most of its followers, having none counts as a choice
+    /// and ties go to the earliest follower. An artist nobody follows has none.
+    fn update_artist_row_schedule(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        artist: &ArtistRow,
+    ) -> Result<(), anyhow::Error> {
+        diesel::sql_query(
+            "UPDATE melonbooks_artist SET schedule = ( \
+                SELECT f.schedule FROM melonbooks_artist_follower f WHERE f.artist_id = melonbooks_artist.id \
+                GROUP BY f.schedule ORDER BY COUNT(*) DESC, MIN(f.date_followed) LIMIT 1 \
+            ) WHERE id = ?"
+        )
+            .bind::<Integer, _>(artist.id)
+            .execute(connection)
+            .with_context(|| format!("cannot update schedule of artist '{}'", artist.name))?;
+        Ok(())
+    }
+
+    fn update_artist_row_date_scraped(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        artist_id: i32,
+        date_scraped: NaiveDateTime,
+    ) -> Result<(), anyhow::Error> {
+        diesel::update(artist_dsl::melonbooks_artist)
+            .filter(artist_dsl::id.eq(artist_id))
+            .set((
+                artist_dsl::date_scraped.eq(date_scraped),
+                artist_dsl::date_first_scraped.eq(sql::<Nullable<Timestamp>>("COALESCE(date_first_scraped, ").bind::<Timestamp, _>(date_scraped).sql(")")),
+            ))
+            .execute(connection)
+            .with_context(|| format!("cannot update date scraped of artist with id {}", artist_id))?;
+        Ok(())
     }
 
     fn get_artist_rows(
@@ -119,6 +250,42 @@ impl Sqlite {
         Ok(artists)
     }
 
+    fn filtered_artist_query<'a>(
+        &self,
+        user_id: i32,
+        following: Option<bool>,
+    ) -> schema::melonbooks_artist::BoxedQuery<'a, SqliteBackend> {
+        let followed_ids = artist_follower_dsl::melonbooks_artist_follower
+            .filter(artist_follower_dsl::user_id.eq(user_id))
+            .select(artist_follower_dsl::artist_id);
+        match following {
+            Some(true) => artist_dsl::melonbooks_artist.filter(artist_dsl::id.eq_any(followed_ids)).into_boxed(),
+            Some(false) => artist_dsl::melonbooks_artist.filter(diesel::dsl::not(artist_dsl::id.eq_any(followed_ids))).into_boxed(),
+            None => artist_dsl::melonbooks_artist.into_boxed(),
+        }
+    }
+
+    fn get_artist_rows_page(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        user_id: i32,
+        following: Option<bool>,
+        page: PageRequest,
+    ) -> Result<(Vec<ArtistRow>, i64), anyhow::Error> {
+        let total = self.filtered_artist_query(user_id, following)
+            .count()
+            .get_result::<i64>(connection)
+            .with_context(|| "cannot count artists")?;
+        let artists = self.filtered_artist_query(user_id, following)
+            .select(ArtistRow::as_select())
+            .order_by(artist_dsl::id.asc())
+            .limit(page.page_size() as i64)
+            .offset(page.offset())
+            .get_results(connection)
+            .with_context(|| "cannot select artists")?;
+        Ok((artists, total))
+    }
+
     fn get_product_artists(
         &self,
         connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
@@ -148,6 +315,20 @@ impl Sqlite {
         Ok(product)
     }
 
+    fn get_product_row_by_id(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32
+    ) -> Result<Option<ProductRow>, anyhow::Error> {
+        let product = product_dsl::melonbooks_product
+            .select(ProductRow::as_select())
+            .find(product_id)
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get product with id '{}'", product_id))?;
+        Ok(product)
+    }
+
     fn get_product_rows(
         &self,
         connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
@@ -175,6 +356,144 @@ impl Sqlite {
         Ok(products)
     }
 
+    fn filtered_product_query<'a>(
+        &self,
+        query: &'a ProductQuery,
+    ) -> schema::melonbooks_product::BoxedQuery<'a, SqliteBackend> {
+        let mut products = product_dsl::melonbooks_product.into_boxed();
+        if let Some(artist_id) = query.artist_id() {
+            products = products.filter(product_dsl::id.eq_any(
+                product_artist_dsl::melonbooks_product_artist
+                    .filter(product_artist_dsl::artist_id.eq(artist_id))
+                    .select(product_artist_dsl::product_id)
+            ));
+        }
+        if let Some(user_id) = query.followed_by() {
+            products = products.filter(product_dsl::id.eq_any(
+                product_artist_dsl::melonbooks_product_artist
+                    .inner_join(artist_follower_dsl::melonbooks_artist_follower.on(artist_follower_dsl::artist_id.eq(product_artist_dsl::artist_id)))
+                    .filter(artist_follower_dsl::user_id.eq(user_id))
+                    .select(product_artist_dsl::product_id)
+            ));
+        }
+        if let Some(category) = query.category() {
+            products = products.filter(product_dsl::category_id.eq_any(
+                category_dsl::melonbooks_category
+                    .filter(category_dsl::category.eq(category))
+                    .select(category_dsl::id)
+            ));
+        }
+        if let Some(availability) = query.availability() {
+            products = products.filter(product_dsl::availability.eq(availability.to_string()));
+        }
+        if let Some(flag) = query.flag() {
+            products = products.filter(product_dsl::id.eq_any(
+                product_flag_dsl::melonbooks_product_flag
+                    .inner_join(flag_dsl::melonbooks_flag)
+                    .filter(flag_dsl::flag.eq(flag))
+                    .select(product_flag_dsl::product_id)
+            ));
+        }
+        if let Some(added_from) = query.added_from() {
+            products = products.filter(product_dsl::date_added.ge(added_from.and_time(NaiveTime::MIN)));
+        }
+        if let Some(added_to) = query.added_to() {
+            products = products.filter(product_dsl::date_added.lt((added_to + Days::new(1)).and_time(NaiveTime::MIN)));
+        }
+        products
+    }
+
+    fn get_product_rows_page(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        query: &ProductQuery,
+    ) -> Result<(Vec<ProductRow>, i64), anyhow::Error> {
+        let total = self.filtered_product_query(query)
+            .count()
+            .get_result::<i64>(connection)
+            .with_context(|| format!("cannot count products for {:?}", query))?;
+        let products = self.filtered_product_query(query).select(ProductRow::as_select());
+        let products = match (query.sort(), query.direction()) {
+            (ProductSort::DateAdded, SortDirection::Asc) => products.order_by(product_dsl::date_added.asc()),
+            (ProductSort::DateAdded, SortDirection::Desc) => products.order_by(product_dsl::date_added.desc()),
+            (ProductSort::DateChanged, SortDirection::Asc) => products.order_by(sql::<Timestamp>(DATE_CHANGED_SORT_EXPRESSION).asc()),
+            (ProductSort::DateChanged, SortDirection::Desc) => products.order_by(sql::<Timestamp>(DATE_CHANGED_SORT_EXPRESSION).desc()),
+            (ProductSort::Title, SortDirection::Asc) => products.order_by(product_dsl::title.asc()),
+            (ProductSort::Title, SortDirection::Desc) => products.order_by(product_dsl::title.desc()),
+            (ProductSort::Price, SortDirection::Asc) => products.order_by(sql::<Nullable<Integer>>(PRICE_SORT_EXPRESSION).asc()),
+            (ProductSort::Price, SortDirection::Desc) => products.order_by(sql::<Nullable<Integer>>(PRICE_SORT_EXPRESSION).desc()),
+        };
+        let products = products
+            .then_order_by(product_dsl::id.desc())
+            .limit(query.page().page_size() as i64)
+            .offset(query.page().offset())
+            .get_results(connection)
+            .with_context(|| format!("cannot get products for {:?}", query))?;
+        Ok((products, total))
+    }
+
+    fn get_category_names(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+    ) -> Result<Vec<String>, anyhow::Error> {
+        let categories = category_dsl::melonbooks_category
+            .select(category_dsl::category)
+            .order_by(category_dsl::category.asc())
+            .get_results(connection)
+            .with_context(|| "cannot get categories")?;
+        Ok(categories)
+    }
+
+    fn get_category_names_page(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        page: PageRequest,
+    ) -> Result<(Vec<String>, i64), anyhow::Error> {
+        let total = category_dsl::melonbooks_category
+            .count()
+            .get_result::<i64>(connection)
+            .with_context(|| "cannot count categories")?;
+        let categories = category_dsl::melonbooks_category
+            .select(category_dsl::category)
+            .order_by(category_dsl::category.asc())
+            .limit(page.page_size() as i64)
+            .offset(page.offset())
+            .get_results(connection)
+            .with_context(|| "cannot get categories")?;
+        Ok((categories, total))
+    }
+
+    fn get_flag_names(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+    ) -> Result<Vec<String>, anyhow::Error> {
+        let flags = flag_dsl::melonbooks_flag
+            .select(flag_dsl::flag)
+            .order_by(flag_dsl::flag.asc())
+            .get_results(connection)
+            .with_context(|| "cannot get flags")?;
+        Ok(flags)
+    }
+
+    fn get_flag_names_page(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        page: PageRequest,
+    ) -> Result<(Vec<String>, i64), anyhow::Error> {
+        let total = flag_dsl::melonbooks_flag
+            .count()
+            .get_result::<i64>(connection)
+            .with_context(|| "cannot count flags")?;
+        let flags = flag_dsl::melonbooks_flag
+            .select(flag_dsl::flag)
+            .order_by(flag_dsl::flag.asc())
+            .limit(page.page_size() as i64)
+            .offset(page.offset())
+            .get_results(connection)
+            .with_context(|| "cannot get flags")?;
+        Ok((flags, total))
+    }
+
     fn insert_product_row(
         &self,
         connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
@@ -333,13 +652,126 @@ impl Sqlite {
         product: &ProductRow,
         args: &UpdateProductArgs,
     ) -> Result<ProductRow, anyhow::Error> {
+        let date_restocked = match !product.availability.is_available() && args.availability().is_available() {
+            true => Some(Utc::now().naive_utc()),
+            false => product.date_restocked,
+        };
+        if product.availability != args.availability() {
+            self.insert_availability_event_row(connection, product.id, Some(product.availability.clone()), args.availability())?;
+        }
         let product = diesel::update(&product)
-            .set(product_dsl::availability.eq(args.availability().to_string()))
+            .set((
+                product_dsl::availability.eq(args.availability().to_string()),
+                product_dsl::date_restocked.eq(date_restocked),
+            ))
             .returning(ProductRow::as_returning())
             .get_result(connection)
             .with_context(|| format!("cannot update product with url '{}'", product.url))?;
         Ok(product)
     }
+
+    fn insert_availability_event_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+        previous_availability: Option<Availability>,
+        availability: Availability,
+    ) -> Result<(), anyhow::Error> {
+        diesel::insert_into(availability_event_dsl::melonbooks_availability_event)
+            .values(AvailabilityEventRowInsert { product_id, availability, previous_availability: previous_availability.map(|a| a.to_string()) })
+            .execute(connection)
+            .with_context(|| format!("cannot insert availability event for product '{}'", product_id))?;
+        Ok(())
+    }
+
+    fn get_availability_event_rows(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+    ) -> Result<Vec<AvailabilityEventRow>, anyhow::Error> {
+        let events = availability_event_dsl::melonbooks_availability_event
+            .select(AvailabilityEventRow::as_select())
+            .order_by(availability_event_dsl::id.asc())
+            .get_results(connection)
+            .with_context(|| "cannot get availability events")?;
+        Ok(events)
+    }
+
+    fn get_artist_availability_event_rows(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        since: NaiveDateTime,
+    ) -> Result<Vec<(i32, AvailabilityEventRow)>, anyhow::Error> {
+        let events = product_artist_dsl::melonbooks_product_artist
+            .inner_join(product_dsl::melonbooks_product.inner_join(availability_event_dsl::melonbooks_availability_event))
+            .select((product_artist_dsl::artist_id, AvailabilityEventRow::as_select()))
+            .filter(availability_event_dsl::date_added.ge(since))
+            .order_by(availability_event_dsl::id.asc())
+            .get_results(connection)
+            .with_context(|| "cannot get availability events of artists")?;
+        Ok(events)
+    }
+
+    /// The id of the artist of each product added since `since` after the first scrape of the artist.
+    fn get_artist_added_product_rows(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        since: NaiveDateTime,
+    ) -> Result<Vec<i32>, anyhow::Error> {
+        let artist_ids = product_artist_dsl::melonbooks_product_artist
+            .inner_join(product_dsl::melonbooks_product)
+            .inner_join(artist_dsl::melonbooks_artist)
+            .select(product_artist_dsl::artist_id)
+            .filter(product_dsl::date_added.ge(since))
+            .filter(product_dsl::date_added.nullable().gt(artist_dsl::date_first_scraped))
+            .get_results(connection)
+            .with_context(|| "cannot get added products of artists")?;
+        Ok(artist_ids)
+    }
+
+    fn insert_price_event_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+        price: Option<&str>,
+    ) -> Result<(), anyhow::Error> {
+        diesel::insert_into(price_event_dsl::melonbooks_price_event)
+            .values(PriceEventRowInsert { product_id, price })
+            .execute(connection)
+            .with_context(|| format!("cannot insert price event for product '{}'", product_id))?;
+        Ok(())
+    }
+
+    fn get_product_history_entries(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+    ) -> Result<Vec<ProductHistoryEntry>, anyhow::Error> {
+        let availability_events = availability_event_dsl::melonbooks_availability_event
+            .select(AvailabilityEventRow::as_select())
+            .filter(availability_event_dsl::product_id.eq(product_id))
+            .order_by(availability_event_dsl::id.asc())
+            .get_results(connection)
+            .with_context(|| format!("cannot get availability events for product '{}'", product_id))?;
+        let price_events = price_event_dsl::melonbooks_price_event
+            .select(PriceEventRow::as_select())
+            .filter(price_event_dsl::product_id.eq(product_id))
+            .order_by(price_event_dsl::id.asc())
+            .get_results(connection)
+            .with_context(|| format!("cannot get price events for product '{}'", product_id))?;
+        let notifications = notification_dsl::melonbooks_notification
+            .inner_join(user_dsl::app_user)
+            .select((NotificationRow::as_select(), user_dsl::username))
+            .filter(notification_dsl::product_id.eq(product_id))
+            .order_by(notification_dsl::id.asc())
+            .get_results::<(NotificationRow, String)>(connection)
+            .with_context(|| format!("cannot get notifications for product '{}'", product_id))?;
+        let mut history = availability_events.into_iter().map(|e| e.into_domain())
+            .chain(price_events.into_iter().map(|e| e.into_domain()))
+            .chain(notifications.into_iter().map(|(n, username)| n.into_domain(username)))
+            .collect::<Vec<_>>();
+        sort_history(&mut history);
+        Ok(history)
+    }
     
     fn add_skip_product<S: AsRef<str>>(
         &self,
@@ -385,25 +817,45 @@ impl Sqlite {
     fn add_title_skip_sequence(
         &self,
         connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        user_id: i32,
         sequence: &str,
     ) -> Result<TitleSkipSequenceRow, anyhow::Error> {
         let skip_sequence = diesel::insert_into(title_skip_dsl::melonbooks_title_skip_sequence)
-            .values(TitleSkipSequenceRowInsert { sequence })
+            .values(TitleSkipSequenceRowInsert { user_id, sequence })
             .returning(TitleSkipSequenceRow::as_returning())
             .get_result(connection)
             .with_context(|| format!("cannot add title skip sequence '{}'", sequence))?;
         Ok(skip_sequence)
     }
 
+    fn get_title_skip_sequence(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        user_id: i32,
+        sequence: &str,
+    ) -> Result<Option<TitleSkipSequenceRow>, anyhow::Error> {
+        let skip_sequence = title_skip_dsl::melonbooks_title_skip_sequence
+            .select(TitleSkipSequenceRow::as_select())
+            .filter(title_skip_dsl::user_id.eq(user_id))
+            .filter(title_skip_dsl::sequence.eq(sequence))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get title skip sequence '{}'", sequence))?;
+        Ok(skip_sequence)
+    }
+
     fn delete_title_skip_sequence(
         &self,
         connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        user_id: i32,
         sequence: &str,
-    ) -> Result<TitleSkipSequenceRow, anyhow::Error> {
+    ) -> Result<Option<TitleSkipSequenceRow>, anyhow::Error> {
         let skip_sequence = diesel::delete(title_skip_dsl::melonbooks_title_skip_sequence)
+            .filter(title_skip_dsl::user_id.eq(user_id))
             .filter(title_skip_dsl::sequence.eq(sequence))
             .returning(TitleSkipSequenceRow::as_returning())
             .get_result(connection)
+            .optional()
             .with_context(|| format!("cannot delete title skip sequence '{}'", sequence))?;
         Ok(skip_sequence)
     }
@@ -411,14 +863,229 @@ impl Sqlite {
     fn get_title_skip_sequences(
         &self,
         connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        user_id: i32,
     ) -> Result<Vec<TitleSkipSequenceRow>, anyhow::Error> {
         let title_skip_sequences = title_skip_dsl::melonbooks_title_skip_sequence
             .select(TitleSkipSequenceRow::as_select())
+            .filter(title_skip_dsl::user_id.eq(user_id))
             .get_results(connection)
-            .with_context(|| "cannot get title skip sequences")?;
+            .with_context(|| format!("cannot get title skip sequences for user '{}'", user_id))?;
         Ok(title_skip_sequences)
     }
 
+    fn get_title_skip_sequence_names_page(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        user_id: i32,
+        page: PageRequest,
+    ) -> Result<(Vec<String>, i64), anyhow::Error> {
+        let total = title_skip_dsl::melonbooks_title_skip_sequence
+            .filter(title_skip_dsl::user_id.eq(user_id))
+            .count()
+            .get_result::<i64>(connection)
+            .with_context(|| format!("cannot count title skip sequences for user '{}'", user_id))?;
+        let title_skip_sequences = title_skip_dsl::melonbooks_title_skip_sequence
+            .select(title_skip_dsl::sequence)
+            .filter(title_skip_dsl::user_id.eq(user_id))
+            .order_by(title_skip_dsl::id.asc())
+            .limit(page.page_size() as i64)
+            .offset(page.offset())
+            .get_results(connection)
+            .with_context(|| format!("cannot get title skip sequences for user '{}'", user_id))?;
+        Ok((title_skip_sequences, total))
+    }
+
+    fn update_product_search_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product: &Product,
+    ) -> Result<(), anyhow::Error> {
+        diesel::sql_query("DELETE FROM melonbooks_product_search WHERE rowid = ?")
+            .bind::<Integer, _>(product.id())
+            .execute(connection)
+            .with_context(|| format!("cannot delete search entry for product '{}'", product.url()))?;
+        diesel::sql_query("INSERT INTO melonbooks_product_search (rowid, title, circle, artists, tags) VALUES (?, ?, ?, ?, ?)")
+            .bind::<Integer, _>(product.id())
+            .bind::<Text, _>(product.title())
+            .bind::<Text, _>(product.circle().unwrap_or_default())
+            .bind::<Text, _>(product.artists().iter().map(|a| a.name()).collect::<Vec<_>>().join(" | "))
+            .bind::<Text, _>(product.tags().join(" | "))
+            .execute(connection)
+            .with_context(|| format!("cannot insert search entry for product '{}'", product.url()))?;
+        Ok(())
+    }
+
+    fn search_product_rows(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        expression: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<ProductSearchRow>, anyhow::Error> {
+        let rows = diesel::sql_query(
+            "SELECT rowid AS id, \
+                bm25(melonbooks_product_search, 10.0, 5.0, 5.0, 1.0) AS rank, \
+                highlight(melonbooks_product_search, 0, char(2), char(3)) AS title, \
+                highlight(melonbooks_product_search, 1, char(2), char(3)) AS circle, \
+                highlight(melonbooks_product_search, 2, char(2), char(3)) AS artists, \
+                highlight(melonbooks_product_search, 3, char(2), char(3)) AS tags \
+            FROM melonbooks_product_search \
+            WHERE melonbooks_product_search MATCH ? \
+            ORDER BY rank, rowid \
+            LIMIT ? OFFSET ?"
+        )
+            .bind::<Text, _>(expression)
+            .bind::<BigInt, _>(limit)
+            .bind::<BigInt, _>(offset)
+            .load(connection)
+            .with_context(|| format!("cannot search products with '{}'", expression))?;
+        Ok(rows)
+    }
+
+    fn load_search_results(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        search_rows: Vec<ProductSearchRow>,
+    ) -> Result<Vec<SearchResult<Product>>, anyhow::Error> {
+        let product_ids = search_rows.iter().map(|r| r.id).collect::<Vec<_>>();
+        let product_rows = self.get_product_rows_by_ids(connection, &product_ids)?;
+        let mut products = self.load_products(connection, product_rows)?
+            .into_iter()
+            .map(|p| (p.id(), p))
+            .collect::<HashMap<_, _>>();
+        let mut results = Vec::new();
+        for search_row in search_rows {
+            let product = products.remove(&search_row.id)
+                .with_context(|| format!("cannot find product with id '{}'", search_row.id))?;
+            let highlights = vec![
+                FieldHighlight::new("title".to_owned(), parse_highlight(&search_row.title)),
+                FieldHighlight::new("circle".to_owned(), parse_highlight(&search_row.circle)),
+                FieldHighlight::new("artists".to_owned(), parse_highlight(&search_row.artists)),
+                FieldHighlight::new("tags".to_owned(), parse_highlight(&search_row.tags)),
+            ];
+            results.push(SearchResult::new(product, search_row.rank, highlights));
+        }
+        Ok(results)
+    }
+
+    fn get_product_rows_by_ids(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_ids: &[i32],
+    ) -> Result<Vec<ProductRow>, anyhow::Error> {
+        let mut products = Vec::with_capacity(product_ids.len());
+        for ids in product_ids.chunks(LOAD_BATCH_SIZE) {
+            let rows = product_dsl::melonbooks_product
+                .select(ProductRow::as_select())
+                .filter(product_dsl::id.eq_any(ids))
+                .get_results(connection)
+                .with_context(|| format!("cannot get products with ids {:?}", ids))?;
+            products.extend(rows);
+        }
+        Ok(products)
+    }
+
+    fn get_artist_rows_by_product_ids(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_ids: &[i32],
+    ) -> Result<Vec<(i32, ArtistRow)>, anyhow::Error> {
+        let artists = product_artist_dsl::melonbooks_product_artist
+            .inner_join(artist_dsl::melonbooks_artist)
+            .select((product_artist_dsl::product_id, ArtistRow::as_select()))
+            .filter(product_artist_dsl::product_id.eq_any(product_ids))
+            .get_results(connection)
+            .with_context(|| "cannot get artists for products")?;
+        Ok(artists)
+    }
+
+    fn get_tag_rows_by_product_ids(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_ids: &[i32],
+    ) -> Result<Vec<(i32, TagRow)>, anyhow::Error> {
+        let tags = product_tag_dsl::melonbooks_product_tag
+            .inner_join(tag_dsl::melonbooks_tag)
+            .select((product_tag_dsl::product_id, TagRow::as_select()))
+            .filter(product_tag_dsl::product_id.eq_any(product_ids))
+            .get_results(connection)
+            .with_context(|| "cannot get tags for products")?;
+        Ok(tags)
+    }
+
+    fn get_flag_rows_by_product_ids(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_ids: &[i32],
+    ) -> Result<Vec<(i32, FlagRow)>, anyhow::Error> {
+        let flags = product_flag_dsl::melonbooks_product_flag
+            .inner_join(flag_dsl::melonbooks_flag)
+            .select((product_flag_dsl::product_id, FlagRow::as_select()))
+            .filter(product_flag_dsl::product_id.eq_any(product_ids))
+            .get_results(connection)
+            .with_context(|| "cannot get flags for products")?;
+        Ok(flags)
+    }
+
+    fn get_category_rows_by_ids(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        category_ids: &[i32],
+    ) -> Result<Vec<CategoryRow>, anyhow::Error> {
+        let categories = category_dsl::melonbooks_category
+            .select(CategoryRow::as_select())
+            .filter(category_dsl::id.eq_any(category_ids))
+            .get_results(connection)
+            .with_context(|| "cannot get categories for products")?;
+        Ok(categories)
+    }
+
+    /// Loads the relations of all given products with a fixed number of queries per batch
+    /// instead of four queries per product. The order of `products` is kept.
+    fn load_products(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        products: Vec<ProductRow>,
+    ) -> Result<Vec<Product>, anyhow::Error> {
+        let mut artists: HashMap<i32, Vec<Artist>> = HashMap::new();
+        let mut tags: HashMap<i32, Vec<String>> = HashMap::new();
+        let mut flags: HashMap<i32, Vec<String>> = HashMap::new();
+        let mut categories: HashMap<i32, String> = HashMap::new();
+        for batch in products.chunks(LOAD_BATCH_SIZE) {
+            let product_ids = batch.iter().map(|p| p.id).collect::<Vec<_>>();
+            for (product_id, artist) in self.get_artist_rows_by_product_ids(connection, &product_ids)? {
+                artists.entry(product_id).or_default().push(artist.into_domain());
+            }
+            for (product_id, tag) in self.get_tag_rows_by_product_ids(connection, &product_ids)? {
+                tags.entry(product_id).or_default().push(tag.into_domain());
+            }
+            for (product_id, flag) in self.get_flag_rows_by_product_ids(connection, &product_ids)? {
+                flags.entry(product_id).or_default().push(flag.into_domain());
+            }
+            let category_ids = batch.iter()
+                .map(|p| p.category_id)
+                .filter(|id| !categories.contains_key(id))
+                .unique()
+                .collect::<Vec<_>>();
+            if !category_ids.is_empty() {
+                for category in self.get_category_rows_by_ids(connection, &category_ids)? {
+                    categories.insert(category.id, category.category);
+                }
+            }
+        }
+        products.into_iter()
+            .map(|product| {
+                let category = categories.get(&product.category_id)
+                    .cloned()
+                    .with_context(|| format!("cannot find category with id '{}'", product.category_id))?;
+                let artists = artists.remove(&product.id).unwrap_or_default();
+                let tags = tags.remove(&product.id).unwrap_or_default();
+                let flags = flags.remove(&product.id).unwrap_or_default();
+                Ok(product.into_domain(artists, category, tags, flags))
+            })
+            .collect()
+    }
+
     fn load_product(
         &self,
         connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
@@ -441,7 +1108,9 @@ impl Sqlite {
             flags.into_iter().map(|f| f.into_domain()).collect(),
             product.price.to_owned(),
             product.availability.to_owned(),
-        );
+        ).with_date_restocked(product.date_restocked.map(|d| d.and_utc()))
+            .with_image_hash(product.image_hash.to_owned())
+            .with_image_phash(product.image_phash.map(|h| h as u64));
         Ok(product)
     }
 }
@@ -449,175 +1118,373 @@ impl Sqlite {
 
 #[async_trait]
 impl MelonbooksRepository for Sqlite {
-    async fn follow_melonbooks_artist(&self, args: &ArtistArgs) -> Result<(), FollowArtistError> {
-        let mut connection = self.get_connection()?;
-        let artist = self.get_artist_row_by_name(&mut connection, args.name())?;
-        match artist {
-            Some(artist) => {
-                if artist.following {
-                    return Err(FollowArtistError::AlreadyFollowedError(artist.date_followed.unwrap().and_utc()));
-                }
-                self.update_artist_row_follow(&mut connection, &artist)?;
-            },
-            None => {
-                self.insert_artist_row(&mut connection, args, true, Some(Utc::now()))?;
+    async fn follow_melonbooks_artist(&self, user_id: i32, args: &ArtistArgs) -> Result<(), FollowArtistError> {
+        let args = args.clone();
+        self.write(move |db, connection| {
+            let artist = match db.get_artist_row_by_name(connection, args.name())? {
+                Some(artist) => artist,
+                None => db.insert_artist_row(connection, &args)?,
+            };
+            if let Some(follower) = db.get_artist_follower_row(connection, &artist, user_id)? {
+                return Err(FollowArtistError::AlreadyFollowedError(follower.date_followed.and_utc()));
             }
-        }
-        self.delete_skip_products_for_artist(&mut connection, args.name())?;
-        Ok(())
+            connection.transaction(|connection| -> Result<(), anyhow::Error> {
+                db.insert_artist_follower_row(connection, &artist, user_id)?;
+                db.update_artist_row_following(connection, &artist)?;
+                db.update_artist_row_schedule(connection, &artist)?;
+                db.delete_skip_products_for_artist(connection, args.name())
+            })?;
+            Ok(())
+        }).await
+    }
+
+    async fn unfollow_melonbooks_artist(&self, user_id: i32, artist_id: i32) -> Result<(), UnfollowArtistError> {
+        self.write(move |db, connection| {
+            let artist = db.get_artist_row_by_id(connection, artist_id)?
+                .ok_or(UnfollowArtistError::UnknownArtist { id: artist_id })?;
+            if db.get_artist_follower_row(connection, &artist, user_id)?.is_none() {
+                return Err(UnfollowArtistError::ArtistNotFollowed { name: artist.name });
+            }
+            connection.transaction(|connection| -> Result<(), anyhow::Error> {
+                db.delete_artist_follower_row(connection, &artist, user_id)?;
+                db.update_artist_row_following(connection, &artist)?;
+                db.update_artist_row_schedule(connection, &artist)
+            })?;
+            Ok(())
+        }).await
     }
 
-    async fn unfollow_melonbooks_artist(&self, artist_id: i32) -> Result<(), UnfollowArtistError> {
-        let mut connection = self.get_connection()?;
-        let artist = self.get_artist_row_by_id(&mut connection, artist_id)?;
-        match artist {
-            Some(artist) => {
-                if artist.following {
-                    self.update_artist_row_unfollow(&mut connection, &artist)?;
-                } else {
-                    return Err(UnfollowArtistError::ArtistNotFollowed { name: artist.name })
+    async fn set_melonbooks_artist_schedule(&self, user_id: i32, artist_id: i32, schedule: Option<&Schedule>) -> Result<(), SetArtistScheduleError> {
+        let schedule = schedule.map(|s| s.expression().to_owned());
+        self.write(move |db, connection| {
+            let artist = db.get_artist_row_by_id(connection, artist_id)?
+                .ok_or(SetArtistScheduleError::UnknownArtist { id: artist_id })?;
+            if db.get_artist_follower_row(connection, &artist, user_id)?.is_none() {
+                return Err(SetArtistScheduleError::ArtistNotFollowed { name: artist.name });
+            }
+            connection.transaction(|connection| -> Result<(), anyhow::Error> {
+                db.update_artist_follower_row_schedule(connection, &artist, user_id, schedule.as_deref())?;
+                db.update_artist_row_schedule(connection, &artist)
+            })?;
+            Ok(())
+        }).await
+    }
+
+    async fn set_melonbooks_artist_scraped(&self, artist_id: i32, date_scraped: DateTime<Utc>) -> Result<(), SetDateScrapedError> {
+        self.write(move |db, connection| {
+            db.update_artist_row_date_scraped(connection, artist_id, date_scraped.naive_utc())?;
+            Ok(())
+        }).await
+    }
+
+    async fn get_melonbooks_artist_activity(&self, since: DateTime<Utc>) -> Result<HashMap<i32, usize>, GetTargetSchedulesError> {
+        self.read(move |db, connection| {
+            let mut activity = db.get_artist_added_product_rows(connection, since.naive_utc())?.into_iter().counts();
+            for (artist_id, event) in db.get_artist_availability_event_rows(connection, since.naive_utc())? {
+                let event = event.into_stats_event().with_context(|| "cannot parse availability events")?;
+                if event.is_restock() {
+                    *activity.entry(artist_id).or_default() += 1;
                 }
-            },
-            None => {
-                return Err(UnfollowArtistError::UnknownArtist { id: artist_id });
             }
-        }
-        Ok(())
+            Ok(activity)
+        }).await
     }
 
-    async fn get_melonbooks_artists(&self) -> Result<Vec<Artist>, GetArtistsError> {
-        let mut connection = self.get_connection()?;
-        let artist_rows = self.get_artist_rows(&mut connection)?;
-        let artists = artist_rows.into_iter()
-            .map(|a| a.into_domain())
-            .collect();
-        Ok(artists)
+    async fn get_melonbooks_artists(&self, user_id: i32) -> Result<Vec<Artist>, GetArtistsError> {
+        self.read(move |db, connection| {
+            let artist_rows = db.get_artist_rows(connection)?;
+            let followers = db.get_artist_follower_rows_by_user(connection, user_id)?
+                .into_iter()
+                .map(|f| (f.artist_id, f))
+                .collect::<HashMap<_, _>>();
+            let artists = artist_rows.into_iter()
+                .map(|a| {
+                    let follower = followers.get(&a.id);
+                    a.into_domain_for(follower)
+                })
+                .collect();
+            Ok(artists)
+        }).await
+    }
+
+    async fn get_melonbooks_artists_page(&self, user_id: i32, following: Option<bool>, page: PageRequest) -> Result<Page<Artist>, GetArtistsError> {
+        self.read(move |db, connection| {
+            let (artist_rows, total) = db.get_artist_rows_page(connection, user_id, following, page)?;
+            let followers = db.get_artist_follower_rows_by_user(connection, user_id)?
+                .into_iter()
+                .map(|f| (f.artist_id, f))
+                .collect::<HashMap<_, _>>();
+            let artists = artist_rows.into_iter()
+                .map(|a| {
+                    let follower = followers.get(&a.id);
+                    a.into_domain_for(follower)
+                })
+                .collect();
+            Ok(Page::new(artists, page, total))
+        }).await
+    }
+
+    async fn get_followed_melonbooks_artists(&self) -> Result<Vec<FollowedArtist>, GetArtistsError> {
+        self.read(move |db, connection| {
+            let follower_rows = db.get_artist_follower_rows(connection)?;
+            let user_ids = follower_rows.iter().map(|f| f.user_id).unique().collect::<Vec<_>>();
+            let users = db.get_user_rows_by_ids(connection, &user_ids)?
+                .into_iter()
+                .map(|u| (u.id, u.into_domain()))
+                .collect::<HashMap<_, _>>();
+            let mut followers = follower_rows.into_iter().into_group_map_by(|f| f.artist_id);
+            let artists = db.get_artist_rows(connection)?
+                .into_iter()
+                .filter_map(|artist| {
+                    let mut artist_followers = followers.remove(&artist.id)?
+                        .into_iter()
+                        .filter_map(|f| users.get(&f.user_id).cloned())
+                        .collect::<Vec<_>>();
+                    artist_followers.sort_by(|a, b| a.username().cmp(b.username()));
+                    let date_scraped = artist.date_scraped.map(|d| d.and_utc());
+                    Some(FollowedArtist::new(artist.into_domain(), artist_followers).with_date_scraped(date_scraped))
+                })
+                .collect();
+            Ok(artists)
+        }).await
     }
 
     async fn create_melonbooks_product(&self, args: &CreateProductArgs) -> Result<Product, CreateProductError> {
-        let mut connection = self.get_connection()?;
-        let product_row = self.get_product_row_by_url(&mut connection, args.url())?;
-        match product_row {
-            Some(product_row) => {
-                Err(CreateProductError::DuplicateProduct { url: product_row.url, title: product_row.title })
-            },
-            None => {
-                let product = connection.transaction(|connection| -> Result<Product, anyhow::Error> {
-                    let category_row = self.insert_category_row(connection, args.category())?;
-                    let product_row = self.insert_product_row(connection, &args, &category_row)?;
-                    let mut tags = Vec::new();
-                    for tag_name in args.tags() {
-                        let tag_row = self.insert_product_tag(connection, &product_row, tag_name)?;
-                        tags.push(tag_row);
-                    }
-                    let mut flags = Vec::new();
-                    for flag_name in args.flags() {
-                        let flag_row = self.insert_product_flag(connection, &product_row, flag_name)?;
-                        flags.push(flag_row);
-                    }
-                    let mut artists = Vec::new();
-                    for artist_name in args.artists() {
-                        let artist = self.get_artist_row_by_name(connection, artist_name)?;
-                        match artist {
-                            Some(artist_row) => {
-                                self.insert_product_artist_row(connection, &product_row, &artist_row)?;
-                                artists.push(artist_row);
-                            },
-                            None => {
-                                let artist_row = self.insert_artist_row(connection, &ArtistArgs::new(artist_name.to_owned()), false, None)?;
-                                self.insert_product_artist_row(connection, &product_row, &artist_row)?;
-                                artists.push(artist_row);
+        let args = args.clone();
+        self.write(move |db, connection| {
+            let product_row = db.get_product_row_by_url(connection, args.url())?;
+            match product_row {
+                Some(product_row) => {
+                    Err(CreateProductError::DuplicateProduct { url: product_row.url, title: product_row.title })
+                },
+                None => {
+                    let product = connection.transaction(|connection| -> Result<Product, anyhow::Error> {
+                        let category_row = db.insert_category_row(connection, args.category())?;
+                        let product_row = db.insert_product_row(connection, &args, &category_row)?;
+                        db.insert_availability_event_row(connection, product_row.id, None, args.availability())?;
+                        db.insert_price_event_row(connection, product_row.id, args.price())?;
+                        let mut tags = Vec::new();
+                        for tag_name in args.tags() {
+                            let tag_row = db.insert_product_tag(connection, &product_row, tag_name)?;
+                            tags.push(tag_row);
+                        }
+                        let mut flags = Vec::new();
+                        for flag_name in args.flags() {
+                            let flag_row = db.insert_product_flag(connection, &product_row, flag_name)?;
+                            flags.push(flag_row);
+                        }
+                        let mut artists = Vec::new();
+                        for artist_name in args.artists() {
+                            let artist = db.get_artist_row_by_name(connection, artist_name)?;
+                            match artist {
+                                Some(artist_row) => {
+                                    db.insert_product_artist_row(connection, &product_row, &artist_row)?;
+                                    artists.push(artist_row);
+                                },
+                                None => {
+                                    let artist_row = db.insert_artist_row(connection, &ArtistArgs::new(artist_name.to_owned()))?;
+                                    db.insert_product_artist_row(connection, &product_row, &artist_row)?;
+                                    artists.push(artist_row);
+                                }
                             }
                         }
-                    }
-                    let product = Product::new(
-                        product_row.id,
-                        product_row.date_added.and_utc(),
-                        product_row.url,
-                        product_row.title,
-                        product_row.circle,
-                        artists.into_iter().map(|a| a.into_domain()).collect(),
-                        product_row.image_url,
-                        category_row.category,
-                        tags.into_iter().map(|t| t.into_domain()).collect(),
-                        flags.into_iter().map(|f| f.into_domain()).collect(),
-                        product_row.price,
-                        product_row.availability
-                    );
+                        let product = Product::new(
+                            product_row.id,
+                            product_row.date_added.and_utc(),
+                            product_row.url,
+                            product_row.title,
+                            product_row.circle,
+                            artists.into_iter().map(|a| a.into_domain()).collect(),
+                            product_row.image_url,
+                            category_row.category,
+                            tags.into_iter().map(|t| t.into_domain()).collect(),
+                            flags.into_iter().map(|f| f.into_domain()).collect(),
+                            product_row.price,
+                            product_row.availability
+                        );
+                        db.update_product_search_row(connection, &product)?;
+                        Ok(product)
+                    })?;
                     Ok(product)
-                })?;
-                Ok(product)
+                }
             }
-        }
+        }).await
     }
 
     async fn update_melonbooks_product(&self, args: &UpdateProductArgs) -> Result<Product, UpdateProductError> {
-        let mut connection = self.get_connection()?;
-        let product_row = self.get_product_row_by_url(&mut connection, args.url())?;
-        match product_row {
-            Some(product_row) => {
-                let product_row = self.update_product_row(&mut connection, &product_row, &args)?;
-                let product = self.load_product(&mut connection, &product_row)?;
-                Ok(product)
-            },
-            None => Err(UpdateProductError::ProductMissing { url: args.url().to_owned() }),
-        }
+        let args = args.clone();
+        self.write(move |db, connection| {
+            let product_row = db.get_product_row_by_url(connection, args.url())?;
+            match product_row {
+                Some(product_row) => {
+                    let product = connection.transaction(|connection| -> Result<Product, anyhow::Error> {
+                        let product_row = db.update_product_row(connection, &product_row, &args)?;
+                        let product = db.load_product(connection, &product_row)?;
+                        db.update_product_search_row(connection, &product)?;
+                        Ok(product)
+                    })?;
+                    Ok(product)
+                },
+                None => Err(UpdateProductError::ProductMissing { url: args.url().to_owned() }),
+            }
+        }).await
+    }
+
+    async fn get_melonbooks_product(&self, product_id: i32) -> Result<Product, GetProductError> {
+        self.read(move |db, connection| {
+            let product_row = db.get_product_row_by_id(connection, product_id)?
+                .ok_or(GetProductError::ProductMissing { id: product_id })?;
+            let product = db.load_product(connection, &product_row)?;
+            Ok(product)
+        }).await
+    }
+
+    async fn get_melonbooks_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError> {
+        self.read(move |db, connection| {
+            if db.get_product_row_by_id(connection, product_id)?.is_none() {
+                return Err(GetProductError::ProductMissing { id: product_id });
+            }
+            let history = db.get_product_history_entries(connection, product_id)?;
+            Ok(history)
+        }).await
+    }
+
+    async fn get_melonbooks_availability_events(&self) -> Result<Vec<AvailabilityEvent>, GetAvailabilityStatsError> {
+        self.read(move |db, connection| {
+            let events = db.get_availability_event_rows(connection)?
+                .into_iter()
+                .map(|e| e.into_stats_event())
+                .collect::<Result<Vec<_>, _>>()
+                .with_context(|| "cannot parse availability events")?;
+            Ok(events)
+        }).await
     }
 
     async fn get_melonbooks_products(&self) -> Result<Vec<Product>, GetProductsError> {
-        let mut connection = self.get_connection()?;
-        let product_rows = self.get_product_rows(&mut connection)?;
-        let mut products = Vec::new();
-        for product_row in product_rows {
-            let product = self.load_product(&mut connection, &product_row)?;
-            products.push(product);
-        }
-        Ok(products)
+        self.read(move |db, connection| {
+            let product_rows = db.get_product_rows(connection)?;
+            let products = db.load_products(connection, product_rows)?;
+            Ok(products)
+        }).await
     }
 
     async fn get_melonbooks_products_by_artist(&self, artist_id: i32) -> Result<Vec<Product>, GetProductsError> {
-        let mut connection = self.get_connection()?;
-        let product_rows = self.get_product_rows_by_artist(&mut connection, artist_id)?;
-        let mut products = Vec::new();
-        for product_row in product_rows {
-            let product = self.load_product(&mut connection, &product_row)?;
-            products.push(product);
-        }
-        Ok(products)
+        self.read(move |db, connection| {
+            let product_rows = db.get_product_rows_by_artist(connection, artist_id)?;
+            let products = db.load_products(connection, product_rows)?;
+            Ok(products)
+        }).await
+    }
+
+    async fn get_melonbooks_products_page(&self, query: &ProductQuery) -> Result<Page<Product>, GetProductsError> {
+        let query = query.clone();
+        self.read(move |db, connection| {
+            let (product_rows, total) = db.get_product_rows_page(connection, &query)?;
+            let products = db.load_products(connection, product_rows)?;
+            Ok(Page::new(products, query.page(), total))
+        }).await
+    }
+
+    async fn get_melonbooks_categories(&self) -> Result<Vec<String>, GetCategoriesError> {
+        self.read(move |db, connection| {
+            let categories = db.get_category_names(connection)?;
+            Ok(categories)
+        }).await
+    }
+
+    async fn get_melonbooks_categories_page(&self, page: PageRequest) -> Result<Page<String>, GetCategoriesError> {
+        self.read(move |db, connection| {
+            let (categories, total) = db.get_category_names_page(connection, page)?;
+            Ok(Page::new(categories, page, total))
+        }).await
+    }
+
+    async fn get_melonbooks_flags(&self) -> Result<Vec<String>, GetFlagsError> {
+        self.read(move |db, connection| {
+            let flags = db.get_flag_names(connection)?;
+            Ok(flags)
+        }).await
+    }
+
+    async fn get_melonbooks_flags_page(&self, page: PageRequest) -> Result<Page<String>, GetFlagsError> {
+        self.read(move |db, connection| {
+            let (flags, total) = db.get_flag_names_page(connection, page)?;
+            Ok(Page::new(flags, page, total))
+        }).await
+    }
+
+    async fn search_melonbooks_products(&self, query: &str) -> Result<Vec<SearchResult<Product>>, SearchProductsError> {
+        let expression = match_expression(query)?;
+        self.read(move |db, connection| {
+            let search_rows = db.search_product_rows(connection, &expression, MAX_SEARCH_RESULTS, 0)?;
+            let results = db.load_search_results(connection, search_rows)?;
+            Ok(results)
+        }).await
+    }
+
+    async fn search_melonbooks_products_page(&self, query: &str, page: PageRequest) -> Result<Page<SearchResult<Product>>, SearchProductsError> {
+        let expression = match_expression(query)?;
+        self.read(move |db, connection| {
+            let total = count_matches(connection, "melonbooks_product_search", &expression)?;
+            let search_rows = db.search_product_rows(connection, &expression, page.page_size() as i64, page.offset())?;
+            let results = db.load_search_results(connection, search_rows)?;
+            Ok(Page::new(results, page, total))
+        }).await
     }
 
     async fn add_melonbooks_skipping_url<S: AsRef<str> + Sync>(&self, url: &str, artists: &[S]) -> Result<(), AddSkippingUrlError> {
-        let mut connection = self.get_connection()?;
-        self.add_skip_product(&mut connection, url, artists)?;
-        Ok(())
+        let url = url.to_owned();
+        let artists = artists.iter().map(|a| a.as_ref().to_owned()).collect::<Vec<_>>();
+        self.write(move |db, connection| {
+            db.add_skip_product(connection, &url, &artists)?;
+            Ok(())
+        }).await
     }
 
     async fn get_melonbooks_skipping_urls(&self) -> Result<Vec<String>, GetSkippingUrlsError> {
-        let mut connection = self.get_connection()?;
-        let skip_products = self.get_skip_products(&mut connection)?;
-        let urls = skip_products.into_iter()
-            .map(|product| product.url)
-            .collect();
-        Ok(urls)
+        self.read(move |db, connection| {
+            let skip_products = db.get_skip_products(connection)?;
+            let urls = skip_products.into_iter()
+                .map(|product| product.url)
+                .collect();
+            Ok(urls)
+        }).await
     }
 
-    async fn add_melonbooks_title_skip_sequence(&self, sequence: &str) -> Result<(), AddTitleSkipSequenceError> {
-        let mut connection = self.get_connection()?;
-        self.add_title_skip_sequence(&mut connection, sequence)?;
-        Ok(())
+    async fn add_melonbooks_title_skip_sequence(&self, user_id: i32, sequence: &str) -> Result<(), AddTitleSkipSequenceError> {
+        let sequence = sequence.to_owned();
+        self.write(move |db, connection| {
+            if db.get_title_skip_sequence(connection, user_id, &sequence)?.is_some() {
+                return Err(AddTitleSkipSequenceError::DuplicateSequence { sequence });
+            }
+            db.add_title_skip_sequence(connection, user_id, &sequence)?;
+            Ok(())
+        }).await
     }
 
-    async fn delete_melonbooks_title_skip_sequence(&self, sequence: &str) -> Result<(), DeleteTitleSkipSequenceError> {
-        let mut connection = self.get_connection()?;
-        self.delete_title_skip_sequence(&mut connection, sequence)?;
-        Ok(())
+    async fn delete_melonbooks_title_skip_sequence(&self, user_id: i32, sequence: &str) -> Result<(), DeleteTitleSkipSequenceError> {
+        let sequence = sequence.to_owned();
+        self.write(move |db, connection| {
+            match db.delete_title_skip_sequence(connection, user_id, &sequence)? {
+                Some(_) => Ok(()),
+                None => Err(DeleteTitleSkipSequenceError::UnknownSequence { sequence }),
+            }
+        }).await
     }
 
-    async fn get_melonbooks_title_skip_sequences(&self) -> Result<Vec<String>, GetTitleSkipSequencesError> {
-        let mut connection = self.get_connection()?;
-        let sequence_rows = self.get_title_skip_sequences(&mut connection)?;
-        let sequences = sequence_rows.into_iter().map(|s| s.sequence).collect();
-        Ok(sequences)
+    async fn get_melonbooks_title_skip_sequences(&self, user_id: i32) -> Result<Vec<String>, GetTitleSkipSequencesError> {
+        self.read(move |db, connection| {
+            let sequence_rows = db.get_title_skip_sequences(connection, user_id)?;
+            let sequences = sequence_rows.into_iter().map(|s| s.sequence).collect();
+            Ok(sequences)
+        }).await
+    }
+
+    async fn get_melonbooks_title_skip_sequences_page(&self, user_id: i32, page: PageRequest) -> Result<Page<String>, GetTitleSkipSequencesError> {
+        self.read(move |db, connection| {
+            let (sequences, total) = db.get_title_skip_sequence_names_page(connection, user_id, page)?;
+            Ok(Page::new(sequences, page, total))
+        }).await
     }
 }
 
@@ -628,15 +1495,25 @@ impl Expression for Availability {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::domain::melonbooks::models::availability::Availability;
+    use crate::domain::availability::Availability;
+    use crate::domain::image::models::image::{CachedImage, SetProductImageError};
+    use crate::domain::melonbooks::SITE;
+    use crate::domain::pagination::PageRequest;
+    use crate::domain::product_history::{NotificationKind, ProductChange};
+    use crate::domain::site::SiteRepository;
+    use crate::domain::user::models::user::DEFAULT_USERNAME;
+    use crate::domain::user::ports::UserRepository;
+    use crate::outbound::sqlite::test_util::default_user_id;
+    use chrono::TimeDelta;
 
     #[tokio::test]
     async fn test_follow_melonbooks_artist() {
         let db = Sqlite::new_in_memory();
         db.setup().unwrap();
-        db.follow_melonbooks_artist(&artist_args()).await.unwrap();
+        let user_id = default_user_id(&db).await;
+        db.follow_melonbooks_artist(user_id, &artist_args()).await.unwrap();
 
-        let artists = db.get_melonbooks_artists().await.unwrap();
+        let artists = db.get_melonbooks_artists(user_id).await.unwrap();
         assert_eq!(artists.len(), 1);
         let artist = artists.get(0).unwrap();
         assert_eq!(artist.name(), artist_args().name());
@@ -648,11 +1525,12 @@ mod test {
     async fn test_unfollow_melonbooks_artist() {
         let db = Sqlite::new_in_memory();
         db.setup().unwrap();
-        db.follow_melonbooks_artist(&artist_args()).await.unwrap();
-        let artist = db.get_melonbooks_artists().await.unwrap().into_iter().find(|a| a.name().eq(artist_args().name())).unwrap();
-        db.unfollow_melonbooks_artist(artist.id()).await.unwrap();
+        let user_id = default_user_id(&db).await;
+        db.follow_melonbooks_artist(user_id, &artist_args()).await.unwrap();
+        let artist = db.get_melonbooks_artists(user_id).await.unwrap().into_iter().find(|a| a.name().eq(artist_args().name())).unwrap();
+        db.unfollow_melonbooks_artist(user_id, artist.id()).await.unwrap();
 
-        let artists = db.get_melonbooks_artists().await.unwrap();
+        let artists = db.get_melonbooks_artists(user_id).await.unwrap();
         assert_eq!(artists.len(), 1);
         let artist = artists.get(0).unwrap();
         assert_eq!(artist.name(), artist_args().name());
@@ -660,16 +1538,129 @@ mod test {
         assert_eq!(artist.date_followed(), None);
     }
 
+    #[tokio::test]
+    async fn test_follow_melonbooks_artist_per_user() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let users = db.setup_users(DEFAULT_USERNAME, &["alice".to_owned()]).await.unwrap();
+        let (alice, default) = (users.first().unwrap(), users.last().unwrap());
+        db.follow_melonbooks_artist(alice.id(), &artist_args()).await.unwrap();
+        db.follow_melonbooks_artist(default.id(), &artist_args()).await.unwrap();
+        assert!(matches!(db.follow_melonbooks_artist(alice.id(), &artist_args()).await, Err(FollowArtistError::AlreadyFollowedError(_))));
+
+        let artist = db.get_melonbooks_artists(alice.id()).await.unwrap().into_iter().next().unwrap();
+        db.unfollow_melonbooks_artist(default.id(), artist.id()).await.unwrap();
+        assert!(db.get_melonbooks_artists(alice.id()).await.unwrap().first().unwrap().following());
+        assert!(!db.get_melonbooks_artists(default.id()).await.unwrap().first().unwrap().following());
+
+        let followed = db.get_followed_melonbooks_artists().await.unwrap();
+        assert_eq!(followed.len(), 1);
+        assert_eq!(followed.first().unwrap().artist().id(), artist.id());
+        assert_eq!(followed.first().unwrap().followers().iter().map(|u| u.username()).collect::<Vec<_>>(), vec!["alice"]);
+    }
+
+    #[tokio::test]
+    async fn test_set_melonbooks_artist_schedule() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        db.follow_melonbooks_artist(user_id, &artist_args()).await.unwrap();
+        db.follow_melonbooks_artist(user_id, &artist_args2()).await.unwrap();
+        let artist2 = db.get_melonbooks_artists(user_id).await.unwrap().into_iter().find(|a| a.name().eq(artist_args2().name())).unwrap();
+        db.unfollow_melonbooks_artist(user_id, artist2.id()).await.unwrap();
+        let artist = db.get_melonbooks_artists(user_id).await.unwrap().into_iter().find(|a| a.name().eq(artist_args().name())).unwrap();
+        assert_eq!(artist.schedule(), None);
+
+        let schedule = Schedule::parse("0 0 */2 * * *").unwrap();
+        db.set_melonbooks_artist_schedule(user_id, artist.id(), Some(&schedule)).await.unwrap();
+        let artist = db.get_melonbooks_artists(user_id).await.unwrap().into_iter().find(|a| a.id() == artist.id()).unwrap();
+        assert_eq!(artist.schedule(), Some(&schedule));
+        let followed = db.get_followed_melonbooks_artists().await.unwrap();
+        assert_eq!(followed.first().unwrap().artist().schedule(), Some(&schedule));
+
+        assert!(matches!(db.set_melonbooks_artist_schedule(user_id, artist2.id(), Some(&schedule)).await, Err(SetArtistScheduleError::ArtistNotFollowed { .. })));
+        assert!(matches!(db.set_melonbooks_artist_schedule(user_id, 1234, None).await, Err(SetArtistScheduleError::UnknownArtist { .. })));
+
+        db.set_melonbooks_artist_schedule(user_id, artist.id(), None).await.unwrap();
+        let artist = db.get_melonbooks_artists(user_id).await.unwrap().into_iter().find(|a| a.id() == artist.id()).unwrap();
+        assert_eq!(artist.schedule(), None);
+    }
+
+    #[tokio::test]
+    async fn test_melonbooks_artist_schedule_of_most_followers() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let users = db.setup_users(DEFAULT_USERNAME, &["alice".to_owned(), "bob".to_owned()]).await.unwrap();
+        for user in users.iter() {
+            db.follow_melonbooks_artist(user.id(), &artist_args()).await.unwrap();
+        }
+        let (alice, bob, default) = (&users[0], &users[1], &users[2]);
+        let artist_id = db.get_melonbooks_artists(alice.id()).await.unwrap().first().unwrap().id();
+        let followed_schedule = async || db.get_followed_melonbooks_artists().await.unwrap().first().unwrap().artist().schedule().cloned();
+        let own_schedule = async |user_id| db.get_melonbooks_artists(user_id).await.unwrap().first().unwrap().schedule().cloned();
+
+        let schedule = Schedule::parse("0 0 */2 * * *").unwrap();
+        db.set_melonbooks_artist_schedule(alice.id(), artist_id, Some(&schedule)).await.unwrap();
+        assert_eq!(followed_schedule().await, None);
+        db.set_melonbooks_artist_schedule(bob.id(), artist_id, Some(&schedule)).await.unwrap();
+        assert_eq!(followed_schedule().await, Some(schedule.clone()));
+        assert_eq!(own_schedule(alice.id()).await, Some(schedule.clone()));
+        assert_eq!(own_schedule(default.id()).await, None);
+
+        db.unfollow_melonbooks_artist(alice.id(), artist_id).await.unwrap();
+        db.follow_melonbooks_artist(alice.id(), &artist_args()).await.unwrap();
+        assert_eq!(own_schedule(alice.id()).await, None);
+        assert_eq!(followed_schedule().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_set_melonbooks_artist_scraped() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        db.follow_melonbooks_artist(user_id, &artist_args()).await.unwrap();
+        let artist = db.get_followed_melonbooks_artists().await.unwrap().into_iter().next().unwrap();
+        assert_eq!(artist.date_scraped(), None);
+
+        let date_scraped = DateTime::parse_from_rfc3339("2026-10-19T12:00:00Z").unwrap().to_utc();
+        db.set_melonbooks_artist_scraped(artist.artist().id(), date_scraped).await.unwrap();
+        let artist = db.get_followed_melonbooks_artists().await.unwrap().into_iter().next().unwrap();
+        assert_eq!(artist.date_scraped(), Some(date_scraped));
+    }
+
+    #[tokio::test]
+    async fn test_get_melonbooks_artist_activity() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        db.create_melonbooks_product(&product_args()).await.unwrap();
+        let product2 = db.create_melonbooks_product(&product_args2()).await.unwrap();
+        db.update_melonbooks_product(&UpdateProductArgs::new(product2.url().to_owned(), Availability::Available)).await.unwrap();
+        let artists = db.get_melonbooks_artists(user_id).await.unwrap();
+        let artist_id = |name: &str| artists.iter().find(|a| a.name() == name).unwrap().id();
+
+        // the products of an artist not scraped yet are its backfill
+        db.set_melonbooks_artist_scraped(artist_id(artist_args2().name()), Utc::now() - TimeDelta::hours(1)).await.unwrap();
+        db.set_melonbooks_artist_scraped(artist_id(artist_args2().name()), Utc::now() + TimeDelta::hours(1)).await.unwrap();
+
+        let activity = db.get_melonbooks_artist_activity(Utc::now() - TimeDelta::hours(1)).await.unwrap();
+        assert_eq!(activity.get(&artist_id(artist_args().name())), Some(&1));
+        assert_eq!(activity.get(&artist_id(artist_args2().name())), Some(&2));
+
+        assert!(db.get_melonbooks_artist_activity(Utc::now() + TimeDelta::hours(1)).await.unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn test_get_melonbooks_artists() {
         let db = Sqlite::new_in_memory();
         db.setup().unwrap();
-        db.follow_melonbooks_artist(&artist_args()).await.unwrap();
-        db.follow_melonbooks_artist(&artist_args2()).await.unwrap();
-        let artist2 = db.get_melonbooks_artists().await.unwrap().into_iter().find(|a| a.name().eq(artist_args2().name())).unwrap();
-        db.unfollow_melonbooks_artist(artist2.id()).await.unwrap();
+        let user_id = default_user_id(&db).await;
+        db.follow_melonbooks_artist(user_id, &artist_args()).await.unwrap();
+        db.follow_melonbooks_artist(user_id, &artist_args2()).await.unwrap();
+        let artist2 = db.get_melonbooks_artists(user_id).await.unwrap().into_iter().find(|a| a.name().eq(artist_args2().name())).unwrap();
+        db.unfollow_melonbooks_artist(user_id, artist2.id()).await.unwrap();
 
-        let artists = db.get_melonbooks_artists().await.unwrap();
+        let artists = db.get_melonbooks_artists(user_id).await.unwrap();
         assert_eq!(artists.len(), 2);
         assert!(artists.iter().find(|a| a.name().eq(artist_args().name())).is_some());
         assert!(artists.iter().find(|a| a.name().eq(artist_args2().name())).is_some());
@@ -697,9 +1688,10 @@ mod test {
     async fn test_create_melonbooks_product_with_existing_artist() {
         let db = Sqlite::new_in_memory();
         db.setup().unwrap();
-        db.follow_melonbooks_artist(&artist_args()).await.unwrap();
+        let user_id = default_user_id(&db).await;
+        db.follow_melonbooks_artist(user_id, &artist_args()).await.unwrap();
         let args = product_args();
-        let artists = db.get_melonbooks_artists().await.unwrap();
+        let artists = db.get_melonbooks_artists(user_id).await.unwrap();
         let product = db.create_melonbooks_product(&args).await.unwrap();
 
         assert_eq!(artists.len(), 1);
@@ -734,6 +1726,75 @@ mod test {
         assert_eq!(products.get(0).unwrap().availability(), Availability::NotAvailable);
     }
 
+    #[tokio::test]
+    async fn test_set_melonbooks_product_image() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let product = db.create_melonbooks_product(&product_args()).await.unwrap();
+        assert_eq!(product.image_hash(), None);
+        assert_eq!(product.image_phash(), None);
+
+        let hash = "a".repeat(64);
+        let image = CachedImage::new(hash.clone(), Some(0xF000_0000_0000_0001));
+        db.set_product_image(SITE, product.id(), &image).await.unwrap();
+        let loaded = db.get_melonbooks_product(product.id()).await.unwrap();
+        assert_eq!(loaded.image_hash(), Some(hash.as_str()));
+        assert_eq!(loaded.image_phash(), Some(0xF000_0000_0000_0001));
+        assert!(matches!(db.set_product_image(SITE, product.id() + 1, &image).await, Err(SetProductImageError::ProductMissing { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_update_melonbooks_product_sets_date_restocked() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let product1 = db.create_melonbooks_product(&product_args()).await.unwrap();
+        let product2 = db.create_melonbooks_product(&product_args2()).await.unwrap();
+        assert_eq!(product1.date_restocked(), None);
+
+        let product1 = db.update_melonbooks_product(&UpdateProductArgs::new(product1.url().to_owned(), Availability::NotAvailable)).await.unwrap();
+        assert_eq!(product1.date_restocked(), None);
+        let product1 = db.update_melonbooks_product(&UpdateProductArgs::new(product1.url().to_owned(), Availability::Available)).await.unwrap();
+        let date_restocked = product1.date_restocked().unwrap();
+        let product1 = db.update_melonbooks_product(&UpdateProductArgs::new(product1.url().to_owned(), Availability::Available)).await.unwrap();
+        assert_eq!(product1.date_restocked(), Some(date_restocked));
+
+        let query = ProductQuery::new(PageRequest::new(1, 10), ProductSort::DateChanged, SortDirection::Desc);
+        let page = db.get_melonbooks_products_page(&query).await.unwrap();
+        assert_eq!(page.items().iter().map(|p| p.id()).collect::<Vec<_>>(), vec![product1.id(), product2.id()]);
+    }
+
+    #[tokio::test]
+    async fn test_get_melonbooks_product_history() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        let product = db.create_melonbooks_product(&product_args()).await.unwrap();
+        db.update_melonbooks_product(&UpdateProductArgs::new(product.url().to_owned(), Availability::Available)).await.unwrap();
+        db.update_melonbooks_product(&UpdateProductArgs::new(product.url().to_owned(), Availability::NotAvailable)).await.unwrap();
+        db.add_notifications(SITE, user_id, NotificationKind::NewProduct, &[product.id()]).await.unwrap();
+
+        let loaded = db.get_melonbooks_product(product.id()).await.unwrap();
+        assert_eq!(loaded.availability(), Availability::NotAvailable);
+        let history = db.get_melonbooks_product_history(product.id()).await.unwrap();
+        let availabilities = history.iter()
+            .filter_map(|e| match e.change() { ProductChange::Availability(a) => Some(a.clone()), _ => None })
+            .collect::<Vec<_>>();
+        assert_eq!(availabilities, vec![Availability::Available, Availability::NotAvailable]);
+        let prices = history.iter()
+            .filter_map(|e| match e.change() { ProductChange::Price(p) => Some(p.clone()), _ => None })
+            .collect::<Vec<_>>();
+        assert_eq!(prices, vec![Some("12.500".to_owned())]);
+        let notifications = history.iter()
+            .filter(|e| matches!(e.change(), ProductChange::Notification { .. }))
+            .map(|e| e.change().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(notifications, vec![ProductChange::Notification { kind: NotificationKind::NewProduct, username: DEFAULT_USERNAME.to_owned() }]);
+        assert!(history.windows(2).all(|w| w[0].date() <= w[1].date()));
+
+        assert!(matches!(db.get_melonbooks_product(product.id() + 1).await, Err(GetProductError::ProductMissing { .. })));
+        assert!(matches!(db.get_melonbooks_product_history(product.id() + 1).await, Err(GetProductError::ProductMissing { .. })));
+    }
+
     #[tokio::test]
     async fn test_get_melonbooks_products() {
         let db = Sqlite::new_in_memory();
@@ -753,11 +1814,12 @@ mod test {
     async fn test_get_melonbooks_products_by_artist() {
         let db = Sqlite::new_in_memory();
         db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
         let args1 = product_args();
         let args2 = product_args2();
         let product1 = db.create_melonbooks_product(&args1).await.unwrap();
         let product2 = db.create_melonbooks_product(&args2).await.unwrap();
-        let artists = db.get_melonbooks_artists().await.unwrap();
+        let artists = db.get_melonbooks_artists(user_id).await.unwrap();
         let artist1 = artists.iter().find(|a| a.name().eq(artist_args().name())).unwrap();
         let artist2 = artists.iter().find(|a| a.name().eq(artist_args2().name())).unwrap();
 
@@ -772,6 +1834,99 @@ mod test {
         assert!(products.iter().filter(|p| p.id().eq(&product2.id())).next().is_some());
     }
     
+    #[tokio::test]
+    async fn test_get_melonbooks_products_loads_relations() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let args1 = product_args();
+        let args2 = CreateProductArgs::new(
+            "https://kantoku.moe".to_owned(),
+            "kantoku_title".to_owned(),
+            None,
+            vec![artist_args2().name().to_owned()],
+            "https://kantoku.png".to_owned(),
+            "category2".to_owned(),
+            vec!["tag1".to_owned(), "tag2".to_owned()],
+            vec!["flag1".to_owned()],
+            None,
+            Availability::NotAvailable
+        );
+        let product1 = db.create_melonbooks_product(&args1).await.unwrap();
+        let product2 = db.create_melonbooks_product(&args2).await.unwrap();
+
+        let products = db.get_melonbooks_products().await.unwrap();
+        assert_eq!(products.len(), 2);
+        assert!(products.contains(&product1));
+        assert!(products.contains(&product2));
+        let product = products.iter().find(|p| p.id() == product2.id()).unwrap();
+        assert_eq!(product.artists().iter().map(|a| a.name()).collect::<Vec<_>>(), vec![artist_args2().name()]);
+        assert_eq!(product.category(), "category2");
+        assert_eq!(product.tags(), &["tag1".to_owned(), "tag2".to_owned()]);
+        assert_eq!(product.flags(), &["flag1".to_owned()]);
+    }
+
+    #[tokio::test]
+    async fn test_get_melonbooks_products_page() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let product1 = db.create_melonbooks_product(&product_args()).await.unwrap();
+        let product2 = db.create_melonbooks_product(&product_args2()).await.unwrap();
+
+        let query = ProductQuery::new(PageRequest::new(1, 1), ProductSort::Title, SortDirection::Asc);
+        let page = db.get_melonbooks_products_page(&query).await.unwrap();
+        assert_eq!(page.total_items(), 2);
+        assert_eq!(page.total_pages(), 2);
+        assert_eq!(page.items().iter().map(|p| p.id()).collect::<Vec<_>>(), vec![product2.id()]);
+
+        let query = ProductQuery::new(PageRequest::new(2, 1), ProductSort::Title, SortDirection::Asc);
+        let page = db.get_melonbooks_products_page(&query).await.unwrap();
+        assert_eq!(page.items().iter().map(|p| p.id()).collect::<Vec<_>>(), vec![product1.id()]);
+
+        let query = ProductQuery::new(PageRequest::new(1, 10), ProductSort::Title, SortDirection::Desc);
+        let page = db.get_melonbooks_products_page(&query).await.unwrap();
+        assert_eq!(page.items().iter().map(|p| p.id()).collect::<Vec<_>>(), vec![product1.id(), product2.id()]);
+    }
+
+    #[tokio::test]
+    async fn test_get_melonbooks_products_page_filtered() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        let product1 = db.create_melonbooks_product(&product_args()).await.unwrap();
+        let product2 = db.create_melonbooks_product(&product_args2()).await.unwrap();
+        let artist2 = db.get_melonbooks_artists(user_id).await.unwrap().into_iter().find(|a| a.name().eq(artist_args2().name())).unwrap();
+
+        let query = ProductQuery::default().with_category(Some("category".to_owned()));
+        let page = db.get_melonbooks_products_page(&query).await.unwrap();
+        assert_eq!(page.items().iter().map(|p| p.id()).collect::<Vec<_>>(), vec![product1.id()]);
+
+        let query = ProductQuery::default().with_availability(Some(Availability::NotAvailable));
+        let page = db.get_melonbooks_products_page(&query).await.unwrap();
+        assert_eq!(page.items().iter().map(|p| p.id()).collect::<Vec<_>>(), vec![product2.id()]);
+
+        let query = ProductQuery::default().with_artist_id(Some(artist2.id()));
+        let page = db.get_melonbooks_products_page(&query).await.unwrap();
+        assert_eq!(page.total_items(), 1);
+        assert_eq!(page.items().first().unwrap().id(), product2.id());
+
+        let tomorrow = Utc::now().date_naive() + Days::new(1);
+        let query = ProductQuery::default().with_date_added_range(Some(tomorrow), None);
+        let page = db.get_melonbooks_products_page(&query).await.unwrap();
+        assert_eq!(page.total_items(), 0);
+        assert_eq!(page.total_pages(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_melonbooks_categories() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        db.create_melonbooks_product(&product_args()).await.unwrap();
+        db.create_melonbooks_product(&product_args2()).await.unwrap();
+
+        let categories = db.get_melonbooks_categories().await.unwrap();
+        assert_eq!(categories, vec!["category".to_owned(), "category2".to_owned()]);
+    }
+
     #[tokio::test]
     async fn test_skip_products() {
         let db = Sqlite::new_in_memory();
@@ -788,6 +1943,7 @@ mod test {
     async fn test_follow_deletes_skip_products() {
         let db = Sqlite::new_in_memory();
         db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
 
         db.add_melonbooks_skipping_url(product_args().url(), product_args().artists()).await.unwrap();
 
@@ -795,7 +1951,7 @@ mod test {
         assert_eq!(urls.len(), 1);
         assert_eq!(urls.get(0).unwrap(), product_args().url());
         
-        db.follow_melonbooks_artist(&artist_args()).await.unwrap();
+        db.follow_melonbooks_artist(user_id, &artist_args()).await.unwrap();
         
         let urls = db.get_melonbooks_skipping_urls().await.unwrap();
         assert_eq!(urls.len(), 0);
@@ -805,26 +1961,118 @@ mod test {
     async fn test_add_title_skip_sequences() {
         let db = Sqlite::new_in_memory();
         db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
 
-        db.add_melonbooks_title_skip_sequence("abc").await.unwrap();
+        db.add_melonbooks_title_skip_sequence(user_id, "abc").await.unwrap();
         
-        let sequences = db.get_melonbooks_title_skip_sequences().await.unwrap();
+        let sequences = db.get_melonbooks_title_skip_sequences(user_id).await.unwrap();
         
         assert_eq!(sequences.len(), 1);
         assert_eq!(sequences.get(0).unwrap(), "abc");
+        assert!(matches!(db.add_melonbooks_title_skip_sequence(user_id, "abc").await, Err(AddTitleSkipSequenceError::DuplicateSequence { .. })));
     }
 
     #[tokio::test]
     async fn test_delete_title_skip_sequences() {
         let db = Sqlite::new_in_memory();
         db.setup().unwrap();
-        db.add_melonbooks_title_skip_sequence("abc").await.unwrap();
-        let sequences = db.get_melonbooks_title_skip_sequences().await.unwrap();
+        let user_id = default_user_id(&db).await;
+        db.add_melonbooks_title_skip_sequence(user_id, "abc").await.unwrap();
+        let sequences = db.get_melonbooks_title_skip_sequences(user_id).await.unwrap();
         assert_eq!(sequences.len(), 1);
         
-        db.delete_melonbooks_title_skip_sequence("abc").await.unwrap();
-        let sequences = db.get_melonbooks_title_skip_sequences().await.unwrap();
+        db.delete_melonbooks_title_skip_sequence(user_id, "abc").await.unwrap();
+        let sequences = db.get_melonbooks_title_skip_sequences(user_id).await.unwrap();
         assert_eq!(sequences.len(), 0);
+        assert!(matches!(db.delete_melonbooks_title_skip_sequence(user_id, "abc").await, Err(DeleteTitleSkipSequenceError::UnknownSequence { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_title_skip_sequences_per_user() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let users = db.setup_users(DEFAULT_USERNAME, &["alice".to_owned()]).await.unwrap();
+        let (alice, default) = (users.first().unwrap(), users.last().unwrap());
+        db.add_melonbooks_title_skip_sequence(alice.id(), "abc").await.unwrap();
+        db.add_melonbooks_title_skip_sequence(default.id(), "abc").await.unwrap();
+        db.add_melonbooks_title_skip_sequence(default.id(), "def").await.unwrap();
+
+        db.delete_melonbooks_title_skip_sequence(alice.id(), "abc").await.unwrap();
+        assert!(matches!(db.delete_melonbooks_title_skip_sequence(alice.id(), "def").await, Err(DeleteTitleSkipSequenceError::UnknownSequence { .. })));
+        assert_eq!(db.get_melonbooks_title_skip_sequences(alice.id()).await.unwrap().len(), 0);
+        assert_eq!(db.get_melonbooks_title_skip_sequences(default.id()).await.unwrap(), vec!["abc".to_owned(), "def".to_owned()]);
+    }
+
+    #[tokio::test]
+    async fn test_search_melonbooks_products() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let product1 = db.create_melonbooks_product(&product_args()).await.unwrap();
+        let product2 = db.create_melonbooks_product(&product_args2()).await.unwrap();
+
+        let results = db.search_melonbooks_products("mafuyu").await.unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results.first().unwrap().product().id(), product1.id());
+        assert!(results.first().unwrap().highlight("title").unwrap().has_match());
+
+        let results = db.search_melonbooks_products("kantoku").await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results.first().unwrap().product().id(), product2.id());
+        assert!(results.first().unwrap().highlight("artists").unwrap().has_match());
+        assert!(!results.first().unwrap().highlight("circle").unwrap().has_match());
+
+        let error = db.search_melonbooks_products("ma").await.unwrap_err();
+        assert!(matches!(error, SearchProductsError::QueryTooShort { .. }));
+        let error = db.search_melonbooks_products("kantoku ma").await.unwrap_err();
+        assert!(matches!(error, SearchProductsError::QueryTooShort { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_search_melonbooks_products_page() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        db.create_melonbooks_product(&product_args()).await.unwrap();
+        db.create_melonbooks_product(&product_args2()).await.unwrap();
+        let results = db.search_melonbooks_products("mafuyu").await.unwrap();
+
+        let page = db.search_melonbooks_products_page("mafuyu", PageRequest::new(1, 1)).await.unwrap();
+        assert_eq!(page.total_items(), 2);
+        assert_eq!(page.items().iter().map(|r| r.product().id()).collect::<Vec<_>>(), vec![results[0].product().id()]);
+
+        let page = db.search_melonbooks_products_page("mafuyu", PageRequest::new(2, 1)).await.unwrap();
+        assert_eq!(page.items().iter().map(|r| r.product().id()).collect::<Vec<_>>(), vec![results[1].product().id()]);
+    }
+
+    #[tokio::test]
+    async fn test_get_melonbooks_artists_page() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        db.follow_melonbooks_artist(user_id, &artist_args()).await.unwrap();
+        db.create_melonbooks_product(&product_args2()).await.unwrap();
+
+        let page = db.get_melonbooks_artists_page(user_id, None, PageRequest::new(1, 1)).await.unwrap();
+        assert_eq!(page.total_items(), 2);
+        assert_eq!(page.items().iter().map(|a| a.name()).collect::<Vec<_>>(), vec![artist_args().name()]);
+
+        let page = db.get_melonbooks_artists_page(user_id, Some(true), PageRequest::new(1, 10)).await.unwrap();
+        assert_eq!(page.total_items(), 1);
+        assert!(page.items().iter().all(|a| a.following()));
+
+        let page = db.get_melonbooks_artists_page(user_id, Some(false), PageRequest::new(1, 10)).await.unwrap();
+        assert_eq!(page.items().iter().map(|a| a.name()).collect::<Vec<_>>(), vec![artist_args2().name()]);
+    }
+
+    #[tokio::test]
+    async fn test_update_melonbooks_product_keeps_search_entry() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let product = db.create_melonbooks_product(&product_args()).await.unwrap();
+        db.update_melonbooks_product(&UpdateProductArgs::new(product.url().to_owned(), Availability::NotAvailable)).await.unwrap();
+
+        let results = db.search_melonbooks_products("mafuyu_circle").await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results.first().unwrap().product().availability(), Availability::NotAvailable);
     }
 
     fn artist_args() -> ArtistArgs {
@@ -864,4 +2112,4 @@ mod test {
             Availability::NotAvailable
         )
     }
-}
\ No newline at end of file
+}
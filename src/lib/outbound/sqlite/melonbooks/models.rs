@@ -1,9 +1,13 @@
 use crate::domain::melonbooks::models::artist::Artist;
-use crate::domain::melonbooks::models::availability::Availability;
-use crate::domain::melonbooks::models::product::Product;
+use crate::domain::availability::Availability;
+use crate::domain::melonbooks::models::product::{Product, ProductHistoryEntry};
+use crate::domain::availability_stats::AvailabilityEvent;
+use crate::domain::product_history::{NotificationKind, ProductChange};
+use crate::domain::schedule::Schedule;
 use crate::outbound::sqlite::schema;
 use chrono::NaiveDateTime;
-use diesel::{AsChangeset, Identifiable, Insertable, Queryable, Selectable, ExpressionMethods};
+use diesel::sql_types::{Double, Integer, Text};
+use diesel::{AsChangeset, Identifiable, Insertable, Queryable, QueryableByName, Selectable, ExpressionMethods};
 
 #[derive(Debug, Queryable, Selectable, Identifiable, AsChangeset)]
 #[diesel(table_name = schema::melonbooks_product)]
@@ -19,6 +23,9 @@ pub struct ProductRow {
     pub price: Option<String>,
     #[diesel(serialize_as = String, deserialize_as = String)]
     pub availability: Availability,
+    pub date_restocked: Option<NaiveDateTime>,
+    pub image_hash: Option<String>,
+    pub image_phash: Option<i64>,
 }
 
 #[derive(Debug, Insertable)]
@@ -35,6 +42,52 @@ pub struct ProductRowInsert<'a> {
     pub availability: Availability,
 }
 
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::melonbooks_availability_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct AvailabilityEventRow {
+    pub date_added: NaiveDateTime,
+    pub product_id: i32,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub availability: Availability,
+    pub previous_availability: Option<String>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::melonbooks_availability_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct AvailabilityEventRowInsert {
+    pub product_id: i32,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub availability: Availability,
+    pub previous_availability: Option<String>,
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::melonbooks_price_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct PriceEventRow {
+    pub date_added: NaiveDateTime,
+    pub price: Option<String>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::melonbooks_price_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct PriceEventRowInsert<'a> {
+    pub product_id: i32,
+    pub price: Option<&'a str>,
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::melonbooks_notification)]
+#[diesel(treat_none_as_null = true)]
+pub struct NotificationRow {
+    pub date_added: NaiveDateTime,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub kind: NotificationKind,
+}
+
 #[derive(Debug, Queryable, Selectable, Identifiable, AsChangeset)]
 #[diesel(table_name = schema::melonbooks_artist)]
 #[diesel(treat_none_as_null = true)]
@@ -44,6 +97,8 @@ pub struct ArtistRow {
     pub name: String,
     pub following: bool,
     pub date_followed: Option<NaiveDateTime>,
+    pub schedule: Option<String>,
+    pub date_scraped: Option<NaiveDateTime>,
 }
 
 #[derive(Debug, Insertable)]
@@ -55,6 +110,24 @@ pub struct ArtistRowInsert<'a> {
     pub date_followed: Option<NaiveDateTime>,
 }
 
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::melonbooks_artist_follower)]
+#[diesel(treat_none_as_null = true)]
+pub struct ArtistFollowerRow {
+    pub artist_id: i32,
+    pub user_id: i32,
+    pub date_followed: NaiveDateTime,
+    pub schedule: Option<String>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::melonbooks_artist_follower)]
+#[diesel(treat_none_as_null = true)]
+pub struct ArtistFollowerRowInsert {
+    pub artist_id: i32,
+    pub user_id: i32,
+}
+
 #[derive(Debug, Queryable, Selectable)]
 #[diesel(table_name = schema::melonbooks_category)]
 #[diesel(treat_none_as_null = true)]
@@ -148,18 +221,80 @@ pub struct TitleSkipSequenceRow {
 #[diesel(table_name = schema::melonbooks_title_skip_sequence)]
 #[diesel(treat_none_as_null = true)]
 pub struct TitleSkipSequenceRowInsert<'a> {
+    pub user_id: i32,
     pub sequence: &'a str,
 }
 
+#[derive(Debug, QueryableByName)]
+pub struct ProductSearchRow {
+    #[diesel(sql_type = Integer)]
+    pub id: i32,
+    #[diesel(sql_type = Double)]
+    pub rank: f64,
+    #[diesel(sql_type = Text)]
+    pub title: String,
+    #[diesel(sql_type = Text)]
+    pub circle: String,
+    #[diesel(sql_type = Text)]
+    pub artists: String,
+    #[diesel(sql_type = Text)]
+    pub tags: String,
+}
+
 impl ArtistRow {
     pub fn into_domain(self) -> Artist {
-        Artist::new(self.id, self.date_added.and_utc(), self.name, self.following, self.date_followed.map(|d| d.and_utc()))
+        let schedule = self.schedule();
+        Artist::new(self.id, self.date_added.and_utc(), self.name, self.following, self.date_followed.map(|d| d.and_utc()), schedule)
     }
+
+    /// Uses the follow of a single user instead of whether anybody follows the artist, with the schedule the user chose.
+    pub fn into_domain_for(self, follower: Option<&ArtistFollowerRow>) -> Artist {
+        let schedule = follower.and_then(|f| parse_schedule(f.schedule.as_deref()));
+        Artist::new(self.id, self.date_added.and_utc(), self.name, follower.is_some(), follower.map(|f| f.date_followed.and_utc()), schedule)
+    }
+
+    fn schedule(&self) -> Option<Schedule> {
+        parse_schedule(self.schedule.as_deref())
+    }
+}
+
+/// Schedules are validated before they are stored.
+fn parse_schedule(schedule: Option<&str>) -> Option<Schedule> {
+    schedule.and_then(|s| Schedule::parse(s).ok())
 }
 
 impl ProductRow {
     pub fn into_domain(self, artists: Vec<Artist>, category: String, tags: Vec<String>, flags: Vec<String>) -> Product {
         Product::new(self.id, self.date_added.and_utc(), self.url, self.title, self.circle, artists, self.image_url, category, tags, flags, self.price, self.availability)
+            .with_date_restocked(self.date_restocked.map(|d| d.and_utc()))
+            .with_image_hash(self.image_hash)
+            .with_image_phash(self.image_phash.map(|h| h as u64))
+    }
+}
+
+impl AvailabilityEventRow {
+    pub fn into_domain(self) -> ProductHistoryEntry {
+        ProductHistoryEntry::new(self.date_added.and_utc(), ProductChange::Availability(self.availability))
+    }
+
+    pub fn into_stats_event(self) -> Result<AvailabilityEvent, strum::ParseError> {
+        let was_available = self.previous_availability
+            .map(Availability::try_from)
+            .transpose()?
+            .map(|a| a.is_available());
+        Ok(AvailabilityEvent::new(self.product_id, self.date_added.and_utc(), was_available, self.availability.is_available()))
+    }
+}
+
+impl PriceEventRow {
+    pub fn into_domain(self) -> ProductHistoryEntry {
+        ProductHistoryEntry::new(self.date_added.and_utc(), ProductChange::Price(self.price))
+    }
+}
+
+impl NotificationRow {
+    pub fn into_domain(self, username: String) -> ProductHistoryEntry {
+        ProductHistoryEntry::new(self.date_added.and_utc(), ProductChange::Notification { kind: self.kind, username })
     }
 }
 
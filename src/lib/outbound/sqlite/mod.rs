@@ -1,6 +1,6 @@
 use anyhow::{anyhow, Context};
 use diesel::connection::SimpleConnection;
-use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::r2d2::{ConnectionManager, CustomizeConnection, Pool};
 use diesel::SqliteConnection;
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 use log::info;
@@ -8,54 +8,166 @@ use r2d2::PooledConnection;
 use std::time::Duration;
 
 mod amiami;
+mod booth;
+mod digital;
+mod duplicates;
+mod figure;
+mod mandarake;
 mod melonbooks;
+mod product_index;
 mod schema;
+mod search;
+mod site;
+mod site_schema;
+mod surugaya;
+#[cfg(test)]
+mod test_util;
+mod toranoana;
+mod user;
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("resources/migrations");
 
+const READER_POOL_SIZE: u32 = 4;
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+const IN_MEMORY_PATH: &str = ":memory:";
+
+type SqliteConnectionPool = Pool<ConnectionManager<SqliteConnection>>;
+type SqlitePooledConnection = PooledConnection<ConnectionManager<SqliteConnection>>;
+
+#[derive(Debug)]
+struct ConnectionOptions {
+    read_only: bool,
+}
+
+impl CustomizeConnection<SqliteConnection, diesel::r2d2::Error> for ConnectionOptions {
+    fn on_acquire(&self, connection: &mut SqliteConnection) -> Result<(), diesel::r2d2::Error> {
+        connection.batch_execute(&format!(
+            "PRAGMA busy_timeout = {}; PRAGMA foreign_keys = ON; PRAGMA synchronous = NORMAL; PRAGMA query_only = {};",
+            BUSY_TIMEOUT.as_millis(),
+            self.read_only,
+        )).map_err(diesel::r2d2::Error::QueryError)
+    }
+}
+
+/// Reads go through a pool of read-only connections while all writes share a single
+/// connection, which with WAL lets page loads proceed during a scrape.
 #[derive(Debug, Clone)]
 pub struct Sqlite {
-    pool: Pool<ConnectionManager<SqliteConnection>>
+    reader: SqliteConnectionPool,
+    writer: SqliteConnectionPool,
 }
 
 impl Sqlite {
     pub fn new(path: &str) -> Result<Sqlite, anyhow::Error> {
+        if path == IN_MEMORY_PATH {
+            // every in-memory connection is its own database, so readers and writer share one
+            let pool = Self::build_pool(path, 1, false)?;
+            return Ok(Sqlite { reader: pool.clone(), writer: pool });
+        }
+        let writer = Self::build_pool(path, 1, false)?;
+        writer.get()?.batch_execute("PRAGMA journal_mode = WAL;")
+            .with_context(|| format!("cannot enable WAL for database at {}", path))?;
+        let reader = Self::build_pool(path, READER_POOL_SIZE, true)?;
+        Ok(Sqlite { reader, writer })
+    }
+
+    fn build_pool(path: &str, size: u32, read_only: bool) -> Result<SqliteConnectionPool, anyhow::Error> {
         let manager = ConnectionManager::<SqliteConnection>::new(path);
         let pool = Pool::builder()
-            .max_size(1)
+            .max_size(size)
             .connection_timeout(Duration::from_secs(5))
             .max_lifetime(None)
+            .connection_customizer(Box::new(ConnectionOptions { read_only }))
             .build(manager)
             .with_context(|| format!("failed to create pool for database at {}", path))?;
-        Ok(Sqlite { pool })
+        Ok(pool)
     }
-    
+
     #[cfg(test)]
     pub fn new_in_memory() -> Sqlite {
-        Sqlite::new(":memory:").unwrap()
+        Sqlite::new(IN_MEMORY_PATH).unwrap()
     }
 
     pub fn setup(&self) -> Result<(), anyhow::Error> {
         info!("setting up database");
-        let mut connection = self.pool.get()?;
-        connection.batch_execute("PRAGMA foreign_keys = ON;")?;
+        let mut connection = self.writer.get()?;
         connection.run_pending_migrations(MIGRATIONS)
             .map_err(|e| anyhow!(e))?;
         info!("database up to date");
         Ok(())
     }
-    
-    fn get_connection(&self) -> Result<PooledConnection<ConnectionManager<SqliteConnection>>, anyhow::Error> {
-       self.pool.get().with_context(|| "cannot get db connection")
+
+    /// Runs `f` with a read-only connection on the blocking thread pool.
+    async fn read<T, E, F>(&self, f: F) -> Result<T, E>
+    where
+        T: Send + 'static,
+        E: From<anyhow::Error> + Send + 'static,
+        F: FnOnce(&Sqlite, &mut SqlitePooledConnection) -> Result<T, E> + Send + 'static,
+    {
+        let db = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = db.reader.get().with_context(|| "cannot get db reader connection")?;
+            f(&db, &mut connection)
+        }).await.map_err(|e| anyhow!(e))?
+    }
+
+    /// Runs `f` with the single writer connection on the blocking thread pool.
+    async fn write<T, E, F>(&self, f: F) -> Result<T, E>
+    where
+        T: Send + 'static,
+        E: From<anyhow::Error> + Send + 'static,
+        F: FnOnce(&Sqlite, &mut SqlitePooledConnection) -> Result<T, E> + Send + 'static,
+    {
+        let db = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = db.writer.get().with_context(|| "cannot get db writer connection")?;
+            f(&db, &mut connection)
+        }).await.map_err(|e| anyhow!(e))?
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use diesel::{QueryableByName, RunQueryDsl};
     #[tokio::test]
     async fn test_setup() {
         let db = Sqlite::new("./data/moe-scraper.sqlite").unwrap();
         db.setup().unwrap();
     }
+
+    #[tokio::test]
+    async fn test_reader_and_writer_pools() {
+        let path = std::env::temp_dir().join(format!("moe-scraper-test-{}.sqlite", std::process::id()));
+        let db = Sqlite::new(path.to_str().unwrap()).unwrap();
+        db.setup().unwrap();
+
+        let journal_mode = db.read(|_, connection| -> Result<String, anyhow::Error> {
+            #[derive(QueryableByName)]
+            struct JournalMode {
+                #[diesel(sql_type = diesel::sql_types::Text)]
+                journal_mode: String,
+            }
+            let mode = diesel::sql_query("PRAGMA journal_mode").get_result::<JournalMode>(connection)?;
+            Ok(mode.journal_mode)
+        }).await.unwrap();
+        assert_eq!(journal_mode, "wal");
+
+        let read_only_write = db.read(|_, connection| -> Result<(), anyhow::Error> {
+            connection.batch_execute("INSERT INTO melonbooks_title_skip_sequence (user_id, sequence) SELECT id, 'test' FROM app_user")?;
+            Ok(())
+        }).await;
+        assert!(read_only_write.is_err());
+
+        let write = db.write(|_, connection| -> Result<(), anyhow::Error> {
+            connection.batch_execute("INSERT INTO melonbooks_title_skip_sequence (user_id, sequence) SELECT id, 'test' FROM app_user")?;
+            Ok(())
+        }).await;
+        assert!(write.is_ok());
+
+        drop(db);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
 }
\ No newline at end of file
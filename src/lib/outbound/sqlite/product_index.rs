@@ -0,0 +1,276 @@
+use crate::domain::availability::Availability;
+use crate::domain::pagination::{Page, PageRequest};
+use crate::domain::product_index::models::product::{parse_price, GetIndexedProductsError, IndexFilter, IndexSort, IndexedProduct};
+use crate::domain::product_index::models::target::{FollowTarget, GetFollowTargetsError, TargetId};
+use crate::domain::product_index::ports::ProductIndexRepository;
+use crate::domain::site::{find_site, Site, SITES};
+use crate::outbound::sqlite::site_schema;
+use crate::outbound::sqlite::site_schema::site_product::dsl as product_dsl;
+use crate::outbound::sqlite::site_schema::site_product_event::dsl as product_event_dsl;
+use crate::outbound::sqlite::site_schema::site_product_target::dsl as product_target_dsl;
+use crate::outbound::sqlite::site_schema::site_target::dsl as target_dsl;
+use crate::outbound::sqlite::site_schema::site_target_follower::dsl as target_follower_dsl;
+use crate::outbound::sqlite::{Sqlite, SqlitePooledConnection};
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use diesel::dsl::{exists, max, sql};
+use diesel::prelude::*;
+use diesel::sql_types::Timestamp;
+use diesel::sqlite::Sqlite as SqliteBackend;
+use std::collections::HashMap;
+
+const DATE_CHANGED_SORT_EXPRESSION: &str = "COALESCE(date_restocked, date_added)";
+
+impl Sqlite {
+    fn filtered_index_query(user_id: i32, filter: &IndexFilter) -> site_schema::site_product::BoxedQuery<'static, SqliteBackend> {
+        let mut products = product_dsl::site_product.into_boxed();
+        if let Some(site) = filter.site() {
+            products = products.filter(product_dsl::site.eq(site.id()));
+        }
+        if let Some(target) = filter.target() {
+            products = products
+                .filter(product_dsl::site.eq(target.site().id()))
+                .filter(product_dsl::product_id.eq_any(
+                    product_target_dsl::site_product_target
+                        .filter(product_target_dsl::site.eq(target.site().id()))
+                        .filter(product_target_dsl::target_id.eq(target.id()))
+                        .select(product_target_dsl::product_id)
+                ));
+        }
+        if filter.followed() {
+            products = products.filter(exists(
+                product_target_dsl::site_product_target
+                    .inner_join(target_follower_dsl::site_target_follower.on(
+                        target_follower_dsl::site.eq(product_target_dsl::site).and(target_follower_dsl::target_id.eq(product_target_dsl::target_id))
+                    ))
+                    .filter(product_target_dsl::site.eq(product_dsl::site))
+                    .filter(product_target_dsl::product_id.eq(product_dsl::product_id))
+                    .filter(target_follower_dsl::user_id.eq(user_id))
+            ));
+        }
+        products
+    }
+
+    fn get_follow_target_rows(&self, connection: &mut SqlitePooledConnection, user_id: i32) -> Result<Vec<FollowTarget>, anyhow::Error> {
+        let rows = target_dsl::site_target
+            .inner_join(target_follower_dsl::site_target_follower.on(
+                target_follower_dsl::site.eq(target_dsl::site).and(target_follower_dsl::target_id.eq(target_dsl::target_id))
+            ))
+            .filter(target_follower_dsl::user_id.eq(user_id))
+            .select((target_dsl::site, target_dsl::target_id, target_dsl::name))
+            .load::<(String, i32, String)>(connection)
+            .with_context(|| "cannot load followed targets")?;
+        rows.into_iter()
+            .map(|(site, id, name)| {
+                let site = find_site(&site).ok_or_else(|| anyhow!("unknown site '{}'", site))?;
+                Ok(FollowTarget::new(TargetId::new(site, id), name))
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl ProductIndexRepository for Sqlite {
+    async fn get_indexed_products(&self, user_id: i32, filter: &IndexFilter, page: PageRequest) -> Result<Page<IndexedProduct>, GetIndexedProductsError> {
+        let filter = filter.clone();
+        self.read(move |_, connection| {
+            let total = Self::filtered_index_query(user_id, &filter)
+                .count()
+                .get_result::<i64>(connection)
+                .with_context(|| "cannot count products")?;
+            let products = Self::filtered_index_query(user_id, &filter);
+            let products = match filter.sort() {
+                IndexSort::DateAdded => products.order_by((product_dsl::date_added.desc(), product_dsl::site.asc(), product_dsl::product_id.desc())),
+                IndexSort::DateChanged => products.order_by((sql::<Timestamp>(DATE_CHANGED_SORT_EXPRESSION).desc(), product_dsl::site.asc(), product_dsl::product_id.desc())),
+            };
+            let products = products
+                .select((
+                    product_dsl::site,
+                    product_dsl::product_id,
+                    product_dsl::date_added,
+                    product_dsl::url,
+                    product_dsl::title,
+                    product_dsl::image_url,
+                    product_dsl::image_hash,
+                    product_dsl::price,
+                    product_dsl::availability,
+                    product_dsl::date_restocked,
+                ))
+                .limit(page.page_size() as i64)
+                .offset(page.offset())
+                .load::<(String, i32, NaiveDateTime, String, String, String, Option<String>, Option<String>, String, Option<NaiveDateTime>)>(connection)
+                .with_context(|| "cannot load products")?
+                .into_iter()
+                .map(|(site, id, date_added, url, title, image_url, image_hash, price, availability, date_restocked)| {
+                    let site = find_site(&site).ok_or_else(|| anyhow!("unknown site '{}'", site))?;
+                    let availability = Availability::try_from(availability.as_str())
+                        .with_context(|| format!("invalid availability of {} product with id '{}'", site.id(), id))?;
+                    let price = price.as_deref().and_then(parse_price);
+                    Ok(IndexedProduct::new(site, id, date_added.and_utc(), url, title, image_url, image_hash, price, availability)
+                        .with_date_restocked(date_restocked.map(|d| d.and_utc())))
+                })
+                .collect::<Result<Vec<_>, anyhow::Error>>()?;
+            Ok(Page::new(products, page, total))
+        }).await
+    }
+
+    async fn get_follow_targets(&self, user_id: i32) -> Result<Vec<FollowTarget>, GetFollowTargetsError> {
+        self.read(move |db, connection| {
+            let mut targets = db.get_follow_target_rows(connection, user_id)?;
+            targets.sort_by_key(|t| (SITES.iter().position(|s| *s == t.site()), t.name().to_lowercase()));
+            Ok(targets)
+        }).await
+    }
+
+    async fn get_dates_updated(&self, site: Site, product_ids: &[i32]) -> Result<HashMap<i32, DateTime<Utc>>, GetIndexedProductsError> {
+        let product_ids = product_ids.to_vec();
+        self.read(move |_, connection| {
+            let dates = product_event_dsl::site_product_event
+                .filter(product_event_dsl::site.eq(site.id()))
+                .filter(product_event_dsl::product_id.eq_any(&product_ids))
+                .group_by(product_event_dsl::product_id)
+                .select((product_event_dsl::product_id, max(product_event_dsl::date_added)))
+                .load::<(i32, Option<NaiveDateTime>)>(connection)
+                .with_context(|| format!("cannot load dates updated of {} products", site.id()))?
+                .into_iter()
+                .filter_map(|(id, date)| date.map(|d| (id, d.and_utc())))
+                .collect();
+            Ok(dates)
+        }).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::domain::amiami;
+    use crate::domain::amiami::models::product::{CreateProductArgs as AmiamiCreateProductArgs, UpdateProductArgs as AmiamiUpdateProductArgs};
+    use crate::domain::amiami::ports::AmiamiRepository;
+    use crate::domain::melonbooks;
+    use crate::domain::melonbooks::models::artist::ArtistArgs;
+    use crate::domain::melonbooks::models::product::CreateProductArgs as MelonbooksCreateProductArgs;
+    use crate::domain::melonbooks::ports::MelonbooksRepository;
+    use crate::outbound::sqlite::test_util::default_user_id;
+    use chrono::NaiveDate;
+
+    #[tokio::test]
+    async fn test_get_indexed_products() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        db.follow_melonbooks_artist(user_id, &ArtistArgs::new("mafuyu".to_owned())).await.unwrap();
+        let artist_id = db.get_melonbooks_artists(user_id).await.unwrap()[0].id();
+        let followed_product = db.create_melonbooks_product(&melonbooks_product_args("https://mafuyu.moe", "mafuyu")).await.unwrap();
+        let other_product = db.create_melonbooks_product(&melonbooks_product_args("https://kantoku.moe", "kantoku")).await.unwrap();
+        let amiami_product = db.create_amiami_product(&amiami_product_args()).await.unwrap();
+
+        let page = db.get_indexed_products(user_id, &IndexFilter::new(), PageRequest::new(1, 2)).await.unwrap();
+        assert_eq!(page.total_items(), 3);
+        assert_eq!(page.items().len(), 2);
+        assert!(page.items()[0].date_added() >= page.items()[1].date_added());
+        let first_page = page.items().to_vec();
+        let page = db.get_indexed_products(user_id, &IndexFilter::new(), PageRequest::new(2, 2)).await.unwrap();
+        assert_eq!(page.items().len(), 1);
+        assert_eq!(page.total_items(), 3);
+        assert!(first_page[1].date_added() >= page.items()[0].date_added());
+        let mut ids = first_page.iter().chain(page.items()).map(|p| (p.site(), p.product_id())).collect::<Vec<_>>();
+        ids.sort_by_key(|(site, id)| (site.id(), *id));
+        assert_eq!(ids, vec![(amiami::SITE, amiami_product.id()), (melonbooks::SITE, followed_product.id()), (melonbooks::SITE, other_product.id())]);
+
+        let page = db.get_indexed_products(user_id, &IndexFilter::new().with_site(Some(amiami::SITE)), PageRequest::default()).await.unwrap();
+        assert_eq!(page.total_items(), 1);
+        let product = &page.items()[0];
+        assert_eq!((product.site(), product.product_id()), (amiami::SITE, amiami_product.id()));
+        assert_eq!(product.price(), Some(18000));
+        assert_eq!(product.availability(), &Availability::Preorder);
+
+        let page = db.get_indexed_products(user_id, &IndexFilter::new().with_followed(true), PageRequest::default()).await.unwrap();
+        assert_eq!(page.total_items(), 1);
+        assert_eq!(page.items()[0].product_id(), followed_product.id());
+        assert_eq!(page.items()[0].price(), Some(1100));
+        let filter = IndexFilter::new().with_target(Some(TargetId::new(melonbooks::SITE, artist_id)));
+        let page = db.get_indexed_products(user_id, &filter, PageRequest::default()).await.unwrap();
+        assert_eq!(page.items().iter().map(|p| p.product_id()).collect::<Vec<_>>(), vec![followed_product.id()]);
+        let filter = IndexFilter::new().with_site(Some(melonbooks::SITE));
+        let page = db.get_indexed_products(user_id, &filter, PageRequest::default()).await.unwrap();
+        assert_eq!(page.items().iter().map(|p| p.product_id()).collect::<Vec<_>>(), vec![other_product.id(), followed_product.id()]);
+    }
+
+    #[tokio::test]
+    async fn test_get_indexed_products_by_date_changed() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        let restocked_product = db.create_amiami_product(&amiami_product_args()).await.unwrap();
+        let melonbooks_product = db.create_melonbooks_product(&melonbooks_product_args("https://mafuyu.moe", "mafuyu")).await.unwrap();
+        let update_args = |availability| AmiamiUpdateProductArgs::new(restocked_product.url().to_owned(), 20000, 18000, restocked_product.release_date(), availability);
+        db.update_amiami_product(&update_args(Availability::NotAvailable)).await.unwrap();
+        db.update_amiami_product(&update_args(Availability::Preorder)).await.unwrap();
+
+        let filter = IndexFilter::new().with_sort(IndexSort::DateChanged);
+        let page = db.get_indexed_products(user_id, &filter, PageRequest::default()).await.unwrap();
+        assert_eq!(page.items().iter().map(|p| (p.site(), p.product_id())).collect::<Vec<_>>(), vec![(amiami::SITE, restocked_product.id()), (melonbooks::SITE, melonbooks_product.id())]);
+        assert!(page.items()[0].date_restocked().is_some());
+        assert_eq!(page.items()[1].date_restocked(), None);
+    }
+
+    #[tokio::test]
+    async fn test_get_dates_updated() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let product = db.create_amiami_product(&amiami_product_args()).await.unwrap();
+        let dates = db.get_dates_updated(amiami::SITE, &[product.id()]).await.unwrap();
+        let date_added = dates[&product.id()];
+        assert!(date_added >= product.date_added());
+
+        let update_args = AmiamiUpdateProductArgs::new(product.url().to_owned(), 20000, 18000, product.release_date(), Availability::NotAvailable);
+        db.update_amiami_product(&update_args).await.unwrap();
+        let dates = db.get_dates_updated(amiami::SITE, &[product.id(), product.id() + 1]).await.unwrap();
+        assert_eq!(dates.keys().collect::<Vec<_>>(), vec![&product.id()]);
+        assert!(dates[&product.id()] >= date_added);
+        assert!(db.get_dates_updated(melonbooks::SITE, &[product.id()]).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_follow_targets() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        assert!(db.get_follow_targets(user_id).await.unwrap().is_empty());
+        db.follow_amiami_category(user_id, "9708").await.unwrap();
+        db.follow_melonbooks_artist(user_id, &ArtistArgs::new("mafuyu".to_owned())).await.unwrap();
+
+        let targets = db.get_follow_targets(user_id).await.unwrap();
+        assert_eq!(targets.iter().map(|t| (t.site(), t.name())).collect::<Vec<_>>(), vec![(melonbooks::SITE, "mafuyu"), (amiami::SITE, "9708")]);
+    }
+
+    fn melonbooks_product_args(url: &str, artist: &str) -> MelonbooksCreateProductArgs {
+        MelonbooksCreateProductArgs::new(
+            url.to_owned(),
+            "title".to_owned(),
+            None,
+            vec![artist.to_owned()],
+            "https://mafuyu.png".to_owned(),
+            "category".to_owned(),
+            vec![],
+            vec![],
+            Some("¥ 1,100".to_owned()),
+            Availability::Available
+        )
+    }
+
+    fn amiami_product_args() -> AmiamiCreateProductArgs {
+        AmiamiCreateProductArgs::new(
+            "https://www.amiami.com/eng/detail/?gcode=FIGURE-1".to_owned(),
+            "figure_title".to_owned(),
+            "https://img.amiami.com/figure-1.jpg".to_owned(),
+            "9708".to_owned(),
+            "Good Smile Company".to_owned(),
+            20000,
+            18000,
+            NaiveDate::from_ymd_opt(2026, 3, 31).unwrap(),
+            Availability::Preorder
+        )
+    }
+}
@@ -1,11 +1,43 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    amiami_availability_event (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        product_id -> Integer,
+        availability -> Text,
+        previous_availability -> Nullable<Text>,
+    }
+}
+
 diesel::table! {
     amiami_category (id) {
         id -> Integer,
         date_added -> Timestamp,
         category -> Text,
         following -> Bool,
+        schedule -> Nullable<Text>,
+        date_scraped -> Nullable<Timestamp>,
+        date_first_scraped -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    amiami_category_follower (category_id, user_id) {
+        category_id -> Integer,
+        user_id -> Integer,
+        date_followed -> Timestamp,
+        schedule -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    amiami_notification (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        product_id -> Integer,
+        user_id -> Integer,
+        kind -> Text,
     }
 }
 
@@ -22,6 +54,340 @@ diesel::table! {
         min_price -> Integer,
         release_date -> Date,
         availability -> Text,
+        date_restocked -> Nullable<Timestamp>,
+        image_hash -> Nullable<Text>,
+        image_phash -> Nullable<BigInt>,
+    }
+}
+
+diesel::table! {
+    amiami_price_event (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        product_id -> Integer,
+        full_price -> Integer,
+        min_price -> Integer,
+    }
+}
+
+diesel::table! {
+    app_user (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        username -> Text,
+    }
+}
+
+diesel::table! {
+    booth_availability_event (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        product_id -> Integer,
+        availability -> Text,
+        previous_availability -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    booth_notification (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        product_id -> Integer,
+        user_id -> Integer,
+        kind -> Text,
+    }
+}
+
+diesel::table! {
+    booth_price_event (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        product_id -> Integer,
+        price -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    booth_product (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        url -> Text,
+        title -> Text,
+        shop_name -> Text,
+        image_url -> Text,
+        price -> Nullable<Integer>,
+        availability -> Text,
+        date_restocked -> Nullable<Timestamp>,
+        image_hash -> Nullable<Text>,
+        image_phash -> Nullable<BigInt>,
+    }
+}
+
+diesel::table! {
+    booth_product_source (product_id, source_id) {
+        product_id -> Integer,
+        source_id -> Integer,
+    }
+}
+
+diesel::table! {
+    booth_source (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        name -> Text,
+        kind -> Text,
+        melonbooks_artist_id -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    booth_source_follower (source_id, user_id) {
+        source_id -> Integer,
+        user_id -> Integer,
+        date_followed -> Timestamp,
+    }
+}
+
+diesel::table! {
+    booth_variation (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        product_id -> Integer,
+        name -> Text,
+        price -> Integer,
+        availability -> Text,
+    }
+}
+
+diesel::table! {
+    digital_circle (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        store -> Text,
+        code -> Text,
+        name -> Text,
+    }
+}
+
+diesel::table! {
+    digital_circle_follower (circle_id, user_id) {
+        circle_id -> Integer,
+        user_id -> Integer,
+        date_followed -> Timestamp,
+    }
+}
+
+diesel::table! {
+    digital_notification (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        product_id -> Integer,
+        user_id -> Integer,
+        kind -> Text,
+    }
+}
+
+diesel::table! {
+    digital_price_event (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        product_id -> Integer,
+        list_price -> Integer,
+    }
+}
+
+diesel::table! {
+    digital_product (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        circle_id -> Integer,
+        store -> Text,
+        url -> Text,
+        title -> Text,
+        circle_name -> Text,
+        image_url -> Text,
+        list_price -> Integer,
+        sale_price -> Nullable<Integer>,
+        sale_end -> Nullable<Timestamp>,
+        date_discounted -> Nullable<Timestamp>,
+        image_hash -> Nullable<Text>,
+        image_phash -> Nullable<BigInt>,
+    }
+}
+
+diesel::table! {
+    digital_sale_event (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        product_id -> Integer,
+        sale_price -> Nullable<Integer>,
+        sale_end -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    figure_notification (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        product_id -> Integer,
+        user_id -> Integer,
+        kind -> Text,
+    }
+}
+
+diesel::table! {
+    figure_preorder_event (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        product_id -> Integer,
+        has_preorder -> Bool,
+        preorder_start -> Nullable<Timestamp>,
+        preorder_end -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    figure_price_event (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        product_id -> Integer,
+        price -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    figure_product (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        store -> Text,
+        url -> Text,
+        title -> Text,
+        maker_name -> Text,
+        image_url -> Text,
+        price -> Nullable<Integer>,
+        release_date -> Nullable<Text>,
+        has_preorder -> Bool,
+        preorder_start -> Nullable<Timestamp>,
+        preorder_end -> Nullable<Timestamp>,
+        date_preorder_opened -> Nullable<Timestamp>,
+        date_preorder_closing -> Nullable<Timestamp>,
+        image_hash -> Nullable<Text>,
+        image_phash -> Nullable<BigInt>,
+    }
+}
+
+diesel::table! {
+    figure_product_source (product_id, source_id) {
+        product_id -> Integer,
+        source_id -> Integer,
+    }
+}
+
+diesel::table! {
+    figure_source (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        store -> Text,
+        kind -> Text,
+        code -> Text,
+        name -> Text,
+    }
+}
+
+diesel::table! {
+    figure_source_follower (source_id, user_id) {
+        source_id -> Integer,
+        user_id -> Integer,
+        date_followed -> Timestamp,
+    }
+}
+
+diesel::table! {
+    image_phash_band (band, value, site, product_id) {
+        site -> Text,
+        product_id -> Integer,
+        band -> Integer,
+        value -> Integer,
+    }
+}
+
+diesel::table! {
+    mandarake_availability_event (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        product_id -> Integer,
+        availability -> Text,
+        previous_availability -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    mandarake_notification (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        product_id -> Integer,
+        user_id -> Integer,
+        kind -> Text,
+    }
+}
+
+diesel::table! {
+    mandarake_price_event (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        product_id -> Integer,
+        price -> Integer,
+    }
+}
+
+diesel::table! {
+    mandarake_product (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        url -> Text,
+        title -> Text,
+        image_url -> Text,
+        store -> Text,
+        condition -> Text,
+        price -> Integer,
+        availability -> Text,
+        date_restocked -> Nullable<Timestamp>,
+        image_hash -> Nullable<Text>,
+        image_phash -> Nullable<BigInt>,
+    }
+}
+
+diesel::table! {
+    mandarake_product_search (product_id, search_id) {
+        product_id -> Integer,
+        search_id -> Integer,
+    }
+}
+
+diesel::table! {
+    mandarake_search (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        keyword -> Text,
+    }
+}
+
+diesel::table! {
+    mandarake_search_follower (search_id, user_id) {
+        search_id -> Integer,
+        user_id -> Integer,
+        max_price -> Nullable<Integer>,
+        date_followed -> Timestamp,
+    }
+}
+
+diesel::table! {
+    melonbooks_availability_event (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        product_id -> Integer,
+        availability -> Text,
+        previous_availability -> Nullable<Text>,
     }
 }
 
@@ -32,6 +398,18 @@ diesel::table! {
         name -> Text,
         following -> Bool,
         date_followed -> Nullable<Timestamp>,
+        schedule -> Nullable<Text>,
+        date_scraped -> Nullable<Timestamp>,
+        date_first_scraped -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    melonbooks_artist_follower (artist_id, user_id) {
+        artist_id -> Integer,
+        user_id -> Integer,
+        date_followed -> Timestamp,
+        schedule -> Nullable<Text>,
     }
 }
 
@@ -51,6 +429,16 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    melonbooks_notification (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        product_id -> Integer,
+        user_id -> Integer,
+        kind -> Text,
+    }
+}
+
 diesel::table! {
     melonbooks_product (id) {
         id -> Integer,
@@ -62,6 +450,9 @@ diesel::table! {
         availability -> Text,
         price -> Nullable<Text>,
         circle -> Nullable<Text>,
+        date_restocked -> Nullable<Timestamp>,
+        image_hash -> Nullable<Text>,
+        image_phash -> Nullable<BigInt>,
     }
 }
 
@@ -79,6 +470,15 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    melonbooks_price_event (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        product_id -> Integer,
+        price -> Nullable<Text>,
+    }
+}
+
 diesel::table! {
     melonbooks_product_tag (product_id, tag_id) {
         product_id -> Integer,
@@ -113,11 +513,211 @@ diesel::table! {
     melonbooks_title_skip_sequence (id) {
         id -> Integer,
         date_added -> Timestamp,
+        user_id -> Integer,
         sequence -> Text,
     }
 }
 
+diesel::table! {
+    surugaya_availability_event (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        product_id -> Integer,
+        availability -> Text,
+        previous_availability -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    surugaya_notification (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        product_id -> Integer,
+        user_id -> Integer,
+        kind -> Text,
+    }
+}
+
+diesel::table! {
+    surugaya_price_event (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        product_id -> Integer,
+        price -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    surugaya_product (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        url -> Text,
+        title -> Text,
+        image_url -> Text,
+        condition -> Text,
+        price -> Nullable<Integer>,
+        availability -> Text,
+        date_restocked -> Nullable<Timestamp>,
+        image_hash -> Nullable<Text>,
+        image_phash -> Nullable<BigInt>,
+    }
+}
+
+diesel::table! {
+    surugaya_product_search (product_id, search_id) {
+        product_id -> Integer,
+        search_id -> Integer,
+    }
+}
+
+diesel::table! {
+    surugaya_search (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        keyword -> Text,
+    }
+}
+
+diesel::table! {
+    surugaya_search_follower (search_id, user_id) {
+        search_id -> Integer,
+        user_id -> Integer,
+        date_followed -> Timestamp,
+    }
+}
+
+diesel::table! {
+    toranoana_availability_event (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        product_id -> Integer,
+        availability -> Text,
+        previous_availability -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    toranoana_creator (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        name -> Text,
+        kind -> Text,
+        following -> Bool,
+        date_followed -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    toranoana_creator_follower (creator_id, user_id) {
+        creator_id -> Integer,
+        user_id -> Integer,
+        date_followed -> Timestamp,
+    }
+}
+
+diesel::table! {
+    toranoana_notification (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        product_id -> Integer,
+        user_id -> Integer,
+        kind -> Text,
+    }
+}
+
+diesel::table! {
+    toranoana_price_event (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        product_id -> Integer,
+        price -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    toranoana_product (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        url -> Text,
+        title -> Text,
+        image_url -> Text,
+        category -> Text,
+        price -> Nullable<Integer>,
+        availability -> Text,
+        date_restocked -> Nullable<Timestamp>,
+        image_hash -> Nullable<Text>,
+        image_phash -> Nullable<BigInt>,
+    }
+}
+
+diesel::table! {
+    toranoana_product_creator (product_id, creator_id) {
+        product_id -> Integer,
+        creator_id -> Integer,
+    }
+}
+
+diesel::table! {
+    toranoana_skip_product (id) {
+        id -> Integer,
+        date_added -> Timestamp,
+        url -> Text,
+    }
+}
+
+diesel::table! {
+    toranoana_skip_product_creator (skip_product_id, creator_name) {
+        skip_product_id -> Integer,
+        creator_name -> Text,
+    }
+}
+
+diesel::joinable!(amiami_availability_event -> amiami_product (product_id));
+diesel::joinable!(amiami_category_follower -> amiami_category (category_id));
+diesel::joinable!(amiami_category_follower -> app_user (user_id));
+diesel::joinable!(amiami_notification -> amiami_product (product_id));
+diesel::joinable!(amiami_notification -> app_user (user_id));
+diesel::joinable!(amiami_price_event -> amiami_product (product_id));
 diesel::joinable!(amiami_product -> amiami_category (category_id));
+diesel::joinable!(booth_availability_event -> booth_product (product_id));
+diesel::joinable!(booth_notification -> app_user (user_id));
+diesel::joinable!(booth_notification -> booth_product (product_id));
+diesel::joinable!(booth_price_event -> booth_product (product_id));
+diesel::joinable!(booth_product_source -> booth_product (product_id));
+diesel::joinable!(booth_product_source -> booth_source (source_id));
+diesel::joinable!(booth_source -> melonbooks_artist (melonbooks_artist_id));
+diesel::joinable!(booth_source_follower -> app_user (user_id));
+diesel::joinable!(booth_source_follower -> booth_source (source_id));
+diesel::joinable!(booth_variation -> booth_product (product_id));
+diesel::joinable!(digital_circle_follower -> app_user (user_id));
+diesel::joinable!(digital_circle_follower -> digital_circle (circle_id));
+diesel::joinable!(digital_notification -> app_user (user_id));
+diesel::joinable!(digital_notification -> digital_product (product_id));
+diesel::joinable!(digital_price_event -> digital_product (product_id));
+diesel::joinable!(digital_product -> digital_circle (circle_id));
+diesel::joinable!(digital_sale_event -> digital_product (product_id));
+diesel::joinable!(figure_notification -> app_user (user_id));
+diesel::joinable!(figure_notification -> figure_product (product_id));
+diesel::joinable!(figure_preorder_event -> figure_product (product_id));
+diesel::joinable!(figure_price_event -> figure_product (product_id));
+diesel::joinable!(figure_product_source -> figure_product (product_id));
+diesel::joinable!(figure_product_source -> figure_source (source_id));
+diesel::joinable!(figure_source_follower -> app_user (user_id));
+diesel::joinable!(figure_source_follower -> figure_source (source_id));
+diesel::joinable!(mandarake_availability_event -> mandarake_product (product_id));
+diesel::joinable!(mandarake_notification -> app_user (user_id));
+diesel::joinable!(mandarake_notification -> mandarake_product (product_id));
+diesel::joinable!(mandarake_price_event -> mandarake_product (product_id));
+diesel::joinable!(mandarake_product_search -> mandarake_product (product_id));
+diesel::joinable!(mandarake_product_search -> mandarake_search (search_id));
+diesel::joinable!(mandarake_search_follower -> app_user (user_id));
+diesel::joinable!(mandarake_search_follower -> mandarake_search (search_id));
+diesel::joinable!(melonbooks_artist_follower -> app_user (user_id));
+diesel::joinable!(melonbooks_artist_follower -> melonbooks_artist (artist_id));
+diesel::joinable!(melonbooks_availability_event -> melonbooks_product (product_id));
+diesel::joinable!(melonbooks_notification -> app_user (user_id));
+diesel::joinable!(melonbooks_notification -> melonbooks_product (product_id));
+diesel::joinable!(melonbooks_price_event -> melonbooks_product (product_id));
 diesel::joinable!(melonbooks_product -> melonbooks_category (category_id));
 diesel::joinable!(melonbooks_product_artist -> melonbooks_artist (artist_id));
 diesel::joinable!(melonbooks_product_artist -> melonbooks_product (product_id));
@@ -126,13 +726,69 @@ diesel::joinable!(melonbooks_product_flag -> melonbooks_product (product_id));
 diesel::joinable!(melonbooks_product_tag -> melonbooks_product (product_id));
 diesel::joinable!(melonbooks_product_tag -> melonbooks_tag (tag_id));
 diesel::joinable!(melonbooks_skip_product_artist -> melonbooks_skip_product (skip_product_id));
+diesel::joinable!(melonbooks_title_skip_sequence -> app_user (user_id));
+diesel::joinable!(surugaya_availability_event -> surugaya_product (product_id));
+diesel::joinable!(surugaya_notification -> app_user (user_id));
+diesel::joinable!(surugaya_notification -> surugaya_product (product_id));
+diesel::joinable!(surugaya_price_event -> surugaya_product (product_id));
+diesel::joinable!(surugaya_product_search -> surugaya_product (product_id));
+diesel::joinable!(surugaya_product_search -> surugaya_search (search_id));
+diesel::joinable!(surugaya_search_follower -> app_user (user_id));
+diesel::joinable!(surugaya_search_follower -> surugaya_search (search_id));
+diesel::joinable!(toranoana_availability_event -> toranoana_product (product_id));
+diesel::joinable!(toranoana_creator_follower -> app_user (user_id));
+diesel::joinable!(toranoana_creator_follower -> toranoana_creator (creator_id));
+diesel::joinable!(toranoana_notification -> app_user (user_id));
+diesel::joinable!(toranoana_notification -> toranoana_product (product_id));
+diesel::joinable!(toranoana_price_event -> toranoana_product (product_id));
+diesel::joinable!(toranoana_product_creator -> toranoana_creator (creator_id));
+diesel::joinable!(toranoana_product_creator -> toranoana_product (product_id));
+diesel::joinable!(toranoana_skip_product_creator -> toranoana_skip_product (skip_product_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    amiami_availability_event,
     amiami_category,
+    amiami_category_follower,
+    amiami_notification,
+    amiami_price_event,
     amiami_product,
+    app_user,
+    booth_availability_event,
+    booth_notification,
+    booth_price_event,
+    booth_product,
+    booth_product_source,
+    booth_source,
+    booth_source_follower,
+    booth_variation,
+    digital_circle,
+    digital_circle_follower,
+    digital_notification,
+    digital_price_event,
+    digital_product,
+    digital_sale_event,
+    figure_notification,
+    figure_preorder_event,
+    figure_price_event,
+    figure_product,
+    figure_product_source,
+    figure_source,
+    figure_source_follower,
+    image_phash_band,
+    mandarake_availability_event,
+    mandarake_notification,
+    mandarake_price_event,
+    mandarake_product,
+    mandarake_product_search,
+    mandarake_search,
+    mandarake_search_follower,
     melonbooks_artist,
+    melonbooks_artist_follower,
+    melonbooks_availability_event,
     melonbooks_category,
     melonbooks_flag,
+    melonbooks_notification,
+    melonbooks_price_event,
     melonbooks_product,
     melonbooks_product_artist,
     melonbooks_product_flag,
@@ -141,4 +797,20 @@ diesel::allow_tables_to_appear_in_same_query!(
     melonbooks_skip_product_artist,
     melonbooks_tag,
     melonbooks_title_skip_sequence,
+    surugaya_availability_event,
+    surugaya_notification,
+    surugaya_price_event,
+    surugaya_product,
+    surugaya_product_search,
+    surugaya_search,
+    surugaya_search_follower,
+    toranoana_availability_event,
+    toranoana_creator,
+    toranoana_creator_follower,
+    toranoana_notification,
+    toranoana_price_event,
+    toranoana_product,
+    toranoana_product_creator,
+    toranoana_skip_product,
+    toranoana_skip_product_creator,
 );
@@ -0,0 +1,93 @@
+use crate::domain::search::{HighlightSegment, HighlightedText, SearchProductsError};
+use anyhow::Context;
+use diesel::sql_types::{BigInt, Text};
+use diesel::{QueryableByName, RunQueryDsl, SqliteConnection};
+
+/// The trigram tokenizer cannot match terms shorter than three characters.
+pub const MIN_TERM_LENGTH: usize = 3;
+pub const MAX_SEARCH_RESULTS: i64 = 200;
+pub const HIGHLIGHT_START: char = '\u{2}';
+pub const HIGHLIGHT_END: char = '\u{3}';
+
+/// Builds an FTS5 match expression requiring every term of the query, each quoted as a phrase
+/// so user input cannot inject FTS5 operators.
+/// A query with a shorter term is rejected, dropping the term would match unrelated products.
+pub fn match_expression(query: &str) -> Result<String, SearchProductsError> {
+    let terms = query.split_whitespace().collect::<Vec<_>>();
+    if terms.is_empty() || terms.iter().any(|t| t.chars().count() < MIN_TERM_LENGTH) {
+        return Err(SearchProductsError::QueryTooShort { query: query.to_owned(), min_length: MIN_TERM_LENGTH });
+    }
+    Ok(
+        terms.into_iter()
+            .map(|t| format!("\"{}\"", t.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(" ")
+    )
+}
+
+#[derive(QueryableByName)]
+struct MatchCountRow {
+    #[diesel(sql_type = BigInt)]
+    count: i64,
+}
+
+/// Number of rows of the FTS5 `table` matching `expression`, `table` is never user input.
+pub fn count_matches(connection: &mut SqliteConnection, table: &str, expression: &str) -> Result<i64, anyhow::Error> {
+    let row = diesel::sql_query(format!("SELECT COUNT(*) AS count FROM {table} WHERE {table} MATCH ?"))
+        .bind::<Text, _>(expression)
+        .get_result::<MatchCountRow>(connection)
+        .with_context(|| format!("cannot count products matching '{}'", expression))?;
+    Ok(row.count)
+}
+
+/// Splits text returned by `highlight(..., char(2), char(3))` into matched and unmatched segments.
+pub fn parse_highlight(text: &str) -> HighlightedText {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut matched = false;
+    for c in text.chars() {
+        match c {
+            HIGHLIGHT_START | HIGHLIGHT_END => {
+                if !current.is_empty() {
+                    segments.push(HighlightSegment::new(std::mem::take(&mut current), matched));
+                }
+                matched = c == HIGHLIGHT_START;
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        segments.push(HighlightSegment::new(current, matched));
+    }
+    HighlightedText::new(segments)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_match_expression() {
+        assert_eq!(match_expression("まふゆ  \"title").unwrap(), "\"まふゆ\" \"\"\"title\"");
+        assert!(matches!(match_expression("ab"), Err(SearchProductsError::QueryTooShort { .. })));
+        assert!(matches!(match_expression("  "), Err(SearchProductsError::QueryTooShort { .. })));
+    }
+
+    #[test]
+    fn test_match_expression_with_short_term() {
+        let error = match_expression("初音 ミクさん").unwrap_err();
+        assert!(matches!(error, SearchProductsError::QueryTooShort { query, min_length: MIN_TERM_LENGTH } if query == "初音 ミクさん"));
+    }
+
+    #[test]
+    fn test_parse_highlight() {
+        let text = parse_highlight("a \u{2}match\u{3} b");
+        assert!(text.has_match());
+        assert_eq!(text.segments(), &[
+            HighlightSegment::new("a ".to_owned(), false),
+            HighlightSegment::new("match".to_owned(), true),
+            HighlightSegment::new(" b".to_owned(), false),
+        ]);
+        assert!(!parse_highlight("no match").has_match());
+    }
+}
@@ -0,0 +1,57 @@
+use crate::domain::image::models::image::{CachedImage, SetProductImageError};
+use crate::domain::product_history::{AddNotificationsError, NotificationKind};
+use crate::domain::site::{Site, SiteRepository};
+use crate::outbound::sqlite::site_schema::site_notification::dsl as notification_dsl;
+use crate::outbound::sqlite::site_schema::site_product::dsl as product_dsl;
+use crate::outbound::sqlite::Sqlite;
+use anyhow::Context;
+use async_trait::async_trait;
+use diesel::dsl::count_star;
+use diesel::prelude::*;
+
+#[async_trait]
+impl SiteRepository for Sqlite {
+    async fn set_product_image(&self, site: Site, product_id: i32, image: &CachedImage) -> Result<(), SetProductImageError> {
+        let image_hash = image.hash().to_owned();
+        let image_phash = image.perceptual_hash().map(|h| h as i64);
+        self.write(move |_, connection| {
+            // the trigger of the view updates the site's table, sqlite counts no changed rows for views
+            let product = product_dsl::site_product
+                .filter(product_dsl::site.eq(site.id()))
+                .filter(product_dsl::product_id.eq(product_id));
+            let count = product.select(count_star())
+                .get_result::<i64>(connection)
+                .with_context(|| format!("cannot load {} product with id '{}'", site.id(), product_id))?;
+            if count == 0 {
+                return Err(SetProductImageError::ProductMissing { id: product_id });
+            }
+            diesel::update(product)
+                .set((product_dsl::image_hash.eq(image_hash), product_dsl::image_phash.eq(image_phash)))
+                .execute(connection)
+                .with_context(|| format!("cannot set image of {} product with id '{}'", site.id(), product_id))?;
+            Ok(())
+        }).await
+    }
+
+    async fn add_notifications(&self, site: Site, user_id: i32, kind: NotificationKind, product_ids: &[i32]) -> Result<(), AddNotificationsError> {
+        if product_ids.is_empty() {
+            return Ok(());
+        }
+        let rows = product_ids.iter()
+            .map(|&product_id| (
+                notification_dsl::site.eq(site.id()),
+                notification_dsl::product_id.eq(product_id),
+                notification_dsl::user_id.eq(user_id),
+                notification_dsl::kind.eq(kind.to_string()),
+            ))
+            .collect::<Vec<_>>();
+        self.write(move |_, connection| {
+            diesel::insert_into(notification_dsl::site_notification)
+                .values(rows)
+                .execute(connection)
+                .with_context(|| format!("cannot insert {} notifications for user '{}'", site.id(), user_id))?;
+            Ok(())
+        }).await
+    }
+}
+
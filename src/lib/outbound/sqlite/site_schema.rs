@@ -0,0 +1,72 @@
+// The views over the tables of all sites, `diesel print-schema` only writes tables into schema.rs.
+
+use crate::outbound::sqlite::schema::app_user;
+
+diesel::table! {
+    site_product (site, product_id) {
+        site -> Text,
+        product_id -> Integer,
+        date_added -> Timestamp,
+        url -> Text,
+        title -> Text,
+        image_url -> Text,
+        image_hash -> Nullable<Text>,
+        image_phash -> Nullable<BigInt>,
+        price -> Nullable<Text>,
+        availability -> Text,
+        date_restocked -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    site_product_event (site, product_id, date_added) {
+        site -> Text,
+        product_id -> Integer,
+        date_added -> Timestamp,
+    }
+}
+
+diesel::table! {
+    site_target (site, target_id) {
+        site -> Text,
+        target_id -> Integer,
+        name -> Text,
+    }
+}
+
+diesel::table! {
+    site_target_follower (site, target_id, user_id) {
+        site -> Text,
+        target_id -> Integer,
+        user_id -> Integer,
+    }
+}
+
+diesel::table! {
+    site_product_target (site, product_id, target_id) {
+        site -> Text,
+        product_id -> Integer,
+        target_id -> Integer,
+    }
+}
+
+diesel::table! {
+    site_notification (site, id) {
+        site -> Text,
+        id -> Integer,
+        date_added -> Timestamp,
+        product_id -> Integer,
+        user_id -> Integer,
+        kind -> Text,
+    }
+}
+
+diesel::allow_tables_to_appear_in_same_query!(
+    app_user,
+    site_notification,
+    site_product,
+    site_product_event,
+    site_product_target,
+    site_target,
+    site_target_follower,
+);
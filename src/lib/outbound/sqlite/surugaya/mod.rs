@@ -0,0 +1,723 @@
+use crate::domain::availability::Availability;
+use crate::domain::surugaya::models::product::{AddProductSearchError, CreateProductArgs, CreateProductError, GetProductsError, Product, ProductHistoryEntry, UpdateProductArgs, UpdateProductError};
+use crate::domain::surugaya::models::search::{DeleteSearchError, FollowedSearch, GetSearchesError, SaveSearchError, Search};
+use crate::domain::surugaya::ports::SurugayaRepository;
+use crate::domain::pagination::{Page, PageRequest};
+use crate::domain::product_history::{sort_history, GetProductError};
+use crate::outbound::sqlite::surugaya::models::{AvailabilityEventRow, AvailabilityEventRowInsert, NotificationRow, PriceEventRow, PriceEventRowInsert, ProductRow, ProductRowInsert, SearchFollowerRow, SearchFollowerRowInsert, SearchRow, SearchRowInsert};
+use crate::outbound::sqlite::{schema, Sqlite};
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::r2d2::ConnectionManager;
+use itertools::Itertools;
+use r2d2::PooledConnection;
+use std::collections::HashMap;
+use schema::app_user::dsl as user_dsl;
+use schema::surugaya_availability_event::dsl as availability_event_dsl;
+use schema::surugaya_notification::dsl as notification_dsl;
+use schema::surugaya_price_event::dsl as price_event_dsl;
+use schema::surugaya_product::dsl as product_dsl;
+use schema::surugaya_product_search::dsl as product_search_dsl;
+use schema::surugaya_search::dsl as search_dsl;
+use schema::surugaya_search_follower::dsl as search_follower_dsl;
+
+mod models;
+
+impl Sqlite {
+    fn get_surugaya_search_row_by_id(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        search_id: i32
+    ) -> Result<Option<SearchRow>, anyhow::Error> {
+        let search = search_dsl::surugaya_search
+            .select(SearchRow::as_select())
+            .filter(search_dsl::id.eq(search_id))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get search with id '{}'", search_id))?;
+        Ok(search)
+    }
+
+    fn get_or_insert_surugaya_search_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        keyword: &str,
+    ) -> Result<SearchRow, anyhow::Error> {
+        let search = search_dsl::surugaya_search
+            .select(SearchRow::as_select())
+            .filter(search_dsl::keyword.eq(keyword))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get search '{}'", keyword))?;
+        if let Some(search) = search {
+            return Ok(search);
+        }
+        let search = diesel::insert_into(search_dsl::surugaya_search)
+            .values(SearchRowInsert { keyword })
+            .returning(SearchRow::as_returning())
+            .get_result(connection)
+            .with_context(|| format!("cannot insert search '{}'", keyword))?;
+        Ok(search)
+    }
+
+    fn get_surugaya_search_rows(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+    ) -> Result<Vec<SearchRow>, anyhow::Error> {
+        let searches = search_dsl::surugaya_search
+            .select(SearchRow::as_select())
+            .order_by(search_dsl::keyword)
+            .get_results(connection)
+            .with_context(|| "cannot select searches")?;
+        Ok(searches)
+    }
+
+    fn get_surugaya_saved_search_rows_page(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        user_id: i32,
+        page: PageRequest,
+    ) -> Result<(Vec<SearchRow>, i64), anyhow::Error> {
+        let saved_ids = || search_follower_dsl::surugaya_search_follower
+            .filter(search_follower_dsl::user_id.eq(user_id))
+            .select(search_follower_dsl::search_id);
+        let total = search_dsl::surugaya_search
+            .filter(search_dsl::id.eq_any(saved_ids()))
+            .count()
+            .get_result::<i64>(connection)
+            .with_context(|| format!("cannot count searches saved by user '{}'", user_id))?;
+        let searches = search_dsl::surugaya_search
+            .select(SearchRow::as_select())
+            .filter(search_dsl::id.eq_any(saved_ids()))
+            .order_by((search_dsl::keyword, search_dsl::id))
+            .limit(page.page_size() as i64)
+            .offset(page.offset())
+            .get_results(connection)
+            .with_context(|| format!("cannot select searches saved by user '{}'", user_id))?;
+        Ok((searches, total))
+    }
+
+    fn get_surugaya_search_follower_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        search: &SearchRow,
+        user_id: i32,
+    ) -> Result<Option<SearchFollowerRow>, anyhow::Error> {
+        let follower = search_follower_dsl::surugaya_search_follower
+            .select(SearchFollowerRow::as_select())
+            .filter(search_follower_dsl::search_id.eq(search.id))
+            .filter(search_follower_dsl::user_id.eq(user_id))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get follower '{}' of search '{}'", user_id, search.keyword))?;
+        Ok(follower)
+    }
+
+    fn get_surugaya_search_follower_rows(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+    ) -> Result<Vec<SearchFollowerRow>, anyhow::Error> {
+        let followers = search_follower_dsl::surugaya_search_follower
+            .select(SearchFollowerRow::as_select())
+            .get_results(connection)
+            .with_context(|| "cannot get search followers")?;
+        Ok(followers)
+    }
+
+    fn get_surugaya_search_follower_rows_by_user(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        user_id: i32,
+    ) -> Result<Vec<SearchFollowerRow>, anyhow::Error> {
+        let followers = search_follower_dsl::surugaya_search_follower
+            .select(SearchFollowerRow::as_select())
+            .filter(search_follower_dsl::user_id.eq(user_id))
+            .get_results(connection)
+            .with_context(|| format!("cannot get searches saved by user '{}'", user_id))?;
+        Ok(followers)
+    }
+
+    /// Products found by any of the searches, once each.
+    fn get_surugaya_product_rows_page_by_searches(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        search_ids: &[i32],
+        page: PageRequest,
+    ) -> Result<(Vec<ProductRow>, i64), anyhow::Error> {
+        let product_ids = || product_search_dsl::surugaya_product_search
+            .filter(product_search_dsl::search_id.eq_any(search_ids))
+            .select(product_search_dsl::product_id);
+        let total = product_dsl::surugaya_product
+            .filter(product_dsl::id.eq_any(product_ids()))
+            .count()
+            .get_result::<i64>(connection)
+            .with_context(|| format!("cannot count products of searches with ids {:?}", search_ids))?;
+        let products = product_dsl::surugaya_product
+            .select(ProductRow::as_select())
+            .filter(product_dsl::id.eq_any(product_ids()))
+            .order_by((product_dsl::date_added.desc(), product_dsl::id.desc()))
+            .limit(page.page_size() as i64)
+            .offset(page.offset())
+            .get_results(connection)
+            .with_context(|| format!("cannot get products of searches with ids {:?}", search_ids))?;
+        Ok((products, total))
+    }
+
+    fn get_surugaya_product_row_by_url(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        url: &str
+    ) -> Result<Option<ProductRow>, anyhow::Error> {
+        let product = product_dsl::surugaya_product
+            .select(ProductRow::as_select())
+            .filter(product_dsl::url.eq(url))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get product with url '{}'", url))?;
+        Ok(product)
+    }
+
+    fn get_surugaya_product_row_by_id(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32
+    ) -> Result<Option<ProductRow>, anyhow::Error> {
+        let product = product_dsl::surugaya_product
+            .select(ProductRow::as_select())
+            .find(product_id)
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get product with id '{}'", product_id))?;
+        Ok(product)
+    }
+
+    fn insert_surugaya_product_search_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+        search_id: i32,
+    ) -> Result<(), anyhow::Error> {
+        diesel::insert_or_ignore_into(product_search_dsl::surugaya_product_search)
+            .values((product_search_dsl::product_id.eq(product_id), product_search_dsl::search_id.eq(search_id)))
+            .execute(connection)
+            .with_context(|| format!("cannot link product '{}' to search '{}'", product_id, search_id))?;
+        Ok(())
+    }
+
+    fn update_surugaya_product_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product: &ProductRow,
+        args: &UpdateProductArgs,
+    ) -> Result<ProductRow, anyhow::Error> {
+        let date_restocked = match !product.availability.is_available() && args.availability().is_available() {
+            true => Some(Utc::now().naive_utc()),
+            false => product.date_restocked,
+        };
+        if product.availability != args.availability() {
+            self.insert_surugaya_availability_event_row(connection, product.id, Some(product.availability.clone()), args.availability())?;
+        }
+        if product.price != args.price() {
+            self.insert_surugaya_price_event_row(connection, product.id, args.price())?;
+        }
+        let product = diesel::update(&product)
+            .set((
+                product_dsl::condition.eq(args.condition().to_string()),
+                product_dsl::price.eq(args.price()),
+                product_dsl::availability.eq(args.availability().to_string()),
+                product_dsl::date_restocked.eq(date_restocked),
+            ))
+            .returning(ProductRow::as_returning())
+            .get_result(connection)
+            .with_context(|| format!("cannot update product with url '{}'", product.url))?;
+        Ok(product)
+    }
+
+    fn insert_surugaya_availability_event_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+        previous_availability: Option<Availability>,
+        availability: Availability,
+    ) -> Result<(), anyhow::Error> {
+        diesel::insert_into(availability_event_dsl::surugaya_availability_event)
+            .values(AvailabilityEventRowInsert { product_id, availability, previous_availability: previous_availability.map(|a| a.to_string()) })
+            .execute(connection)
+            .with_context(|| format!("cannot insert availability event for product '{}'", product_id))?;
+        Ok(())
+    }
+
+    fn insert_surugaya_price_event_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+        price: Option<i32>,
+    ) -> Result<(), anyhow::Error> {
+        diesel::insert_into(price_event_dsl::surugaya_price_event)
+            .values(PriceEventRowInsert { product_id, price })
+            .execute(connection)
+            .with_context(|| format!("cannot insert price event for product '{}'", product_id))?;
+        Ok(())
+    }
+
+    fn get_surugaya_product_history_entries(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+    ) -> Result<Vec<ProductHistoryEntry>, anyhow::Error> {
+        let availability_events = availability_event_dsl::surugaya_availability_event
+            .select(AvailabilityEventRow::as_select())
+            .filter(availability_event_dsl::product_id.eq(product_id))
+            .order_by(availability_event_dsl::id.asc())
+            .get_results(connection)
+            .with_context(|| format!("cannot get availability events for product '{}'", product_id))?;
+        let price_events = price_event_dsl::surugaya_price_event
+            .select(PriceEventRow::as_select())
+            .filter(price_event_dsl::product_id.eq(product_id))
+            .order_by(price_event_dsl::id.asc())
+            .get_results(connection)
+            .with_context(|| format!("cannot get price events for product '{}'", product_id))?;
+        let notifications = notification_dsl::surugaya_notification
+            .inner_join(user_dsl::app_user)
+            .select((NotificationRow::as_select(), user_dsl::username))
+            .filter(notification_dsl::product_id.eq(product_id))
+            .order_by(notification_dsl::id.asc())
+            .get_results::<(NotificationRow, String)>(connection)
+            .with_context(|| format!("cannot get notifications for product '{}'", product_id))?;
+        let mut history = availability_events.into_iter().map(|e| e.into_domain())
+            .chain(price_events.into_iter().map(|e| e.into_domain()))
+            .chain(notifications.into_iter().map(|(n, username)| n.into_domain(username)))
+            .collect::<Vec<_>>();
+        sort_history(&mut history);
+        Ok(history)
+    }
+}
+
+#[async_trait]
+impl SurugayaRepository for Sqlite {
+    async fn save_surugaya_search(&self, user_id: i32, keyword: &str) -> Result<(), SaveSearchError> {
+        let keyword = keyword.to_owned();
+        self.write(move |db, connection| {
+            let search = db.get_or_insert_surugaya_search_row(connection, &keyword)?;
+            if db.get_surugaya_search_follower_row(connection, &search, user_id)?.is_some() {
+                return Err(SaveSearchError::AlreadySavedError(search.keyword));
+            }
+            diesel::insert_into(search_follower_dsl::surugaya_search_follower)
+                .values(SearchFollowerRowInsert { search_id: search.id, user_id })
+                .execute(connection)
+                .with_context(|| format!("cannot save search '{}' for user '{}'", search.keyword, user_id))?;
+            Ok(())
+        }).await
+    }
+
+    async fn delete_surugaya_search(&self, user_id: i32, search_id: i32) -> Result<(), DeleteSearchError> {
+        self.write(move |db, connection| {
+            let search = db.get_surugaya_search_row_by_id(connection, search_id)?
+                .ok_or(DeleteSearchError::UnknownSearch { id: search_id })?;
+            if db.get_surugaya_search_follower_row(connection, &search, user_id)?.is_none() {
+                return Err(DeleteSearchError::SearchNotFollowed { keyword: search.keyword });
+            }
+            diesel::delete(search_follower_dsl::surugaya_search_follower)
+                .filter(search_follower_dsl::search_id.eq(search.id))
+                .filter(search_follower_dsl::user_id.eq(user_id))
+                .execute(connection)
+                .with_context(|| format!("cannot delete search '{}' for user '{}'", search.keyword, user_id))?;
+            Ok(())
+        }).await
+    }
+
+    async fn get_surugaya_searches(&self, user_id: i32) -> Result<Vec<Search>, GetSearchesError> {
+        self.read(move |db, connection| {
+            let search_rows = db.get_surugaya_search_rows(connection)?;
+            let followers = db.get_surugaya_search_follower_rows_by_user(connection, user_id)?
+                .into_iter()
+                .map(|f| (f.search_id, f))
+                .collect::<HashMap<_, _>>();
+            let searches = search_rows.into_iter()
+                .map(|s| {
+                    let follower = followers.get(&s.id);
+                    s.into_domain_for(follower)
+                })
+                .collect();
+            Ok(searches)
+        }).await
+    }
+
+    async fn get_surugaya_saved_searches_page(&self, user_id: i32, page: PageRequest) -> Result<Page<Search>, GetSearchesError> {
+        self.read(move |db, connection| {
+            let (search_rows, total) = db.get_surugaya_saved_search_rows_page(connection, user_id, page)?;
+            let followers = db.get_surugaya_search_follower_rows_by_user(connection, user_id)?
+                .into_iter()
+                .map(|f| (f.search_id, f))
+                .collect::<HashMap<_, _>>();
+            let searches = search_rows.into_iter()
+                .map(|s| {
+                    let follower = followers.get(&s.id);
+                    s.into_domain_for(follower)
+                })
+                .collect();
+            Ok(Page::new(searches, page, total))
+        }).await
+    }
+
+    async fn get_followed_surugaya_searches(&self) -> Result<Vec<FollowedSearch>, GetSearchesError> {
+        self.read(move |db, connection| {
+            let follower_rows = db.get_surugaya_search_follower_rows(connection)?;
+            let user_ids = follower_rows.iter().map(|f| f.user_id).unique().collect::<Vec<_>>();
+            let users = db.get_user_rows_by_ids(connection, &user_ids)?
+                .into_iter()
+                .map(|u| (u.id, u.into_domain()))
+                .collect::<HashMap<_, _>>();
+            let mut followers = follower_rows.into_iter().into_group_map_by(|f| f.search_id);
+            let searches = db.get_surugaya_search_rows(connection)?
+                .into_iter()
+                .filter_map(|search| {
+                    let search_followers = followers.remove(&search.id)?;
+                    let first_follower = search_followers.iter().min_by_key(|f| f.date_followed);
+                    let mut search_followers = search_followers.iter()
+                        .filter_map(|f| users.get(&f.user_id).cloned())
+                        .collect::<Vec<_>>();
+                    search_followers.sort_by(|a, b| a.username().cmp(b.username()));
+                    let search = Search::new(search.id, search.date_added.and_utc(), search.keyword, true, first_follower.map(|f| f.date_followed.and_utc()));
+                    Some(FollowedSearch::new(search, search_followers))
+                })
+                .collect();
+            Ok(searches)
+        }).await
+    }
+
+    async fn create_surugaya_product(&self, args: &CreateProductArgs) -> Result<Product, CreateProductError> {
+        let args = args.clone();
+        self.write(move |db, connection| {
+            if let Some(product_row) = db.get_surugaya_product_row_by_url(connection, args.url())? {
+                return Err(CreateProductError::DuplicateProduct { url: product_row.url, title: product_row.title });
+            }
+            let product = connection.transaction(|connection| -> Result<Product, anyhow::Error> {
+                let product_row = diesel::insert_into(product_dsl::surugaya_product)
+                    .values(ProductRowInsert {
+                        url: args.url(),
+                        title: args.title(),
+                        image_url: args.image_url(),
+                        condition: args.condition(),
+                        price: args.price(),
+                        availability: args.availability(),
+                    })
+                    .returning(ProductRow::as_returning())
+                    .get_result(connection)
+                    .with_context(|| format!("cannot insert product with url '{}'", args.url()))?;
+                db.insert_surugaya_availability_event_row(connection, product_row.id, None, args.availability())?;
+                db.insert_surugaya_price_event_row(connection, product_row.id, args.price())?;
+                db.insert_surugaya_product_search_row(connection, product_row.id, args.search_id())?;
+                Ok(product_row.into_domain())
+            })?;
+            Ok(product)
+        }).await
+    }
+
+    async fn update_surugaya_product(&self, args: &UpdateProductArgs) -> Result<Product, UpdateProductError> {
+        let args = args.clone();
+        self.write(move |db, connection| {
+            let product_row = db.get_surugaya_product_row_by_url(connection, args.url())?
+                .ok_or_else(|| UpdateProductError::ProductMissing { url: args.url().to_owned() })?;
+            let product_row = connection.transaction(|connection| db.update_surugaya_product_row(connection, &product_row, &args))?;
+            Ok(product_row.into_domain())
+        }).await
+    }
+
+    async fn add_surugaya_product_search(&self, url: &str, search_id: i32) -> Result<(), AddProductSearchError> {
+        let url = url.to_owned();
+        self.write(move |db, connection| {
+            let product_row = db.get_surugaya_product_row_by_url(connection, &url)?
+                .ok_or_else(|| AddProductSearchError::ProductMissing { url: url.clone() })?;
+            db.insert_surugaya_product_search_row(connection, product_row.id, search_id)?;
+            Ok(())
+        }).await
+    }
+
+    async fn get_surugaya_product(&self, product_id: i32) -> Result<Product, GetProductError> {
+        self.read(move |db, connection| {
+            let product_row = db.get_surugaya_product_row_by_id(connection, product_id)?
+                .ok_or(GetProductError::ProductMissing { id: product_id })?;
+            Ok(product_row.into_domain())
+        }).await
+    }
+
+    async fn get_surugaya_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError> {
+        self.read(move |db, connection| {
+            if db.get_surugaya_product_row_by_id(connection, product_id)?.is_none() {
+                return Err(GetProductError::ProductMissing { id: product_id });
+            }
+            let history = db.get_surugaya_product_history_entries(connection, product_id)?;
+            Ok(history)
+        }).await
+    }
+
+    async fn get_surugaya_products(&self) -> Result<Vec<Product>, GetProductsError> {
+        self.read(move |_, connection| {
+            let products = product_dsl::surugaya_product
+                .select(ProductRow::as_select())
+                .order_by(product_dsl::date_added.desc())
+                .get_results(connection)
+                .with_context(|| "cannot get products")?
+                .into_iter()
+                .map(|p| p.into_domain())
+                .collect();
+            Ok(products)
+        }).await
+    }
+
+    async fn get_surugaya_products_by_search(&self, search_id: i32) -> Result<Vec<Product>, GetProductsError> {
+        self.read(move |_, connection| {
+            let products = product_search_dsl::surugaya_product_search
+                .inner_join(product_dsl::surugaya_product)
+                .select(ProductRow::as_select())
+                .filter(product_search_dsl::search_id.eq(search_id))
+                .order_by(product_dsl::date_added.desc())
+                .get_results(connection)
+                .with_context(|| format!("cannot get products of search with id {}", search_id))?
+                .into_iter()
+                .map(|p| p.into_domain())
+                .collect();
+            Ok(products)
+        }).await
+    }
+
+    async fn get_surugaya_products_page(&self, page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        self.read(move |_, connection| {
+            let total = product_dsl::surugaya_product
+                .count()
+                .get_result::<i64>(connection)
+                .with_context(|| "cannot count products")?;
+            let products = product_dsl::surugaya_product
+                .select(ProductRow::as_select())
+                .order_by((product_dsl::date_added.desc(), product_dsl::id.desc()))
+                .limit(page.page_size() as i64)
+                .offset(page.offset())
+                .get_results(connection)
+                .with_context(|| "cannot get products")?
+                .into_iter()
+                .map(|p| p.into_domain())
+                .collect();
+            Ok(Page::new(products, page, total))
+        }).await
+    }
+
+    async fn get_surugaya_products_page_by_search(&self, search_id: i32, page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        self.read(move |db, connection| {
+            let (product_rows, total) = db.get_surugaya_product_rows_page_by_searches(connection, &[search_id], page)?;
+            let products = product_rows.into_iter().map(|p| p.into_domain()).collect();
+            Ok(Page::new(products, page, total))
+        }).await
+    }
+
+    async fn get_surugaya_products_page_by_searches(&self, search_ids: &[i32], page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        let search_ids = search_ids.to_vec();
+        self.read(move |db, connection| {
+            let (product_rows, total) = db.get_surugaya_product_rows_page_by_searches(connection, &search_ids, page)?;
+            let products = product_rows.into_iter().map(|p| p.into_domain()).collect();
+            Ok(Page::new(products, page, total))
+        }).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::domain::image::models::image::{CachedImage, SetProductImageError};
+    use crate::domain::product_history::{NotificationKind, ProductChange};
+    use crate::domain::site::SiteRepository;
+    use crate::domain::surugaya::models::condition::Condition;
+    use crate::domain::surugaya::models::product::ListingData;
+    use crate::domain::surugaya::SITE;
+    use crate::domain::user::models::user::DEFAULT_USERNAME;
+    use crate::domain::user::ports::UserRepository;
+    use crate::outbound::sqlite::test_util::default_user_id;
+
+    #[tokio::test]
+    async fn test_save_surugaya_search() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        db.save_surugaya_search(user_id, "mafuyu").await.unwrap();
+
+        let searches = db.get_surugaya_searches(user_id).await.unwrap();
+        assert_eq!(searches.len(), 1);
+        let search = searches.first().unwrap();
+        assert_eq!(search.keyword(), "mafuyu");
+        assert!(search.following());
+        assert_ne!(search.date_followed(), None);
+        assert!(matches!(db.save_surugaya_search(user_id, "mafuyu").await, Err(SaveSearchError::AlreadySavedError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_delete_surugaya_search() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let users = db.setup_users(DEFAULT_USERNAME, &["alice".to_owned()]).await.unwrap();
+        let (alice, default) = (users.first().unwrap(), users.last().unwrap());
+        db.save_surugaya_search(alice.id(), "mafuyu").await.unwrap();
+        db.save_surugaya_search(default.id(), "mafuyu").await.unwrap();
+        let search = db.get_surugaya_searches(alice.id()).await.unwrap().into_iter().next().unwrap();
+
+        let followed = db.get_followed_surugaya_searches().await.unwrap();
+        assert_eq!(followed.len(), 1);
+        let usernames = followed.first().unwrap().followers().iter().map(|u| u.username()).collect::<Vec<_>>();
+        assert_eq!(usernames, vec!["alice", DEFAULT_USERNAME]);
+
+        db.delete_surugaya_search(default.id(), search.id()).await.unwrap();
+        assert!(db.get_surugaya_searches(alice.id()).await.unwrap().first().unwrap().following());
+        assert!(!db.get_surugaya_searches(default.id()).await.unwrap().first().unwrap().following());
+        assert!(matches!(db.delete_surugaya_search(default.id(), search.id()).await, Err(DeleteSearchError::SearchNotFollowed { .. })));
+        assert!(matches!(db.delete_surugaya_search(default.id(), search.id() + 1).await, Err(DeleteSearchError::UnknownSearch { .. })));
+
+        db.delete_surugaya_search(alice.id(), search.id()).await.unwrap();
+        assert!(db.get_followed_surugaya_searches().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_create_surugaya_product() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let search_id = search_id(&db).await;
+        let args = CreateProductArgs::new(search_id, listing("ZHORE1234", Some(1200), Availability::Available));
+        let product = db.create_surugaya_product(&args).await.unwrap();
+
+        assert_eq!(product.url(), args.url());
+        assert_eq!(product.condition(), Condition::Used);
+        assert_eq!(product.price(), Some(1200));
+        assert_eq!(db.get_surugaya_products_by_search(search_id).await.unwrap(), vec![product]);
+        assert!(matches!(db.create_surugaya_product(&args).await, Err(CreateProductError::DuplicateProduct { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_add_surugaya_product_search() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        let search_id = search_id(&db).await;
+        db.save_surugaya_search(user_id, "kantoku").await.unwrap();
+        let other_search_id = db.get_surugaya_searches(user_id).await.unwrap().into_iter().find(|s| s.keyword() == "kantoku").unwrap().id();
+        let product = db.create_surugaya_product(&CreateProductArgs::new(search_id, listing("ZHORE1234", Some(1200), Availability::Available))).await.unwrap();
+
+        db.add_surugaya_product_search(product.url(), other_search_id).await.unwrap();
+        db.add_surugaya_product_search(product.url(), other_search_id).await.unwrap();
+        assert_eq!(db.get_surugaya_products_by_search(other_search_id).await.unwrap(), vec![product]);
+        assert_eq!(db.get_surugaya_products().await.unwrap().len(), 1);
+        assert!(matches!(db.add_surugaya_product_search("https://missing", other_search_id).await, Err(AddProductSearchError::ProductMissing { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_get_surugaya_products_page() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        let search_id = search_id(&db).await;
+        db.save_surugaya_search(user_id, "kantoku").await.unwrap();
+        let other_search_id = db.get_surugaya_searches(user_id).await.unwrap().into_iter().find(|s| s.keyword() == "kantoku").unwrap().id();
+        let mut products = Vec::new();
+        for code in ["ZHORE1", "ZHORE2", "ZHORE3"] {
+            products.push(db.create_surugaya_product(&CreateProductArgs::new(search_id, listing(code, Some(1200), Availability::Available))).await.unwrap());
+        }
+        db.create_surugaya_product(&CreateProductArgs::new(other_search_id, listing("ZHORE4", Some(1200), Availability::Available))).await.unwrap();
+
+        let page = db.get_surugaya_products_page(PageRequest::new(2, 3)).await.unwrap();
+        assert_eq!(page.total_items(), 4);
+        assert_eq!(page.items().len(), 1);
+        let page = db.get_surugaya_products_page_by_search(search_id, PageRequest::new(1, 2)).await.unwrap();
+        assert_eq!(page.total_items(), 3);
+        assert_eq!(page.items(), &[products[2].clone(), products[1].clone()]);
+        let page = db.get_surugaya_products_page_by_search(search_id, PageRequest::new(2, 2)).await.unwrap();
+        assert_eq!(page.items(), &[products[0].clone()]);
+    }
+
+    #[tokio::test]
+    async fn test_get_surugaya_products_page_by_searches() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        let search_id = search_id(&db).await;
+        db.save_surugaya_search(user_id, "kantoku").await.unwrap();
+        let other_search_id = db.get_surugaya_searches(user_id).await.unwrap().into_iter().find(|s| s.keyword() == "kantoku").unwrap().id();
+        let product = db.create_surugaya_product(&CreateProductArgs::new(search_id, listing("ZHORE1", Some(1200), Availability::Available))).await.unwrap();
+        let product2 = db.create_surugaya_product(&CreateProductArgs::new(other_search_id, listing("ZHORE2", Some(1200), Availability::Available))).await.unwrap();
+        db.add_surugaya_product_search(product.url(), other_search_id).await.unwrap();
+
+        let page = db.get_surugaya_products_page_by_searches(&[search_id, other_search_id], PageRequest::new(1, 10)).await.unwrap();
+        assert_eq!(page.total_items(), 2);
+        assert_eq!(page.items(), &[product2.clone(), product.clone()]);
+        let page = db.get_surugaya_products_page_by_searches(&[search_id, other_search_id], PageRequest::new(2, 1)).await.unwrap();
+        assert_eq!(page.items(), &[product]);
+        assert!(db.get_surugaya_products_page_by_searches(&[], PageRequest::default()).await.unwrap().items().is_empty());
+
+        db.delete_surugaya_search(user_id, search_id).await.unwrap();
+        let page = db.get_surugaya_saved_searches_page(user_id, PageRequest::new(1, 10)).await.unwrap();
+        assert_eq!(page.total_items(), 1);
+        assert_eq!(page.items().iter().map(|s| (s.keyword(), s.following())).collect::<Vec<_>>(), vec![("kantoku", true)]);
+    }
+
+    #[tokio::test]
+    async fn test_update_surugaya_product() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        let product = db.create_surugaya_product(&CreateProductArgs::new(search_id(&db).await, listing("ZHORE1234", None, Availability::NotAvailable))).await.unwrap();
+
+        let product = db.update_surugaya_product(&UpdateProductArgs::new(product.url().to_owned(), Condition::New, Some(3000), Availability::Available)).await.unwrap();
+        assert_eq!(product.condition(), Condition::New);
+        assert_eq!(product.price(), Some(3000));
+        assert_eq!(product.availability(), Availability::Available);
+        assert_ne!(product.date_restocked(), None);
+        assert!(matches!(db.update_surugaya_product(&UpdateProductArgs::new("https://missing".to_owned(), Condition::Used, None, Availability::Available)).await, Err(UpdateProductError::ProductMissing { .. })));
+
+        db.add_notifications(SITE, user_id, NotificationKind::RestockedProduct, &[product.id()]).await.unwrap();
+        let history = db.get_surugaya_product_history(product.id()).await.unwrap();
+        let prices = history.iter()
+            .filter_map(|e| match e.change() { ProductChange::Price(p) => Some(*p), _ => None })
+            .collect::<Vec<_>>();
+        assert_eq!(prices, vec![None, Some(3000)]);
+        let availabilities = history.iter()
+            .filter_map(|e| match e.change() { ProductChange::Availability(a) => Some(a.clone()), _ => None })
+            .collect::<Vec<_>>();
+        assert_eq!(availabilities, vec![Availability::NotAvailable, Availability::Available]);
+        let notifications = history.iter()
+            .filter(|e| matches!(e.change(), ProductChange::Notification { .. }))
+            .map(|e| e.change().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(notifications, vec![ProductChange::Notification { kind: NotificationKind::RestockedProduct, username: DEFAULT_USERNAME.to_owned() }]);
+        assert!(matches!(db.get_surugaya_product_history(product.id() + 1).await, Err(GetProductError::ProductMissing { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_set_surugaya_product_image() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let product = db.create_surugaya_product(&CreateProductArgs::new(search_id(&db).await, listing("ZHORE1234", Some(1200), Availability::Available))).await.unwrap();
+
+        let hash = "a".repeat(64);
+        let image = CachedImage::new(hash.clone(), Some(0xF000_0000_0000_0001));
+        db.set_product_image(SITE, product.id(), &image).await.unwrap();
+        let loaded = db.get_surugaya_product(product.id()).await.unwrap();
+        assert_eq!(loaded.image_hash(), Some(hash.as_str()));
+        assert_eq!(loaded.image_phash(), Some(0xF000_0000_0000_0001));
+        assert!(matches!(db.set_product_image(SITE, product.id() + 1, &image).await, Err(SetProductImageError::ProductMissing { .. })));
+    }
+
+    fn listing(code: &str, price: Option<i32>, availability: Availability) -> ListingData {
+        ListingData::new(
+            format!("https://www.suruga-ya.jp/product/detail/{}", code),
+            "mafuyu_title".to_owned(),
+            format!("https://www.suruga-ya.jp/database/pics_light/game/{}.jpg", code.to_lowercase()),
+            Condition::Used,
+            price,
+            availability
+        )
+    }
+
+    async fn search_id(db: &Sqlite) -> i32 {
+        let user_id = default_user_id(db).await;
+        db.save_surugaya_search(user_id, "mafuyu").await.unwrap();
+        db.get_surugaya_searches(user_id).await.unwrap().into_iter().find(|s| s.keyword() == "mafuyu").unwrap().id()
+    }
+}
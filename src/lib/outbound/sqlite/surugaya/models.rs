@@ -0,0 +1,158 @@
+use crate::domain::availability::Availability;
+use crate::domain::surugaya::models::condition::Condition;
+use crate::domain::surugaya::models::product::{Product, ProductHistoryEntry};
+use crate::domain::surugaya::models::search::Search;
+use crate::domain::product_history::{NotificationKind, ProductChange};
+use crate::outbound::sqlite::schema;
+use chrono::NaiveDateTime;
+use diesel::{Identifiable, Insertable, Queryable, Selectable};
+
+#[derive(Debug, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = schema::surugaya_product)]
+#[diesel(treat_none_as_null = true)]
+pub struct ProductRow {
+    pub id: i32,
+    pub date_added: NaiveDateTime,
+    pub url: String,
+    pub title: String,
+    pub image_url: String,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub condition: Condition,
+    pub price: Option<i32>,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub availability: Availability,
+    pub date_restocked: Option<NaiveDateTime>,
+    pub image_hash: Option<String>,
+    pub image_phash: Option<i64>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::surugaya_product)]
+#[diesel(treat_none_as_null = true)]
+pub struct ProductRowInsert<'a> {
+    pub url: &'a str,
+    pub title: &'a str,
+    pub image_url: &'a str,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub condition: Condition,
+    pub price: Option<i32>,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub availability: Availability,
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::surugaya_availability_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct AvailabilityEventRow {
+    pub date_added: NaiveDateTime,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub availability: Availability,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::surugaya_availability_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct AvailabilityEventRowInsert {
+    pub product_id: i32,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub availability: Availability,
+    pub previous_availability: Option<String>,
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::surugaya_price_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct PriceEventRow {
+    pub date_added: NaiveDateTime,
+    pub price: Option<i32>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::surugaya_price_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct PriceEventRowInsert {
+    pub product_id: i32,
+    pub price: Option<i32>,
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::surugaya_notification)]
+#[diesel(treat_none_as_null = true)]
+pub struct NotificationRow {
+    pub date_added: NaiveDateTime,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub kind: NotificationKind,
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::surugaya_search)]
+#[diesel(treat_none_as_null = true)]
+pub struct SearchRow {
+    pub id: i32,
+    pub date_added: NaiveDateTime,
+    pub keyword: String,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::surugaya_search)]
+#[diesel(treat_none_as_null = true)]
+pub struct SearchRowInsert<'a> {
+    pub keyword: &'a str,
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::surugaya_search_follower)]
+#[diesel(treat_none_as_null = true)]
+pub struct SearchFollowerRow {
+    pub search_id: i32,
+    pub user_id: i32,
+    pub date_followed: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::surugaya_search_follower)]
+#[diesel(treat_none_as_null = true)]
+pub struct SearchFollowerRowInsert {
+    pub search_id: i32,
+    pub user_id: i32,
+}
+
+impl SearchRow {
+    /// The search as saved by a single user, not saved when `follower` is `None`.
+    pub fn into_domain_for(self, follower: Option<&SearchFollowerRow>) -> Search {
+        Search::new(
+            self.id,
+            self.date_added.and_utc(),
+            self.keyword,
+            follower.is_some(),
+            follower.map(|f| f.date_followed.and_utc())
+        )
+    }
+}
+
+impl ProductRow {
+    pub fn into_domain(self) -> Product {
+        Product::new(self.id, self.date_added.and_utc(), self.url, self.title, self.image_url, self.condition, self.price, self.availability)
+            .with_date_restocked(self.date_restocked.map(|d| d.and_utc()))
+            .with_image_hash(self.image_hash)
+            .with_image_phash(self.image_phash.map(|h| h as u64))
+    }
+}
+
+impl AvailabilityEventRow {
+    pub fn into_domain(self) -> ProductHistoryEntry {
+        ProductHistoryEntry::new(self.date_added.and_utc(), ProductChange::Availability(self.availability))
+    }
+}
+
+impl PriceEventRow {
+    pub fn into_domain(self) -> ProductHistoryEntry {
+        ProductHistoryEntry::new(self.date_added.and_utc(), ProductChange::Price(self.price))
+    }
+}
+
+impl NotificationRow {
+    pub fn into_domain(self, username: String) -> ProductHistoryEntry {
+        ProductHistoryEntry::new(self.date_added.and_utc(), ProductChange::Notification { kind: self.kind, username })
+    }
+}
@@ -0,0 +1,7 @@
+use crate::domain::user::models::user::DEFAULT_USERNAME;
+use crate::domain::user::ports::UserRepository;
+use crate::outbound::sqlite::Sqlite;
+
+pub async fn default_user_id(db: &Sqlite) -> i32 {
+    db.get_user_by_name(DEFAULT_USERNAME).await.unwrap().unwrap().id()
+}
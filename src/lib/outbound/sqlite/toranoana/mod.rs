@@ -0,0 +1,970 @@
+use crate::domain::pagination::{Page, PageRequest};
+use crate::domain::product_history::{sort_history, GetProductError};
+use crate::domain::availability::Availability;
+use crate::domain::toranoana::models::creator::{Creator, CreatorArgs, CreatorKind, FollowCreatorError, FollowedCreator, GetCreatorsError, UnfollowCreatorError};
+use crate::domain::toranoana::models::product::{AddSkippingUrlError, CreateProductArgs, CreateProductError, GetProductsError, GetSkippingUrlsError, Product, ProductHistoryEntry, UpdateProductArgs, UpdateProductError};
+use crate::domain::toranoana::ports::ToranoanaRepository;
+use crate::outbound::sqlite::toranoana::models::{AvailabilityEventRow, AvailabilityEventRowInsert, CreatorFollowerRow, CreatorFollowerRowInsert, CreatorRow, CreatorRowInsert, NotificationRow, PriceEventRow, PriceEventRowInsert, ProductRow, ProductRowInsert, SkipProductCreatorRowInsert, SkipProductRow, SkipProductRowInsert};
+use crate::outbound::sqlite::{schema, Sqlite};
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::r2d2::ConnectionManager;
+use diesel::sql_types::Integer;
+use diesel::sqlite::Sqlite as SqliteBackend;
+use itertools::Itertools;
+use r2d2::PooledConnection;
+use std::collections::HashMap;
+use schema::app_user::dsl as user_dsl;
+use schema::toranoana_availability_event::dsl as availability_event_dsl;
+use schema::toranoana_creator::dsl as creator_dsl;
+use schema::toranoana_creator_follower::dsl as creator_follower_dsl;
+use schema::toranoana_notification::dsl as notification_dsl;
+use schema::toranoana_price_event::dsl as price_event_dsl;
+use schema::toranoana_product::dsl as product_dsl;
+use schema::toranoana_product_creator::dsl as product_creator_dsl;
+use schema::toranoana_skip_product::dsl as skip_product_dsl;
+use schema::toranoana_skip_product_creator::dsl as skip_product_creator_dsl;
+
+mod models;
+
+const LOAD_BATCH_SIZE: usize = 500;
+
+impl Sqlite {
+    fn get_toranoana_creator_row_by_id(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        creator_id: i32
+    ) -> Result<Option<CreatorRow>, anyhow::Error> {
+        let creator = creator_dsl::toranoana_creator
+            .select(CreatorRow::as_select())
+            .filter(creator_dsl::id.eq(creator_id))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get creator with id '{}'", creator_id))?;
+        Ok(creator)
+    }
+
+    fn get_toranoana_creator_row_by_name(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        creator_args: &CreatorArgs,
+    ) -> Result<Option<CreatorRow>, anyhow::Error> {
+        let creator = creator_dsl::toranoana_creator
+            .select(CreatorRow::as_select())
+            .filter(creator_dsl::name.eq(creator_args.name()))
+            .filter(creator_dsl::kind.eq(creator_args.kind().to_string()))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get {} with name '{}'", creator_args.kind(), creator_args.name()))?;
+        Ok(creator)
+    }
+
+    fn insert_toranoana_creator_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        creator_args: &CreatorArgs,
+    ) -> Result<CreatorRow, anyhow::Error> {
+        let creator = diesel::insert_into(creator_dsl::toranoana_creator)
+            .values(CreatorRowInsert { name: creator_args.name(), kind: creator_args.kind(), following: false, date_followed: None })
+            .returning(CreatorRow::as_returning())
+            .get_result(connection)
+            .with_context(|| format!("cannot insert {} with name '{}'", creator_args.kind(), creator_args.name()))?;
+        Ok(creator)
+    }
+
+    fn get_or_insert_toranoana_creator_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        creator_args: &CreatorArgs,
+    ) -> Result<CreatorRow, anyhow::Error> {
+        match self.get_toranoana_creator_row_by_name(connection, creator_args)? {
+            Some(creator) => Ok(creator),
+            None => self.insert_toranoana_creator_row(connection, creator_args),
+        }
+    }
+
+    fn get_toranoana_creator_rows(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+    ) -> Result<Vec<CreatorRow>, anyhow::Error> {
+        let creators = creator_dsl::toranoana_creator
+            .select(CreatorRow::as_select())
+            .get_results(connection)
+            .with_context(|| "cannot select creators")?;
+        Ok(creators)
+    }
+
+    fn filtered_toranoana_creator_query<'a>(
+        &self,
+        user_id: i32,
+        following: Option<bool>,
+    ) -> schema::toranoana_creator::BoxedQuery<'a, SqliteBackend> {
+        let followed_ids = creator_follower_dsl::toranoana_creator_follower
+            .filter(creator_follower_dsl::user_id.eq(user_id))
+            .select(creator_follower_dsl::creator_id);
+        match following {
+            Some(true) => creator_dsl::toranoana_creator.filter(creator_dsl::id.eq_any(followed_ids)).into_boxed(),
+            Some(false) => creator_dsl::toranoana_creator.filter(diesel::dsl::not(creator_dsl::id.eq_any(followed_ids))).into_boxed(),
+            None => creator_dsl::toranoana_creator.into_boxed(),
+        }
+    }
+
+    fn get_toranoana_creator_rows_page(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        user_id: i32,
+        following: Option<bool>,
+        page: PageRequest,
+    ) -> Result<(Vec<CreatorRow>, i64), anyhow::Error> {
+        let total = self.filtered_toranoana_creator_query(user_id, following)
+            .count()
+            .get_result::<i64>(connection)
+            .with_context(|| "cannot count creators")?;
+        let creators = self.filtered_toranoana_creator_query(user_id, following)
+            .select(CreatorRow::as_select())
+            .order_by(creator_dsl::id.asc())
+            .limit(page.page_size() as i64)
+            .offset(page.offset())
+            .get_results(connection)
+            .with_context(|| "cannot select creators")?;
+        Ok((creators, total))
+    }
+
+    fn get_toranoana_creator_follower_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        creator: &CreatorRow,
+        user_id: i32,
+    ) -> Result<Option<CreatorFollowerRow>, anyhow::Error> {
+        let follower = creator_follower_dsl::toranoana_creator_follower
+            .select(CreatorFollowerRow::as_select())
+            .filter(creator_follower_dsl::creator_id.eq(creator.id))
+            .filter(creator_follower_dsl::user_id.eq(user_id))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get follower '{}' of creator '{}'", user_id, creator.name))?;
+        Ok(follower)
+    }
+
+    fn get_toranoana_creator_follower_rows(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+    ) -> Result<Vec<CreatorFollowerRow>, anyhow::Error> {
+        let followers = creator_follower_dsl::toranoana_creator_follower
+            .select(CreatorFollowerRow::as_select())
+            .get_results(connection)
+            .with_context(|| "cannot get creator followers")?;
+        Ok(followers)
+    }
+
+    fn get_toranoana_creator_follower_rows_by_user(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        user_id: i32,
+    ) -> Result<Vec<CreatorFollowerRow>, anyhow::Error> {
+        let followers = creator_follower_dsl::toranoana_creator_follower
+            .select(CreatorFollowerRow::as_select())
+            .filter(creator_follower_dsl::user_id.eq(user_id))
+            .get_results(connection)
+            .with_context(|| format!("cannot get creators followed by user '{}'", user_id))?;
+        Ok(followers)
+    }
+
+    fn insert_toranoana_creator_follower_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        creator: &CreatorRow,
+        user_id: i32,
+    ) -> Result<(), anyhow::Error> {
+        diesel::insert_into(creator_follower_dsl::toranoana_creator_follower)
+            .values(CreatorFollowerRowInsert { creator_id: creator.id, user_id })
+            .execute(connection)
+            .with_context(|| format!("cannot follow creator '{}' for user '{}'", creator.name, user_id))?;
+        Ok(())
+    }
+
+    fn delete_toranoana_creator_follower_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        creator: &CreatorRow,
+        user_id: i32,
+    ) -> Result<(), anyhow::Error> {
+        diesel::delete(creator_follower_dsl::toranoana_creator_follower)
+            .filter(creator_follower_dsl::creator_id.eq(creator.id))
+            .filter(creator_follower_dsl::user_id.eq(user_id))
+            .execute(connection)
+            .with_context(|| format!("cannot unfollow creator '{}' for user '{}'", creator.name, user_id))?;
+        Ok(())
+    }
+
+    /// Keeps `following` and `date_followed` of the creator in sync with its followers, they mean followed by anybody.
+    fn update_toranoana_creator_row_following(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        creator: &CreatorRow,
+    ) -> Result<(), anyhow::Error> {
+        diesel::sql_query(
+            "UPDATE toranoana_creator SET \
+                following = EXISTS (SELECT 1 FROM toranoana_creator_follower f WHERE f.creator_id = toranoana_creator.id), \
+                date_followed = (SELECT MIN(f.date_followed) FROM toranoana_creator_follower f WHERE f.creator_id = toranoana_creator.id) \
+            WHERE id = ?"
+        )
+            .bind::<Integer, _>(creator.id)
+            .execute(connection)
+            .with_context(|| format!("cannot update following of creator '{}'", creator.name))?;
+        Ok(())
+    }
+
+    fn get_toranoana_product_row_by_url(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        url: &str
+    ) -> Result<Option<ProductRow>, anyhow::Error> {
+        let product = product_dsl::toranoana_product
+            .select(ProductRow::as_select())
+            .filter(product_dsl::url.eq(url))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get product with url '{}'", url))?;
+        Ok(product)
+    }
+
+    fn get_toranoana_product_row_by_id(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32
+    ) -> Result<Option<ProductRow>, anyhow::Error> {
+        let product = product_dsl::toranoana_product
+            .select(ProductRow::as_select())
+            .find(product_id)
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get product with id '{}'", product_id))?;
+        Ok(product)
+    }
+
+    fn get_toranoana_product_rows(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+    ) -> Result<Vec<ProductRow>, anyhow::Error> {
+        let products = product_dsl::toranoana_product
+            .select(ProductRow::as_select())
+            .order_by(product_dsl::date_added.desc())
+            .get_results(connection)
+            .with_context(|| "cannot get products")?;
+        Ok(products)
+    }
+
+    fn get_toranoana_product_rows_by_creator(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        creator_id: i32
+    ) -> Result<Vec<ProductRow>, anyhow::Error> {
+        let products = product_creator_dsl::toranoana_product_creator
+            .inner_join(product_dsl::toranoana_product)
+            .select(ProductRow::as_select())
+            .filter(product_creator_dsl::creator_id.eq(creator_id))
+            .order_by(product_dsl::date_added.desc())
+            .get_results(connection)
+            .with_context(|| format!("cannot get products by creator with id {}", creator_id))?;
+        Ok(products)
+    }
+
+    fn get_toranoana_product_rows_page(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        page: PageRequest,
+    ) -> Result<(Vec<ProductRow>, i64), anyhow::Error> {
+        let total = product_dsl::toranoana_product
+            .count()
+            .get_result::<i64>(connection)
+            .with_context(|| "cannot count products")?;
+        let products = product_dsl::toranoana_product
+            .select(ProductRow::as_select())
+            .order_by((product_dsl::date_added.desc(), product_dsl::id.desc()))
+            .limit(page.page_size() as i64)
+            .offset(page.offset())
+            .get_results(connection)
+            .with_context(|| "cannot get products")?;
+        Ok((products, total))
+    }
+
+    /// Products found for any of the creators, once each.
+    fn get_toranoana_product_rows_page_by_creators(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        creator_ids: &[i32],
+        page: PageRequest,
+    ) -> Result<(Vec<ProductRow>, i64), anyhow::Error> {
+        let product_ids = || product_creator_dsl::toranoana_product_creator
+            .filter(product_creator_dsl::creator_id.eq_any(creator_ids))
+            .select(product_creator_dsl::product_id);
+        let total = product_dsl::toranoana_product
+            .filter(product_dsl::id.eq_any(product_ids()))
+            .count()
+            .get_result::<i64>(connection)
+            .with_context(|| format!("cannot count products by creators with ids {:?}", creator_ids))?;
+        let products = product_dsl::toranoana_product
+            .select(ProductRow::as_select())
+            .filter(product_dsl::id.eq_any(product_ids()))
+            .order_by((product_dsl::date_added.desc(), product_dsl::id.desc()))
+            .limit(page.page_size() as i64)
+            .offset(page.offset())
+            .get_results(connection)
+            .with_context(|| format!("cannot get products by creators with ids {:?}", creator_ids))?;
+        Ok((products, total))
+    }
+
+    fn insert_toranoana_product_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_args: &CreateProductArgs,
+    ) -> Result<ProductRow, anyhow::Error> {
+        let product = diesel::insert_into(product_dsl::toranoana_product)
+            .values(ProductRowInsert {
+                url: product_args.url(),
+                title: product_args.title(),
+                image_url: product_args.image_url(),
+                category: product_args.category(),
+                price: product_args.price(),
+                availability: product_args.availability(),
+            })
+            .returning(ProductRow::as_returning())
+            .get_result(connection)
+            .with_context(|| format!("cannot insert product with url '{}'", product_args.url()))?;
+        Ok(product)
+    }
+
+    fn insert_toranoana_product_creator_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_row: &ProductRow,
+        creator_row: &CreatorRow,
+    ) -> Result<(), anyhow::Error> {
+        diesel::insert_into(product_creator_dsl::toranoana_product_creator)
+            .values((product_creator_dsl::product_id.eq(product_row.id), product_creator_dsl::creator_id.eq(creator_row.id)))
+            .execute(connection)
+            .with_context(|| format!("cannot insert creator with name '{}' for product '{}'", creator_row.name, product_row.url))?;
+        Ok(())
+    }
+
+    fn update_toranoana_product_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product: &ProductRow,
+        args: &UpdateProductArgs,
+    ) -> Result<ProductRow, anyhow::Error> {
+        let date_restocked = match !product.availability.is_available() && args.availability().is_available() {
+            true => Some(Utc::now().naive_utc()),
+            false => product.date_restocked,
+        };
+        if product.availability != args.availability() {
+            self.insert_toranoana_availability_event_row(connection, product.id, Some(product.availability.clone()), args.availability())?;
+        }
+        let product = diesel::update(&product)
+            .set((
+                product_dsl::availability.eq(args.availability().to_string()),
+                product_dsl::date_restocked.eq(date_restocked),
+            ))
+            .returning(ProductRow::as_returning())
+            .get_result(connection)
+            .with_context(|| format!("cannot update product with url '{}'", product.url))?;
+        Ok(product)
+    }
+
+    fn insert_toranoana_availability_event_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+        previous_availability: Option<Availability>,
+        availability: Availability,
+    ) -> Result<(), anyhow::Error> {
+        diesel::insert_into(availability_event_dsl::toranoana_availability_event)
+            .values(AvailabilityEventRowInsert { product_id, availability, previous_availability: previous_availability.map(|a| a.to_string()) })
+            .execute(connection)
+            .with_context(|| format!("cannot insert availability event for product '{}'", product_id))?;
+        Ok(())
+    }
+
+    fn insert_toranoana_price_event_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+        price: Option<i32>,
+    ) -> Result<(), anyhow::Error> {
+        diesel::insert_into(price_event_dsl::toranoana_price_event)
+            .values(PriceEventRowInsert { product_id, price })
+            .execute(connection)
+            .with_context(|| format!("cannot insert price event for product '{}'", product_id))?;
+        Ok(())
+    }
+
+    fn get_toranoana_product_history_entries(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_id: i32,
+    ) -> Result<Vec<ProductHistoryEntry>, anyhow::Error> {
+        let availability_events = availability_event_dsl::toranoana_availability_event
+            .select(AvailabilityEventRow::as_select())
+            .filter(availability_event_dsl::product_id.eq(product_id))
+            .order_by(availability_event_dsl::id.asc())
+            .get_results(connection)
+            .with_context(|| format!("cannot get availability events for product '{}'", product_id))?;
+        let price_events = price_event_dsl::toranoana_price_event
+            .select(PriceEventRow::as_select())
+            .filter(price_event_dsl::product_id.eq(product_id))
+            .order_by(price_event_dsl::id.asc())
+            .get_results(connection)
+            .with_context(|| format!("cannot get price events for product '{}'", product_id))?;
+        let notifications = notification_dsl::toranoana_notification
+            .inner_join(user_dsl::app_user)
+            .select((NotificationRow::as_select(), user_dsl::username))
+            .filter(notification_dsl::product_id.eq(product_id))
+            .order_by(notification_dsl::id.asc())
+            .get_results::<(NotificationRow, String)>(connection)
+            .with_context(|| format!("cannot get notifications for product '{}'", product_id))?;
+        let mut history = availability_events.into_iter().map(|e| e.into_domain())
+            .chain(price_events.into_iter().map(|e| e.into_domain()))
+            .chain(notifications.into_iter().map(|(n, username)| n.into_domain(username)))
+            .collect::<Vec<_>>();
+        sort_history(&mut history);
+        Ok(history)
+    }
+
+    fn add_toranoana_skip_product<S: AsRef<str>>(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        url: &str,
+        creators: &[S],
+    ) -> Result<SkipProductRow, anyhow::Error> {
+        let skip_product = diesel::insert_into(skip_product_dsl::toranoana_skip_product)
+            .values(SkipProductRowInsert { url })
+            .returning(SkipProductRow::as_returning())
+            .get_result(connection)
+            .with_context(|| format!("cannot insert skip product with url '{}'", url))?;
+        diesel::insert_into(skip_product_creator_dsl::toranoana_skip_product_creator)
+            .values(creators.iter().map(|c| c.as_ref()).unique().map(|c| SkipProductCreatorRowInsert { skip_product_id: skip_product.id, creator_name: c }).collect::<Vec<_>>())
+            .execute(connection)
+            .with_context(|| format!("cannot insert creators for skip product with url '{}'", url))?;
+        Ok(skip_product)
+    }
+
+    fn delete_toranoana_skip_products_for_creator(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        creator: &str,
+    ) -> Result<(), anyhow::Error> {
+        diesel::delete(skip_product_dsl::toranoana_skip_product)
+            .filter(skip_product_dsl::id.eq_any(skip_product_creator_dsl::toranoana_skip_product_creator.filter(skip_product_creator_dsl::creator_name.eq(creator)).select(skip_product_creator_dsl::skip_product_id)))
+            .execute(connection)
+            .with_context(|| format!("cannot delete skip products for creator '{}'", creator))?;
+        Ok(())
+    }
+
+    fn get_toranoana_skip_products(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+    ) -> Result<Vec<SkipProductRow>, anyhow::Error> {
+        let skip_products = skip_product_dsl::toranoana_skip_product
+            .select(SkipProductRow::as_select())
+            .get_results(connection)
+            .with_context(|| "cannot get skip products")?;
+        Ok(skip_products)
+    }
+
+    fn get_toranoana_creator_rows_by_product_ids(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product_ids: &[i32],
+    ) -> Result<Vec<(i32, CreatorRow)>, anyhow::Error> {
+        let creators = product_creator_dsl::toranoana_product_creator
+            .inner_join(creator_dsl::toranoana_creator)
+            .select((product_creator_dsl::product_id, CreatorRow::as_select()))
+            .filter(product_creator_dsl::product_id.eq_any(product_ids))
+            .order_by((product_creator_dsl::product_id, creator_dsl::kind.desc(), creator_dsl::id))
+            .get_results(connection)
+            .with_context(|| "cannot get creators of products")?;
+        Ok(creators)
+    }
+
+    /// Loads the creators of all given products with one query per batch. The order of `products` is kept.
+    fn load_toranoana_products(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        products: Vec<ProductRow>,
+    ) -> Result<Vec<Product>, anyhow::Error> {
+        let mut creators: HashMap<i32, Vec<Creator>> = HashMap::new();
+        for batch in products.chunks(LOAD_BATCH_SIZE) {
+            let product_ids = batch.iter().map(|p| p.id).collect::<Vec<_>>();
+            for (product_id, creator) in self.get_toranoana_creator_rows_by_product_ids(connection, &product_ids)? {
+                creators.entry(product_id).or_default().push(creator.into_domain());
+            }
+        }
+        Ok(
+            products.into_iter()
+                .map(|product| {
+                    let creators = creators.remove(&product.id).unwrap_or_default();
+                    product.into_domain(creators)
+                })
+                .collect()
+        )
+    }
+
+    fn load_toranoana_product(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        product: ProductRow,
+    ) -> Result<Product, anyhow::Error> {
+        let product = self.load_toranoana_products(connection, vec![product])?
+            .pop()
+            .with_context(|| "cannot load product")?;
+        Ok(product)
+    }
+}
+
+#[async_trait]
+impl ToranoanaRepository for Sqlite {
+    async fn follow_toranoana_creator(&self, user_id: i32, args: &CreatorArgs) -> Result<(), FollowCreatorError> {
+        let args = args.clone();
+        self.write(move |db, connection| {
+            let creator = db.get_or_insert_toranoana_creator_row(connection, &args)?;
+            if let Some(follower) = db.get_toranoana_creator_follower_row(connection, &creator, user_id)? {
+                return Err(FollowCreatorError::AlreadyFollowedError(follower.date_followed.and_utc()));
+            }
+            connection.transaction(|connection| -> Result<(), anyhow::Error> {
+                db.insert_toranoana_creator_follower_row(connection, &creator, user_id)?;
+                db.update_toranoana_creator_row_following(connection, &creator)?;
+                db.delete_toranoana_skip_products_for_creator(connection, args.name())
+            })?;
+            Ok(())
+        }).await
+    }
+
+    async fn unfollow_toranoana_creator(&self, user_id: i32, creator_id: i32) -> Result<(), UnfollowCreatorError> {
+        self.write(move |db, connection| {
+            let creator = db.get_toranoana_creator_row_by_id(connection, creator_id)?
+                .ok_or(UnfollowCreatorError::UnknownCreator { id: creator_id })?;
+            if db.get_toranoana_creator_follower_row(connection, &creator, user_id)?.is_none() {
+                return Err(UnfollowCreatorError::CreatorNotFollowed { name: creator.name });
+            }
+            connection.transaction(|connection| -> Result<(), anyhow::Error> {
+                db.delete_toranoana_creator_follower_row(connection, &creator, user_id)?;
+                db.update_toranoana_creator_row_following(connection, &creator)
+            })?;
+            Ok(())
+        }).await
+    }
+
+    async fn get_toranoana_creators(&self, user_id: i32) -> Result<Vec<Creator>, GetCreatorsError> {
+        self.read(move |db, connection| {
+            let creator_rows = db.get_toranoana_creator_rows(connection)?;
+            let followers = db.get_toranoana_creator_follower_rows_by_user(connection, user_id)?
+                .into_iter()
+                .map(|f| (f.creator_id, f))
+                .collect::<HashMap<_, _>>();
+            let creators = creator_rows.into_iter()
+                .map(|c| {
+                    let follower = followers.get(&c.id);
+                    c.into_domain_for(follower)
+                })
+                .collect();
+            Ok(creators)
+        }).await
+    }
+
+    async fn get_toranoana_creators_page(&self, user_id: i32, following: Option<bool>, page: PageRequest) -> Result<Page<Creator>, GetCreatorsError> {
+        self.read(move |db, connection| {
+            let (creator_rows, total) = db.get_toranoana_creator_rows_page(connection, user_id, following, page)?;
+            let followers = db.get_toranoana_creator_follower_rows_by_user(connection, user_id)?
+                .into_iter()
+                .map(|f| (f.creator_id, f))
+                .collect::<HashMap<_, _>>();
+            let creators = creator_rows.into_iter()
+                .map(|c| {
+                    let follower = followers.get(&c.id);
+                    c.into_domain_for(follower)
+                })
+                .collect();
+            Ok(Page::new(creators, page, total))
+        }).await
+    }
+
+    async fn get_followed_toranoana_creators(&self) -> Result<Vec<FollowedCreator>, GetCreatorsError> {
+        self.read(move |db, connection| {
+            let follower_rows = db.get_toranoana_creator_follower_rows(connection)?;
+            let user_ids = follower_rows.iter().map(|f| f.user_id).unique().collect::<Vec<_>>();
+            let users = db.get_user_rows_by_ids(connection, &user_ids)?
+                .into_iter()
+                .map(|u| (u.id, u.into_domain()))
+                .collect::<HashMap<_, _>>();
+            let mut followers = follower_rows.into_iter().into_group_map_by(|f| f.creator_id);
+            let creators = db.get_toranoana_creator_rows(connection)?
+                .into_iter()
+                .filter_map(|creator| {
+                    let mut creator_followers = followers.remove(&creator.id)?
+                        .into_iter()
+                        .filter_map(|f| users.get(&f.user_id).cloned())
+                        .collect::<Vec<_>>();
+                    creator_followers.sort_by(|a, b| a.username().cmp(b.username()));
+                    Some(FollowedCreator::new(creator.into_domain(), creator_followers))
+                })
+                .collect();
+            Ok(creators)
+        }).await
+    }
+
+    async fn create_toranoana_product(&self, args: &CreateProductArgs) -> Result<Product, CreateProductError> {
+        let args = args.clone();
+        self.write(move |db, connection| {
+            if let Some(product_row) = db.get_toranoana_product_row_by_url(connection, args.url())? {
+                return Err(CreateProductError::DuplicateProduct { url: product_row.url, title: product_row.title });
+            }
+            let product = connection.transaction(|connection| -> Result<Product, anyhow::Error> {
+                let product_row = db.insert_toranoana_product_row(connection, &args)?;
+                db.insert_toranoana_availability_event_row(connection, product_row.id, None, args.availability())?;
+                db.insert_toranoana_price_event_row(connection, product_row.id, args.price())?;
+                let creators = args.circle().map(|c| CreatorArgs::new(c.to_owned(), CreatorKind::Circle)).into_iter()
+                    .chain(args.artists().iter().unique().map(|a| CreatorArgs::new(a.to_owned(), CreatorKind::Artist)));
+                for creator_args in creators {
+                    let creator_row = db.get_or_insert_toranoana_creator_row(connection, &creator_args)?;
+                    db.insert_toranoana_product_creator_row(connection, &product_row, &creator_row)?;
+                }
+                db.load_toranoana_product(connection, product_row)
+            })?;
+            Ok(product)
+        }).await
+    }
+
+    async fn update_toranoana_product(&self, args: &UpdateProductArgs) -> Result<Product, UpdateProductError> {
+        let args = args.clone();
+        self.write(move |db, connection| {
+            let product_row = db.get_toranoana_product_row_by_url(connection, args.url())?
+                .ok_or_else(|| UpdateProductError::ProductMissing { url: args.url().to_owned() })?;
+            let product = connection.transaction(|connection| -> Result<Product, anyhow::Error> {
+                let product_row = db.update_toranoana_product_row(connection, &product_row, &args)?;
+                db.load_toranoana_product(connection, product_row)
+            })?;
+            Ok(product)
+        }).await
+    }
+
+    async fn get_toranoana_product(&self, product_id: i32) -> Result<Product, GetProductError> {
+        self.read(move |db, connection| {
+            let product_row = db.get_toranoana_product_row_by_id(connection, product_id)?
+                .ok_or(GetProductError::ProductMissing { id: product_id })?;
+            let product = db.load_toranoana_product(connection, product_row)?;
+            Ok(product)
+        }).await
+    }
+
+    async fn get_toranoana_product_history(&self, product_id: i32) -> Result<Vec<ProductHistoryEntry>, GetProductError> {
+        self.read(move |db, connection| {
+            if db.get_toranoana_product_row_by_id(connection, product_id)?.is_none() {
+                return Err(GetProductError::ProductMissing { id: product_id });
+            }
+            let history = db.get_toranoana_product_history_entries(connection, product_id)?;
+            Ok(history)
+        }).await
+    }
+
+    async fn get_toranoana_products(&self) -> Result<Vec<Product>, GetProductsError> {
+        self.read(move |db, connection| {
+            let product_rows = db.get_toranoana_product_rows(connection)?;
+            let products = db.load_toranoana_products(connection, product_rows)?;
+            Ok(products)
+        }).await
+    }
+
+    async fn get_toranoana_products_by_creator(&self, creator_id: i32) -> Result<Vec<Product>, GetProductsError> {
+        self.read(move |db, connection| {
+            let product_rows = db.get_toranoana_product_rows_by_creator(connection, creator_id)?;
+            let products = db.load_toranoana_products(connection, product_rows)?;
+            Ok(products)
+        }).await
+    }
+
+    async fn get_toranoana_products_page(&self, page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        self.read(move |db, connection| {
+            let (product_rows, total) = db.get_toranoana_product_rows_page(connection, page)?;
+            let products = db.load_toranoana_products(connection, product_rows)?;
+            Ok(Page::new(products, page, total))
+        }).await
+    }
+
+    async fn get_toranoana_products_page_by_creator(&self, creator_id: i32, page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        self.read(move |db, connection| {
+            let (product_rows, total) = db.get_toranoana_product_rows_page_by_creators(connection, &[creator_id], page)?;
+            let products = db.load_toranoana_products(connection, product_rows)?;
+            Ok(Page::new(products, page, total))
+        }).await
+    }
+
+    async fn get_toranoana_products_page_by_creators(&self, creator_ids: &[i32], page: PageRequest) -> Result<Page<Product>, GetProductsError> {
+        let creator_ids = creator_ids.to_vec();
+        self.read(move |db, connection| {
+            let (product_rows, total) = db.get_toranoana_product_rows_page_by_creators(connection, &creator_ids, page)?;
+            let products = db.load_toranoana_products(connection, product_rows)?;
+            Ok(Page::new(products, page, total))
+        }).await
+    }
+
+    async fn add_toranoana_skipping_url<S: AsRef<str> + Sync>(&self, url: &str, creators: &[S]) -> Result<(), AddSkippingUrlError> {
+        let url = url.to_owned();
+        let creators = creators.iter().map(|c| c.as_ref().to_owned()).collect::<Vec<_>>();
+        self.write(move |db, connection| {
+            db.add_toranoana_skip_product(connection, &url, &creators)?;
+            Ok(())
+        }).await
+    }
+
+    async fn get_toranoana_skipping_urls(&self) -> Result<Vec<String>, GetSkippingUrlsError> {
+        self.read(move |db, connection| {
+            let skip_products = db.get_toranoana_skip_products(connection)?;
+            let urls = skip_products.into_iter()
+                .map(|product| product.url)
+                .collect();
+            Ok(urls)
+        }).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::domain::image::models::image::{CachedImage, SetProductImageError};
+    use crate::domain::product_history::{NotificationKind, ProductChange};
+    use crate::domain::site::SiteRepository;
+    use crate::domain::toranoana::SITE;
+    use crate::domain::user::models::user::DEFAULT_USERNAME;
+    use crate::domain::user::ports::UserRepository;
+    use crate::outbound::sqlite::test_util::default_user_id;
+
+    #[tokio::test]
+    async fn test_follow_toranoana_creator() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        db.follow_toranoana_creator(user_id, &artist_args()).await.unwrap();
+
+        let creators = db.get_toranoana_creators(user_id).await.unwrap();
+        assert_eq!(creators.len(), 1);
+        let creator = creators.first().unwrap();
+        assert_eq!(creator.name(), artist_args().name());
+        assert_eq!(creator.kind(), CreatorKind::Artist);
+        assert!(creator.following());
+        assert_ne!(creator.date_followed(), None);
+        assert!(matches!(db.follow_toranoana_creator(user_id, &artist_args()).await, Err(FollowCreatorError::AlreadyFollowedError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_follow_toranoana_artist_and_circle_of_same_name() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        db.follow_toranoana_creator(user_id, &artist_args()).await.unwrap();
+        db.follow_toranoana_creator(user_id, &CreatorArgs::new(artist_args().name().to_owned(), CreatorKind::Circle)).await.unwrap();
+
+        let creators = db.get_toranoana_creators(user_id).await.unwrap();
+        assert_eq!(creators.iter().map(|c| c.kind()).collect::<Vec<_>>(), vec![CreatorKind::Artist, CreatorKind::Circle]);
+        assert!(creators.iter().all(|c| c.following()));
+    }
+
+    #[tokio::test]
+    async fn test_unfollow_toranoana_creator() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let users = db.setup_users(DEFAULT_USERNAME, &["alice".to_owned()]).await.unwrap();
+        let (alice, default) = (users.first().unwrap(), users.last().unwrap());
+        db.follow_toranoana_creator(alice.id(), &circle_args()).await.unwrap();
+        db.follow_toranoana_creator(default.id(), &circle_args()).await.unwrap();
+        let circle = db.get_toranoana_creators(alice.id()).await.unwrap().into_iter().next().unwrap();
+
+        db.unfollow_toranoana_creator(default.id(), circle.id()).await.unwrap();
+        assert!(db.get_toranoana_creators(alice.id()).await.unwrap().first().unwrap().following());
+        assert!(!db.get_toranoana_creators(default.id()).await.unwrap().first().unwrap().following());
+        assert!(matches!(db.unfollow_toranoana_creator(default.id(), circle.id()).await, Err(UnfollowCreatorError::CreatorNotFollowed { .. })));
+        assert!(matches!(db.unfollow_toranoana_creator(default.id(), circle.id() + 1).await, Err(UnfollowCreatorError::UnknownCreator { .. })));
+
+        let followed = db.get_followed_toranoana_creators().await.unwrap();
+        assert_eq!(followed.len(), 1);
+        assert_eq!(followed.first().unwrap().creator().id(), circle.id());
+        assert_eq!(followed.first().unwrap().followers().iter().map(|u| u.username()).collect::<Vec<_>>(), vec!["alice"]);
+    }
+
+    #[tokio::test]
+    async fn test_create_toranoana_product() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let args = product_args();
+        let product = db.create_toranoana_product(&args).await.unwrap();
+
+        assert_eq!(product.url(), args.url());
+        assert_eq!(product.title(), args.title());
+        assert_eq!(product.circle().map(|c| c.name()), args.circle());
+        assert_eq!(product.artists().iter().map(|a| a.name()).collect::<Vec<_>>(), args.artists());
+        assert_eq!(product.image_url(), args.image_url());
+        assert_eq!(product.category(), args.category());
+        assert_eq!(product.price(), args.price());
+        assert_eq!(product.availability(), args.availability());
+        assert!(matches!(db.create_toranoana_product(&args).await, Err(CreateProductError::DuplicateProduct { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_get_toranoana_products_by_creator() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        db.follow_toranoana_creator(user_id, &circle_args()).await.unwrap();
+        let product = db.create_toranoana_product(&product_args()).await.unwrap();
+        db.create_toranoana_product(&product_args2()).await.unwrap();
+
+        let circle = db.get_toranoana_creators(user_id).await.unwrap().into_iter().find(|c| c.kind() == CreatorKind::Circle).unwrap();
+        let products = db.get_toranoana_products_by_creator(circle.id()).await.unwrap();
+        assert_eq!(products.iter().map(|p| p.id()).collect::<Vec<_>>(), vec![product.id()]);
+        assert_eq!(products.first().unwrap().circle().map(|c| c.id()), Some(circle.id()));
+        let artist = products.first().unwrap().artists().first().unwrap().id();
+        assert_eq!(db.get_toranoana_products_by_creator(artist).await.unwrap().len(), 2);
+        assert_eq!(db.get_toranoana_products().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_toranoana_products_page() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        db.follow_toranoana_creator(user_id, &artist_args()).await.unwrap();
+        let product = db.create_toranoana_product(&product_args()).await.unwrap();
+        let product2 = db.create_toranoana_product(&product_args2()).await.unwrap();
+
+        let page = db.get_toranoana_products_page(PageRequest::new(2, 1)).await.unwrap();
+        assert_eq!(page.total_items(), 2);
+        assert_eq!(page.items(), &[product]);
+        let artist = db.get_toranoana_creators(user_id).await.unwrap().first().unwrap().id();
+        let page = db.get_toranoana_products_page_by_creator(artist, PageRequest::new(1, 1)).await.unwrap();
+        assert_eq!(page.total_items(), 2);
+        assert_eq!(page.items(), &[product2]);
+    }
+
+    #[tokio::test]
+    async fn test_get_toranoana_products_page_by_creators() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        db.follow_toranoana_creator(user_id, &artist_args()).await.unwrap();
+        db.follow_toranoana_creator(user_id, &circle_args()).await.unwrap();
+        let product = db.create_toranoana_product(&product_args()).await.unwrap();
+        let product2 = db.create_toranoana_product(&product_args2()).await.unwrap();
+        let creator_ids = db.get_toranoana_creators(user_id).await.unwrap().iter().map(|c| c.id()).collect::<Vec<_>>();
+
+        let page = db.get_toranoana_products_page_by_creators(&creator_ids, PageRequest::new(1, 10)).await.unwrap();
+        assert_eq!(page.total_items(), 2);
+        assert_eq!(page.items(), &[product2.clone(), product.clone()]);
+        let page = db.get_toranoana_products_page_by_creators(&creator_ids, PageRequest::new(2, 1)).await.unwrap();
+        assert_eq!(page.items(), &[product]);
+        assert!(db.get_toranoana_products_page_by_creators(&[], PageRequest::default()).await.unwrap().items().is_empty());
+
+        let page = db.get_toranoana_creators_page(user_id, Some(true), PageRequest::new(1, 10)).await.unwrap();
+        assert_eq!(page.total_items(), 2);
+        let page = db.get_toranoana_creators_page(user_id, Some(false), PageRequest::new(1, 10)).await.unwrap();
+        assert_eq!(page.items().iter().map(|c| c.name()).collect::<Vec<_>>(), vec!["kantoku"]);
+    }
+
+    #[tokio::test]
+    async fn test_update_toranoana_product() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let product = db.create_toranoana_product(&product_args()).await.unwrap();
+
+        let product = db.update_toranoana_product(&UpdateProductArgs::new(product.url().to_owned(), Availability::NotAvailable)).await.unwrap();
+        assert_eq!(product.availability(), Availability::NotAvailable);
+        assert_eq!(product.date_restocked(), None);
+        assert_eq!(product.creators().len(), 2);
+        let product = db.update_toranoana_product(&UpdateProductArgs::new(product.url().to_owned(), Availability::Available)).await.unwrap();
+        assert_ne!(product.date_restocked(), None);
+        assert!(matches!(db.update_toranoana_product(&UpdateProductArgs::new("https://missing".to_owned(), Availability::Available)).await, Err(UpdateProductError::ProductMissing { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_set_toranoana_product_image() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let product = db.create_toranoana_product(&product_args()).await.unwrap();
+
+        let hash = "a".repeat(64);
+        let image = CachedImage::new(hash.clone(), Some(0xF000_0000_0000_0001));
+        db.set_product_image(SITE, product.id(), &image).await.unwrap();
+        let loaded = db.get_toranoana_product(product.id()).await.unwrap();
+        assert_eq!(loaded.image_hash(), Some(hash.as_str()));
+        assert_eq!(loaded.image_phash(), Some(0xF000_0000_0000_0001));
+        assert!(matches!(db.set_product_image(SITE, product.id() + 1, &image).await, Err(SetProductImageError::ProductMissing { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_get_toranoana_product_history() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        let product = db.create_toranoana_product(&product_args()).await.unwrap();
+        db.update_toranoana_product(&UpdateProductArgs::new(product.url().to_owned(), Availability::NotAvailable)).await.unwrap();
+        db.add_notifications(SITE, user_id, NotificationKind::NewProduct, &[product.id()]).await.unwrap();
+
+        let history = db.get_toranoana_product_history(product.id()).await.unwrap();
+        let availabilities = history.iter()
+            .filter_map(|e| match e.change() { ProductChange::Availability(a) => Some(a.clone()), _ => None })
+            .collect::<Vec<_>>();
+        assert_eq!(availabilities, vec![Availability::Available, Availability::NotAvailable]);
+        let prices = history.iter()
+            .filter_map(|e| match e.change() { ProductChange::Price(p) => Some(*p), _ => None })
+            .collect::<Vec<_>>();
+        assert_eq!(prices, vec![Some(1100)]);
+        let notifications = history.iter()
+            .filter(|e| matches!(e.change(), ProductChange::Notification { .. }))
+            .map(|e| e.change().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(notifications, vec![ProductChange::Notification { kind: NotificationKind::NewProduct, username: DEFAULT_USERNAME.to_owned() }]);
+        assert!(matches!(db.get_toranoana_product_history(product.id() + 1).await, Err(GetProductError::ProductMissing { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_follow_deletes_toranoana_skip_products() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let user_id = default_user_id(&db).await;
+        db.add_toranoana_skipping_url(product_args().url(), &[circle_args().name(), artist_args().name()]).await.unwrap();
+        assert_eq!(db.get_toranoana_skipping_urls().await.unwrap(), vec![product_args().url().to_owned()]);
+
+        db.follow_toranoana_creator(user_id, &circle_args()).await.unwrap();
+        assert!(db.get_toranoana_skipping_urls().await.unwrap().is_empty());
+    }
+
+    fn artist_args() -> CreatorArgs {
+        CreatorArgs::new("mafuyu".to_owned(), CreatorKind::Artist)
+    }
+
+    fn circle_args() -> CreatorArgs {
+        CreatorArgs::new("mafuyu_circle".to_owned(), CreatorKind::Circle)
+    }
+
+    fn product_args() -> CreateProductArgs {
+        item_args("040031000001", Some(circle_args().name()), &[artist_args().name()])
+    }
+
+    fn product_args2() -> CreateProductArgs {
+        item_args("040031000002", None, &[artist_args().name(), "kantoku"])
+    }
+
+    fn item_args(item_id: &str, circle: Option<&str>, artists: &[&str]) -> CreateProductArgs {
+        CreateProductArgs::new(
+            format!("https://ecs.toranoana.jp/tora/ec/item/{}/", item_id),
+            format!("title_{}", item_id),
+            circle.map(|c| c.to_owned()),
+            artists.iter().map(|a| a.to_string()).collect(),
+            format!("https://ecdnimg.toranoana.jp/ec/img/04/0031/00/{}/{}-1p.jpg", &item_id[8..], item_id),
+            "同人誌".to_owned(),
+            Some(1100),
+            Availability::Available
+        )
+    }
+}
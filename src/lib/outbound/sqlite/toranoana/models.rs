@@ -0,0 +1,184 @@
+use crate::domain::product_history::{NotificationKind, ProductChange};
+use crate::domain::availability::Availability;
+use crate::domain::toranoana::models::creator::{Creator, CreatorKind};
+use crate::domain::toranoana::models::product::{Product, ProductHistoryEntry};
+use crate::outbound::sqlite::schema;
+use chrono::NaiveDateTime;
+use diesel::{Identifiable, Insertable, Queryable, Selectable};
+
+#[derive(Debug, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = schema::toranoana_product)]
+#[diesel(treat_none_as_null = true)]
+pub struct ProductRow {
+    pub id: i32,
+    pub date_added: NaiveDateTime,
+    pub url: String,
+    pub title: String,
+    pub image_url: String,
+    pub category: String,
+    pub price: Option<i32>,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub availability: Availability,
+    pub date_restocked: Option<NaiveDateTime>,
+    pub image_hash: Option<String>,
+    pub image_phash: Option<i64>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::toranoana_product)]
+#[diesel(treat_none_as_null = true)]
+pub struct ProductRowInsert<'a> {
+    pub url: &'a str,
+    pub title: &'a str,
+    pub image_url: &'a str,
+    pub category: &'a str,
+    pub price: Option<i32>,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub availability: Availability,
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::toranoana_availability_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct AvailabilityEventRow {
+    pub date_added: NaiveDateTime,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub availability: Availability,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::toranoana_availability_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct AvailabilityEventRowInsert {
+    pub product_id: i32,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub availability: Availability,
+    pub previous_availability: Option<String>,
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::toranoana_price_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct PriceEventRow {
+    pub date_added: NaiveDateTime,
+    pub price: Option<i32>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::toranoana_price_event)]
+#[diesel(treat_none_as_null = true)]
+pub struct PriceEventRowInsert {
+    pub product_id: i32,
+    pub price: Option<i32>,
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::toranoana_notification)]
+#[diesel(treat_none_as_null = true)]
+pub struct NotificationRow {
+    pub date_added: NaiveDateTime,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub kind: NotificationKind,
+}
+
+#[derive(Debug, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = schema::toranoana_creator)]
+#[diesel(treat_none_as_null = true)]
+pub struct CreatorRow {
+    pub id: i32,
+    pub date_added: NaiveDateTime,
+    pub name: String,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub kind: CreatorKind,
+    pub following: bool,
+    pub date_followed: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::toranoana_creator)]
+#[diesel(treat_none_as_null = true)]
+pub struct CreatorRowInsert<'a> {
+    pub name: &'a str,
+    #[diesel(serialize_as = String, deserialize_as = String)]
+    pub kind: CreatorKind,
+    pub following: bool,
+    pub date_followed: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::toranoana_creator_follower)]
+#[diesel(treat_none_as_null = true)]
+pub struct CreatorFollowerRow {
+    pub creator_id: i32,
+    pub user_id: i32,
+    pub date_followed: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::toranoana_creator_follower)]
+#[diesel(treat_none_as_null = true)]
+pub struct CreatorFollowerRowInsert {
+    pub creator_id: i32,
+    pub user_id: i32,
+}
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = schema::toranoana_skip_product)]
+#[diesel(treat_none_as_null = true)]
+pub struct SkipProductRow {
+    pub id: i32,
+    pub url: String,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::toranoana_skip_product)]
+#[diesel(treat_none_as_null = true)]
+pub struct SkipProductRowInsert<'a> {
+    pub url: &'a str,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::toranoana_skip_product_creator)]
+#[diesel(treat_none_as_null = true)]
+pub struct SkipProductCreatorRowInsert<'a> {
+    pub skip_product_id: i32,
+    pub creator_name: &'a str,
+}
+
+impl CreatorRow {
+    pub fn into_domain(self) -> Creator {
+        Creator::new(self.id, self.date_added.and_utc(), self.name, self.kind, self.following, self.date_followed.map(|d| d.and_utc()))
+    }
+
+    /// Uses the follow of a single user instead of whether anybody follows the creator.
+    pub fn into_domain_for(self, follower: Option<&CreatorFollowerRow>) -> Creator {
+        Creator::new(self.id, self.date_added.and_utc(), self.name, self.kind, follower.is_some(), follower.map(|f| f.date_followed.and_utc()))
+    }
+}
+
+impl ProductRow {
+    pub fn into_domain(self, creators: Vec<Creator>) -> Product {
+        Product::new(self.id, self.date_added.and_utc(), self.url, self.title, creators, self.image_url, self.category, self.price, self.availability)
+            .with_date_restocked(self.date_restocked.map(|d| d.and_utc()))
+            .with_image_hash(self.image_hash)
+            .with_image_phash(self.image_phash.map(|h| h as u64))
+    }
+}
+
+impl AvailabilityEventRow {
+    pub fn into_domain(self) -> ProductHistoryEntry {
+        ProductHistoryEntry::new(self.date_added.and_utc(), ProductChange::Availability(self.availability))
+    }
+}
+
+impl PriceEventRow {
+    pub fn into_domain(self) -> ProductHistoryEntry {
+        ProductHistoryEntry::new(self.date_added.and_utc(), ProductChange::Price(self.price))
+    }
+}
+
+impl NotificationRow {
+    pub fn into_domain(self, username: String) -> ProductHistoryEntry {
+        ProductHistoryEntry::new(self.date_added.and_utc(), ProductChange::Notification { kind: self.kind, username })
+    }
+}
@@ -0,0 +1,150 @@
+use crate::domain::user::models::user::{GetUserError, GetUsersError, SetupUsersError, User, DEFAULT_USERNAME};
+use crate::domain::user::ports::UserRepository;
+use crate::outbound::sqlite::user::models::{UserRow, UserRowInsert};
+use crate::outbound::sqlite::{schema, Sqlite};
+use anyhow::Context;
+use async_trait::async_trait;
+use diesel::prelude::*;
+use diesel::r2d2::ConnectionManager;
+use r2d2::PooledConnection;
+use schema::app_user::dsl as user_dsl;
+
+pub(super) mod models;
+
+impl Sqlite {
+    pub(super) fn get_user_row_by_name(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        username: &str,
+    ) -> Result<Option<UserRow>, anyhow::Error> {
+        let user = user_dsl::app_user
+            .select(UserRow::as_select())
+            .filter(user_dsl::username.eq(username))
+            .first(connection)
+            .optional()
+            .with_context(|| format!("cannot get user with name '{}'", username))?;
+        Ok(user)
+    }
+
+    pub(super) fn get_user_rows_by_ids(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        user_ids: &[i32],
+    ) -> Result<Vec<UserRow>, anyhow::Error> {
+        let users = user_dsl::app_user
+            .select(UserRow::as_select())
+            .filter(user_dsl::id.eq_any(user_ids))
+            .get_results(connection)
+            .with_context(|| "cannot get users by id")?;
+        Ok(users)
+    }
+
+    fn get_user_rows(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+    ) -> Result<Vec<UserRow>, anyhow::Error> {
+        let users = user_dsl::app_user
+            .select(UserRow::as_select())
+            .order_by(user_dsl::username.asc())
+            .get_results(connection)
+            .with_context(|| "cannot get users")?;
+        Ok(users)
+    }
+
+    fn insert_user_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        username: &str,
+    ) -> Result<(), anyhow::Error> {
+        diesel::insert_or_ignore_into(user_dsl::app_user)
+            .values(UserRowInsert { username })
+            .execute(connection)
+            .with_context(|| format!("cannot insert user with name '{}'", username))?;
+        Ok(())
+    }
+
+    fn rename_user_row(
+        &self,
+        connection: &mut PooledConnection<ConnectionManager<SqliteConnection>>,
+        user: &UserRow,
+        username: &str,
+    ) -> Result<(), anyhow::Error> {
+        diesel::update(user)
+            .set(user_dsl::username.eq(username))
+            .execute(connection)
+            .with_context(|| format!("cannot rename user '{}' to '{}'", user.username, username))?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl UserRepository for Sqlite {
+    async fn setup_users(&self, default_username: &str, usernames: &[String]) -> Result<Vec<User>, SetupUsersError> {
+        let default_username = default_username.to_owned();
+        let usernames = usernames.to_vec();
+        self.write(move |db, connection| {
+            let users = connection.transaction(|connection| -> Result<Vec<UserRow>, anyhow::Error> {
+                // hand the follows from before user accounts existed over to the configured default user
+                if default_username != DEFAULT_USERNAME && db.get_user_row_by_name(connection, &default_username)?.is_none() {
+                    if let Some(user) = db.get_user_row_by_name(connection, DEFAULT_USERNAME)? {
+                        db.rename_user_row(connection, &user, &default_username)?;
+                    }
+                }
+                for username in std::iter::once(&default_username).chain(usernames.iter()) {
+                    db.insert_user_row(connection, username)?;
+                }
+                db.get_user_rows(connection)
+            })?;
+            Ok(users.into_iter().map(|u| u.into_domain()).collect())
+        }).await
+    }
+
+    async fn get_user_by_name(&self, username: &str) -> Result<Option<User>, GetUserError> {
+        let username = username.to_owned();
+        self.read(move |db, connection| {
+            let user = db.get_user_row_by_name(connection, &username)?;
+            Ok(user.map(|u| u.into_domain()))
+        }).await
+    }
+
+    async fn get_users(&self) -> Result<Vec<User>, GetUsersError> {
+        self.read(move |db, connection| {
+            let users = db.get_user_rows(connection)?;
+            Ok(users.into_iter().map(|u| u.into_domain()).collect())
+        }).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_setup_users() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let users = db.setup_users(DEFAULT_USERNAME, &["alice".to_owned(), "bob".to_owned()]).await.unwrap();
+        assert_eq!(users.iter().map(|u| u.username()).collect::<Vec<_>>(), vec!["alice", "bob", DEFAULT_USERNAME]);
+
+        let users = db.setup_users(DEFAULT_USERNAME, &["alice".to_owned()]).await.unwrap();
+        assert_eq!(users.len(), 3);
+        assert_eq!(db.get_user_by_name("bob").await.unwrap().unwrap().username(), "bob");
+        assert!(db.get_user_by_name("carol").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_setup_users_renames_default_user() {
+        let db = Sqlite::new_in_memory();
+        db.setup().unwrap();
+        let default_user = db.get_user_by_name(DEFAULT_USERNAME).await.unwrap().unwrap();
+
+        let users = db.setup_users("admin", &["admin".to_owned(), "bob".to_owned()]).await.unwrap();
+        assert_eq!(users.iter().map(|u| u.username()).collect::<Vec<_>>(), vec!["admin", "bob"]);
+        assert_eq!(db.get_user_by_name("admin").await.unwrap().unwrap().id(), default_user.id());
+
+        // an existing user is never replaced by the default user
+        db.setup_users("bob", &[]).await.unwrap();
+        assert!(db.get_user_by_name(DEFAULT_USERNAME).await.unwrap().is_none());
+        assert_eq!(db.get_users().await.unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,26 @@
+use crate::domain::user::models::user::User;
+use crate::outbound::sqlite::schema;
+use chrono::NaiveDateTime;
+use diesel::{Identifiable, Insertable, Queryable, Selectable};
+
+#[derive(Debug, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = schema::app_user)]
+#[diesel(treat_none_as_null = true)]
+pub struct UserRow {
+    pub id: i32,
+    pub date_added: NaiveDateTime,
+    pub username: String,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = schema::app_user)]
+#[diesel(treat_none_as_null = true)]
+pub struct UserRowInsert<'a> {
+    pub username: &'a str,
+}
+
+impl UserRow {
+    pub fn into_domain(self) -> User {
+        User::new(self.id, self.date_added.and_utc(), self.username)
+    }
+}
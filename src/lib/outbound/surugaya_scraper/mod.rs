@@ -0,0 +1,83 @@
+use crate::domain::surugaya::models::product::{ListingData, ScrapeProductsError};
+use crate::domain::surugaya::ports::SurugayaScraper;
+use crate::outbound::scraper_client::ScraperClient;
+use crate::outbound::surugaya_scraper::parser::parse_listings;
+use anyhow::Context;
+use async_trait::async_trait;
+use log::info;
+pub use parser::ParseError;
+use reqwest::Url;
+
+mod parser;
+
+const SEARCH_URL: &str = "https://www.suruga-ya.jp/search";
+const PRODUCT_URL: &str = "https://www.suruga-ya.jp{relative_url}";
+const PAGE_SIZE: usize = 24;
+
+#[derive(Debug, Clone)]
+pub struct SurugayaScraperImpl {
+    client: ScraperClient,
+}
+
+impl SurugayaScraperImpl {
+    pub fn new() -> Result<Self, anyhow::Error> {
+        let client = ScraperClient::new("SurugayaScraper", &[])?;
+        Ok(SurugayaScraperImpl { client })
+    }
+
+    fn search_url(keyword: &str, page_no: u32) -> Result<Url, anyhow::Error> {
+        let url = Url::parse_with_params(SEARCH_URL, &[
+            ("category", ""),
+            ("search_word", keyword),
+            ("rankBy", "modificationTime:descending"),
+            ("page", page_no.to_string().as_str()),
+        ])?;
+        Ok(url)
+    }
+
+    async fn get_listing_page(&self, keyword: &str, page_no: u32) -> Result<Vec<ListingData>, ScrapeProductsError> {
+        let url = Self::search_url(keyword, page_no)
+            .with_context(|| format!("Error building search url for '{}'", keyword))?;
+        let document = self.client.get_document(url).await
+            .with_context(|| format!("Error getting listings for '{}'", keyword))?;
+        let listings = parse_listings(document)?;
+        info!("Found {} listings on page {} for '{}'", listings.len(), page_no, keyword);
+        Ok(listings)
+    }
+
+    async fn get_listings(&self, keyword: &str) -> Result<Vec<ListingData>, ScrapeProductsError> {
+        let mut page_no = 1_u32;
+        let mut listings = Vec::<ListingData>::new();
+        loop {
+            let page_listings = self.get_listing_page(keyword, page_no).await?;
+            let page_listing_count = page_listings.len();
+            listings.extend(page_listings);
+            if page_listing_count < PAGE_SIZE {
+                break;
+            }
+            page_no += 1;
+        }
+        info!("Found {} total listings for '{}'", listings.len(), keyword);
+        Ok(listings)
+    }
+}
+
+#[async_trait]
+impl SurugayaScraper for SurugayaScraperImpl {
+    async fn get_listings(&self, keyword: &str) -> Result<Vec<ListingData>, ScrapeProductsError> {
+        self.get_listings(keyword).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_search_url() {
+        assert_eq!(
+            SurugayaScraperImpl::search_url("まふゆ", 2).unwrap().as_str(),
+            "https://www.suruga-ya.jp/search?category=&search_word=%E3%81%BE%E3%81%B5%E3%82%86&rankBy=modificationTime%3Adescending&page=2"
+        );
+    }
+}
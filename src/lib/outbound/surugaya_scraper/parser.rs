@@ -0,0 +1,133 @@
+use crate::domain::availability::Availability;
+use crate::domain::surugaya::models::condition::Condition;
+use crate::domain::surugaya::models::product::ListingData;
+use crate::outbound::surugaya_scraper::PRODUCT_URL;
+use itertools::Itertools;
+use select::document::Document;
+use select::node::Node;
+use select::predicate::{Attr, Class, Name, Predicate};
+use thiserror::Error;
+
+pub fn parse_listings(document: Document) -> Result<Vec<ListingData>, ParseError> {
+    let search_result = match document.find(Attr("id", "search_result")).next() {
+        Some(search_result) => search_result,
+        // searches without results have no result list at all
+        None if document.find(Class("search_zero")).next().is_some() => return Ok(Vec::new()),
+        None => return Err(ParseError::ListingListNotFound),
+    };
+    let listings = search_result.find(Class("item"))
+        .map(parse_listing)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(listings.into_iter().unique_by(|l| l.url().to_owned()).collect())
+}
+
+fn parse_listing(item: Node) -> Result<ListingData, ParseError> {
+    let a = item.find(Class("title").descendant(Name("a"))).next()
+        .ok_or(ParseError::ListingLinkNodeNotFound)?;
+    let href = a.attr("href")
+        .ok_or_else(|| ParseError::ListingUrlNotFound(a.text()))?;
+    // links carry the tracking parameters of the search
+    let href = href.split('?').next().unwrap_or(href);
+    let url = match href.starts_with('/') {
+        true => PRODUCT_URL.replace("{relative_url}", href),
+        false => href.to_owned(),
+    };
+    let title = a.text().trim().to_owned();
+    if title.is_empty() {
+        return Err(ParseError::ListingTitleNotFound(url));
+    }
+    let image_url = item.find(Class("photo_box").descendant(Name("img"))).next()
+        .and_then(|i| i.attr("src"))
+        .map(|src| src.to_owned())
+        .ok_or_else(|| ParseError::ListingImageUrlNotFound(url.clone()))?;
+    let (condition, price) = parse_listing_price(item, &url)?;
+    let availability = match price {
+        Some(_) => Availability::Available,
+        None => Availability::NotAvailable,
+    };
+    Ok(ListingData::new(url, title, image_url, condition, price, availability))
+}
+
+/// The offer shown in the list is prefixed with its condition, e.g. `中古：￥1,200` or `中古：品切れ`.
+fn parse_listing_price(item: Node, url: &str) -> Result<(Condition, Option<i32>), ParseError> {
+    let text = item.find(Class("price_teika")).next()
+        .map(|n| n.text().trim().to_owned())
+        .ok_or_else(|| ParseError::ListingPriceNotFound(url.to_owned()))?;
+    let (condition_text, price_text) = text.split_once('：')
+        .ok_or_else(|| ParseError::ListingPriceUnknown(text.clone()))?;
+    let condition = match condition_text.trim() {
+        "中古" => Condition::Used,
+        "新品" => Condition::New,
+        other => Err(ParseError::ListingConditionUnknown(other.to_owned()))?
+    };
+    if price_text.contains("品切れ") {
+        return Ok((condition, None));
+    }
+    let value = price_text.split_whitespace().next().unwrap_or_default().replace(['￥', ',', '円'], "");
+    let price = value.parse::<i32>()
+        .map_err(|_| ParseError::ListingPriceUnknown(text.clone()))?;
+    Ok((condition, Some(price)))
+}
+
+#[derive(Debug, Error)]
+pub enum ParseError {
+    #[error("Could not find listing list")]
+    ListingListNotFound,
+    #[error("Could not find listing link node")]
+    ListingLinkNodeNotFound,
+    #[error("Could not find listing url in link node: {0}")]
+    ListingUrlNotFound(String),
+    #[error("Could not find title of listing {0}")]
+    ListingTitleNotFound(String),
+    #[error("Could not find image url of listing {0}")]
+    ListingImageUrlNotFound(String),
+    #[error("Could not find price of listing {0}")]
+    ListingPriceNotFound(String),
+    #[error("Unknown listing condition: {0}")]
+    ListingConditionUnknown(String),
+    #[error("Unknown listing price: {0}")]
+    ListingPriceUnknown(String),
+}
+
+#[cfg(test)]
+mod test {
+    use crate::domain::availability::Availability;
+    use crate::domain::surugaya::models::condition::Condition;
+    use crate::outbound::surugaya_scraper::parser::parse_listings;
+    use select::document::Document;
+
+    #[test]
+    fn test_parse_listings() {
+        let document = Document::from(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/test-data/surugaya/listing-list.html")));
+        let listings = parse_listings(document).unwrap();
+        let urls = listings.iter().map(|l| l.url()).collect::<Vec<_>>();
+        assert_eq!(urls, vec![
+            "https://www.suruga-ya.jp/product/detail/ZHORE12345",
+            "https://www.suruga-ya.jp/product/detail/602098765",
+            "https://www.suruga-ya.jp/product/detail/ZHORE23456",
+        ]);
+
+        let used = listings.first().unwrap();
+        assert_eq!(used.title(), "まふゆ画集 冬のアトリエ");
+        assert_eq!(used.image_url(), "https://www.suruga-ya.jp/database/pics_light/game/zhore12345.jpg");
+        assert_eq!(used.condition(), Condition::Used);
+        assert_eq!(used.price(), Some(2580));
+        assert_eq!(used.availability(), &Availability::Available);
+
+        let new = listings.get(1).unwrap();
+        assert_eq!(new.condition(), Condition::New);
+        assert_eq!(new.price(), Some(15800));
+        assert_eq!(new.availability(), &Availability::Available);
+
+        let sold_out = listings.last().unwrap();
+        assert_eq!(sold_out.condition(), Condition::Used);
+        assert_eq!(sold_out.price(), None);
+        assert_eq!(sold_out.availability(), &Availability::NotAvailable);
+    }
+
+    #[test]
+    fn test_parse_no_listings() {
+        let document = Document::from(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/test-data/surugaya/listing-list-empty.html")));
+        assert!(parse_listings(document).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,105 @@
+use crate::domain::toranoana::models::creator::{CreatorArgs, CreatorKind};
+use crate::domain::toranoana::models::product::{ProductData, ScrapeProductsError};
+use crate::domain::toranoana::ports::ToranoanaScraper;
+use crate::outbound::scraper_client::ScraperClient;
+use crate::outbound::toranoana_scraper::parser::{parse_product_details, parse_product_list};
+use anyhow::Context;
+use async_trait::async_trait;
+use log::info;
+pub use parser::ParseError;
+use reqwest::Url;
+
+mod parser;
+
+const BASE_URL: &str = "https://ecs.toranoana.jp";
+const SEARCH_URL: &str = "https://ecs.toranoana.jp/tora/ec/app/catalog/list";
+const PRODUCT_URL: &str = "https://ecs.toranoana.jp{relative_url}";
+const PAGE_SIZE: usize = 30;
+
+#[derive(Debug, Clone)]
+pub struct ToranoanaScraperImpl {
+    client: ScraperClient,
+}
+
+impl ToranoanaScraperImpl {
+    pub fn new() -> Result<Self, anyhow::Error> {
+        let client = ScraperClient::new("ToranoanaScraper", &[("adflg=0", BASE_URL)])?;
+        Ok(ToranoanaScraperImpl { client })
+    }
+
+    fn search_url(creator: &CreatorArgs, page_no: u32) -> Result<Url, anyhow::Error> {
+        let param = match creator.kind() {
+            CreatorKind::Artist => "searchActor",
+            CreatorKind::Circle => "searchCircle",
+        };
+        let url = Url::parse_with_params(SEARCH_URL, &[(param, creator.name()), ("currentPage", page_no.to_string().as_str())])?;
+        Ok(url)
+    }
+
+    async fn get_product_list_urls(&self, creator: &CreatorArgs, page_no: u32) -> Result<Vec<String>, ScrapeProductsError> {
+        let url = Self::search_url(creator, page_no)
+            .with_context(|| format!("Error building search url for {} '{}'", creator.kind(), creator.name()))?;
+        let document = self.client.get_document(url).await
+            .with_context(|| format!("Error getting product urls for {} '{}'", creator.kind(), creator.name()))?;
+        let urls = parse_product_list(document)?;
+        info!("Found {} products on page {} for {} '{}'", urls.len(), page_no, creator.kind(), creator.name());
+        Ok(urls)
+    }
+
+    async fn get_product_urls(&self, creator: &CreatorArgs) -> Result<Vec<String>, ScrapeProductsError> {
+        let mut page_no = 1_u32;
+        let mut urls = Vec::<String>::new();
+        loop {
+            let page_urls = self.get_product_list_urls(creator, page_no).await?;
+            let page_url_count = page_urls.len();
+            urls.extend(page_urls);
+            if page_url_count < PAGE_SIZE {
+                break;
+            }
+            page_no += 1;
+        }
+        urls.reverse();
+        info!("Found {} total products for {} '{}'", urls.len(), creator.kind(), creator.name());
+        Ok(urls)
+    }
+
+    async fn get_product(&self, url: &str) -> Result<ProductData, ScrapeProductsError> {
+        let parsed_url = Url::parse(url)
+            .with_context(|| format!("Invalid product url '{}'", url))?;
+        let document = self.client.get_document(parsed_url).await
+            .with_context(|| format!("Error getting product details for url '{}'", url))?;
+        let product = parse_product_details(document)?;
+        info!("Parsed product '{}' ({})", product.title(), url);
+        Ok(product)
+    }
+}
+
+#[async_trait]
+impl ToranoanaScraper for ToranoanaScraperImpl {
+    async fn get_potential_product_urls(&self, creator: &CreatorArgs) -> Result<Vec<String>, ScrapeProductsError> {
+        self.get_product_urls(creator).await
+    }
+
+    async fn get_product(&self, url: &str) -> Result<ProductData, ScrapeProductsError> {
+        self.get_product(url).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_search_url() {
+        let artist = CreatorArgs::new("まふゆ".to_owned(), CreatorKind::Artist);
+        let circle = CreatorArgs::new("A & B".to_owned(), CreatorKind::Circle);
+        assert_eq!(
+            ToranoanaScraperImpl::search_url(&artist, 1).unwrap().as_str(),
+            "https://ecs.toranoana.jp/tora/ec/app/catalog/list?searchActor=%E3%81%BE%E3%81%B5%E3%82%86&currentPage=1"
+        );
+        assert_eq!(
+            ToranoanaScraperImpl::search_url(&circle, 2).unwrap().as_str(),
+            "https://ecs.toranoana.jp/tora/ec/app/catalog/list?searchCircle=A+%26+B&currentPage=2"
+        );
+    }
+}
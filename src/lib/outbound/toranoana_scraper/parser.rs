@@ -0,0 +1,163 @@
+use crate::domain::availability::Availability;
+use crate::outbound::toranoana_scraper::{ProductData, PRODUCT_URL};
+use itertools::Itertools;
+use select::document::Document;
+use select::node::Node;
+use select::predicate::{Class, Name, Predicate};
+use thiserror::Error;
+
+pub fn parse_product_list(document: Document) -> Result<Vec<String>, ParseError> {
+    let product_list = document.find(Class("search-result-container")).next()
+        .ok_or(ParseError::ProductListNotFound)?;
+    let product_urls = product_list.find(Class("product-list-item"))
+        .map(parse_product_url_from_list_item)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(product_urls.into_iter().unique().collect())
+}
+
+fn parse_product_url_from_list_item(node: Node) -> Result<String, ParseError> {
+    let a = node.find(Class("product-list-title").descendant(Name("a"))).next()
+        .ok_or(ParseError::ProductLinkNodeNotFound)?;
+    let href = a.attr("href")
+        .ok_or_else(|| ParseError::ProductUrlNotFound(a.text()))?;
+    Ok(PRODUCT_URL.replace("{relative_url}", href))
+}
+
+pub fn parse_product_details(document: Document) -> Result<ProductData, ParseError> {
+    let detail = document.find(Class("product-detail")).next()
+        .ok_or(ParseError::ProductDetailNotFound)?;
+    let title = parse_product_title(detail)?;
+    let circle = parse_product_spec(detail, "サークル").into_iter().next();
+    let artists = parse_product_spec(detail, "作家");
+    let category = parse_product_spec(detail, "種別").into_iter().next()
+        .ok_or(ParseError::ProductCategoryNotFound)?;
+    let price = parse_product_price(detail)?;
+    let availability = parse_product_availability(detail)?;
+    let image_url = parse_product_image_url(detail)?;
+    Ok(ProductData::new(title, circle, artists, image_url, category, price, availability))
+}
+
+fn parse_product_title(detail: Node) -> Result<String, ParseError> {
+    let title = detail.find(Class("product-detail-desc-title")).next()
+        .map(|n| n.text().trim().to_owned())
+        .filter(|t| !t.is_empty())
+        .ok_or(ParseError::ProductTitleNotFound)?;
+    Ok(title)
+}
+
+/// The linked values of the row of the spec table with the header `name`, empty if the row is missing.
+fn parse_product_spec(detail: Node, name: &str) -> Vec<String> {
+    detail.find(Class("product-detail-spec-table").descendant(Name("tr")))
+        .find(|tr| tr.find(Class("product-detail-spec-table-th")).any(|th| th.text().trim() == name))
+        .map(|tr| tr.find(Name("a"))
+            .map(|a| a.text().trim().to_owned())
+            .filter(|v| !v.is_empty())
+            .unique()
+            .collect())
+        .unwrap_or_default()
+}
+
+// sold out products have no price
+fn parse_product_price(detail: Node) -> Result<Option<i32>, ParseError> {
+    let price = match detail.find(Class("pricearea__price")).next() {
+        Some(price) => price,
+        None => return Ok(None),
+    };
+    let text = price.first_child().map(|n| n.text()).unwrap_or_default();
+    let value = text.trim().replace([',', '円'], "");
+    let price = value.parse::<i32>()
+        .map_err(|_| ParseError::ProductPriceUnknown(text.trim().to_owned()))?;
+    Ok(Some(price))
+}
+
+fn parse_product_availability(detail: Node) -> Result<Availability, ParseError> {
+    let availability_text = detail.find(Class("product-detail-stock").descendant(Class("stock-status"))).next()
+        .map(|n| n.text().trim().to_owned())
+        .ok_or(ParseError::ProductAvailabilityNotFound)?;
+    let availability = match availability_text.as_str() {
+        "-" | "" => Availability::NotAvailable,
+        "在庫なし" => Availability::NotAvailable,
+        "販売終了" => Availability::NotAvailable,
+        "予約受付中" => Availability::Preorder,
+        "残りわずか" => Availability::Available,
+        "在庫あり" => Availability::Available,
+        other => Err(ParseError::ProductAvailabilityUnknown(other.to_owned()))?
+    };
+    Ok(availability)
+}
+
+fn parse_product_image_url(detail: Node) -> Result<String, ParseError> {
+    let img_url = detail.find(Class("product-detail-image-main").descendant(Name("img")))
+        .next()
+        .and_then(|i| i.attr("src"))
+        .map(|src| src.to_owned())
+        .ok_or(ParseError::ProductImageUrlNotFound)?;
+    Ok(img_url)
+}
+
+#[derive(Debug, Error)]
+pub enum ParseError {
+    #[error("Could not find product list")]
+    ProductListNotFound,
+    #[error("Could not find product link node from list item")]
+    ProductLinkNodeNotFound,
+    #[error("Could not find product url in link node: {0}")]
+    ProductUrlNotFound(String),
+    #[error("Could not find product detail part")]
+    ProductDetailNotFound,
+    #[error("Could not find product title")]
+    ProductTitleNotFound,
+    #[error("Could not find product category")]
+    ProductCategoryNotFound,
+    #[error("Could not find product availability")]
+    ProductAvailabilityNotFound,
+    #[error("Could not find product image url")]
+    ProductImageUrlNotFound,
+    #[error("Unknown product availability: {0}")]
+    ProductAvailabilityUnknown(String),
+    #[error("Unknown product price: {0}")]
+    ProductPriceUnknown(String),
+}
+
+#[cfg(test)]
+mod test {
+    use crate::domain::availability::Availability;
+    use crate::outbound::toranoana_scraper::parser::{parse_product_details, parse_product_list};
+    use select::document::Document;
+
+    #[test]
+    fn test_parse_product_urls() {
+        let document = Document::from(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/test-data/toranoana/product-list.html")));
+        let urls = parse_product_list(document).unwrap();
+        assert_eq!(urls, vec![
+            "https://ecs.toranoana.jp/tora/ec/item/040031146235/",
+            "https://ecs.toranoana.jp/tora/ec/item/040031138990/",
+            "https://ecs.toranoana.jp/tora/ec/item/040030870523/",
+        ]);
+    }
+
+    #[test]
+    fn test_parse_product_details() {
+        let document = Document::from(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/test-data/toranoana/product-details.html")));
+        let details = parse_product_details(document).unwrap();
+        assert_eq!(details.title(), "まふゆの冬休み");
+        assert_eq!(details.circle(), Some("ほしまくら"));
+        assert_eq!(details.artists(), &["まふゆ".to_owned(), "かんとく".to_owned()]);
+        assert_eq!(details.category(), "同人誌");
+        assert_eq!(details.price(), Some(1100));
+        assert_eq!(details.availability(), &Availability::Available);
+        assert_eq!(details.image_url(), "https://ecdnimg.toranoana.jp/ec/img/04/0031/14/6235/040031146235-1p.jpg");
+    }
+
+    #[test]
+    fn test_parse_sold_out_product_without_circle() {
+        let document = Document::from(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/test-data/toranoana/product-sold-out.html")));
+        let details = parse_product_details(document).unwrap();
+        assert_eq!(details.title(), "まふゆ画集 2");
+        assert_eq!(details.circle(), None);
+        assert_eq!(details.artists(), &["まふゆ".to_owned()]);
+        assert_eq!(details.category(), "画集");
+        assert_eq!(details.price(), None);
+        assert_eq!(details.availability(), &Availability::NotAvailable);
+    }
+}
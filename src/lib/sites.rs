@@ -0,0 +1,64 @@
+use crate::config::ServerConfiguration;
+use crate::domain::amiami;
+use crate::domain::amiami::service::AmiamiServiceImpl;
+use crate::domain::booth::service::BoothServiceImpl;
+use crate::domain::digital::service::DigitalServiceImpl;
+use crate::domain::figure::service::FigureServiceImpl;
+use crate::domain::image::ports::ImageCache;
+use crate::domain::mandarake::service::MandarakeServiceImpl;
+use crate::domain::melonbooks;
+use crate::domain::melonbooks::service::MelonbooksServiceImpl;
+use crate::domain::schedule::AdaptiveInterval;
+use crate::domain::site::{Site, SiteCore, SiteProduct};
+use crate::domain::surugaya::service::SurugayaServiceImpl;
+use crate::domain::toranoana::service::ToranoanaServiceImpl;
+use crate::inbound::http::site::{AmiamiHttpSite, BoothHttpSite, DigitalHttpSite, FigureHttpSite, HttpSite, MandarakeHttpSite, MelonbooksHttpSite, SurugayaHttpSite, ToranoanaHttpSite};
+use crate::outbound::amiami_scraper::AmiamiScraperImpl;
+use crate::outbound::booth_scraper::BoothScraperImpl;
+use crate::outbound::digital_scraper::DigitalScraperImpl;
+use crate::outbound::discord_notifier::DiscordNotifier;
+use crate::outbound::figure_scraper::FigureScraperImpl;
+use crate::outbound::mandarake_scraper::MandarakeScraperImpl;
+use crate::outbound::melonbooks_scraper::MelonbooksScraperImpl;
+use crate::outbound::sqlite::Sqlite;
+use crate::outbound::surugaya_scraper::SurugayaScraperImpl;
+use crate::outbound::toranoana_scraper::ToranoanaScraperImpl;
+use chrono::Duration;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Every site with its service, scraper and notifiers, in the order of `SITES`.
+/// A site is registered by adding its line here and its `SITE` to `SITES`.
+pub fn http_sites<I: ImageCache>(config: &ServerConfiguration, db: Sqlite, images: I) -> Result<Vec<Arc<dyn HttpSite>>, anyhow::Error> {
+    let melonbooks_service = Arc::new(MelonbooksServiceImpl::new(db.clone(), MelonbooksScraperImpl::new()?, site_core(config, images.clone())).with_adaptive_interval(adaptive_interval(config, melonbooks::SITE)));
+    Ok(vec![
+        Arc::new(MelonbooksHttpSite::new(melonbooks_service.clone())),
+        Arc::new(ToranoanaHttpSite::new(Arc::new(ToranoanaServiceImpl::new(db.clone(), ToranoanaScraperImpl::new()?, site_core(config, images.clone()))))),
+        Arc::new(MandarakeHttpSite::new(Arc::new(MandarakeServiceImpl::new(db.clone(), MandarakeScraperImpl::new()?, site_core(config, images.clone()))))),
+        Arc::new(SurugayaHttpSite::new(Arc::new(SurugayaServiceImpl::new(db.clone(), SurugayaScraperImpl::new()?, site_core(config, images.clone()))))),
+        Arc::new(BoothHttpSite::new(Arc::new(BoothServiceImpl::new(db.clone(), BoothScraperImpl::new()?, site_core(config, images.clone()))), melonbooks_service)),
+        Arc::new(DigitalHttpSite::new(Arc::new(DigitalServiceImpl::new(db.clone(), DigitalScraperImpl::new()?, site_core(config, images.clone()))))),
+        Arc::new(FigureHttpSite::new(Arc::new(FigureServiceImpl::new(db.clone(), FigureScraperImpl::new()?, site_core(config, images.clone()))))),
+        Arc::new(AmiamiHttpSite::new(Arc::new(AmiamiServiceImpl::new(db, AmiamiScraperImpl::new()?, site_core(config, images)).with_adaptive_interval(adaptive_interval(config, amiami::SITE))))),
+    ])
+}
+
+fn adaptive_interval(config: &ServerConfiguration, site: Site) -> Option<AdaptiveInterval> {
+    config.site_settings(site.id()).adaptive.as_ref().map(|a| AdaptiveInterval::new(
+        Duration::minutes(a.min_interval_minutes.into()),
+        Duration::minutes(a.max_interval_minutes.into()),
+    ))
+}
+
+/// The site's own webhook, the webhooks of the users that configured one for the site and the image cache.
+fn site_core<P: SiteProduct, I: ImageCache>(config: &ServerConfiguration, images: I) -> SiteCore<DiscordNotifier<P>, I> {
+    let settings = config.site_settings(P::SITE.id());
+    let public_url = &config.http_settings.public_url;
+    let notifier = DiscordNotifier::new(settings.discord_settings.clone()).with_public_url(public_url.clone());
+    let user_notifiers = config.users.iter()
+        .filter_map(|u| u.discord_settings.get(P::SITE.id()).map(|d| (u.username.clone(), DiscordNotifier::new(Some(d.clone())).with_public_url(public_url.clone()))))
+        .collect::<HashMap<_, _>>();
+    SiteCore::new(notifier, images)
+        .with_user_notifiers(user_notifiers)
+        .with_duplicate_suppression(settings.suppress_duplicates)
+}